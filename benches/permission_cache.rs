@@ -0,0 +1,114 @@
+//! The in-memory `PermissionCache` alone and under contention.
+//!
+//! Contended runs split each iteration batch across concurrent tasks doing
+//! nine lookups per write. With `one_user` every task hits the same shard;
+//! with `many_users` they spread across shards, which is what sharding by
+//! user is meant to buy.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use reprime_backend::auth::cache::{PermissionCache, PermissionCacheBackend};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+const OBJECTS: u64 = 1_000;
+
+fn cache() -> Arc<PermissionCache> {
+    Arc::new(PermissionCache::new(Duration::from_secs(300), 100_000))
+}
+
+async fn fill(cache: &PermissionCache, users: &[Uuid]) {
+    for user_id in users {
+        for object in 0..OBJECTS {
+            cache
+                .set(*user_id, "viewer", "document", &object.to_string(), true)
+                .await;
+        }
+    }
+}
+
+fn single_task(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let cache = cache();
+    let user_id = Uuid::new_v4();
+    runtime.block_on(fill(&cache, &[user_id]));
+
+    let mut group = c.benchmark_group("permission_cache");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("get_hit", |b| {
+        let mut object = 0u64;
+        b.to_async(&runtime).iter(|| {
+            object = (object + 1) % OBJECTS;
+            let cache = cache.clone();
+            async move { cache.get(user_id, "viewer", "document", &object.to_string()).await }
+        })
+    });
+    group.bench_function("get_miss", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { cache.get(Uuid::new_v4(), "viewer", "document", "1").await })
+    });
+    group.bench_function("set", |b| {
+        let mut object = 0u64;
+        b.to_async(&runtime).iter(|| {
+            object = (object + 1) % OBJECTS;
+            let cache = cache.clone();
+            async move {
+                cache
+                    .set(user_id, "editor", "document", &object.to_string(), true)
+                    .await
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Run `iters` operations split across `tasks` tasks; returns the wall time
+async fn contended_run(cache: Arc<PermissionCache>, users: Arc<Vec<Uuid>>, tasks: u64, iters: u64) -> Duration {
+    let per_task = iters.div_ceil(tasks);
+    let started = Instant::now();
+    let handles: Vec<_> = (0..tasks)
+        .map(|task| {
+            let (cache, users) = (cache.clone(), users.clone());
+            tokio::spawn(async move {
+                let user_id = users[task as usize % users.len()];
+                for i in 0..per_task {
+                    let object = ((task * per_task + i) % OBJECTS).to_string();
+                    if i % 10 == 9 {
+                        cache.set(user_id, "viewer", "document", &object, true).await;
+                    } else {
+                        cache.get(user_id, "viewer", "document", &object).await;
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    started.elapsed()
+}
+
+fn contention(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+
+    let mut group = c.benchmark_group("permission_cache_contended");
+    group.throughput(Throughput::Elements(1));
+    for tasks in [1u64, 4, 16] {
+        for (label, user_count) in [("one_user", 1usize), ("many_users", 16)] {
+            let cache = cache();
+            let users: Arc<Vec<Uuid>> = Arc::new((0..user_count).map(|_| Uuid::new_v4()).collect());
+            runtime.block_on(fill(&cache, &users));
+
+            group.bench_with_input(BenchmarkId::new(label, tasks), &tasks, |b, &tasks| {
+                b.iter_custom(|iters| {
+                    runtime.block_on(contended_run(cache.clone(), users.clone(), tasks, iters))
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_task, contention);
+criterion_main!(benches);
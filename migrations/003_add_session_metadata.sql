@@ -0,0 +1,12 @@
+-- Structured session records: the session ID is embedded in the JWT as the
+-- `sid` claim, so the token itself no longer needs to be hashed and stored
+ALTER TABLE user_sessions ALTER COLUMN token_hash DROP NOT NULL;
+
+ALTER TABLE user_sessions
+    ADD COLUMN device VARCHAR(255) NULL,
+    ADD COLUMN ip_address VARCHAR(64) NULL,
+    ADD COLUMN scope TEXT NULL,
+    ADD COLUMN last_used_at TIMESTAMPTZ NULL;
+
+CREATE INDEX idx_user_sessions_active ON user_sessions(user_id, expires_at)
+    WHERE revoked_at IS NULL;
@@ -0,0 +1,44 @@
+-- Denormalized per-user row backing admin listings. Profile columns are kept
+-- in sync by triggers on users; roles and last login are written by the
+-- application, since auth tables may live in another database
+CREATE TABLE user_summaries (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    username VARCHAR(100) NOT NULL,
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    last_login_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_user_summaries_created_at ON user_summaries(created_at DESC, user_id);
+
+CREATE OR REPLACE FUNCTION sync_user_summary()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO user_summaries (user_id, email, username, created_at, updated_at)
+    VALUES (NEW.id, NEW.email, NEW.username, NEW.created_at, NEW.updated_at)
+    ON CONFLICT (user_id) DO UPDATE SET
+        email = EXCLUDED.email,
+        username = EXCLUDED.username,
+        updated_at = EXCLUDED.updated_at;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER sync_user_summary_trigger
+    AFTER INSERT OR UPDATE ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION sync_user_summary();
+
+-- Backfill existing users, including roles when auth tables are co-located
+INSERT INTO user_summaries (user_id, email, username, roles, created_at, updated_at)
+SELECT
+    u.id,
+    u.email,
+    u.username,
+    COALESCE(ARRAY(SELECT r.role::TEXT FROM user_roles r WHERE r.user_id = u.id ORDER BY r.role), '{}'),
+    u.created_at,
+    u.updated_at
+FROM users u
+ON CONFLICT (user_id) DO NOTHING;
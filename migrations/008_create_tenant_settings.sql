@@ -0,0 +1,9 @@
+-- Per-tenant settings, one JSONB document per section (branding, password_policy, ...)
+CREATE TABLE tenant_settings (
+    tenant_id VARCHAR(255) NOT NULL,
+    key VARCHAR(64) NOT NULL,
+    value JSONB NOT NULL,
+    updated_by UUID NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, key)
+);
@@ -0,0 +1,4 @@
+-- Conditional GETs on user collections read MAX(updated_at) on every request;
+-- summary rows also bump updated_at when roles or last login change
+CREATE INDEX idx_users_updated_at ON users(updated_at);
+CREATE INDEX idx_user_summaries_updated_at ON user_summaries(updated_at);
@@ -0,0 +1,6 @@
+-- Column types shared by billing/usage tables (see models::types).
+-- Money is stored as an exact amount plus its currency; add a currency by
+-- extending both this enum and `Currency` in the same release.
+CREATE DOMAIN money_amount AS NUMERIC(19, 4);
+
+CREATE TYPE currency_code AS ENUM ('USD', 'EUR', 'GBP', 'JPY', 'VND');
@@ -0,0 +1,19 @@
+-- Named, long-lived tokens users create for scripts and integrations
+CREATE TABLE personal_access_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    -- SHA-256 of the token; the token itself is only shown once
+    token_hash VARCHAR(255) NOT NULL UNIQUE,
+    -- Leading characters of the token so users can tell them apart
+    token_prefix VARCHAR(32) NOT NULL,
+    -- Roles the token may act with; a subset of the owner's roles
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    expires_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NULL,
+    last_used_ip VARCHAR(64) NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_personal_access_tokens_user_id ON personal_access_tokens(user_id);
@@ -0,0 +1,13 @@
+-- Security-relevant actions, placed on the tenant's shard
+CREATE TABLE audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id VARCHAR(255) NOT NULL,
+    actor_id UUID NULL,
+    action VARCHAR(64) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    ip_address VARCHAR(64) NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_tenant_created_at ON audit_log(tenant_id, created_at DESC);
+CREATE INDEX idx_audit_log_actor_id ON audit_log(actor_id);
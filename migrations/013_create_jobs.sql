@@ -0,0 +1,25 @@
+CREATE TYPE job_status AS ENUM ('pending', 'running', 'succeeded', 'failed');
+
+-- Background jobs; workers claim them with FOR UPDATE SKIP LOCKED
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind VARCHAR(64) NOT NULL,
+    -- Identifies the work, so enqueueing it again while queued is a no-op
+    dedupe_key VARCHAR(255) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status job_status NOT NULL DEFAULT 'pending',
+    -- Written by the job as it goes, e.g. how many items are done
+    progress JSONB NOT NULL DEFAULT '{}',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT NULL,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ NULL
+);
+
+CREATE UNIQUE INDEX idx_jobs_queued_dedupe ON jobs(kind, dedupe_key)
+    WHERE status IN ('pending', 'running');
+CREATE INDEX idx_jobs_pending_run_at ON jobs(run_at) WHERE status = 'pending';
+CREATE INDEX idx_jobs_created_at ON jobs(created_at DESC);
@@ -0,0 +1,3 @@
+-- Response format (`X-Response-Format` options) for requests made with the
+-- token when they don't send the header; NULL keeps the default format
+ALTER TABLE personal_access_tokens ADD COLUMN response_format VARCHAR(64) NULL;
@@ -0,0 +1,33 @@
+CREATE TYPE organization_role AS ENUM ('member', 'admin');
+
+-- Organizations, kept on the primary database; the id is the tenant id and
+-- the OpenFGA object id (`organization:{id}`)
+CREATE TABLE organizations (
+    id VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    created_by UUID NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Mirrors the `member` and `admin` tuples written to OpenFGA
+CREATE TABLE organization_members (
+    organization_id VARCHAR(255) NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL,
+    role organization_role NOT NULL DEFAULT 'member',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX idx_organization_members_user_id ON organization_members(user_id);
+
+CREATE TRIGGER update_organizations_updated_at
+    BEFORE UPDATE ON organizations
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Every account registered so far already has a `member` tuple on the
+-- default organization
+INSERT INTO organizations (id, name) VALUES ('default', 'Default');
+INSERT INTO organization_members (organization_id, user_id, role, created_at)
+SELECT 'default', id, 'member', created_at FROM users;
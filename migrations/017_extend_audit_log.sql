@@ -0,0 +1,11 @@
+-- What an audited action changed, and the trace of the request behind it
+ALTER TABLE audit_log
+    ADD COLUMN resource_type VARCHAR(64) NULL,
+    ADD COLUMN resource_id VARCHAR(255) NULL,
+    ADD COLUMN before JSONB NULL,
+    ADD COLUMN after JSONB NULL,
+    ADD COLUMN trace_id VARCHAR(32) NULL;
+
+CREATE INDEX idx_audit_log_resource ON audit_log(tenant_id, resource_type, resource_id);
+-- Retention deletes by age across tenants
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
@@ -0,0 +1,10 @@
+-- Every access token carries the version current when it was issued (the
+-- `ver` claim). Bumping it, e.g. after a signing secret leaks, invalidates
+-- all tokens issued before, including ones not bound to a session.
+CREATE TABLE token_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO token_version (id, version) VALUES (TRUE, 0);
@@ -0,0 +1,13 @@
+-- Sessions record the device they were created from as a fingerprint: a
+-- hash of the user agent and the client's IP subnet. A sign-in from a
+-- fingerprint none of the user's sessions has is emailed to them, and its
+-- session stays unverified until they follow the link in that email.
+ALTER TABLE user_sessions
+    ADD COLUMN device_fingerprint VARCHAR(64) NULL,
+    ADD COLUMN verified BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN verification_token_hash VARCHAR(64) NULL;
+
+CREATE INDEX idx_user_sessions_device ON user_sessions(user_id, device_fingerprint);
+
+CREATE UNIQUE INDEX idx_user_sessions_verification_token ON user_sessions(verification_token_hash)
+    WHERE verification_token_hash IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Access tokens list the user's roles as of when they were issued, along
+-- with when those roles last changed (the `roles_updated_at` claim). A token
+-- older than the user's latest role change is answered with
+-- `token_refresh_required`, so clients refresh it instead of acting on stale
+-- roles until it expires.
+ALTER TABLE user_token_versions ADD COLUMN roles_updated_at TIMESTAMPTZ NULL;
@@ -0,0 +1,9 @@
+-- User listings filter on a substring of email or username (`q`), which
+-- B-tree indexes can't serve; trigram indexes can. Role filters match the
+-- denormalized roles on user_summaries, since auth tables may live in
+-- another database.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_users_email_trgm ON users USING GIN (email gin_trgm_ops);
+CREATE INDEX idx_users_username_trgm ON users USING GIN (username gin_trgm_ops);
+CREATE INDEX idx_user_summaries_roles ON user_summaries USING GIN (roles);
@@ -0,0 +1,4 @@
+-- Cursor pagination on user listings seeks to (created_at, id) and reads
+-- forward; the composite index also serves everything idx_users_created_at did
+CREATE INDEX idx_users_created_at_id ON users(created_at, id);
+DROP INDEX IF EXISTS idx_users_created_at;
@@ -0,0 +1,2 @@
+-- Object storage key of the user's avatar; the URL is signed per response
+ALTER TABLE users ADD COLUMN avatar_key TEXT;
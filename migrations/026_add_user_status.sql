@@ -0,0 +1,9 @@
+CREATE TYPE user_status AS ENUM ('active', 'suspended', 'banned');
+
+-- Suspended and banned users keep their data and credentials, but every
+-- authenticated request they make is refused with a 403 until an admin
+-- reactivates them
+ALTER TABLE users ADD COLUMN status user_status NOT NULL DEFAULT 'active';
+
+-- Listings filter for the few users who aren't active
+CREATE INDEX idx_users_status ON users (status) WHERE status <> 'active';
@@ -0,0 +1,11 @@
+-- Emails and usernames of every user, kept on the primary database. With
+-- users sharded, the unique constraints on `users` only see one shard's rows;
+-- these see all of them, so a handle can't be taken twice across shards.
+CREATE TABLE user_handles (
+    user_id UUID PRIMARY KEY,
+    email VARCHAR(255) NOT NULL CONSTRAINT user_handles_email_key UNIQUE,
+    username VARCHAR(100) NOT NULL CONSTRAINT user_handles_username_key UNIQUE
+);
+
+INSERT INTO user_handles (user_id, email, username)
+SELECT id, email, username FROM users;
@@ -0,0 +1,12 @@
+-- The worker holding a running job and when its hold runs out. Completing or
+-- failing a job checks both, so a worker whose lease was taken over can't
+-- overwrite the new holder's result.
+ALTER TABLE jobs
+    ADD COLUMN lease_owner VARCHAR(255) NULL,
+    ADD COLUMN lease_expires_at TIMESTAMPTZ NULL;
+
+-- Jobs already running keep the default five-minute lease from when they
+-- were claimed
+UPDATE jobs
+SET lease_expires_at = locked_at + INTERVAL '5 minutes'
+WHERE status = 'running';
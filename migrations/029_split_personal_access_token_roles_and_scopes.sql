@@ -0,0 +1,5 @@
+-- `scopes` held the roles a token acts with, so it is now `roles`. The new
+-- `scopes` lists the API scopes (`resource:action`) the token is restricted
+-- to, as in a JWT's `scope` claim; NULL leaves existing tokens unrestricted.
+ALTER TABLE personal_access_tokens RENAME COLUMN scopes TO roles;
+ALTER TABLE personal_access_tokens ADD COLUMN scopes TEXT[] NULL;
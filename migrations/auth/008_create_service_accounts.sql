@@ -0,0 +1,15 @@
+-- Machine-to-machine callers; they get short-lived tokens with the
+-- client-credentials grant, using the id as client id
+CREATE TABLE service_accounts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    -- SHA-256 of the client secret; the secret itself is only shown once
+    secret_hash VARCHAR(255) NOT NULL UNIQUE,
+    -- Leading characters of the secret so admins can tell them apart
+    secret_prefix VARCHAR(32) NOT NULL,
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    created_by UUID NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NULL,
+    disabled_at TIMESTAMPTZ NULL
+);
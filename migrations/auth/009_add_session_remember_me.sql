@@ -0,0 +1,8 @@
+-- "Remember me" sessions slide: each use pushes `expires_at` out by the idle
+-- timeout, but never past `absolute_expires_at`. Other sessions keep a fixed
+-- expiry, recorded in both columns.
+ALTER TABLE user_sessions
+    ADD COLUMN remember_me BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN absolute_expires_at TIMESTAMPTZ NULL;
+
+UPDATE user_sessions SET absolute_expires_at = expires_at;
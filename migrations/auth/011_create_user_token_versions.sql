@@ -0,0 +1,10 @@
+-- Each user's access tokens also carry the user's version when issued (the
+-- `uver` claim). Bumping it after a password reset, a role removal or an
+-- admin action invalidates that user's tokens, including ones not bound to
+-- a session. Users without a row are at version 0; rows outlive the user so
+-- a recreated id can't revive old tokens.
+CREATE TABLE user_token_versions (
+    user_id UUID PRIMARY KEY,
+    token_version BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,223 @@
+//! `reprime-backend anonymize`: scrub PII from a copy of production data.
+//!
+//! User IDs are kept, so foreign keys, shard placement and OpenFGA tuples
+//! (`user:<id>`) stay valid. Emails and usernames are derived from the ID,
+//! which makes repeated runs produce the same values. Credentials can't be
+//! faked meaningfully, so passwords are reset and everything else that
+//! authenticates a user (sessions, refresh/reset tokens, passkeys, personal
+//! access tokens) is deleted. Avatars are photos, so they are unset and their
+//! objects deleted from the configured storage, which must be the copy's own.
+//! Audit events keep who did what to which resource and when, but lose their
+//! details, before/after snapshots and client IPs, which hold emails,
+//! usernames and addresses.
+
+use crate::config::Config;
+use crate::metrics::MetricsRegistry;
+use crate::services::storage_from_config;
+use crate::utils::{
+    create_auth_database_pool, create_database_pool, create_shard_pools, run_migrations,
+};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Domain reserved by RFC 2606, so fake addresses can never be delivered
+pub const FAKE_EMAIL_DOMAIN: &str = "example.invalid";
+
+pub fn fake_email(user_id: Uuid) -> String {
+    format!("user-{}@{}", user_id.simple(), FAKE_EMAIL_DOMAIN)
+}
+
+pub fn fake_username(user_id: Uuid) -> String {
+    format!("user_{}", &user_id.simple().to_string()[..16])
+}
+
+/// Rows touched by an anonymization run
+#[derive(Debug, Default)]
+pub struct AnonymizeReport {
+    pub users: u64,
+    /// Avatar objects deleted from storage
+    pub avatars: u64,
+    /// Audit events whose details were cleared
+    pub audit_events: u64,
+    pub credentials: u64,
+    pub sessions: u64,
+    pub password_reset_tokens: u64,
+    pub passkeys: u64,
+    pub personal_access_tokens: u64,
+}
+
+/// Run against the configured databases (primary, shards and auth store)
+pub async fn run(config: &Config) -> anyhow::Result<AnonymizeReport> {
+    let run_mode = std::env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string());
+    if run_mode == "production" {
+        anyhow::bail!("Refusing to anonymize with RUN_MODE=production");
+    }
+
+    let primary = create_database_pool(config).await?;
+    let shards = create_shard_pools(config).await?;
+    let auth_pool = create_auth_database_pool(config).await?;
+    run_migrations(config, &primary, &shards, auth_pool.as_deref()).await?;
+    let auth = auth_pool.unwrap_or_else(|| primary.clone());
+
+    let batch_size = config.anonymize.batch_size.max(1);
+    let mut report = AnonymizeReport::default();
+
+    let mut avatar_keys = Vec::new();
+    for pool in std::iter::once(&primary).chain(shards.iter()) {
+        report.users += anonymize_users(pool, &primary, batch_size, &mut avatar_keys).await?;
+        report.audit_events += scrub_audit_log(pool).await?;
+    }
+
+    // Only once no user points at them; an object that fails to go is
+    // logged and left behind
+    let storage = storage_from_config(&config.storage, &config.auth.jwt_secret, &MetricsRegistry::new())?;
+    for key in avatar_keys {
+        match storage.delete(&key).await {
+            Ok(()) => report.avatars += 1,
+            Err(e) => tracing::warn!(key, error = %e, "Failed to delete avatar object"),
+        }
+    }
+
+    let password_hash = match &config.anonymize.password {
+        Some(password) => bcrypt::hash(password, bcrypt::DEFAULT_COST)?,
+        // A hash of a password nobody knows
+        None => bcrypt::hash(Uuid::new_v4().to_string(), bcrypt::DEFAULT_COST)?,
+    };
+    scrub_credentials(&auth, &password_hash, &mut report).await?;
+
+    tracing::info!(?report, "Anonymization finished");
+    Ok(report)
+}
+
+/// Rewrite emails and usernames and unset avatars of one shard in a single
+/// transaction, adding the avatar keys that were set to `avatar_keys`
+///
+/// `user_summaries` follows through its sync trigger; the users' rows in
+/// `user_handles` are rewritten on the shard and on `primary`, which holds
+/// the claims of every shard.
+pub async fn anonymize_users(
+    pool: &PgPool,
+    primary: &PgPool,
+    batch_size: i64,
+    avatar_keys: &mut Vec<String>,
+) -> anyhow::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let mut last_id = Uuid::nil();
+    let mut updated = 0;
+
+    loop {
+        let ids: Vec<Uuid> = sqlx::query("SELECT id FROM users WHERE id > $1 ORDER BY id LIMIT $2")
+            .bind(last_id)
+            .bind(batch_size)
+            .fetch_all(&mut *tx)
+            .await?
+            .iter()
+            .map(|row| row.get("id"))
+            .collect();
+
+        let Some(&last) = ids.last() else {
+            break;
+        };
+        last_id = last;
+
+        let keys: Vec<String> = sqlx::query_scalar(
+            "SELECT avatar_key FROM users WHERE id = ANY($1) AND avatar_key IS NOT NULL",
+        )
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        avatar_keys.extend(keys);
+
+        let emails: Vec<String> = ids.iter().copied().map(fake_email).collect();
+        let usernames: Vec<String> = ids.iter().copied().map(fake_username).collect();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users AS u
+            SET email = f.email, username = f.username, avatar_key = NULL, updated_at = NOW()
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[]) AS f(id, email, username)
+            WHERE u.id = f.id
+            "#,
+        )
+        .bind(&ids)
+        .bind(&emails)
+        .bind(&usernames)
+        .execute(&mut *tx)
+        .await?;
+        updated += result.rows_affected();
+
+        let rewrite_handles = sqlx::query(
+            r#"
+            UPDATE user_handles AS h
+            SET email = f.email, username = f.username
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[]) AS f(id, email, username)
+            WHERE h.user_id = f.id
+            "#,
+        )
+        .bind(&ids)
+        .bind(&emails)
+        .bind(&usernames);
+        if std::ptr::eq(pool, primary) {
+            rewrite_handles.execute(&mut *tx).await?;
+        } else {
+            rewrite_handles.execute(primary).await?;
+            // The shard's own copy from the migration that created the table
+            sqlx::query("DELETE FROM user_handles WHERE user_id = ANY($1)")
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(updated)
+}
+
+/// Clear what one shard's audit events recorded about their subjects
+async fn scrub_audit_log(pool: &PgPool) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE audit_log SET details = '{}', before = NULL, after = NULL, ip_address = NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn scrub_credentials(
+    pool: &PgPool,
+    password_hash: &str,
+    report: &mut AnonymizeReport,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    report.credentials = sqlx::query("UPDATE user_credentials SET password_hash = $1, updated_at = NOW()")
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    // Refresh tokens go with their sessions
+    report.sessions = sqlx::query("DELETE FROM user_sessions")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    report.password_reset_tokens = sqlx::query("DELETE FROM password_reset_tokens")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    report.passkeys = sqlx::query("DELETE FROM webauthn_credentials")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query("DELETE FROM webauthn_challenges")
+        .execute(&mut *tx)
+        .await?;
+    report.personal_access_tokens = sqlx::query("DELETE FROM personal_access_tokens")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,436 @@
+//! Pluggable request authentication
+//!
+//! Each [`Authenticator`] recognises one kind of credential (a JWT, a
+//! personal access token, the session cookie, a client certificate) and
+//! turns it into an [`AuthContext`]. Route groups get an ordered chain of
+//! them from `auth.authentication`; the first authenticator that recognises
+//! the request's credential decides, so an invalid JWT is a 401 even when a
+//! cookie would also have been accepted.
+
+use crate::auth::cookies::SessionCookies;
+use crate::auth::jwt::JwtService;
+use crate::auth::middleware::TOKEN_REFRESH_REQUIRED;
+use crate::auth::models::{AuthContext, PERSONAL_ACCESS_TOKEN_PREFIX};
+use crate::auth::session::SessionValidator;
+use crate::config::{AuthenticationConfig, MtlsConfig};
+use crate::errors::AppError;
+use crate::metrics::RouteGroups;
+use crate::models::format::ResponseFormat;
+use crate::request_context;
+use async_trait::async_trait;
+use axum::http::{HeaderMap, Method, StatusCode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const JWT: &str = "jwt";
+pub const PERSONAL_ACCESS_TOKEN: &str = "personal_access_token";
+pub const COOKIE: &str = "cookie";
+pub const MTLS: &str = "mtls";
+
+/// Names of the authenticators every deployment has
+pub const BUILT_IN: &[&str] = &[JWT, PERSONAL_ACCESS_TOKEN, COOKIE, MTLS];
+
+/// Header carrying a personal access token for clients that can't set
+/// `Authorization`
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The caller an authenticator resolved
+#[derive(Debug, Clone)]
+pub struct Authenticated {
+    pub context: AuthContext,
+    /// Response format the credential asks for, used when the request
+    /// doesn't pick one
+    pub response_format: Option<ResponseFormat>,
+}
+
+impl From<AuthContext> for Authenticated {
+    fn from(context: AuthContext) -> Self {
+        Self {
+            context,
+            response_format: None,
+        }
+    }
+}
+
+/// One way of authenticating a request
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Name the chains in `auth.authentication` refer to it by
+    fn name(&self) -> &'static str;
+
+    /// `Ok(None)` when the request carries no credential of this kind, so
+    /// the next authenticator gets a go; an error when it carries one that
+    /// isn't valid
+    async fn authenticate(
+        &self,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Option<Authenticated>, (StatusCode, String)>;
+}
+
+/// Run `chain` in order; the first authenticator that recognises a
+/// credential decides
+pub async fn authenticate(
+    chain: &[Arc<dyn Authenticator>],
+    method: &Method,
+    headers: &HeaderMap,
+) -> Result<Authenticated, (StatusCode, String)> {
+    for authenticator in chain {
+        if let Some(authenticated) = authenticator.authenticate(method, headers).await? {
+            return Ok(authenticated);
+        }
+    }
+
+    Err((StatusCode::UNAUTHORIZED, "Missing credentials".to_string()))
+}
+
+/// The chain each route group authenticates with
+#[derive(Clone, Default)]
+pub struct AuthenticatorChains {
+    default: Vec<Arc<dyn Authenticator>>,
+    groups: HashMap<String, Vec<Arc<dyn Authenticator>>>,
+    route_groups: RouteGroups,
+}
+
+impl AuthenticatorChains {
+    /// Resolve the chains in `config` against the available authenticators;
+    /// names without one (the cookie outside cookie mode) are left out
+    pub fn new(
+        config: &AuthenticationConfig,
+        route_groups: RouteGroups,
+        available: &[Arc<dyn Authenticator>],
+    ) -> Self {
+        let resolve = |names: &[String]| -> Vec<Arc<dyn Authenticator>> {
+            names
+                .iter()
+                .filter_map(|name| {
+                    available
+                        .iter()
+                        .find(|authenticator| authenticator.name() == name)
+                        .cloned()
+                })
+                .collect()
+        };
+
+        Self {
+            default: resolve(&config.default),
+            groups: config
+                .route_groups
+                .iter()
+                .map(|(group, names)| (group.clone(), resolve(names)))
+                .collect(),
+            route_groups,
+        }
+    }
+
+    /// The chain for a route template (or raw path when unmatched)
+    pub fn chain(&self, route: &str) -> &[Arc<dyn Authenticator>] {
+        self.groups
+            .get(self.route_groups.resolve(route))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Check every chain in `config` only names known authenticators, so a typo
+/// fails startup rather than locking a route group out
+pub fn validate_config(config: &AuthenticationConfig, custom: &[&str]) -> anyhow::Result<()> {
+    let chains = std::iter::once(("default", &config.default)).chain(
+        config
+            .route_groups
+            .iter()
+            .map(|(group, names)| (group.as_str(), names)),
+    );
+
+    for (group, names) in chains {
+        if names.is_empty() {
+            anyhow::bail!("Authentication chain for '{}' is empty", group);
+        }
+        for name in names {
+            if !BUILT_IN.contains(&name.as_str()) && !custom.contains(&name.as_str()) {
+                anyhow::bail!("Unknown authenticator '{}' for '{}'", name, group);
+            }
+            if name == MTLS && config.mtls.subject_header.is_none() {
+                anyhow::bail!(
+                    "Authenticator 'mtls' for '{}' needs auth.authentication.mtls.subject_header",
+                    group
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bearer JWTs, checked against the token versions and their session
+#[derive(Clone)]
+pub struct JwtAuthenticator {
+    jwt_service: Arc<JwtService>,
+    sessions: Arc<SessionValidator>,
+}
+
+impl JwtAuthenticator {
+    pub fn new(jwt_service: Arc<JwtService>, sessions: Arc<SessionValidator>) -> Self {
+        Self {
+            jwt_service,
+            sessions,
+        }
+    }
+
+    /// Validate a JWT and the session it is bound to
+    pub async fn validate(&self, token: &str) -> Result<AuthContext, (StatusCode, String)> {
+        let invalid = |e: AppError| (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e));
+        let claims = self.jwt_service.validate_token(token).map_err(invalid)?;
+        let (version, user_version, roles_updated_at) =
+            (claims.ver.unwrap_or(0), claims.uver.unwrap_or(0), claims.roles_updated_at);
+        let auth_context = self.jwt_service.auth_context(claims).map_err(invalid)?;
+
+        // Bumping the token version revokes every token issued before, and
+        // bumping a user's revokes that user's
+        let user_state = self.sessions.user_token_state(auth_context.user_id).await;
+        if !self.sessions.is_token_version_current(version).await
+            || user_state.is_some_and(|state| state.revokes(user_version))
+        {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "Token has been revoked".to_string(),
+            ));
+        }
+
+        // The token lists roles the user no longer has, or lacks new ones;
+        // a refreshed token is fine
+        if user_state.is_some_and(|state| state.requires_refresh(roles_updated_at)) {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                TOKEN_REFRESH_REQUIRED.to_string(),
+            ));
+        }
+
+        // Tokens without a `sid` predate server-side sessions and can't be revoked
+        if let Some(session_id) = auth_context.session_id {
+            let valid = self
+                .sessions
+                .is_valid(session_id, auth_context.user_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!(session_id = %session_id, error = %e, "Session check failed");
+                    (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "Unable to verify session".to_string(),
+                    )
+                })?;
+
+            if !valid {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    "Session has been revoked or has expired".to_string(),
+                ));
+            }
+        }
+
+        Ok(auth_context)
+    }
+}
+
+#[async_trait]
+impl Authenticator for JwtAuthenticator {
+    fn name(&self) -> &'static str {
+        JWT
+    }
+
+    /// Claims any `Authorization` header but a bearer personal access token
+    async fn authenticate(
+        &self,
+        _method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Option<Authenticated>, (StatusCode, String)> {
+        let Some(auth_header) = headers.get("authorization") else {
+            return Ok(None);
+        };
+        let invalid = |e: String| {
+            (
+                StatusCode::UNAUTHORIZED,
+                format!("Invalid authorization header: {}", e),
+            )
+        };
+        let auth_header = auth_header.to_str().map_err(|e| invalid(e.to_string()))?;
+        let token = JwtService::extract_token_from_header(auth_header)
+            .map_err(|e| invalid(e.to_string()))?;
+        if token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+            return Ok(None);
+        }
+
+        self.validate(token).await.map(|context| Some(context.into()))
+    }
+}
+
+/// Personal access tokens, as a bearer token or in `X-API-Key`
+#[derive(Clone)]
+pub struct PersonalTokenAuthenticator {
+    sessions: Arc<SessionValidator>,
+}
+
+impl PersonalTokenAuthenticator {
+    pub fn new(sessions: Arc<SessionValidator>) -> Self {
+        Self { sessions }
+    }
+}
+
+#[async_trait]
+impl Authenticator for PersonalTokenAuthenticator {
+    fn name(&self) -> &'static str {
+        PERSONAL_ACCESS_TOKEN
+    }
+
+    async fn authenticate(
+        &self,
+        _method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Option<Authenticated>, (StatusCode, String)> {
+        let bearer = headers
+            .get("authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .filter(|token| token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX));
+        let token = match bearer {
+            Some(token) => token,
+            None => match headers.get(API_KEY_HEADER) {
+                Some(key) => key
+                    .to_str()
+                    .ok()
+                    .filter(|key| key.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX))
+                    .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid API key".to_string()))?,
+                None => return Ok(None),
+            },
+        };
+
+        let ip_address = request_context::client_ip(headers);
+        let caller = self
+            .sessions
+            .authenticate_personal_token(token, ip_address.as_deref())
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Personal access token check failed");
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Unable to verify token".to_string(),
+                )
+            })?
+            .ok_or_else(|| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    "Token has been revoked or has expired".to_string(),
+                )
+            })?;
+
+        Ok(Some(Authenticated {
+            context: caller.context,
+            response_format: caller.response_format,
+        }))
+    }
+}
+
+/// The access cookie of cookie mode, which has to come with the CSRF header
+/// on unsafe methods
+#[derive(Clone)]
+pub struct CookieAuthenticator {
+    cookies: Arc<SessionCookies>,
+    jwt: JwtAuthenticator,
+}
+
+impl CookieAuthenticator {
+    pub fn new(cookies: Arc<SessionCookies>, jwt: JwtAuthenticator) -> Self {
+        Self { cookies, jwt }
+    }
+}
+
+#[async_trait]
+impl Authenticator for CookieAuthenticator {
+    fn name(&self) -> &'static str {
+        COOKIE
+    }
+
+    async fn authenticate(
+        &self,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Option<Authenticated>, (StatusCode, String)> {
+        let Some(token) = self.cookies.access_token(headers) else {
+            return Ok(None);
+        };
+        if !self.cookies.csrf_valid(method, headers) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Missing or invalid CSRF token".to_string(),
+            ));
+        }
+
+        self.jwt.validate(token).await.map(|context| Some(context.into()))
+    }
+}
+
+/// Service accounts identified by the client certificate the proxy in front
+/// verified, from the subject it forwards
+#[derive(Clone)]
+pub struct MtlsAuthenticator {
+    subject_header: String,
+    identities: HashMap<String, Uuid>,
+    sessions: Arc<SessionValidator>,
+}
+
+impl MtlsAuthenticator {
+    /// `None` without a subject header configured
+    pub fn from_config(config: &MtlsConfig, sessions: Arc<SessionValidator>) -> Option<Self> {
+        Some(Self {
+            subject_header: config.subject_header.clone()?.to_ascii_lowercase(),
+            identities: config.identities.clone(),
+            sessions,
+        })
+    }
+}
+
+#[async_trait]
+impl Authenticator for MtlsAuthenticator {
+    fn name(&self) -> &'static str {
+        MTLS
+    }
+
+    async fn authenticate(
+        &self,
+        _method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Option<Authenticated>, (StatusCode, String)> {
+        let Some(subject) = headers.get(self.subject_header.as_str()) else {
+            return Ok(None);
+        };
+        let account_id = subject
+            .to_str()
+            .ok()
+            .and_then(|subject| self.identities.get(subject.trim()))
+            .ok_or_else(|| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    "Unknown client certificate".to_string(),
+                )
+            })?;
+
+        let context = self
+            .sessions
+            .authenticate_service_account(*account_id)
+            .await
+            .map_err(|e| {
+                tracing::error!(service_account_id = %account_id, error = %e, "Service account check failed");
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Unable to verify client certificate".to_string(),
+                )
+            })?
+            .ok_or_else(|| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    "Service account has been disabled".to_string(),
+                )
+            })?;
+
+        Ok(Some(context.into()))
+    }
+}
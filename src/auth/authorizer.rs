@@ -0,0 +1,98 @@
+use crate::auth::models::{AuthorizationResult, Subject};
+use crate::auth::openfga::OpenFgaService;
+use crate::errors::Result;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+/// Relationship-based permission checks used by `require_permission`
+#[async_trait]
+pub trait Authorizer: Send + Sync {
+    async fn check(
+        &self,
+        subject: Subject,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<AuthorizationResult>;
+}
+
+#[async_trait]
+impl Authorizer for OpenFgaService {
+    async fn check(
+        &self,
+        subject: Subject,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<AuthorizationResult> {
+        self.check_subject_permission(subject, relation, object_type, object_id)
+            .await
+    }
+}
+
+/// Authorizer over a fixed set of tuples, for tests and local runs without OpenFGA
+///
+/// Only direct tuples match; there is no rewrite of relations (e.g. an
+/// `editor` is not implicitly a `viewer`), so scenarios list every tuple
+/// they rely on.
+#[derive(Debug, Default)]
+pub struct StaticAuthorizer {
+    tuples: RwLock<HashSet<(String, String, String)>>,
+}
+
+impl StaticAuthorizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grant a tuple to a subject; a bare `Uuid` is a user
+    pub fn with_tuple(
+        self,
+        subject: impl Into<Subject>,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Self {
+        self.grant(subject, relation, object_type, object_id);
+        self
+    }
+
+    pub fn grant(&self, subject: impl Into<Subject>, relation: &str, object_type: &str, object_id: &str) {
+        self.tuples.write().unwrap().insert(Self::key(subject.into(), relation, object_type, object_id));
+    }
+
+    pub fn revoke(&self, subject: impl Into<Subject>, relation: &str, object_type: &str, object_id: &str) {
+        self.tuples.write().unwrap().remove(&Self::key(subject.into(), relation, object_type, object_id));
+    }
+
+    fn key(subject: Subject, relation: &str, object_type: &str, object_id: &str) -> (String, String, String) {
+        (
+            subject.to_string(),
+            relation.to_string(),
+            format!("{}:{}", object_type, object_id),
+        )
+    }
+}
+
+#[async_trait]
+impl Authorizer for StaticAuthorizer {
+    async fn check(
+        &self,
+        subject: Subject,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<AuthorizationResult> {
+        let allowed = self
+            .tuples
+            .read()
+            .unwrap()
+            .contains(&Self::key(subject, relation, object_type, object_id));
+
+        Ok(AuthorizationResult {
+            allowed,
+            reason: (!allowed).then(|| "No matching tuple".to_string()),
+        })
+    }
+}
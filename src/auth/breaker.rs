@@ -0,0 +1,141 @@
+//! Circuit breaker for calls to OpenFGA.
+//!
+//! Closed, calls go through and consecutive failures are counted. Once they
+//! reach the threshold the breaker opens and calls are refused for the open
+//! period; after that a single probe is let through (half-open), which closes
+//! the breaker on success or opens it again on failure.
+
+use crate::config::CircuitBreakerConfig;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+impl BreakerState {
+    /// Gauge value exported as `openfga_circuit_breaker_state`
+    pub fn as_metric(self) -> f64 {
+        match self {
+            BreakerState::Closed => 0.0,
+            BreakerState::HalfOpen => 1.0,
+            BreakerState::Open => 2.0,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::HalfOpen => "half_open",
+            BreakerState::Open => "open",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    // A probe that never reports back (e.g. its request was dropped) stops
+    // blocking others once it is older than the open period
+    probe_started_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started_at: None,
+            }),
+        }
+    }
+
+    /// `None` when disabled in config
+    pub fn from_config(config: &CircuitBreakerConfig) -> Option<Self> {
+        config.enabled.then(|| {
+            Self::new(
+                config.failure_threshold,
+                Duration::from_secs(config.open_seconds),
+            )
+        })
+    }
+
+    /// Whether a call may go out now; every admitted call must be followed by
+    /// [`Self::record_success`] or [`Self::record_failure`]
+    pub fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => {
+                let elapsed = inner.opened_at.map_or(self.open_for, |at| now - at);
+                if elapsed < self.open_for {
+                    return false;
+                }
+                inner.state = BreakerState::HalfOpen;
+                inner.probe_started_at = Some(now);
+                true
+            }
+            BreakerState::HalfOpen => {
+                let probe_stale = inner
+                    .probe_started_at
+                    .is_none_or(|at| now - at >= self.open_for);
+                if probe_stale {
+                    inner.probe_started_at = Some(now);
+                }
+                probe_stale
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != BreakerState::Closed {
+            tracing::info!("OpenFGA circuit breaker closed");
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_started_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        let trip = match inner.state {
+            BreakerState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if trip {
+            tracing::warn!(
+                consecutive_failures = inner.consecutive_failures,
+                open_seconds = self.open_for.as_secs(),
+                "OpenFGA circuit breaker opened"
+            );
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some(Instant::now());
+            inner.probe_started_at = None;
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.inner.lock().unwrap().state
+    }
+}
@@ -1,7 +1,17 @@
+use crate::auth::registry::Relation;
+use crate::config::{Config, OpenFgaConfig, PermissionCacheBackendKind, RedisConfig};
+use crate::errors::{AppError, Result};
+use crate::request_cost;
+use async_trait::async_trait;
+use lru::LruCache;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::AsyncCommands;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
 
 /// Cache entry with expiration
@@ -24,206 +34,701 @@ impl<T> CacheEntry<T> {
     }
 }
 
+/// Where permission check results are cached
+///
+/// Backends never fail a check: an unreachable store behaves as a miss.
+#[async_trait]
+pub trait PermissionCacheBackend: Send + Sync {
+    /// Get cached permission result
+    async fn get(&self, user_id: Uuid, relation: &str, object_type: &str, object_id: &str)
+        -> Option<bool>;
+
+    /// Set cached permission result with the default TTL
+    async fn set(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+        allowed: bool,
+    );
+
+    /// Invalidate cache entry
+    async fn invalidate(&self, user_id: Uuid, relation: &str, object_type: &str, object_id: &str);
+
+    /// Invalidate all cache entries for a user
+    async fn invalidate_user(&self, user_id: Uuid);
+
+    /// Invalidate all cache entries for an object
+    async fn invalidate_object(&self, object_type: &str, object_id: &str);
+
+    /// Clear all cache entries
+    async fn clear(&self);
+
+    /// Get cache statistics
+    async fn stats(&self) -> CacheStats;
+}
+
+/// How long a permission check result is cached
+///
+/// Denied results can be kept for less time than allowed ones, so a grant
+/// shows up quickly while the common allowed case stays cached. A relation
+/// override replaces the allowed TTL and caps the denied one.
+#[derive(Debug, Clone)]
+pub struct CacheTtl {
+    allowed: Duration,
+    denied: Duration,
+    relations: HashMap<String, Duration>,
+}
+
+impl CacheTtl {
+    /// The same TTL for every result
+    pub fn uniform(ttl: Duration) -> Self {
+        Self {
+            allowed: ttl,
+            denied: ttl,
+            relations: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &OpenFgaConfig) -> Result<Self> {
+        let relations = config
+            .cache_relation_ttl_seconds
+            .iter()
+            .map(|(relation, seconds)| {
+                Relation::parse(relation).ok_or_else(|| {
+                    AppError::Validation(format!("Unknown relation '{}' in cache TTLs", relation))
+                })?;
+                Ok((relation.clone(), Duration::from_secs(*seconds)))
+            })
+            .collect::<Result<_>>()?;
+
+        let allowed = Duration::from_secs(config.cache_ttl_seconds);
+        Ok(Self {
+            allowed,
+            denied: config
+                .cache_denied_ttl_seconds
+                .map(Duration::from_secs)
+                .unwrap_or(allowed),
+            relations,
+        })
+    }
+
+    pub fn for_result(&self, relation: &str, allowed: bool) -> Duration {
+        match (self.relations.get(relation), allowed) {
+            (Some(ttl), true) => *ttl,
+            (Some(ttl), false) => self.denied.min(*ttl),
+            (None, true) => self.allowed,
+            (None, false) => self.denied,
+        }
+    }
+}
+
+/// Build the permission cache `auth.openfga` asks for
+///
+/// A disabled cache is an in-memory one with a zero TTL.
+///
+/// Each tenant with a dedicated store gets its own cache; in Redis its keys
+/// are prefixed with the tenant.
+pub fn permission_cache_from_config(
+    config: &Config,
+    tenant: Option<&str>,
+) -> Result<Arc<dyn PermissionCacheBackend>> {
+    let openfga = &config.auth.openfga;
+    if !openfga.cache_enabled {
+        tracing::info!("OpenFGA cache disabled");
+        return Ok(Arc::new(PermissionCache::new(Duration::from_secs(0), 1)));
+    }
+
+    let ttl = CacheTtl::from_config(openfga)?;
+    match openfga.cache_backend {
+        PermissionCacheBackendKind::Memory => {
+            let cache = Arc::new(
+                PermissionCache::new(ttl.allowed, openfga.cache_max_entries).with_ttl(ttl),
+            );
+            let cache_cleanup = cache.clone();
+            tokio::spawn(async move {
+                cache_cleanup.cleanup_task().await;
+            });
+
+            tracing::info!(
+                "OpenFGA cache enabled: TTL={}s, denied TTL={}s, max_entries={}",
+                openfga.cache_ttl_seconds,
+                openfga.cache_denied_ttl_seconds.unwrap_or(openfga.cache_ttl_seconds),
+                openfga.cache_max_entries
+            );
+            Ok(cache)
+        }
+        PermissionCacheBackendKind::Redis => {
+            let mut cache = RedisPermissionCache::new(&config.redis, ttl.allowed)?.with_ttl(ttl);
+            if let Some(tenant) = tenant {
+                cache = cache.with_key_prefix(format!("{}{}:perm:", config.redis.key_prefix, tenant));
+            }
+            tracing::info!(
+                "OpenFGA cache enabled in Redis: TTL={}s, denied TTL={}s, key_prefix={}",
+                openfga.cache_ttl_seconds,
+                openfga.cache_denied_ttl_seconds.unwrap_or(openfga.cache_ttl_seconds),
+                cache.key_prefix
+            );
+            Ok(Arc::new(cache))
+        }
+    }
+}
+
+/// Generate cache key for permission check
+fn cache_key(user_id: Uuid, relation: &str, object_type: &str, object_id: &str) -> String {
+    format!("{}:{}:{}:{}", user_id, relation, object_type, object_id)
+}
+
+/// Shards in the in-memory permission cache; lookups for different users
+/// rarely contend for the same lock
+const PERMISSION_CACHE_SHARDS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PermissionKey {
+    user_id: Uuid,
+    relation: String,
+    object_type: String,
+    object_id: String,
+}
+
+impl PermissionKey {
+    fn new(user_id: Uuid, relation: &str, object_type: &str, object_id: &str) -> Self {
+        Self {
+            user_id,
+            relation: relation.to_string(),
+            object_type: object_type.to_string(),
+            object_id: object_id.to_string(),
+        }
+    }
+}
+
 /// In-memory cache for OpenFGA permission checks
+///
+/// Entries are sharded by user, each shard an LRU, so a full cache evicts
+/// the least recently used entries rather than arbitrary ones and a user's
+/// entries can be invalidated without touching other shards.
 #[derive(Debug)]
 pub struct PermissionCache {
-    cache: Arc<RwLock<HashMap<String, CacheEntry<bool>>>>,
-    default_ttl: Duration,
+    shards: Vec<Mutex<LruCache<PermissionKey, CacheEntry<bool>>>>,
+    ttl: CacheTtl,
     max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
 }
 
 impl PermissionCache {
+    /// Every result cached for `default_ttl`; see [`Self::with_ttl`]
     pub fn new(default_ttl: Duration, max_entries: usize) -> Self {
+        let max_entries = max_entries.max(1);
+        let shard_count = PERMISSION_CACHE_SHARDS.min(max_entries);
+        let shard_capacity = NonZeroUsize::new(max_entries.div_ceil(shard_count))
+            .unwrap_or(NonZeroUsize::MIN);
+
         Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            default_ttl,
+            shards: (0..shard_count)
+                .map(|_| Mutex::new(LruCache::new(shard_capacity)))
+                .collect(),
+            ttl: CacheTtl::uniform(default_ttl),
             max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
-    /// Generate cache key for permission check
-    fn cache_key(user_id: Uuid, relation: &str, object_type: &str, object_id: &str) -> String {
-        format!("{}:{}:{}:{}", user_id, relation, object_type, object_id)
+    /// Cache allowed, denied and per-relation results for different times
+    pub fn with_ttl(mut self, ttl: CacheTtl) -> Self {
+        self.ttl = ttl;
+        self
     }
 
-    /// Get cached permission result
-    pub async fn get(
+    fn shard(&self, user_id: Uuid) -> &Mutex<LruCache<PermissionKey, CacheEntry<bool>>> {
+        let index = (user_id.as_u128() % self.shards.len() as u128) as usize;
+        &self.shards[index]
+    }
+
+    /// Set cached permission result with custom TTL
+    pub async fn set_with_ttl(
         &self,
         user_id: Uuid,
         relation: &str,
         object_type: &str,
         object_id: &str,
-    ) -> Option<bool> {
-        let key = Self::cache_key(user_id, relation, object_type, object_id);
-        let cache = self.cache.read().await;
-        
-        if let Some(entry) = cache.get(&key) {
-            if !entry.is_expired() {
-                tracing::debug!("Cache hit for permission check: {}", key);
-                return Some(entry.value);
+        allowed: bool,
+        ttl: Duration,
+    ) {
+        if ttl.is_zero() {
+            return;
+        }
+
+        let key = PermissionKey::new(user_id, relation, object_type, object_id);
+        let mut shard = self.shard(user_id).lock().unwrap();
+
+        // Make room with expired entries before evicting live ones
+        if shard.len() == shard.cap().get() && !shard.contains(&key) {
+            Self::evict_expired(&mut shard);
+        }
+        if let Some((evicted, _)) = shard.push(key.clone(), CacheEntry::new(allowed, ttl)) {
+            if evicted != key {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        tracing::debug!(
+            "Cached permission result: {}:{}:{}:{} = {}",
+            user_id,
+            relation,
+            object_type,
+            object_id,
+            allowed
+        );
+    }
+
+    /// Remove the entries in one shard that `remove` matches
+    fn remove_where(
+        shard: &mut LruCache<PermissionKey, CacheEntry<bool>>,
+        remove: impl Fn(&PermissionKey, &CacheEntry<bool>) -> bool,
+    ) -> usize {
+        let keys: Vec<PermissionKey> = shard
+            .iter()
+            .filter(|(key, entry)| remove(key, entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            shard.pop(key);
+        }
+        keys.len()
+    }
+
+    /// Evict expired entries
+    fn evict_expired(shard: &mut LruCache<PermissionKey, CacheEntry<bool>>) -> usize {
+        Self::remove_where(shard, |_, entry| entry.is_expired())
+    }
+
+    /// Background task to periodically clean up expired entries
+    pub async fn cleanup_task(&self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(60)); // Clean up every minute
+
+        loop {
+            interval.tick().await;
+
+            let removed: usize = self
+                .shards
+                .iter()
+                .map(|shard| Self::evict_expired(&mut shard.lock().unwrap()))
+                .sum();
+
+            if removed > 0 {
+                tracing::debug!("Cache cleanup: removed {} expired entries", removed);
             }
         }
-        
-        tracing::debug!("Cache miss for permission check: {}", key);
-        None
     }
+}
 
-    /// Set cached permission result
-    pub async fn set(
+#[async_trait]
+impl PermissionCacheBackend for PermissionCache {
+    async fn get(
         &self,
         user_id: Uuid,
         relation: &str,
         object_type: &str,
         object_id: &str,
-        allowed: bool,
-    ) {
-        self.set_with_ttl(user_id, relation, object_type, object_id, allowed, self.default_ttl)
-            .await;
+    ) -> Option<bool> {
+        let key = PermissionKey::new(user_id, relation, object_type, object_id);
+        let value = {
+            let mut shard = self.shard(user_id).lock().unwrap();
+            match shard.get(&key) {
+                Some(entry) if !entry.is_expired() => Some(entry.value),
+                Some(_) => {
+                    shard.pop(&key);
+                    None
+                }
+                None => None,
+            }
+        };
+
+        match value {
+            Some(_) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                request_cost::record_cache_hit();
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                request_cost::record_cache_miss();
+            }
+        }
+        value
     }
 
-    /// Set cached permission result with custom TTL
-    pub async fn set_with_ttl(
+    async fn set(
         &self,
         user_id: Uuid,
         relation: &str,
         object_type: &str,
         object_id: &str,
         allowed: bool,
-        ttl: Duration,
     ) {
-        let key = Self::cache_key(user_id, relation, object_type, object_id);
-        let entry = CacheEntry::new(allowed, ttl);
-        
-        let mut cache = self.cache.write().await;
-        
-        // Evict expired entries if cache is full
-        if cache.len() >= self.max_entries {
-            self.evict_expired(&mut cache).await;
-            
-            // If still full, remove oldest entries (simple LRU approximation)
-            if cache.len() >= self.max_entries {
-                let keys_to_remove: Vec<String> = cache
-                    .keys()
-                    .take(cache.len() - self.max_entries + 1)
-                    .cloned()
-                    .collect();
-                
-                for key_to_remove in keys_to_remove {
-                    cache.remove(&key_to_remove);
-                }
-            }
-        }
-        
-        cache.insert(key.clone(), entry);
-        tracing::debug!("Cached permission result: {} = {}", key, allowed);
+        let ttl = self.ttl.for_result(relation, allowed);
+        self.set_with_ttl(user_id, relation, object_type, object_id, allowed, ttl)
+            .await;
     }
 
-    /// Invalidate cache entry
-    pub async fn invalidate(
+    async fn invalidate(
         &self,
         user_id: Uuid,
         relation: &str,
         object_type: &str,
         object_id: &str,
     ) {
-        let key = Self::cache_key(user_id, relation, object_type, object_id);
-        let mut cache = self.cache.write().await;
-        cache.remove(&key);
-        tracing::debug!("Invalidated cache entry: {}", key);
+        let key = PermissionKey::new(user_id, relation, object_type, object_id);
+        self.shard(user_id).lock().unwrap().pop(&key);
+        tracing::debug!(
+            "Invalidated cache entry: {}:{}:{}:{}",
+            user_id,
+            relation,
+            object_type,
+            object_id
+        );
     }
 
-    /// Invalidate all cache entries for a user
-    pub async fn invalidate_user(&self, user_id: Uuid) {
-        let user_prefix = format!("{}:", user_id);
-        let mut cache = self.cache.write().await;
-        
-        let keys_to_remove: Vec<String> = cache
-            .keys()
-            .filter(|key| key.starts_with(&user_prefix))
-            .cloned()
-            .collect();
-        
-        for key in keys_to_remove {
-            cache.remove(&key);
-        }
-        
+    async fn invalidate_user(&self, user_id: Uuid) {
+        let mut shard = self.shard(user_id).lock().unwrap();
+        Self::remove_where(&mut shard, |key, _| key.user_id == user_id);
+
         tracing::debug!("Invalidated all cache entries for user: {}", user_id);
     }
 
-    /// Invalidate all cache entries for an object
-    pub async fn invalidate_object(&self, object_type: &str, object_id: &str) {
-        let object_suffix = format!(":{}:{}", object_type, object_id);
-        let mut cache = self.cache.write().await;
-        
-        let keys_to_remove: Vec<String> = cache
-            .keys()
-            .filter(|key| key.ends_with(&object_suffix))
-            .cloned()
-            .collect();
-        
-        for key in keys_to_remove {
-            cache.remove(&key);
+    async fn invalidate_object(&self, object_type: &str, object_id: &str) {
+        for shard in &self.shards {
+            Self::remove_where(&mut shard.lock().unwrap(), |key, _| {
+                key.object_type == object_type && key.object_id == object_id
+            });
         }
-        
+
         tracing::debug!("Invalidated all cache entries for object: {}:{}", object_type, object_id);
     }
 
-    /// Clear all cache entries
-    pub async fn clear(&self) {
-        let mut cache = self.cache.write().await;
-        let count = cache.len();
-        cache.clear();
+    async fn clear(&self) {
+        let mut count = 0;
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            count += shard.len();
+            shard.clear();
+        }
         tracing::info!("Cleared {} cache entries", count);
     }
 
-    /// Evict expired entries
-    async fn evict_expired(&self, cache: &mut HashMap<String, CacheEntry<bool>>) {
-        let keys_to_remove: Vec<String> = cache
-            .iter()
-            .filter(|(_, entry)| entry.is_expired())
-            .map(|(key, _)| key.clone())
-            .collect();
-        
-        for key in keys_to_remove {
-            cache.remove(&key);
+    async fn stats(&self) -> CacheStats {
+        let (mut total_entries, mut expired_entries) = (0, 0);
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+            total_entries += shard.len();
+            expired_entries += shard.iter().filter(|(_, entry)| entry.is_expired()).count();
         }
-    }
 
-    /// Get cache statistics
-    pub async fn stats(&self) -> CacheStats {
-        let cache = self.cache.read().await;
-        let total_entries = cache.len();
-        let expired_entries = cache.values().filter(|entry| entry.is_expired()).count();
-        
         CacheStats {
             total_entries,
             expired_entries,
             active_entries: total_entries - expired_entries,
             max_entries: self.max_entries,
-            default_ttl: self.default_ttl,
+            default_ttl: self.ttl.allowed,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
         }
     }
+}
 
-    /// Background task to periodically clean up expired entries
-    pub async fn cleanup_task(&self) {
-        let mut interval = tokio::time::interval(Duration::from_secs(60)); // Clean up every minute
-        
+/// Permission check results shared through Redis
+///
+/// Keys are `{key_prefix}perm:{user}:{relation}:{type}:{id}` and expire with
+/// the cache TTL, so there's nothing to clean up. The connection is opened
+/// on first use and re-established in the background after a failure; until
+/// then every lookup is a miss and every write is dropped.
+pub struct RedisPermissionCache {
+    client: redis::Client,
+    connection: OnceCell<ConnectionManager>,
+    /// When connecting last failed; no new attempt is made for a while so an
+    /// outage doesn't add the connect timeout to every check
+    last_failure: Mutex<Option<Instant>>,
+    manager_config: ConnectionManagerConfig,
+    connect_timeout: Duration,
+    key_prefix: String,
+    ttl: CacheTtl,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RedisPermissionCache {
+    pub fn new(config: &RedisConfig, default_ttl: Duration) -> Result<Self> {
+        let client = redis::Client::open(config.url.as_str())
+            .map_err(|e| AppError::Internal(format!("Invalid Redis URL: {}", e)))?;
+        let connect_timeout = Duration::from_millis(config.connect_timeout_ms);
+
+        Ok(Self {
+            client,
+            connection: OnceCell::new(),
+            last_failure: Mutex::new(None),
+            manager_config: ConnectionManagerConfig::new()
+                .set_connection_timeout(connect_timeout)
+                .set_response_timeout(Duration::from_millis(config.response_timeout_ms))
+                .set_number_of_retries(1),
+            connect_timeout,
+            key_prefix: format!("{}perm:", config.key_prefix),
+            ttl: CacheTtl::uniform(default_ttl),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    /// Cache allowed, denied and per-relation results for different times
+    pub fn with_ttl(mut self, ttl: CacheTtl) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Replace `{key_prefix}perm:` in front of every key
+    pub fn with_key_prefix(mut self, key_prefix: String) -> Self {
+        self.key_prefix = key_prefix;
+        self
+    }
+
+    async fn connection(&self) -> Option<ConnectionManager> {
+        if let Some(connection) = self.connection.get() {
+            return Some(connection.clone());
+        }
+        let backing_off = self
+            .last_failure
+            .lock()
+            .unwrap()
+            .is_some_and(|failed_at| failed_at.elapsed() < RECONNECT_BACKOFF);
+        if backing_off {
+            return None;
+        }
+
+        let connect = async {
+            tokio::time::timeout(
+                self.connect_timeout,
+                self.client
+                    .get_connection_manager_with_config(self.manager_config.clone()),
+            )
+            .await
+            .unwrap_or_else(|_| {
+                Err(redis::RedisError::from((
+                    redis::ErrorKind::IoError,
+                    "connect timed out",
+                )))
+            })
+        };
+
+        match self.connection.get_or_try_init(|| connect).await {
+            Ok(connection) => Some(connection.clone()),
+            Err(e) => {
+                *self.last_failure.lock().unwrap() = Some(Instant::now());
+                tracing::warn!(error = %e, "Redis permission cache unavailable");
+                None
+            }
+        }
+    }
+
+    fn key(&self, user_id: Uuid, relation: &str, object_type: &str, object_id: &str) -> String {
+        format!(
+            "{}{}",
+            self.key_prefix,
+            cache_key(user_id, relation, object_type, object_id)
+        )
+    }
+
+    /// Delete every key matching `pattern`, a SCAN glob
+    async fn delete_matching(&self, pattern: &str) -> redis::RedisResult<usize> {
+        let Some(mut connection) = self.connection().await else {
+            return Ok(0);
+        };
+
+        let mut cursor = 0u64;
+        let mut deleted = 0;
         loop {
-            interval.tick().await;
-            
-            let mut cache = self.cache.write().await;
-            let initial_count = cache.len();
-            self.evict_expired(&mut cache).await;
-            let final_count = cache.len();
-            
-            if initial_count > final_count {
-                tracing::debug!(
-                    "Cache cleanup: removed {} expired entries ({} -> {})",
-                    initial_count - final_count,
-                    initial_count,
-                    final_count
-                );
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(SCAN_BATCH)
+                .query_async(&mut connection)
+                .await?;
+            if !keys.is_empty() {
+                deleted += connection.del::<_, usize>(&keys).await?;
             }
+            if next == 0 {
+                return Ok(deleted);
+            }
+            cursor = next;
+        }
+    }
+
+    async fn count_matching(&self, pattern: &str) -> redis::RedisResult<usize> {
+        let Some(mut connection) = self.connection().await else {
+            return Ok(0);
+        };
+
+        let mut cursor = 0u64;
+        let mut count = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(SCAN_BATCH)
+                .query_async(&mut connection)
+                .await?;
+            count += keys.len();
+            if next == 0 {
+                return Ok(count);
+            }
+            cursor = next;
+        }
+    }
+
+    fn log_failure(&self, operation: &str, result: redis::RedisResult<usize>) {
+        if let Err(e) = result {
+            tracing::warn!(operation, error = %e, "Redis permission cache operation failed");
+        }
+    }
+}
+
+const SCAN_BATCH: usize = 500;
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Escape SCAN glob characters in a key fragment
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait]
+impl PermissionCacheBackend for RedisPermissionCache {
+    async fn get(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Option<bool> {
+        let value = match self.connection().await {
+            Some(mut connection) => connection
+                .get::<_, Option<String>>(self.key(user_id, relation, object_type, object_id))
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Redis permission cache read failed");
+                    None
+                }),
+            None => None,
+        };
+
+        let allowed = match value.as_deref() {
+            Some("1") => Some(true),
+            Some("0") => Some(false),
+            _ => None,
+        };
+        match allowed {
+            Some(_) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                request_cost::record_cache_hit();
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                request_cost::record_cache_miss();
+            }
+        }
+        allowed
+    }
+
+    async fn set(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+        allowed: bool,
+    ) {
+        let ttl = self.ttl.for_result(relation, allowed);
+        if ttl.is_zero() {
+            return;
+        }
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+
+        let key = self.key(user_id, relation, object_type, object_id);
+        let value = if allowed { "1" } else { "0" };
+        if let Err(e) = connection
+            .set_ex::<_, _, ()>(key, value, ttl.as_secs().max(1))
+            .await
+        {
+            tracing::warn!(error = %e, "Redis permission cache write failed");
+        }
+    }
+
+    async fn invalidate(&self, user_id: Uuid, relation: &str, object_type: &str, object_id: &str) {
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+        let result = connection
+            .del::<_, usize>(self.key(user_id, relation, object_type, object_id))
+            .await;
+        self.log_failure("invalidate", result);
+    }
+
+    async fn invalidate_user(&self, user_id: Uuid) {
+        let pattern = format!("{}{}:*", escape_glob(&self.key_prefix), user_id);
+        let result = self.delete_matching(&pattern).await;
+        self.log_failure("invalidate_user", result);
+    }
+
+    async fn invalidate_object(&self, object_type: &str, object_id: &str) {
+        let pattern = format!(
+            "{}*:{}:{}",
+            escape_glob(&self.key_prefix),
+            escape_glob(object_type),
+            escape_glob(object_id)
+        );
+        let result = self.delete_matching(&pattern).await;
+        self.log_failure("invalidate_object", result);
+    }
+
+    async fn clear(&self) {
+        let pattern = format!("{}*", escape_glob(&self.key_prefix));
+        match self.delete_matching(&pattern).await {
+            Ok(count) => tracing::info!("Cleared {} cache entries", count),
+            Err(e) => tracing::warn!(error = %e, "Failed to clear Redis permission cache"),
+        }
+    }
+
+    async fn stats(&self) -> CacheStats {
+        let pattern = format!("{}*", escape_glob(&self.key_prefix));
+        let total_entries = self.count_matching(&pattern).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to count Redis permission cache entries");
+            0
+        });
+
+        // Redis drops expired keys itself and isn't bounded by entry count
+        CacheStats {
+            total_entries,
+            expired_entries: 0,
+            active_entries: total_entries,
+            max_entries: usize::MAX,
+            default_ttl: self.ttl.allowed,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: 0,
         }
     }
 }
@@ -236,6 +741,11 @@ pub struct CacheStats {
     pub active_entries: usize,
     pub max_entries: usize,
     pub default_ttl: Duration,
+    /// Lookups answered from the cache since startup
+    pub hits: u64,
+    pub misses: u64,
+    /// Live entries dropped to make room
+    pub evictions: u64,
 }
 
 impl Default for PermissionCache {
@@ -246,3 +756,92 @@ impl Default for PermissionCache {
         )
     }
 }
+
+/// Cached outcome of a session lookup
+#[derive(Debug, Clone, Copy)]
+struct SessionStatus {
+    user_id: Uuid,
+    valid: bool,
+}
+
+/// In-memory cache of session validity for the auth middleware
+///
+/// Valid sessions are re-checked after the TTL, so a revocation made by
+/// another instance takes effect within one TTL. Revocations made through
+/// this process are invalidated immediately.
+#[derive(Debug)]
+pub struct SessionCache {
+    cache: RwLock<HashMap<Uuid, CacheEntry<SessionStatus>>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl SessionCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            cache: RwLock::new(HashMap::new()),
+            ttl,
+            max_entries,
+        }
+    }
+
+    /// Cached validity of a session, if still fresh
+    pub async fn get(&self, session_id: Uuid) -> Option<bool> {
+        let cache = self.cache.read().await;
+        let valid = cache
+            .get(&session_id)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.valid);
+
+        match valid {
+            Some(_) => request_cost::record_cache_hit(),
+            None => request_cost::record_cache_miss(),
+        }
+        valid
+    }
+
+    pub async fn set(&self, session_id: Uuid, user_id: Uuid, valid: bool) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        let mut cache = self.cache.write().await;
+
+        if cache.len() >= self.max_entries {
+            cache.retain(|_, entry| !entry.is_expired());
+
+            // Still full: drop an arbitrary entry, it'll just be looked up again
+            if cache.len() >= self.max_entries {
+                if let Some(key) = cache.keys().next().copied() {
+                    cache.remove(&key);
+                }
+            }
+        }
+
+        cache.insert(session_id, CacheEntry::new(SessionStatus { user_id, valid }, self.ttl));
+    }
+
+    /// Forget a session after it has been revoked
+    pub async fn invalidate(&self, session_id: Uuid) {
+        self.cache.write().await.remove(&session_id);
+    }
+
+    /// Forget all of a user's sessions
+    pub async fn invalidate_user(&self, user_id: Uuid) {
+        self.cache
+            .write()
+            .await
+            .retain(|_, entry| entry.value.user_id != user_id);
+    }
+
+    /// Forget every session
+    pub async fn clear(&self) {
+        self.cache.write().await.clear();
+    }
+}
+
+impl Default for SessionCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), 10000)
+    }
+}
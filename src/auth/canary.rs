@@ -0,0 +1,129 @@
+//! Dual evaluation of role checks against OpenFGA relations.
+//!
+//! While role checks (`require_role`) are migrated to relations, each route
+//! group can run in one of three modes: `legacy` checks the role only,
+//! `shadow` also asks OpenFGA but still serves the role decision, and
+//! `relations` serves the OpenFGA decision. Whenever both paths run, their
+//! disagreements are logged and counted, so a group is flipped only once it
+//! has been quiet in shadow mode.
+
+use crate::auth::authorizer::Authorizer;
+use crate::auth::jwt::JwtService;
+use crate::auth::models::{object_types, AuthContext};
+use crate::auth::registry;
+use crate::config::{AuthorizationCanaryConfig, CanaryMode, MetricsConfig};
+use crate::metrics::{AppMetrics, RouteGroups};
+use anyhow::Context;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Decides role checks for the routes in each group
+pub struct AuthorizationCanary {
+    groups: RouteGroups,
+    modes: HashMap<String, CanaryMode>,
+    default_mode: CanaryMode,
+    /// Role -> relation on the caller's tenant organization
+    role_relations: HashMap<String, String>,
+    authorizer: Arc<dyn Authorizer>,
+    metrics: Option<AppMetrics>,
+}
+
+impl AuthorizationCanary {
+    /// Modes are keyed by the group names in `[metrics.route_groups]`; a
+    /// role mapped to a relation organizations don't have is a config error
+    pub fn new(
+        config: &AuthorizationCanaryConfig,
+        metrics_config: &MetricsConfig,
+        authorizer: Arc<dyn Authorizer>,
+    ) -> anyhow::Result<Self> {
+        for (role, relation) in &config.role_relations {
+            registry::validate(object_types::ORGANIZATION, relation)
+                .with_context(|| format!("Invalid relation for role '{}'", role))?;
+        }
+
+        Ok(Self {
+            groups: RouteGroups::from_config(&metrics_config.route_groups),
+            modes: config.route_groups.clone(),
+            default_mode: config.default_mode,
+            role_relations: config.role_relations.clone(),
+            authorizer,
+            metrics: None,
+        })
+    }
+
+    /// Count agreements and disagreements per route group
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn mode_for(&self, route: &str) -> CanaryMode {
+        self.modes
+            .get(self.groups.resolve(route))
+            .copied()
+            .unwrap_or(self.default_mode)
+    }
+
+    /// Whether the caller may use `route`, which requires `role`
+    ///
+    /// A role without a mapped relation, or an OpenFGA error, leaves the
+    /// role decision in force whatever the mode.
+    pub async fn allows(&self, route: &str, context: &AuthContext, role: &str) -> bool {
+        let legacy = JwtService::has_role(context, role);
+        let mode = self.mode_for(route);
+        if mode == CanaryMode::Legacy {
+            return legacy;
+        }
+        let Some(relation) = self.role_relations.get(role) else {
+            return legacy;
+        };
+
+        let group = self.groups.resolve(route);
+        let relations = match self
+            .authorizer
+            .check(context.subject(), relation, object_types::ORGANIZATION, context.tenant())
+            .await
+        {
+            Ok(result) => result.allowed,
+            Err(e) => {
+                tracing::warn!(
+                    route,
+                    role,
+                    error = %e,
+                    "Authorization canary could not evaluate the relation path"
+                );
+                self.record(group, "error");
+                return legacy;
+            }
+        };
+
+        if relations == legacy {
+            self.record(group, "agree");
+        } else {
+            tracing::warn!(
+                route,
+                route_group = group,
+                user_id = %context.user_id,
+                tenant = context.tenant(),
+                role,
+                relation = relation.as_str(),
+                legacy,
+                relations,
+                mode = ?mode,
+                "Authorization canary disagreement"
+            );
+            self.record(group, "disagree");
+        }
+
+        match mode {
+            CanaryMode::Relations => relations,
+            CanaryMode::Legacy | CanaryMode::Shadow => legacy,
+        }
+    }
+
+    fn record(&self, group: &str, outcome: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_authz_canary(group, outcome);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::auth::openfga::OpenFgaService;
+use crate::config::OpenFgaChangesConfig;
+use crate::errors::Result;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Follows the store's change log and drops the cached checks each change
+/// may have affected
+///
+/// Tuples written through this instance are invalidated as they're written;
+/// this catches the ones other services write. It starts from the time it
+/// was created, since anything older can't be in a cache that's only now
+/// filling up.
+pub struct ChangeWatcher {
+    openfga: Arc<OpenFgaService>,
+    poll_interval: Duration,
+    page_size: u32,
+    started_at: chrono::DateTime<Utc>,
+    continuation_token: Option<String>,
+}
+
+impl ChangeWatcher {
+    pub fn new(config: &OpenFgaChangesConfig, openfga: Arc<OpenFgaService>) -> Self {
+        Self {
+            openfga,
+            poll_interval: Duration::from_millis(config.poll_interval_ms.max(1)),
+            page_size: config.page_size.clamp(1, 100),
+            started_at: Utc::now(),
+            continuation_token: None,
+        }
+    }
+
+    pub fn from_config(config: &OpenFgaChangesConfig, openfga: Arc<OpenFgaService>) -> Option<Self> {
+        config.enabled.then(|| Self::new(config, openfga))
+    }
+
+    /// Apply every change since the last poll; returns how many there were
+    pub async fn poll(&mut self) -> Result<usize> {
+        let mut applied = 0;
+        loop {
+            let page = self
+                .openfga
+                .read_changes(
+                    self.continuation_token.as_deref(),
+                    Some(self.started_at),
+                    Some(self.page_size),
+                )
+                .await?;
+
+            for change in &page.changes {
+                self.openfga.invalidate_for_change(change).await;
+            }
+            applied += page.changes.len();
+
+            if !page.continuation_token.is_empty() {
+                self.continuation_token = Some(page.continuation_token);
+            }
+            if page.changes.len() < self.page_size as usize {
+                return Ok(applied);
+            }
+        }
+    }
+
+    /// Poll until the process exits; a failed poll is retried next interval
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match self.poll().await {
+                Ok(0) => {}
+                Ok(applied) => tracing::debug!(
+                    changes = applied,
+                    "Invalidated permission cache for OpenFGA tuple changes"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Failed to read OpenFGA changes"),
+            }
+        }
+    }
+}
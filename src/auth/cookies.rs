@@ -0,0 +1,232 @@
+//! Cookie-based sessions for browser frontends
+//!
+//! With `auth.cookies.enabled`, endpoints that issue tokens set them as
+//! HttpOnly cookies and leave them out of the response body, so scripts on
+//! the page never see them, and the auth middleware falls back to the access
+//! cookie when a request has no `Authorization` header.
+//!
+//! Browsers attach cookies to requests other sites trigger, so a request
+//! authenticated by cookie with an unsafe method must also send the CSRF
+//! cookie's value in the CSRF header (double submit). The CSRF cookie isn't
+//! HttpOnly: the frontend reads it, and other origins can't.
+
+use crate::auth::jwt::JwtService;
+use crate::auth::models::{LoginResponse, SwitchTenantResponse};
+use crate::config::{CookieSameSite, SessionCookieConfig};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Method};
+use std::sync::Arc;
+
+/// Where the refresh cookie is sent: the refresh and logout endpoints
+const REFRESH_COOKIE_PATH: &str = "/api/v1/auth";
+
+/// Issues, reads and clears the session cookies
+#[derive(Debug)]
+pub struct SessionCookies {
+    config: SessionCookieConfig,
+    csrf_header: HeaderName,
+}
+
+impl SessionCookies {
+    pub fn new(config: &SessionCookieConfig) -> anyhow::Result<Self> {
+        if config.same_site == CookieSameSite::None && !config.secure {
+            anyhow::bail!(
+                "auth.cookies.same_site = \"none\" requires secure = true"
+            );
+        }
+        let csrf_header = HeaderName::try_from(config.csrf_header.as_str())
+            .map_err(|e| {
+                anyhow::anyhow!("Invalid auth.cookies.csrf_header: {}", e)
+            })?;
+        for name in [
+            &config.access_cookie,
+            &config.refresh_cookie,
+            &config.csrf_cookie,
+        ] {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                anyhow::bail!("Invalid cookie name '{}'", name);
+            }
+        }
+        if let Some(domain) = &config.domain {
+            HeaderValue::from_str(domain).map_err(|_| {
+                anyhow::anyhow!("Invalid auth.cookies.domain '{}'", domain)
+            })?;
+        }
+
+        Ok(Self { config: config.clone(), csrf_header })
+    }
+
+    /// Build the cookies from config, or `None` when cookie mode is disabled
+    pub fn from_config(
+        config: &SessionCookieConfig,
+    ) -> anyhow::Result<Option<Arc<Self>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Ok(Some(Arc::new(Self::new(config)?)))
+    }
+
+    /// Move the tokens of a login or refresh into cookies, along with a new
+    /// CSRF token
+    pub fn issue(&self, response: &mut LoginResponse) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let access_token = std::mem::take(&mut response.access_token);
+        let refresh_token = std::mem::take(&mut response.refresh_token);
+
+        self.append(
+            &mut headers,
+            &self.config.access_cookie,
+            &access_token,
+            "/",
+            response.expires_in,
+            true,
+        );
+        self.append(
+            &mut headers,
+            &self.config.refresh_cookie,
+            &refresh_token,
+            REFRESH_COOKIE_PATH,
+            response.refresh_expires_in,
+            true,
+        );
+        self.append(
+            &mut headers,
+            &self.config.csrf_cookie,
+            &JwtService::generate_opaque_token(),
+            "/",
+            response.refresh_expires_in,
+            false,
+        );
+        headers
+    }
+
+    /// Move a re-scoped access token into the access cookie
+    pub fn issue_access(
+        &self,
+        response: &mut SwitchTenantResponse,
+    ) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let access_token = std::mem::take(&mut response.access_token);
+        self.append(
+            &mut headers,
+            &self.config.access_cookie,
+            &access_token,
+            "/",
+            response.expires_in,
+            true,
+        );
+        headers
+    }
+
+    /// Expire every session cookie
+    pub fn clear(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        self.append(
+            &mut headers,
+            &self.config.access_cookie,
+            "",
+            "/",
+            0,
+            true,
+        );
+        self.append(
+            &mut headers,
+            &self.config.refresh_cookie,
+            "",
+            REFRESH_COOKIE_PATH,
+            0,
+            true,
+        );
+        self.append(&mut headers, &self.config.csrf_cookie, "", "/", 0, false);
+        headers
+    }
+
+    pub fn access_token<'h>(&self, headers: &'h HeaderMap) -> Option<&'h str> {
+        cookie(headers, &self.config.access_cookie)
+    }
+
+    pub fn refresh_token<'h>(
+        &self,
+        headers: &'h HeaderMap,
+    ) -> Option<&'h str> {
+        cookie(headers, &self.config.refresh_cookie)
+    }
+
+    /// Whether the CSRF header matches the CSRF cookie; always true for safe
+    /// methods
+    pub fn csrf_valid(&self, method: &Method, headers: &HeaderMap) -> bool {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            return true;
+        }
+
+        let expected = cookie(headers, &self.config.csrf_cookie);
+        let sent = headers
+            .get(&self.csrf_header)
+            .and_then(|value| value.to_str().ok());
+        match (expected, sent) {
+            (Some(expected), Some(sent)) => {
+                constant_time_eq(expected.as_bytes(), sent.as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn append(
+        &self,
+        headers: &mut HeaderMap,
+        name: &str,
+        value: &str,
+        path: &str,
+        max_age: u64,
+        http_only: bool,
+    ) {
+        let mut cookie = format!(
+            "{}={}; Path={}; Max-Age={}; SameSite={}",
+            name,
+            value,
+            path,
+            max_age,
+            self.config.same_site.as_str()
+        );
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.config.secure {
+            cookie.push_str("; Secure");
+        }
+        if let Some(domain) = &self.config.domain {
+            cookie.push_str("; Domain=");
+            cookie.push_str(domain);
+        }
+
+        // Names and the domain are checked on startup; values are tokens we
+        // generated, which are URL-safe
+        headers.append(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&cookie)
+                .expect("session cookies are valid header values"),
+        );
+    }
+}
+
+/// Value of the cookie `name` across every `Cookie` header
+fn cookie<'h>(headers: &'h HeaderMap, name: &str) -> Option<&'h str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+        .filter(|value| !value.is_empty())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
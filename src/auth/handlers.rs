@@ -1,35 +1,66 @@
-use crate::auth::jwt::JwtService;
 use crate::auth::models::{
-    AuthContext, LoginRequest, LoginResponse, RegisterRequest, UserInfo,
+    AuthContext, ClientCredentialsRequest, ClientCredentialsResponse, ConfirmDeviceRequest,
+    IntrospectionRequest, IntrospectionResponse,
+    CreatePersonalAccessTokenRequest, CreateServiceAccountRequest, CreatedPersonalAccessToken,
+    CreatedServiceAccount, CurrentUser, DeleteAccountRequest, ForgotPasswordRequest, LoginRequest,
+    LoginResponse,
+    PersonalAccessTokenInfo, RefreshTokenRequest, RegisterRequest, ResetPasswordRequest,
+    RevokeAllSessionsRequest, RevokeAllSessionsResponse,
+    ServiceAccountInfo, SessionInfo, SessionMetadata, SwitchTenantRequest, SwitchTenantResponse,
+    UserAccessReport, UserInfo, object_types, roles,
+};
+use crate::auth::cookies::SessionCookies;
+use crate::auth::jwt::JwtService;
+use crate::auth::openfga::{
+    AuthorizationModel, AuthorizationModelPage, ObjectPage, ObjectUsers, OpenFgaService,
+    TupleFilter, TuplePage, WriteAuthorizationModelResponse,
+};
+use crate::auth::webauthn::{
+    PasskeyInfo, PasskeyLoginFinish, PasskeyLoginStart, PasskeyLoginStartRequest,
+    PasskeyRegistrationFinish, PasskeyRegistrationStart,
 };
-use crate::auth::openfga::OpenFgaService;
-use crate::errors::Result;
-use crate::models::ApiResponse;
+use crate::errors::{AppError, Result};
+use crate::models::{audit_actions, audit_resources, fixtures, ApiResponse, AuditEvent};
+use crate::request_context::RequestContext;
 use crate::services::Services;
+use crate::utils::{validate_range, Json, Validate, ValidatedQuery};
 use axum::{
-    extract::{Extension, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    response::IntoResponse,
 };
+use serde::Deserialize;
 use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AuthHandlers {
     services: Arc<Services>,
-    jwt_service: Arc<JwtService>,
     openfga_service: Arc<OpenFgaService>,
+    cookies: Option<Arc<SessionCookies>>,
 }
 
 impl AuthHandlers {
-    pub fn new(
-        services: Arc<Services>,
-        jwt_service: Arc<JwtService>,
-        openfga_service: Arc<OpenFgaService>,
-    ) -> Self {
+    pub fn new(services: Arc<Services>, openfga_service: Arc<OpenFgaService>) -> Self {
         Self {
             services,
-            jwt_service,
             openfga_service,
+            cookies: None,
+        }
+    }
+
+    /// Hand tokens to browsers as cookies instead of in response bodies
+    pub fn with_cookies(mut self, cookies: Arc<SessionCookies>) -> Self {
+        self.cookies = Some(cookies);
+        self
+    }
+
+    /// In cookie mode, the cookies carrying `response`'s tokens
+    fn issue(&self, response: &mut LoginResponse) -> HeaderMap {
+        match &self.cookies {
+            Some(cookies) => cookies.issue(response),
+            None => HeaderMap::new(),
         }
     }
 }
@@ -39,24 +70,40 @@ impl AuthHandlers {
     post,
     path = "/api/v1/auth/register",
     tag = "authentication",
-    request_body = RegisterRequest,
+    request_body(
+        content = RegisterRequest,
+        examples(
+            ("valid" = (summary = "Valid registration", value = json!(fixtures::register::valid()))),
+            ("min_length" = (summary = "Shortest accepted username and password", value = json!(fixtures::register::min_length()))),
+            ("invalid_email" = (summary = "Rejected: malformed email", value = json!(fixtures::register::invalid_email()))),
+            ("short_password" = (summary = "Rejected: password under 8 characters", value = json!(fixtures::register::short_password()))),
+        )
+    ),
     responses(
-        (status = 201, description = "User registered successfully", body = ApiResponse<LoginResponse>),
+        (status = 201, description = "User registered successfully; in cookie mode the tokens are set as cookies", body = ApiResponse<LoginResponse>),
         (status = 400, description = "Bad request"),
-        (status = 409, description = "User already exists")
+        (status = 409, description = "Email or username already registered"),
+        (status = 429, description = "Too many attempts; see Retry-After")
     )
 )]
 pub async fn register(
     State(handlers): State<AuthHandlers>,
+    context: RequestContext,
     Json(request): Json<RegisterRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<LoginResponse>>)> {
+) -> Result<(StatusCode, HeaderMap, Json<ApiResponse<LoginResponse>>)> {
     // Use the auth service to handle the complete registration process
-    let response = handlers.services.auth.register(request).await?;
+    let mut response = handlers
+        .services
+        .auth
+        .register(request, SessionMetadata::from(&context))
+        .await?;
 
     tracing::info!("User registered successfully: {}", response.user.id);
 
+    let cookies = handlers.issue(&mut response);
     Ok((
         StatusCode::CREATED,
+        cookies,
         Json(ApiResponse::success_with_message(
             response,
             "User registered successfully".to_string(),
@@ -69,31 +116,42 @@ pub async fn register(
     post,
     path = "/api/v1/auth/login",
     tag = "authentication",
-    request_body = LoginRequest,
+    request_body(
+        content = LoginRequest,
+        examples(
+            ("valid" = (summary = "Email and password", value = json!(fixtures::login::valid()))),
+        )
+    ),
     responses(
-        (status = 200, description = "Login successful", body = ApiResponse<LoginResponse>),
-        (status = 401, description = "Invalid credentials")
+        (status = 200, description = "Login successful; in cookie mode the tokens are set as cookies", body = ApiResponse<LoginResponse>),
+        (status = 401, description = "Invalid credentials"),
+        (status = 429, description = "Too many attempts; see Retry-After")
     )
 )]
 pub async fn login(
     State(handlers): State<AuthHandlers>,
+    context: RequestContext,
     Json(request): Json<LoginRequest>,
-) -> Result<Json<ApiResponse<LoginResponse>>> {
+) -> Result<(HeaderMap, Json<ApiResponse<LoginResponse>>)> {
     // Use the auth service to handle the complete login process
-    let response = handlers.services.auth.login(request).await?;
+    let mut response = handlers
+        .services
+        .auth
+        .login(request, SessionMetadata::from(&context))
+        .await?;
 
     tracing::info!("User logged in successfully: {}", response.user.id);
 
-    Ok(Json(ApiResponse::success(response)))
+    Ok((handlers.issue(&mut response), Json(ApiResponse::success(response))))
 }
 
-/// Get current user profile
+/// Get current user profile and the tenants they can switch to
 #[utoipa::path(
     get,
     path = "/api/v1/auth/me",
     tag = "authentication",
     responses(
-        (status = 200, description = "Current user profile", body = ApiResponse<UserInfo>),
+        (status = 200, description = "Current user profile", body = ApiResponse<CurrentUser>),
         (status = 401, description = "Unauthorized")
     ),
     security(
@@ -101,56 +159,108 @@ pub async fn login(
     )
 )]
 pub async fn me(
+    State(handlers): State<AuthHandlers>,
     Extension(auth_context): Extension<AuthContext>,
-) -> Result<Json<ApiResponse<UserInfo>>> {
-    let user_info = UserInfo {
-        id: auth_context.user_id,
-        email: auth_context.email,
-        username: auth_context.username,
-        roles: auth_context.roles,
+) -> Result<Json<ApiResponse<CurrentUser>>> {
+    let tenant = auth_context.tenant().to_string();
+
+    // The profile is still useful when OpenFGA can't be reached
+    let tenants = match handlers
+        .services
+        .auth
+        .available_tenants(auth_context.user_id)
+        .await
+    {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            tracing::warn!(user_id = %auth_context.user_id, error = %e, "Failed to list tenants");
+            vec![tenant.clone()]
+        }
     };
 
-    Ok(Json(ApiResponse::success(user_info)))
+    Ok(Json(ApiResponse::success(CurrentUser {
+        user: UserInfo {
+            id: auth_context.user_id,
+            email: auth_context.email,
+            username: auth_context.username,
+            roles: auth_context.roles,
+        },
+        tenant,
+        tenants,
+    })))
 }
 
-/// Refresh JWT token
+/// Switch the current session to another tenant the user belongs to
 #[utoipa::path(
     post,
-    path = "/api/v1/auth/refresh",
+    path = "/api/v1/auth/switch-tenant",
     tag = "authentication",
+    request_body = SwitchTenantRequest,
     responses(
-        (status = 200, description = "Token refreshed successfully", body = ApiResponse<LoginResponse>),
-        (status = 401, description = "Invalid token")
+        (status = 200, description = "Access token scoped to the tenant", body = ApiResponse<SwitchTenantResponse>),
+        (status = 400, description = "Invalid tenant, or the token has no session"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not a member of the tenant")
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
-pub async fn refresh_token(
+pub async fn switch_tenant(
     State(handlers): State<AuthHandlers>,
     Extension(auth_context): Extension<AuthContext>,
-) -> Result<Json<ApiResponse<LoginResponse>>> {
-    // Generate new JWT token
-    let token = handlers.jwt_service.generate_token(
-        auth_context.user_id,
-        auth_context.email.clone(),
-        auth_context.username.clone(),
-        auth_context.roles.clone(),
-    )?;
+    context: RequestContext,
+    Json(request): Json<SwitchTenantRequest>,
+) -> Result<(HeaderMap, Json<ApiResponse<SwitchTenantResponse>>)> {
+    let mut response = handlers
+        .services
+        .auth
+        .switch_tenant(&auth_context, &request.tenant, SessionMetadata::from(&context))
+        .await?;
 
-    let response = LoginResponse {
-        access_token: token,
-        token_type: "Bearer".to_string(),
-        expires_in: 24 * 3600, // 24 hours in seconds
-        user: UserInfo {
-            id: auth_context.user_id,
-            email: auth_context.email,
-            username: auth_context.username,
-            roles: auth_context.roles,
-        },
+    let cookies = match &handlers.cookies {
+        Some(cookies) => cookies.issue_access(&mut response),
+        None => HeaderMap::new(),
     };
+    Ok((cookies, Json(ApiResponse::success(response))))
+}
 
-    Ok(Json(ApiResponse::success(response)))
+/// Exchange a refresh token for a new access token (the refresh token is rotated)
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    tag = "authentication",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "Token refreshed successfully", body = ApiResponse<LoginResponse>),
+        (status = 400, description = "No refresh token in the body or cookie"),
+        (status = 401, description = "Invalid, expired or reused refresh token"),
+        (status = 403, description = "Refresh cookie sent without a valid CSRF token")
+    )
+)]
+pub async fn refresh_token(
+    State(handlers): State<AuthHandlers>,
+    headers: HeaderMap,
+    Json(request): Json<RefreshTokenRequest>,
+) -> Result<(HeaderMap, Json<ApiResponse<LoginResponse>>)> {
+    // In cookie mode the token may come from the refresh cookie, which the
+    // browser sends on its own, so it needs the CSRF header
+    let refresh_token = match &handlers.cookies {
+        Some(cookies) if request.refresh_token.is_empty() => {
+            if !cookies.csrf_valid(&Method::POST, &headers) {
+                return Err(AppError::Forbidden);
+            }
+            cookies.refresh_token(&headers).unwrap_or_default()
+        }
+        _ => request.refresh_token.as_str(),
+    };
+    if refresh_token.is_empty() {
+        return Err(AppError::Validation("refresh_token is required".to_string()));
+    }
+
+    let mut response = handlers.services.auth.refresh_token(refresh_token).await?;
+
+    Ok((handlers.issue(&mut response), Json(ApiResponse::success(response))))
 }
 
 /// Check user permissions for a specific resource
@@ -191,6 +301,130 @@ pub async fn check_permission(
     Ok(Json(ApiResponse::success(result.allowed)))
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListUsersParams {
+    /// `type:id` of the object being shared
+    #[param(example = "document:roadmap")]
+    pub object: String,
+    #[param(example = "editor")]
+    pub relation: String,
+    /// Type of the users to list; defaults to `user`
+    #[param(example = "user")]
+    pub user_type: Option<String>,
+    /// List usersets instead, e.g. `member` for `group:<id>#member`
+    pub user_relation: Option<String>,
+}
+
+/// List who has a relation to an object (e.g. who can edit a document)
+///
+/// For sharing UIs: callers must hold the relation themselves, so an editor
+/// can see the other editors; admins can ask about any object.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/list-users",
+    tag = "authentication",
+    params(ListUsersParams),
+    responses(
+        (status = 200, description = "Users with the relation", body = ApiResponse<ObjectUsers>),
+        (status = 400, description = "Invalid object or relation"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller doesn't hold the relation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_users(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Query(params): Query<ListUsersParams>,
+) -> Result<Json<ApiResponse<ObjectUsers>>> {
+    let (object_type, object_id) = params
+        .object
+        .split_once(':')
+        .filter(|(object_type, object_id)| !object_type.is_empty() && !object_id.is_empty())
+        .ok_or_else(|| {
+            AppError::Validation("Invalid object format. Expected 'type:id'".to_string())
+        })?;
+
+    if !JwtService::has_role(&auth_context, roles::ADMIN) {
+        let result = handlers
+            .openfga_service
+            .check_permission(auth_context.user_id, &params.relation, object_type, object_id)
+            .await?;
+        if !result.allowed {
+            return Err(AppError::Forbidden);
+        }
+    }
+
+    let users = handlers
+        .openfga_service
+        .list_users(
+            object_type,
+            object_id,
+            &params.relation,
+            params.user_type.as_deref().unwrap_or(object_types::USER),
+            params.user_relation.as_deref().filter(|relation| !relation.is_empty()),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(ObjectUsers {
+        object: params.object.clone(),
+        relation: params.relation,
+        users,
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListObjectsParams {
+    /// Type of the objects to list
+    #[serde(rename = "type")]
+    #[param(rename = "type", example = "document")]
+    pub object_type: String,
+    #[param(example = "viewer")]
+    pub relation: String,
+    /// Defaults to `auth.openfga.list_objects.default_page_size`, capped at
+    /// `max_page_size`
+    #[param(example = 50)]
+    pub page_size: Option<u32>,
+    /// `continuation_token` from the previous page
+    pub continuation_token: Option<String>,
+}
+
+/// List the objects of a type the caller has a relation to, a page at a time
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/list-objects",
+    tag = "authentication",
+    params(ListObjectsParams),
+    responses(
+        (status = 200, description = "A page of objects", body = ApiResponse<ObjectPage>),
+        (status = 400, description = "Invalid type, relation or continuation token"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_objects(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Query(params): Query<ListObjectsParams>,
+) -> Result<Json<ApiResponse<ObjectPage>>> {
+    let page = handlers
+        .openfga_service
+        .list_objects_page(
+            auth_context.user_id,
+            &params.relation,
+            &params.object_type,
+            params.page_size,
+            params.continuation_token.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(page)))
+}
+
 /// Logout user and invalidate token
 #[utoipa::path(
     post,
@@ -206,23 +440,838 @@ pub async fn check_permission(
 )]
 pub async fn logout(
     State(handlers): State<AuthHandlers>,
-    headers: HeaderMap,
     Extension(auth_context): Extension<AuthContext>,
+) -> Result<(HeaderMap, Json<ApiResponse<String>>)> {
+    handlers.services.auth.logout(&auth_context).await?;
+
+    tracing::info!("User logged out successfully: {}", auth_context.user_id);
+
+    let cookies = match &handlers.cookies {
+        Some(cookies) => cookies.clear(),
+        None => HeaderMap::new(),
+    };
+    Ok((cookies, Json(ApiResponse::success_with_message(
+        "Logged out successfully".to_string(),
+        "User session has been terminated".to_string(),
+    ))))
+}
+
+/// Delete the caller's account, with everything stored about it
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/me",
+    tag = "authentication",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 401, description = "Unauthorized or wrong password"),
+        (status = 403, description = "Service accounts can't delete themselves")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_me(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<StatusCode> {
+    handlers
+        .services
+        .auth
+        .delete_account(&auth_context, &request.password)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// JSON Web Key Set with the public keys tokens are signed with
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "JSON Web Key Set (RFC 7517)", content_type = "application/json")
+    )
+)]
+pub async fn jwks(State(jwt_service): State<Arc<JwtService>>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(jwt_service.jwks()),
+    )
+}
+
+/// Request a password reset email
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/forgot-password",
+    tag = "authentication",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 200, description = "Reset email sent if the account exists", body = ApiResponse<String>)
+    )
+)]
+pub async fn forgot_password(
+    State(handlers): State<AuthHandlers>,
+    Json(request): Json<ForgotPasswordRequest>,
 ) -> Result<Json<ApiResponse<String>>> {
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| crate::errors::AppError::Unauthorized)?;
+    handlers.services.auth.forgot_password(&request.email).await?;
 
-    let token = JwtService::extract_token_from_header(auth_header)
-        .map_err(|_| crate::errors::AppError::Unauthorized)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Password reset requested".to_string(),
+        "If the account exists, a reset link has been sent".to_string(),
+    )))
+}
 
-    handlers.services.auth.logout(token).await?;
+/// Reset password with an emailed one-time token
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/reset-password",
+    tag = "authentication",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset successfully", body = ApiResponse<String>),
+        (status = 400, description = "Invalid or expired token, or weak password")
+    )
+)]
+pub async fn reset_password(
+    State(handlers): State<AuthHandlers>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<Json<ApiResponse<String>>> {
+    handlers
+        .services
+        .auth
+        .reset_password(&request.token, &request.new_password)
+        .await?;
 
-    tracing::info!("User logged out successfully: {}", auth_context.user_id);
+    Ok(Json(ApiResponse::success_with_message(
+        "Password reset successfully".to_string(),
+        "All existing sessions have been signed out".to_string(),
+    )))
+}
+
+/// Confirm a sign-in from a new device with the emailed one-time token
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/confirm-device",
+    tag = "authentication",
+    request_body = ConfirmDeviceRequest,
+    responses(
+        (status = 200, description = "Device confirmed", body = ApiResponse<String>),
+        (status = 400, description = "Invalid or expired token")
+    )
+)]
+pub async fn confirm_device(
+    State(handlers): State<AuthHandlers>,
+    Json(request): Json<ConfirmDeviceRequest>,
+) -> Result<Json<ApiResponse<String>>> {
+    handlers.services.auth.confirm_device(&request.token).await?;
 
     Ok(Json(ApiResponse::success_with_message(
-        "Logged out successfully".to_string(),
-        "User session has been terminated".to_string(),
+        "Device confirmed".to_string(),
+        "The session has been verified".to_string(),
     )))
 }
+
+/// Begin passkey registration for the current user
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/register/start",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "Credential creation options", body = ApiResponse<PasskeyRegistrationStart>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn start_passkey_registration(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<PasskeyRegistrationStart>>> {
+    let response = handlers
+        .services
+        .auth
+        .start_passkey_registration(&auth_context)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Complete passkey registration
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/register/finish",
+    tag = "authentication",
+    request_body = PasskeyRegistrationFinish,
+    responses(
+        (status = 201, description = "Passkey registered", body = ApiResponse<PasskeyInfo>),
+        (status = 400, description = "Invalid or expired challenge"),
+        (status = 401, description = "Verification failed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn finish_passkey_registration(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<PasskeyRegistrationFinish>,
+) -> Result<(StatusCode, Json<ApiResponse<PasskeyInfo>>)> {
+    let passkey = handlers
+        .services
+        .auth
+        .finish_passkey_registration(&auth_context, request)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success_with_message(
+            passkey,
+            "Passkey registered successfully".to_string(),
+        )),
+    ))
+}
+
+/// Begin passkey login
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/login/start",
+    tag = "authentication",
+    request_body = PasskeyLoginStartRequest,
+    responses(
+        (status = 200, description = "Credential request options", body = ApiResponse<PasskeyLoginStart>)
+    )
+)]
+pub async fn start_passkey_login(
+    State(handlers): State<AuthHandlers>,
+    Json(request): Json<PasskeyLoginStartRequest>,
+) -> Result<Json<ApiResponse<PasskeyLoginStart>>> {
+    let response = handlers.services.auth.start_passkey_login(request).await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Complete passkey login
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/webauthn/login/finish",
+    tag = "authentication",
+    request_body = PasskeyLoginFinish,
+    responses(
+        (status = 200, description = "Login successful", body = ApiResponse<LoginResponse>),
+        (status = 401, description = "Invalid passkey")
+    )
+)]
+pub async fn finish_passkey_login(
+    State(handlers): State<AuthHandlers>,
+    context: RequestContext,
+    Json(request): Json<PasskeyLoginFinish>,
+) -> Result<(HeaderMap, Json<ApiResponse<LoginResponse>>)> {
+    let mut response = handlers
+        .services
+        .auth
+        .finish_passkey_login(request, SessionMetadata::from(&context))
+        .await?;
+
+    Ok((handlers.issue(&mut response), Json(ApiResponse::success(response))))
+}
+
+/// List the current user's active sessions
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/sessions",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "Active sessions, newest first", body = ApiResponse<Vec<SessionInfo>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_sessions(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<Vec<SessionInfo>>>> {
+    let sessions = handlers.services.auth.list_sessions(&auth_context).await?;
+
+    Ok(Json(ApiResponse::success(sessions)))
+}
+
+/// Sign out one of the current user's sessions
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/sessions/{id}",
+    tag = "authentication",
+    params(
+        ("id" = Uuid, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "Session not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_session(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    handlers
+        .services
+        .auth
+        .revoke_session(&auth_context, id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the current user's passkeys
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/webauthn/credentials",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "Registered passkeys", body = ApiResponse<Vec<PasskeyInfo>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_passkeys(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<Vec<PasskeyInfo>>>> {
+    let passkeys = handlers.services.auth.list_passkeys(auth_context.user_id).await?;
+
+    Ok(Json(ApiResponse::success(passkeys)))
+}
+
+/// Remove one of the current user's passkeys
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/webauthn/credentials/{id}",
+    tag = "authentication",
+    params(
+        ("id" = Uuid, Path, description = "Passkey ID")
+    ),
+    responses(
+        (status = 204, description = "Passkey removed"),
+        (status = 404, description = "Passkey not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_passkey(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    handlers
+        .services
+        .auth
+        .delete_passkey(&auth_context, id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Create a personal access token for the current user
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/tokens",
+    tag = "authentication",
+    request_body = CreatePersonalAccessTokenRequest,
+    responses(
+        (status = 201, description = "Token created; it is only shown in this response", body = ApiResponse<CreatedPersonalAccessToken>),
+        (status = 400, description = "Invalid name, roles, scopes or expiry"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_personal_access_token(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<CreatePersonalAccessTokenRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedPersonalAccessToken>>)> {
+    let token = handlers
+        .services
+        .auth
+        .create_personal_access_token(&auth_context, request)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(token))))
+}
+
+/// List the current user's personal access tokens
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/tokens",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "Tokens that haven't been revoked, newest first", body = ApiResponse<Vec<PersonalAccessTokenInfo>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_personal_access_tokens(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<Vec<PersonalAccessTokenInfo>>>> {
+    let tokens = handlers
+        .services
+        .auth
+        .list_personal_access_tokens(auth_context.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(tokens)))
+}
+
+/// Revoke one of the current user's personal access tokens
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/tokens/{id}",
+    tag = "authentication",
+    params(
+        ("id" = Uuid, Path, description = "Token ID")
+    ),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "Token not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_personal_access_token(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    handlers
+        .services
+        .auth
+        .revoke_personal_access_token(&auth_context, id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Issue a short-lived access token to a service account (client-credentials grant)
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/token",
+    tag = "authentication",
+    request_body = ClientCredentialsRequest,
+    responses(
+        (status = 200, description = "Token issued", body = ApiResponse<ClientCredentialsResponse>),
+        (status = 400, description = "Unsupported grant type"),
+        (status = 401, description = "Unknown or disabled client, or wrong secret"),
+        (status = 429, description = "Too many attempts")
+    )
+)]
+pub async fn client_credentials_token(
+    State(handlers): State<AuthHandlers>,
+    Json(request): Json<ClientCredentialsRequest>,
+) -> Result<Json<ApiResponse<ClientCredentialsResponse>>> {
+    let response = handlers.services.auth.client_credentials(request).await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Report whether an access token is active, with its claims and session
+/// status, so other services can validate tokens without the signing secret
+/// (service accounts only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/introspect",
+    tag = "authentication",
+    request_body = IntrospectionRequest,
+    responses(
+        (status = 200, description = "Token state; inactive tokens carry no claims", body = ApiResponse<IntrospectionResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not a service account")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn introspect_token(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<IntrospectionRequest>,
+) -> Result<Json<ApiResponse<IntrospectionResponse>>> {
+    let response = handlers
+        .services
+        .auth
+        .introspect(&auth_context, &request.token)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Create a service account (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/service-accounts",
+    tag = "admin",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 201, description = "Account created; the client secret is only shown in this response", body = ApiResponse<CreatedServiceAccount>),
+        (status = 400, description = "Invalid name or unknown role"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_service_account(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<CreateServiceAccountRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedServiceAccount>>)> {
+    let account = handlers
+        .services
+        .auth
+        .create_service_account(&auth_context, request)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(account))))
+}
+
+/// List service accounts (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/service-accounts",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Service accounts, newest first", body = ApiResponse<Vec<ServiceAccountInfo>>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_service_accounts(
+    State(handlers): State<AuthHandlers>,
+) -> Result<Json<ApiResponse<Vec<ServiceAccountInfo>>>> {
+    let accounts = handlers.services.auth.list_service_accounts().await?;
+
+    Ok(Json(ApiResponse::success(accounts)))
+}
+
+/// Disable a service account so it can't get new tokens (admin only)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/service-accounts/{id}",
+    tag = "admin",
+    params(
+        ("id" = Uuid, Path, description = "Service account ID")
+    ),
+    responses(
+        (status = 204, description = "Account disabled"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Service account not found or already disabled")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_service_account(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    handlers
+        .services
+        .auth
+        .disable_service_account(&auth_context, id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revoke sessions in bulk, e.g. after a secret leaks (admin only)
+///
+/// With no filters every session is revoked and the token version is bumped,
+/// so every access token issued so far stops working, the caller's included.
+/// Filtering by tenant or creation time revokes only the matching sessions.
+/// A leaked signing key also has to be rotated in `auth.jwt_keys`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/sessions/revoke-all",
+    tag = "admin",
+    request_body = RevokeAllSessionsRequest,
+    responses(
+        (status = 200, description = "Sessions revoked", body = ApiResponse<RevokeAllSessionsResponse>),
+        (status = 400, description = "Invalid tenant"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_all_sessions(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<RevokeAllSessionsRequest>,
+) -> Result<Json<ApiResponse<RevokeAllSessionsResponse>>> {
+    let response = handlers
+        .services
+        .auth
+        .revoke_all_sessions(&auth_context, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuthorizationModelListParams {
+    #[param(example = 20, minimum = 1, maximum = 100)]
+    pub page_size: Option<u32>,
+    /// Token from the previous page
+    pub continuation_token: Option<String>,
+}
+
+impl Validate for AuthorizationModelListParams {
+    fn validate(&self) -> Result<()> {
+        validate_range("page_size", self.page_size, 1, 100)
+    }
+}
+
+/// List the OpenFGA store's authorization models, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/authorization-models",
+    tag = "admin",
+    params(AuthorizationModelListParams),
+    responses(
+        (status = 200, description = "Authorization models", body = ApiResponse<AuthorizationModelPage>),
+        (status = 400, description = "Invalid page size"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_authorization_models(
+    State(handlers): State<AuthHandlers>,
+    ValidatedQuery(params): ValidatedQuery<AuthorizationModelListParams>,
+) -> Result<Json<ApiResponse<AuthorizationModelPage>>> {
+    let page = handlers
+        .openfga_service
+        .list_authorization_models(
+            params.page_size,
+            params.continuation_token.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(page)))
+}
+
+/// Get one authorization model
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/authorization-models/{id}",
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "Authorization model ID")
+    ),
+    responses(
+        (status = 200, description = "Authorization model", body = ApiResponse<AuthorizationModel>),
+        (status = 404, description = "Authorization model not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_authorization_model(
+    State(handlers): State<AuthHandlers>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<AuthorizationModel>>> {
+    let model = handlers.openfga_service.read_authorization_model(&id).await?;
+
+    Ok(Json(ApiResponse::success(model)))
+}
+
+/// Upload a new authorization model (JSON form; convert DSL with `fga model transform`)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/authorization-models",
+    tag = "admin",
+    request_body = AuthorizationModel,
+    responses(
+        (status = 201, description = "Model written", body = ApiResponse<WriteAuthorizationModelResponse>),
+        (status = 400, description = "Invalid authorization model"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn write_authorization_model(
+    State(handlers): State<AuthHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(model): Json<AuthorizationModel>,
+) -> Result<(StatusCode, Json<ApiResponse<WriteAuthorizationModelResponse>>)> {
+    let authorization_model_id = handlers
+        .openfga_service
+        .write_authorization_model(&model)
+        .await?;
+
+    handlers
+        .services
+        .audit
+        .record_best_effort(
+            AuditEvent::new(
+                auth_context.tenant(),
+                Some(auth_context.user_id),
+                audit_actions::AUTHORIZATION_MODEL_WRITTEN,
+            )
+            .with_resource(audit_resources::AUTHORIZATION_MODEL, &authorization_model_id)
+            .with_change(None::<&AuthorizationModel>, Some(&model)),
+        )
+        .await;
+
+    tracing::info!(
+        admin = %auth_context.user_id,
+        authorization_model_id = %authorization_model_id,
+        "Authorization model uploaded"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(WriteAuthorizationModelResponse {
+            authorization_model_id,
+        })),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AccessReportParams {
+    /// Only objects of this type; all types by default
+    #[serde(rename = "type")]
+    #[param(example = "document")]
+    pub object_type: Option<String>,
+}
+
+/// What a user can access
+///
+/// Lists every object the user holds a relation on, including relations the
+/// model derives, and marks each as direct, implied by another relation on
+/// the object, or inherited from a parent object. For support and security
+/// reviews.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}/access",
+    tag = "admin",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        AccessReportParams
+    ),
+    responses(
+        (status = 200, description = "Access report", body = ApiResponse<UserAccessReport>),
+        (status = 400, description = "Unknown object type"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user_access(
+    State(handlers): State<AuthHandlers>,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<AccessReportParams>,
+) -> Result<Json<ApiResponse<UserAccessReport>>> {
+    let report = handlers
+        .services
+        .auth
+        .access_report(user_id, params.object_type.as_deref().filter(|t| !t.is_empty()))
+        .await?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RelationshipQueryParams {
+    /// `user:<id>`, or a bare user ID
+    pub user: Option<String>,
+    #[param(example = "viewer")]
+    pub relation: Option<String>,
+    /// `type:id`, or `type:` for every object of a type
+    #[param(example = "document:")]
+    pub object: Option<String>,
+    #[param(example = 50, minimum = 1, maximum = 100)]
+    pub page_size: Option<u32>,
+    /// Token from the previous page
+    pub continuation_token: Option<String>,
+}
+
+impl Validate for RelationshipQueryParams {
+    fn validate(&self) -> Result<()> {
+        validate_range("page_size", self.page_size, 1, 100)
+    }
+}
+
+/// Inspect stored relationship tuples for a user or object
+///
+/// Shows direct tuples only; relations the model derives from them (e.g. an
+/// editor also being a viewer) aren't listed.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/relationships",
+    tag = "admin",
+    params(RelationshipQueryParams),
+    responses(
+        (status = 200, description = "Matching tuples", body = ApiResponse<TuplePage>),
+        (status = 400, description = "Filter needs an object type, or invalid page size"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_relationships(
+    State(handlers): State<AuthHandlers>,
+    ValidatedQuery(params): ValidatedQuery<RelationshipQueryParams>,
+) -> Result<Json<ApiResponse<TuplePage>>> {
+    let user = params.user.filter(|user| !user.is_empty()).map(|user| {
+        match Uuid::parse_str(&user) {
+            Ok(user_id) => format!("user:{}", user_id),
+            Err(_) => user,
+        }
+    });
+    let object = params.object.filter(|object| !object.is_empty());
+
+    if (user.is_some() || params.relation.is_some()) && object.is_none() {
+        return Err(AppError::Validation(
+            "Filtering by user or relation requires an object or object type (e.g. `document:`)"
+                .to_string(),
+        ));
+    }
+
+    let filter = TupleFilter {
+        user,
+        relation: params.relation.filter(|relation| !relation.is_empty()),
+        object,
+    };
+    let page = handlers
+        .openfga_service
+        .read_tuples(
+            &filter,
+            params.page_size,
+            params.continuation_token.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(page)))
+}
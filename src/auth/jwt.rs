@@ -1,24 +1,105 @@
-use crate::auth::models::{AuthContext, Claims};
+use crate::auth::keys::JwtKey;
+use crate::auth::models::{AuthContext, Claims, SubjectType, UserTokenState};
 use crate::config::Config;
 use crate::errors::{AppError, Result};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Optional claims that tie a token to a session, tenant or scopes, and
+/// what the token is for when it isn't a user's
+#[derive(Default)]
+struct TokenBinding {
+    session_id: Option<Uuid>,
+    tenant: Option<String>,
+    scope: Option<String>,
+    subject_type: SubjectType,
+    /// The subject's own token version (`uver`)
+    user_version: Option<i64>,
+    roles_updated_at: Option<i64>,
+    /// Overrides `jwt_expiration_hours`
+    lifetime: Option<Duration>,
+}
+
 #[derive(Clone)]
 pub struct JwtService {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
+    /// Asymmetric keys by `kid`; tokens without a `kid` use the shared secret
+    keys: Vec<JwtKey>,
+    /// Index into `keys` of the key signing new tokens
+    signing_key: Option<usize>,
+    /// With a signing key, when tokens without a `kid` stop being accepted
+    shared_secret_until: Option<DateTime<Utc>>,
     expiration_hours: u64,
+    refresh_expiration_days: u64,
+    service_token_seconds: u64,
+    issuer: String,
+    audience: String,
+    require_issuer_audience: bool,
+    /// Stamped on new tokens as `ver`; kept current by
+    /// [`crate::auth::session::SessionValidator::with_token_version`]
+    token_version: Arc<AtomicI64>,
 }
 
 impl JwtService {
-    pub fn new(config: &Config) -> Self {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
         let secret = config.auth.jwt_secret.as_bytes();
-        Self {
+        let keys = config
+            .auth
+            .jwt_keys
+            .iter()
+            .map(JwtKey::from_config)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let signing_key = match &config.auth.jwt_active_kid {
+            Some(kid) => {
+                let index = keys
+                    .iter()
+                    .position(|key| key.kid() == kid)
+                    .ok_or_else(|| anyhow::anyhow!("Active JWT key '{}' is not configured", kid))?;
+                if !keys[index].can_sign() {
+                    anyhow::bail!("Active JWT key '{}' has no private key", kid);
+                }
+                Some(index)
+            }
+            None => keys.iter().position(JwtKey::can_sign),
+        };
+
+        Ok(Self {
             encoding_key: EncodingKey::from_secret(secret),
             decoding_key: DecodingKey::from_secret(secret),
+            keys,
+            signing_key,
+            shared_secret_until: config.auth.jwt_accept_shared_secret_until,
             expiration_hours: config.auth.jwt_expiration_hours,
+            refresh_expiration_days: config.auth.refresh_token_expiration_days,
+            service_token_seconds: config.auth.service_accounts.token_ttl_seconds,
+            issuer: config.auth.jwt_issuer.clone(),
+            audience: config.auth.jwt_audience.clone(),
+            require_issuer_audience: config.auth.jwt_require_issuer_audience,
+            token_version: Arc::new(AtomicI64::new(0)),
+        })
+    }
+
+    /// Token version new tokens are issued with
+    pub fn token_version(&self) -> i64 {
+        self.token_version.load(Ordering::Relaxed)
+    }
+
+    pub fn set_token_version(&self, version: i64) {
+        self.token_version.store(version, Ordering::Relaxed);
+    }
+
+    /// Public keys for other services to validate our tokens
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().map(|key| key.jwk().clone()).collect(),
         }
     }
 
@@ -29,9 +110,154 @@ impl JwtService {
         email: String,
         username: String,
         roles: Vec<String>,
+    ) -> Result<String> {
+        self.encode_token(user_id, email, username, roles, TokenBinding::default())
+    }
+
+    /// Generate a JWT token bound to a server-side session (`sid` claim),
+    /// stamped with the user's token state (`uver` and `roles_updated_at`)
+    pub fn generate_session_token(
+        &self,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        roles: Vec<String>,
+        session_id: Uuid,
+        state: UserTokenState,
+    ) -> Result<String> {
+        self.encode_token(
+            user_id,
+            email,
+            username,
+            roles,
+            TokenBinding {
+                session_id: Some(session_id),
+                user_version: Some(state.token_version),
+                roles_updated_at: state.roles_updated_at.map(|at| at.timestamp()),
+                ..TokenBinding::default()
+            },
+        )
+    }
+
+    /// Generate a session token that acts in `tenant` (`tenant` claim)
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_tenant_token(
+        &self,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        roles: Vec<String>,
+        session_id: Uuid,
+        tenant: String,
+        state: UserTokenState,
+    ) -> Result<String> {
+        self.encode_token(
+            user_id,
+            email,
+            username,
+            roles,
+            TokenBinding {
+                session_id: Some(session_id),
+                tenant: Some(tenant),
+                user_version: Some(state.token_version),
+                roles_updated_at: state.roles_updated_at.map(|at| at.timestamp()),
+                ..TokenBinding::default()
+            },
+        )
+    }
+
+    /// Generate a token restricted to `scopes`, e.g. for a third-party
+    /// integration; endpoints guarded by `require_scope` reject it unless
+    /// their scope is listed
+    pub fn generate_scoped_token(
+        &self,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        roles: Vec<String>,
+        scopes: &[&str],
+    ) -> Result<String> {
+        if let Some(scope) = scopes
+            .iter()
+            .find(|scope| scope.is_empty() || scope.contains(char::is_whitespace))
+        {
+            return Err(AppError::Validation(format!("Invalid scope '{}'", scope)));
+        }
+
+        self.encode_token(
+            user_id,
+            email,
+            username,
+            roles,
+            TokenBinding {
+                scope: Some(scopes.join(" ")),
+                ..TokenBinding::default()
+            },
+        )
+    }
+
+    /// Generate a short-lived token for a service account
+    /// (`sub_type: service`); it has no session, so disabling the account
+    /// bumps its token version (`uver`) to end it early
+    pub fn generate_service_token(
+        &self,
+        service_account_id: Uuid,
+        name: String,
+        roles: Vec<String>,
+        account_version: i64,
+    ) -> Result<String> {
+        self.encode_token(
+            service_account_id,
+            String::new(),
+            name,
+            roles,
+            TokenBinding {
+                subject_type: SubjectType::Service,
+                user_version: Some(account_version),
+                lifetime: Some(Duration::seconds(self.service_token_seconds as i64)),
+                ..TokenBinding::default()
+            },
+        )
+    }
+
+    /// Service account token lifetime in seconds
+    pub fn service_expires_in(&self) -> u64 {
+        self.service_token_seconds
+    }
+
+    /// Token lifetime in seconds
+    pub fn expires_in(&self) -> u64 {
+        self.expiration_hours * 3600
+    }
+
+    /// Refresh token (session family) lifetime in seconds
+    pub fn refresh_expires_in(&self) -> u64 {
+        self.refresh_expiration_days * 86400
+    }
+
+    /// Generate an opaque token (refresh, password reset); only its hash is stored
+    pub fn generate_opaque_token() -> String {
+        URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+    }
+
+    /// Hash an opaque token for storage and lookup
+    pub fn hash_opaque_token(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.as_bytes()))
+    }
+
+    fn encode_token(
+        &self,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        roles: Vec<String>,
+        binding: TokenBinding,
     ) -> Result<String> {
         let now = Utc::now();
-        let expiration = now + Duration::hours(self.expiration_hours as i64);
+        let expiration = now
+            + binding
+                .lifetime
+                .unwrap_or_else(|| Duration::hours(self.expiration_hours as i64));
 
         let claims = Claims {
             sub: user_id.to_string(),
@@ -40,34 +266,112 @@ impl JwtService {
             roles,
             exp: expiration.timestamp() as usize,
             iat: now.timestamp() as usize,
+            sid: binding.session_id.map(|id| id.to_string()),
+            tenant: binding.tenant,
+            scope: binding.scope,
+            sub_type: (binding.subject_type != SubjectType::User)
+                .then(|| binding.subject_type.as_str().to_string()),
+            iss: Some(self.issuer.clone()),
+            aud: Some(self.audience.clone()),
+            ver: Some(self.token_version()),
+            uver: binding.user_version,
+            roles_updated_at: binding.roles_updated_at,
+        };
+
+        let encoded = match self.signing_key.map(|index| &self.keys[index]) {
+            Some(key) => {
+                let mut header = Header::new(key.algorithm());
+                header.kid = Some(key.kid().to_string());
+                // signing_key only ever points at a key that can sign
+                encode(&header, &claims, key.encoding_key().expect("signing key"))
+            }
+            None => encode(&Header::default(), &claims, &self.encoding_key),
         };
 
-        encode(&Header::default(), &claims, &self.encoding_key)
-            .map_err(|e| AppError::Authentication(format!("Failed to generate token: {}", e)))
+        encoded.map_err(|e| AppError::Authentication(format!("Failed to generate token: {}", e)))
     }
 
     /// Validate and decode a JWT token
     pub fn validate_token(&self, token: &str) -> Result<Claims> {
-        let mut validation = Validation::new(Algorithm::HS256);
+        let header = decode_header(token)
+            .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))?;
+
+        let (algorithm, decoding_key) = match header.kid.as_deref() {
+            Some(kid) => {
+                let key = self
+                    .keys
+                    .iter()
+                    .find(|key| key.kid() == kid)
+                    .ok_or_else(|| AppError::Authentication("Unknown token signing key".to_string()))?;
+                (key.algorithm(), key.decoding_key())
+            }
+            None if self.accepts_shared_secret() => (Algorithm::HS256, &self.decoding_key),
+            // Anyone holding jwt_secret could otherwise still mint tokens
+            None => {
+                return Err(AppError::Authentication(
+                    "Token has no signing key id".to_string(),
+                ))
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
         validation.validate_exp = true;
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        if self.require_issuer_audience {
+            validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        }
 
-        decode::<Claims>(token, &self.decoding_key, &validation)
+        decode::<Claims>(token, decoding_key, &validation)
             .map(|data| data.claims)
             .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))
     }
 
+    /// Whether tokens signed with the shared secret are still valid: always
+    /// without a signing key, and during the configured grace period after
+    /// switching to one
+    fn accepts_shared_secret(&self) -> bool {
+        self.signing_key.is_none()
+            || self
+                .shared_secret_until
+                .is_some_and(|until| Utc::now() < until)
+    }
+
     /// Extract auth context from token
     pub fn extract_auth_context(&self, token: &str) -> Result<AuthContext> {
-        let claims = self.validate_token(token)?;
-        
+        self.auth_context(self.validate_token(token)?)
+    }
+
+    /// Auth context for the claims of a validated token
+    pub fn auth_context(&self, claims: Claims) -> Result<AuthContext> {
         let user_id = Uuid::parse_str(&claims.sub)
             .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
 
+        let session_id = claims
+            .sid
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| AppError::Authentication("Invalid session ID in token".to_string()))?;
+
+        let subject_type = match claims.sub_type.as_deref() {
+            None => SubjectType::User,
+            Some(sub_type) => SubjectType::parse(sub_type).ok_or_else(|| {
+                AppError::Authentication("Invalid subject type in token".to_string())
+            })?,
+        };
+
         Ok(AuthContext {
             user_id,
             email: claims.email,
             username: claims.username,
             roles: claims.roles,
+            session_id,
+            tenant_id: claims.tenant,
+            scopes: claims
+                .scope
+                .map(|scope| scope.split_whitespace().map(str::to_string).collect()),
+            subject_type,
         })
     }
 
@@ -0,0 +1,165 @@
+use crate::config::JwtKeyConfig;
+use anyhow::Context;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters, Jwk,
+    KeyAlgorithm, PublicKeyUse, RSAKeyParameters,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+/// An asymmetric JWT key identified by `kid`
+///
+/// Keys loaded from a public PEM only verify tokens; they stay in the JWKS
+/// during rotation so tokens signed before the switch keep validating.
+#[derive(Clone)]
+pub struct JwtKey {
+    kid: String,
+    algorithm: Algorithm,
+    encoding_key: Option<EncodingKey>,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl JwtKey {
+    pub fn from_config(config: &JwtKeyConfig) -> anyhow::Result<Self> {
+        let pem = match (&config.pem, &config.pem_file) {
+            (Some(pem), _) => pem.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read JWT key file {}", path))?,
+            (None, None) => anyhow::bail!("JWT key '{}' needs `pem` or `pem_file`", config.kid),
+        };
+
+        let algorithm = match config.algorithm.as_str() {
+            "RS256" => Algorithm::RS256,
+            "ES256" => Algorithm::ES256,
+            other => anyhow::bail!("Unsupported JWT key algorithm '{}' for '{}'", other, config.kid),
+        };
+
+        Self::from_pem(&config.kid, algorithm, &pem)
+            .with_context(|| format!("Invalid JWT key '{}'", config.kid))
+    }
+
+    /// Load a PKCS#8 / PKCS#1 / SEC1 private key or an SPKI public key
+    pub fn from_pem(kid: &str, algorithm: Algorithm, pem: &str) -> anyhow::Result<Self> {
+        let (encoding_key, params) = match algorithm {
+            Algorithm::RS256 => rsa_key(pem)?,
+            Algorithm::ES256 => ec_key(pem)?,
+            other => anyhow::bail!("Unsupported JWT key algorithm {:?}", other),
+        };
+
+        let jwk = Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(match algorithm {
+                    Algorithm::RS256 => KeyAlgorithm::RS256,
+                    _ => KeyAlgorithm::ES256,
+                }),
+                key_id: Some(kid.to_string()),
+                ..CommonParameters::default()
+            },
+            algorithm: params,
+        };
+        let decoding_key = DecodingKey::from_jwk(&jwk)?;
+
+        Ok(Self {
+            kid: kid.to_string(),
+            algorithm,
+            encoding_key,
+            decoding_key,
+            jwk,
+        })
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn can_sign(&self) -> bool {
+        self.encoding_key.is_some()
+    }
+
+    pub fn encoding_key(&self) -> Option<&EncodingKey> {
+        self.encoding_key.as_ref()
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+
+    /// Public half as a JWK
+    pub fn jwk(&self) -> &Jwk {
+        &self.jwk
+    }
+}
+
+fn rsa_key(pem: &str) -> anyhow::Result<(Option<EncodingKey>, AlgorithmParameters)> {
+    let private = RsaPrivateKey::from_pkcs8_pem(pem).or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem));
+
+    let (encoding_key, public) = match private {
+        Ok(private) => {
+            let der = private.to_pkcs1_der()?;
+            (
+                Some(EncodingKey::from_rsa_der(der.as_bytes())),
+                private.to_public_key(),
+            )
+        }
+        Err(_) => {
+            let public = RsaPublicKey::from_public_key_pem(pem)
+                .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+                .context("not an RSA private or public key")?;
+            (None, public)
+        }
+    };
+
+    Ok((
+        encoding_key,
+        AlgorithmParameters::RSA(RSAKeyParameters {
+            n: URL_SAFE_NO_PAD.encode(public.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public.e().to_bytes_be()),
+            ..RSAKeyParameters::default()
+        }),
+    ))
+}
+
+fn ec_key(pem: &str) -> anyhow::Result<(Option<EncodingKey>, AlgorithmParameters)> {
+    let secret = p256::SecretKey::from_pkcs8_pem(pem).or_else(|_| p256::SecretKey::from_sec1_pem(pem));
+
+    let (encoding_key, public) = match secret {
+        Ok(secret) => {
+            // ring only accepts PKCS#8 for ECDSA signing keys
+            let der = secret.to_pkcs8_der()?;
+            (
+                Some(EncodingKey::from_ec_der(der.as_bytes())),
+                secret.public_key(),
+            )
+        }
+        Err(_) => (
+            None,
+            p256::PublicKey::from_public_key_pem(pem).context("not a P-256 private or public key")?,
+        ),
+    };
+
+    let point = public.to_encoded_point(false);
+    let (Some(x), Some(y)) = (point.x(), point.y()) else {
+        anyhow::bail!("P-256 public key is the identity point");
+    };
+
+    Ok((
+        encoding_key,
+        AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+            curve: EllipticCurve::P256,
+            x: URL_SAFE_NO_PAD.encode(x),
+            y: URL_SAFE_NO_PAD.encode(y),
+            ..EllipticCurveKeyParameters::default()
+        }),
+    ))
+}
@@ -1,78 +1,223 @@
+use crate::auth::authenticator::{
+    self, Authenticated, Authenticator, AuthenticatorChains, CookieAuthenticator,
+    JwtAuthenticator, MtlsAuthenticator, PersonalTokenAuthenticator,
+};
+use crate::auth::authorizer::Authorizer;
+use crate::auth::canary::AuthorizationCanary;
+use crate::auth::cookies::SessionCookies;
 use crate::auth::jwt::JwtService;
-use crate::auth::models::AuthContext;
-use crate::auth::openfga::OpenFgaService;
+use crate::auth::models::{AuthContext, SubjectType};
+use crate::auth::registry;
+use crate::auth::session::SessionValidator;
+use crate::auth::stores;
+use crate::config::AuthenticationConfig;
 use crate::errors::AppError;
+use crate::metrics::RouteGroups;
+use crate::models::format::ResponseFormat;
+use crate::models::UserStatus;
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{FromRequestParts, MatchedPath, RawPathParams, Request, State},
+    http::{request::Parts, HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
 };
 use std::sync::Arc;
 
-/// Authentication middleware that validates JWT tokens
+/// The boxed future returned by the middleware built in this module
+type MiddlewareFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, (StatusCode, String)>> + Send>>;
+
+/// Body of the 401 for a token issued before the user's roles last changed;
+/// clients refresh the token and retry rather than signing the user out
+pub const TOKEN_REFRESH_REQUIRED: &str = "token_refresh_required";
+
+/// State shared by the authentication middlewares
+#[derive(Clone)]
+pub struct AuthState {
+    pub jwt_service: Arc<JwtService>,
+    pub sessions: Arc<SessionValidator>,
+    pub cookies: Option<Arc<SessionCookies>>,
+    authentication: AuthenticationConfig,
+    route_groups: RouteGroups,
+    /// Authenticators registered beside the built-in ones
+    custom: Vec<Arc<dyn Authenticator>>,
+    chains: Arc<AuthenticatorChains>,
+}
+
+impl AuthState {
+    /// JWTs and personal access tokens on every route, until
+    /// [`with_authentication`](Self::with_authentication) says otherwise
+    pub fn new(jwt_service: Arc<JwtService>, sessions: Arc<SessionValidator>) -> Self {
+        let mut state = Self {
+            jwt_service,
+            sessions,
+            cookies: None,
+            authentication: AuthenticationConfig::default(),
+            route_groups: RouteGroups::default(),
+            custom: Vec::new(),
+            chains: Arc::default(),
+        };
+        state.rebuild();
+        state
+    }
+
+    /// Accept the access cookie from requests without an `Authorization`
+    /// header
+    pub fn with_cookies(mut self, cookies: Arc<SessionCookies>) -> Self {
+        self.cookies = Some(cookies);
+        self.rebuild();
+        self
+    }
+
+    /// Authenticate each route group with the chain `config` gives it
+    ///
+    /// Fails on chains naming an authenticator that isn't built in or
+    /// registered with [`with_authenticator`](Self::with_authenticator)
+    /// before.
+    pub fn with_authentication(
+        mut self,
+        config: &AuthenticationConfig,
+        route_groups: RouteGroups,
+    ) -> anyhow::Result<Self> {
+        let custom: Vec<_> = self.custom.iter().map(|authenticator| authenticator.name()).collect();
+        authenticator::validate_config(config, &custom)?;
+
+        self.authentication = config.clone();
+        self.route_groups = route_groups;
+        self.rebuild();
+        Ok(self)
+    }
+
+    /// Make another authenticator available to the chains by its name,
+    /// replacing a built-in one of the same name
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.custom.retain(|custom| custom.name() != authenticator.name());
+        self.custom.push(authenticator);
+        self.rebuild();
+        self
+    }
+
+    fn rebuild(&mut self) {
+        // Registered ones come first, so one can replace a built-in one of
+        // the same name
+        let mut available = self.custom.clone();
+        let jwt = JwtAuthenticator::new(self.jwt_service.clone(), self.sessions.clone());
+        available.push(Arc::new(jwt.clone()));
+        available.push(Arc::new(PersonalTokenAuthenticator::new(self.sessions.clone())));
+        if let Some(cookies) = &self.cookies {
+            available.push(Arc::new(CookieAuthenticator::new(cookies.clone(), jwt)));
+        }
+        if let Some(mtls) =
+            MtlsAuthenticator::from_config(&self.authentication.mtls, self.sessions.clone())
+        {
+            available.push(Arc::new(mtls));
+        }
+
+        self.chains = Arc::new(AuthenticatorChains::new(
+            &self.authentication,
+            self.route_groups.clone(),
+            &available,
+        ));
+    }
+
+    /// Authenticate `request` with its route group's chain
+    ///
+    /// Users who aren't active are refused with a 403 whatever credential
+    /// they bring, and users who no longer exist with a 401.
+    ///
+    /// Takes the request's parts rather than the request, whose body isn't
+    /// `Sync`, so the middleware futures stay `Send`.
+    async fn authenticate(&self, parts: &Parts) -> Result<Authenticated, (StatusCode, String)> {
+        let route = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map(|path| path.as_str())
+            .unwrap_or_else(|| parts.uri.path());
+        let chain = self.chains.chain(route);
+
+        let authenticated = authenticator::authenticate(chain, &parts.method, &parts.headers).await?;
+
+        let context = &authenticated.context;
+        if context.subject_type == SubjectType::User {
+            match self.sessions.user_status(context.user_id).await {
+                Some(UserStatus::Active) => {}
+                Some(status) => {
+                    return Err((StatusCode::FORBIDDEN, format!("Account is {}", status)));
+                }
+                None => return Err((StatusCode::UNAUTHORIZED, "Account not found".to_string())),
+            }
+        }
+
+        Ok(authenticated)
+    }
+}
+
+/// Authentication middleware running the route group's authenticator chain
 pub async fn auth_middleware(
-    State(jwt_service): State<Arc<JwtService>>,
-    mut request: Request,
+    State(auth_state): State<AuthState>,
+    request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, String)> {
-    let headers = request.headers();
-    
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| {
-            (
-                StatusCode::UNAUTHORIZED,
-                "Missing authorization header".to_string(),
-            )
-        })?;
+    let (parts, body) = request.into_parts();
+    let Authenticated {
+        context: auth_context,
+        response_format,
+    } = auth_state.authenticate(&parts).await?;
+    let mut request = Request::from_parts(parts, body);
 
-    let token = JwtService::extract_token_from_header(auth_header).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            format!("Invalid authorization header: {}", e),
-        )
-    })?;
+    // Add auth context to request extensions
+    request.extensions_mut().insert(auth_context.clone());
 
-    let auth_context = jwt_service.extract_auth_context(token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            format!("Invalid token: {}", e),
-        )
-    })?;
+    // A format the request asked for explicitly wins over the credential's
+    let response_format =
+        response_format.filter(|_| request.extensions().get::<ResponseFormat>().is_none());
 
-    // Add auth context to request extensions
-    request.extensions_mut().insert(auth_context);
+    // OpenFGA calls go to the store of the tenant the request acts in
+    let tenant = auth_context.tenant().to_string();
+    let run = async move {
+        match response_format {
+            Some(format) => format.scope(next.run(request)).await,
+            None => next.run(request).await,
+        }
+    };
 
-    Ok(next.run(request).await)
+    // Outer layers (usage tracking) identify the caller from the response
+    let mut response = stores::scope(tenant, run).await;
+    response.extensions_mut().insert(auth_context);
+    Ok(response)
 }
 
-/// Optional authentication middleware that doesn't fail if no token is provided
+/// Optional authentication middleware that doesn't fail if no credential is
+/// provided, or an invalid one
 pub async fn optional_auth_middleware(
-    State(jwt_service): State<Arc<JwtService>>,
-    mut request: Request,
+    State(auth_state): State<AuthState>,
+    request: Request,
     next: Next,
 ) -> Response {
-    let headers = request.headers();
-    
-    if let Some(auth_header) = headers.get("authorization").and_then(|h| h.to_str().ok()) {
-        if let Ok(token) = JwtService::extract_token_from_header(auth_header) {
-            if let Ok(auth_context) = jwt_service.extract_auth_context(token) {
-                request.extensions_mut().insert(auth_context);
-            }
-        }
+    let (parts, body) = request.into_parts();
+    let authenticated = auth_state.authenticate(&parts).await;
+    let mut request = Request::from_parts(parts, body);
+
+    if let Ok(authenticated) = authenticated {
+        let auth_context = authenticated.context;
+        let tenant = auth_context.tenant().to_string();
+        request.extensions_mut().insert(auth_context);
+        return stores::scope(tenant, next.run(request)).await;
     }
 
     next.run(request).await
 }
 
 /// Role-based authorization middleware
-pub fn require_role(required_role: &'static str) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, (StatusCode, String)>> + Send>> + Clone {
+///
+/// With an [`AuthorizationCanary`] in the request extensions the decision is
+/// the canary's, per the route group's mode.
+pub fn require_role(required_role: &'static str) -> impl Fn(Request, Next) -> MiddlewareFuture + Clone {
     move |request: Request, next: Next| Box::pin(async move {
         let auth_context = request
             .extensions()
             .get::<AuthContext>()
+            .cloned()
             .ok_or_else(|| {
                 (
                     StatusCode::UNAUTHORIZED,
@@ -80,7 +225,19 @@ pub fn require_role(required_role: &'static str) -> impl Fn(Request, Next) -> st
                 )
             })?;
 
-        if !JwtService::has_role(auth_context, required_role) {
+        let allowed = match request.extensions().get::<Arc<AuthorizationCanary>>().cloned() {
+            Some(canary) => {
+                let route = request
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map(|path| path.as_str().to_string())
+                    .unwrap_or_else(|| request.uri().path().to_string());
+                canary.allows(&route, &auth_context, required_role).await
+            }
+            None => JwtService::has_role(&auth_context, required_role),
+        };
+
+        if !allowed {
             return Err((
                 StatusCode::FORBIDDEN,
                 format!("Required role '{}' not found", required_role),
@@ -91,8 +248,11 @@ pub fn require_role(required_role: &'static str) -> impl Fn(Request, Next) -> st
     })
 }
 
-/// Multiple roles authorization middleware
-pub fn require_any_role(required_roles: &'static [&'static str]) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, (StatusCode, String)>> + Send>> + Clone {
+/// Scope-based authorization middleware
+///
+/// Unscoped tokens pass; a scoped one must list `required_scope`. Combine
+/// with the role checks rather than replacing them.
+pub fn require_scope(required_scope: &'static str) -> impl Fn(Request, Next) -> MiddlewareFuture + Clone {
     move |request: Request, next: Next| Box::pin(async move {
         let auth_context = request
             .extensions()
@@ -104,10 +264,10 @@ pub fn require_any_role(required_roles: &'static [&'static str]) -> impl Fn(Requ
                 )
             })?;
 
-        if !JwtService::has_any_role(auth_context, required_roles) {
+        if !auth_context.has_scope(required_scope) {
             return Err((
                 StatusCode::FORBIDDEN,
-                format!("One of the required roles {:?} not found", required_roles),
+                format!("Required scope '{}' not granted", required_scope),
             ));
         }
 
@@ -115,12 +275,35 @@ pub fn require_any_role(required_roles: &'static [&'static str]) -> impl Fn(Requ
     })
 }
 
-/// Resource-based authorization middleware using OpenFGA
-pub fn require_permission(
-    relation: &'static str,
-    object_type: &'static str,
-) -> impl Fn(State<Arc<OpenFgaService>>, Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, (StatusCode, String)>> + Send>> + Clone {
-    move |State(openfga): State<Arc<OpenFgaService>>, request: Request, next: Next| Box::pin(async move {
+/// Service-account-only middleware, for endpoints other services call with
+/// a client-credentials token
+pub async fn require_service_account(
+    request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    let auth_context = request
+        .extensions()
+        .get::<AuthContext>()
+        .ok_or_else(|| {
+            (
+                StatusCode::UNAUTHORIZED,
+                "Authentication required".to_string(),
+            )
+        })?;
+
+    if auth_context.subject_type != SubjectType::Service {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Service account credentials required".to_string(),
+        ));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Multiple roles authorization middleware
+pub fn require_any_role(required_roles: &'static [&'static str]) -> impl Fn(Request, Next) -> MiddlewareFuture + Clone {
+    move |request: Request, next: Next| Box::pin(async move {
         let auth_context = request
             .extensions()
             .get::<AuthContext>()
@@ -131,18 +314,52 @@ pub fn require_permission(
                 )
             })?;
 
-        // Extract object ID from path parameters
-        // This is a simplified example - in practice, you'd extract this from the request path
-        let object_id = extract_object_id_from_request(&request, object_type)
+        if !JwtService::has_any_role(auth_context, required_roles) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("One of the required roles {:?} not found", required_roles),
+            ));
+        }
+
+        Ok(next.run(request).await)
+    })
+}
+
+/// Resource-based authorization middleware (OpenFGA in production)
+///
+/// The object id is the matched route's `param` path parameter, so
+/// `require_permission("viewer", "document", "id")` on
+/// `/api/v1/documents/{id}` checks `viewer` on `document:{id}`. Apply it with
+/// `Router::layer` after the routes it guards; a route without `param` is a
+/// wiring mistake and fails with a 500.
+pub fn require_permission(
+    relation: &'static str,
+    object_type: &'static str,
+    param: &'static str,
+) -> impl Fn(State<Arc<dyn Authorizer>>, Request, Next) -> MiddlewareFuture + Clone {
+    // Caught while building the routes rather than denying every request
+    if let Err(e) = registry::validate(object_type, relation) {
+        panic!("require_permission({}, {}): {}", relation, object_type, e);
+    }
+
+    move |State(authorizer): State<Arc<dyn Authorizer>>, request: Request, next: Next| Box::pin(async move {
+        let (mut parts, body) = request.into_parts();
+
+        let subject = parts
+            .extensions
+            .get::<AuthContext>()
+            .map(AuthContext::subject)
             .ok_or_else(|| {
                 (
-                    StatusCode::BAD_REQUEST,
-                    "Object ID not found in request".to_string(),
+                    StatusCode::UNAUTHORIZED,
+                    "Authentication required".to_string(),
                 )
             })?;
 
-        let result = openfga
-            .check_permission(auth_context.user_id, relation, object_type, &object_id)
+        let object_id = path_param(&mut parts, param).await?;
+
+        let result = authorizer
+            .check(subject, relation, object_type, &object_id)
             .await
             .map_err(|e| {
                 (
@@ -158,28 +375,26 @@ pub fn require_permission(
             ));
         }
 
-        Ok(next.run(request).await)
+        Ok(next.run(Request::from_parts(parts, body)).await)
     })
 }
 
-/// Extract object ID from request path
-/// This is a helper function that would need to be customized based on your routing structure
-fn extract_object_id_from_request(request: &Request, _object_type: &str) -> Option<String> {
-    // This is a simplified implementation
-    // In practice, you'd parse the request path to extract the object ID
-    // For example, from a path like "/api/v1/users/{id}", extract the {id} part
-    
-    let path = request.uri().path();
-    let segments: Vec<&str> = path.split('/').collect();
-    
-    // Look for UUID-like segments (this is a basic implementation)
-    for segment in segments {
-        if segment.len() == 36 && segment.chars().filter(|&c| c == '-').count() == 4 {
-            return Some(segment.to_string());
-        }
-    }
-    
-    None
+/// The percent-decoded value of a matched route's path parameter
+async fn path_param(parts: &mut Parts, param: &str) -> Result<String, (StatusCode, String)> {
+    let params = RawPathParams::from_request_parts(parts, &())
+        .await
+        .map_err(|rejection| (rejection.status(), rejection.body_text()))?;
+
+    params
+        .iter()
+        .find(|(name, _)| *name == param)
+        .map(|(_, value)| value.to_string())
+        .ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Route has no '{}' path parameter", param),
+            )
+        })
 }
 
 /// Helper function to extract auth context from request
@@ -1,13 +1,29 @@
+pub mod authenticator;
+pub mod authorizer;
+pub mod breaker;
 pub mod cache;
+pub mod canary;
+pub mod changes;
+pub mod cookies;
 pub mod handlers;
 pub mod jwt;
+pub mod keys;
 pub mod middleware;
 pub mod models;
 pub mod openfga;
+pub mod permission;
+pub mod rate_limit;
+pub mod registry;
+pub mod session;
+pub mod stores;
+pub mod transport;
+pub mod webauthn;
 
+pub use authorizer::*;
 pub use cache::*;
 pub use handlers::*;
 pub use jwt::*;
 pub use middleware::*;
 pub use models::*;
 pub use openfga::*;
+pub use permission::*;
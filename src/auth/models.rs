@@ -1,11 +1,17 @@
+use crate::auth::registry::ObjectType;
+use crate::errors::{AppError, Result};
+use crate::models::format;
+use crate::request_context::{self, RequestContext};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::FromRow;
+use std::net::IpAddr;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// JWT Claims structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Claims {
     pub sub: String,        // Subject (user ID)
     pub email: String,      // User email
@@ -13,6 +19,24 @@ pub struct Claims {
     pub roles: Vec<String>, // User roles
     pub exp: usize,         // Expiration time
     pub iat: usize,         // Issued at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>, // Server-side session ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>, // Tenant selected with switch-tenant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>, // Space-separated API scopes; absent means unrestricted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_type: Option<String>, // Subject type, `service` for service accounts; absent means a user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>, // Issuer, `auth.jwt_issuer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>, // Audience, `auth.jwt_audience`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ver: Option<i64>, // Token version at issue; absent counts as 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uver: Option<i64>, // The user's token version at issue; absent counts as 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roles_updated_at: Option<i64>, // When the user's roles last changed, as of issue
 }
 
 /// Authentication context for requests
@@ -22,6 +46,99 @@ pub struct AuthContext {
     pub email: String,
     pub username: String,
     pub roles: Vec<String>,
+    pub session_id: Option<Uuid>,
+    pub tenant_id: Option<String>,
+    /// API scopes the token is restricted to; `None` for an unscoped token
+    pub scopes: Option<Vec<String>>,
+    /// Whether `user_id` is a user or a service account
+    pub subject_type: SubjectType,
+}
+
+impl AuthContext {
+    /// Tenant the request acts in: the token's tenant claim, or the default
+    pub fn tenant(&self) -> &str {
+        self.tenant_id.as_deref().unwrap_or(crate::models::DEFAULT_TENANT)
+    }
+
+    /// Whether the token may call endpoints that require `scope`; roles
+    /// still apply on top
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|granted| granted == scope))
+    }
+
+    /// The caller as an OpenFGA subject
+    pub fn subject(&self) -> Subject {
+        Subject {
+            subject_type: self.subject_type,
+            id: self.user_id,
+        }
+    }
+}
+
+/// Kind of caller a token authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SubjectType {
+    #[default]
+    User,
+    /// A machine caller using the client-credentials grant
+    Service,
+}
+
+impl SubjectType {
+    /// OpenFGA type of the caller, also the token's `sub_type` claim
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            SubjectType::User => ObjectType::User.as_str(),
+            SubjectType::Service => ObjectType::Service.as_str(),
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match ObjectType::parse(value)? {
+            ObjectType::User => Some(SubjectType::User),
+            ObjectType::Service => Some(SubjectType::Service),
+            _ => None,
+        }
+    }
+}
+
+/// The user or service account a permission check is for, written
+/// `user:{id}` or `service:{id}` in OpenFGA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subject {
+    pub subject_type: SubjectType,
+    pub id: Uuid,
+}
+
+impl Subject {
+    pub fn user(id: Uuid) -> Self {
+        Self {
+            subject_type: SubjectType::User,
+            id,
+        }
+    }
+
+    pub fn service(id: Uuid) -> Self {
+        Self {
+            subject_type: SubjectType::Service,
+            id,
+        }
+    }
+}
+
+impl From<Uuid> for Subject {
+    /// A bare id is a user
+    fn from(id: Uuid) -> Self {
+        Subject::user(id)
+    }
+}
+
+impl std::fmt::Display for Subject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.subject_type.as_str(), self.id)
+    }
 }
 
 /// Login request
@@ -31,26 +148,152 @@ pub struct LoginRequest {
     pub email: String,
     #[schema(example = "password123")]
     pub password: String,
+    /// Keep the session alive while it's used, see `auth.remember_me`
+    #[serde(default)]
+    pub remember_me: bool,
 }
 
 /// Login response
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
+    /// Omitted in cookie mode, where it's set as a cookie instead
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub access_token: String,
     pub token_type: String,
     pub expires_in: u64,
+    /// Opaque, single-use token for the refresh grant; omitted in cookie mode
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
+    pub refresh_expires_in: u64,
     pub user: UserInfo,
 }
 
+/// Forgot password request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
+
+/// Reset password request, using the emailed one-time token
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    #[schema(example = "newpassword123")]
+    pub new_password: String,
+}
+
+/// Confirm a sign-in from a new device, using the emailed one-time token
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfirmDeviceRequest {
+    pub token: String,
+}
+
+/// Delete the caller's own account; the password is asked for again
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    #[schema(example = "password123")]
+    pub password: String,
+}
+
+/// Refresh token grant request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    /// May be left out in cookie mode, where the refresh cookie is used
+    #[serde(default)]
+    pub refresh_token: String,
+}
+
 /// User info in auth responses
 #[derive(Debug, Serialize, ToSchema)]
 pub struct UserInfo {
+    #[serde(with = "format::id")]
     pub id: Uuid,
     pub email: String,
     pub username: String,
     pub roles: Vec<String>,
 }
 
+/// The signed-in user and the tenants they can switch to
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CurrentUser {
+    #[serde(flatten)]
+    pub user: UserInfo,
+    /// Tenant the token acts in
+    #[schema(example = "default")]
+    pub tenant: String,
+    /// Organizations the user is a member of
+    #[schema(example = json!(["default", "acme"]))]
+    pub tenants: Vec<String>,
+}
+
+/// How a user came to hold a relation on an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessSource {
+    /// A tuple grants the relation itself
+    Direct,
+    /// Follows from another relation held directly on the object, e.g. an
+    /// owner being a viewer
+    Implied,
+    /// Comes from a role on a parent object, e.g. project admins editing its
+    /// documents
+    Inherited,
+}
+
+/// One relation a user holds on an object
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RelationAccess {
+    #[schema(example = "viewer")]
+    pub relation: String,
+    pub source: AccessSource,
+    /// The directly held relation an implied one follows from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "owner")]
+    pub via: Option<String>,
+}
+
+/// Everything a user can do with one object
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ObjectAccess {
+    #[schema(example = "document:roadmap")]
+    pub object: String,
+    pub relations: Vec<RelationAccess>,
+}
+
+/// What a user can access, for support and security reviews
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserAccessReport {
+    #[serde(with = "format::id")]
+    pub user_id: Uuid,
+    /// Application roles; these gate API routes, not objects
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+    /// Object types that were checked
+    #[schema(example = json!(["organization", "project", "document"]))]
+    pub object_types: Vec<String>,
+    pub objects: Vec<ObjectAccess>,
+}
+
+/// Switch tenant request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SwitchTenantRequest {
+    #[schema(example = "acme")]
+    pub tenant: String,
+}
+
+/// Access token for the selected tenant; the refresh token is unchanged
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SwitchTenantResponse {
+    /// Omitted in cookie mode, where it's set as a cookie instead
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    #[schema(example = "acme")]
+    pub tenant: String,
+}
+
 /// Register request
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterRequest {
@@ -62,6 +305,51 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
+impl RegisterRequest {
+    /// Field checks that don't need the database
+    pub fn validate(&self) -> Result<()> {
+        validate_password(&self.password)?;
+
+        crate::models::CreateUserRequest {
+            email: self.email.clone(),
+            username: self.username.clone(),
+        }
+        .validate()
+    }
+}
+
+/// Password strength rules shared by registration, change and reset
+pub fn validate_password(password: &str) -> Result<()> {
+    if password.len() < 8 {
+        return Err(AppError::Validation(
+            "Password must be at least 8 characters long".to_string(),
+        ));
+    }
+
+    // Add more password validation rules as needed
+    // - Must contain uppercase letter
+    // - Must contain lowercase letter
+    // - Must contain number
+    // - Must contain special character
+
+    Ok(())
+}
+
+/// Hash a password for storage (bcrypt at the default cost)
+pub fn hash_password(password: &str) -> Result<String> {
+    bcrypt::hash(password, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))
+}
+
+/// Check a password against a stored hash
+///
+/// Every hash this service has ever stored must keep verifying here; see
+/// `tests/wire_compat_tests.rs` before changing the scheme.
+pub fn verify_password(password: &str, password_hash: &str) -> Result<bool> {
+    bcrypt::verify(password, password_hash)
+        .map_err(|e| AppError::Internal(format!("Failed to verify password: {}", e)))
+}
+
 /// User credentials stored in database
 #[derive(Debug, Clone, FromRow)]
 pub struct UserCredentials {
@@ -81,6 +369,513 @@ pub struct UserRole {
     pub created_at: DateTime<Utc>,
 }
 
+/// Server-side session record
+#[derive(Debug, Clone, FromRow)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub scope: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Use extends `expires_at`, up to `absolute_expires_at`
+    pub remember_me: bool,
+    pub absolute_expires_at: Option<DateTime<Utc>>,
+    /// See [`SessionMetadata::device_fingerprint`]
+    pub device_fingerprint: Option<String>,
+    /// False for a sign-in from a new device until the user confirms it
+    pub verified: bool,
+}
+
+/// A user's token version and when their roles last changed, stamped on
+/// their access tokens as `uver` and `roles_updated_at`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromRow)]
+pub struct UserTokenState {
+    pub token_version: i64,
+    pub roles_updated_at: Option<DateTime<Utc>>,
+}
+
+impl UserTokenState {
+    /// Whether a token issued at the user's `version` has been revoked since
+    pub fn revokes(&self, version: i64) -> bool {
+        version < self.token_version
+    }
+
+    /// Whether the roles in a token stamped with `roles_updated_at` have
+    /// changed since; the token is fine again once refreshed
+    pub fn requires_refresh(&self, roles_updated_at: Option<i64>) -> bool {
+        self.roles_updated_at.map(|at| at.timestamp()) > roles_updated_at
+    }
+}
+
+/// Which sessions an admin revokes in bulk; every session when empty
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RevokeAllSessionsRequest {
+    /// Only sessions acting in this tenant
+    #[schema(example = "acme")]
+    pub tenant: Option<String>,
+    /// Only sessions created before this time
+    #[serde(default, with = "format::option_timestamp")]
+    pub issued_before: Option<DateTime<Utc>>,
+}
+
+/// Outcome of a bulk session revocation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevokeAllSessionsResponse {
+    pub sessions_revoked: u64,
+    /// The new token version when every token was revoked; absent when the
+    /// revocation was filtered
+    pub token_version: Option<i64>,
+}
+
+/// An active session as shown to its owner
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionInfo {
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    /// User agent the session was created from
+    #[schema(example = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) Safari/605.1.15")]
+    pub device: Option<String>,
+    #[schema(example = "203.0.113.7")]
+    pub ip_address: Option<String>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::timestamp")]
+    pub expires_at: DateTime<Utc>,
+    /// Whether use extends `expires_at`
+    pub remember_me: bool,
+    /// False for a sign-in from a new device the user hasn't confirmed yet
+    pub verified: bool,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+impl SessionInfo {
+    pub fn from_session(session: UserSession, current_session: Option<Uuid>) -> Self {
+        Self {
+            current: current_session == Some(session.id),
+            id: session.id,
+            device: session.device,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+            remember_me: session.remember_me,
+            verified: session.verified,
+        }
+    }
+}
+
+/// Marks bearer tokens that are personal access tokens rather than JWTs
+pub const PERSONAL_ACCESS_TOKEN_PREFIX: &str = "rpat_";
+
+/// Longest lifetime a personal access token can be created with
+pub const PERSONAL_ACCESS_TOKEN_MAX_DAYS: i64 = 365;
+
+/// Stored personal access token
+#[derive(Debug, Clone, FromRow)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_prefix: String,
+    pub roles: Vec<String>,
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub response_format: Option<String>,
+}
+
+/// Create personal access token request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatePersonalAccessTokenRequest {
+    #[schema(example = "CI deploys")]
+    pub name: String,
+    /// Roles the token acts with; each must be one of the caller's roles
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+    /// API scopes the token is restricted to; omit for an unrestricted token.
+    /// A caller whose own token is scoped must list a subset of its scopes.
+    #[schema(example = json!(["users:read"]))]
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    /// Days until the token expires; omit for a token that doesn't expire
+    #[schema(example = 90)]
+    pub expires_in_days: Option<i64>,
+    /// `X-Response-Format` options applied when a request doesn't send the header
+    #[schema(example = "epoch-millis,base62")]
+    #[serde(default)]
+    pub response_format: Option<String>,
+}
+
+impl CreatePersonalAccessTokenRequest {
+    /// `granted_scopes` are the caller's own API scopes, `None` if its token
+    /// isn't scoped
+    pub fn validate(&self, granted_roles: &[String], granted_scopes: Option<&[String]>) -> Result<()> {
+        let name = self.name.trim();
+        if name.is_empty() || name.len() > 255 {
+            return Err(AppError::Validation(
+                "Token name must be between 1 and 255 characters".to_string(),
+            ));
+        }
+
+        if self.roles.is_empty() {
+            return Err(AppError::Validation(
+                "At least one role is required".to_string(),
+            ));
+        }
+        if let Some(role) = self.roles.iter().find(|role| !granted_roles.contains(role)) {
+            return Err(AppError::Validation(format!(
+                "Role '{}' is not one of your roles",
+                role
+            )));
+        }
+
+        match (&self.scopes, granted_scopes) {
+            (Some(requested), _) if requested.is_empty() => {
+                return Err(AppError::Validation(
+                    "At least one scope is required; omit scopes for an unrestricted token".to_string(),
+                ));
+            }
+            (Some(requested), granted) => {
+                if let Some(scope) = requested.iter().find(|scope| !scopes::ALL.contains(&scope.as_str())) {
+                    return Err(AppError::Validation(format!("Unknown scope '{}'", scope)));
+                }
+                if let Some(scope) = granted.and_then(|granted| {
+                    requested.iter().find(|scope| !granted.contains(scope))
+                }) {
+                    return Err(AppError::Validation(format!(
+                        "Scope '{}' is not granted to your token",
+                        scope
+                    )));
+                }
+            }
+            (None, Some(_)) => {
+                return Err(AppError::Validation(
+                    "A scoped token can only create tokens limited to its own scopes".to_string(),
+                ));
+            }
+            (None, None) => {}
+        }
+
+        if let Some(days) = self.expires_in_days {
+            if !(1..=PERSONAL_ACCESS_TOKEN_MAX_DAYS).contains(&days) {
+                return Err(AppError::Validation(format!(
+                    "expires_in_days must be between 1 and {}",
+                    PERSONAL_ACCESS_TOKEN_MAX_DAYS
+                )));
+            }
+        }
+
+        if let Some(response_format) = &self.response_format {
+            format::ResponseFormat::parse(response_format)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A personal access token as shown to its owner
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PersonalAccessTokenInfo {
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    #[schema(example = "CI deploys")]
+    pub name: String,
+    /// Start of the token, to tell tokens apart
+    #[schema(example = "rpat_Xk3v")]
+    pub token_prefix: String,
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+    /// API scopes the token is restricted to; absent for an unrestricted token
+    #[schema(example = json!(["users:read"]))]
+    pub scopes: Option<Vec<String>>,
+    #[serde(with = "format::option_timestamp")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[schema(example = "203.0.113.7")]
+    pub last_used_ip: Option<String>,
+    #[schema(example = "epoch-millis,base62")]
+    pub response_format: Option<String>,
+}
+
+impl From<PersonalAccessToken> for PersonalAccessTokenInfo {
+    fn from(token: PersonalAccessToken) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            roles: token.roles,
+            scopes: token.scopes,
+            expires_at: token.expires_at,
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+            last_used_ip: token.last_used_ip,
+            response_format: token.response_format,
+        }
+    }
+}
+
+/// A newly created personal access token; the token is never shown again
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedPersonalAccessToken {
+    #[schema(example = "rpat_Xk3vQ2...")]
+    pub token: String,
+    #[serde(flatten)]
+    pub info: PersonalAccessTokenInfo,
+}
+
+/// Marks service account client secrets
+pub const SERVICE_ACCOUNT_SECRET_PREFIX: &str = "rsvc_";
+
+/// Only grant type `POST /api/v1/auth/token` accepts
+pub const CLIENT_CREDENTIALS_GRANT: &str = "client_credentials";
+
+/// Stored service account; the client id is its id
+#[derive(Debug, Clone, FromRow)]
+pub struct ServiceAccount {
+    pub id: Uuid,
+    pub name: String,
+    pub secret_prefix: String,
+    pub roles: Vec<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+/// Create service account request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateServiceAccountRequest {
+    #[schema(example = "billing-sync")]
+    pub name: String,
+    /// Roles the account's tokens carry
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+}
+
+impl CreateServiceAccountRequest {
+    pub fn validate(&self) -> Result<()> {
+        let name = self.name.trim();
+        if name.is_empty() || name.len() > 255 {
+            return Err(AppError::Validation(
+                "Service account name must be between 1 and 255 characters".to_string(),
+            ));
+        }
+
+        if let Some(role) = self.roles.iter().find(|role| !roles::ALL.contains(&role.as_str())) {
+            return Err(AppError::Validation(format!("Unknown role '{}'", role)));
+        }
+
+        Ok(())
+    }
+}
+
+/// A service account as shown to admins
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServiceAccountInfo {
+    /// Also the `client_id` of the client-credentials grant
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    #[schema(example = "billing-sync")]
+    pub name: String,
+    /// Start of the client secret, to tell secrets apart
+    #[schema(example = "rsvc_Xk3v")]
+    pub secret_prefix: String,
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Set once disabled; disabled accounts can't get new tokens
+    #[serde(with = "format::option_timestamp")]
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+impl From<ServiceAccount> for ServiceAccountInfo {
+    fn from(account: ServiceAccount) -> Self {
+        Self {
+            id: account.id,
+            name: account.name,
+            secret_prefix: account.secret_prefix,
+            roles: account.roles,
+            created_at: account.created_at,
+            last_used_at: account.last_used_at,
+            disabled_at: account.disabled_at,
+        }
+    }
+}
+
+/// A newly created service account; the secret is never shown again
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedServiceAccount {
+    #[schema(example = "rsvc_Xk3vQ2...")]
+    pub client_secret: String,
+    #[serde(flatten)]
+    pub info: ServiceAccountInfo,
+}
+
+/// Client-credentials grant request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ClientCredentialsRequest {
+    #[schema(example = "client_credentials")]
+    pub grant_type: String,
+    /// The service account's id
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+/// Access token issued to a service account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClientCredentialsResponse {
+    pub access_token: String,
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+    #[schema(example = 900)]
+    pub expires_in: u64,
+}
+
+/// Token introspection request (RFC 7662 style)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IntrospectionRequest {
+    pub token: String,
+    /// Accepted for compatibility; only access tokens are introspected
+    #[schema(example = "access_token")]
+    pub token_type_hint: Option<String>,
+}
+
+/// State of the session an introspected token is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSessionStatus {
+    Active,
+    /// Revoked or expired
+    Revoked,
+    /// The token isn't bound to a session, e.g. a service account's
+    None,
+}
+
+/// Whether a token would be accepted right now, and what it says
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IntrospectionResponse {
+    /// False for a token that is malformed, expired, revoked, or due a
+    /// refresh after a role change
+    pub active: bool,
+    /// Only for tokens that could be verified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_status: Option<TokenSessionStatus>,
+    /// Only for active tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claims: Option<Claims>,
+}
+
+impl IntrospectionResponse {
+    pub fn inactive(session_status: Option<TokenSessionStatus>) -> Self {
+        Self {
+            active: false,
+            session_status,
+            claims: None,
+        }
+    }
+}
+
+/// Stored refresh token; the session it belongs to is its family
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of presenting a refresh token
+#[derive(Debug, Clone)]
+pub enum RefreshRotation {
+    /// The token was valid and has been replaced
+    Rotated {
+        session_id: Uuid,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    },
+    /// An already-used token was presented; the session has been revoked
+    Reused { session_id: Uuid, user_id: Uuid },
+    /// Unknown, expired, or belonging to a revoked session
+    Invalid,
+}
+
+/// Client metadata captured when a session is created
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadata {
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub scope: Option<String>,
+}
+
+impl SessionMetadata {
+    /// Build session metadata from request headers (user agent and forwarded client IP)
+    pub fn from_headers(headers: &axum::http::HeaderMap) -> Self {
+        Self {
+            device: request_context::user_agent(headers),
+            ip_address: request_context::client_ip(headers),
+            scope: None,
+        }
+    }
+
+    /// Identifies the device a session comes from: a hash of the user agent
+    /// and the client's IP subnet (/24 for IPv4, /48 for IPv6), so a new
+    /// address on the same network is still the same device; `None` when
+    /// neither is known
+    pub fn device_fingerprint(&self) -> Option<String> {
+        if self.device.is_none() && self.ip_address.is_none() {
+            return None;
+        }
+
+        let subnet = self.ip_address.as_deref().map(|ip| match ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                let [a, b, c, _] = ip.octets();
+                format!("{}.{}.{}.0/24", a, b, c)
+            }
+            Ok(IpAddr::V6(ip)) => {
+                let [a, b, c, ..] = ip.segments();
+                format!("{:x}:{:x}:{:x}::/48", a, b, c)
+            }
+            Err(_) => ip.to_string(),
+        });
+
+        let source = format!(
+            "{}\n{}",
+            self.device.as_deref().unwrap_or_default(),
+            subnet.unwrap_or_default()
+        );
+        Some(format!("{:x}", Sha256::digest(source.as_bytes())))
+    }
+}
+
+impl From<&RequestContext> for SessionMetadata {
+    fn from(context: &RequestContext) -> Self {
+        Self {
+            device: context.user_agent.clone(),
+            ip_address: context.client_ip.clone(),
+            scope: None,
+        }
+    }
+}
+
 /// Permission check request for openFGA
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PermissionCheck {
@@ -104,21 +899,36 @@ pub mod roles {
     pub const ADMIN: &str = "admin";
     pub const USER: &str = "user";
     pub const MODERATOR: &str = "moderator";
+
+    pub const ALL: &[&str] = &[ADMIN, USER, MODERATOR];
+}
+
+/// API scopes a token can be restricted to, as `resource:action`
+pub mod scopes {
+    pub const USERS_READ: &str = "users:read";
+    pub const USERS_WRITE: &str = "users:write";
+
+    pub const ALL: &[&str] = &[USERS_READ, USERS_WRITE];
 }
 
-/// Common relations for openFGA
+/// Common relations for openFGA, from the typed registry
 pub mod relations {
-    pub const OWNER: &str = "owner";
-    pub const EDITOR: &str = "editor";
-    pub const VIEWER: &str = "viewer";
-    pub const MEMBER: &str = "member";
-    pub const ADMIN: &str = "admin";
+    use crate::auth::registry::Relation;
+
+    pub const OWNER: &str = Relation::Owner.as_str();
+    pub const EDITOR: &str = Relation::Editor.as_str();
+    pub const VIEWER: &str = Relation::Viewer.as_str();
+    pub const MEMBER: &str = Relation::Member.as_str();
+    pub const ADMIN: &str = Relation::Admin.as_str();
 }
 
-/// Common object types for openFGA
+/// Common object types for openFGA, from the typed registry
 pub mod object_types {
-    pub const USER: &str = "user";
-    pub const ORGANIZATION: &str = "organization";
-    pub const PROJECT: &str = "project";
-    pub const DOCUMENT: &str = "document";
+    use crate::auth::registry::ObjectType;
+
+    pub const USER: &str = ObjectType::User.as_str();
+    pub const ORGANIZATION: &str = ObjectType::Organization.as_str();
+    pub const PROJECT: &str = ObjectType::Project.as_str();
+    pub const DOCUMENT: &str = ObjectType::Document.as_str();
+    pub const SERVICE: &str = ObjectType::Service.as_str();
 }
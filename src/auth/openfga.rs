@@ -1,21 +1,75 @@
-use crate::auth::cache::PermissionCache;
-use crate::auth::models::AuthorizationResult;
-use crate::config::Config;
+use crate::auth::breaker::{BreakerState, CircuitBreaker};
+use crate::auth::models::{AuthorizationResult, Subject};
+use crate::auth::registry;
+use crate::auth::stores::{OpenFgaStore, StoreRouter};
+use crate::auth::transport::{transport_from_config, OpenFgaTransport, TransportError};
+use crate::config::{
+    CircuitBreakerConfig, Config, FallbackMode, ListObjectsConfig, OpenFgaTimeoutsConfig,
+};
 use crate::errors::{AppError, Result};
-use reqwest::Client;
+use crate::metrics::AppMetrics;
+use crate::{dependencies, request_context, request_cost};
+use crate::utils::retry::RetryPolicy;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
+/// Most tuples OpenFGA accepts in a single write request
+const MAX_TUPLES_PER_WRITE: usize = 100;
+
+/// Kind of OpenFGA call, which picks its time budget from
+/// `auth.openfga.timeouts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenFgaOperation {
+    Check,
+    Read,
+    Write,
+}
+
+impl OpenFgaOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OpenFgaOperation::Check => "check",
+            OpenFgaOperation::Read => "read",
+            OpenFgaOperation::Write => "write",
+        }
+    }
+
+    pub fn budget(self, timeouts: &OpenFgaTimeoutsConfig) -> Duration {
+        Duration::from_millis(match self {
+            OpenFgaOperation::Check => timeouts.check_ms,
+            OpenFgaOperation::Read => timeouts.read_ms,
+            OpenFgaOperation::Write => timeouts.write_ms,
+        })
+    }
+}
+
+/// Why an attempt at an OpenFGA request didn't produce a response to use
+enum SendFailure {
+    /// Nothing was sent, and retrying won't change that
+    Failed(AppError),
+    Transport(reqwest::Error),
+    /// A 5xx; handed to the caller if retrying doesn't get past it
+    ServerError(reqwest::Response),
+}
+
 /// OpenFGA API request/response models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckRequest {
     pub tuple_key: TupleKey,
     pub contextual_tuples: Option<ContextualTuples>,
+    pub authorization_model_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TupleKey {
     pub user: String,
     pub relation: String,
@@ -64,14 +118,322 @@ pub struct ListObjectsResponse {
     pub objects: Vec<String>,
 }
 
+/// One line of a streamed list objects response
+#[derive(Debug, Deserialize)]
+struct StreamedListObjectsLine {
+    result: Option<StreamedObject>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamedObject {
+    object: String,
+}
+
+/// A page of the objects a user holds a relation on, in sorted order
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ObjectPage {
+    #[schema(example = json!(["document:roadmap", "document:spec"]))]
+    pub objects: Vec<String>,
+    /// Pass back for the next page; `None` on the last one
+    pub continuation_token: Option<String>,
+    /// The listing reached `auth.openfga.list_objects.max_results`, so
+    /// objects past it are missing
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FgaObject {
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserTypeFilter {
+    #[serde(rename = "type")]
+    pub user_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListUsersRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_model_id: Option<String>,
+    pub object: FgaObject,
+    pub relation: String,
+    pub user_filters: Vec<UserTypeFilter>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListUsersResponse {
+    pub users: Vec<ListedUser>,
+}
+
+/// A user entry from list-users: a concrete object, a userset or a wildcard
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListedUser {
+    Object(FgaObject),
+    Userset {
+        #[serde(rename = "type")]
+        user_type: String,
+        id: String,
+        relation: String,
+    },
+    Wildcard {
+        #[serde(rename = "type")]
+        user_type: String,
+    },
+}
+
+impl std::fmt::Display for ListedUser {
+    /// The tuple notation: `user:<id>`, `group:eng#member` or `user:*`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListedUser::Object(object) => write!(f, "{}:{}", object.object_type, object.id),
+            ListedUser::Userset {
+                user_type,
+                id,
+                relation,
+            } => write!(f, "{}:{}#{}", user_type, id, relation),
+            ListedUser::Wildcard { user_type } => write!(f, "{}:*", user_type),
+        }
+    }
+}
+
+/// Which tuples to read; fields left out match anything
+///
+/// OpenFGA needs at least the object type (`document:`) when a user or
+/// relation is given.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TupleFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+}
+
+impl TupleFilter {
+    fn is_empty(&self) -> bool {
+        self.user.is_none() && self.relation.is_none() && self.object.is_none()
+    }
+}
+
+/// A stored relationship tuple
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RelationshipTuple {
+    #[schema(example = "user:6f9619ff-8b86-4d01-b42d-00cf4fc964ff")]
+    pub user: String,
+    #[schema(example = "viewer")]
+    pub relation: String,
+    #[schema(example = "document:roadmap")]
+    pub object: String,
+    /// When the tuple was written
+    pub timestamp: Option<String>,
+}
+
+/// One page of tuples
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TuplePage {
+    pub tuples: Vec<RelationshipTuple>,
+    /// Pass back to get the next page; empty on the last one
+    #[serde(default)]
+    pub continuation_token: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tuple_key: Option<&'a TupleFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    continuation_token: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadResponse {
+    tuples: Vec<ReadTuple>,
+    #[serde(default)]
+    continuation_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadTuple {
+    key: TupleKey,
+    timestamp: Option<String>,
+}
+
+/// A tuple write or delete from the store's change log
+#[derive(Debug, Deserialize)]
+pub struct TupleChange {
+    pub tuple_key: TupleKey,
+    /// `TUPLE_OPERATION_WRITE` or `TUPLE_OPERATION_DELETE`
+    pub operation: String,
+    pub timestamp: Option<String>,
+}
+
+/// One page of the change log, oldest first
+#[derive(Debug, Deserialize)]
+pub struct ChangesPage {
+    #[serde(default)]
+    pub changes: Vec<TupleChange>,
+    /// Pass back to continue after the last change; OpenFGA returns it even
+    /// when there is nothing newer yet
+    #[serde(default)]
+    pub continuation_token: String,
+}
+
+/// Who has a relation to an object
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ObjectUsers {
+    #[schema(example = "document:roadmap")]
+    pub object: String,
+    #[schema(example = "editor")]
+    pub relation: String,
+    /// Tuple notation: `user:<id>`, `group:<id>#member`, or `user:*` for everyone
+    #[schema(example = json!(["user:6f9619ff-8b86-4d01-b42d-00cf4fc964ff", "group:eng#member"]))]
+    pub users: Vec<String>,
+}
+
+/// An OpenFGA authorization model in its JSON form
+///
+/// The DSL has to be converted first (`fga model transform`); this service
+/// doesn't parse it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuthorizationModel {
+    /// Assigned by OpenFGA; ignored on upload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[schema(example = "1.1")]
+    pub schema_version: String,
+    #[schema(value_type = Vec<Object>)]
+    pub type_definitions: Vec<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub conditions: Option<serde_json::Value>,
+}
+
+impl AuthorizationModel {
+    /// Load a model from its JSON form, as written by `fga model transform`
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Internal(format!(
+                "Failed to read authorization model {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&contents).map_err(|e| {
+            AppError::Internal(format!(
+                "Failed to parse authorization model {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Whether both models define the same types and conditions
+    ///
+    /// IDs are ignored, as are fields OpenFGA fills in with empty defaults
+    /// (null metadata, empty relation maps) when returning a stored model.
+    pub fn same_definition(&self, other: &AuthorizationModel) -> bool {
+        let definition = |model: &AuthorizationModel| {
+            (
+                model.schema_version.clone(),
+                model.type_definitions.iter().map(without_empty).collect::<Vec<_>>(),
+                model.conditions.as_ref().map(without_empty).filter(|c| !c.is_null()),
+            )
+        };
+        definition(self) == definition(other)
+    }
+}
+
+/// Drop nulls and empty objects/arrays, recursively
+fn without_empty(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    let is_empty = |value: &Value| match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    };
+
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), without_empty(value)))
+                .filter(|(_, value)| !is_empty(value))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(without_empty).collect()),
+        other => other.clone(),
+    }
+}
+
+/// One page of a store's models, newest first
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuthorizationModelPage {
+    pub authorization_models: Vec<AuthorizationModel>,
+    /// Pass back to get the next page; empty on the last one
+    #[serde(default)]
+    pub continuation_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WriteAuthorizationModelResponse {
+    pub authorization_model_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadAuthorizationModelResponse {
+    authorization_model: AuthorizationModel,
+}
+
+/// `cache_type` label for permission cache hits and misses in [`AppMetrics`]
+const PERMISSION_CACHE_METRIC: &str = "openfga_permission";
+
+/// JSON content type plus the bearer token, when one is configured
+pub(crate) fn request_headers(api_token: Option<&str>) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::CONTENT_TYPE,
+        reqwest::header::HeaderValue::from_static("application/json"),
+    );
+
+    if let Some(token) = api_token {
+        if let Ok(auth_value) = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token)) {
+            headers.insert(reqwest::header::AUTHORIZATION, auth_value);
+        }
+    }
+
+    headers
+}
+
 #[derive(Clone)]
 pub struct OpenFgaService {
     client: Client,
     endpoint: String,
-    store_id: String,
-    auth_model_id: Option<String>,
     api_token: Option<String>,
-    cache: Arc<PermissionCache>,
+    /// Store, pinned model and permission cache per tenant
+    stores: StoreRouter,
+    /// Carries permission checks only; every other call uses `client`
+    transport: Arc<dyn OpenFgaTransport>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    fallback_policy: Arc<CircuitBreakerConfig>,
+    timeouts: OpenFgaTimeoutsConfig,
+    retry: RetryPolicy,
+    list_objects: ListObjectsConfig,
+    /// Set once the server turns out not to have the streamed list objects
+    /// endpoint, so listings go straight to the buffered one
+    streaming_unsupported: Arc<AtomicBool>,
+    metrics: Option<AppMetrics>,
 }
 
 impl OpenFgaService {
@@ -81,63 +443,191 @@ impl OpenFgaService {
             .build()
             .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
 
-        // Initialize cache with configuration-based settings
-        let cache = if config.auth.openfga.cache_enabled {
-            Arc::new(PermissionCache::new(
-                Duration::from_secs(config.auth.openfga.cache_ttl_seconds),
-                config.auth.openfga.cache_max_entries,
-            ))
-        } else {
-            // Disabled cache (TTL = 0 effectively disables caching)
-            Arc::new(PermissionCache::new(
-                Duration::from_secs(0),
-                1, // Minimal cache size
-            ))
-        };
+        let stores = StoreRouter::from_config(config)?;
+
+        let transport = transport_from_config(&config.auth.openfga, &client)?;
+        tracing::info!("OpenFGA permission checks use the {} transport", transport.name());
 
         let service = Self {
             client,
             endpoint: config.auth.openfga.endpoint.clone(),
-            store_id: config.auth.openfga.store_id.clone(),
-            auth_model_id: config.auth.openfga.auth_model_id.clone(),
             api_token: config.auth.openfga.api_token.clone(),
-            cache: cache.clone(),
+            stores,
+            transport,
+            breaker: CircuitBreaker::from_config(&config.auth.openfga.circuit_breaker).map(Arc::new),
+            fallback_policy: Arc::new(config.auth.openfga.circuit_breaker.clone()),
+            timeouts: config.auth.openfga.timeouts.clone(),
+            retry: RetryPolicy::from_config("openfga", &config.auth.openfga.retry),
+            list_objects: config.auth.openfga.list_objects.clone(),
+            streaming_unsupported: Arc::new(AtomicBool::new(false)),
+            metrics: None,
         };
 
-        // Start background cache cleanup task only if caching is enabled
-        if config.auth.openfga.cache_enabled {
-            let cache_cleanup = cache.clone();
-            tokio::spawn(async move {
-                cache_cleanup.cleanup_task().await;
-            });
+        Ok(service)
+    }
 
-            tracing::info!(
-                "OpenFGA cache enabled: TTL={}s, max_entries={}",
-                config.auth.openfga.cache_ttl_seconds,
-                config.auth.openfga.cache_max_entries
-            );
-        } else {
-            tracing::info!("OpenFGA cache disabled");
+    /// Export circuit breaker state, fallback decisions and retries
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        if let Some(breaker) = &self.breaker {
+            metrics.set_openfga_circuit_state(breaker.state().as_metric());
         }
+        self.retry = self.retry.clone().with_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
 
-        Ok(service)
+    /// Which transport permission checks use, `http` or `grpc`
+    pub fn transport_name(&self) -> &'static str {
+        self.transport.name()
     }
 
-    /// Build request headers with optional API token
-    fn build_headers(&self) -> reqwest::header::HeaderMap {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::CONTENT_TYPE,
-            reqwest::header::HeaderValue::from_static("application/json"),
-        );
+    /// `None` when the circuit breaker is disabled
+    pub fn breaker_state(&self) -> Option<BreakerState> {
+        self.breaker.as_ref().map(|breaker| breaker.state())
+    }
 
-        if let Some(token) = &self.api_token {
-            if let Ok(auth_value) = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token)) {
-                headers.insert(reqwest::header::AUTHORIZATION, auth_value);
+    /// Send a request to OpenFGA through the circuit breaker, within the
+    /// operation's time budget
+    ///
+    /// Transport errors, timeouts and 5xx responses count as failures; any
+    /// other response, 4xx included, shows OpenFGA is up. While the breaker
+    /// is open nothing is sent and this fails straight away.
+    ///
+    /// Reads that fail are retried under `auth.openfga.retry` until the budget
+    /// runs out; writes aren't idempotent, so they're only retried when the
+    /// connection couldn't be made. A 5xx left after retrying is returned for
+    /// the caller to report.
+    async fn send(
+        &self,
+        operation: OpenFgaOperation,
+        what: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        // Nothing is gained by waiting past the point the caller gives up
+        let budget = operation.budget(&self.timeouts);
+        let deadline = request_context::deadline_within(budget);
+
+        let result = self
+            .retry
+            .run_until(
+                tokio::time::sleep_until(deadline),
+                |_| {
+                    let request = request.try_clone();
+                    async move {
+                        let Some(request) = request else {
+                            return Err(SendFailure::Failed(AppError::Internal(format!(
+                                "OpenFGA {} failed: request can't be sent twice",
+                                what
+                            ))));
+                        };
+                        if !self.admit() {
+                            return Err(SendFailure::Failed(AppError::Internal(format!(
+                                "OpenFGA {} failed: circuit breaker open",
+                                what
+                            ))));
+                        }
+
+                        request_cost::record_openfga_call();
+                        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+                        let started = std::time::Instant::now();
+                        let result = request.timeout(remaining).send().await;
+                        let error = match &result {
+                            Ok(response) if response.status().is_server_error() => {
+                                Some(format!("{}: HTTP {}", what, response.status()))
+                            }
+                            Ok(_) => None,
+                            Err(e) => Some(format!("{}: {}", what, e)),
+                        };
+                        self.record_outcome(error.is_none());
+                        dependencies::record(dependencies::OPENFGA, started.elapsed(), error);
+                        match result {
+                            Ok(response) if response.status().is_server_error() => {
+                                Err(SendFailure::ServerError(response))
+                            }
+                            Ok(response) => Ok(response),
+                            Err(e) => Err(SendFailure::Transport(e)),
+                        }
+                    }
+                },
+                |failure| match failure {
+                    SendFailure::Failed(_) => false,
+                    SendFailure::Transport(e) if e.is_timeout() => false,
+                    SendFailure::Transport(e) => e.is_connect() || operation != OpenFgaOperation::Write,
+                    SendFailure::ServerError(_) => operation != OpenFgaOperation::Write,
+                },
+            )
+            .await;
+
+        match result {
+            Ok(response) | Err(SendFailure::ServerError(response)) => Ok(response),
+            Err(SendFailure::Failed(error)) => Err(error),
+            Err(SendFailure::Transport(e)) if e.is_timeout() => {
+                Err(self.timed_out(operation, what, budget))
+            }
+            Err(SendFailure::Transport(e)) => {
+                Err(AppError::Internal(format!("OpenFGA {} failed: {}", what, e)))
             }
         }
+    }
 
-        headers
+    fn timed_out(&self, operation: OpenFgaOperation, what: &str, budget: Duration) -> AppError {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_openfga_timeout(operation.as_str());
+        }
+        AppError::Timeout(format!("OpenFGA {} timed out after {}ms", what, budget.as_millis()))
+    }
+
+    /// Whether the circuit breaker lets a call through; always true without one
+    fn admit(&self) -> bool {
+        let Some(breaker) = &self.breaker else {
+            return true;
+        };
+        let admitted = breaker.try_acquire();
+        self.report_breaker_state();
+        admitted
+    }
+
+    fn record_outcome(&self, reachable: bool) {
+        if let Some(breaker) = &self.breaker {
+            if reachable {
+                breaker.record_success();
+            } else {
+                breaker.record_failure();
+            }
+            self.report_breaker_state();
+        }
+    }
+
+    fn report_breaker_state(&self) {
+        if let (Some(metrics), Some(breaker)) = (&self.metrics, &self.breaker) {
+            metrics.set_openfga_circuit_state(breaker.state().as_metric());
+        }
+    }
+
+    /// Answer a permission check from the fallback policy while OpenFGA is
+    /// unavailable; the answer isn't cached
+    fn fallback(&self, relation: &str, cause: &str) -> AuthorizationResult {
+        let allowed = self.fallback_policy.fallback_for(relation) == FallbackMode::FailOpen;
+        tracing::warn!(
+            relation = %relation,
+            allowed,
+            cause = %cause,
+            "OpenFGA unavailable, permission check answered by fallback policy"
+        );
+        if let Some(metrics) = &self.metrics {
+            metrics.record_openfga_fallback(relation, allowed);
+        }
+
+        AuthorizationResult {
+            allowed,
+            reason: (!allowed)
+                .then(|| "Permission denied: authorization service unavailable".to_string()),
+        }
+    }
+
+    /// Build request headers with optional API token
+    fn build_headers(&self) -> reqwest::header::HeaderMap {
+        request_headers(self.api_token.as_deref())
     }
 
     /// Check if a user has permission to perform an action on a resource
@@ -148,8 +638,36 @@ impl OpenFgaService {
         object_type: &str,
         object_id: &str,
     ) -> Result<AuthorizationResult> {
+        self.check_subject_permission(Subject::user(user_id), relation, object_type, object_id)
+            .await
+    }
+
+    /// Check a permission for a user or a service account
+    ///
+    /// Cached results are keyed by the subject's id alone; user and service
+    /// account ids are both random UUIDs, so they don't collide.
+    pub async fn check_subject_permission(
+        &self,
+        subject: Subject,
+        relation: &str,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<AuthorizationResult> {
+        let store = self.stores.current();
+        registry::validate(object_type, relation)?;
+        let user_id = subject.id;
+
         // Check cache first
-        if let Some(cached_result) = self.cache.get(user_id, relation, object_type, object_id).await {
+        let lookup_started = std::time::Instant::now();
+        let cached = store.cache.get(user_id, relation, object_type, object_id).await;
+        if let Some(metrics) = &self.metrics {
+            let elapsed = lookup_started.elapsed().as_secs_f64();
+            match cached {
+                Some(_) => metrics.record_cache_hit(PERMISSION_CACHE_METRIC, "check", elapsed),
+                None => metrics.record_cache_miss(PERMISSION_CACHE_METRIC, "check", elapsed),
+            }
+        }
+        if let Some(cached_result) = cached {
             tracing::debug!(
                 "Cache hit for permission check: user={}, relation={}, object={}:{}, allowed={}",
                 user_id,
@@ -169,64 +687,103 @@ impl OpenFgaService {
             });
         }
 
-        let user = format!("user:{}", user_id);
+        let user = subject.to_string();
         let object = format!("{}:{}", object_type, object_id);
 
-        let request = CheckRequest {
-            tuple_key: TupleKey {
-                user: user.clone(),
-                relation: relation.to_string(),
-                object: object.clone(),
-            },
-            contextual_tuples: None,
-        };
-
         tracing::debug!(
-            "Checking permission via OpenFGA: user={}, relation={}, object={}",
+            "Checking permission via OpenFGA: user={}, relation={}, object={}, transport={}",
             user,
             relation,
-            object
+            object,
+            self.transport.name()
         );
 
-        let url = format!("{}/stores/{}/check", self.endpoint, self.store_id);
-
-        let response = self
-            .client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::Internal(format!(
-                "OpenFGA check failed with status {}: {}",
-                status, error_text
-            )));
-        }
+        // Failures on OpenFGA's side are retried within the check's budget;
+        // `None` means the breaker refused the attempt
+        let budget = OpenFgaOperation::Check.budget(&self.timeouts);
+        let deadline = request_context::deadline_within(budget);
+        let tuple_key = TupleKey {
+            user: user.clone(),
+            relation: relation.to_string(),
+            object: object.clone(),
+        };
+        let result = self
+            .retry
+            .run_until(
+                tokio::time::sleep_until(deadline),
+                |_| {
+                    let tuple_key = tuple_key.clone();
+                    async move {
+                        if !self.admit() {
+                            return Err(None);
+                        }
+
+                        request_cost::record_openfga_call();
+                        let started = std::time::Instant::now();
+                        let check = self.transport.check(
+                            &store.store_id,
+                            store.auth_model_id.as_deref(),
+                            tuple_key,
+                        );
+                        let result = tokio::time::timeout_at(deadline, check)
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(TransportError::TimedOut(format!(
+                                    "OpenFGA check timed out after {}ms",
+                                    budget.as_millis()
+                                )))
+                            });
+                        if matches!(result, Err(TransportError::TimedOut(_))) {
+                            if let Some(metrics) = &self.metrics {
+                                metrics.record_openfga_timeout(OpenFgaOperation::Check.as_str());
+                            }
+                        }
+                        let error = match &result {
+                            Err(TransportError::Unavailable(cause) | TransportError::TimedOut(cause)) => {
+                                Some(format!("check: {}", cause))
+                            }
+                            _ => None,
+                        };
+                        self.record_outcome(error.is_none());
+                        dependencies::record(dependencies::OPENFGA, started.elapsed(), error);
+                        result.map_err(Some)
+                    }
+                },
+                |error| matches!(error, Some(TransportError::Unavailable(_))),
+            )
+            .await;
+        let result = match result {
+            Ok(allowed) => Ok(allowed),
+            Err(Some(error)) => Err(error),
+            Err(None) => return Ok(self.fallback(relation, "circuit breaker open")),
+        };
 
-        let check_response: CheckResponse = response
-            .json()
-            .await
-            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA response: {}", e)))?;
+        // With a breaker configured, an outage or slow answer is handled by
+        // the fallback policy rather than failing the request
+        let allowed = match result {
+            Ok(allowed) => allowed,
+            Err(TransportError::Unavailable(cause) | TransportError::TimedOut(cause))
+                if self.breaker.is_some() =>
+            {
+                return Ok(self.fallback(relation, &cause));
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         // Cache the result
-        self.cache.set(user_id, relation, object_type, object_id, check_response.allowed).await;
+        store.cache.set(user_id, relation, object_type, object_id, allowed).await;
 
         tracing::debug!(
             "Permission check result: user={}, relation={}, object={}, allowed={}",
             user,
             relation,
             object,
-            check_response.allowed
+            allowed
         );
 
         Ok(AuthorizationResult {
-            allowed: check_response.allowed,
-            reason: if check_response.allowed {
+            allowed,
+            reason: if allowed {
                 None
             } else {
                 Some("Permission denied by OpenFGA".to_string())
@@ -242,6 +799,9 @@ impl OpenFgaService {
         object_type: &str,
         object_id: &str,
     ) -> Result<()> {
+        let store = self.stores.current();
+        registry::validate(object_type, relation)?;
+
         let user = format!("user:{}", user_id);
         let object = format!("{}:{}", object_type, object_id);
 
@@ -256,7 +816,7 @@ impl OpenFgaService {
                 tuple_keys: vec![tuple_key],
             }),
             deletes: None,
-            authorization_model_id: self.auth_model_id.clone(),
+            authorization_model_id: store.auth_model_id.clone(),
         };
 
         tracing::debug!(
@@ -266,16 +826,18 @@ impl OpenFgaService {
             object
         );
 
-        let url = format!("{}/stores/{}/write", self.endpoint, self.store_id);
+        let url = format!("{}/stores/{}/write", self.endpoint, store.store_id);
 
         let response = self
-            .client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA write request failed: {}", e)))?;
+            .send(
+                OpenFgaOperation::Write,
+                "write request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -287,7 +849,7 @@ impl OpenFgaService {
         }
 
         // Invalidate cache for this object since permissions may have changed
-        self.cache.invalidate_object(object_type, object_id).await;
+        store.cache.invalidate_object(object_type, object_id).await;
 
         tracing::info!(
             "Successfully wrote relationship: user={}, relation={}, object={}",
@@ -307,6 +869,9 @@ impl OpenFgaService {
         object_type: &str,
         object_id: &str,
     ) -> Result<()> {
+        let store = self.stores.current();
+        registry::validate(object_type, relation)?;
+
         let user = format!("user:{}", user_id);
         let object = format!("{}:{}", object_type, object_id);
 
@@ -321,7 +886,7 @@ impl OpenFgaService {
             deletes: Some(TupleKeys {
                 tuple_keys: vec![tuple_key],
             }),
-            authorization_model_id: self.auth_model_id.clone(),
+            authorization_model_id: store.auth_model_id.clone(),
         };
 
         tracing::debug!(
@@ -331,16 +896,18 @@ impl OpenFgaService {
             object
         );
 
-        let url = format!("{}/stores/{}/write", self.endpoint, self.store_id);
+        let url = format!("{}/stores/{}/write", self.endpoint, store.store_id);
 
         let response = self
-            .client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA delete request failed: {}", e)))?;
+            .send(
+                OpenFgaOperation::Write,
+                "delete request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -352,7 +919,7 @@ impl OpenFgaService {
         }
 
         // Invalidate cache for this object since permissions may have changed
-        self.cache.invalidate_object(object_type, object_id).await;
+        store.cache.invalidate_object(object_type, object_id).await;
 
         tracing::info!(
             "Successfully deleted relationship: user={}, relation={}, object={}",
@@ -365,12 +932,135 @@ impl OpenFgaService {
     }
 
     /// List objects that a user has a specific relation to
+    ///
+    /// At most `auth.openfga.list_objects.max_results` objects are returned;
+    /// a listing cut short is logged. Use [`Self::list_objects_stream`] to
+    /// consume objects as they arrive, or [`Self::list_objects_page`] to hand
+    /// them out a page at a time.
     pub async fn list_objects(
         &self,
         user_id: Uuid,
         relation: &str,
         object_type: &str,
     ) -> Result<Vec<String>> {
+        let (objects, _) = self.collect_objects(user_id, relation, object_type).await?;
+        Ok(objects)
+    }
+
+    /// Stream the objects a user has a specific relation to as OpenFGA finds
+    /// them, up to `auth.openfga.list_objects.max_results`
+    ///
+    /// Uses OpenFGA's streamed list objects endpoint, so objects don't pile
+    /// up on either side; servers without it are listed in one response.
+    pub async fn list_objects_stream(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        Ok(self
+            .object_stream(user_id, relation, object_type)
+            .await?
+            .take(self.list_objects.max_results)
+            .boxed())
+    }
+
+    /// One page of the objects a user has a specific relation to, in sorted
+    /// order
+    ///
+    /// `page_size` defaults to `auth.openfga.list_objects.default_page_size`
+    /// and is capped at `max_page_size`. `continuation_token` is the one the
+    /// previous page returned. Pages are cut from a fresh listing each time,
+    /// so a grant made between pages shows up on a later page if it sorts
+    /// after the token, and not at all otherwise.
+    pub async fn list_objects_page(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+        page_size: Option<u32>,
+        continuation_token: Option<&str>,
+    ) -> Result<ObjectPage> {
+        let after = continuation_token
+            .map(|token| {
+                URL_SAFE_NO_PAD
+                    .decode(token)
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .ok_or_else(|| AppError::Validation("Invalid continuation token".to_string()))
+            })
+            .transpose()?;
+        let page_size = page_size
+            .unwrap_or(self.list_objects.default_page_size)
+            .clamp(1, self.list_objects.max_page_size.max(1)) as usize;
+
+        let (mut objects, truncated) = self.collect_objects(user_id, relation, object_type).await?;
+        objects.sort_unstable();
+        objects.dedup();
+
+        let start = match &after {
+            Some(after) => objects.partition_point(|object| object <= after),
+            None => 0,
+        };
+        let end = (start + page_size).min(objects.len());
+        let continuation_token = (end < objects.len())
+            .then(|| URL_SAFE_NO_PAD.encode(objects[end - 1].as_bytes()));
+
+        Ok(ObjectPage {
+            objects: objects.drain(start..end).collect(),
+            continuation_token,
+            truncated,
+        })
+    }
+
+    /// Read a listing up to the cap, and whether there was more past it
+    async fn collect_objects(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+    ) -> Result<(Vec<String>, bool)> {
+        let max_results = self.list_objects.max_results;
+        let mut objects: Vec<String> = self
+            .object_stream(user_id, relation, object_type)
+            .await?
+            .take(max_results.saturating_add(1))
+            .try_collect()
+            .await?;
+
+        let truncated = objects.len() > max_results;
+        if truncated {
+            objects.truncate(max_results);
+            tracing::warn!(
+                "Listing objects for user={}, relation={}, object_type={} stopped at max_results={}",
+                user_id,
+                relation,
+                object_type,
+                max_results
+            );
+        }
+
+        tracing::debug!(
+            "Listed {} objects for user={}, relation={}, object_type={}",
+            objects.len(),
+            user_id,
+            relation,
+            object_type
+        );
+
+        Ok((objects, truncated))
+    }
+
+    /// Every object the user has the relation to, uncapped
+    async fn object_stream(
+        &self,
+        user_id: Uuid,
+        relation: &str,
+        object_type: &str,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let store = self.stores.current();
+        registry::validate(object_type, relation)?;
+
         let user = format!("user:{}", user_id);
 
         let request = ListObjectsRequest {
@@ -387,16 +1077,49 @@ impl OpenFgaService {
             object_type
         );
 
-        let url = format!("{}/stores/{}/list-objects", self.endpoint, self.store_id);
+        if !self.streaming_unsupported.load(Ordering::Relaxed) {
+            let url = format!("{}/stores/{}/streamed-list-objects", self.endpoint, store.store_id);
+            let response = self
+                .send(
+                    OpenFgaOperation::Read,
+                    "streamed list objects request",
+                    self.client
+                        .post(&url)
+                        .headers(self.build_headers())
+                        .json(&request),
+                )
+                .await?;
+
+            match response.status() {
+                status if status.is_success() => return Ok(streamed_objects(response)),
+                StatusCode::NOT_FOUND | StatusCode::NOT_IMPLEMENTED => {
+                    tracing::info!(
+                        "OpenFGA has no streamed list objects endpoint; listing objects in one response"
+                    );
+                    self.streaming_unsupported.store(true, Ordering::Relaxed);
+                }
+                status => {
+                    let error_text = response.text().await.unwrap_or_default();
+                    return Err(AppError::Internal(format!(
+                        "OpenFGA list objects failed with status {}: {}",
+                        status, error_text
+                    )));
+                }
+            }
+        }
+
+        let url = format!("{}/stores/{}/list-objects", self.endpoint, store.store_id);
 
         let response = self
-            .client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA list objects request failed: {}", e)))?;
+            .send(
+                OpenFgaOperation::Read,
+                "list objects request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -412,15 +1135,416 @@ impl OpenFgaService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA list objects response: {}", e)))?;
 
+        Ok(stream::iter(list_response.objects.into_iter().map(Ok)).boxed())
+    }
+
+    /// List users of `user_type` that have a relation to an object
+    ///
+    /// Unlike tuple reads this goes through the model, so an owner shows up
+    /// when asking for editors if the model says owners can edit. Entries
+    /// are in tuple notation (`user:<id>`, `group:eng#member`, `user:*`);
+    /// `user_relation` asks for usersets such as `group#member` instead.
+    pub async fn list_users(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        relation: &str,
+        user_type: &str,
+        user_relation: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let store = self.stores.current();
+        registry::validate(object_type, relation)?;
+        let user_type = registry::validate_object_type(user_type)?;
+        if let Some(user_relation) = user_relation {
+            registry::validate(user_type.as_str(), user_relation)?;
+        }
+
+        let request = ListUsersRequest {
+            authorization_model_id: store.auth_model_id.clone(),
+            object: FgaObject {
+                object_type: object_type.to_string(),
+                id: object_id.to_string(),
+            },
+            relation: relation.to_string(),
+            user_filters: vec![UserTypeFilter {
+                user_type: user_type.to_string(),
+                relation: user_relation.map(str::to_string),
+            }],
+        };
+
         tracing::debug!(
-            "Listed {} objects for user={}, relation={}, object_type={}",
-            list_response.objects.len(),
-            user,
+            "Listing users: object={}:{}, relation={}, user_type={}",
+            object_type,
+            object_id,
             relation,
-            object_type
+            user_type
         );
 
-        Ok(list_response.objects)
+        let url = format!("{}/stores/{}/list-users", self.endpoint, store.store_id);
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "list users request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            // e.g. a relation the object type doesn't define
+            if status == reqwest::StatusCode::BAD_REQUEST {
+                return Err(AppError::Validation(format!(
+                    "Invalid list users query: {}",
+                    error_text
+                )));
+            }
+            return Err(AppError::Internal(format!(
+                "OpenFGA list users failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let list_response: ListUsersResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA list users response: {}", e)))?;
+
+        Ok(list_response.users.iter().map(ListedUser::to_string).collect())
+    }
+
+    /// Read stored tuples matching `filter`, one page at a time
+    ///
+    /// Only direct tuples are returned, not relations implied by the model,
+    /// so this shows what was written rather than what a check would allow.
+    pub async fn read_tuples(
+        &self,
+        filter: &TupleFilter,
+        page_size: Option<u32>,
+        continuation_token: Option<&str>,
+    ) -> Result<TuplePage> {
+        let store = self.stores.current();
+        let request = ReadRequest {
+            tuple_key: (!filter.is_empty()).then_some(filter),
+            page_size,
+            continuation_token: continuation_token.filter(|token| !token.is_empty()),
+        };
+
+        let url = format!("{}/stores/{}/read", self.endpoint, store.store_id);
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "read request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            // e.g. a user filter without an object type
+            if status == reqwest::StatusCode::BAD_REQUEST {
+                return Err(AppError::Validation(format!(
+                    "Invalid tuple filter: {}",
+                    error_text
+                )));
+            }
+            return Err(AppError::Internal(format!(
+                "OpenFGA read failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let read: ReadResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA read response: {}", e)))?;
+
+        Ok(TuplePage {
+            tuples: read
+                .tuples
+                .into_iter()
+                .map(|tuple| RelationshipTuple {
+                    user: tuple.key.user,
+                    relation: tuple.key.relation,
+                    object: tuple.key.object,
+                    timestamp: tuple.timestamp,
+                })
+                .collect(),
+            continuation_token: read.continuation_token,
+        })
+    }
+
+    /// Read the store's change log after `continuation_token`, or from
+    /// `start_time` when there is no token yet
+    pub async fn read_changes(
+        &self,
+        continuation_token: Option<&str>,
+        start_time: Option<DateTime<Utc>>,
+        page_size: Option<u32>,
+    ) -> Result<ChangesPage> {
+        let store = self.stores.current();
+        let url = format!("{}/stores/{}/changes", self.endpoint, store.store_id);
+
+        let mut query: Vec<(&str, String)> = Vec::new();
+        if let Some(page_size) = page_size {
+            query.push(("page_size", page_size.to_string()));
+        }
+        match continuation_token.filter(|token| !token.is_empty()) {
+            Some(token) => query.push(("continuation_token", token.to_string())),
+            None => {
+                if let Some(start_time) = start_time {
+                    query.push(("start_time", start_time.to_rfc3339_opts(SecondsFormat::Millis, true)));
+                }
+            }
+        }
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "changes request",
+                self.client
+                    .get(&url)
+                    .headers(self.build_headers())
+                    .query(&query),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "OpenFGA changes read failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA changes response: {}", e)))
+    }
+
+    /// Drop cached checks a tuple change may have affected
+    ///
+    /// A direct tuple for one user can change any relation that user derives
+    /// from it, so all of that user's entries go. Usersets (`group:eng#member`),
+    /// wildcards and parent links reach users the cache can't enumerate, so
+    /// they clear the whole cache.
+    pub async fn invalidate_for_change(&self, change: &TupleChange) {
+        let store = self.stores.current();
+        let key = &change.tuple_key;
+        let parent_link = registry::Relation::parse(&key.relation)
+            .is_some_and(|relation| relation.is_parent_link());
+        let user_id = key
+            .user
+            .strip_prefix("user:")
+            .and_then(|id| Uuid::parse_str(id).ok());
+
+        match user_id {
+            Some(user_id) if !parent_link => store.cache.invalidate_user(user_id).await,
+            _ => store.cache.clear().await,
+        }
+    }
+
+    /// Upload a new authorization model; OpenFGA keeps every version
+    ///
+    /// Checks use the newest model unless `auth_model_id` pins one, so the
+    /// permission cache is cleared either way.
+    pub async fn write_authorization_model(&self, model: &AuthorizationModel) -> Result<String> {
+        let store = self.stores.current();
+        if model.type_definitions.is_empty() {
+            return Err(AppError::Validation(
+                "Authorization model needs at least one type definition".to_string(),
+            ));
+        }
+
+        let url = format!("{}/stores/{}/authorization-models", self.endpoint, store.store_id);
+
+        let response = self
+            .send(
+                OpenFgaOperation::Write,
+                "model write request",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&AuthorizationModel {
+                        id: None,
+                        ..model.clone()
+                    }),
+            )
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            // Invalid models come back as 400 with OpenFGA's explanation
+            if status == reqwest::StatusCode::BAD_REQUEST {
+                return Err(AppError::Validation(format!(
+                    "Invalid authorization model: {}",
+                    error_text
+                )));
+            }
+            return Err(AppError::Internal(format!(
+                "OpenFGA model write failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let written: WriteAuthorizationModelResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA model write response: {}", e)))?;
+
+        store.cache.clear().await;
+
+        tracing::info!(
+            authorization_model_id = %written.authorization_model_id,
+            pinned = ?store.auth_model_id,
+            "Wrote OpenFGA authorization model"
+        );
+
+        Ok(written.authorization_model_id)
+    }
+
+    /// Read one authorization model by ID
+    pub async fn read_authorization_model(&self, model_id: &str) -> Result<AuthorizationModel> {
+        let store = self.stores.current();
+        let url = format!(
+            "{}/stores/{}/authorization-models/{}",
+            self.endpoint, store.store_id, model_id
+        );
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "model request",
+                self.client
+                    .get(&url)
+                    .headers(self.build_headers()),
+            )
+            .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::BAD_REQUEST {
+            return Err(AppError::NotFound("Authorization model not found".to_string()));
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "OpenFGA model read failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let read: ReadAuthorizationModelResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA model response: {}", e)))?;
+
+        Ok(read.authorization_model)
+    }
+
+    /// List the store's authorization models, newest first
+    pub async fn list_authorization_models(
+        &self,
+        page_size: Option<u32>,
+        continuation_token: Option<&str>,
+    ) -> Result<AuthorizationModelPage> {
+        let store = self.stores.current();
+        let url = format!("{}/stores/{}/authorization-models", self.endpoint, store.store_id);
+
+        let mut query: Vec<(&str, String)> = Vec::new();
+        if let Some(page_size) = page_size {
+            query.push(("page_size", page_size.to_string()));
+        }
+        if let Some(token) = continuation_token.filter(|token| !token.is_empty()) {
+            query.push(("continuation_token", token.to_string()));
+        }
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "model list request",
+                self.client
+                    .get(&url)
+                    .headers(self.build_headers())
+                    .query(&query),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "OpenFGA model list failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse OpenFGA model list response: {}", e)))
+    }
+
+    /// Make `model` the store's latest model and use it from now on
+    ///
+    /// A new version is only written when the definition differs from the
+    /// latest one, so restarting with the same file doesn't pile up model
+    /// versions. The resulting ID replaces any configured `auth_model_id`.
+    pub async fn sync_authorization_model(&mut self, model: &AuthorizationModel) -> Result<String> {
+        let latest = self
+            .list_authorization_models(Some(1), None)
+            .await?
+            .authorization_models
+            .into_iter()
+            .next();
+
+        let model_id = match latest {
+            Some(latest) if latest.same_definition(model) => {
+                let id = latest.id.ok_or_else(|| {
+                    AppError::Internal("OpenFGA returned a model without an ID".to_string())
+                })?;
+                tracing::info!(authorization_model_id = %id, "OpenFGA authorization model is up to date");
+                id
+            }
+            _ => self.write_authorization_model(model).await?,
+        };
+
+        self.stores.current_mut().auth_model_id = Some(model_id.clone());
+        Ok(model_id)
+    }
+
+    /// The model ID sent with checks and writes, if pinned
+    pub fn auth_model_id(&self) -> Option<&str> {
+        self.stores.current().auth_model_id.as_deref()
+    }
+
+    /// The model checks run against: the pinned one, or the store's latest
+    pub async fn active_model(&self) -> Result<AuthorizationModel> {
+        if let Some(model_id) = self.auth_model_id() {
+            return self.read_authorization_model(model_id).await;
+        }
+        self.list_authorization_models(Some(1), None)
+            .await?
+            .authorization_models
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound("OpenFGA store has no authorization model".to_string()))
+    }
+
+    /// Registered types and relations the active model lacks
+    pub async fn preflight_model(&self) -> Result<registry::ModelDiff> {
+        Ok(registry::diff_model(&self.active_model().await?))
     }
 
     /// Health check for OpenFGA service
@@ -428,21 +1552,88 @@ impl OpenFgaService {
         let url = format!("{}/healthz", self.endpoint);
 
         let response = self
-            .client
-            .get(&url)
-            .timeout(std::time::Duration::from_secs(5))
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA health check failed: {}", e)))?;
+            .send(
+                OpenFgaOperation::Read,
+                "health check",
+                self.client.get(&url),
+            )
+            .await?;
 
         Ok(response.status().is_success())
     }
 
+    /// Establish connections to OpenFGA and load every store's authorization
+    /// model
+    pub async fn warm_up(&self) -> Result<()> {
+        if !self.health_check().await? {
+            return Err(AppError::Internal("OpenFGA is not healthy".to_string()));
+        }
+
+        for store in self.stores.all() {
+            self.load_model(store).await?;
+        }
+        Ok(())
+    }
+
+    /// Whether each store's authorization model can be read, shared store
+    /// first; `None` for the shared store's tenant
+    pub async fn store_health(&self) -> Vec<(Option<String>, Result<()>)> {
+        let mut health = Vec::new();
+        for store in self.stores.all() {
+            health.push((store.tenant.clone(), self.load_model(store).await));
+        }
+        health
+    }
+
+    /// Tenants with a dedicated store
+    pub fn dedicated_tenants(&self) -> Vec<String> {
+        self.stores.tenants().map(str::to_string).collect()
+    }
+
+    /// Read the store's pinned model, or its latest one
+    async fn load_model(&self, store: &OpenFgaStore) -> Result<()> {
+        let url = match &store.auth_model_id {
+            Some(model_id) => format!(
+                "{}/stores/{}/authorization-models/{}",
+                self.endpoint, store.store_id, model_id
+            ),
+            None => format!(
+                "{}/stores/{}/authorization-models?page_size=1",
+                self.endpoint, store.store_id
+            ),
+        };
+
+        let response = self
+            .send(
+                OpenFgaOperation::Read,
+                "model request",
+                self.client
+                    .get(&url)
+                    .headers(self.build_headers()),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "OpenFGA model read for store {} failed with status {}: {}",
+                store.store_id, status, error_text
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Batch write multiple relationships
     pub async fn batch_write_relationships(&self, relationships: Vec<(Uuid, &str, &str, &str)>) -> Result<()> {
+        let store = self.stores.current();
         if relationships.is_empty() {
             return Ok(());
         }
+        for (_, relation, object_type, _) in &relationships {
+            registry::validate(object_type, relation)?;
+        }
 
         // Clone the relationships for cache invalidation before consuming them
         let objects_to_invalidate: Vec<(String, String)> = relationships
@@ -462,19 +1653,21 @@ impl OpenFgaService {
         let request = WriteRequest {
             writes: Some(TupleKeys { tuple_keys }),
             deletes: None,
-            authorization_model_id: self.auth_model_id.clone(),
+            authorization_model_id: store.auth_model_id.clone(),
         };
 
-        let url = format!("{}/stores/{}/write", self.endpoint, self.store_id);
+        let url = format!("{}/stores/{}/write", self.endpoint, store.store_id);
 
         let response = self
-            .client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("OpenFGA batch write failed: {}", e)))?;
+            .send(
+                OpenFgaOperation::Write,
+                "batch write",
+                self.client
+                    .post(&url)
+                    .headers(self.build_headers())
+                    .json(&request),
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -489,34 +1682,159 @@ impl OpenFgaService {
 
         // Invalidate cache for all affected objects
         for (object_type, object_id) in objects_to_invalidate {
-            self.cache.invalidate_object(&object_type, &object_id).await;
+            store.cache.invalidate_object(&object_type, &object_id).await;
         }
 
         Ok(())
     }
 
-    /// Get cache statistics
+    /// Write tuples with any subject, e.g. `organization:acme` as the
+    /// `organization` of `project:website`
+    ///
+    /// Sent in writes of at most 100 tuples, OpenFGA's limit. Writing a
+    /// tuple that already exists fails the whole write.
+    pub async fn write_tuples(&self, tuples: &[RelationshipTuple]) -> Result<()> {
+        let store = self.stores.current();
+        for tuple in tuples {
+            let (object_type, _) = tuple.object.split_once(':').ok_or_else(|| {
+                AppError::Validation(format!("Object '{}' is not of the form type:id", tuple.object))
+            })?;
+            registry::validate(object_type, &tuple.relation)?;
+        }
+        let url = format!("{}/stores/{}/write", self.endpoint, store.store_id);
+
+        for chunk in tuples.chunks(MAX_TUPLES_PER_WRITE) {
+            let request = WriteRequest {
+                writes: Some(TupleKeys {
+                    tuple_keys: chunk
+                        .iter()
+                        .map(|tuple| TupleKey {
+                            user: tuple.user.clone(),
+                            relation: tuple.relation.clone(),
+                            object: tuple.object.clone(),
+                        })
+                        .collect(),
+                }),
+                deletes: None,
+                authorization_model_id: store.auth_model_id.clone(),
+            };
+
+            let response = self
+                .send(
+                    OpenFgaOperation::Write,
+                    "batch write",
+                    self.client
+                        .post(&url)
+                        .headers(self.build_headers())
+                        .json(&request),
+                )
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(AppError::Internal(format!(
+                    "OpenFGA batch write failed with status {}: {}",
+                    status, error_text
+                )));
+            }
+
+            for tuple in chunk {
+                if let Some((object_type, object_id)) = tuple.object.split_once(':') {
+                    store.cache.invalidate_object(object_type, object_id).await;
+                }
+            }
+        }
+
+        tracing::info!("Successfully wrote {} relationships in batch", tuples.len());
+
+        Ok(())
+    }
+
+    /// Delete stored tuples, e.g. a page returned by [`Self::read_tuples`]
+    ///
+    /// Sent in writes of at most 100 tuples, OpenFGA's limit. Deleting a
+    /// tuple that no longer exists fails the whole write.
+    pub async fn delete_tuples(&self, tuples: &[RelationshipTuple]) -> Result<()> {
+        let store = self.stores.current();
+        let url = format!("{}/stores/{}/write", self.endpoint, store.store_id);
+
+        for chunk in tuples.chunks(MAX_TUPLES_PER_WRITE) {
+            let request = WriteRequest {
+                writes: None,
+                deletes: Some(TupleKeys {
+                    tuple_keys: chunk
+                        .iter()
+                        .map(|tuple| TupleKey {
+                            user: tuple.user.clone(),
+                            relation: tuple.relation.clone(),
+                            object: tuple.object.clone(),
+                        })
+                        .collect(),
+                }),
+                authorization_model_id: store.auth_model_id.clone(),
+            };
+
+            let response = self
+                .send(
+                    OpenFgaOperation::Write,
+                    "batch delete",
+                    self.client
+                        .post(&url)
+                        .headers(self.build_headers())
+                        .json(&request),
+                )
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(AppError::Internal(format!(
+                    "OpenFGA batch delete failed with status {}: {}",
+                    status, error_text
+                )));
+            }
+
+            for tuple in chunk {
+                if let Some((object_type, object_id)) = tuple.object.split_once(':') {
+                    store.cache.invalidate_object(object_type, object_id).await;
+                }
+            }
+        }
+
+        tracing::info!("Successfully deleted {} relationships in batch", tuples.len());
+
+        Ok(())
+    }
+
+    /// Get cache statistics for the current tenant's store
     pub async fn cache_stats(&self) -> crate::auth::cache::CacheStats {
-        self.cache.stats().await
+        self.stores.current().cache.stats().await
     }
 
-    /// Clear permission cache
+    /// Clear every store's permission cache
     pub async fn clear_cache(&self) {
-        self.cache.clear().await;
+        for store in self.stores.all() {
+            store.cache.clear().await;
+        }
     }
 
-    /// Invalidate cache for a specific user
+    /// Invalidate cache for a specific user, in every store
     pub async fn invalidate_user_cache(&self, user_id: Uuid) {
-        self.cache.invalidate_user(user_id).await;
+        for store in self.stores.all() {
+            store.cache.invalidate_user(user_id).await;
+        }
     }
 
-    /// Invalidate cache for a specific object
+    /// Invalidate cache for a specific object, in every store
     pub async fn invalidate_object_cache(&self, object_type: &str, object_id: &str) {
-        self.cache.invalidate_object(object_type, object_id).await;
+        for store in self.stores.all() {
+            store.cache.invalidate_object(object_type, object_id).await;
+        }
     }
 }
 
-/// Helper functions for common authorization patterns
+// Helper functions for common authorization patterns
 
 /// Check if a user can read a resource
 pub async fn can_read(
@@ -556,3 +1874,68 @@ pub async fn is_owner(
         .await?;
     Ok(result.allowed)
 }
+
+/// Objects from a streamed list objects response, one JSON document per line
+///
+/// A line carrying an error, or a broken connection, ends the stream with an
+/// error.
+fn streamed_objects(response: reqwest::Response) -> BoxStream<'static, Result<String>> {
+    let chunks = response.bytes_stream().boxed();
+    stream::unfold(Some((chunks, Vec::new())), |state| async move {
+        let (mut chunks, mut buffer) = state?;
+        loop {
+            if let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                match parse_streamed_line(&line) {
+                    Some(Ok(object)) => return Some((Ok(object), Some((chunks, buffer)))),
+                    Some(Err(e)) => return Some((Err(e), None)),
+                    None => continue,
+                }
+            }
+
+            match chunks.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    return Some((
+                        Err(AppError::Internal(format!("OpenFGA streamed list objects failed: {}", e))),
+                        None,
+                    ))
+                }
+                // The last line may have no newline after it
+                None => {
+                    return parse_streamed_line(&std::mem::take(&mut buffer)).map(|result| {
+                        let state = result.is_ok().then(|| (chunks, Vec::new()));
+                        (result, state)
+                    })
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+/// `None` for a blank line
+fn parse_streamed_line(line: &[u8]) -> Option<Result<String>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+
+    let parsed: StreamedListObjectsLine = match serde_json::from_slice(line) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Some(Err(AppError::Internal(format!(
+                "Failed to parse OpenFGA streamed list objects response: {}",
+                e
+            ))))
+        }
+    };
+    match (parsed.result, parsed.error) {
+        (_, Some(error)) => Some(Err(AppError::Internal(format!(
+            "OpenFGA streamed list objects failed: {}",
+            error
+        )))),
+        (Some(result), None) => Some(Ok(result.object)),
+        (None, None) => None,
+    }
+}
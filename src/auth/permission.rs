@@ -0,0 +1,106 @@
+//! Authorization checks declared in handler signatures.
+//!
+//! ```ignore
+//! crate::required_permission!(pub ViewDocument = Document.Viewer on "id");
+//!
+//! async fn get_document(
+//!     RequirePermission { object_id, .. }: RequirePermission<ViewDocument>,
+//! ) -> Result<Json<Document>> { ... }
+//! ```
+//!
+//! Extraction checks the permission against the matched route's path
+//! parameter with the state's [`Authorizer`] (via `FromRef`), so the handler
+//! only runs once OpenFGA has allowed the call.
+
+use crate::auth::authorizer::Authorizer;
+use crate::auth::models::AuthContext;
+use crate::auth::registry::Permission;
+use crate::errors::AppError;
+use axum::{
+    extract::{FromRef, FromRequestParts, RawPathParams},
+    http::request::Parts,
+};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A permission on the object a route parameter names
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+    /// Path parameter holding the object id
+    const PARAM: &'static str = "id";
+}
+
+/// Declare a [`RequiredPermission`]; the pair is checked against the
+/// registry at compile time
+#[macro_export]
+macro_rules! required_permission {
+    ($vis:vis $name:ident = $object_type:ident . $relation:ident on $param:literal) => {
+        $vis struct $name;
+
+        impl $crate::auth::permission::RequiredPermission for $name {
+            const PERMISSION: $crate::auth::registry::Permission = $crate::auth::registry::Permission::new(
+                $crate::auth::registry::ObjectType::$object_type,
+                $crate::auth::registry::Relation::$relation,
+            );
+            const PARAM: &'static str = $param;
+        }
+    };
+}
+
+/// Extractor that allows the request only if the caller holds `P`
+///
+/// Yields the checked object id and the caller. Rejects with 401 without an
+/// authenticated caller, 403 when the check denies, and 500 when the route
+/// has no `P::PARAM` parameter.
+pub struct RequirePermission<P> {
+    pub object_id: String,
+    pub auth_context: AuthContext,
+    permission: PhantomData<fn() -> P>,
+}
+
+impl<P, S> FromRequestParts<S> for RequirePermission<P>
+where
+    P: RequiredPermission,
+    Arc<dyn Authorizer>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_context = parts
+            .extensions
+            .get::<AuthContext>()
+            .cloned()
+            .ok_or(AppError::Unauthorized)?;
+
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+        let object_id = params
+            .iter()
+            .find(|(name, _)| *name == P::PARAM)
+            .map(|(_, value)| value.to_string())
+            .ok_or_else(|| {
+                AppError::Internal(format!("Route has no '{}' path parameter", P::PARAM))
+            })?;
+
+        let permission = P::PERMISSION;
+        let result = Arc::<dyn Authorizer>::from_ref(state)
+            .check(
+                auth_context.subject(),
+                permission.relation.as_str(),
+                permission.object_type.as_str(),
+                &object_id,
+            )
+            .await?;
+        if !result.allowed {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(RequirePermission {
+            object_id,
+            auth_context,
+            permission: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,168 @@
+use crate::config::LoginRateLimitConfig;
+use crate::errors::AppError;
+use crate::request_context;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Login/register bodies are small; anything larger is rejected
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Token buckets keyed by an arbitrary string
+#[derive(Debug)]
+pub struct TokenBuckets {
+    buckets: Mutex<HashMap<String, Bucket>>,
+    capacity: f64,
+    refill_per_sec: f64,
+    max_entries: usize,
+}
+
+impl TokenBuckets {
+    pub fn new(burst: u32, per_minute: u32, max_entries: usize) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            capacity: f64::from(burst.max(1)),
+            refill_per_sec: f64::from(per_minute.max(1)) / 60.0,
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// Take a token for `key`, or return how long until one is available
+    pub fn try_acquire(&self, key: &str) -> Result<(), Duration> {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    pub fn try_acquire_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if !buckets.contains_key(key) && buckets.len() >= self.max_entries {
+            // Buckets that have refilled completely carry no state
+            buckets.retain(|_, bucket| self.refilled(bucket, now) < self.capacity);
+            // Still full: make room by dropping the bucket idle the longest,
+            // so new keys keep being limited
+            if buckets.len() >= self.max_entries {
+                let oldest = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.updated_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    tracing::warn!(max_entries = self.max_entries, "Rate limiter is full, evicting the oldest bucket");
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated_at: now,
+        });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec))
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+/// Limits credential attempts per client IP and per email address
+///
+/// The client IP is the connecting peer, or with `trusted_proxy_hops` set,
+/// the `X-Forwarded-For` entry the outermost trusted proxy appended; see
+/// [`request_context::forwarded_client_ip`].
+#[derive(Debug)]
+pub struct LoginRateLimiter {
+    per_ip: TokenBuckets,
+    per_account: TokenBuckets,
+    trusted_proxy_hops: usize,
+}
+
+impl LoginRateLimiter {
+    pub fn new(config: &LoginRateLimitConfig) -> Self {
+        Self {
+            per_ip: TokenBuckets::new(config.ip_burst, config.ip_per_minute, config.max_entries),
+            per_account: TokenBuckets::new(
+                config.account_burst,
+                config.account_per_minute,
+                config.max_entries,
+            ),
+            trusted_proxy_hops: config.trusted_proxy_hops,
+        }
+    }
+
+    /// Build the limiter from config, or `None` when disabled
+    pub fn from_config(config: &LoginRateLimitConfig) -> Option<Arc<Self>> {
+        config.enabled.then(|| Arc::new(Self::new(config)))
+    }
+
+    pub fn check(&self, ip: Option<&str>, email: Option<&str>) -> Result<(), Duration> {
+        if let Some(ip) = ip {
+            self.per_ip.try_acquire(ip)?;
+        }
+        if let Some(email) = email {
+            self.per_account.try_acquire(&email.trim().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct EmailField {
+    /// A service account's client id is throttled like an email
+    #[serde(alias = "client_id")]
+    email: Option<String>,
+}
+
+/// Middleware for `/login`, `/register` and `/token`: 429 with `Retry-After` when over the limit
+pub async fn login_rate_limit_middleware(
+    State(limiter): State<Arc<LoginRateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Only missing when served without connect info, as in tests
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| *peer);
+    let ip = request_context::forwarded_client_ip(request.headers(), peer, limiter.trusted_proxy_hops);
+
+    // The email is in the JSON body, which has to be put back for the handler
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return AppError::BadRequest("Request body too large".to_string()).into_response(),
+    };
+    let email = serde_json::from_slice::<EmailField>(&bytes)
+        .ok()
+        .and_then(|field| field.email);
+
+    if let Err(wait) = limiter.check(ip.as_deref(), email.as_deref()) {
+        tracing::warn!(ip = ?ip, "Login rate limit exceeded");
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        return AppError::TooManyRequests(retry_after).into_response();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
@@ -0,0 +1,243 @@
+//! Typed OpenFGA object types and the relations each one defines.
+//!
+//! Mirrors `config/openfga-model.json`. The string constants in
+//! [`crate::auth::models::object_types`] and [`crate::auth::models::relations`]
+//! are derived from these enums, and every call into OpenFGA goes through
+//! [`validate`], so an object type / relation pair the model doesn't have is
+//! rejected up front instead of coming back as a deny. [`Permission::new`]
+//! does the same check at compile time when used in a `const`, and
+//! [`check_model`] compares the registry with a model file at startup.
+
+use crate::auth::openfga::AuthorizationModel;
+use crate::errors::{AppError, Result};
+use std::fmt;
+
+macro_rules! fga_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $label:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant),+
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $label,)+
+                }
+            }
+
+            pub fn parse(value: &str) -> Option<Self> {
+                match value {
+                    $($label => Some($name::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+fga_enum! {
+    /// Object types in the authorization model
+    pub enum ObjectType {
+        User => "user",
+        Organization => "organization",
+        Project => "project",
+        Document => "document",
+        /// Service accounts, as subjects only
+        Service => "service",
+    }
+}
+
+fga_enum! {
+    /// Relations defined on at least one object type
+    pub enum Relation {
+        Owner => "owner",
+        Admin => "admin",
+        Editor => "editor",
+        Viewer => "viewer",
+        Member => "member",
+        /// Parent organization of a project
+        Organization => "organization",
+        /// Parent project of a document
+        Project => "project",
+    }
+}
+
+impl ObjectType {
+    /// Relations the model defines on this type
+    pub const fn relations(self) -> &'static [Relation] {
+        match self {
+            ObjectType::User | ObjectType::Service => &[],
+            ObjectType::Organization => &[Relation::Admin, Relation::Member, Relation::Owner],
+            ObjectType::Project => &[
+                Relation::Admin,
+                Relation::Editor,
+                Relation::Organization,
+                Relation::Owner,
+                Relation::Viewer,
+            ],
+            ObjectType::Document => &[
+                Relation::Editor,
+                Relation::Owner,
+                Relation::Project,
+                Relation::Viewer,
+            ],
+        }
+    }
+
+    pub const fn has_relation(self, relation: Relation) -> bool {
+        let relations = self.relations();
+        let mut i = 0;
+        while i < relations.len() {
+            if relations[i] as u8 == relation as u8 {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+impl Relation {
+    /// Links an object to its parent object rather than granting a user anything
+    pub const fn is_parent_link(self) -> bool {
+        matches!(self, Relation::Organization | Relation::Project)
+    }
+}
+
+/// A relation on an object type that the model defines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permission {
+    pub object_type: ObjectType,
+    pub relation: Relation,
+}
+
+impl Permission {
+    /// Panics for a pair the model doesn't define, which is a compile error
+    /// in a `const`:
+    ///
+    /// ```ignore
+    /// const CAN_VIEW: Permission = Permission::new(ObjectType::Document, Relation::Viewer);
+    /// ```
+    pub const fn new(object_type: ObjectType, relation: Relation) -> Self {
+        assert!(
+            object_type.has_relation(relation),
+            "relation is not defined on this object type"
+        );
+        Self {
+            object_type,
+            relation,
+        }
+    }
+}
+
+/// Resolve and check a stringly-typed object type / relation pair
+pub fn validate(object_type: &str, relation: &str) -> Result<Permission> {
+    let object_type = ObjectType::parse(object_type).ok_or_else(|| {
+        AppError::Validation(format!("Unknown object type '{}'", object_type))
+    })?;
+    let relation = Relation::parse(relation)
+        .filter(|relation| object_type.has_relation(*relation))
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Relation '{}' is not defined on '{}'",
+                relation, object_type
+            ))
+        })?;
+
+    Ok(Permission {
+        object_type,
+        relation,
+    })
+}
+
+/// Check an object type name, e.g. the user type of a list-users query
+pub fn validate_object_type(object_type: &str) -> Result<ObjectType> {
+    ObjectType::parse(object_type)
+        .ok_or_else(|| AppError::Validation(format!("Unknown object type '{}'", object_type)))
+}
+
+/// What the registry expects that an authorization model doesn't define
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelDiff {
+    /// Registered types the model has no definition for
+    pub missing_types: Vec<ObjectType>,
+    /// Registered relations missing from types the model does define
+    pub missing_relations: Vec<Permission>,
+}
+
+impl ModelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing_types.is_empty() && self.missing_relations.is_empty()
+    }
+}
+
+impl fmt::Display for ModelDiff {
+    /// `project, document#viewer, document#editor`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing: Vec<String> = self
+            .missing_types
+            .iter()
+            .map(ToString::to_string)
+            .chain(
+                self.missing_relations
+                    .iter()
+                    .map(|permission| format!("{}#{}", permission.object_type, permission.relation)),
+            )
+            .collect();
+        f.write_str(&missing.join(", "))
+    }
+}
+
+/// Registered types and relations `model` lacks
+///
+/// Types and relations only the model has are allowed; the service just
+/// can't address them until they're registered here.
+pub fn diff_model(model: &AuthorizationModel) -> ModelDiff {
+    let mut diff = ModelDiff::default();
+    for &object_type in ObjectType::ALL {
+        let definition = model
+            .type_definitions
+            .iter()
+            .find(|definition| definition["type"] == object_type.as_str());
+        let Some(definition) = definition else {
+            diff.missing_types.push(object_type);
+            continue;
+        };
+        for &relation in object_type.relations() {
+            if definition["relations"].get(relation.as_str()).is_none() {
+                diff.missing_relations.push(Permission {
+                    object_type,
+                    relation,
+                });
+            }
+        }
+    }
+    diff
+}
+
+/// Every registered type and relation must exist in `model`
+pub fn check_model(model: &AuthorizationModel) -> Result<()> {
+    let diff = diff_model(model);
+    if !diff.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Authorization model is missing registered types or relations: {}",
+            diff
+        )));
+    }
+    Ok(())
+}
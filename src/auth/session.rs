@@ -0,0 +1,335 @@
+use crate::auth::cache::SessionCache;
+use crate::auth::jwt::JwtService;
+use crate::auth::models::{AuthContext, SubjectType, UserTokenState};
+use crate::config::{RememberMeConfig, SessionCacheConfig};
+use crate::errors::{AppError, Result};
+use crate::models::format::ResponseFormat;
+use crate::models::UserStatus;
+use crate::repositories::{AuthRepository, UserRepository};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// The caller a personal access token acts as, with the token's settings
+#[derive(Debug, Clone)]
+pub struct PersonalTokenCaller {
+    pub context: AuthContext,
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Checks that the session behind a token hasn't been revoked or expired,
+/// and resolves personal access tokens
+pub struct SessionValidator {
+    repository: AuthRepository,
+    users: UserRepository,
+    cache: SessionCache,
+    /// How far a check pushes out a `remember_me` session's expiry
+    idle_timeout: chrono::Duration,
+    /// Issues tokens with the current token version; `None` leaves token
+    /// versions unchecked
+    jwt_service: Option<Arc<JwtService>>,
+    /// How long a token version read from the database is trusted
+    version_ttl: Duration,
+    version_checked_at: Mutex<Option<Instant>>,
+    /// Each user's token state and when it was read, at most
+    /// `session_cache.max_entries` of them
+    user_states: Mutex<HashMap<Uuid, (UserTokenState, Instant)>>,
+    /// Whether users' statuses are checked, and each one's as of when it
+    /// was read (`None` for users that don't exist), bounded like
+    /// `user_states`
+    check_statuses: bool,
+    user_statuses: Mutex<HashMap<Uuid, (Option<UserStatus>, Instant)>>,
+    max_user_states: usize,
+}
+
+impl SessionValidator {
+    pub fn new(repository: AuthRepository, users: UserRepository, config: &SessionCacheConfig) -> Self {
+        Self {
+            repository,
+            users,
+            cache: SessionCache::new(Duration::from_secs(config.ttl_seconds), config.max_entries),
+            idle_timeout: RememberMeConfig::default().idle_timeout(),
+            jwt_service: None,
+            version_ttl: Duration::from_secs(config.ttl_seconds),
+            version_checked_at: Mutex::new(None),
+            user_states: Mutex::new(HashMap::new()),
+            check_statuses: false,
+            user_statuses: Mutex::new(HashMap::new()),
+            max_user_states: config.max_entries,
+        }
+    }
+
+    /// Extend `remember_me` sessions by `config.idle_timeout_hours` on use
+    pub fn with_remember_me(mut self, config: &RememberMeConfig) -> Self {
+        self.idle_timeout = config.idle_timeout();
+        self
+    }
+
+    /// Reject tokens issued before the token version, or their user's, was
+    /// last bumped, and keep `jwt_service` issuing tokens with the current
+    /// version
+    ///
+    /// Versions are read again after `session_cache.ttl_seconds`, so a bump
+    /// made by another instance takes effect within one TTL.
+    pub fn with_token_version(mut self, jwt_service: Arc<JwtService>) -> Self {
+        self.jwt_service = Some(jwt_service);
+        self
+    }
+
+    /// Refuse users who aren't active, see [`user_status`](Self::user_status)
+    pub fn with_user_status(mut self) -> Self {
+        self.check_statuses = true;
+        self
+    }
+
+    /// Whether a token issued at `version` is still accepted; always true
+    /// when token versions aren't checked
+    ///
+    /// When the version can't be read, the last one seen is used until the
+    /// next read, one TTL later.
+    pub async fn is_token_version_current(&self, version: i64) -> bool {
+        let Some(jwt_service) = &self.jwt_service else {
+            return true;
+        };
+
+        let fresh = self
+            .version_checked_at
+            .lock()
+            .unwrap()
+            .is_some_and(|checked_at| checked_at.elapsed() < self.version_ttl);
+        if !fresh {
+            match self.repository.token_version().await {
+                // The version only goes up; don't step back past a bump made here
+                Ok(current) => {
+                    jwt_service.set_token_version(current.max(jwt_service.token_version()))
+                }
+                Err(e) => tracing::warn!(error = %e, "Failed to read the token version"),
+            }
+            *self.version_checked_at.lock().unwrap() = Some(Instant::now());
+        }
+
+        version >= jwt_service.token_version()
+    }
+
+    /// Invalidate every token issued so far, returning the new version
+    pub async fn bump_token_version(&self) -> Result<i64> {
+        let Some(jwt_service) = &self.jwt_service else {
+            return Err(AppError::Internal("Token versions are not enabled".to_string()));
+        };
+
+        let version = self.repository.bump_token_version().await?;
+        jwt_service.set_token_version(version);
+        *self.version_checked_at.lock().unwrap() = Some(Instant::now());
+        Ok(version)
+    }
+
+    /// `user_id`'s token version and when their roles last changed, to
+    /// check their tokens against; `None` when token versions aren't checked
+    ///
+    /// Like the global version, the last state seen stands when it can't be
+    /// read.
+    pub async fn user_token_state(&self, user_id: Uuid) -> Option<UserTokenState> {
+        self.jwt_service.as_ref()?;
+
+        let cached = self.user_states.lock().unwrap().get(&user_id).copied();
+        if let Some((state, read_at)) = cached {
+            if read_at.elapsed() < self.version_ttl {
+                return Some(state);
+            }
+        }
+
+        let last_seen = cached.map(|(state, _)| state).unwrap_or_default();
+        let state = match self.repository.user_token_state(user_id).await {
+            // Neither goes back; don't step back past a change made here
+            Ok(state) => UserTokenState {
+                token_version: state.token_version.max(last_seen.token_version),
+                roles_updated_at: state.roles_updated_at.max(last_seen.roles_updated_at),
+            },
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, error = %e, "Failed to read the user's token state");
+                last_seen
+            }
+        };
+        self.remember_user_state(user_id, state);
+        Some(state)
+    }
+
+    /// Invalidate every token issued to `user_id` so far, e.g. after a
+    /// password reset, returning the new version
+    pub async fn bump_user_token_version(&self, user_id: Uuid) -> Result<i64> {
+        let state = self.repository.bump_user_token_version(user_id).await?;
+        self.remember_user_state(user_id, state);
+        Ok(state.token_version)
+    }
+
+    /// Have `user_id`'s tokens refreshed after a change to their roles
+    pub async fn roles_changed(&self, user_id: Uuid) -> Result<()> {
+        let state = self.repository.touch_user_roles(user_id).await?;
+        self.remember_user_state(user_id, state);
+        Ok(())
+    }
+
+    fn remember_user_state(&self, user_id: Uuid, state: UserTokenState) {
+        self.remember(&self.user_states, user_id, state);
+    }
+
+    /// `user_id`'s account status, or `None` when there is no such user;
+    /// always active when statuses aren't checked
+    ///
+    /// Statuses are read again after `session_cache.ttl_seconds`, so a
+    /// suspension made by another instance takes effect within one TTL. When
+    /// the status can't be read the last one seen stands, and a user not
+    /// seen before counts as active, so a database outage doesn't lock
+    /// everyone out.
+    pub async fn user_status(&self, user_id: Uuid) -> Option<UserStatus> {
+        if !self.check_statuses {
+            return Some(UserStatus::Active);
+        }
+
+        let cached = self.user_statuses.lock().unwrap().get(&user_id).copied();
+        if let Some((status, read_at)) = cached {
+            if read_at.elapsed() < self.version_ttl {
+                return status;
+            }
+        }
+
+        let status = match self.users.find_status(user_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, error = %e, "Failed to read the user's status");
+                cached.map_or(Some(UserStatus::Active), |(status, _)| status)
+            }
+        };
+        self.remember(&self.user_statuses, user_id, status);
+        status
+    }
+
+    /// Apply a status change here right away rather than after the TTL
+    pub fn status_changed(&self, user_id: Uuid, status: UserStatus) {
+        self.remember(&self.user_statuses, user_id, Some(status));
+    }
+
+    /// Refuse a deleted user here right away rather than after the TTL
+    pub fn user_deleted(&self, user_id: Uuid) {
+        self.remember(&self.user_statuses, user_id, None);
+    }
+
+    fn remember<T>(&self, entries: &Mutex<HashMap<Uuid, (T, Instant)>>, user_id: Uuid, value: T) {
+        let mut entries = entries.lock().unwrap();
+        if entries.len() >= self.max_user_states && !entries.contains_key(&user_id) {
+            entries.retain(|_, (_, read_at)| read_at.elapsed() < self.version_ttl);
+
+            // Still full: drop an arbitrary entry, it'll just be read again
+            if entries.len() >= self.max_user_states {
+                if let Some(key) = entries.keys().next().copied() {
+                    entries.remove(&key);
+                }
+            }
+        }
+        entries.insert(user_id, (value, Instant::now()));
+    }
+
+    /// Drop every cached session state after revoking sessions in bulk
+    pub async fn invalidate_all(&self) {
+        self.cache.clear().await;
+    }
+
+    pub async fn is_valid(&self, session_id: Uuid, user_id: Uuid) -> Result<bool> {
+        if let Some(valid) = self.cache.get(session_id).await {
+            return Ok(valid);
+        }
+
+        // Only checks that miss the cache extend a session, at most one per
+        // session_cache.ttl_seconds
+        let valid = self.repository.is_session_valid(session_id, self.idle_timeout).await?;
+        self.cache.set(session_id, user_id, valid).await;
+        Ok(valid)
+    }
+
+    /// Drop cached state after revoking a session
+    pub async fn invalidate(&self, session_id: Uuid) {
+        self.cache.invalidate(session_id).await;
+    }
+
+    /// Drop cached state after revoking all of a user's sessions
+    pub async fn invalidate_user(&self, user_id: Uuid) {
+        self.cache.invalidate_user(user_id).await;
+    }
+
+    /// Resolve a personal access token to the caller it acts as, recording the use
+    ///
+    /// Personal access tokens aren't cached, so revoking one takes effect on
+    /// the next request. The context carries the owner's current roles
+    /// narrowed to the token's roles, the token's API scopes, and no session.
+    pub async fn authenticate_personal_token(
+        &self,
+        token: &str,
+        ip_address: Option<&str>,
+    ) -> Result<Option<PersonalTokenCaller>> {
+        let Some(token) = self
+            .repository
+            .use_personal_access_token(&JwtService::hash_opaque_token(token), ip_address)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(user) = self.users.find_by_id(token.user_id).await? else {
+            return Ok(None);
+        };
+
+        let roles = self
+            .repository
+            .get_user_roles(user.id)
+            .await?
+            .into_iter()
+            .filter(|role| token.roles.contains(role))
+            .collect();
+
+        // Validated on creation, so only a hand-edited row fails to parse
+        let response_format = token.response_format.as_deref().and_then(|format| {
+            ResponseFormat::parse(format)
+                .inspect_err(|e| {
+                    tracing::warn!(token_id = %token.id, error = %e, "Ignoring stored response format")
+                })
+                .ok()
+        });
+
+        Ok(Some(PersonalTokenCaller {
+            context: AuthContext {
+                user_id: user.id,
+                email: user.email,
+                username: user.username,
+                roles,
+                session_id: None,
+                tenant_id: None,
+                scopes: token.scopes,
+                subject_type: SubjectType::User,
+            },
+            response_format,
+        }))
+    }
+
+    /// Resolve a service account identified out of band (a verified client
+    /// certificate) to its caller, recording the use
+    ///
+    /// Not cached, like personal access tokens, so disabling the account
+    /// takes effect on the next request.
+    pub async fn authenticate_service_account(&self, id: Uuid) -> Result<Option<AuthContext>> {
+        let Some(account) = self.repository.use_service_account_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(AuthContext {
+            user_id: account.id,
+            email: String::new(),
+            username: account.name,
+            roles: account.roles,
+            session_id: None,
+            tenant_id: None,
+            scopes: None,
+            subject_type: SubjectType::Service,
+        }))
+    }
+}
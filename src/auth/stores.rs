@@ -0,0 +1,108 @@
+//! Routing OpenFGA calls to the store of the tenant they're made for
+//!
+//! Tenants listed in `auth.openfga.tenant_stores` keep their tuples and
+//! models in a store of their own; everyone else shares `store_id`. The
+//! auth middleware runs each request inside [`scope`] for the caller's
+//! tenant, and [`crate::auth::openfga::OpenFgaService`] resolves the store
+//! from there. Outside a scope (background jobs, startup) calls go to the
+//! default store unless wrapped in [`scope`] explicitly.
+//!
+//! The scope is task-local, so work moved to a `tokio::spawn`ed task has to
+//! be scoped again.
+
+use crate::auth::cache::{permission_cache_from_config, PermissionCacheBackend};
+use crate::config::Config;
+use crate::errors::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+tokio::task_local! {
+    static TENANT: String;
+}
+
+/// Run `future` with OpenFGA calls routed to `tenant`'s store
+pub async fn scope<F: Future>(tenant: impl Into<String>, future: F) -> F::Output {
+    TENANT.scope(tenant.into(), future).await
+}
+
+/// Tenant of the enclosing [`scope`], if any
+pub fn current_tenant() -> Option<String> {
+    TENANT.try_with(|tenant| tenant.clone()).ok()
+}
+
+/// One OpenFGA store and the permission checks cached for it
+#[derive(Clone)]
+pub struct OpenFgaStore {
+    /// Tenant the store is dedicated to; `None` for the shared store
+    pub tenant: Option<String>,
+    pub store_id: String,
+    /// Model sent with checks and writes, if pinned
+    pub auth_model_id: Option<String>,
+    pub cache: Arc<dyn PermissionCacheBackend>,
+}
+
+/// The shared store plus every tenant's dedicated one
+#[derive(Clone)]
+pub struct StoreRouter {
+    default: OpenFgaStore,
+    tenants: HashMap<String, OpenFgaStore>,
+}
+
+impl StoreRouter {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let openfga = &config.auth.openfga;
+        let default = OpenFgaStore {
+            tenant: None,
+            store_id: openfga.store_id.clone(),
+            auth_model_id: openfga.auth_model_id.clone(),
+            cache: permission_cache_from_config(config, None)?,
+        };
+
+        let mut tenants = HashMap::new();
+        for (tenant, store) in &openfga.tenant_stores {
+            tracing::info!(tenant = %tenant, store_id = %store.store_id, "Tenant has a dedicated OpenFGA store");
+            tenants.insert(
+                tenant.clone(),
+                OpenFgaStore {
+                    tenant: Some(tenant.clone()),
+                    store_id: store.store_id.clone(),
+                    auth_model_id: store.auth_model_id.clone(),
+                    cache: permission_cache_from_config(config, Some(tenant))?,
+                },
+            );
+        }
+
+        Ok(Self { default, tenants })
+    }
+
+    /// The store for the current [`scope`]
+    pub fn current(&self) -> &OpenFgaStore {
+        match current_tenant() {
+            Some(tenant) => self.resolve(&tenant),
+            None => &self.default,
+        }
+    }
+
+    pub fn current_mut(&mut self) -> &mut OpenFgaStore {
+        match current_tenant().and_then(|tenant| self.tenants.get_mut(&tenant)) {
+            Some(store) => store,
+            None => &mut self.default,
+        }
+    }
+
+    /// `tenant`'s dedicated store, or the shared one
+    pub fn resolve(&self, tenant: &str) -> &OpenFgaStore {
+        self.tenants.get(tenant).unwrap_or(&self.default)
+    }
+
+    /// The shared store first, then the dedicated ones
+    pub fn all(&self) -> impl Iterator<Item = &OpenFgaStore> {
+        std::iter::once(&self.default).chain(self.tenants.values())
+    }
+
+    /// Tenants with a dedicated store
+    pub fn tenants(&self) -> impl Iterator<Item = &str> {
+        self.tenants.keys().map(String::as_str)
+    }
+}
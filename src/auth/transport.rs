@@ -0,0 +1,261 @@
+//! How permission checks reach OpenFGA.
+//!
+//! Checks are the hot path, so they go through an [`OpenFgaTransport`]
+//! selected by `auth.openfga.transport`: JSON over HTTP, or gRPC, which avoids
+//! JSON encoding and reuses one HTTP/2 connection. Model management, tuple
+//! reads and writes stay on HTTP either way.
+
+use crate::auth::openfga::{CheckRequest, CheckResponse, TupleKey};
+use crate::config::{OpenFgaConfig, OpenFgaTransportKind};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use axum::http::uri::PathAndQuery;
+use reqwest::Client;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::metadata::{AsciiMetadataValue, MetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use tonic::Code;
+
+const CHECK_PATH: &str = "/openfga.v1.OpenFGAService/Check";
+
+/// Why a check didn't get an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportError {
+    /// OpenFGA couldn't be reached or failed on its side; counts against the
+    /// circuit breaker
+    Unavailable(String),
+    /// OpenFGA answered but refused the request
+    Rejected(String),
+    /// No answer within the check's time budget; counts against the circuit
+    /// breaker like `Unavailable`
+    TimedOut(String),
+}
+
+impl From<TransportError> for AppError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Unavailable(message) | TransportError::Rejected(message) => {
+                AppError::Internal(message)
+            }
+            TransportError::TimedOut(message) => AppError::Timeout(message),
+        }
+    }
+}
+
+#[async_trait]
+pub trait OpenFgaTransport: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether the tuple's user has the relation to the object in the store
+    async fn check(
+        &self,
+        store_id: &str,
+        auth_model_id: Option<&str>,
+        tuple_key: TupleKey,
+    ) -> std::result::Result<bool, TransportError>;
+}
+
+/// Build the transport `config.transport` names
+pub fn transport_from_config(
+    config: &OpenFgaConfig,
+    client: &Client,
+) -> Result<Arc<dyn OpenFgaTransport>> {
+    match config.transport {
+        OpenFgaTransportKind::Http => Ok(Arc::new(HttpTransport::new(config, client.clone()))),
+        OpenFgaTransportKind::Grpc => Ok(Arc::new(GrpcTransport::new(config)?)),
+    }
+}
+
+/// `POST /stores/{store_id}/check`
+pub struct HttpTransport {
+    client: Client,
+    endpoint: String,
+    headers: reqwest::header::HeaderMap,
+}
+
+impl HttpTransport {
+    pub fn new(config: &OpenFgaConfig, client: Client) -> Self {
+        Self {
+            client,
+            endpoint: config.endpoint.clone(),
+            headers: crate::auth::openfga::request_headers(config.api_token.as_deref()),
+        }
+    }
+}
+
+#[async_trait]
+impl OpenFgaTransport for HttpTransport {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn check(
+        &self,
+        store_id: &str,
+        auth_model_id: Option<&str>,
+        tuple_key: TupleKey,
+    ) -> std::result::Result<bool, TransportError> {
+        let request = CheckRequest {
+            tuple_key,
+            contextual_tuples: None,
+            authorization_model_id: auth_model_id.map(str::to_string),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/stores/{}/check", self.endpoint, store_id))
+            .headers(self.headers.clone())
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                let message = format!("OpenFGA request failed: {}", e);
+                if e.is_timeout() {
+                    TransportError::TimedOut(message)
+                } else {
+                    TransportError::Unavailable(message)
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            let message = format!("OpenFGA check failed with status {}: {}", status, error_text);
+            return Err(if status.is_server_error() {
+                TransportError::Unavailable(message)
+            } else {
+                TransportError::Rejected(message)
+            });
+        }
+
+        let check_response: CheckResponse = response.json().await.map_err(|e| {
+            TransportError::Rejected(format!("Failed to parse OpenFGA response: {}", e))
+        })?;
+        Ok(check_response.allowed)
+    }
+}
+
+/// `openfga.v1.OpenFGAService/Check` over a lazily connected channel
+pub struct GrpcTransport {
+    channel: Channel,
+    authorization: Option<AsciiMetadataValue>,
+}
+
+impl GrpcTransport {
+    pub fn new(config: &OpenFgaConfig) -> Result<Self> {
+        let endpoint = Endpoint::from_shared(config.grpc_endpoint.clone())
+            .map_err(|e| {
+                AppError::Internal(format!(
+                    "Invalid OpenFGA gRPC endpoint '{}': {}",
+                    config.grpc_endpoint, e
+                ))
+            })?
+            .timeout(Duration::from_secs(config.request_timeout_seconds));
+
+        let authorization = config
+            .api_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+            .map(|token| MetadataValue::try_from(format!("Bearer {}", token)))
+            .transpose()
+            .map_err(|_| AppError::Internal("OpenFGA API token is not a valid header".to_string()))?;
+
+        Ok(Self {
+            channel: endpoint.connect_lazy(),
+            authorization,
+        })
+    }
+}
+
+#[async_trait]
+impl OpenFgaTransport for GrpcTransport {
+    fn name(&self) -> &'static str {
+        "grpc"
+    }
+
+    async fn check(
+        &self,
+        store_id: &str,
+        auth_model_id: Option<&str>,
+        tuple_key: TupleKey,
+    ) -> std::result::Result<bool, TransportError> {
+        let mut grpc = tonic::client::Grpc::new(self.channel.clone());
+        grpc.ready().await.map_err(|e| {
+            TransportError::Unavailable(format!("OpenFGA gRPC connection failed: {}", e))
+        })?;
+
+        let mut request = tonic::Request::new(proto::CheckRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(proto::CheckRequestTupleKey {
+                user: tuple_key.user,
+                relation: tuple_key.relation,
+                object: tuple_key.object,
+            }),
+            authorization_model_id: auth_model_id.unwrap_or_default().to_string(),
+        });
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+
+        let response: tonic::Response<proto::CheckResponse> = grpc
+            .unary(
+                request,
+                PathAndQuery::from_static(CHECK_PATH),
+                tonic_prost::ProstCodec::default(),
+            )
+            .await
+            .map_err(|status| {
+                let message = format!(
+                    "OpenFGA check failed with status {:?}: {}",
+                    status.code(),
+                    status.message()
+                );
+                match status.code() {
+                    Code::DeadlineExceeded => TransportError::TimedOut(message),
+                    Code::Unavailable
+                    | Code::Internal
+                    | Code::Unknown
+                    | Code::ResourceExhausted
+                    | Code::Cancelled => TransportError::Unavailable(message),
+                    _ => TransportError::Rejected(message),
+                }
+            })?;
+
+        Ok(response.into_inner().allowed)
+    }
+}
+
+/// The subset of `openfga/v1/openfga_service.proto` used here; unknown
+/// fields in responses are skipped by prost
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CheckRequest {
+        #[prost(string, tag = "1")]
+        pub store_id: String,
+        #[prost(message, optional, tag = "2")]
+        pub tuple_key: Option<CheckRequestTupleKey>,
+        #[prost(string, tag = "4")]
+        pub authorization_model_id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CheckRequestTupleKey {
+        #[prost(string, tag = "1")]
+        pub user: String,
+        #[prost(string, tag = "2")]
+        pub relation: String,
+        #[prost(string, tag = "3")]
+        pub object: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CheckResponse {
+        #[prost(bool, tag = "1")]
+        pub allowed: bool,
+        #[prost(string, tag = "2")]
+        pub resolution: String,
+    }
+}
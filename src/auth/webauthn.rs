@@ -0,0 +1,424 @@
+//! WebAuthn (passkey) ceremonies for ES256 credentials.
+//!
+//! Registration uses the `none` attestation conveyance: the browser's
+//! `getPublicKey()` / `getAuthenticatorData()` results are sent as-is, so the
+//! attestation object doesn't need to be decoded. Authentication verifies the
+//! assertion signature against the stored SubjectPublicKeyInfo.
+
+use crate::config::WebAuthnConfig;
+use crate::errors::{AppError, Result};
+use crate::models::format;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use p256::pkcs8::DecodePublicKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// COSE algorithm identifier for ECDSA P-256 with SHA-256
+pub const COSE_ALG_ES256: i32 = -7;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// Stored passkey
+#[derive(Debug, Clone, FromRow)]
+pub struct WebAuthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub algorithm: i32,
+    pub sign_count: i64,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Pending ceremony challenge
+#[derive(Debug, Clone, FromRow)]
+pub struct WebAuthnChallenge {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub challenge: String,
+    pub ceremony: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyUser {
+    /// base64url user handle
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublicKeyCredentialParameters {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub alg: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// base64url credential ID
+    pub id: String,
+}
+
+/// `PublicKeyCredentialCreationOptions` for `navigator.credentials.create()`
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    pub challenge: String,
+    pub rp: RelyingParty,
+    pub user: PublicKeyUser,
+    pub pub_key_cred_params: Vec<PublicKeyCredentialParameters>,
+    pub timeout: u64,
+    pub attestation: String,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+}
+
+/// `PublicKeyCredentialRequestOptions` for `navigator.credentials.get()`
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub timeout: u64,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyRegistrationStart {
+    #[serde(with = "format::id")]
+    pub challenge_id: Uuid,
+    pub options: CreationOptions,
+}
+
+/// Result of `navigator.credentials.create()`, fields base64url encoded
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PasskeyRegistrationFinish {
+    #[serde(with = "format::id")]
+    pub challenge_id: Uuid,
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    /// `AuthenticatorAttestationResponse.getPublicKey()` (SubjectPublicKeyInfo DER)
+    pub public_key: String,
+    #[schema(example = -7)]
+    pub public_key_algorithm: i32,
+    #[schema(example = "MacBook Touch ID")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PasskeyLoginStartRequest {
+    /// Restricts allowed credentials to this account; omit for discoverable credentials
+    #[schema(example = "user@example.com")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyLoginStart {
+    #[serde(with = "format::id")]
+    pub challenge_id: Uuid,
+    pub options: RequestOptions,
+}
+
+/// Result of `navigator.credentials.get()`, fields base64url encoded
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PasskeyLoginFinish {
+    #[serde(with = "format::id")]
+    pub challenge_id: Uuid,
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyInfo {
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    pub name: Option<String>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<WebAuthnCredential> for PasskeyInfo {
+    fn from(credential: WebAuthnCredential) -> Self {
+        Self {
+            id: credential.id,
+            name: credential.name,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/// Parsed authenticator data
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    pub credential_id: Option<Vec<u8>>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 37 {
+            return Err(invalid("authenticator data too short"));
+        }
+
+        let mut rp_id_hash = [0u8; 32];
+        rp_id_hash.copy_from_slice(&bytes[..32]);
+        let flags = bytes[32];
+        let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+        // Attested credential data: AAGUID (16) | length (2) | credential ID
+        let credential_id = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+            let rest = &bytes[37..];
+            if rest.len() < 18 {
+                return Err(invalid("attested credential data too short"));
+            }
+            let length = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            let id = rest
+                .get(18..18 + length)
+                .ok_or_else(|| invalid("credential ID out of bounds"))?;
+            Some(id.to_vec())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash,
+            flags,
+            sign_count,
+            credential_id,
+        })
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+}
+
+/// Credential data to store after a successful registration
+#[derive(Debug)]
+pub struct VerifiedRegistration {
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+/// Verifies WebAuthn ceremonies for the configured relying party
+#[derive(Clone)]
+pub struct WebAuthnVerifier {
+    rp_id: String,
+    rp_name: String,
+    origins: Vec<String>,
+    timeout_ms: u64,
+}
+
+impl WebAuthnVerifier {
+    pub fn new(config: &WebAuthnConfig) -> Self {
+        Self {
+            rp_id: config.rp_id.clone(),
+            rp_name: config.rp_name.clone(),
+            origins: config.origins.clone(),
+            timeout_ms: config.challenge_ttl_seconds * 1000,
+        }
+    }
+
+    /// Expiry for a challenge issued now
+    pub fn challenge_expires_at(&self) -> DateTime<Utc> {
+        Utc::now() + chrono::Duration::milliseconds(self.timeout_ms as i64)
+    }
+
+    /// Random base64url challenge
+    pub fn new_challenge() -> String {
+        URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+    }
+
+    pub fn creation_options(
+        &self,
+        challenge: String,
+        user_id: Uuid,
+        email: &str,
+        username: &str,
+        existing: &[WebAuthnCredential],
+    ) -> CreationOptions {
+        CreationOptions {
+            challenge,
+            rp: RelyingParty {
+                id: self.rp_id.clone(),
+                name: self.rp_name.clone(),
+            },
+            user: PublicKeyUser {
+                id: URL_SAFE_NO_PAD.encode(user_id.as_bytes()),
+                name: email.to_string(),
+                display_name: username.to_string(),
+            },
+            pub_key_cred_params: vec![PublicKeyCredentialParameters {
+                kind: "public-key".to_string(),
+                alg: COSE_ALG_ES256,
+            }],
+            timeout: self.timeout_ms,
+            attestation: "none".to_string(),
+            exclude_credentials: descriptors(existing),
+        }
+    }
+
+    pub fn request_options(&self, challenge: String, allowed: &[WebAuthnCredential]) -> RequestOptions {
+        RequestOptions {
+            challenge,
+            rp_id: self.rp_id.clone(),
+            allow_credentials: descriptors(allowed),
+            timeout: self.timeout_ms,
+            user_verification: "preferred".to_string(),
+        }
+    }
+
+    /// Verify a registration response against the issued challenge
+    pub fn verify_registration(
+        &self,
+        challenge: &str,
+        response: &PasskeyRegistrationFinish,
+    ) -> Result<VerifiedRegistration> {
+        if response.public_key_algorithm != COSE_ALG_ES256 {
+            return Err(invalid("unsupported public key algorithm"));
+        }
+
+        let client_data_json = decode(&response.client_data_json)?;
+        self.verify_client_data(&client_data_json, "webauthn.create", challenge)?;
+
+        let auth_data = AuthenticatorData::parse(&decode(&response.authenticator_data)?)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let credential_id = decode(&response.credential_id)?;
+        if auth_data.credential_id.as_deref() != Some(credential_id.as_slice()) {
+            return Err(invalid("credential ID mismatch"));
+        }
+
+        let public_key = decode(&response.public_key)?;
+        VerifyingKey::from_public_key_der(&public_key).map_err(|_| invalid("invalid public key"))?;
+
+        Ok(VerifiedRegistration {
+            credential_id,
+            public_key,
+            sign_count: auth_data.sign_count,
+        })
+    }
+
+    /// Verify an assertion for a stored credential, returning the new sign count
+    pub fn verify_assertion(
+        &self,
+        challenge: &str,
+        credential: &WebAuthnCredential,
+        response: &PasskeyLoginFinish,
+    ) -> Result<u32> {
+        let client_data_json = decode(&response.client_data_json)?;
+        self.verify_client_data(&client_data_json, "webauthn.get", challenge)?;
+
+        let auth_data_bytes = decode(&response.authenticator_data)?;
+        let auth_data = AuthenticatorData::parse(&auth_data_bytes)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let key = VerifyingKey::from_public_key_der(&credential.public_key)
+            .map_err(|_| invalid("stored public key is invalid"))?;
+        let signature = Signature::from_der(&decode(&response.signature)?)
+            .map_err(|_| invalid("malformed signature"))?;
+
+        let mut signed = auth_data_bytes;
+        signed.extend_from_slice(&Sha256::digest(&client_data_json));
+        key.verify(&signed, &signature)
+            .map_err(|_| invalid("signature verification failed"))?;
+
+        // A counter that doesn't advance suggests a cloned authenticator;
+        // authenticators that don't implement counters always report 0
+        let stored = credential.sign_count as u32;
+        if (auth_data.sign_count != 0 || stored != 0) && auth_data.sign_count <= stored {
+            return Err(invalid("signature counter did not increase"));
+        }
+
+        Ok(auth_data.sign_count)
+    }
+
+    fn verify_client_data(&self, client_data_json: &[u8], kind: &str, challenge: &str) -> Result<()> {
+        let client_data: ClientData =
+            serde_json::from_slice(client_data_json).map_err(|_| invalid("malformed client data"))?;
+
+        if client_data.kind != kind {
+            return Err(invalid("unexpected ceremony type"));
+        }
+        if client_data.challenge != challenge {
+            return Err(invalid("challenge mismatch"));
+        }
+        if !self.origins.contains(&client_data.origin) {
+            return Err(invalid("origin not allowed"));
+        }
+
+        Ok(())
+    }
+
+    fn verify_authenticator_data(&self, auth_data: &AuthenticatorData) -> Result<()> {
+        if auth_data.rp_id_hash.as_slice() != Sha256::digest(self.rp_id.as_bytes()).as_slice() {
+            return Err(invalid("relying party mismatch"));
+        }
+        if !auth_data.user_present() {
+            return Err(invalid("user presence required"));
+        }
+
+        Ok(())
+    }
+}
+
+fn descriptors(credentials: &[WebAuthnCredential]) -> Vec<CredentialDescriptor> {
+    credentials
+        .iter()
+        .map(|credential| CredentialDescriptor {
+            kind: "public-key".to_string(),
+            id: URL_SAFE_NO_PAD.encode(&credential.credential_id),
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| invalid("invalid base64url encoding"))
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::Authentication(format!("Passkey verification failed: {}", reason))
+}
@@ -1,14 +1,43 @@
+use crate::metrics::MetricsRegistry;
 use anyhow::Result;
-use reqwest::{Client, Method, Response, Url};
+use prometheus::{CounterVec, HistogramVec};
+use reqwest::{Client, Method, RequestBuilder, Response, Url};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// HTTP Client wrapper with Tower middleware support
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
     base_url: Option<String>,
-    default_timeout: Duration,
+    dependency: Option<&'static str>,
+    metrics: Option<OutboundMetrics>,
+}
+
+/// Outbound call counters, registered under the `outbound` subsystem
+#[derive(Clone)]
+struct OutboundMetrics {
+    requests_total: CounterVec,
+    duration_seconds: HistogramVec,
+}
+
+impl OutboundMetrics {
+    fn register(registry: &MetricsRegistry) -> Result<Self, prometheus::Error> {
+        let registry = registry.subsystem("outbound");
+        Ok(Self {
+            requests_total: registry.counter_vec(
+                "requests_total",
+                "Outbound HTTP calls by dependency and outcome (ok, server_error, error)",
+                &["dependency", "outcome"],
+            )?,
+            duration_seconds: registry.histogram_vec(
+                "request_duration_seconds",
+                "Outbound HTTP call duration in seconds, by dependency",
+                vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+                &["dependency"],
+            )?,
+        })
+    }
 }
 
 /// Builder for creating HTTP clients with various configurations
@@ -17,6 +46,8 @@ pub struct HttpClientBuilder {
     base_url: Option<String>,
     user_agent: Option<String>,
     default_headers: reqwest::header::HeaderMap,
+    dependency: Option<&'static str>,
+    metrics: Option<MetricsRegistry>,
 }
 
 impl Default for HttpClientBuilder {
@@ -26,6 +57,8 @@ impl Default for HttpClientBuilder {
             base_url: None,
             user_agent: Some(format!("reprime-backend/{}", env!("CARGO_PKG_VERSION"))),
             default_headers: reqwest::header::HeaderMap::new(),
+            dependency: None,
+            metrics: None,
         }
     }
 }
@@ -50,6 +83,18 @@ impl HttpClientBuilder {
         self
     }
 
+    /// Record calls in the dependency stats under `name`
+    pub fn dependency(mut self, name: &'static str) -> Self {
+        self.dependency = Some(name);
+        self
+    }
+
+    /// Count calls and their latency in `registry`, labelled by dependency
+    pub fn metrics(mut self, registry: &MetricsRegistry) -> Self {
+        self.metrics = Some(registry.clone());
+        self
+    }
+
     pub fn default_header<K, V>(mut self, key: K, value: V) -> Self
     where
         K: reqwest::header::IntoHeaderName,
@@ -71,11 +116,17 @@ impl HttpClientBuilder {
         }
 
         let client = client_builder.build()?;
+        let metrics = self
+            .metrics
+            .as_ref()
+            .map(OutboundMetrics::register)
+            .transpose()?;
 
         Ok(HttpClient {
             client,
             base_url: self.base_url,
-            default_timeout: self.timeout,
+            dependency: self.dependency,
+            metrics,
         })
     }
 }
@@ -111,7 +162,7 @@ impl HttpClient {
         T: for<'de> Deserialize<'de>,
     {
         let url = self.resolve_url(url)?;
-        let response = self.client.get(url).send().await?;
+        let response = self.send(self.client.get(url)).await?;
         self.handle_response(response).await
     }
 
@@ -122,7 +173,7 @@ impl HttpClient {
         T: for<'de> Deserialize<'de>,
     {
         let url = self.resolve_url(url)?;
-        let response = self.client.post(url).json(body).send().await?;
+        let response = self.send(self.client.post(url).json(body)).await?;
         self.handle_response(response).await
     }
 
@@ -133,7 +184,7 @@ impl HttpClient {
         T: for<'de> Deserialize<'de>,
     {
         let url = self.resolve_url(url)?;
-        let response = self.client.put(url).json(body).send().await?;
+        let response = self.send(self.client.put(url).json(body)).await?;
         self.handle_response(response).await
     }
 
@@ -143,7 +194,7 @@ impl HttpClient {
         T: for<'de> Deserialize<'de>,
     {
         let url = self.resolve_url(url)?;
-        let response = self.client.delete(url).send().await?;
+        let response = self.send(self.client.delete(url)).await?;
         self.handle_response(response).await
     }
 
@@ -160,10 +211,48 @@ impl HttpClient {
             request = request.json(body);
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         self.handle_response(response).await
     }
 
+    /// All requests go out through here, so each is charged to the current
+    /// request's cost and, for named clients, recorded in the dependency stats
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        crate::request_cost::record_http_call();
+        let started = Instant::now();
+        let result = request.send().await;
+        let elapsed = started.elapsed();
+
+        if let Some(metrics) = &self.metrics {
+            let dependency = self.dependency.unwrap_or("unnamed");
+            let outcome = match &result {
+                Ok(response) if response.status().is_server_error() => "server_error",
+                Ok(_) => "ok",
+                Err(_) => "error",
+            };
+            metrics
+                .requests_total
+                .with_label_values(&[dependency, outcome])
+                .inc();
+            metrics
+                .duration_seconds
+                .with_label_values(&[dependency])
+                .observe(elapsed.as_secs_f64());
+        }
+
+        if let Some(dependency) = self.dependency {
+            let error = match &result {
+                Ok(response) if response.status().is_server_error() => {
+                    Some(format!("HTTP {}", response.status()))
+                }
+                Ok(_) => None,
+                Err(e) => Some(e.to_string()),
+            };
+            crate::dependencies::record(dependency, elapsed, error);
+        }
+        result
+    }
+
     /// Handle response and deserialize JSON
     async fn handle_response<T>(&self, response: Response) -> Result<T>
     where
@@ -180,10 +269,25 @@ impl HttpClient {
         }
     }
 
+    /// Send a request with raw headers and body, returning the raw response
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        url: &str,
+        headers: reqwest::header::HeaderMap,
+        body: Vec<u8>,
+    ) -> Result<Response> {
+        let url = self.resolve_url(url)?;
+        let response = self
+            .send(self.client.request(method, url).headers(headers).body(body))
+            .await?;
+        Ok(response)
+    }
+
     /// Get raw response for custom handling
     pub async fn get_response(&self, url: &str) -> Result<Response> {
         let url = self.resolve_url(url)?;
-        let response = self.client.get(url).send().await?;
+        let response = self.send(self.client.get(url)).await?;
         Ok(response)
     }
 
@@ -1,3 +1,4 @@
+use axum::http::StatusCode;
 use std::time::Duration;
 use tower_http::{
     timeout::TimeoutLayer,
@@ -36,7 +37,8 @@ impl HttpMiddlewareBuilder {
     }
 
     pub fn build_timeout_layer(&self) -> Option<TimeoutLayer> {
-        self.timeout.map(TimeoutLayer::new)
+        self.timeout
+            .map(|timeout| TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout))
     }
 
     pub fn build_trace_layer(&self) -> Option<TraceLayer<tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>>> {
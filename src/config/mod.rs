@@ -1,6 +1,61 @@
 use config::{Config as ConfigBuilder, ConfigError, Environment, File};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::OrganizationRole;
+use crate::utils::id::IdStrategy;
+use crate::utils::retry::Jitter;
+use std::collections::HashMap;
 use std::env;
+use uuid::Uuid;
+
+/// `Debug` for a config struct holding secrets: the fields after `redact`
+/// print as `[redacted]` when set, so the configuration can be logged.
+/// Every field has to be listed, or the impl doesn't compile.
+macro_rules! redacted_debug {
+    ($name:ident { redact $($secret:ident),+ $(,)? }) => {
+        redacted_debug!($name { ; redact $($secret),+ });
+    };
+    ($name:ident { $($field:ident),* $(,)?; redact $($secret:ident),+ $(,)? }) => {
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let Self { $($field,)* $($secret,)+ } = self;
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($field), $field))*
+                    $(.field(stringify!($secret), &Redacted($secret)))+
+                    .finish()
+            }
+        }
+    };
+}
+
+/// A secret as `[redacted]`, or as itself when it's empty or unset
+struct Redacted<'a, T>(&'a T);
+
+trait Secret: std::fmt::Debug {
+    fn is_set(&self) -> bool;
+}
+
+impl Secret for String {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T: std::fmt::Debug> Secret for Option<T> {
+    fn is_set(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: Secret> std::fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_set() {
+            f.write_str("[redacted]")
+        } else {
+            self.0.fmt(f)
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
@@ -9,6 +64,52 @@ pub struct Config {
     pub logging: LoggingConfig,
     pub telemetry: TelemetryConfig,
     pub auth: AuthConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub mirror: MirrorConfig,
+    #[serde(default)]
+    pub pagination: PaginationConfig,
+    #[serde(default)]
+    pub live_tail: LiveTailConfig,
+    #[serde(default)]
+    pub mailer: MailerConfig,
+    #[serde(default)]
+    pub tenant: TenantConfig,
+    #[serde(default)]
+    pub api_usage: ApiUsageConfig,
+    #[serde(default)]
+    pub client_analytics: ClientAnalyticsConfig,
+    #[serde(default)]
+    pub anonymize: AnonymizeConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub edge_cache: EdgeCacheConfig,
+    #[serde(default)]
+    pub redis: RedisConfig,
+    #[serde(default)]
+    pub bandwidth: BandwidthConfig,
+    #[serde(default)]
+    pub fanout: FanOutConfig,
+    #[serde(default)]
+    pub authorization_canary: AuthorizationCanaryConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub coalesce: CoalesceConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub request_context: RequestContextConfig,
+    #[serde(default)]
+    pub banner: BannerConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub avatars: AvatarConfig,
+    #[serde(default)]
+    pub seed: SeedConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -17,7 +118,7 @@ pub struct ServerConfig {
     pub port: u16,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
@@ -25,8 +126,114 @@ pub struct DatabaseConfig {
     pub acquire_timeout: u64,
     pub idle_timeout: u64,
     pub max_lifetime: u64,
+    #[serde(default)]
+    pub auth: Option<AuthDatabaseConfig>,
+    /// Additional shards for partitioned user data; `url` above is shard 0
+    #[serde(default)]
+    pub shards: Vec<ShardConfig>,
+    #[serde(default)]
+    pub slow_query: SlowQueryConfig,
+    /// What DB spans record about statements; switchable at runtime
+    #[serde(default)]
+    pub statement_recording: StatementRecording,
+    #[serde(default)]
+    pub migrations: MigrationConfig,
+    /// How repositories generate primary keys for new rows
+    #[serde(default)]
+    pub id_strategy: IdStrategy,
+}
+
+redacted_debug!(DatabaseConfig {
+    max_connections, min_connections, acquire_timeout, idle_timeout, max_lifetime, auth,
+    shards, slow_query, statement_recording, migrations, id_strategy;
+    redact url
+});
+
+/// How much of a SQL statement is recorded on database spans
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementRecording {
+    /// Full statement text plus bind parameter count
+    Statement,
+    /// Query name plus bind parameter count
+    BindCount,
+    /// Only the query name (operation and table)
+    #[default]
+    Name,
 }
 
+/// Slow query logging and optional query plan capture
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SlowQueryConfig {
+    pub threshold_ms: u64,
+    /// Run `EXPLAIN (FORMAT JSON)` for slow statements and log the plan
+    pub explain: bool,
+    /// Upper bound on plans captured per minute
+    pub explain_per_minute: u32,
+}
+
+impl Default for SlowQueryConfig {
+    fn default() -> Self {
+        Self {
+            threshold_ms: 500,
+            explain: false,
+            explain_per_minute: 10,
+        }
+    }
+}
+
+/// Advisory lock that serializes startup migrations across replicas
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MigrationConfig {
+    /// `pg_advisory_lock` key; must be the same for every replica
+    pub lock_key: i64,
+    /// Give up (and fail startup) after waiting this long for the lock
+    pub lock_timeout_secs: u64,
+    pub lock_poll_interval_ms: u64,
+}
+
+impl Default for MigrationConfig {
+    fn default() -> Self {
+        Self {
+            lock_key: 7_202_501,
+            lock_timeout_secs: 300,
+            lock_poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// An additional Postgres database holding a partition of user data
+#[derive(Deserialize, Clone)]
+pub struct ShardConfig {
+    pub url: String,
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+}
+
+redacted_debug!(ShardConfig {
+    max_connections, min_connections;
+    redact url
+});
+
+/// Optional dedicated storage for auth tables (credentials, sessions, roles).
+///
+/// When `url` is set the auth tables live in a separate database; when only
+/// `schema` is set they live in a separate schema of the application database.
+#[derive(Deserialize, Clone)]
+pub struct AuthDatabaseConfig {
+    pub url: Option<String>,
+    pub schema: Option<String>,
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+}
+
+redacted_debug!(AuthDatabaseConfig {
+    schema, max_connections, min_connections;
+    redact url
+});
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct LoggingConfig {
     pub level: String,
@@ -41,25 +248,1350 @@ pub struct TelemetryConfig {
     pub enable_tracing: bool,
     pub enable_metrics: bool,
     pub enable_logging: bool,
+    /// Log export backend: `loki`, `otlp`, `both` or `none`
+    #[serde(default = "default_log_exporter")]
+    pub log_exporter: String,
+    /// Log records buffered for OTLP export before new ones are dropped
+    #[serde(default = "default_otlp_log_queue_size")]
+    pub otlp_log_queue_size: usize,
+    #[serde(default)]
+    pub loki: LokiLabelConfig,
 }
 
+/// Which Loki stream labels log pushes carry
+///
+/// Every label value combination is a separate stream, so labels should be
+/// few and low-cardinality; resource attributes that aren't labels are sent
+/// as fields on each line instead.
 #[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LokiLabelConfig {
+    /// Resource attributes sent as labels: `service`, `version`,
+    /// `environment`, `region`, `instance`
+    pub resource_labels: Vec<String>,
+    /// Fixed labels, e.g. `cluster`, `namespace`, `team`
+    pub extra_labels: HashMap<String, String>,
+    /// Environment variable holding more labels as `name=value,...`; these
+    /// win over `extra_labels`
+    pub extra_labels_env: String,
+    /// High-cardinality label names to accept anyway
+    pub allow_high_cardinality: Vec<String>,
+}
+
+impl Default for LokiLabelConfig {
+    fn default() -> Self {
+        Self {
+            resource_labels: vec![
+                "service".to_string(),
+                "version".to_string(),
+                "environment".to_string(),
+                "region".to_string(),
+            ],
+            extra_labels: HashMap::new(),
+            extra_labels_env: "LOKI_EXTRA_LABELS".to_string(),
+            allow_high_cardinality: Vec::new(),
+        }
+    }
+}
+
+fn default_log_exporter() -> String {
+    "loki".to_string()
+}
+
+fn default_otlp_log_queue_size() -> usize {
+    2048
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricsConfig {
+    /// Route group -> route templates (prefixes) used for error-budget counters
+    pub route_groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub tenant_labels: TenantLabelConfig,
+}
+
+/// Per-tenant request, error and usage counters
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TenantLabelConfig {
+    pub enabled: bool,
+    /// Tenants labeled individually besides the pinned ones; the rest are
+    /// counted as `other`
+    pub max_tenants: usize,
+    /// Tenants that always get their own label
+    pub pinned: Vec<String>,
+}
+
+impl Default for TenantLabelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_tenants: 50,
+            pinned: Vec::new(),
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            route_groups: HashMap::from([
+                ("auth".to_string(), vec!["/api/v1/auth".to_string()]),
+                ("users".to_string(), vec!["/api/v1/users".to_string()]),
+                (
+                    "admin".to_string(),
+                    vec!["/api/v1/admin".to_string(), "/internal".to_string()],
+                ),
+                (
+                    "system".to_string(),
+                    vec![
+                        "/health".to_string(),
+                        "/metrics".to_string(),
+                        "/status".to_string(),
+                        "/api/v1/banner".to_string(),
+                    ],
+                ),
+            ]),
+            tenant_labels: TenantLabelConfig::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct PaginationConfig {
+    /// HMAC key for signed cursors; derived from the JWT secret when unset
+    pub cursor_secret: Option<String>,
+}
+
+redacted_debug!(PaginationConfig { redact cursor_secret });
+
+/// Asynchronous traffic mirroring to a shadow environment
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MirrorConfig {
+    pub enabled: bool,
+    pub target_url: String,
+    /// Share of requests to mirror, 0-100
+    pub sample_percent: f64,
+    /// Requests with larger bodies are not mirrored
+    pub max_body_bytes: usize,
+    pub timeout_ms: u64,
+    /// Mirrored requests beyond this many in flight are dropped
+    pub max_in_flight: usize,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_url: String::new(),
+            sample_percent: 0.0,
+            max_body_bytes: 64 * 1024,
+            timeout_ms: 5000,
+            max_in_flight: 100,
+        }
+    }
+}
+
+/// In-process log/metric tail streamed to admins over WebSocket
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LiveTailConfig {
+    pub enabled: bool,
+    /// Number of recent log events kept in memory
+    pub buffer_size: usize,
+    pub metrics_interval_secs: u64,
+}
+
+impl Default for LiveTailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            buffer_size: 1000,
+            metrics_interval_secs: 5,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
 pub struct AuthConfig {
     pub jwt_secret: String,
     pub jwt_expiration_hours: u64,
+    /// Lifetime of a refresh token family (the session), in days
+    #[serde(default = "default_refresh_token_expiration_days")]
+    pub refresh_token_expiration_days: u64,
+    /// Asymmetric signing keys; tokens fall back to HS256 with `jwt_secret`
+    /// when none can sign
+    #[serde(default)]
+    pub jwt_keys: Vec<JwtKeyConfig>,
+    /// Key used to sign new tokens; defaults to the first key with a private part
+    #[serde(default)]
+    pub jwt_active_kid: Option<String>,
+    /// Once a key can sign, tokens without a `kid` (HS256 with `jwt_secret`)
+    /// are only accepted until this time, so sessions from before the switch
+    /// can be refreshed; unset rejects them right away
+    #[serde(default)]
+    pub jwt_accept_shared_secret_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// `iss` claim of issued tokens; tokens from another issuer are rejected
+    #[serde(default = "default_jwt_issuer")]
+    pub jwt_issuer: String,
+    /// `aud` claim of issued tokens; tokens for another audience are rejected
+    #[serde(default = "default_jwt_audience")]
+    pub jwt_audience: String,
+    /// Reject tokens without `iss` or `aud`; turn off while tokens minted
+    /// before the claims were added are still live
+    #[serde(default = "default_jwt_require_issuer_audience")]
+    pub jwt_require_issuer_audience: bool,
+    #[serde(default)]
+    pub session_cache: SessionCacheConfig,
+    #[serde(default)]
+    pub remember_me: RememberMeConfig,
+    #[serde(default)]
+    pub cookies: SessionCookieConfig,
+    #[serde(default)]
+    pub password_reset: PasswordResetConfig,
+    #[serde(default)]
+    pub new_device: NewDeviceConfig,
+    #[serde(default)]
+    pub onboarding: OnboardingConfig,
+    #[serde(default)]
+    pub webauthn: WebAuthnConfig,
+    #[serde(default)]
+    pub login_rate_limit: LoginRateLimitConfig,
+    #[serde(default)]
+    pub service_accounts: ServiceAccountConfig,
+    #[serde(default)]
+    pub authentication: AuthenticationConfig,
     pub openfga: OpenFgaConfig,
 }
 
+redacted_debug!(AuthConfig {
+    jwt_expiration_hours, refresh_token_expiration_days, jwt_keys, jwt_active_kid,
+    jwt_accept_shared_secret_until, jwt_issuer, jwt_audience, jwt_require_issuer_audience,
+    session_cache, remember_me, cookies, password_reset, new_device, onboarding, webauthn,
+    login_rate_limit, service_accounts, authentication, openfga;
+    redact jwt_secret
+});
+
+/// Machine-to-machine callers using the client-credentials grant
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ServiceAccountConfig {
+    /// Lifetime of issued tokens; they can't be revoked, so keep it short
+    pub token_ttl_seconds: u64,
+}
+
+impl Default for ServiceAccountConfig {
+    fn default() -> Self {
+        Self {
+            token_ttl_seconds: 900,
+        }
+    }
+}
+
+/// Which credentials authenticate a request, per route group (groups as in
+/// `metrics.route_groups`)
+///
+/// Authenticators are tried in order and the first that recognises the
+/// request's credential decides; a rejected credential doesn't fall through
+/// to the next one.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AuthenticationConfig {
+    /// Chain for route groups without their own
+    pub default: Vec<String>,
+    pub route_groups: HashMap<String, Vec<String>>,
+    pub mtls: MtlsConfig,
+}
+
+impl Default for AuthenticationConfig {
+    fn default() -> Self {
+        Self {
+            default: vec![
+                "jwt".to_string(),
+                "personal_access_token".to_string(),
+                "cookie".to_string(),
+            ],
+            route_groups: HashMap::new(),
+            mtls: MtlsConfig::default(),
+        }
+    }
+}
+
+/// Service accounts identified by a client certificate the TLS-terminating
+/// proxy verified
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct MtlsConfig {
+    /// Header the proxy puts the verified certificate's subject in; it must
+    /// strip the header from client requests
+    pub subject_header: Option<String>,
+    /// Certificate subject -> service account id
+    pub identities: HashMap<String, Uuid>,
+}
+
+fn default_refresh_token_expiration_days() -> u64 {
+    30
+}
+
+fn default_jwt_issuer() -> String {
+    "reprime-backend".to_string()
+}
+
+fn default_jwt_audience() -> String {
+    "reprime-api".to_string()
+}
+
+fn default_jwt_require_issuer_audience() -> bool {
+    true
+}
+
+/// RS256 / ES256 JWT key, published at `/.well-known/jwks.json`
+#[derive(Deserialize, Clone)]
+pub struct JwtKeyConfig {
+    /// Key ID carried in the token header
+    pub kid: String,
+    /// `RS256` or `ES256`
+    pub algorithm: String,
+    /// PEM private key, or a public key for one that only verifies older tokens
+    pub pem: Option<String>,
+    /// Path to the PEM file, used when `pem` is unset
+    pub pem_file: Option<String>,
+}
+
+redacted_debug!(JwtKeyConfig {
+    kid, algorithm, pem_file;
+    redact pem
+});
+
+/// Token buckets guarding login and registration
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LoginRateLimitConfig {
+    pub enabled: bool,
+    /// Attempts a single client IP can make in a burst
+    pub ip_burst: u32,
+    pub ip_per_minute: u32,
+    /// Attempts against a single email address in a burst
+    pub account_burst: u32,
+    pub account_per_minute: u32,
+    /// Tracked IPs/emails per bucket set; idle ones are dropped first
+    pub max_entries: usize,
+    /// Proxies in front of the service that append to `X-Forwarded-For`;
+    /// 0 keys on the connecting peer and ignores the header
+    pub trusted_proxy_hops: usize,
+}
+
+impl Default for LoginRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ip_burst: 20,
+            ip_per_minute: 10,
+            account_burst: 5,
+            account_per_minute: 2,
+            max_entries: 100_000,
+            trusted_proxy_hops: 0,
+        }
+    }
+}
+
+/// Caching of session revocation checks in the auth middleware
 #[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionCacheConfig {
+    /// How long a valid session is trusted before re-checking; 0 disables caching
+    pub ttl_seconds: u64,
+    pub max_entries: usize,
+}
+
+impl Default for SessionCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: 30,
+            max_entries: 10000,
+        }
+    }
+}
+
+/// Lifetime of sessions created with `remember_me` on login
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RememberMeConfig {
+    /// The session expires after this long without being used
+    pub idle_timeout_hours: u64,
+    /// ...and this long after login, however active
+    pub absolute_timeout_days: u64,
+}
+
+impl Default for RememberMeConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_hours: 14 * 24,
+            absolute_timeout_days: 90,
+        }
+    }
+}
+
+impl RememberMeConfig {
+    pub fn idle_timeout(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.idle_timeout_hours as i64)
+    }
+
+    pub fn absolute_timeout(&self) -> chrono::Duration {
+        chrono::Duration::days(self.absolute_timeout_days as i64)
+    }
+}
+
+/// `SameSite` attribute of the session cookies
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieSameSite {
+    Strict,
+    #[default]
+    Lax,
+    /// Requires `secure`
+    None,
+}
+
+impl CookieSameSite {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CookieSameSite::Strict => "Strict",
+            CookieSameSite::Lax => "Lax",
+            CookieSameSite::None => "None",
+        }
+    }
+}
+
+/// Tokens in HttpOnly cookies instead of response bodies, for browser
+/// frontends
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionCookieConfig {
+    pub enabled: bool,
+    pub access_cookie: String,
+    /// Only sent to `/api/v1/auth`, where it's refreshed and cleared
+    pub refresh_cookie: String,
+    /// Readable by the frontend, which echoes it in `csrf_header`
+    pub csrf_cookie: String,
+    pub csrf_header: String,
+    pub secure: bool,
+    pub same_site: CookieSameSite,
+    /// Defaults to the host the API was called on
+    pub domain: Option<String>,
+}
+
+impl Default for SessionCookieConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            access_cookie: "reprime_session".to_string(),
+            refresh_cookie: "reprime_refresh".to_string(),
+            csrf_cookie: "reprime_csrf".to_string(),
+            csrf_header: "x-csrf-token".to_string(),
+            secure: true,
+            same_site: CookieSameSite::Lax,
+            domain: None,
+        }
+    }
+}
+
+/// Emailed one-time password reset tokens
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordResetConfig {
+    pub token_ttl_minutes: u64,
+    /// Frontend page receiving the token as `?token=...`
+    pub url: String,
+}
+
+impl Default for PasswordResetConfig {
+    fn default() -> Self {
+        Self {
+            token_ttl_minutes: 30,
+            url: "http://localhost:3000/reset-password".to_string(),
+        }
+    }
+}
+
+/// What newly registered users start with
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OnboardingConfig {
+    /// Global roles, see `auth::models::roles`
+    pub default_roles: Vec<String>,
+    /// Organizations to join; each has to exist
+    pub memberships: Vec<OnboardingMembership>,
+    /// Other OpenFGA relationships to write for the user
+    pub relationships: Vec<OnboardingRelationship>,
+}
+
+impl Default for OnboardingConfig {
+    fn default() -> Self {
+        Self {
+            default_roles: vec!["user".to_string()],
+            memberships: vec![OnboardingMembership {
+                organization: "default".to_string(),
+                role: OrganizationRole::Member,
+            }],
+            relationships: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OnboardingMembership {
+    pub organization: String,
+    pub role: OrganizationRole,
+}
+
+/// `relation` on `object`, e.g. `viewer` on `document:welcome`
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct OnboardingRelationship {
+    pub relation: String,
+    pub object: String,
+}
+
+/// Emails sent when someone signs in from a device the user hasn't used
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct NewDeviceConfig {
+    pub enabled: bool,
+    /// Frontend page receiving the confirmation token as `?token=...`
+    pub url: String,
+}
+
+impl Default for NewDeviceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            url: "http://localhost:3000/confirm-device".to_string(),
+        }
+    }
+}
+
+/// WebAuthn relying party settings for passkeys
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebAuthnConfig {
+    /// Registrable domain passkeys are scoped to
+    pub rp_id: String,
+    pub rp_name: String,
+    /// Origins allowed to run ceremonies
+    pub origins: Vec<String>,
+    pub challenge_ttl_seconds: u64,
+}
+
+impl Default for WebAuthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            rp_name: "Reprime".to_string(),
+            origins: vec!["http://localhost:3000".to_string()],
+            challenge_ttl_seconds: 300,
+        }
+    }
+}
+
+/// Outgoing email delivery
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MailerConfig {
+    /// `log` (development) or `http` (JSON POST to an email relay)
+    pub provider: String,
+    pub from: String,
+    pub endpoint: String,
+    pub api_token: Option<String>,
+}
+
+redacted_debug!(MailerConfig {
+    provider, from, endpoint;
+    redact api_token
+});
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        Self {
+            provider: "log".to_string(),
+            from: "no-reply@localhost".to_string(),
+            endpoint: String::new(),
+            api_token: None,
+        }
+    }
+}
+
+/// `reprime-backend anonymize` settings
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AnonymizeConfig {
+    /// Password every anonymized account can sign in with; when unset
+    /// nobody can sign in with a password
+    pub password: Option<String>,
+    /// Users rewritten per statement
+    pub batch_size: i64,
+}
+
+redacted_debug!(AnonymizeConfig {
+    batch_size;
+    redact password
+});
+
+impl Default for AnonymizeConfig {
+    fn default() -> Self {
+        Self {
+            password: None,
+            batch_size: 1000,
+        }
+    }
+}
+
+/// Per-endpoint usage tracking and deprecation headers
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ApiUsageConfig {
+    pub enabled: bool,
+    /// Distinct (route, method, client) combinations tracked; later ones
+    /// are counted under the `other` client
+    pub max_entries: usize,
+    pub deprecations: Vec<DeprecatedRouteConfig>,
+}
+
+impl Default for ApiUsageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 10000,
+            deprecations: Vec::new(),
+        }
+    }
+}
+
+/// Per-client-version call counts, from the `User-Agent` header
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClientAnalyticsConfig {
+    pub enabled: bool,
+    /// Distinct (client version, route, method) combinations tracked; later
+    /// ones are counted under the `other` client
+    pub max_entries: usize,
+    /// `User-Agent` product names reported as their own family, with version;
+    /// everything else is `browser`, `other` or `unknown`
+    pub families: Vec<String>,
+}
+
+impl Default for ClientAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 5000,
+            families: [
+                "reprime-js",
+                "reprime-python",
+                "reprime-cli",
+                "curl",
+                "python-requests",
+                "axios",
+                "okhttp",
+                "Go-http-client",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+/// A route that answers with `Deprecation` / `Sunset` headers
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeprecatedRouteConfig {
+    /// Route template, e.g. `/api/v1/users/{id}`
+    pub route: String,
+    /// Limit to one method; all methods when unset
+    pub method: Option<String>,
+    /// Date the route was deprecated (`YYYY-MM-DD`)
+    pub deprecated_on: String,
+    /// Date the route will be removed (`YYYY-MM-DD`)
+    pub sunset_on: Option<String>,
+    /// Migration guide, sent as `Link: <...>; rel="deprecation"`
+    pub link: Option<String>,
+}
+
+/// Per-tenant settings store
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TenantConfig {
+    /// How long settings are cached per tenant; updates through this
+    /// instance invalidate immediately
+    pub settings_cache_ttl_seconds: u64,
+}
+
+impl Default for TenantConfig {
+    fn default() -> Self {
+        Self {
+            settings_cache_ttl_seconds: 60,
+        }
+    }
+}
+
+/// Caching headers for deployments behind a CDN or shared cache
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct EdgeCacheConfig {
+    pub enabled: bool,
+    /// `Cache-Control` for responses no route group policy covers
+    pub default_cache_control: String,
+    /// `Cache-Control` for requests carrying credentials, whatever the group
+    pub authenticated_cache_control: String,
+    /// Route group (as in `metrics.route_groups`) -> headers for anonymous,
+    /// successful GET and HEAD responses
+    pub route_groups: HashMap<String, EdgeCachePolicy>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EdgeCachePolicy {
+    pub cache_control: Option<String>,
+    /// Honoured by the CDN and stripped before the client sees it
+    pub surrogate_control: Option<String>,
+}
+
+impl Default for EdgeCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_cache_control: "no-store".to_string(),
+            authenticated_cache_control: "private, no-store".to_string(),
+            route_groups: HashMap::from([(
+                "system".to_string(),
+                EdgeCachePolicy {
+                    cache_control: Some("public, max-age=5".to_string()),
+                    surrogate_control: Some("max-age=5".to_string()),
+                },
+            )]),
+        }
+    }
+}
+
+/// Per-client bandwidth limits for request and response bodies
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BandwidthConfig {
+    pub enabled: bool,
+    /// Route group (as in `metrics.route_groups`) -> limits; groups not
+    /// listed aren't throttled
+    pub route_groups: HashMap<String, BandwidthLimit>,
+    /// Proxies in front of the service that append to `X-Forwarded-For`;
+    /// 0 keys clients on the connecting peer and ignores the header
+    pub trusted_proxy_hops: usize,
+    /// Clients tracked at once; idle ones are dropped first
+    pub max_entries: usize,
+}
+
+impl Default for BandwidthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            route_groups: HashMap::new(),
+            trusted_proxy_hops: 0,
+            max_entries: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BandwidthLimit {
+    /// Request body rate; unlimited when unset
+    pub upload_bytes_per_sec: Option<u64>,
+    /// Response body rate; unlimited when unset
+    pub download_bytes_per_sec: Option<u64>,
+    /// Bytes that may pass at full speed before the rate applies; one
+    /// second's worth when unset
+    pub burst_bytes: Option<u64>,
+}
+
+/// Which authorization path decides role checks in a route group
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryMode {
+    /// Roles from the token only
+    #[default]
+    Legacy,
+    /// Both paths run, the role decision is served
+    Shadow,
+    /// Both paths run, the OpenFGA relation decision is served
+    Relations,
+}
+
+/// Dual evaluation of role checks against OpenFGA relations
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AuthorizationCanaryConfig {
+    pub enabled: bool,
+    /// Mode for route groups not listed in `route_groups`
+    pub default_mode: CanaryMode,
+    /// Route group (as in `metrics.route_groups`) -> mode
+    pub route_groups: HashMap<String, CanaryMode>,
+    /// Role -> relation the caller must hold on their tenant organization
+    pub role_relations: HashMap<String, String>,
+}
+
+impl Default for AuthorizationCanaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_mode: CanaryMode::Legacy,
+            route_groups: HashMap::new(),
+            role_relations: HashMap::from([
+                ("admin".to_string(), "admin".to_string()),
+                ("user".to_string(), "member".to_string()),
+            ]),
+        }
+    }
+}
+
+/// Concurrent fan-out of per-item service calls
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct FanOutConfig {
+    /// Calls in flight at once per fan-out
+    pub concurrency: usize,
+    /// A single call slower than this counts as failed
+    pub item_timeout_ms: u64,
+}
+
+impl Default for FanOutConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            item_timeout_ms: 5000,
+        }
+    }
+}
+
+/// Dependency checks behind `/ready`, cached so frequent probes don't each
+/// reach Postgres and OpenFGA
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub enabled: bool,
+    /// Results younger than this are served from cache
+    pub ttl_ms: u64,
+    /// Background refreshes run every `ttl_ms` less up to this much, so
+    /// replicas don't probe in lockstep
+    pub refresh_jitter_ms: u64,
+    /// Budget per dependency; a slower check counts as failed
+    pub database_timeout_ms: u64,
+    pub openfga_timeout_ms: u64,
+    /// Only checked when logs are exported to Loki; never critical
+    pub loki_timeout_ms: u64,
+    /// Whether OpenFGA being down makes the instance unready; like warmup,
+    /// only the database does by default
+    pub openfga_critical: bool,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_ms: 5000,
+            refresh_jitter_ms: 1000,
+            database_timeout_ms: 1000,
+            openfga_timeout_ms: 1000,
+            loki_timeout_ms: 1000,
+            openfga_critical: false,
+        }
+    }
+}
+
+/// Identical concurrent GETs to these routes share one execution
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CoalesceConfig {
+    pub enabled: bool,
+    /// Route templates, e.g. `/api/v1/admin/api-usage`; only list
+    /// idempotent endpoints with bounded responses
+    pub routes: Vec<String>,
+    /// Buffered per execution; a larger response fails every caller
+    pub max_body_bytes: usize,
+}
+
+impl Default for CoalesceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            routes: vec![
+                "/api/v1/admin/api-usage".to_string(),
+                "/api/v1/admin/clients".to_string(),
+            ],
+            max_body_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Audit log retention
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Entries older than this are deleted; 0 keeps them forever
+    pub retention_days: u32,
+    /// How often expired entries are purged
+    pub purge_interval_seconds: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 365,
+            purge_interval_seconds: 3600,
+        }
+    }
+}
+
+/// Admin-set service banner (`PUT /api/v1/admin/banner`)
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BannerConfig {
+    /// Also send the banner as `X-Service-Notice` on every response
+    pub notice_header: bool,
+}
+
+/// Object storage for uploaded files, see [`crate::services::storage`]
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// `local` (files under `local_path`, served by the API) or `s3`
+    pub provider: String,
+    pub local_path: String,
+    /// Prepended to local-disk URLs, e.g. `https://api.example.com`; the
+    /// URLs are relative to the API when empty
+    pub public_base_url: String,
+    /// Key signing local-disk URLs; derived from `auth.jwt_secret` when unset
+    pub signing_secret: Option<String>,
+    /// How long the URLs in responses stay valid
+    pub signed_url_ttl_seconds: u64,
+    pub s3: S3StorageConfig,
+}
+
+redacted_debug!(StorageConfig {
+    provider, local_path, public_base_url, signed_url_ttl_seconds, s3;
+    redact signing_secret
+});
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            provider: "local".to_string(),
+            local_path: "data/uploads".to_string(),
+            public_base_url: String::new(),
+            signing_secret: None,
+            signed_url_ttl_seconds: 3600,
+            s3: S3StorageConfig::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct S3StorageConfig {
+    pub bucket: String,
+    pub region: String,
+    /// For S3-compatible stores (MinIO, R2), e.g. `http://localhost:9000`;
+    /// AWS when unset
+    pub endpoint: Option<String>,
+    /// `<endpoint>/<bucket>/<key>` instead of `<bucket>.<endpoint host>/<key>`
+    pub path_style: bool,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+redacted_debug!(S3StorageConfig {
+    bucket, region, endpoint, path_style, access_key_id;
+    redact secret_access_key
+});
+
+impl Default for S3StorageConfig {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            endpoint: None,
+            path_style: false,
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+        }
+    }
+}
+
+/// Avatar uploads (`PUT /api/v1/users/{id}/avatar`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AvatarConfig {
+    pub max_bytes: usize,
+    /// Accepted image types, out of PNG, JPEG, WebP and GIF
+    pub content_types: Vec<String>,
+}
+
+impl Default for AvatarConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 2 * 1024 * 1024,
+            content_types: vec![
+                "image/png".to_string(),
+                "image/jpeg".to_string(),
+                "image/webp".to_string(),
+            ],
+        }
+    }
+}
+
+/// Declarative seed data applied once migrations have run
+///
+/// For development and demos only: the seed's users have known passwords.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SeedConfig {
+    pub enabled: bool,
+    /// Seed file, see [`crate::services::seed::Seed`]
+    pub file: String,
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: "config/seeds/demo.toml".to_string(),
+        }
+    }
+}
+
+/// What each request's [`crate::request_context::RequestContext`] falls
+/// back to
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RequestContextConfig {
+    /// Locales `Accept-Language` is matched against
+    pub supported_locales: Vec<String>,
+    /// Used when the caller asks for none of the supported locales
+    pub default_locale: String,
+    pub default_timezone: String,
+    /// Time a request has to complete; callers can ask for less with
+    /// `X-Request-Timeout-Ms`
+    pub timeout_ms: u64,
+}
+
+impl Default for RequestContextConfig {
+    fn default() -> Self {
+        Self {
+            supported_locales: vec!["en".to_string()],
+            default_locale: "en".to_string(),
+            default_timezone: "UTC".to_string(),
+            timeout_ms: 30000,
+        }
+    }
+}
+
+/// Redis connection for shared caches
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RedisConfig {
+    pub url: String,
+    /// Prepended to every key so several deployments can share a Redis
+    pub key_prefix: String,
+    pub connect_timeout_ms: u64,
+    /// A command slower than this is treated as a cache miss
+    pub response_timeout_ms: u64,
+}
+
+redacted_debug!(RedisConfig {
+    key_prefix, connect_timeout_ms, response_timeout_ms;
+    redact url
+});
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://localhost:6379".to_string(),
+            key_prefix: "reprime:".to_string(),
+            connect_timeout_ms: 500,
+            response_timeout_ms: 200,
+        }
+    }
+}
+
+/// Background job worker
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Run a worker in this instance; jobs are still enqueued when disabled
+    pub enabled: bool,
+    /// How often an idle worker looks for jobs
+    pub poll_interval_ms: u64,
+    /// Attempts before a job is marked failed
+    pub max_attempts: i32,
+    /// Delay before the first retry; doubles with each attempt
+    pub retry_backoff_secs: u64,
+    /// A running job not finished within this is assumed abandoned and retried;
+    /// the worker that held it can no longer record its result
+    pub lease_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_ms: 1000,
+            max_attempts: 5,
+            retry_backoff_secs: 30,
+            lease_secs: 300,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
 pub struct OpenFgaConfig {
     pub endpoint: String,
     pub store_id: String,
     pub auth_model_id: Option<String>,
+    /// Authorization model (JSON) applied on startup; overrides `auth_model_id`
+    #[serde(default)]
+    pub model_file: Option<String>,
     pub api_token: Option<String>,
     pub cache_enabled: bool,
     pub cache_ttl_seconds: u64,
     pub cache_max_entries: usize,
+    /// TTL for denied results; `cache_ttl_seconds` when unset
+    #[serde(default)]
+    pub cache_denied_ttl_seconds: Option<u64>,
+    /// Relation -> TTL for its allowed results; also caps its denied TTL
+    #[serde(default)]
+    pub cache_relation_ttl_seconds: HashMap<String, u64>,
+    /// Where permission checks are cached; `redis` shares them across replicas
+    #[serde(default)]
+    pub cache_backend: PermissionCacheBackendKind,
+    /// Ceiling for any call, connecting included; `timeouts` sets the
+    /// tighter per-operation budgets
     pub request_timeout_seconds: u64,
+    #[serde(default)]
+    pub timeouts: OpenFgaTimeoutsConfig,
+    /// How permission checks are sent; everything else uses `endpoint`
+    #[serde(default)]
+    pub transport: OpenFgaTransportKind,
+    /// OpenFGA's gRPC listener, used when `transport` is `grpc`
+    #[serde(default = "default_openfga_grpc_endpoint")]
+    pub grpc_endpoint: String,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub changes: OpenFgaChangesConfig,
+    /// Retrying checks and reads that failed on OpenFGA's side
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Tenant -> dedicated store; other tenants use `store_id`
+    #[serde(default)]
+    pub tenant_stores: HashMap<String, OpenFgaStoreConfig>,
+    /// Checking on startup that each store's model defines every
+    /// registered type and relation
+    #[serde(default)]
+    pub preflight: ModelPreflight,
+    /// Bounds on listing the objects a user holds a relation on
+    #[serde(default)]
+    pub list_objects: ListObjectsConfig,
+}
+
+redacted_debug!(OpenFgaConfig {
+    endpoint, store_id, auth_model_id, model_file, cache_enabled, cache_ttl_seconds,
+    cache_max_entries, cache_denied_ttl_seconds, cache_relation_ttl_seconds, cache_backend,
+    request_timeout_seconds, timeouts, transport, grpc_endpoint, circuit_breaker, changes,
+    retry, tenant_stores, preflight, list_objects;
+    redact api_token
+});
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelPreflight {
+    Off,
+    /// Log what's missing and start anyway
+    #[default]
+    Warn,
+    /// Refuse to start while anything is missing
+    Enforce,
+}
+
+/// A store holding one tenant's authorization data apart from the others
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenFgaStoreConfig {
+    pub store_id: String,
+    /// Model pinned for the store; its latest model when unset
+    #[serde(default)]
+    pub auth_model_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenFgaTransportKind {
+    /// JSON over HTTP to `endpoint`
+    #[default]
+    Http,
+    /// gRPC to `grpc_endpoint`
+    Grpc,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionCacheBackendKind {
+    /// Per process, bounded by `cache_max_entries`
+    #[default]
+    Memory,
+    /// Shared through `[redis]`; entries expire by TTL in Redis
+    Redis,
+}
+
+fn default_openfga_grpc_endpoint() -> String {
+    "http://localhost:8081".to_string()
+}
+
+/// Time budget per kind of OpenFGA call; a call over budget fails with a
+/// timeout instead of holding the request
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OpenFgaTimeoutsConfig {
+    /// Permission checks; on the request path, so kept short
+    pub check_ms: u64,
+    /// Listing objects, users and tuples, reading models and health checks
+    pub read_ms: u64,
+    /// Tuple and model writes
+    pub write_ms: u64,
+}
+
+impl Default for OpenFgaTimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            check_ms: 500,
+            read_ms: 5000,
+            write_ms: 10000,
+        }
+    }
+}
+
+/// Polling the store's change log to invalidate cached checks
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OpenFgaChangesConfig {
+    pub enabled: bool,
+    pub poll_interval_ms: u64,
+    /// Changes read per request; OpenFGA allows at most 100
+    pub page_size: u32,
+}
+
+impl Default for OpenFgaChangesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_ms: 2000,
+            page_size: 100,
+        }
+    }
+}
+
+/// Listing the objects a user holds a relation on
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ListObjectsConfig {
+    /// Objects read per listing at most; the rest are dropped with a warning
+    pub max_results: usize,
+    /// Objects per page when the caller doesn't ask for a size
+    pub default_page_size: u32,
+    /// Largest page a caller may ask for
+    pub max_page_size: u32,
+}
+
+impl Default for ListObjectsConfig {
+    fn default() -> Self {
+        Self {
+            max_results: 1000,
+            default_page_size: 50,
+            max_page_size: 200,
+        }
+    }
+}
+
+/// Backoff and budget for retrying a call, see [`crate::utils::retry`]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts in total, the first included; 1 disables retries
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    pub jitter: Jitter,
+    /// Retries earned per call; caps retries to this share of traffic
+    pub budget_ratio: f64,
+    /// Retries allowed before the ratio applies
+    pub budget_burst: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 25,
+            max_delay_ms: 250,
+            multiplier: 2.0,
+            jitter: Jitter::Full,
+            budget_ratio: 0.1,
+            budget_burst: 10,
+        }
+    }
+}
+
+/// What a permission check answers while OpenFGA is unavailable
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackMode {
+    /// Deny
+    #[default]
+    FailClosed,
+    /// Allow; only for relations where an outage matters more than access
+    FailOpen,
+}
+
+/// Stops calling OpenFGA after repeated failures, answering checks from the
+/// fallback policy instead of waiting on timeouts
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Consecutive failures (errors or 5xx) that open the breaker
+    pub failure_threshold: u32,
+    /// How long it stays open before one request is let through to probe
+    pub open_seconds: u64,
+    pub fallback: FallbackMode,
+    /// Per-relation overrides of `fallback`
+    pub relation_fallback: HashMap<String, FallbackMode>,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: 5,
+            open_seconds: 30,
+            fallback: FallbackMode::FailClosed,
+            relation_fallback: HashMap::new(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn fallback_for(&self, relation: &str) -> FallbackMode {
+        self.relation_fallback
+            .get(relation)
+            .copied()
+            .unwrap_or(self.fallback)
+    }
 }
 
 impl Config {
@@ -103,6 +1635,12 @@ impl Default for Config {
                 acquire_timeout: 30,
                 idle_timeout: 600,
                 max_lifetime: 1800,
+                auth: None,
+                shards: Vec::new(),
+                slow_query: SlowQueryConfig::default(),
+                statement_recording: StatementRecording::default(),
+                migrations: MigrationConfig::default(),
+                id_strategy: IdStrategy::default(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -115,21 +1653,77 @@ impl Default for Config {
                 enable_tracing: true,
                 enable_metrics: true,
                 enable_logging: true,
+                log_exporter: default_log_exporter(),
+                otlp_log_queue_size: default_otlp_log_queue_size(),
+                loki: LokiLabelConfig::default(),
             },
             auth: AuthConfig {
                 jwt_secret: "your-secret-key-change-in-production".to_string(),
                 jwt_expiration_hours: 24,
+                refresh_token_expiration_days: default_refresh_token_expiration_days(),
+                jwt_keys: Vec::new(),
+                jwt_active_kid: None,
+                jwt_accept_shared_secret_until: None,
+                jwt_issuer: default_jwt_issuer(),
+                jwt_audience: default_jwt_audience(),
+                jwt_require_issuer_audience: true,
+                session_cache: SessionCacheConfig::default(),
+                remember_me: RememberMeConfig::default(),
+                cookies: SessionCookieConfig::default(),
+                password_reset: PasswordResetConfig::default(),
+                new_device: NewDeviceConfig::default(),
+                onboarding: OnboardingConfig::default(),
+                webauthn: WebAuthnConfig::default(),
+                login_rate_limit: LoginRateLimitConfig::default(),
+                service_accounts: ServiceAccountConfig::default(),
+                authentication: AuthenticationConfig::default(),
                 openfga: OpenFgaConfig {
                     endpoint: "http://localhost:8080".to_string(),
                     store_id: "01ARZ3NDEKTSV4RRFFQ69G5FAV".to_string(),
                     auth_model_id: None,
+                    model_file: None,
                     api_token: None,
                     cache_enabled: true,
                     cache_ttl_seconds: 300,
                     cache_max_entries: 50000,
+                    cache_denied_ttl_seconds: None,
+                    cache_relation_ttl_seconds: HashMap::new(),
+                    cache_backend: PermissionCacheBackendKind::Memory,
                     request_timeout_seconds: 30,
+                    timeouts: OpenFgaTimeoutsConfig::default(),
+                    transport: OpenFgaTransportKind::Http,
+                    grpc_endpoint: default_openfga_grpc_endpoint(),
+                    circuit_breaker: CircuitBreakerConfig::default(),
+                    changes: OpenFgaChangesConfig::default(),
+                    retry: RetryConfig::default(),
+                    tenant_stores: HashMap::new(),
+                    preflight: ModelPreflight::Warn,
+                    list_objects: ListObjectsConfig::default(),
                 },
             },
+            metrics: MetricsConfig::default(),
+            mirror: MirrorConfig::default(),
+            pagination: PaginationConfig::default(),
+            live_tail: LiveTailConfig::default(),
+            mailer: MailerConfig::default(),
+            tenant: TenantConfig::default(),
+            api_usage: ApiUsageConfig::default(),
+            client_analytics: ClientAnalyticsConfig::default(),
+            anonymize: AnonymizeConfig::default(),
+            jobs: JobsConfig::default(),
+            edge_cache: EdgeCacheConfig::default(),
+            redis: RedisConfig::default(),
+            bandwidth: BandwidthConfig::default(),
+            fanout: FanOutConfig::default(),
+            authorization_canary: AuthorizationCanaryConfig::default(),
+            health: HealthConfig::default(),
+            coalesce: CoalesceConfig::default(),
+            audit: AuditConfig::default(),
+            request_context: RequestContextConfig::default(),
+            banner: BannerConfig::default(),
+            storage: StorageConfig::default(),
+            avatars: AvatarConfig::default(),
+            seed: SeedConfig::default(),
         }
     }
 }
@@ -0,0 +1,172 @@
+use crate::database::instrumentation::{record_statement, SlowQueryLog};
+use crate::{dependencies, request_cost};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
+use sqlx::postgres::{PgQueryResult, PgRow, PgStatement, PgTypeInfo};
+use sqlx::error::ErrorKind;
+use sqlx::{Describe, Either, Error, Execute, Executor, PgPool, Postgres};
+use std::ops::Deref;
+use std::time::Instant;
+use tracing::{Instrument, Span};
+
+/// A `&PgPool` that charges each statement to the current request's cost,
+/// records it in the Postgres dependency stats and on a `database_statement`
+/// span (see [`crate::database::set_statement_recording`]), and hands slow
+/// ones to the [`SlowQueryLog`]
+///
+/// Derefs to the pool, so transactions and explicit `acquire()` still work;
+/// statements run on a transaction or acquired connection aren't counted.
+#[derive(Debug, Clone, Copy)]
+pub struct AccountedPool<'p> {
+    pool: &'p PgPool,
+    slow_query_log: Option<&'p SlowQueryLog>,
+}
+
+impl<'p> AccountedPool<'p> {
+    pub fn new(pool: &'p PgPool) -> Self {
+        Self {
+            pool,
+            slow_query_log: None,
+        }
+    }
+
+    /// Log statements exceeding the slow query threshold
+    pub fn with_slow_query_log(mut self, slow_query_log: Option<&'p SlowQueryLog>) -> Self {
+        self.slow_query_log = slow_query_log;
+        self
+    }
+
+    fn start<'e>(&self, sql: &'e str) -> StatementTimer<'e>
+    where
+        'p: 'e,
+    {
+        let span = tracing::info_span!(
+            "database_statement",
+            db.query_name = tracing::field::Empty,
+            db.bind_count = tracing::field::Empty,
+            db.statement = tracing::field::Empty,
+        );
+        if !span.is_disabled() {
+            record_statement(&span, sql);
+        }
+
+        StatementTimer {
+            started: Instant::now(),
+            error: None,
+            sql,
+            pool: self.pool,
+            slow_query_log: self.slow_query_log,
+            span,
+        }
+    }
+}
+
+impl Deref for AccountedPool<'_> {
+    type Target = PgPool;
+
+    fn deref(&self) -> &PgPool {
+        self.pool
+    }
+}
+
+/// Records the statement once its future or stream is dropped, so abandoned
+/// statements are counted too; the time includes waiting for a connection
+struct StatementTimer<'p> {
+    started: Instant,
+    error: Option<String>,
+    sql: &'p str,
+    pool: &'p PgPool,
+    slow_query_log: Option<&'p SlowQueryLog>,
+    span: Span,
+}
+
+impl StatementTimer<'_> {
+
+    fn observe<T>(&mut self, result: &Result<T, Error>) {
+        if let Err(e) = result {
+            // Missing rows and constraint violations are answers, not outages
+            let answered = match e {
+                Error::RowNotFound => true,
+                Error::Database(e) => e.kind() != ErrorKind::Other,
+                _ => false,
+            };
+            if !answered {
+                self.error.get_or_insert_with(|| e.to_string());
+            }
+        }
+    }
+}
+
+impl Drop for StatementTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        request_cost::record_db_query(elapsed);
+        dependencies::record(dependencies::POSTGRES, elapsed, self.error.take());
+        if let Some(slow_query_log) = self.slow_query_log {
+            let _entered = self.span.enter();
+            slow_query_log.check(self.pool, self.sql, elapsed);
+        }
+    }
+}
+
+impl<'p> Executor<'p> for AccountedPool<'p> {
+    type Database = Postgres;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<Either<PgQueryResult, PgRow>, Error>>
+    where
+        'p: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        let mut timer = self.start(query.sql());
+        self.pool
+            .fetch_many(query)
+            .map(move |step| {
+                timer.observe(&step);
+                step
+            })
+            .boxed()
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<PgRow>, Error>>
+    where
+        'p: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        let mut timer = self.start(query.sql());
+        let span = timer.span.clone();
+        self.pool
+            .fetch_optional(query)
+            .map(move |row| {
+                timer.observe(&row);
+                drop(timer);
+                row
+            })
+            .instrument(span)
+            .boxed()
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [PgTypeInfo],
+    ) -> BoxFuture<'e, Result<PgStatement<'q>, Error>>
+    where
+        'p: 'e,
+    {
+        self.pool.prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(self, sql: &'q str) -> BoxFuture<'e, Result<Describe<Postgres>, Error>>
+    where
+        'p: 'e,
+    {
+        self.pool.describe(sql)
+    }
+}
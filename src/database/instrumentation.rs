@@ -1,17 +1,216 @@
-use sqlx::PgPool;
-use std::time::Instant;
-use tracing::{instrument, Span};
+use sqlx::{Executor, PgPool, Row};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{instrument, Instrument, Span};
+use crate::config::{SlowQueryConfig, StatementRecording};
+use crate::database::AccountedPool;
 use crate::metrics::AppMetrics;
 
+static STATEMENT_RECORDING: AtomicU8 = AtomicU8::new(StatementRecording::Name as u8);
+
+/// Change what database spans record about statements, effective immediately
+pub fn set_statement_recording(mode: StatementRecording) {
+    STATEMENT_RECORDING.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn statement_recording() -> StatementRecording {
+    match STATEMENT_RECORDING.load(Ordering::Relaxed) {
+        x if x == StatementRecording::Statement as u8 => StatementRecording::Statement,
+        x if x == StatementRecording::BindCount as u8 => StatementRecording::BindCount,
+        _ => StatementRecording::Name,
+    }
+}
+
+/// Record statement details on a DB span according to the current mode
+pub(crate) fn record_statement(span: &Span, query: &str) {
+    let mode = statement_recording();
+
+    span.record(
+        "db.query_name",
+        format!("{} {}", extract_query_type(query), extract_table_name(query)),
+    );
+
+    if mode != StatementRecording::Name {
+        span.record("db.bind_count", count_bind_parameters(query));
+    }
+
+    if mode == StatementRecording::Statement {
+        span.record("db.statement", query);
+    }
+}
+
+/// Number of distinct `$n` placeholders in a statement
+pub fn count_bind_parameters(query: &str) -> usize {
+    let mut highest = 0;
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            let mut digits = String::new();
+            while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                digits.push(*d);
+                chars.next();
+            }
+            if let Ok(index) = digits.parse::<usize>() {
+                highest = highest.max(index);
+            }
+        }
+    }
+
+    highest
+}
+
+/// Slow query detection with rate-limited `EXPLAIN` capture
+#[derive(Debug)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    explain: bool,
+    explain_per_minute: u32,
+    // (window start, plans captured in the window)
+    window: Mutex<(Instant, u32)>,
+}
+
+impl SlowQueryLog {
+    pub fn new(config: &SlowQueryConfig) -> Self {
+        Self {
+            threshold: Duration::from_millis(config.threshold_ms),
+            explain: config.explain,
+            explain_per_minute: config.explain_per_minute,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    pub fn is_slow(&self, duration: Duration) -> bool {
+        duration >= self.threshold
+    }
+
+    /// Whether a plan may be captured now, consuming one slot of the budget
+    pub fn try_acquire_explain(&self) -> bool {
+        if !self.explain {
+            return false;
+        }
+
+        let mut window = self.window.lock().unwrap();
+        if window.0.elapsed() >= Duration::from_secs(60) {
+            *window = (Instant::now(), 0);
+        }
+
+        if window.1 >= self.explain_per_minute {
+            return false;
+        }
+
+        window.1 += 1;
+        true
+    }
+
+    /// Log `query` if it took `duration` or longer, capturing its plan in
+    /// the background when allowed
+    ///
+    /// Parameterized statements get their generic plan, the one used for
+    /// any parameter values.
+    pub fn check(&self, pool: &PgPool, query: &str, duration: Duration) {
+        if !self.is_slow(duration) {
+            return;
+        }
+
+        let query_type = extract_query_type(query);
+        let table_name = extract_table_name(query);
+        let duration_ms = duration.as_millis() as u64;
+
+        let runtime = tokio::runtime::Handle::try_current();
+        let (Ok(runtime), true) = (runtime, is_explainable(&query_type) && self.try_acquire_explain())
+        else {
+            tracing::warn!(
+                query_type = %query_type,
+                table = %table_name,
+                duration_ms = duration_ms,
+                statement = %query,
+                "Slow database query"
+            );
+            return;
+        };
+
+        let pool = pool.clone();
+        let statement = query.to_string();
+        runtime.spawn(
+            async move {
+                let plan = match explain(&pool, &statement).await {
+                    Ok(plan) => plan.to_string(),
+                    Err(e) => format!("unavailable: {}", e),
+                };
+
+                tracing::warn!(
+                    query_type = %query_type,
+                    table = %table_name,
+                    duration_ms = duration_ms,
+                    statement = %statement,
+                    plan = %plan,
+                    "Slow database query"
+                );
+            }
+            .instrument(Span::current()),
+        );
+    }
+}
+
+/// `query`'s plan as JSON; EXPLAIN without ANALYZE only plans the statement,
+/// so it's safe for writes
+///
+/// Sent with the simple query protocol, so `$n` placeholders reach the
+/// server as part of the text rather than as parameters to bind.
+async fn explain(pool: &PgPool, query: &str) -> Result<serde_json::Value, sqlx::Error> {
+    let parameters = count_bind_parameters(query);
+    if parameters == 0 {
+        let row = sqlx::raw_sql(&format!("EXPLAIN (FORMAT JSON) {}", query))
+            .fetch_one(pool)
+            .await?;
+        return row.try_get(0);
+    }
+
+    // Postgres 16 plans placeholders directly
+    if let Ok(row) = sqlx::raw_sql(&format!("EXPLAIN (GENERIC_PLAN, FORMAT JSON) {}", query))
+        .fetch_one(pool)
+        .await
+    {
+        return row.try_get(0);
+    }
+
+    // Before that, prepare it and explain executing it with nulls while
+    // forcing the generic plan. The prepared statement and setting live on
+    // the connection, so it's closed rather than returned to the pool.
+    let mut connection = pool.acquire().await?.detach();
+    let prepare = format!(
+        "SET plan_cache_mode = force_generic_plan; PREPARE slow_query_plan AS {}",
+        query
+    );
+    connection.execute(prepare.as_str()).await?;
+    let nulls = vec!["NULL"; parameters].join(", ");
+    let explain = format!("EXPLAIN (FORMAT JSON) EXECUTE slow_query_plan({})", nulls);
+    let row = connection.fetch_one(explain.as_str()).await?;
+    row.try_get(0)
+}
+
 /// Database instrumentation wrapper for query metrics and tracing
 pub struct InstrumentedDatabase {
     pool: PgPool,
     metrics: Option<AppMetrics>,
+    slow_query_log: Option<Arc<SlowQueryLog>>,
 }
 
 impl InstrumentedDatabase {
     pub fn new(pool: PgPool, metrics: Option<AppMetrics>) -> Self {
-        Self { pool, metrics }
+        Self {
+            pool,
+            metrics,
+            slow_query_log: None,
+        }
+    }
+
+    /// Log statements exceeding the slow query threshold
+    pub fn with_slow_query_log(mut self, slow_query_log: Arc<SlowQueryLog>) -> Self {
+        self.slow_query_log = Some(slow_query_log);
+        self
     }
 
     /// Execute a query with full instrumentation
@@ -20,7 +219,9 @@ impl InstrumentedDatabase {
         skip(self, query),
         fields(
             db.operation = "query",
-            db.statement = %query,
+            db.query_name = tracing::field::Empty,
+            db.bind_count = tracing::field::Empty,
+            db.statement = tracing::field::Empty,
             db.rows_affected = tracing::field::Empty,
             duration_ms = tracing::field::Empty,
             trace_id = tracing::field::Empty,
@@ -32,14 +233,15 @@ impl InstrumentedDatabase {
     {
         let start = Instant::now();
         let span = Span::current();
-        
+        record_statement(&span, query);
+
         // Add trace correlation
         if let Some(trace_id) = crate::telemetry::current_trace_id() {
             span.record("trace_id", &trace_id);
         }
 
         let result = sqlx::query_as::<_, T>(query)
-            .fetch_one(&self.pool)
+            .fetch_one(self.pool())
             .await;
 
         let duration = start.elapsed();
@@ -91,7 +293,9 @@ impl InstrumentedDatabase {
         skip(self, query),
         fields(
             db.operation = "query_many",
-            db.statement = %query,
+            db.query_name = tracing::field::Empty,
+            db.bind_count = tracing::field::Empty,
+            db.statement = tracing::field::Empty,
             db.rows_affected = tracing::field::Empty,
             duration_ms = tracing::field::Empty,
             trace_id = tracing::field::Empty,
@@ -103,14 +307,15 @@ impl InstrumentedDatabase {
     {
         let start = Instant::now();
         let span = Span::current();
-        
+        record_statement(&span, query);
+
         // Add trace correlation
         if let Some(trace_id) = crate::telemetry::current_trace_id() {
             span.record("trace_id", &trace_id);
         }
 
         let result = sqlx::query_as::<_, T>(query)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pool())
             .await;
 
         let duration = start.elapsed();
@@ -163,7 +368,9 @@ impl InstrumentedDatabase {
         skip(self, query),
         fields(
             db.operation = "execute",
-            db.statement = %query,
+            db.query_name = tracing::field::Empty,
+            db.bind_count = tracing::field::Empty,
+            db.statement = tracing::field::Empty,
             db.rows_affected = tracing::field::Empty,
             duration_ms = tracing::field::Empty,
             trace_id = tracing::field::Empty,
@@ -172,14 +379,15 @@ impl InstrumentedDatabase {
     pub async fn execute_command(&self, query: &str) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
         let start = Instant::now();
         let span = Span::current();
-        
+        record_statement(&span, query);
+
         // Add trace correlation
         if let Some(trace_id) = crate::telemetry::current_trace_id() {
             span.record("trace_id", &trace_id);
         }
 
         let result = sqlx::query(query)
-            .execute(&self.pool)
+            .execute(self.pool())
             .await;
 
         let duration = start.elapsed();
@@ -239,9 +447,10 @@ impl InstrumentedDatabase {
         (active, idle as u32, size)
     }
 
-    /// Get the underlying pool for direct access (when instrumentation is not needed)
-    pub fn pool(&self) -> &PgPool {
-        &self.pool
+    /// The pool for queries that don't need metrics; statements still count
+    /// towards the current request's cost and go through the slow query log
+    pub fn pool(&self) -> AccountedPool<'_> {
+        AccountedPool::new(&self.pool).with_slow_query_log(self.slow_query_log.as_deref())
     }
 
 
@@ -266,6 +475,11 @@ fn extract_table_name(query: &str) -> String {
     "unknown".to_string()
 }
 
+/// Only DML can be explained
+fn is_explainable(query_type: &str) -> bool {
+    matches!(query_type, "SELECT" | "INSERT" | "UPDATE" | "DELETE")
+}
+
 /// Extract query type from SQL query
 fn extract_query_type(query: &str) -> String {
     let query_trimmed = query.trim().to_lowercase();
@@ -1,3 +1,10 @@
+pub mod accounted;
 pub mod instrumentation;
+pub mod shard;
 
-pub use instrumentation::InstrumentedDatabase;
+pub use accounted::AccountedPool;
+pub use instrumentation::{
+    count_bind_parameters, set_statement_recording, statement_recording, InstrumentedDatabase,
+    SlowQueryLog,
+};
+pub use shard::{ShardKey, ShardRouter};
@@ -0,0 +1,98 @@
+use crate::database::InstrumentedDatabase;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Key used to pick the shard that owns a row
+#[derive(Debug, Clone, Copy)]
+pub enum ShardKey<'a> {
+    User(Uuid),
+    Tenant(&'a str),
+}
+
+impl ShardKey<'_> {
+    fn hash(&self) -> u64 {
+        match self {
+            ShardKey::User(id) => fnv1a(id.as_bytes()),
+            ShardKey::Tenant(tenant) => fnv1a(tenant.as_bytes()),
+        }
+    }
+}
+
+/// Maps tenant/user IDs to one of several Postgres pools
+///
+/// Shard 0 is always the primary database. Placement uses jump consistent
+/// hashing, so adding a shard only moves ~1/n of the keys. With a single
+/// shard every key resolves to the primary, which keeps unsharded
+/// deployments unchanged.
+pub struct ShardRouter {
+    shards: Vec<Arc<InstrumentedDatabase>>,
+}
+
+impl ShardRouter {
+    pub fn new(primary: Arc<InstrumentedDatabase>, additional: Vec<Arc<InstrumentedDatabase>>) -> Self {
+        let mut shards = Vec::with_capacity(additional.len() + 1);
+        shards.push(primary);
+        shards.extend(additional);
+        Self { shards }
+    }
+
+    /// Router with no additional shards
+    pub fn single(primary: Arc<InstrumentedDatabase>) -> Self {
+        Self::new(primary, Vec::new())
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn is_sharded(&self) -> bool {
+        self.shards.len() > 1
+    }
+
+    /// Database for data that isn't partitioned
+    pub fn primary(&self) -> &Arc<InstrumentedDatabase> {
+        &self.shards[0]
+    }
+
+    /// All shards, for queries that have to fan out
+    pub fn all(&self) -> &[Arc<InstrumentedDatabase>] {
+        &self.shards
+    }
+
+    pub fn shard_index(&self, key: ShardKey<'_>) -> usize {
+        jump_consistent_hash(key.hash(), self.shards.len())
+    }
+
+    pub fn resolve(&self, key: ShardKey<'_>) -> &Arc<InstrumentedDatabase> {
+        &self.shards[self.shard_index(key)]
+    }
+
+    pub fn for_user(&self, user_id: Uuid) -> &Arc<InstrumentedDatabase> {
+        self.resolve(ShardKey::User(user_id))
+    }
+
+    pub fn for_tenant(&self, tenant_id: &str) -> &Arc<InstrumentedDatabase> {
+        self.resolve(ShardKey::Tenant(tenant_id))
+    }
+}
+
+/// Lamping & Veach jump consistent hash
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+
+    while j < buckets as i64 {
+        b = j;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+
+    b.max(0) as usize
+}
+
+/// Stable across processes and releases, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,195 @@
+//! Availability and latency of the downstream dependencies, as this instance
+//! sees them
+//!
+//! Every call to Postgres (through [`crate::database::AccountedPool`]),
+//! OpenFGA and named [`crate::client::HttpClient`]s is recorded here with its
+//! latency and, if it failed, why. [`DependencyStats::snapshot`] summarizes
+//! the most recent calls per dependency for the admin dependencies report,
+//! so on-call can tell which dependency an incident starts from without
+//! correlating dashboards.
+//!
+//! Stats live in memory, per instance, and reset on restart.
+
+use crate::models::format;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+pub const POSTGRES: &str = "postgres";
+pub const OPENFGA: &str = "openfga";
+pub const LOKI: &str = "loki";
+
+/// Calls per dependency that availability and latency are computed over
+const WINDOW: usize = 1000;
+/// Error samples kept per dependency
+const ERROR_SAMPLES: usize = 5;
+/// Error messages are cut to this many characters
+const MAX_ERROR_LEN: usize = 300;
+
+static GLOBAL: OnceLock<DependencyStats> = OnceLock::new();
+
+/// Record a call to `dependency` in the process-wide stats
+pub fn record(dependency: &str, elapsed: Duration, error: Option<String>) {
+    DependencyStats::global().record(dependency, elapsed, error);
+}
+
+/// One failed call
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ErrorSample {
+    pub message: String,
+    #[serde(with = "format::timestamp")]
+    pub at: DateTime<Utc>,
+}
+
+/// Latency of the calls in the window, in milliseconds
+#[derive(Debug, Clone, Copy, Default, Serialize, ToSchema)]
+pub struct LatencySummary {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// What this instance has observed of one dependency
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DependencyReport {
+    #[schema(example = "postgres")]
+    pub name: String,
+    /// Calls since startup
+    pub calls: u64,
+    /// Failed calls since startup
+    pub errors: u64,
+    /// Calls the availability and latency below are computed over
+    pub window_calls: usize,
+    /// Share of the window's calls that succeeded, from 0 to 1
+    pub availability: f64,
+    pub latency: LatencySummary,
+    /// `closed`, `half_open` or `open` for dependencies behind a circuit
+    /// breaker
+    pub circuit_breaker: Option<String>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_call_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Most recent failures, newest first
+    pub recent_errors: Vec<ErrorSample>,
+}
+
+#[derive(Debug, Default)]
+struct Observed {
+    calls: u64,
+    errors: u64,
+    /// Latency in microseconds and whether the call succeeded, oldest first
+    window: VecDeque<(u64, bool)>,
+    last_call_at: Option<DateTime<Utc>>,
+    recent_errors: VecDeque<ErrorSample>,
+}
+
+impl Observed {
+    fn report(&self, name: &str) -> DependencyReport {
+        let mut latencies: Vec<u64> = self.window.iter().map(|(micros, _)| *micros).collect();
+        latencies.sort_unstable();
+        let percentile = |p: f64| match latencies.len() {
+            0 => 0.0,
+            len => {
+                let index = ((len as f64 * p).ceil() as usize).clamp(1, len) - 1;
+                latencies[index] as f64 / 1000.0
+            }
+        };
+
+        let succeeded = self.window.iter().filter(|(_, ok)| *ok).count();
+        DependencyReport {
+            name: name.to_string(),
+            calls: self.calls,
+            errors: self.errors,
+            window_calls: self.window.len(),
+            availability: match self.window.len() {
+                0 => 1.0,
+                len => succeeded as f64 / len as f64,
+            },
+            latency: LatencySummary {
+                p50_ms: percentile(0.50),
+                p95_ms: percentile(0.95),
+                p99_ms: percentile(0.99),
+                max_ms: percentile(1.0),
+            },
+            circuit_breaker: None,
+            last_call_at: self.last_call_at,
+            last_error_at: self.recent_errors.front().map(|sample| sample.at),
+            recent_errors: self.recent_errors.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Calls observed per dependency
+#[derive(Debug, Default)]
+pub struct DependencyStats {
+    dependencies: Mutex<BTreeMap<String, Observed>>,
+}
+
+impl DependencyStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stats [`record`] writes to
+    pub fn global() -> &'static Self {
+        GLOBAL.get_or_init(Self::new)
+    }
+
+    /// Make `dependency` show up in reports before its first call
+    pub fn register(&self, dependency: &str) {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .entry(dependency.to_string())
+            .or_default();
+    }
+
+    pub fn record(&self, dependency: &str, elapsed: Duration, error: Option<String>) {
+        let now = Utc::now();
+        let mut dependencies = self.dependencies.lock().unwrap();
+        let observed = dependencies.entry(dependency.to_string()).or_default();
+
+        observed.calls += 1;
+        observed.last_call_at = Some(now);
+        if observed.window.len() == WINDOW {
+            observed.window.pop_front();
+        }
+        observed
+            .window
+            .push_back((elapsed.as_micros() as u64, error.is_none()));
+
+        if let Some(message) = error {
+            observed.errors += 1;
+            if observed.recent_errors.len() == ERROR_SAMPLES {
+                observed.recent_errors.pop_back();
+            }
+            observed.recent_errors.push_front(ErrorSample {
+                message: message.chars().take(MAX_ERROR_LEN).collect(),
+                at: now,
+            });
+        }
+    }
+
+    /// Every dependency seen so far, by name
+    pub fn snapshot(&self) -> Vec<DependencyReport> {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, observed)| observed.report(name))
+            .collect()
+    }
+
+    pub fn get(&self, dependency: &str) -> Option<DependencyReport> {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .get(dependency)
+            .map(|observed| observed.report(dependency))
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -18,6 +18,65 @@ pub enum AppError {
     Internal(String),
     BadRequest(String),
     Authentication(String),
+    /// Rate limited; retry after this many seconds
+    TooManyRequests(u64),
+    /// An upstream call ran out of its time budget
+    Timeout(String),
+    /// A request body that isn't the JSON the handler expects
+    InvalidBody(BodyError),
+    /// A unique value, such as an email, that is already taken
+    Conflict(ConflictError),
+}
+
+/// Why a JSON request body was rejected, see [`crate::utils::Json`]
+#[derive(Debug, Clone)]
+pub struct BodyError {
+    pub status: StatusCode,
+    pub message: String,
+    /// Path of the offending field, e.g. `members[2].role`
+    pub field: Option<String>,
+    /// What serde expected there, e.g. `a string`
+    pub expected: Option<String>,
+}
+
+/// What a request clashed with, see [`AppError::Conflict`]
+#[derive(Debug, Clone)]
+pub struct ConflictError {
+    pub message: String,
+    /// Field holding the taken value, e.g. `email`
+    pub field: Option<String>,
+}
+
+impl AppError {
+    /// 409 for an email another user already has
+    pub fn email_taken() -> Self {
+        AppError::Conflict(ConflictError {
+            message: "Email already registered".to_string(),
+            field: Some("email".to_string()),
+        })
+    }
+
+    /// 409 for a username another user already has
+    pub fn username_taken() -> Self {
+        AppError::Conflict(ConflictError {
+            message: "Username already taken".to_string(),
+            field: Some("username".to_string()),
+        })
+    }
+}
+
+/// Name of the unique constraint `err` violates, if it is a unique violation
+///
+/// Checking for a value before inserting it doesn't stop two requests from
+/// inserting it together; the constraint does, and this tells its error
+/// apart from other database errors.
+pub fn unique_violation(err: &sqlx::Error) -> Option<&str> {
+    match err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Some(db.constraint().unwrap_or_default())
+        }
+        _ => None,
+    }
 }
 
 impl fmt::Display for AppError {
@@ -31,6 +90,10 @@ impl fmt::Display for AppError {
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             AppError::Authentication(msg) => write!(f, "Authentication error: {}", msg),
+            AppError::TooManyRequests(secs) => write!(f, "Too many requests, retry after {}s", secs),
+            AppError::Timeout(msg) => write!(f, "Timed out: {}", msg),
+            AppError::InvalidBody(err) => write!(f, "Invalid request body: {}", err.message),
+            AppError::Conflict(err) => write!(f, "Conflict: {}", err.message),
         }
     }
 }
@@ -54,13 +117,42 @@ impl IntoResponse for AppError {
             }
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Authentication(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
+            AppError::TooManyRequests(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, "Too many requests".to_string())
+            }
+            AppError::Timeout(msg) => {
+                tracing::warn!("Upstream timeout: {}", msg);
+                (StatusCode::GATEWAY_TIMEOUT, "Upstream request timed out".to_string())
+            }
+            AppError::InvalidBody(err) => (err.status, err.message.clone()),
+            AppError::Conflict(err) => (StatusCode::CONFLICT, err.message.clone()),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
-        }));
+        });
+        if let AppError::InvalidBody(err) = &self {
+            if let Some(field) = &err.field {
+                body["field"] = json!(field);
+            }
+            if let Some(expected) = &err.expected {
+                body["expected"] = json!(expected);
+            }
+        }
+        if let AppError::Conflict(ConflictError {
+            field: Some(field), ..
+        }) = &self
+        {
+            body["field"] = json!(field);
+        }
+        let body = Json(body);
 
-        (status, body).into_response()
+        match self {
+            AppError::TooManyRequests(secs) => {
+                (status, [(header::RETRY_AFTER, secs.to_string())], body).into_response()
+            }
+            _ => (status, body).into_response(),
+        }
     }
 }
 
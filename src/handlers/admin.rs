@@ -0,0 +1,386 @@
+use crate::auth::openfga::OpenFgaService;
+use crate::config::StatementRecording;
+use crate::database::{set_statement_recording, statement_recording};
+use crate::dependencies::{self, DependencyReport, DependencyStats};
+use crate::errors::{AppError, Result};
+use crate::live_tail::{LiveTail, LogEvent, TraceBundle};
+use crate::metrics::AppMetrics;
+use crate::middleware::api_usage::{ApiUsage, ApiUsageEntry};
+use crate::middleware::client_analytics::{ClientAnalytics, ClientUsageEntry};
+use crate::models::ApiResponse;
+use crate::services::health::DependencyHealth;
+use crate::services::WarmupService;
+use crate::utils::Json;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::Level;
+use utoipa::ToSchema;
+
+#[derive(Clone)]
+pub struct LiveTailHandlers {
+    tail: Arc<LiveTail>,
+    metrics: AppMetrics,
+    metrics_interval: Duration,
+}
+
+impl LiveTailHandlers {
+    pub fn new(tail: Arc<LiveTail>, metrics: AppMetrics, metrics_interval: Duration) -> Self {
+        Self {
+            tail,
+            metrics,
+            metrics_interval,
+        }
+    }
+}
+
+/// Per-endpoint call counts collected by the usage middleware
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/api-usage",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Calls per route and client, most-called first", body = ApiResponse<Vec<ApiUsageEntry>>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_api_usage(
+    State(usage): State<Arc<ApiUsage>>,
+) -> Json<ApiResponse<Vec<ApiUsageEntry>>> {
+    Json(ApiResponse::success(usage.snapshot()))
+}
+
+/// Calls per client family and version, from the `User-Agent` header
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/clients",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Calls per client version and route, grouped by family, newest version first", body = ApiResponse<Vec<ClientUsageEntry>>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_client_analytics(
+    State(analytics): State<Arc<ClientAnalytics>>,
+) -> Json<ApiResponse<Vec<ClientUsageEntry>>> {
+    Json(ApiResponse::success(analytics.snapshot()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TailParams {
+    /// Minimum level to stream, e.g. `warn`; defaults to everything
+    pub level: Option<String>,
+}
+
+/// Download the buffered logs, span timings and cost summary of one request
+///
+/// Reads the live tail's in-process buffers, so it only covers requests this
+/// instance served recently.
+#[utoipa::path(
+    get,
+    path = "/internal/admin/traces/{trace_id}",
+    tag = "admin",
+    params(
+        ("trace_id" = String, Path, description = "Trace ID, as sent in `x-trace-id` or found in the logs")
+    ),
+    responses(
+        (status = 200, description = "Trace bundle as a JSON attachment", body = TraceBundle),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Nothing buffered for this trace")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_trace_bundle(
+    State(handlers): State<LiveTailHandlers>,
+    Path(trace_id): Path<String>,
+) -> Result<Response> {
+    let bundle = handlers
+        .tail
+        .trace_bundle(&trace_id)
+        .ok_or_else(|| AppError::NotFound(format!("Nothing buffered for trace {}", trace_id)))?;
+
+    let file_name: String = trace_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let disposition = format!("attachment; filename=\"trace-{}.json\"", file_name);
+
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(bundle)).into_response())
+}
+
+/// Frames sent over the tail socket
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TailFrame<'a> {
+    Log(&'a LogEvent),
+    Metrics { deltas: HashMap<String, f64> },
+    Lagged { skipped: u64 },
+}
+
+/// Stream recent log events and metric deltas to an admin over WebSocket
+pub async fn live_tail(
+    State(handlers): State<LiveTailHandlers>,
+    Query(params): Query<TailParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let min_level = params
+        .level
+        .and_then(|level| level.parse::<Level>().ok())
+        .unwrap_or(Level::TRACE);
+
+    ws.on_upgrade(move |socket| stream_tail(socket, handlers, min_level))
+}
+
+async fn stream_tail(mut socket: WebSocket, handlers: LiveTailHandlers, min_level: Level) {
+    // Subscribe before replaying the backlog so nothing falls in between
+    let mut events = handlers.tail.subscribe();
+
+    for event in handlers.tail.recent() {
+        if event.is_at_least(min_level) && !send_frame(&mut socket, &TailFrame::Log(&event)).await {
+            return;
+        }
+    }
+
+    let mut previous = counter_snapshot(&handlers.metrics);
+    let mut ticker = tokio::time::interval(handlers.metrics_interval);
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            received = events.recv() => {
+                let sent = match received {
+                    Ok(event) if event.is_at_least(min_level) => {
+                        send_frame(&mut socket, &TailFrame::Log(&event)).await
+                    }
+                    Ok(_) => true,
+                    Err(RecvError::Lagged(skipped)) => {
+                        send_frame(&mut socket, &TailFrame::Lagged { skipped }).await
+                    }
+                    Err(RecvError::Closed) => false,
+                };
+                if !sent {
+                    return;
+                }
+            }
+            _ = ticker.tick() => {
+                let current = counter_snapshot(&handlers.metrics);
+                let deltas: HashMap<String, f64> = current
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        let delta = value - previous.get(key).copied().unwrap_or(0.0);
+                        (delta != 0.0).then(|| (key.clone(), delta))
+                    })
+                    .collect();
+                previous = current;
+
+                if !deltas.is_empty() && !send_frame(&mut socket, &TailFrame::Metrics { deltas }).await {
+                    return;
+                }
+            }
+            incoming = socket.recv() => {
+                // Clients only ever close the stream
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+async fn send_frame(socket: &mut WebSocket, frame: &TailFrame<'_>) -> bool {
+    match serde_json::to_string(frame) {
+        Ok(text) => socket.send(Message::Text(text.into())).await.is_ok(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to serialize live tail frame");
+            true
+        }
+    }
+}
+
+/// Current counter values keyed by `name{label="value",...}`
+fn counter_snapshot(metrics: &AppMetrics) -> HashMap<String, f64> {
+    let mut snapshot = HashMap::new();
+
+    for family in metrics.registry.gather() {
+        if family.get_field_type() != prometheus::proto::MetricType::COUNTER {
+            continue;
+        }
+
+        for metric in family.get_metric() {
+            let labels: Vec<String> = metric
+                .get_label()
+                .iter()
+                .map(|label| format!("{}=\"{}\"", label.name(), label.value()))
+                .collect();
+            let key = if labels.is_empty() {
+                family.name().to_string()
+            } else {
+                format!("{}{{{}}}", family.name(), labels.join(","))
+            };
+
+            snapshot.insert(key, metric.get_counter().value());
+        }
+    }
+
+    snapshot
+}
+
+/// Runtime logging settings
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogSettings {
+    /// Active filter directives
+    #[schema(example = "info,sqlx=warn")]
+    pub filter: Option<String>,
+    pub statement_recording: StatementRecording,
+}
+
+/// Partial update of runtime logging settings
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateLogSettingsRequest {
+    #[schema(example = "debug,hyper=info")]
+    pub filter: Option<String>,
+    pub statement_recording: Option<StatementRecording>,
+}
+
+fn current_log_settings() -> LogSettings {
+    LogSettings {
+        filter: crate::telemetry::current_log_filter(),
+        statement_recording: statement_recording(),
+    }
+}
+
+/// Get the runtime log level and SQL statement recording mode
+#[utoipa::path(
+    get,
+    path = "/internal/admin/log-level",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Current log settings", body = ApiResponse<LogSettings>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_log_level() -> Json<ApiResponse<LogSettings>> {
+    Json(ApiResponse::success(current_log_settings()))
+}
+
+/// Change the log level and/or SQL statement recording mode without a restart
+#[utoipa::path(
+    put,
+    path = "/internal/admin/log-level",
+    tag = "admin",
+    request_body = UpdateLogSettingsRequest,
+    responses(
+        (status = 200, description = "Log settings updated", body = ApiResponse<LogSettings>),
+        (status = 400, description = "Invalid filter directives"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_log_level(
+    Json(request): Json<UpdateLogSettingsRequest>,
+) -> Result<Json<ApiResponse<LogSettings>>> {
+    if let Some(filter) = &request.filter {
+        crate::telemetry::set_log_filter(filter)
+            .map_err(|e| AppError::BadRequest(format!("Invalid log filter: {}", e)))?;
+    }
+
+    if let Some(mode) = request.statement_recording {
+        set_statement_recording(mode);
+        tracing::info!(statement_recording = ?mode, "Statement recording changed");
+    }
+
+    Ok(Json(ApiResponse::success(current_log_settings())))
+}
+
+#[derive(Clone)]
+pub struct DependencyHandlers {
+    openfga_service: Arc<OpenFgaService>,
+    warmup: Arc<WarmupService>,
+}
+
+impl DependencyHandlers {
+    /// Postgres and OpenFGA are listed from the start, before their first call
+    pub fn new(openfga_service: Arc<OpenFgaService>, warmup: Arc<WarmupService>) -> Self {
+        DependencyStats::global().register(dependencies::POSTGRES);
+        DependencyStats::global().register(dependencies::OPENFGA);
+        Self {
+            openfga_service,
+            warmup,
+        }
+    }
+}
+
+/// Observed state of every downstream dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependenciesResponse {
+    /// Calls this instance made, by dependency name
+    pub dependencies: Vec<DependencyReport>,
+    /// Latest cached health checks; absent when checks are disabled
+    pub health_checks: Option<Vec<DependencyHealth>>,
+}
+
+/// Availability, latency, breaker state and recent errors per dependency
+///
+/// Covers the calls this instance made recently, so compare instances to
+/// tell a dependency outage from one replica's network trouble.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dependencies",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Per-dependency stats, by name", body = ApiResponse<DependenciesResponse>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_dependencies(
+    State(handlers): State<DependencyHandlers>,
+) -> Json<ApiResponse<DependenciesResponse>> {
+    let breaker = handlers.openfga_service.breaker_state();
+    let dependencies = DependencyStats::global()
+        .snapshot()
+        .into_iter()
+        .map(|mut report| {
+            if report.name == dependencies::OPENFGA {
+                report.circuit_breaker = breaker.map(|state| state.as_str().to_string());
+            }
+            report
+        })
+        .collect();
+
+    let health_checks = match handlers.warmup.health() {
+        Some(health) => Some(health.report().await.dependencies.clone()),
+        None => None,
+    };
+
+    Json(ApiResponse::success(DependenciesResponse {
+        dependencies,
+        health_checks,
+    }))
+}
@@ -0,0 +1,97 @@
+use crate::auth::models::AuthContext;
+use crate::errors::Result;
+use crate::models::{ApiResponse, AuditCursor, AuditFilter, AuditPage};
+use crate::services::Services;
+use crate::utils::{validate_range, Cursor, CursorSigner, Validate, ValidatedQuery};
+use axum::{extract::State, response::Json, Extension};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct AuditHandlers {
+    services: Arc<Services>,
+}
+
+impl AuditHandlers {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditEventListParams {
+    /// Only entries by this user or service account
+    pub actor_id: Option<Uuid>,
+    /// Only this action
+    #[param(example = "user.updated")]
+    pub action: Option<String>,
+    /// Only entries about this kind of resource
+    #[param(example = "user")]
+    pub resource_type: Option<String>,
+    /// Only entries about this resource; combine with `resource_type`
+    pub resource_id: Option<String>,
+    #[param(example = 50, minimum = 1, maximum = 100)]
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+impl Validate for AuditEventListParams {
+    fn validate(&self) -> Result<()> {
+        validate_range("limit", self.limit, 1, 100)
+    }
+}
+
+/// List the caller's tenant's audit log, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit-events",
+    tag = "admin",
+    params(AuditEventListParams),
+    responses(
+        (status = 200, description = "A page of audit log entries", body = ApiResponse<AuditPage>),
+        (status = 400, description = "Invalid query parameters or cursor"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_audit_events(
+    State(handlers): State<AuditHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Extension(signer): Extension<Arc<CursorSigner>>,
+    Cursor(after): Cursor<AuditCursor>,
+    ValidatedQuery(params): ValidatedQuery<AuditEventListParams>,
+) -> Result<Json<ApiResponse<AuditPage>>> {
+    let limit = params.limit.unwrap_or(50);
+    let filter = AuditFilter {
+        actor_id: params.actor_id,
+        action: params.action,
+        resource_type: params.resource_type,
+        resource_id: params.resource_id,
+    };
+
+    let events = handlers
+        .services
+        .audit
+        .list(auth_context.tenant(), &filter, after, limit)
+        .await?;
+
+    // A full page may have more behind it
+    let next_cursor = match events.last() {
+        Some(last) if events.len() as i64 == limit => Some(signer.encode(
+            &AuditCursor {
+                created_at: last.created_at,
+                id: last.id,
+            },
+            Some(&auth_context.user_id.to_string()),
+            None,
+        )?),
+        _ => None,
+    };
+
+    Ok(Json(ApiResponse::success(AuditPage { events, next_cursor })))
+}
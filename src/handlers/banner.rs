@@ -0,0 +1,65 @@
+use crate::errors::Result;
+use crate::models::{ApiResponse, DeleteResponse};
+use crate::services::banner::{BannerResponse, BannerService, ServiceBanner, SetBannerRequest};
+use crate::utils::Json;
+use axum::extract::State;
+use std::sync::Arc;
+
+/// Current service banner, for clients to show to their users
+#[utoipa::path(
+    get,
+    path = "/api/v1/banner",
+    tag = "health",
+    responses(
+        (status = 200, description = "The banner, or null when none is shown", body = BannerResponse)
+    )
+)]
+pub async fn get_banner(State(banner): State<Arc<BannerService>>) -> Json<BannerResponse> {
+    Json(BannerResponse {
+        banner: banner.current(),
+    })
+}
+
+/// Set the service banner, e.g. to announce a maintenance window
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/banner",
+    tag = "admin",
+    request_body = SetBannerRequest,
+    responses(
+        (status = 200, description = "The banner now shown", body = ApiResponse<ServiceBanner>),
+        (status = 400, description = "Message blank, too long or with control characters, or expiry in the past"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_banner(
+    State(banner): State<Arc<BannerService>>,
+    Json(request): Json<SetBannerRequest>,
+) -> Result<Json<ApiResponse<ServiceBanner>>> {
+    let banner = banner.set(request)?;
+    Ok(Json(ApiResponse::success(banner)))
+}
+
+/// Take the service banner down before it expires
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/banner",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Banner cleared", body = DeleteResponse),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn clear_banner(State(banner): State<Arc<BannerService>>) -> Json<DeleteResponse> {
+    banner.clear();
+    Json(DeleteResponse {
+        success: true,
+        message: "Banner cleared".to_string(),
+    })
+}
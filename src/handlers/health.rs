@@ -1,5 +1,8 @@
-use axum::{response::Json, http::StatusCode};
+use crate::services::health::DependencyHealth;
+use crate::services::warmup::{WarmupReport, WarmupService};
+use axum::{extract::State, response::Json, http::StatusCode};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use utoipa::ToSchema;
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -32,3 +35,86 @@ pub async fn health_check() -> Result<Json<HealthResponse>, StatusCode> {
 
     Ok(Json(health_response))
 }
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    #[schema(example = "ready")]
+    pub status: String,
+    pub timestamp: String,
+    /// Cached dependency checks, once warmed up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<DependencyHealth>>,
+}
+
+/// Readiness check endpoint; reports ready only after a successful warmup,
+/// and while critical dependencies pass their (cached) checks
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Instance is warmed up and ready for traffic", body = ReadinessResponse),
+        (status = 503, description = "Instance is still warming up, waiting for migrations, or a critical dependency is down", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness_check(
+    State(warmup): State<Arc<WarmupService>>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let mut ready = warmup.is_ready();
+    let mut status = warmup.status();
+
+    let dependencies = match warmup.health() {
+        Some(health) if ready => {
+            let report = health.report().await;
+            if !report.healthy {
+                ready = false;
+                status = "dependencies_unhealthy";
+            }
+            Some(report.dependencies.clone())
+        }
+        _ => None,
+    };
+
+    let status_code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status_code,
+        Json(ReadinessResponse {
+            status: status.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            dependencies,
+        }),
+    )
+}
+
+/// Warm up connection pools, OpenFGA and hot code paths before taking traffic
+#[utoipa::path(
+    post,
+    path = "/internal/warmup",
+    tag = "health",
+    responses(
+        (status = 200, description = "Warmup completed and instance is ready", body = WarmupReport),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Service account credentials required"),
+        (status = 503, description = "Warmup failed", body = WarmupReport)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn warmup(
+    State(warmup): State<Arc<WarmupService>>,
+) -> (StatusCode, Json<WarmupReport>) {
+    let report = warmup.warmup().await;
+    let status_code = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(report))
+}
@@ -0,0 +1,91 @@
+use crate::errors::Result;
+use crate::models::{ApiResponse, Job, JobStatus};
+use crate::services::Services;
+use crate::utils::{validate_range, Validate, ValidatedQuery};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct JobHandlers {
+    services: Arc<Services>,
+}
+
+impl JobHandlers {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct JobListParams {
+    /// Only jobs in this state
+    pub status: Option<JobStatus>,
+    #[param(example = 50, minimum = 1, maximum = 100)]
+    pub limit: Option<i64>,
+}
+
+impl Validate for JobListParams {
+    fn validate(&self) -> Result<()> {
+        validate_range("limit", self.limit, 1, 100)
+    }
+}
+
+/// List recent background jobs, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs",
+    tag = "admin",
+    params(JobListParams),
+    responses(
+        (status = 200, description = "Recent jobs", body = ApiResponse<Vec<Job>>),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_jobs(
+    State(handlers): State<JobHandlers>,
+    ValidatedQuery(params): ValidatedQuery<JobListParams>,
+) -> Result<Json<ApiResponse<Vec<Job>>>> {
+    let jobs = handlers
+        .services
+        .jobs
+        .list(params.status, params.limit.unwrap_or(50))
+        .await?;
+
+    Ok(Json(ApiResponse::success(jobs)))
+}
+
+/// Get a background job, including its progress and last error
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs/{id}",
+    tag = "admin",
+    params(
+        ("id" = Uuid, Path, description = "Job ID")
+    ),
+    responses(
+        (status = 200, description = "Job", body = ApiResponse<Job>),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Job not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_job(
+    State(handlers): State<JobHandlers>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Job>>> {
+    let job = handlers.services.jobs.get(id).await?;
+
+    Ok(Json(ApiResponse::success(job)))
+}
@@ -1,32 +1,113 @@
+pub mod admin;
+pub mod audit;
+pub mod banner;
 pub mod health;
+pub mod jobs;
 pub mod metrics;
+pub mod organization;
+pub mod status;
+pub mod tenant;
 pub mod user;
 
+use crate::auth::cookies::SessionCookies;
 use crate::auth::handlers::AuthHandlers;
-use crate::auth::jwt::JwtService;
 use crate::auth::openfga::OpenFgaService;
-use crate::services::Services;
+use crate::auth::rate_limit::LoginRateLimiter;
+use crate::middleware::{ApiUsage, ClientAnalytics};
+use crate::services::{BannerService, Services, StatusPage, WarmupService};
 use std::sync::Arc;
 
-pub use health::{health_check, HealthResponse};
+pub use admin::{
+    get_api_usage, get_client_analytics, get_dependencies, get_log_level, get_trace_bundle,
+    live_tail, update_log_level, DependencyHandlers, LiveTailHandlers,
+};
+pub use audit::{list_audit_events, AuditHandlers};
+pub use banner::{clear_banner, get_banner, set_banner};
+pub use jobs::{get_job, list_jobs, JobHandlers};
+pub use health::{health_check, readiness_check, warmup, HealthResponse, ReadinessResponse};
 pub use metrics::metrics_handler;
-pub use user::{UserHandlers, create_user, get_user, get_users, update_user, delete_user};
+pub use organization::{
+    add_organization_member, create_organization, delete_organization, get_organization,
+    list_all_organizations, list_organization_members, list_organizations, remove_organization_member,
+    update_organization, update_organization_member, OrganizationHandlers,
+};
+pub use status::{clear_status_incident, get_status, set_status_incident};
+pub use tenant::{get_tenant_settings, update_tenant_settings, TenantHandlers};
+pub use user::{
+    UserHandlers, bulk_delete_users, bulk_update_user_roles, create_user, get_avatar_file, get_user,
+    get_users, list_user_summaries, reactivate_user, suspend_user, update_user, upload_avatar,
+    delete_user,
+};
 
 #[derive(Clone)]
 pub struct Handlers {
     pub user: UserHandlers,
     pub auth: AuthHandlers,
+    pub tenant: TenantHandlers,
+    pub organizations: OrganizationHandlers,
+    pub jobs: JobHandlers,
+    pub audit: AuditHandlers,
+    pub dependencies: DependencyHandlers,
+    pub warmup: Arc<WarmupService>,
+    pub status: Arc<StatusPage>,
+    pub banner: Arc<BannerService>,
+    pub live_tail: Option<LiveTailHandlers>,
+    pub api_usage: Option<Arc<ApiUsage>>,
+    pub client_analytics: Option<Arc<ClientAnalytics>>,
+    pub login_rate_limit: Option<Arc<LoginRateLimiter>>,
 }
 
 impl Handlers {
     pub fn new(
         services: Arc<Services>,
-        jwt_service: Arc<JwtService>,
         openfga_service: Arc<OpenFgaService>,
+        warmup: Arc<WarmupService>,
     ) -> Self {
         Self {
             user: UserHandlers::new(services.clone()),
-            auth: AuthHandlers::new(services, jwt_service, openfga_service),
+            tenant: TenantHandlers::new(services.clone()),
+            organizations: OrganizationHandlers::new(services.clone()),
+            jobs: JobHandlers::new(services.clone()),
+            audit: AuditHandlers::new(services.clone()),
+            dependencies: DependencyHandlers::new(openfga_service.clone(), warmup.clone()),
+            auth: AuthHandlers::new(services, openfga_service),
+            status: Arc::new(StatusPage::new(warmup.clone())),
+            banner: Arc::new(BannerService::new()),
+            warmup,
+            live_tail: None,
+            api_usage: None,
+            client_analytics: None,
+            login_rate_limit: None,
         }
     }
+
+    /// Expose the admin live tail endpoint
+    pub fn with_live_tail(mut self, live_tail: LiveTailHandlers) -> Self {
+        self.live_tail = Some(live_tail);
+        self
+    }
+
+    /// Expose the admin API usage report
+    pub fn with_api_usage(mut self, api_usage: Arc<ApiUsage>) -> Self {
+        self.api_usage = Some(api_usage);
+        self
+    }
+
+    /// Expose the admin client analytics report
+    pub fn with_client_analytics(mut self, analytics: Arc<ClientAnalytics>) -> Self {
+        self.client_analytics = Some(analytics);
+        self
+    }
+
+    /// Set tokens as cookies and accept them back from browsers
+    pub fn with_session_cookies(mut self, cookies: Arc<SessionCookies>) -> Self {
+        self.auth = self.auth.with_cookies(cookies);
+        self
+    }
+
+    /// Throttle login and registration attempts
+    pub fn with_login_rate_limit(mut self, limiter: Arc<LoginRateLimiter>) -> Self {
+        self.login_rate_limit = Some(limiter);
+        self
+    }
 }
@@ -0,0 +1,341 @@
+use crate::auth::models::AuthContext;
+use crate::errors::Result;
+use crate::models::{
+    AddOrganizationMemberRequest, ApiResponse, CreateOrganizationRequest, DeleteResponse, Organization,
+    OrganizationMember, PaginatedResponse, PaginationParams, UpdateOrganizationMemberRequest,
+    UpdateOrganizationRequest,
+};
+use crate::services::Services;
+use crate::utils::{Json, ValidatedQuery};
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct OrganizationHandlers {
+    services: Arc<Services>,
+}
+
+impl OrganizationHandlers {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+/// Create an organization; the caller becomes its admin
+#[utoipa::path(
+    post,
+    path = "/api/v1/organizations",
+    tag = "organizations",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Organization created", body = ApiResponse<Organization>),
+        (status = 400, description = "Invalid or taken id")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_organization(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<CreateOrganizationRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Organization>>)> {
+    let organization = handlers
+        .services
+        .organizations
+        .create(&auth_context, request)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success_with_message(
+            organization,
+            "Organization created successfully".to_string(),
+        )),
+    ))
+}
+
+/// List the organizations the caller is a member of
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations",
+    tag = "organizations",
+    responses(
+        (status = 200, description = "Organizations", body = ApiResponse<Vec<Organization>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_organizations(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<Vec<Organization>>>> {
+    let organizations = handlers
+        .services
+        .organizations
+        .list_for_user(auth_context.user_id)
+        .await?;
+    Ok(Json(ApiResponse::success(organizations)))
+}
+
+/// List every organization
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/organizations",
+    tag = "organizations",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Organizations", body = ApiResponse<PaginatedResponse<Organization>>),
+        (status = 400, description = "Invalid pagination parameters"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_all_organizations(
+    State(handlers): State<OrganizationHandlers>,
+    ValidatedQuery(pagination): ValidatedQuery<PaginationParams>,
+) -> Result<Json<ApiResponse<PaginatedResponse<Organization>>>> {
+    let organizations = handlers.services.organizations.list(pagination).await?;
+    Ok(Json(ApiResponse::success(organizations)))
+}
+
+/// Get an organization the caller is a member of
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations/{id}",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Organization found", body = ApiResponse<Organization>),
+        (status = 403, description = "Not a member"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_organization(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Organization>>> {
+    let organization = handlers.services.organizations.get(&auth_context, &id).await?;
+    Ok(Json(ApiResponse::success(organization)))
+}
+
+/// Rename an organization
+#[utoipa::path(
+    put,
+    path = "/api/v1/organizations/{id}",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID")
+    ),
+    request_body = UpdateOrganizationRequest,
+    responses(
+        (status = 200, description = "Organization updated", body = ApiResponse<Organization>),
+        (status = 403, description = "Organization admin required"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_organization(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateOrganizationRequest>,
+) -> Result<Json<ApiResponse<Organization>>> {
+    let organization = handlers
+        .services
+        .organizations
+        .update(&auth_context, &id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        organization,
+        "Organization updated successfully".to_string(),
+    )))
+}
+
+/// Delete an organization and all of its memberships
+#[utoipa::path(
+    delete,
+    path = "/api/v1/organizations/{id}",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Organization deleted", body = DeleteResponse),
+        (status = 400, description = "The default organization can't be deleted"),
+        (status = 403, description = "Organization admin required"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_organization(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteResponse>> {
+    handlers.services.organizations.delete(&auth_context, &id).await?;
+
+    Ok(Json(DeleteResponse {
+        success: true,
+        message: "Organization deleted successfully".to_string(),
+    }))
+}
+
+/// List an organization's members, admins first
+#[utoipa::path(
+    get,
+    path = "/api/v1/organizations/{id}/members",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Members", body = ApiResponse<Vec<OrganizationMember>>),
+        (status = 403, description = "Not a member"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_organization_members(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<OrganizationMember>>>> {
+    let members = handlers
+        .services
+        .organizations
+        .list_members(&auth_context, &id)
+        .await?;
+    Ok(Json(ApiResponse::success(members)))
+}
+
+/// Add a user to an organization
+#[utoipa::path(
+    post,
+    path = "/api/v1/organizations/{id}/members",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID")
+    ),
+    request_body = AddOrganizationMemberRequest,
+    responses(
+        (status = 201, description = "Member added", body = ApiResponse<OrganizationMember>),
+        (status = 400, description = "Already a member"),
+        (status = 403, description = "Organization admin required"),
+        (status = 404, description = "Organization or user not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn add_organization_member(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<String>,
+    Json(request): Json<AddOrganizationMemberRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<OrganizationMember>>)> {
+    let member = handlers
+        .services
+        .organizations
+        .add_member(&auth_context, &id, request)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success_with_message(
+            member,
+            "Member added successfully".to_string(),
+        )),
+    ))
+}
+
+/// Change a member's role
+#[utoipa::path(
+    put,
+    path = "/api/v1/organizations/{id}/members/{user_id}",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = UpdateOrganizationMemberRequest,
+    responses(
+        (status = 200, description = "Member updated", body = ApiResponse<OrganizationMember>),
+        (status = 400, description = "Would leave the organization without an admin"),
+        (status = 403, description = "Organization admin required"),
+        (status = 404, description = "Member not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_organization_member(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path((id, user_id)): Path<(String, Uuid)>,
+    Json(request): Json<UpdateOrganizationMemberRequest>,
+) -> Result<Json<ApiResponse<OrganizationMember>>> {
+    let member = handlers
+        .services
+        .organizations
+        .update_member(&auth_context, &id, user_id, request)
+        .await?;
+    Ok(Json(ApiResponse::success(member)))
+}
+
+/// Remove a member; members may remove themselves
+#[utoipa::path(
+    delete,
+    path = "/api/v1/organizations/{id}/members/{user_id}",
+    tag = "organizations",
+    params(
+        ("id" = String, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Member removed", body = DeleteResponse),
+        (status = 400, description = "Would leave the organization without an admin"),
+        (status = 403, description = "Organization admin required"),
+        (status = 404, description = "Member not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn remove_organization_member(
+    State(handlers): State<OrganizationHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path((id, user_id)): Path<(String, Uuid)>,
+) -> Result<Json<DeleteResponse>> {
+    handlers
+        .services
+        .organizations
+        .remove_member(&auth_context, &id, user_id)
+        .await?;
+
+    Ok(Json(DeleteResponse {
+        success: true,
+        message: "Member removed successfully".to_string(),
+    }))
+}
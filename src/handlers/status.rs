@@ -0,0 +1,78 @@
+use crate::errors::Result;
+use crate::models::{ApiResponse, DeleteResponse};
+use crate::services::status::{IncidentNote, PublicStatus, SetIncidentRequest, StatusPage};
+use crate::utils::Json;
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+/// How long browsers and CDNs may reuse a `/status` response
+pub const STATUS_MAX_AGE_SECS: u64 = 15;
+
+/// Public service status for embedding in a status page
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "health",
+    responses(
+        (status = 200, description = "Overall and per-component status, with any incident note", body = PublicStatus)
+    )
+)]
+pub async fn get_status(State(status_page): State<Arc<StatusPage>>) -> impl IntoResponse {
+    (
+        [(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", STATUS_MAX_AGE_SECS),
+        )],
+        Json(status_page.summary().await),
+    )
+}
+
+/// Set the incident note shown by `/status`
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/status/incident",
+    tag = "admin",
+    request_body = SetIncidentRequest,
+    responses(
+        (status = 200, description = "The note now shown", body = ApiResponse<IncidentNote>),
+        (status = 400, description = "Message blank or too long"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_status_incident(
+    State(status_page): State<Arc<StatusPage>>,
+    Json(request): Json<SetIncidentRequest>,
+) -> Result<Json<ApiResponse<IncidentNote>>> {
+    let note = status_page.set_incident(&request.message)?;
+    Ok(Json(ApiResponse::success(note)))
+}
+
+/// Clear the incident note shown by `/status`
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/status/incident",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Incident note cleared", body = DeleteResponse),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn clear_status_incident(
+    State(status_page): State<Arc<StatusPage>>,
+) -> Json<DeleteResponse> {
+    status_page.clear_incident();
+    Json(DeleteResponse {
+        success: true,
+        message: "Incident note cleared".to_string(),
+    })
+}
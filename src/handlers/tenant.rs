@@ -0,0 +1,76 @@
+use crate::auth::models::AuthContext;
+use crate::errors::Result;
+use crate::models::{ApiResponse, TenantSettings};
+use crate::services::Services;
+use crate::utils::Json;
+use axum::extract::{Extension, State};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct TenantHandlers {
+    services: Arc<Services>,
+}
+
+impl TenantHandlers {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+/// Get the current tenant's settings
+#[utoipa::path(
+    get,
+    path = "/api/v1/tenant/settings",
+    tag = "tenant",
+    responses(
+        (status = 200, description = "Tenant settings", body = ApiResponse<TenantSettings>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_tenant_settings(
+    State(handlers): State<TenantHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Json<ApiResponse<TenantSettings>>> {
+    let settings = handlers
+        .services
+        .tenant_settings
+        .get(auth_context.tenant())
+        .await?;
+
+    Ok(Json(ApiResponse::success((*settings).clone())))
+}
+
+/// Update the current tenant's settings; omitted sections are left unchanged
+#[utoipa::path(
+    put,
+    path = "/api/v1/tenant/settings",
+    tag = "tenant",
+    request_body = TenantSettings,
+    responses(
+        (status = 200, description = "Tenant settings updated", body = ApiResponse<TenantSettings>),
+        (status = 400, description = "Settings failed validation"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_tenant_settings(
+    State(handlers): State<TenantHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<TenantSettings>,
+) -> Result<Json<ApiResponse<TenantSettings>>> {
+    let settings = handlers
+        .services
+        .tenant_settings
+        .update(auth_context.tenant(), request, auth_context.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        (*settings).clone(),
+        "Tenant settings updated".to_string(),
+    )))
+}
@@ -1,14 +1,22 @@
-use crate::errors::Result;
+use crate::auth::models::AuthContext;
+use crate::errors::{AppError, BodyError, Result};
+use crate::models::fixtures;
 use crate::models::{
-    ApiResponse, CreateUserRequest, DeleteResponse, PaginatedResponse, PaginationParams, UpdateUserRequest, UserResponse,
+    ApiResponse, BulkDeleteUsersRequest, BulkResult, BulkUserRolesRequest, CreateUserRequest, DeleteResponse, PaginatedResponse, PaginationParams, UpdateUserRequest, UserCursor,
+    SuspendUserRequest, UserFilter, UserResponse, UserSortField, UserStatus, UserSummary,
 };
 use crate::services::Services;
+use crate::utils::{CollectionVersion, Cursor, CursorSigner, Json, Validate, ValidatedQuery};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{multipart::MultipartError, Multipart, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -20,6 +28,17 @@ impl UserHandlers {
     pub fn new(services: Arc<Services>) -> Self {
         Self { services }
     }
+
+    /// Request body limit for avatar uploads: the largest accepted image
+    /// plus room for the multipart framing
+    pub fn avatar_body_limit(&self) -> usize {
+        const MULTIPART_OVERHEAD: usize = 64 * 1024;
+        self.services
+            .user
+            .avatars()
+            .map_or(0, |avatars| avatars.max_bytes())
+            + MULTIPART_OVERHEAD
+    }
 }
 
 /// Create a new user
@@ -27,17 +46,27 @@ impl UserHandlers {
     post,
     path = "/api/v1/users",
     tag = "users",
-    request_body = CreateUserRequest,
+    request_body(
+        content = CreateUserRequest,
+        examples(
+            ("valid" = (summary = "Valid user", value = json!(fixtures::create_user::valid()))),
+            ("unicode_username" = (summary = "Non-ASCII username", value = json!(fixtures::create_user::unicode_username()))),
+            ("invalid_email" = (summary = "Rejected: malformed email", value = json!(fixtures::create_user::invalid_email()))),
+            ("short_username" = (summary = "Rejected: username under 3 characters", value = json!(fixtures::create_user::short_username()))),
+        )
+    ),
     responses(
         (status = 201, description = "User created successfully", body = ApiResponse<UserResponse>),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 409, description = "Email or username already taken")
     )
 )]
 pub async fn create_user(
     State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
     Json(request): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<UserResponse>>)> {
-    let user = handlers.services.user.create_user(request).await?;
+    let user = handlers.services.user.create_user(Some(&auth_context), request).await?;
 
     Ok((
         StatusCode::CREATED,
@@ -69,22 +98,201 @@ pub async fn get_user(
     Ok(Json(ApiResponse::success(user)))
 }
 
-/// Get all users with pagination
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UserListParams {
+    /// Case-insensitive substring of the email or username
+    #[param(example = "jane")]
+    pub q: Option<String>,
+    /// Only users created at or after this time (RFC 3339)
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only users created before this time (RFC 3339)
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only users holding any of these roles, comma-separated
+    #[param(example = "admin,moderator")]
+    pub role: Option<String>,
+    /// Only users in any of these statuses (`active`, `suspended`,
+    /// `banned`), comma-separated
+    #[param(example = "suspended,banned")]
+    pub status: Option<String>,
+}
+
+impl UserListParams {
+    pub const MAX_QUERY_LENGTH: usize = 100;
+    pub const MAX_ROLES: usize = 10;
+
+    /// Blank `q` and empty role or status entries are ignored, as are
+    /// unknown statuses, which [`Validate`] rejects
+    pub fn filter(&self) -> UserFilter {
+        UserFilter {
+            q: self
+                .q
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(str::to_string),
+            created_after: self.created_after,
+            created_before: self.created_before,
+            roles: self
+                .role
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|role| !role.is_empty())
+                .map(str::to_string)
+                .collect(),
+            statuses: self
+                .statuses()
+                .filter_map(|status| status.parse().ok())
+                .collect(),
+        }
+    }
+
+    fn statuses(&self) -> impl Iterator<Item = &str> {
+        self.status
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|status| !status.is_empty())
+    }
+}
+
+impl Validate for UserListParams {
+    fn validate(&self) -> Result<()> {
+        let filter = self.filter();
+        if filter.q.as_ref().is_some_and(|q| q.chars().count() > Self::MAX_QUERY_LENGTH) {
+            return Err(AppError::Validation(format!(
+                "q must be at most {} characters",
+                Self::MAX_QUERY_LENGTH
+            )));
+        }
+        if filter.roles.len() > Self::MAX_ROLES {
+            return Err(AppError::Validation(format!(
+                "role accepts at most {} roles",
+                Self::MAX_ROLES
+            )));
+        }
+        for status in self.statuses() {
+            status.parse::<UserStatus>()?;
+        }
+        if let (Some(after), Some(before)) = (filter.created_after, filter.created_before) {
+            if after >= before {
+                return Err(AppError::Validation(
+                    "created_after must be earlier than created_before".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get all users with pagination, optionally filtered
+///
+/// Pages are numbered (`page`) or, when sorted by `created_at`, followed
+/// with the `next_cursor` of the previous page; cursors seek on
+/// `(created_at, id)` so deep pages cost the same as the first.
 #[utoipa::path(
     get,
     path = "/api/v1/users",
     tag = "users",
-    params(PaginationParams),
+    params(
+        PaginationParams,
+        UserListParams,
+        ("If-Modified-Since" = Option<String>, Header, description = "Return 304 if the collection hasn't changed since this HTTP date"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response")
+    ),
     responses(
-        (status = 200, description = "Users retrieved successfully", body = ApiResponse<PaginatedResponse<UserResponse>>)
+        (status = 200, description = "Users retrieved successfully", body = ApiResponse<PaginatedResponse<UserResponse>>),
+        (status = 400, description = "Invalid pagination, sort or filter parameters, or cursor"),
+        (status = 304, description = "Collection unchanged since the given validator")
     )
 )]
 pub async fn get_users(
     State(handlers): State<UserHandlers>,
-    Query(pagination): Query<PaginationParams>,
-) -> Result<Json<ApiResponse<PaginatedResponse<UserResponse>>>> {
-    let users = handlers.services.user.get_users(pagination).await?;
-    Ok(Json(ApiResponse::success(users)))
+    Extension(auth_context): Extension<AuthContext>,
+    Extension(signer): Extension<Arc<CursorSigner>>,
+    Cursor(after): Cursor<UserCursor>,
+    ValidatedQuery(pagination): ValidatedQuery<PaginationParams>,
+    ValidatedQuery(params): ValidatedQuery<UserListParams>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let sort = pagination.user_sort()?;
+    pagination.validate_user_cursor()?;
+    let filter = params.filter();
+    let version = handlers.services.user.users_version(&filter).await?;
+    if version.is_not_modified(&headers) {
+        return Ok(not_modified(version));
+    }
+
+    let mut users = handlers
+        .services
+        .user
+        .get_users(pagination, &filter, after)
+        .await?;
+
+    // Offered whenever rows remain, so clients can switch from `page` to
+    // cursors after the first page
+    if sort == UserSortField::CreatedAt && users.page * users.per_page < users.total {
+        if let Some(last) = users.data.last() {
+            users.next_cursor = Some(signer.encode(
+                &UserCursor {
+                    created_at: last.created_at,
+                    id: last.id,
+                    page: users.page + 1,
+                },
+                Some(&auth_context.user_id.to_string()),
+                None,
+            )?);
+        }
+    }
+
+    Ok(with_version(version, Json(ApiResponse::success(users))))
+}
+
+/// List user summaries (roles, last login) for administration
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    tag = "users",
+    params(
+        PaginationParams,
+        ("If-Modified-Since" = Option<String>, Header, description = "Return 304 if the collection hasn't changed since this HTTP date"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response")
+    ),
+    responses(
+        (status = 200, description = "User summaries retrieved successfully", body = ApiResponse<PaginatedResponse<UserSummary>>),
+        (status = 304, description = "Collection unchanged since the given validator"),
+        (status = 400, description = "Invalid pagination parameters"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_user_summaries(
+    State(handlers): State<UserHandlers>,
+    ValidatedQuery(pagination): ValidatedQuery<PaginationParams>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let version = handlers.services.user.user_summaries_version().await?;
+    if version.is_not_modified(&headers) {
+        return Ok(not_modified(version));
+    }
+
+    let summaries = handlers.services.user.get_user_summaries(pagination).await?;
+    Ok(with_version(version, Json(ApiResponse::success(summaries))))
+}
+
+fn not_modified(version: CollectionVersion) -> Response {
+    with_version(version, StatusCode::NOT_MODIFIED)
+}
+
+/// Attach `Last-Modified` / `ETag` for the collection to a response
+fn with_version(version: CollectionVersion, body: impl IntoResponse) -> Response {
+    let mut response = body.into_response();
+    version.apply(response.headers_mut());
+    response
 }
 
 /// Update user by ID
@@ -95,25 +303,160 @@ pub async fn get_users(
     params(
         ("id" = Uuid, Path, description = "User ID")
     ),
-    request_body = UpdateUserRequest,
+    request_body(
+        content = UpdateUserRequest,
+        examples(
+            ("valid" = (summary = "Change email and username", value = json!(fixtures::update_user::valid()))),
+            ("username_only" = (summary = "Partial update", value = json!(fixtures::update_user::username_only()))),
+            ("empty_username" = (summary = "Rejected: blank username", value = json!(fixtures::update_user::empty_username()))),
+        )
+    ),
     responses(
         (status = 200, description = "User updated successfully", body = ApiResponse<UserResponse>),
         (status = 404, description = "User not found"),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 409, description = "Email or username already taken")
     )
 )]
 pub async fn update_user(
     State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateUserRequest>,
 ) -> Result<Json<ApiResponse<UserResponse>>> {
-    let user = handlers.services.user.update_user(id, request).await?;
+    let user = handlers.services.user.update_user(&auth_context, id, request).await?;
     Ok(Json(ApiResponse::success_with_message(
         user,
         "User updated successfully".to_string(),
     )))
 }
 
+/// Form accepted by `PUT /api/v1/users/{id}/avatar`
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AvatarUploadForm {
+    /// The image; PNG, JPEG or WebP unless `avatars.content_types` says
+    /// otherwise
+    #[schema(value_type = String, format = Binary)]
+    avatar: Vec<u8>,
+}
+
+/// Upload a user's avatar
+///
+/// Callers can replace their own avatar; admins can replace anyone's. The
+/// image type is read from the file itself and must match the part's
+/// `Content-Type` when one is given. The response's `avatar_url` is signed
+/// and expires after `storage.signed_url_ttl_seconds`.
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}/avatar",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body(content = AvatarUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Avatar updated", body = ApiResponse<UserResponse>),
+        (status = 400, description = "No `avatar` part, or an empty one"),
+        (status = 403, description = "Another user's avatar, and the caller isn't an admin"),
+        (status = 404, description = "User not found"),
+        (status = 413, description = "Larger than `avatars.max_bytes`"),
+        (status = 415, description = "Not an accepted image type")
+    )
+)]
+pub async fn upload_avatar(
+    State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<ApiResponse<UserResponse>>> {
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() != Some("avatar") {
+            continue;
+        }
+        let content_type = field.content_type().map(str::to_string);
+        let body = field.bytes().await.map_err(multipart_error)?;
+
+        let user = handlers
+            .services
+            .user
+            .set_avatar(&auth_context, id, content_type.as_deref(), body.to_vec())
+            .await?;
+        return Ok(Json(ApiResponse::success_with_message(
+            user,
+            "Avatar updated successfully".to_string(),
+        )));
+    }
+
+    Err(AppError::BadRequest("Expected a multipart `avatar` part".to_string()))
+}
+
+fn multipart_error(error: MultipartError) -> AppError {
+    AppError::InvalidBody(BodyError {
+        status: error.status(),
+        message: error.body_text(),
+        field: Some("avatar".to_string()),
+        expected: None,
+    })
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SignedUrlParams {
+    /// Unix time the URL stops working
+    pub expires: i64,
+    pub signature: String,
+}
+
+impl Validate for SignedUrlParams {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Fetch an avatar stored on local disk
+///
+/// Only reachable through the signed `avatar_url` of a user response; with
+/// S3 storage those URLs point at the bucket instead and this returns 404.
+#[utoipa::path(
+    get,
+    path = "/api/v1/avatars/{user_id}/{file}",
+    tag = "users",
+    params(
+        ("user_id" = Uuid, Path, description = "User ID"),
+        ("file" = String, Path, description = "Stored file name"),
+        SignedUrlParams
+    ),
+    responses(
+        (status = 200, description = "The image", content_type = "image/*"),
+        (status = 404, description = "No such avatar, or the signature is invalid or expired")
+    )
+)]
+pub async fn get_avatar_file(
+    State(handlers): State<UserHandlers>,
+    Path((user_id, file)): Path<(Uuid, String)>,
+    ValidatedQuery(params): ValidatedQuery<SignedUrlParams>,
+) -> Result<Response> {
+    let key = format!("avatars/{}/{}", user_id, file);
+    let object = handlers
+        .services
+        .user
+        .avatar_file(&key, params.expires, &params.signature)
+        .await?;
+
+    // Cacheable for as long as the URL stays valid
+    let max_age = (params.expires - Utc::now().timestamp()).max(0);
+    Ok((
+        [
+            (header::CONTENT_TYPE, object.content_type),
+            (header::CACHE_CONTROL, format!("private, max-age={}", max_age)),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        object.body,
+    )
+        .into_response())
+}
+
 /// Delete user by ID
 #[utoipa::path(
     delete,
@@ -129,9 +472,10 @@ pub async fn update_user(
 )]
 pub async fn delete_user(
     State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
     Path(id): Path<Uuid>,
 ) -> Result<(StatusCode, Json<DeleteResponse>)> {
-    handlers.services.user.delete_user(id).await?;
+    handlers.services.user.delete_user(&auth_context, id).await?;
     Ok((
         StatusCode::OK,
         Json(DeleteResponse {
@@ -141,3 +485,143 @@ pub async fn delete_user(
     ))
 }
 
+
+/// Delete several users at once (admin only)
+///
+/// In `all_or_nothing` mode (the default) a user that doesn't exist fails
+/// the request and nobody is deleted; in `best_effort` mode the others are
+/// deleted and the rest listed under `failed`. Each database shard commits
+/// on its own, so if one fails after another committed, the users left on
+/// it are listed under `failed` in either mode.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/bulk-delete",
+    tag = "users",
+    request_body = BulkDeleteUsersRequest,
+    responses(
+        (status = 200, description = "Users deleted", body = ApiResponse<BulkResult>),
+        (status = 400, description = "No users, too many, or duplicates"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Some users not found (all_or_nothing mode); nothing was deleted")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn bulk_delete_users(
+    State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<BulkDeleteUsersRequest>,
+) -> Result<Json<ApiResponse<BulkResult>>> {
+    let result = handlers
+        .services
+        .user
+        .bulk_delete_users(&auth_context, request)
+        .await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Add or remove roles for several users at once (admin only)
+///
+/// Every user's roles change in one transaction. Modes work as for bulk
+/// deletion; adding a role a user already has, or removing one they don't,
+/// counts as success.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/bulk-roles",
+    tag = "users",
+    request_body = BulkUserRolesRequest,
+    responses(
+        (status = 200, description = "Roles updated", body = ApiResponse<BulkResult>),
+        (status = 400, description = "No users or roles, too many users, duplicates or unknown role"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Some users not found (all_or_nothing mode); no roles changed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn bulk_update_user_roles(
+    State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(request): Json<BulkUserRolesRequest>,
+) -> Result<Json<ApiResponse<BulkResult>>> {
+    let result = handlers
+        .services
+        .auth
+        .bulk_update_roles(&auth_context, request)
+        .await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Suspend or ban a user (admin only)
+///
+/// Every authenticated request the user makes is refused with a 403 until
+/// they're reactivated; their data, sessions and tokens are kept.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/suspend",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = SuspendUserRequest,
+    responses(
+        (status = 200, description = "User suspended or banned", body = ApiResponse<UserResponse>),
+        (status = 400, description = "The caller's own account"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn suspend_user(
+    State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SuspendUserRequest>,
+) -> Result<Json<ApiResponse<UserResponse>>> {
+    let status = if request.ban {
+        UserStatus::Banned
+    } else {
+        UserStatus::Suspended
+    };
+    let user = handlers
+        .services
+        .auth
+        .set_user_status(&auth_context, id, status, request.reason)
+        .await?;
+    Ok(Json(ApiResponse::success(user)))
+}
+
+/// Reactivate a suspended or banned user (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/reactivate",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User reactivated", body = ApiResponse<UserResponse>),
+        (status = 400, description = "The caller's own account"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reactivate_user(
+    State(handlers): State<UserHandlers>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<UserResponse>>> {
+    let user = handlers
+        .services
+        .auth
+        .set_user_status(&auth_context, id, UserStatus::Active, None)
+        .await?;
+    Ok(Json(ApiResponse::success(user)))
+}
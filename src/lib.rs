@@ -1,13 +1,19 @@
+pub mod anonymize;
 pub mod auth;
 pub mod client;
 pub mod config;
 pub mod database;
+pub mod dependencies;
 pub mod errors;
 pub mod handlers;
+pub mod live_tail;
 pub mod metrics;
 pub mod middleware;
 pub mod models;
+pub mod openapi;
 pub mod repositories;
+pub mod request_context;
+pub mod request_cost;
 pub mod routes;
 pub mod services;
 pub mod telemetry;
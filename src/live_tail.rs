@@ -0,0 +1,322 @@
+use crate::request_cost::REQUEST_COST_MESSAGE;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+use utoipa::ToSchema;
+
+/// A structured log event kept for the admin live tail
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LogEvent {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[schema(value_type = Object)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    /// Trace of the request the event was logged under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+/// A closed span, kept so a request's timings can be pulled up by trace ID
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SpanTiming {
+    pub trace_id: String,
+    #[schema(example = "database_query")]
+    pub name: String,
+    pub target: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: f64,
+}
+
+/// Everything buffered in-process about one request, for support tickets
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TraceBundle {
+    pub trace_id: String,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// Log events, oldest first
+    pub logs: Vec<LogEvent>,
+    /// Closed spans, in the order they finished
+    pub spans: Vec<SpanTiming>,
+    /// Fields of the request's cost accounting summary, if it was logged
+    #[schema(value_type = Option<Object>)]
+    pub cost: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl LogEvent {
+    /// Whether the event is at least as severe as `min_level`
+    pub fn is_at_least(&self, min_level: Level) -> bool {
+        self.level
+            .parse::<Level>()
+            .map(|level| level <= min_level)
+            .unwrap_or(true)
+    }
+}
+
+/// In-process ring buffer of recent log events with live fan-out
+pub struct LiveTail {
+    buffer: Mutex<VecDeque<LogEvent>>,
+    spans: Mutex<VecDeque<SpanTiming>>,
+    capacity: usize,
+    sender: broadcast::Sender<LogEvent>,
+}
+
+impl LiveTail {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+
+        Self {
+            buffer: Mutex::new(VecDeque::with_capacity(capacity)),
+            spans: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            sender,
+        }
+    }
+
+    pub fn push(&self, event: LogEvent) {
+        {
+            let mut buffer = self.buffer.lock().unwrap();
+            if buffer.len() == self.capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(event.clone());
+        }
+
+        // No receivers just means nobody is tailing right now
+        let _ = self.sender.send(event);
+    }
+
+    /// Buffered events, oldest first
+    pub fn recent(&self) -> Vec<LogEvent> {
+        self.buffer.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn push_span(&self, span: SpanTiming) {
+        let mut spans = self.spans.lock().unwrap();
+        if spans.len() == self.capacity {
+            spans.pop_front();
+        }
+        spans.push_back(span);
+    }
+
+    /// Buffered logs and spans for one trace, or `None` if nothing is left
+    ///
+    /// The buffers are shared by all requests, so on a busy instance only
+    /// recent traces are complete.
+    pub fn trace_bundle(&self, trace_id: &str) -> Option<TraceBundle> {
+        let logs: Vec<LogEvent> = self
+            .buffer
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.trace_id.as_deref() == Some(trace_id))
+            .cloned()
+            .collect();
+        let spans: Vec<SpanTiming> = self
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|span| span.trace_id == trace_id)
+            .cloned()
+            .collect();
+
+        if logs.is_empty() && spans.is_empty() {
+            return None;
+        }
+
+        let cost = logs
+            .iter()
+            .rev()
+            .find(|event| event.message == REQUEST_COST_MESSAGE)
+            .map(|event| event.fields.clone());
+
+        Some(TraceBundle {
+            trace_id: trace_id.to_string(),
+            generated_at: chrono::Utc::now(),
+            logs,
+            spans,
+            cost,
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Tracing layer feeding this buffer
+    pub fn layer(self: &Arc<Self>) -> LiveTailLayer {
+        LiveTailLayer { tail: self.clone() }
+    }
+}
+
+/// Tracing layer that records events and span timings into a `LiveTail`
+pub struct LiveTailLayer {
+    tail: Arc<LiveTail>,
+}
+
+/// Per-span state: the trace it belongs to and when it started
+struct SpanTrace {
+    trace_id: Option<String>,
+    started_at: chrono::DateTime<chrono::Utc>,
+    start: Instant,
+}
+
+impl<S> Layer<S> for LiveTailLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = TraceIdVisitor::default();
+        attrs.record(&mut visitor);
+        // Child spans belong to their parent's trace
+        let trace_id = visitor.trace_id.or_else(|| {
+            span.parent().and_then(|parent| {
+                parent
+                    .extensions()
+                    .get::<SpanTrace>()
+                    .and_then(|trace| trace.trace_id.clone())
+            })
+        });
+
+        span.extensions_mut().insert(SpanTrace {
+            trace_id,
+            started_at: chrono::Utc::now(),
+            start: Instant::now(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        // The request span gets its trace ID recorded after it is created
+        let mut visitor = TraceIdVisitor::default();
+        values.record(&mut visitor);
+        if let (Some(trace_id), Some(span)) = (visitor.trace_id, ctx.span(id)) {
+            if let Some(trace) = span.extensions_mut().get_mut::<SpanTrace>() {
+                trace.trace_id = Some(trace_id);
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(SpanTrace {
+            trace_id: Some(trace_id),
+            started_at,
+            start,
+        }) = extensions.get::<SpanTrace>()
+        else {
+            return;
+        };
+
+        self.tail.push_span(SpanTiming {
+            trace_id: trace_id.clone(),
+            name: span.name().to_string(),
+            target: span.metadata().target().to_string(),
+            started_at: *started_at,
+            duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+
+        let trace_id = ctx.event_scope(event).and_then(|scope| {
+            scope.into_iter().find_map(|span| {
+                span.extensions()
+                    .get::<SpanTrace>()
+                    .and_then(|trace| trace.trace_id.clone())
+            })
+        });
+
+        self.tail.push(LogEvent {
+            timestamp: chrono::Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message.unwrap_or_default(),
+            fields: visitor.fields,
+            trace_id,
+        });
+    }
+}
+
+/// Picks the `trace_id` field out of span attributes or records
+#[derive(Default)]
+struct TraceIdVisitor {
+    trace_id: Option<String>,
+}
+
+impl Visit for TraceIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "trace_id" {
+            self.trace_id = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "trace_id" {
+            // `%trace_id` arrives Display-formatted, `?trace_id` quoted
+            self.trace_id = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+#[derive(Default)]
+struct JsonVisitor {
+    message: Option<String>,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: serde_json::Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                serde_json::Value::String(message) => message,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
@@ -1,99 +1,43 @@
 use anyhow::Result;
 use reprime_backend::{
-    auth::{jwt::JwtService, openfga::OpenFgaService},
-    config::Config,
-    handlers::{Handlers, metrics::metrics_handler},
-    middleware::{cors_layer, logging_layer, prometheus::prometheus_middleware},
+    auth::{
+        authorizer::Authorizer, canary::AuthorizationCanary, changes::ChangeWatcher,
+        cookies::SessionCookies,
+        jwt::JwtService, middleware::AuthState, openfga::{AuthorizationModel, OpenFgaService},
+        rate_limit::LoginRateLimiter, registry, stores,
+    },
+    config::{Config, ModelPreflight},
+    handlers::{Handlers, LiveTailHandlers, metrics::metrics_handler},
+    live_tail::LiveTail,
+    middleware::{
+        api_usage_middleware, client_analytics_middleware, coalesce_middleware, cors_layer,
+        edge_cache_middleware,
+        logging_layer, prometheus::prometheus_middleware, request_context_middleware,
+        request_cost_middleware, response_format_middleware, service_notice_middleware,
+        traffic_mirror_middleware, ApiUsage, BandwidthLayer, BandwidthThrottle, ClientAnalytics,
+        EdgeCache, RequestCoalescer, TrafficMirror,
+    },
+    openapi::ApiDoc,
     repositories::Repositories,
     routes::create_routes,
-    services::Services,
-    utils::create_database_pool,
-    metrics::AppMetrics,
-    database::InstrumentedDatabase,
+    services::{
+        mailer_from_config, storage_from_config, AuditRetention, Avatars, ConfiguredOnboarding,
+        HealthService, JobWorker, LokiProbe, Seed, Seeder, Services, TupleCleanupJob, WarmupService,
+    },
+    utils::{
+        create_auth_database_pool, create_database_pool, create_shard_pools, run_migrations,
+        CursorSigner,
+    },
+    metrics::{AppMetrics, MetricsRegistry, RouteGroups, TenantLabels},
+    database::{InstrumentedDatabase, ShardRouter, SlowQueryLog},
+    dependencies,
 };
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{net::TcpListener, time::interval};
-use utoipa::{
-    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
-    Modify, OpenApi,
-};
+use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 
-#[derive(OpenApi)]
-#[allow(dead_code)]
-#[openapi(
-    paths(
-        reprime_backend::handlers::health::health_check,
-        reprime_backend::handlers::user::create_user,
-        reprime_backend::handlers::user::get_users,
-        reprime_backend::handlers::user::get_user,
-        reprime_backend::handlers::user::update_user,
-        reprime_backend::handlers::user::delete_user,
-        reprime_backend::auth::handlers::register,
-        reprime_backend::auth::handlers::login,
-        reprime_backend::auth::handlers::logout,
-        reprime_backend::auth::handlers::me,
-        reprime_backend::auth::handlers::refresh_token,
-        reprime_backend::auth::handlers::check_permission,
-    ),
-    components(
-        schemas(
-            reprime_backend::models::User,
-            reprime_backend::models::UserResponse,
-            reprime_backend::models::CreateUserRequest,
-            reprime_backend::models::UpdateUserRequest,
-            reprime_backend::models::ApiResponse<reprime_backend::models::UserResponse>,
-            reprime_backend::models::PaginatedResponse<reprime_backend::models::UserResponse>,
-            reprime_backend::models::PaginationParams,
-            reprime_backend::models::DeleteResponse,
-            reprime_backend::handlers::HealthResponse,
-            reprime_backend::auth::models::LoginRequest,
-            reprime_backend::auth::models::LoginResponse,
-            reprime_backend::auth::models::RegisterRequest,
-            reprime_backend::auth::models::UserInfo,
-            reprime_backend::auth::models::PermissionCheck,
-            reprime_backend::models::ApiResponse<reprime_backend::auth::models::LoginResponse>,
-            reprime_backend::models::ApiResponse<reprime_backend::auth::models::UserInfo>,
-            reprime_backend::models::ApiResponse<bool>,
-        )
-    ),
-    tags(
-        (name = "health", description = "Health check endpoints"),
-        (name = "users", description = "User management endpoints"),
-        (name = "authentication", description = "Authentication and authorization endpoints"),
-    ),
-    info(
-        title = "Reprime Backend API",
-        version = "0.1.0",
-        description = "A modern Rust backend API with OpenAPI documentation and ABAC authorization using OpenFGA",
-        contact(
-            name = "API Support",
-            email = "support@reprime.com"
-        )
-    ),
-    modifiers(&SecurityAddon)
-)]
-struct ApiDoc;
-
-struct SecurityAddon;
-
-impl Modify for SecurityAddon {
-    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
-        if let Some(components) = openapi.components.as_mut() {
-            components.add_security_scheme(
-                "bearer_auth",
-                SecurityScheme::Http(
-                    HttpBuilder::new()
-                        .scheme(HttpAuthScheme::Bearer)
-                        .bearer_format("JWT")
-                        .build(),
-                ),
-            )
-        }
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load configuration
@@ -102,8 +46,24 @@ async fn main() -> Result<()> {
         Config::default()
     });
 
+    // `reprime-backend anonymize` scrubs PII from a database copy and exits
+    if std::env::args().nth(1).as_deref() == Some("anonymize") {
+        reprime_backend::utils::init_tracing(&config);
+        reprime_backend::anonymize::run(&config).await?;
+        return Ok(());
+    }
+
+    // Recent log events for the admin live tail
+    let live_tail = config
+        .live_tail
+        .enabled
+        .then(|| Arc::new(LiveTail::new(config.live_tail.buffer_size)));
+
     // Initialize comprehensive telemetry with OpenTelemetry, Loki, and structured logging
-    reprime_backend::telemetry::init_telemetry_with_loki(&config).await?;
+    reprime_backend::telemetry::init_telemetry_with_loki(&config, live_tail.clone()).await?;
+
+    reprime_backend::database::set_statement_recording(config.database.statement_recording);
+    reprime_backend::utils::id::set_strategy(config.database.id_strategy);
 
     tracing::info!("Starting reprime-backend server...");
     tracing::info!("Configuration loaded: {:?}", config);
@@ -111,25 +71,257 @@ async fn main() -> Result<()> {
     // Create a database connection pool
     let pool = create_database_pool(&config).await?;
 
+    // One registry for every subsystem, served at /metrics
+    let metrics_registry = MetricsRegistry::new();
+
     // Initialize custom metrics
-    let metrics = AppMetrics::new().expect("Failed to create metrics");
+    let metrics = AppMetrics::with_registry(&metrics_registry)
+        .expect("Failed to create metrics")
+        .with_route_groups(RouteGroups::from_config(&config.metrics.route_groups));
+    let metrics = match TenantLabels::from_config(&config.metrics.tenant_labels) {
+        Some(tenant_labels) => metrics.with_tenant_labels(tenant_labels),
+        None => metrics,
+    };
 
-    // Create instrumented database
-    let instrumented_db = Arc::new(InstrumentedDatabase::new((*pool).clone(), Some(metrics.clone())));
+    // Create instrumented databases; all pools share one slow query log so
+    // plan capture is rate-limited process-wide
+    let slow_query_log = Arc::new(SlowQueryLog::new(&config.database.slow_query));
+    let instrument = |pool: &sqlx::PgPool| {
+        Arc::new(
+            InstrumentedDatabase::new(pool.clone(), Some(metrics.clone()))
+                .with_slow_query_log(slow_query_log.clone()),
+        )
+    };
+    let instrumented_db = instrument(&pool);
+
+    // Auth tables may live in a dedicated schema or database
+    let auth_pool = create_auth_database_pool(&config).await?;
+    let auth_db = match &auth_pool {
+        Some(auth_pool) => instrument(auth_pool),
+        None => instrumented_db.clone(),
+    };
+
+    // User data may be partitioned across additional shards
+    let shard_pools = create_shard_pools(&config).await?;
+    let shard_dbs: Vec<Arc<InstrumentedDatabase>> = shard_pools
+        .iter()
+        .map(|shard_pool| instrument(shard_pool))
+        .collect();
+    let shard_router = Arc::new(ShardRouter::new(instrumented_db.clone(), shard_dbs.clone()));
 
     // Initialize auth services
-    let jwt_service = Arc::new(JwtService::new(&config));
-    let openfga_service = Arc::new(OpenFgaService::new(&config).await?);
+    let jwt_service = Arc::new(JwtService::new(&config)?);
+    let mut openfga_service = OpenFgaService::new(&config).await?.with_metrics(metrics.clone());
+
+    // A bad model file fails startup; an unreachable OpenFGA doesn't, as
+    // elsewhere, and checks keep using the configured model
+    if let Some(model_file) = &config.auth.openfga.model_file {
+        let model = AuthorizationModel::from_file(model_file)?;
+        registry::check_model(&model)?;
+        // Every store gets the model: the shared one, then each tenant's
+        let tenants = openfga_service.dedicated_tenants();
+        for tenant in std::iter::once(None).chain(tenants.into_iter().map(Some)) {
+            let synced = match &tenant {
+                Some(tenant) => {
+                    stores::scope(tenant.clone(), openfga_service.sync_authorization_model(&model))
+                        .await
+                }
+                None => openfga_service.sync_authorization_model(&model).await,
+            };
+            match synced {
+                Ok(model_id) => tracing::info!(
+                    model_file = %model_file,
+                    tenant = ?tenant,
+                    authorization_model_id = %model_id,
+                    "Applied OpenFGA authorization model"
+                ),
+                Err(e) => tracing::warn!(
+                    model_file = %model_file,
+                    tenant = ?tenant,
+                    error = %e,
+                    "Failed to apply OpenFGA authorization model"
+                ),
+            }
+        }
+    }
+    let openfga_service = Arc::new(openfga_service);
+
+    // Find out now rather than per request if a deployed model lacks
+    // relations the code relies on
+    if config.auth.openfga.preflight != ModelPreflight::Off {
+        let tenants = openfga_service.dedicated_tenants();
+        for tenant in std::iter::once(None).chain(tenants.into_iter().map(Some)) {
+            let diff = match &tenant {
+                Some(tenant) => stores::scope(tenant.clone(), openfga_service.preflight_model()).await,
+                None => openfga_service.preflight_model().await,
+            };
+            match diff {
+                Ok(diff) if diff.is_empty() => {
+                    tracing::info!(tenant = ?tenant, "OpenFGA authorization model preflight passed")
+                }
+                Ok(diff) if config.auth.openfga.preflight == ModelPreflight::Enforce => {
+                    anyhow::bail!(
+                        "OpenFGA authorization model{} is missing registered types or relations: {}",
+                        tenant.map(|tenant| format!(" of tenant {}", tenant)).unwrap_or_default(),
+                        diff
+                    );
+                }
+                Ok(diff) => tracing::warn!(
+                    tenant = ?tenant,
+                    missing = %diff,
+                    "OpenFGA authorization model is missing registered types or relations"
+                ),
+                Err(e) => tracing::warn!(
+                    tenant = ?tenant,
+                    error = %e,
+                    "Could not read the OpenFGA authorization model for preflight"
+                ),
+            }
+        }
+    }
+
+    // Invalidate cached checks for tuples other services write
+    let change_watcher =
+        ChangeWatcher::from_config(&config.auth.openfga.changes, openfga_service.clone());
+    if let Some(watcher) = change_watcher {
+        tokio::spawn(watcher.run());
+    }
+    for tenant in openfga_service.dedicated_tenants() {
+        if let Some(watcher) =
+            ChangeWatcher::from_config(&config.auth.openfga.changes, openfga_service.clone())
+        {
+            tokio::spawn(stores::scope(tenant, watcher.run()));
+        }
+    }
+
+    // Role checks also evaluated as OpenFGA relations while migrating
+    let authorization_canary = if config.authorization_canary.enabled {
+        tracing::info!(
+            default_mode = ?config.authorization_canary.default_mode,
+            route_groups = ?config.authorization_canary.route_groups,
+            "Authorization canary enabled"
+        );
+        let authorizer: Arc<dyn Authorizer> = openfga_service.clone();
+        Some(Arc::new(
+            AuthorizationCanary::new(&config.authorization_canary, &config.metrics, authorizer)?
+                .with_metrics(metrics.clone()),
+        ))
+    } else {
+        None
+    };
 
     // Initialize layers
-    let repositories = Arc::new(Repositories::new(instrumented_db.clone()));
+    let repositories = Arc::new(Repositories::sharded(shard_router, auth_db.clone()));
+
+    // Background jobs start once migrations have run
+    let job_worker = if config.jobs.enabled {
+        Some(Arc::new(
+            JobWorker::new(repositories.jobs.clone(), &config.jobs)
+                .with_metrics(&metrics_registry)?
+                .register(Arc::new(TupleCleanupJob::new(openfga_service.clone()))),
+        ))
+    } else {
+        None
+    };
+    let mailer = mailer_from_config(&config.mailer, &metrics_registry)?;
+    let storage = storage_from_config(&config.storage, &config.auth.jwt_secret, &metrics_registry)?;
+    ConfiguredOnboarding::new(&config.auth.onboarding).check()?;
+    // A broken seed file fails startup; applying it waits for migrations
+    let seed = if config.seed.enabled {
+        // Demo accounts have published passwords
+        let run_mode = std::env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string());
+        if run_mode == "production" {
+            anyhow::bail!("Refusing to seed demo data with RUN_MODE=production");
+        }
+        let seed = Seed::from_file(&config.seed.file)?;
+        seed.check()?;
+        Some(seed)
+    } else {
+        None
+    };
     let services = Arc::new(Services::new(
-        repositories,
+        repositories.clone(),
         jwt_service.clone(),
         openfga_service.clone(),
-    ));
+        mailer,
+        &config,
+    )
+    .with_metrics(metrics.clone())
+    .with_avatars(Avatars::new(storage, &config.avatars, &config.storage)));
+    let seeder = seed.map(|seed| {
+        Seeder::new(seed, repositories.clone(), &services, openfga_service.clone())
+    });
+    // Purges start once migrations have run
+    let audit_retention = AuditRetention::from_config(&config.audit, services.audit.clone());
+    let session_cookies = SessionCookies::from_config(&config.auth.cookies)?;
+    let mut auth_state = AuthState::new(jwt_service.clone(), services.sessions.clone());
+    if let Some(cookies) = &session_cookies {
+        auth_state = auth_state.with_cookies(cookies.clone());
+    }
+    let auth_state = auth_state.with_authentication(
+        &config.auth.authentication,
+        RouteGroups::from_config(&config.metrics.route_groups),
+    )?;
 
-    let handlers = Handlers::new(services, jwt_service.clone(), openfga_service);
+    let mut warmup_databases = vec![instrumented_db.clone()];
+    warmup_databases.extend(shard_dbs);
+    if !Arc::ptr_eq(&auth_db, &instrumented_db) {
+        warmup_databases.push(auth_db.clone());
+    }
+    let loki_probe = LokiProbe::from_config(&config, &metrics_registry)?;
+    let health_service = HealthService::from_config(
+        &config.health,
+        warmup_databases.clone(),
+        openfga_service.clone(),
+    )
+    .map(|health| match loki_probe {
+        Some(probe) => health.with_dependency(
+            dependencies::LOKI,
+            Arc::new(probe),
+            Duration::from_millis(config.health.loki_timeout_ms),
+            false,
+        ),
+        None => health,
+    })
+    .map(Arc::new);
+    let mut warmup_service = WarmupService::new(
+        warmup_databases,
+        config.database.min_connections,
+        jwt_service.clone(),
+        openfga_service.clone(),
+    );
+    if let Some(health) = health_service.clone() {
+        warmup_service = warmup_service.with_health(health);
+    }
+    let warmup_service = Arc::new(warmup_service);
+
+    let mut handlers = Handlers::new(services, openfga_service, warmup_service.clone());
+    if let Some(live_tail) = live_tail {
+        handlers = handlers.with_live_tail(LiveTailHandlers::new(
+            live_tail,
+            metrics.clone(),
+            Duration::from_secs(config.live_tail.metrics_interval_secs.max(1)),
+        ));
+    }
+
+    if let Some(limiter) = LoginRateLimiter::from_config(&config.auth.login_rate_limit) {
+        handlers = handlers.with_login_rate_limit(limiter);
+    }
+    if let Some(cookies) = session_cookies {
+        tracing::info!("Cookie session mode enabled");
+        handlers = handlers.with_session_cookies(cookies);
+    }
+
+    let api_usage = ApiUsage::from_config(&config.api_usage)?;
+    if let Some(api_usage) = &api_usage {
+        handlers = handlers.with_api_usage(api_usage.clone());
+    }
+
+    let client_analytics = ClientAnalytics::from_config(&config.client_analytics)
+        .map(|analytics| Arc::new(analytics.with_metrics(metrics.clone())));
+    if let Some(analytics) = &client_analytics {
+        handlers = handlers.with_client_analytics(analytics.clone());
+    }
 
     // Create OpenAPI documentation
     let openapi = ApiDoc::openapi();
@@ -148,13 +340,115 @@ async fn main() -> Result<()> {
         .route("/metrics", axum::routing::get(metrics_handler))
         .with_state(metrics.clone());
 
-    let app = create_routes(handlers, jwt_service)
+    let banner = handlers.banner.clone();
+    let mut app = create_routes(handlers, auth_state)
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", openapi))
-        .merge(metrics_router)
+        .merge(metrics_router);
+
+    // Identical concurrent GETs to expensive routes share one execution
+    if let Some(mut coalescer) = RequestCoalescer::from_config(&config.coalesce) {
+        if let Some(subject_header) = &config.auth.authentication.mtls.subject_header {
+            coalescer = coalescer.with_vary_header(subject_header)?;
+        }
+        let coalescer = Arc::new(coalescer.with_metrics(metrics.clone()));
+        app = app.layer(axum::middleware::from_fn_with_state(coalescer, coalesce_middleware));
+    }
+
+    // Optionally mirror a sample of traffic to a shadow environment
+    if let Some(mut mirror) = TrafficMirror::from_config(&config.mirror, &metrics_registry)? {
+        if let Some(subject_header) = &config.auth.authentication.mtls.subject_header {
+            mirror = mirror.with_skipped_header(subject_header)?;
+        }
+        app = app.layer(axum::middleware::from_fn_with_state(Arc::new(mirror), traffic_mirror_middleware));
+    }
+
+    // Caching headers for CDNs: public groups cacheable, credentials never
+    if let Some(mut edge_cache) = EdgeCache::from_config(&config.edge_cache, &config.metrics)? {
+        if let Some(subject_header) = &config.auth.authentication.mtls.subject_header {
+            edge_cache = edge_cache.with_credential_header(subject_header)?;
+        }
+        app = app.layer(axum::middleware::from_fn_with_state(Arc::new(edge_cache), edge_cache_middleware));
+    }
+
+    // Pace large uploads and downloads per route group
+    if let Some(throttle) = BandwidthThrottle::from_config(&config.bandwidth, &config.metrics) {
+        app = app.layer(BandwidthLayer::new(throttle));
+    }
+
+    if let Some(canary) = authorization_canary {
+        app = app.layer(axum::Extension(canary));
+    }
+
+    // Count calls per endpoint and flag deprecated routes
+    if let Some(api_usage) = api_usage {
+        app = app.layer(axum::middleware::from_fn_with_state(api_usage, api_usage_middleware));
+    }
+
+    // Which client families and versions call which routes
+    if let Some(analytics) = client_analytics {
+        app = app.layer(axum::middleware::from_fn_with_state(analytics, client_analytics_middleware));
+    }
+
+    // Announce the admin-set banner on every response
+    if config.banner.notice_header {
+        app = app.layer(axum::middleware::from_fn_with_state(banner, service_notice_middleware));
+    }
+
+    // Signs and verifies pagination/continuation cursors
+    let cursor_signer = Arc::new(match config.pagination.cursor_secret.as_deref() {
+        Some(secret) if !secret.is_empty() => CursorSigner::new(secret),
+        _ => CursorSigner::derived_from(&config.auth.jwt_secret),
+    });
+
+    let app = app
+        .layer(axum::Extension(cursor_signer))
+        .layer(axum::middleware::from_fn(response_format_middleware))
+        .layer(axum::middleware::from_fn_with_state(metrics.clone(), request_cost_middleware))
         .layer(axum::middleware::from_fn_with_state(metrics.clone(), prometheus_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(config.request_context.clone()),
+            request_context_middleware,
+        ))
         .layer(cors_layer())
         .layer(logging_layer());
 
+    // Migrate, then warm up, in the background; `/ready` reports 503 until
+    // both succeed. Replicas booting together wait on each other's migrations
+    // instead of racing them.
+    let startup_warmup = warmup_service.clone();
+    startup_warmup.set_migrations_pending(true);
+    let migration_config = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) = run_migrations(
+            &migration_config,
+            &pool,
+            &shard_pools,
+            auth_pool.as_deref(),
+        )
+        .await
+        {
+            tracing::error!(error = %e, "Database migrations failed, shutting down");
+            reprime_backend::telemetry::shutdown_telemetry();
+            std::process::exit(1);
+        }
+        startup_warmup.set_migrations_pending(false);
+        if let Some(job_worker) = job_worker {
+            tokio::spawn(job_worker.run());
+        }
+        if let Some(retention) = audit_retention {
+            tokio::spawn(retention.run());
+        }
+        if let Some(seeder) = seeder {
+            if let Err(e) = seeder.run().await {
+                tracing::error!(seed_file = %migration_config.seed.file, error = %e, "Failed to apply seed");
+            }
+        }
+        startup_warmup.warmup().await;
+        if let Some(health) = health_service {
+            tokio::spawn(health.run());
+        }
+    });
+
     // Start server
     let listener = TcpListener::bind(&config.server_address()).await?;
 
@@ -175,7 +469,8 @@ async fn main() -> Result<()> {
     };
 
     // Run server with graceful shutdown
-    axum::serve(listener, app)
+    // Connect info gives the login rate limiter the peer address
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal)
         .await?;
 
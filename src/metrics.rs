@@ -1,18 +1,269 @@
 use prometheus::{
-    Counter, CounterVec, Gauge, GaugeVec, HistogramOpts, HistogramVec, Opts, Registry,
+    core::Collector, proto::MetricFamily, Counter, CounterVec, Gauge, GaugeVec, HistogramOpts,
+    HistogramVec, Opts, Registry,
 };
-use std::sync::Arc;
+use crate::config::TenantLabelConfig;
+use crate::request_cost::RequestCostSnapshot;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// A registered metric's label names and the metric itself, type-erased
+type RegisteredMetric = (Vec<String>, Box<dyn Any + Send + Sync>);
+
+/// Label used for routes that don't belong to any configured group
+pub const DEFAULT_ROUTE_GROUP: &str = "other";
+
+/// Tenant label for tenants past the cardinality guard
+pub const OTHER_TENANT: &str = "other";
+
+/// Maps route templates to stable route groups (auth, users, admin, ...)
+///
+/// Alerting rules key on the group label, so renaming a route only requires
+/// updating the mapping in config rather than every alert expression.
+#[derive(Debug, Clone, Default)]
+pub struct RouteGroups {
+    // (route template prefix, group), longest prefix first
+    prefixes: Vec<(String, String)>,
+}
+
+impl RouteGroups {
+    /// Build from a `group -> [route templates]` mapping
+    pub fn from_config(groups: &HashMap<String, Vec<String>>) -> Self {
+        let mut prefixes: Vec<(String, String)> = groups
+            .iter()
+            .flat_map(|(group, routes)| {
+                routes
+                    .iter()
+                    .map(move |route| (route.trim_end_matches('/').to_string(), group.clone()))
+            })
+            .collect();
+
+        prefixes.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        Self { prefixes }
+    }
+
+    /// Resolve the group for a route template (or raw path when unmatched)
+    pub fn resolve(&self, route: &str) -> &str {
+        self.prefixes
+            .iter()
+            .find(|(prefix, _)| {
+                route == prefix
+                    || (route.starts_with(prefix.as_str())
+                        && route[prefix.len()..].starts_with('/'))
+            })
+            .map(|(_, group)| group.as_str())
+            .unwrap_or(DEFAULT_ROUTE_GROUP)
+    }
+}
+
+/// Bounds the `tenant` label on per-tenant metrics
+///
+/// Pinned tenants always get their own label; the first `max_tenants` other
+/// tenants seen by this process get one too, and everyone after them is
+/// folded into `other`. Pin the tenants you bill or page on so they keep
+/// their series across restarts whatever order traffic arrives in.
+#[derive(Debug)]
+pub struct TenantLabels {
+    pinned: HashSet<String>,
+    max_tenants: usize,
+    admitted: Mutex<HashSet<String>>,
+}
+
+impl TenantLabels {
+    pub fn new(pinned: impl IntoIterator<Item = String>, max_tenants: usize) -> Self {
+        Self {
+            pinned: pinned.into_iter().collect(),
+            max_tenants,
+            admitted: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// `None` unless per-tenant labels are enabled
+    pub fn from_config(config: &TenantLabelConfig) -> Option<Self> {
+        config
+            .enabled
+            .then(|| Self::new(config.pinned.iter().cloned(), config.max_tenants))
+    }
+
+    /// Label value for `tenant`, admitting it while there is room
+    pub fn resolve<'a>(&self, tenant: &'a str) -> &'a str {
+        if self.pinned.contains(tenant) {
+            return tenant;
+        }
+
+        let mut admitted = self.admitted.lock().unwrap();
+        if admitted.contains(tenant) {
+            return tenant;
+        }
+        if admitted.len() < self.max_tenants {
+            admitted.insert(tenant.to_string());
+            return tenant;
+        }
+        OTHER_TENANT
+    }
+}
+
+/// Classify an HTTP status into an error class for error-budget counters
+pub fn error_class(status_code: u16) -> Option<&'static str> {
+    match status_code {
+        401 | 403 => Some("unauthorized"),
+        408 | 504 => Some("timeout"),
+        429 => Some("rate_limited"),
+        400..=499 => Some("client_error"),
+        500..=599 => Some("server_error"),
+        _ => None,
+    }
+}
+
+/// One Prometheus registry shared by every subsystem
+///
+/// Subsystems register through a namespaced view from [`Self::subsystem`].
+/// Asking for a metric that is already registered hands back the existing
+/// collector instead of failing, so components built more than once (an HTTP
+/// client per dependency, a second `AppMetrics`) share their series. Asking
+/// for it with another type or other labels is an error.
+#[derive(Clone)]
+pub struct MetricsRegistry {
+    registry: Arc<Registry>,
+    prefix: Option<String>,
+    // full metric name -> (label names, collector)
+    registered: Arc<Mutex<HashMap<String, RegisteredMetric>>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(Registry::new()),
+            prefix: None,
+            registered: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// A view of the same registry whose metric names start with `name_`
+    pub fn subsystem(&self, name: &str) -> Self {
+        Self {
+            prefix: Some(self.full_name(name)),
+            ..self.clone()
+        }
+    }
+
+    /// `name` with this view's subsystem prefix
+    pub fn full_name(&self, name: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}_{}", prefix, name),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        self.registry.gather()
+    }
+
+    pub fn counter(&self, name: &str, help: &str) -> Result<Counter, prometheus::Error> {
+        self.get_or_register(name, &[], |name| Counter::new(name, help))
+    }
+
+    pub fn counter_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> Result<CounterVec, prometheus::Error> {
+        self.get_or_register(name, labels, |name| CounterVec::new(Opts::new(name, help), labels))
+    }
+
+    pub fn gauge(&self, name: &str, help: &str) -> Result<Gauge, prometheus::Error> {
+        self.get_or_register(name, &[], |name| Gauge::new(name, help))
+    }
+
+    pub fn gauge_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> Result<GaugeVec, prometheus::Error> {
+        self.get_or_register(name, labels, |name| GaugeVec::new(Opts::new(name, help), labels))
+    }
+
+    pub fn histogram_vec(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: Vec<f64>,
+        labels: &[&str],
+    ) -> Result<HistogramVec, prometheus::Error> {
+        self.get_or_register(name, labels, |name| {
+            HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), labels)
+        })
+    }
+
+    fn get_or_register<C>(
+        &self,
+        name: &str,
+        labels: &[&str],
+        build: impl FnOnce(String) -> Result<C, prometheus::Error>,
+    ) -> Result<C, prometheus::Error>
+    where
+        C: Collector + Clone + Send + Sync + 'static,
+    {
+        let name = self.full_name(name);
+        let labels: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        let mut registered = self.registered.lock().unwrap();
+
+        if let Some((existing_labels, existing)) = registered.get(&name) {
+            return match existing.downcast_ref::<C>() {
+                Some(collector) if *existing_labels == labels => Ok(collector.clone()),
+                _ => Err(prometheus::Error::Msg(format!(
+                    "metric {} is already registered with another type or labels",
+                    name
+                ))),
+            };
+        }
+
+        let collector = build(name.clone())?;
+        self.registry.register(Box::new(collector.clone()))?;
+        registered.insert(name, (labels, Box::new(collector.clone())));
+        Ok(collector)
+    }
+}
+
+impl Default for MetricsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Application metrics collector
 #[derive(Clone)]
 pub struct AppMetrics {
-    pub registry: Arc<Registry>,
-    
+    pub registry: MetricsRegistry,
+
     // HTTP metrics
     pub http_requests_total: CounterVec,
     pub http_request_duration_seconds: HistogramVec,
     pub http_requests_in_flight: GaugeVec,
     pub http_error_rate: CounterVec,
+    pub http_route_group_requests_total: CounterVec,
+    pub http_route_group_errors_total: CounterVec,
+    pub http_client_requests_total: CounterVec,
+    pub http_coalesced_requests_total: CounterVec,
+    pub route_groups: Arc<RouteGroups>,
+
+    // Per-tenant usage, only recorded with tenant labels enabled
+    pub tenant_http_requests_total: CounterVec,
+    pub tenant_http_errors_total: CounterVec,
+    pub tenant_db_seconds_total: CounterVec,
+    pub tenant_openfga_calls_total: CounterVec,
+    pub tenant_labels: Option<Arc<TenantLabels>>,
+
+    // Per-request backend cost
+    pub request_db_queries: HistogramVec,
+    pub request_db_seconds: HistogramVec,
+    pub request_openfga_calls: HistogramVec,
+    pub request_cache_hits: HistogramVec,
+    pub request_outbound_http_calls: HistogramVec,
 
     // Database metrics
     pub database_connections_active: Gauge,
@@ -26,6 +277,21 @@ pub struct AppMetrics {
     pub cache_misses_total: CounterVec,
     pub cache_operations_duration_seconds: HistogramVec,
 
+    // OpenFGA circuit breaker
+    pub openfga_circuit_breaker_state: Gauge,
+    pub openfga_fallback_decisions_total: CounterVec,
+    pub openfga_timeouts_total: CounterVec,
+
+    // Retries of failed calls, see utils::retry
+    pub retries_total: CounterVec,
+
+    // Dual evaluation of role checks against relations
+    pub authz_canary_evaluations_total: CounterVec,
+
+    // Concurrent fan-out of service calls
+    pub fanout_items_total: CounterVec,
+    pub fanout_duration_seconds: HistogramVec,
+
     // Application metrics
     pub users_created_total: Counter,
     pub users_updated_total: Counter,
@@ -39,141 +305,274 @@ pub struct AppMetrics {
 
 impl AppMetrics {
     pub fn new() -> Result<Self, prometheus::Error> {
-        let registry = Arc::new(Registry::new());
+        Self::with_registry(&MetricsRegistry::new())
+    }
+
+    /// Register the application metrics into a shared registry
+    pub fn with_registry(registry: &MetricsRegistry) -> Result<Self, prometheus::Error> {
+        let registry = registry.clone();
 
         // HTTP metrics
-        let http_requests_total = CounterVec::new(
-            Opts::new("http_requests_total", "Total number of HTTP requests"),
+        let http_requests_total = registry.counter_vec(
+            "http_requests_total",
+            "Total number of HTTP requests",
             &["method", "endpoint", "status_code", "status_class"],
         )?;
 
-        let http_request_duration_seconds = HistogramVec::new(
-            HistogramOpts::new(
-                "http_request_duration_seconds",
-                "HTTP request duration in seconds",
-            )
-            .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        let http_request_duration_seconds = registry.histogram_vec(
+            "http_request_duration_seconds",
+            "HTTP request duration in seconds",
+            vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
             &["method", "endpoint"],
         )?;
 
-        let http_requests_in_flight = GaugeVec::new(
-            Opts::new("http_requests_in_flight", "Number of HTTP requests currently being processed"),
+        let http_requests_in_flight = registry.gauge_vec(
+            "http_requests_in_flight",
+            "Number of HTTP requests currently being processed",
             &["method", "endpoint"],
         )?;
 
-        let http_error_rate = CounterVec::new(
-            Opts::new("http_error_rate", "HTTP error rate counter"),
+        let http_error_rate = registry.counter_vec(
+            "http_error_rate",
+            "HTTP error rate counter",
             &["method", "endpoint", "status_code"],
         )?;
 
+        let http_route_group_requests_total = registry.counter_vec(
+            "http_route_group_requests_total",
+            "Total number of HTTP requests per route group",
+            &["route_group"],
+        )?;
+
+        let http_route_group_errors_total = registry.counter_vec(
+            "http_route_group_errors_total",
+            "Total number of HTTP errors per route group and error class",
+            &["route_group", "error_class"],
+        )?;
+
+        // Client families come from a configured list, so both labels are bounded
+        let http_client_requests_total = registry.counter_vec(
+            "http_client_requests_total",
+            "Total number of HTTP requests per client family (from User-Agent) and route group",
+            &["client_family", "route_group"],
+        )?;
+
+        // Only configured routes coalesce, so the label is bounded
+        let http_coalesced_requests_total = registry.counter_vec(
+            "http_coalesced_requests_total",
+            "GET requests served by an identical request already in flight, by route template",
+            &["route"],
+        )?;
+
+        // Per-tenant usage; TenantLabels bounds the tenant label
+        let tenant_http_requests_total = registry.counter_vec(
+            "tenant_http_requests_total",
+            "Total number of authenticated HTTP requests per tenant and route group",
+            &["tenant", "route_group"],
+        )?;
+
+        let tenant_http_errors_total = registry.counter_vec(
+            "tenant_http_errors_total",
+            "Total number of authenticated HTTP errors per tenant, route group and error class",
+            &["tenant", "route_group", "error_class"],
+        )?;
+
+        let tenant_db_seconds_total = registry.counter_vec(
+            "tenant_db_seconds_total",
+            "Time spent in database statements on behalf of each tenant",
+            &["tenant"],
+        )?;
+
+        let tenant_openfga_calls_total = registry.counter_vec(
+            "tenant_openfga_calls_total",
+            "OpenFGA API calls made on behalf of each tenant",
+            &["tenant"],
+        )?;
+
+        // Per-request backend cost, by route template
+        let count_buckets = vec![0.0, 1.0, 2.0, 3.0, 5.0, 8.0, 13.0, 21.0, 34.0, 55.0, 89.0];
+        let request_db_queries = registry.histogram_vec(
+            "http_request_db_queries",
+            "Database statements executed per HTTP request",
+            count_buckets.clone(),
+            &["method", "route"],
+        )?;
+
+        let request_db_seconds = registry.histogram_vec(
+            "http_request_db_seconds",
+            "Time spent in database statements per HTTP request",
+            vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0],
+            &["method", "route"],
+        )?;
+
+        let request_openfga_calls = registry.histogram_vec(
+            "http_request_openfga_calls",
+            "OpenFGA API calls per HTTP request",
+            count_buckets.clone(),
+            &["method", "route"],
+        )?;
+
+        let request_cache_hits = registry.histogram_vec(
+            "http_request_cache_hits",
+            "In-process cache hits per HTTP request",
+            count_buckets.clone(),
+            &["method", "route"],
+        )?;
+
+        let request_outbound_http_calls = registry.histogram_vec(
+            "http_request_outbound_http_calls",
+            "Outbound HTTP calls (other than OpenFGA) per HTTP request",
+            count_buckets,
+            &["method", "route"],
+        )?;
+
         // Database metrics
-        let database_connections_active = Gauge::new(
+        let database_connections_active = registry.gauge(
             "database_connections_active",
             "Number of active database connections",
         )?;
 
-        let database_connections_idle = Gauge::new(
+        let database_connections_idle = registry.gauge(
             "database_connections_idle",
             "Number of idle database connections",
         )?;
 
-        let database_query_duration_seconds = HistogramVec::new(
-            HistogramOpts::new(
-                "database_query_duration_seconds",
-                "Database query duration in seconds",
-            )
-            .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+        let database_query_duration_seconds = registry.histogram_vec(
+            "database_query_duration_seconds",
+            "Database query duration in seconds",
+            vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0],
             &["query_type", "table"],
         )?;
 
-        let database_queries_total = CounterVec::new(
-            Opts::new("database_queries_total", "Total number of database queries"),
+        let database_queries_total = registry.counter_vec(
+            "database_queries_total",
+            "Total number of database queries",
             &["query_type", "table", "status"],
         )?;
 
-        let database_query_errors_total = CounterVec::new(
-            Opts::new("database_query_errors_total", "Total number of database query errors"),
+        let database_query_errors_total = registry.counter_vec(
+            "database_query_errors_total",
+            "Total number of database query errors",
             &["query_type", "table"],
         )?;
 
         // Cache metrics
-        let cache_hits_total = CounterVec::new(
-            Opts::new("cache_hits_total", "Total number of cache hits"),
+        let cache_hits_total = registry.counter_vec(
+            "cache_hits_total",
+            "Total number of cache hits",
             &["cache_type", "operation"],
         )?;
 
-        let cache_misses_total = CounterVec::new(
-            Opts::new("cache_misses_total", "Total number of cache misses"),
+        let cache_misses_total = registry.counter_vec(
+            "cache_misses_total",
+            "Total number of cache misses",
             &["cache_type", "operation"],
         )?;
 
-        let cache_operations_duration_seconds = HistogramVec::new(
-            HistogramOpts::new(
-                "cache_operations_duration_seconds",
-                "Cache operation duration in seconds",
-            )
-            .buckets(vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5]),
+        let cache_operations_duration_seconds = registry.histogram_vec(
+            "cache_operations_duration_seconds",
+            "Cache operation duration in seconds",
+            vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5],
             &["cache_type", "operation"],
         )?;
 
+        // OpenFGA circuit breaker
+        let openfga_circuit_breaker_state = registry.gauge(
+            "openfga_circuit_breaker_state",
+            "OpenFGA circuit breaker state (0 closed, 1 half-open, 2 open)",
+        )?;
+
+        let openfga_fallback_decisions_total = registry.counter_vec(
+            "openfga_fallback_decisions_total",
+            "Permission checks answered by the fallback policy while OpenFGA was unavailable",
+            &["relation", "decision"],
+        )?;
+        let openfga_timeouts_total = registry.counter_vec(
+            "openfga_timeouts_total",
+            "OpenFGA calls that ran out of their time budget, by operation (check, read, write)",
+            &["operation"],
+        )?;
+
+        // Retries of failed calls
+        let retries_total = registry.counter_vec(
+            "retries_total",
+            "Retry decisions by policy and outcome (retried, exhausted, budget_exhausted, cancelled)",
+            &["policy", "outcome"],
+        )?;
+
+        // Dual evaluation of role checks against relations
+        let authz_canary_evaluations_total = registry.counter_vec(
+            "authz_canary_evaluations_total",
+            "Role checks also evaluated as OpenFGA relations, by outcome (agree, disagree, error)",
+            &["route_group", "outcome"],
+        )?;
+
+        // Concurrent fan-out of service calls
+        let fanout_items_total = registry.counter_vec(
+            "fanout_items_total",
+            "Items processed by concurrent fan-outs, by outcome (ok, error, timeout)",
+            &["operation", "outcome"],
+        )?;
+
+        let fanout_duration_seconds = registry.histogram_vec(
+            "fanout_duration_seconds",
+            "Wall time of a concurrent fan-out, all items included",
+            vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+            &["operation"],
+        )?;
+
         // Application metrics
-        let users_created_total = Counter::new(
+        let users_created_total = registry.counter(
             "users_created_total",
             "Total number of users created",
         )?;
 
-        let users_updated_total = Counter::new(
+        let users_updated_total = registry.counter(
             "users_updated_total",
             "Total number of users updated",
         )?;
 
-        let users_deleted_total = Counter::new(
+        let users_deleted_total = registry.counter(
             "users_deleted_total",
             "Total number of users deleted",
         )?;
 
-        let users_retrieved_total = Counter::new(
+        let users_retrieved_total = registry.counter(
             "users_retrieved_total",
             "Total number of user retrievals",
         )?;
 
         // System metrics
-        let memory_usage_bytes = Gauge::new(
+        let memory_usage_bytes = registry.gauge(
             "memory_usage_bytes",
             "Current memory usage in bytes",
         )?;
 
-        let cpu_usage_percent = Gauge::new(
+        let cpu_usage_percent = registry.gauge(
             "cpu_usage_percent",
             "Current CPU usage percentage",
         )?;
-
-        // Register all metrics
-        registry.register(Box::new(http_requests_total.clone()))?;
-        registry.register(Box::new(http_request_duration_seconds.clone()))?;
-        registry.register(Box::new(http_requests_in_flight.clone()))?;
-        registry.register(Box::new(http_error_rate.clone()))?;
-        registry.register(Box::new(database_connections_active.clone()))?;
-        registry.register(Box::new(database_connections_idle.clone()))?;
-        registry.register(Box::new(database_query_duration_seconds.clone()))?;
-        registry.register(Box::new(database_queries_total.clone()))?;
-        registry.register(Box::new(database_query_errors_total.clone()))?;
-        registry.register(Box::new(cache_hits_total.clone()))?;
-        registry.register(Box::new(cache_misses_total.clone()))?;
-        registry.register(Box::new(cache_operations_duration_seconds.clone()))?;
-        registry.register(Box::new(users_created_total.clone()))?;
-        registry.register(Box::new(users_updated_total.clone()))?;
-        registry.register(Box::new(users_deleted_total.clone()))?;
-        registry.register(Box::new(users_retrieved_total.clone()))?;
-        registry.register(Box::new(memory_usage_bytes.clone()))?;
-        registry.register(Box::new(cpu_usage_percent.clone()))?;
-
         Ok(Self {
             registry,
             http_requests_total,
             http_request_duration_seconds,
             http_requests_in_flight,
             http_error_rate,
+            http_route_group_requests_total,
+            http_route_group_errors_total,
+            http_client_requests_total,
+            http_coalesced_requests_total,
+            route_groups: Arc::new(RouteGroups::default()),
+            tenant_http_requests_total,
+            tenant_http_errors_total,
+            tenant_db_seconds_total,
+            tenant_openfga_calls_total,
+            tenant_labels: None,
+            request_db_queries,
+            request_db_seconds,
+            request_openfga_calls,
+            request_cache_hits,
+            request_outbound_http_calls,
             database_connections_active,
             database_connections_idle,
             database_query_duration_seconds,
@@ -182,6 +581,13 @@ impl AppMetrics {
             cache_hits_total,
             cache_misses_total,
             cache_operations_duration_seconds,
+            openfga_circuit_breaker_state,
+            openfga_fallback_decisions_total,
+            openfga_timeouts_total,
+            retries_total,
+            authz_canary_evaluations_total,
+            fanout_items_total,
+            fanout_duration_seconds,
             users_created_total,
             users_updated_total,
             users_deleted_total,
@@ -230,6 +636,98 @@ impl AppMetrics {
         }
     }
 
+    /// Record what a request cost in backend calls
+    pub fn record_request_cost(&self, method: &str, route: &str, cost: &RequestCostSnapshot) {
+        let labels = [method, route];
+        self.request_db_queries
+            .with_label_values(&labels)
+            .observe(cost.db_queries as f64);
+        self.request_db_seconds
+            .with_label_values(&labels)
+            .observe(cost.db_time.as_secs_f64());
+        self.request_openfga_calls
+            .with_label_values(&labels)
+            .observe(cost.openfga_calls as f64);
+        self.request_cache_hits
+            .with_label_values(&labels)
+            .observe(cost.cache_hits as f64);
+        self.request_outbound_http_calls
+            .with_label_values(&labels)
+            .observe(cost.http_calls as f64);
+    }
+
+    /// Use the given route template -> group mapping for error-budget counters
+    pub fn with_route_groups(mut self, route_groups: RouteGroups) -> Self {
+        self.route_groups = Arc::new(route_groups);
+        self
+    }
+
+    /// Record a request against its route group's error budget
+    pub fn record_route_group_request(&self, route: &str, status_code: u16) {
+        let group = self.route_groups.resolve(route);
+
+        self.http_route_group_requests_total
+            .with_label_values(&[group])
+            .inc();
+
+        if let Some(class) = error_class(status_code) {
+            self.http_route_group_errors_total
+                .with_label_values(&[group, class])
+                .inc();
+        }
+    }
+
+    /// Label per-tenant metrics, bounded by `tenant_labels`; without this
+    /// they aren't recorded
+    pub fn with_tenant_labels(mut self, tenant_labels: TenantLabels) -> Self {
+        self.tenant_labels = Some(Arc::new(tenant_labels));
+        self
+    }
+
+    /// Record an authenticated request against its tenant
+    pub fn record_tenant_request(&self, tenant: &str, route: &str, status_code: u16) {
+        let Some(tenant_labels) = &self.tenant_labels else {
+            return;
+        };
+        let tenant = tenant_labels.resolve(tenant);
+        let group = self.route_groups.resolve(route);
+
+        self.tenant_http_requests_total
+            .with_label_values(&[tenant, group])
+            .inc();
+
+        if let Some(class) = error_class(status_code) {
+            self.tenant_http_errors_total
+                .with_label_values(&[tenant, group, class])
+                .inc();
+        }
+    }
+
+    /// Record what a request cost its tenant in backend calls
+    pub fn record_tenant_cost(&self, tenant: &str, cost: &RequestCostSnapshot) {
+        let Some(tenant_labels) = &self.tenant_labels else {
+            return;
+        };
+        let tenant = tenant_labels.resolve(tenant);
+
+        self.tenant_db_seconds_total
+            .with_label_values(&[tenant])
+            .inc_by(cost.db_time.as_secs_f64());
+        self.tenant_openfga_calls_total
+            .with_label_values(&[tenant])
+            .inc_by(cost.openfga_calls as f64);
+    }
+
+    pub fn record_client_request(&self, client_family: &str, route: &str) {
+        self.http_client_requests_total
+            .with_label_values(&[client_family, self.route_groups.resolve(route)])
+            .inc();
+    }
+
+    pub fn record_coalesced_request(&self, route: &str) {
+        self.http_coalesced_requests_total.with_label_values(&[route]).inc();
+    }
+
     /// Record database query with trace correlation
     pub fn record_database_query(&self, query_type: &str, table: &str, status: &str, duration: f64) {
         self.database_queries_total
@@ -266,6 +764,54 @@ impl AppMetrics {
         self.database_connections_idle.set(idle as f64);
     }
 
+    /// Record an OpenFGA circuit breaker state change
+    pub fn set_openfga_circuit_state(&self, state: f64) {
+        self.openfga_circuit_breaker_state.set(state);
+    }
+
+    /// Record a permission check answered by the OpenFGA fallback policy
+    pub fn record_openfga_fallback(&self, relation: &str, allowed: bool) {
+        let decision = if allowed { "allow" } else { "deny" };
+        self.openfga_fallback_decisions_total
+            .with_label_values(&[relation, decision])
+            .inc();
+    }
+
+    pub fn record_openfga_timeout(&self, operation: &str) {
+        self.openfga_timeouts_total.with_label_values(&[operation]).inc();
+    }
+
+    pub fn record_retry(&self, policy: &str, outcome: &str) {
+        self.retries_total.with_label_values(&[policy, outcome]).inc();
+    }
+
+    pub fn record_authz_canary(&self, route_group: &str, outcome: &str) {
+        self.authz_canary_evaluations_total
+            .with_label_values(&[route_group, outcome])
+            .inc();
+    }
+
+    /// Record one fan-out: item counts per outcome and total wall time
+    pub fn record_fanout(
+        &self,
+        operation: &str,
+        succeeded: usize,
+        failed: usize,
+        timed_out: usize,
+        duration: f64,
+    ) {
+        for (outcome, count) in [("ok", succeeded), ("error", failed), ("timeout", timed_out)] {
+            if count > 0 {
+                self.fanout_items_total
+                    .with_label_values(&[operation, outcome])
+                    .inc_by(count as f64);
+            }
+        }
+        self.fanout_duration_seconds
+            .with_label_values(&[operation])
+            .observe(duration);
+    }
+
     /// Record user operations
     pub fn record_user_created(&self) {
         self.users_created_total.inc();
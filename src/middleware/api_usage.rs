@@ -0,0 +1,253 @@
+use crate::auth::models::AuthContext;
+use crate::config::{ApiUsageConfig, DeprecatedRouteConfig};
+use crate::models::format;
+use crate::utils::http_date;
+use anyhow::Context;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// Client label for requests without an authenticated caller
+pub const ANONYMOUS_CLIENT: &str = "anonymous";
+/// Client label that absorbs new clients once the store is full
+pub const OVERFLOW_CLIENT: &str = "other";
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    method: String,
+    route: String,
+    client: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UsageCounter {
+    calls: u64,
+    last_used_at: DateTime<Utc>,
+}
+
+/// A route scheduled for removal, with its headers pre-rendered
+#[derive(Debug, Clone)]
+struct Deprecation {
+    route: String,
+    method: Option<String>,
+    deprecated_on: DateTime<Utc>,
+    sunset_on: Option<DateTime<Utc>>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl Deprecation {
+    fn from_config(config: &DeprecatedRouteConfig) -> anyhow::Result<Self> {
+        let deprecated_on = parse_date(&config.deprecated_on)
+            .with_context(|| format!("Invalid deprecated_on for {}", config.route))?;
+        let sunset_on = config
+            .sunset_on
+            .as_deref()
+            .map(parse_date)
+            .transpose()
+            .with_context(|| format!("Invalid sunset_on for {}", config.route))?;
+
+        // RFC 9745 structured date, RFC 8594 HTTP-date
+        let mut headers = vec![(
+            DEPRECATION,
+            HeaderValue::from_str(&format!("@{}", deprecated_on.timestamp()))?,
+        )];
+        if let Some(sunset_on) = sunset_on {
+            headers.push((SUNSET, HeaderValue::from_str(&http_date(sunset_on))?));
+        }
+        if let Some(link) = &config.link {
+            headers.push((
+                axum::http::header::LINK,
+                HeaderValue::from_str(&format!("<{}>; rel=\"deprecation\"", link))
+                    .with_context(|| format!("Invalid link for {}", config.route))?,
+            ));
+        }
+
+        Ok(Self {
+            route: config.route.clone(),
+            method: config.method.as_ref().map(|m| m.to_uppercase()),
+            deprecated_on,
+            sunset_on,
+            headers,
+        })
+    }
+
+    fn matches(&self, method: &str, route: &str) -> bool {
+        self.route == route && self.method.as_deref().is_none_or(|m| m == method)
+    }
+}
+
+fn parse_date(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("expected YYYY-MM-DD, got '{}'", value))?;
+    Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc())
+}
+
+/// Usage of one route by one client
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApiUsageEntry {
+    pub method: String,
+    pub route: String,
+    /// API version taken from the route prefix, e.g. `v1`
+    pub version: String,
+    /// `user:<id>` for authenticated callers, otherwise `anonymous`
+    pub client: String,
+    pub calls: u64,
+    #[serde(with = "format::timestamp")]
+    pub last_used_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub deprecated_on: Option<DateTime<Utc>>,
+    #[serde(with = "format::option_timestamp")]
+    pub sunset_on: Option<DateTime<Utc>>,
+}
+
+/// In-memory call counts per (route, method, client)
+///
+/// Counts reset on restart; they are meant to answer "who still calls this"
+/// before a route is removed, not to bill anyone.
+pub struct ApiUsage {
+    counters: Mutex<HashMap<UsageKey, UsageCounter>>,
+    max_entries: usize,
+    deprecations: Vec<Deprecation>,
+}
+
+impl ApiUsage {
+    pub fn new(config: &ApiUsageConfig) -> anyhow::Result<Self> {
+        let deprecations = config
+            .deprecations
+            .iter()
+            .map(Deprecation::from_config)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            counters: Mutex::new(HashMap::new()),
+            max_entries: config.max_entries.max(1),
+            deprecations,
+        })
+    }
+
+    /// Build the store from config, or `None` when tracking is disabled
+    pub fn from_config(config: &ApiUsageConfig) -> anyhow::Result<Option<Arc<Self>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        Ok(Some(Arc::new(Self::new(config)?)))
+    }
+
+    pub fn record(&self, method: &str, route: &str, client: &str) {
+        let mut counters = self.counters.lock().unwrap();
+
+        let mut key = UsageKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            client: client.to_string(),
+        };
+        if !counters.contains_key(&key) && counters.len() >= self.max_entries {
+            key.client = OVERFLOW_CLIENT.to_string();
+        }
+
+        let now = Utc::now();
+        let counter = counters.entry(key).or_insert(UsageCounter {
+            calls: 0,
+            last_used_at: now,
+        });
+        counter.calls += 1;
+        counter.last_used_at = now;
+    }
+
+    /// All tracked usage, most-called first
+    pub fn snapshot(&self) -> Vec<ApiUsageEntry> {
+        let counters = self.counters.lock().unwrap();
+
+        let mut entries: Vec<ApiUsageEntry> = counters
+            .iter()
+            .map(|(key, counter)| {
+                let deprecation = self.deprecation(&key.method, &key.route);
+                ApiUsageEntry {
+                    method: key.method.clone(),
+                    route: key.route.clone(),
+                    version: api_version(&key.route).to_string(),
+                    client: key.client.clone(),
+                    calls: counter.calls,
+                    last_used_at: counter.last_used_at,
+                    deprecated_on: deprecation.map(|d| d.deprecated_on),
+                    sunset_on: deprecation.and_then(|d| d.sunset_on),
+                }
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            b.calls
+                .cmp(&a.calls)
+                .then_with(|| a.route.cmp(&b.route))
+                .then_with(|| a.method.cmp(&b.method))
+                .then_with(|| a.client.cmp(&b.client))
+        });
+        entries
+    }
+
+    fn deprecation(&self, method: &str, route: &str) -> Option<&Deprecation> {
+        self.deprecations.iter().find(|d| d.matches(method, route))
+    }
+
+    /// Add `Deprecation` / `Sunset` / `Link` headers when the route is flagged
+    pub fn apply_headers(&self, method: &str, route: &str, response: &mut Response) {
+        if let Some(deprecation) = self.deprecation(method, route) {
+            let headers = response.headers_mut();
+            for (name, value) in &deprecation.headers {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// API version segment of a route template (`/api/v1/...` -> `v1`)
+pub fn api_version(route: &str) -> &str {
+    route
+        .strip_prefix("/api/")
+        .and_then(|rest| rest.split('/').next())
+        .filter(|segment| segment.starts_with('v'))
+        .unwrap_or("unversioned")
+}
+
+/// Middleware that counts calls per route and client and flags deprecated routes
+pub async fn api_usage_middleware(
+    State(usage): State<Arc<ApiUsage>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Unmatched paths would let callers grow the store without bound
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+    let method = request.method().to_string();
+
+    let mut response = next.run(request).await;
+
+    // The auth middleware copies the caller onto the response
+    let client = response
+        .extensions()
+        .get::<AuthContext>()
+        .map(|auth| auth.subject().to_string())
+        .unwrap_or_else(|| ANONYMOUS_CLIENT.to_string());
+
+    usage.record(&method, &route, &client);
+    usage.apply_headers(&method, &route, &mut response);
+
+    response
+}
@@ -0,0 +1,358 @@
+use crate::config::{BandwidthConfig, BandwidthLimit, MetricsConfig};
+use crate::metrics::RouteGroups;
+use crate::request_context;
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, MatchedPath, Request},
+    response::Response,
+};
+use futures::future::BoxFuture;
+use http_body::{Frame, SizeHint};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::time::Sleep;
+use tower::{Layer, Service};
+
+#[derive(Debug, Clone, Copy)]
+struct Rate {
+    bytes_per_sec: u64,
+    burst_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Limits {
+    upload: Option<Rate>,
+    download: Option<Rate>,
+}
+
+impl Limits {
+    fn from_config(limit: &BandwidthLimit) -> Self {
+        let rate = |bytes_per_sec: Option<u64>| {
+            bytes_per_sec.filter(|rate| *rate > 0).map(|bytes_per_sec| Rate {
+                bytes_per_sec,
+                burst_bytes: limit.burst_bytes.unwrap_or(bytes_per_sec).max(1),
+            })
+        };
+        Self {
+            upload: rate(limit.upload_bytes_per_sec),
+            download: rate(limit.download_bytes_per_sec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Upload,
+    Download,
+}
+
+/// One client's bucket for one route group and direction
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    group: String,
+    client: String,
+    direction: Direction,
+}
+
+type SharedBucket = Arc<Mutex<TokenBucket>>;
+
+/// Paces request and response bodies per route group, so one client
+/// streaming a large import or export can't take the whole link
+///
+/// Every body of a client in a group draws on the same token bucket, so
+/// parallel requests or HTTP/2 streams share the client's rate. Clients are
+/// keyed on their address (see [`request_context::forwarded_client_ip`]).
+pub struct BandwidthThrottle {
+    groups: RouteGroups,
+    limits: HashMap<String, Limits>,
+    buckets: Mutex<HashMap<BucketKey, SharedBucket>>,
+    trusted_proxy_hops: usize,
+    max_entries: usize,
+}
+
+impl BandwidthThrottle {
+    /// Limits are keyed by the group names in `[metrics.route_groups]`;
+    /// routes in a group without limits aren't paced
+    pub fn new(config: &BandwidthConfig, metrics: &MetricsConfig) -> Self {
+        Self {
+            groups: RouteGroups::from_config(&metrics.route_groups),
+            limits: config
+                .route_groups
+                .iter()
+                .map(|(group, limit)| (group.clone(), Limits::from_config(limit)))
+                .collect(),
+            buckets: Mutex::new(HashMap::new()),
+            trusted_proxy_hops: config.trusted_proxy_hops,
+            max_entries: config.max_entries.max(1),
+        }
+    }
+
+    /// Build from config, or `None` when disabled
+    pub fn from_config(config: &BandwidthConfig, metrics: &MetricsConfig) -> Option<Arc<Self>> {
+        if !config.enabled || config.route_groups.is_empty() {
+            return None;
+        }
+
+        tracing::info!(
+            route_groups = ?config.route_groups.keys().collect::<Vec<_>>(),
+            "Bandwidth throttling enabled"
+        );
+        Some(Arc::new(Self::new(config, metrics)))
+    }
+
+    /// The client's bucket for `group` and `direction`, created at `rate`
+    fn bucket(&self, group: &str, client: &str, direction: Direction, rate: Rate) -> SharedBucket {
+        let key = BucketKey {
+            group: group.to_string(),
+            client: client.to_string(),
+            direction,
+        };
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if !buckets.contains_key(&key) && buckets.len() >= self.max_entries {
+            let now = Instant::now();
+            // Buckets no body is using that have refilled carry no state
+            buckets.retain(|_, bucket| {
+                Arc::strong_count(bucket) > 1 || !bucket.lock().unwrap().is_full(now)
+            });
+            // Still full: drop the one idle the longest; bodies using it keep
+            // their handle
+            if buckets.len() >= self.max_entries {
+                let oldest = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.lock().unwrap().refilled_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    tracing::warn!(
+                        max_entries = self.max_entries,
+                        "Bandwidth throttle is full, evicting the oldest bucket"
+                    );
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+
+        buckets
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(TokenBucket::new(rate))))
+            .clone()
+    }
+}
+
+/// Wraps request and response bodies in [`ThrottledBody`]
+#[derive(Clone)]
+pub struct BandwidthLayer {
+    throttle: Arc<BandwidthThrottle>,
+}
+
+impl BandwidthLayer {
+    pub fn new(throttle: Arc<BandwidthThrottle>) -> Self {
+        Self { throttle }
+    }
+}
+
+impl<S> Layer<S> for BandwidthLayer {
+    type Service = BandwidthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BandwidthService {
+            inner,
+            throttle: self.throttle.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BandwidthService<S> {
+    inner: S,
+    throttle: Arc<BandwidthThrottle>,
+}
+
+impl<S> Service<Request> for BandwidthService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| request.uri().path().to_string());
+        let group = self.throttle.groups.resolve(&route).to_string();
+        let limits = self.throttle.limits.get(&group).copied().unwrap_or_default();
+        if limits.upload.is_none() && limits.download.is_none() {
+            return Box::pin(self.inner.call(request));
+        }
+
+        // Only missing when served without connect info, as in tests
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| *peer);
+        let client = request_context::forwarded_client_ip(
+            request.headers(),
+            peer,
+            self.throttle.trusted_proxy_hops,
+        )
+        .unwrap_or_else(|| "unknown".to_string());
+
+        let request = match limits.upload {
+            Some(rate) => {
+                let bucket = self.throttle.bucket(&group, &client, Direction::Upload, rate);
+                request.map(|body| Body::new(ThrottledBody::new(body, bucket, rate)))
+            }
+            None => request,
+        };
+        let response = self.inner.call(request);
+        let throttle = self.throttle.clone();
+
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(match limits.download {
+                Some(rate) => {
+                    let bucket = throttle.bucket(&group, &client, Direction::Download, rate);
+                    response.map(|body| Body::new(ThrottledBody::new(body, bucket, rate)))
+                }
+                None => response,
+            })
+        })
+    }
+}
+
+/// Token bucket refilled at the configured rate, holding at most a burst
+#[derive(Debug)]
+struct TokenBucket {
+    rate: Rate,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: Rate) -> Self {
+        Self {
+            rate,
+            tokens: rate.burst_bytes as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    fn refilled(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        (self.tokens + elapsed * self.rate.bytes_per_sec as f64).min(self.rate.burst_bytes as f64)
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        self.refilled(now) >= self.rate.burst_bytes as f64
+    }
+
+    /// How long until `bytes` tokens are available, after refilling
+    fn wait_for(&mut self, bytes: usize) -> Option<Duration> {
+        let now = Instant::now();
+        self.tokens = self.refilled(now);
+        self.refilled_at = now;
+
+        let missing = bytes as f64 - self.tokens;
+        (missing > 0.0).then(|| Duration::from_secs_f64(missing / self.rate.bytes_per_sec as f64))
+    }
+
+    fn consume(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+}
+
+/// A body whose data is released no faster than its client's bucket allows,
+/// in slices of at most one burst
+pub struct ThrottledBody {
+    inner: Body,
+    bucket: SharedBucket,
+    burst_bytes: usize,
+    /// Data read from `inner` but not yet released
+    pending: Bytes,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl ThrottledBody {
+    fn new(inner: Body, bucket: SharedBucket, rate: Rate) -> Self {
+        Self {
+            inner,
+            bucket,
+            burst_bytes: rate.burst_bytes as usize,
+            pending: Bytes::new(),
+            sleep: None,
+        }
+    }
+}
+
+impl http_body::Body for ThrottledBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let this = &mut *self;
+        loop {
+            if this.pending.is_empty() {
+                match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                    Some(Ok(frame)) => match frame.into_data() {
+                        Ok(data) => this.pending = data,
+                        // Trailers aren't counted
+                        Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                    },
+                    other => return Poll::Ready(other),
+                }
+                continue;
+            }
+
+            if let Some(sleep) = this.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                this.sleep = None;
+            }
+
+            let len = this.pending.len().min(this.burst_bytes);
+            let wait = {
+                let mut bucket = this.bucket.lock().unwrap();
+                let wait = bucket.wait_for(len);
+                if wait.is_none() {
+                    bucket.consume(len);
+                }
+                wait
+            };
+            if let Some(wait) = wait {
+                this.sleep = Some(Box::pin(tokio::time::sleep(wait)));
+                continue;
+            }
+
+            return Poll::Ready(Some(Ok(Frame::data(this.pending.split_to(len)))));
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.pending.is_empty() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let mut hint = self.inner.size_hint();
+        let pending = self.pending.len() as u64;
+        if let Some(upper) = hint.upper() {
+            hint.set_upper(upper + pending);
+        }
+        hint.set_lower(hint.lower() + pending);
+        hint
+    }
+}
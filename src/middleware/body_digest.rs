@@ -0,0 +1,127 @@
+use crate::errors::AppError;
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256, Sha512};
+
+const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+const X_CONTENT_SHA256: HeaderName = HeaderName::from_static("x-content-sha256");
+
+/// Same cap as axum's default body limit for extractors
+pub const MAX_VERIFIED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// A digest the client sent for the request body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectedDigest {
+    Sha256(Vec<u8>),
+    Sha512(Vec<u8>),
+}
+
+impl ExpectedDigest {
+    /// Read `Content-Digest` (RFC 9530), falling back to `X-Content-SHA256`
+    /// (hex); `Ok(None)` when the client sent neither
+    pub fn from_headers(headers: &HeaderMap) -> Result<Option<Self>, AppError> {
+        if let Some(value) = headers.get(CONTENT_DIGEST) {
+            let value = value
+                .to_str()
+                .map_err(|_| AppError::BadRequest("Malformed Content-Digest header".to_string()))?;
+            return parse_content_digest(value).map(Some);
+        }
+
+        match headers.get(X_CONTENT_SHA256) {
+            Some(value) => {
+                let digest = value
+                    .to_str()
+                    .ok()
+                    .and_then(decode_hex)
+                    .filter(|digest| digest.len() == 32)
+                    .ok_or_else(|| {
+                        AppError::BadRequest("Malformed X-Content-SHA256 header".to_string())
+                    })?;
+                Ok(Some(ExpectedDigest::Sha256(digest)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn matches(&self, body: &[u8]) -> bool {
+        match self {
+            ExpectedDigest::Sha256(expected) => Sha256::digest(body).as_slice() == expected.as_slice(),
+            ExpectedDigest::Sha512(expected) => Sha512::digest(body).as_slice() == expected.as_slice(),
+        }
+    }
+}
+
+/// `sha-256=:<base64>:, sha-512=:<base64>:`; the strongest supported
+/// algorithm wins and unknown ones are ignored
+fn parse_content_digest(value: &str) -> Result<ExpectedDigest, AppError> {
+    let malformed = || AppError::BadRequest("Malformed Content-Digest header".to_string());
+
+    let mut sha256 = None;
+    let mut sha512 = None;
+    for member in value.split(',') {
+        let (algorithm, digest) = member.trim().split_once('=').ok_or_else(malformed)?;
+        let digest = digest
+            .strip_prefix(':')
+            .and_then(|digest| digest.strip_suffix(':'))
+            .ok_or_else(malformed)?;
+        match algorithm.trim().to_ascii_lowercase().as_str() {
+            "sha-256" => sha256 = Some(STANDARD.decode(digest).map_err(|_| malformed())?),
+            "sha-512" => sha512 = Some(STANDARD.decode(digest).map_err(|_| malformed())?),
+            _ => {}
+        }
+    }
+
+    sha512
+        .map(ExpectedDigest::Sha512)
+        .or(sha256.map(ExpectedDigest::Sha256))
+        .ok_or_else(|| {
+            AppError::BadRequest(
+                "Content-Digest has no supported algorithm (sha-256, sha-512)".to_string(),
+            )
+        })
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Reject a request whose body doesn't match the digest the client sent,
+/// e.g. after truncation by a proxy
+///
+/// Requests without a digest header pass through untouched.
+pub async fn verify_body_digest(request: Request, next: Next) -> Result<Response, AppError> {
+    let Some(expected) = ExpectedDigest::from_headers(request.headers())? else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_VERIFIED_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::BadRequest("Request body could not be read".to_string()))?;
+
+    if !expected.matches(&bytes) {
+        tracing::warn!(
+            method = %parts.method,
+            path = %parts.uri.path(),
+            body_bytes = bytes.len(),
+            "Request body does not match its digest"
+        );
+        return Err(AppError::BadRequest(
+            "Request body does not match its digest".to_string(),
+        ));
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
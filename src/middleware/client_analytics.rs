@@ -0,0 +1,276 @@
+use crate::config::ClientAnalyticsConfig;
+use crate::metrics::AppMetrics;
+use crate::models::format;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header::USER_AGENT,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// Client family of requests without a `User-Agent`
+pub const UNKNOWN_CLIENT: &str = "unknown";
+/// Client family of `Mozilla/...` user agents
+pub const BROWSER_CLIENT: &str = "browser";
+/// Client family of user agents matching no configured family, and of new
+/// combinations once the store is full
+pub const OTHER_CLIENT: &str = "other";
+
+/// Longest version kept; anything after it is cut off
+const MAX_VERSION_LEN: usize = 32;
+
+/// Who a request says it came from, per its `User-Agent`
+///
+/// Added to the request extensions by [`client_analytics_middleware`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientInfo {
+    /// One of the configured families, `browser`, `other` or `unknown`
+    pub family: String,
+    /// Only known for configured families
+    pub version: Option<String>,
+}
+
+impl ClientInfo {
+    /// Match the product tokens of `user_agent` against `families`
+    ///
+    /// Every token is tried, so an app that prepends its own
+    /// (`acme-app/3.0 reprime-js/2.1.0`) is still attributed to the SDK.
+    /// Families are matched case-insensitively and reported as configured.
+    pub fn parse(user_agent: Option<&str>, families: &[String]) -> Self {
+        let Some(user_agent) = user_agent.map(str::trim).filter(|ua| !ua.is_empty()) else {
+            return Self::family(UNKNOWN_CLIENT);
+        };
+
+        let products = without_comments(user_agent);
+        let tokens: Vec<(&str, Option<&str>)> = products
+            .split_whitespace()
+            .map(|token| match token.split_once('/') {
+                Some((name, version)) => (name, Some(version)),
+                None => (token, None),
+            })
+            .collect();
+
+        for (name, version) in &tokens {
+            if let Some(family) = families.iter().find(|f| f.eq_ignore_ascii_case(name)) {
+                return Self {
+                    family: family.clone(),
+                    version: version.and_then(clean_version),
+                };
+            }
+        }
+
+        match tokens.first() {
+            Some((name, _)) if name.eq_ignore_ascii_case("Mozilla") => Self::family(BROWSER_CLIENT),
+            _ => Self::family(OTHER_CLIENT),
+        }
+    }
+
+    fn family(family: &str) -> Self {
+        Self {
+            family: family.to_string(),
+            version: None,
+        }
+    }
+}
+
+/// `User-Agent` with its parenthesized comments removed
+fn without_comments(user_agent: &str) -> String {
+    let mut depth = 0usize;
+    user_agent
+        .chars()
+        .filter(|c| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+fn clean_version(version: &str) -> Option<String> {
+    let version: String = version
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'))
+        .take(MAX_VERSION_LEN)
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    client: ClientInfo,
+    method: String,
+    route: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClientCounter {
+    calls: u64,
+    last_seen_at: DateTime<Utc>,
+}
+
+/// Calls to one route from one client version
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClientUsageEntry {
+    #[schema(example = "reprime-js")]
+    pub client_family: String,
+    #[schema(example = "2.1.0")]
+    pub client_version: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub calls: u64,
+    #[serde(with = "format::timestamp")]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// In-memory call counts per (client family, version, route, method)
+///
+/// Like [`crate::middleware::ApiUsage`], counts reset on restart; they show
+/// which client versions still call what before an API changes.
+pub struct ClientAnalytics {
+    counters: Mutex<HashMap<ClientKey, ClientCounter>>,
+    max_entries: usize,
+    families: Vec<String>,
+    metrics: Option<AppMetrics>,
+}
+
+impl ClientAnalytics {
+    pub fn new(config: &ClientAnalyticsConfig) -> Self {
+        Self {
+            counters: Mutex::new(HashMap::new()),
+            max_entries: config.max_entries.max(1),
+            families: config.families.clone(),
+            metrics: None,
+        }
+    }
+
+    /// Build the store from config, or `None` when tracking is disabled
+    pub fn from_config(config: &ClientAnalyticsConfig) -> Option<Self> {
+        config.enabled.then(|| Self::new(config))
+    }
+
+    /// Count requests per client family and route group in Prometheus
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn identify(&self, user_agent: Option<&str>) -> ClientInfo {
+        ClientInfo::parse(user_agent, &self.families)
+    }
+
+    pub fn record(&self, client: &ClientInfo, method: &str, route: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_client_request(&client.family, route);
+        }
+
+        let mut counters = self.counters.lock().unwrap();
+
+        let mut key = ClientKey {
+            client: client.clone(),
+            method: method.to_string(),
+            route: route.to_string(),
+        };
+        if !counters.contains_key(&key) && counters.len() >= self.max_entries {
+            key.client = ClientInfo::family(OTHER_CLIENT);
+        }
+
+        let now = Utc::now();
+        let counter = counters.entry(key).or_insert(ClientCounter {
+            calls: 0,
+            last_seen_at: now,
+        });
+        counter.calls += 1;
+        counter.last_seen_at = now;
+    }
+
+    /// All tracked usage grouped by client family, newest version first
+    pub fn snapshot(&self) -> Vec<ClientUsageEntry> {
+        let counters = self.counters.lock().unwrap();
+
+        let mut entries: Vec<ClientUsageEntry> = counters
+            .iter()
+            .map(|(key, counter)| ClientUsageEntry {
+                client_family: key.client.family.clone(),
+                client_version: key.client.version.clone(),
+                method: key.method.clone(),
+                route: key.route.clone(),
+                calls: counter.calls,
+                last_seen_at: counter.last_seen_at,
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            a.client_family
+                .cmp(&b.client_family)
+                .then_with(|| compare_versions(b.client_version.as_deref(), a.client_version.as_deref()))
+                .then_with(|| b.calls.cmp(&a.calls))
+                .then_with(|| a.route.cmp(&b.route))
+                .then_with(|| a.method.cmp(&b.method))
+        });
+        entries
+    }
+}
+
+/// Dotted versions compared part by part, numerically where both parts are
+/// numbers; a missing version sorts before any version
+fn compare_versions(a: Option<&str>, b: Option<&str>) -> std::cmp::Ordering {
+    let (Some(a), Some(b)) = (a, b) else {
+        return a.is_some().cmp(&b.is_some());
+    };
+
+    let mut a_parts = a.split(['.', '-', '+']);
+    let mut b_parts = b.split(['.', '-', '+']);
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+            (x, y) => return x.is_some().cmp(&y.is_some()),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// Middleware that identifies the client from its `User-Agent` and counts
+/// its calls per route
+pub async fn client_analytics_middleware(
+    State(analytics): State<Arc<ClientAnalytics>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let client = analytics.identify(
+        request
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok()),
+    );
+    request.extensions_mut().insert(client.clone());
+
+    // Unmatched paths would let callers grow the store without bound
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+    let method = request.method().to_string();
+
+    let response = next.run(request).await;
+    analytics.record(&client, &method, &route);
+    response
+}
@@ -0,0 +1,208 @@
+use crate::auth::authenticator::API_KEY_HEADER;
+use crate::config::CoalesceConfig;
+use crate::errors::AppError;
+use crate::metrics::AppMetrics;
+use crate::models::format::RESPONSE_FORMAT_HEADER;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Version},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::future::{BoxFuture, FutureExt, Shared, WeakShared};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Request headers a response may depend on; requests coalesce only when all
+/// of them match, so one caller is never served another's response
+///
+/// Credentials are among them since coalescing runs before authentication;
+/// the mTLS subject header is added with
+/// [`RequestCoalescer::with_vary_header`].
+const VARY_HEADERS: [&str; 8] = [
+    "authorization",
+    "cookie",
+    API_KEY_HEADER,
+    "accept",
+    "accept-language",
+    "if-none-match",
+    "if-modified-since",
+    RESPONSE_FORMAT_HEADER,
+];
+
+type Execution = BoxFuture<'static, Result<Arc<BufferedResponse>, String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CoalesceKey {
+    uri: String,
+    /// Every value of each vary header, in order
+    headers: Vec<Vec<HeaderValue>>,
+}
+
+impl CoalesceKey {
+    fn new(request: &Request, vary_headers: &[HeaderName]) -> Self {
+        Self {
+            uri: request.uri().to_string(),
+            headers: vary_headers
+                .iter()
+                .map(|name| request.headers().get_all(name).iter().cloned().collect())
+                .collect(),
+        }
+    }
+}
+
+struct BufferedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl BufferedResponse {
+    async fn read(response: Response, limit: usize) -> Result<Self, String> {
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, limit)
+            .await
+            .map_err(|e| format!("Failed to buffer coalesced response: {}", e))?;
+
+        Ok(Self {
+            status: parts.status,
+            version: parts.version,
+            headers: parts.headers,
+            body,
+        })
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.version_mut() = self.version;
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+/// Lets identical concurrent GETs to expensive routes share one execution
+///
+/// The first request runs the handler; others with the same URL and
+/// [`VARY_HEADERS`] (plus any added) that arrive before it finishes wait
+/// for its response and get a copy. Nothing is cached: the next request after it completes
+/// runs the handler again. Response extensions are not copied.
+pub struct RequestCoalescer {
+    routes: HashSet<String>,
+    vary_headers: Vec<HeaderName>,
+    max_body_bytes: usize,
+    in_flight: Mutex<HashMap<CoalesceKey, WeakShared<Execution>>>,
+    metrics: Option<AppMetrics>,
+}
+
+impl RequestCoalescer {
+    pub fn new(config: &CoalesceConfig) -> Self {
+        Self {
+            routes: config.routes.iter().cloned().collect(),
+            vary_headers: VARY_HEADERS.iter().map(|name| HeaderName::from_static(name)).collect(),
+            max_body_bytes: config.max_body_bytes,
+            in_flight: Mutex::new(HashMap::new()),
+            metrics: None,
+        }
+    }
+
+    /// Build from config, or `None` when disabled or no route is listed
+    pub fn from_config(config: &CoalesceConfig) -> Option<Self> {
+        if !config.enabled || config.routes.is_empty() {
+            return None;
+        }
+
+        Some(Self::new(config))
+    }
+
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Also keep apart requests that differ in `name`, e.g. a header an
+    /// authenticator reads credentials from
+    pub fn with_vary_header(mut self, name: &str) -> anyhow::Result<Self> {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| anyhow::anyhow!("Invalid header name '{}': {}", name, e))?;
+        if !self.vary_headers.contains(&name) {
+            self.vary_headers.push(name);
+        }
+        Ok(self)
+    }
+
+    /// Requests currently being executed on behalf of one or more callers
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|execution| execution.upgrade().is_some())
+            .count()
+    }
+
+    /// The in-flight execution for `key`, or a new one running `request`
+    fn join_or_start(
+        self: &Arc<Self>,
+        key: CoalesceKey,
+        request: Request,
+        next: Next,
+    ) -> (Shared<Execution>, bool) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        // An execution whose callers all went away is dropped, not resumed
+        if let Some(execution) = in_flight.get(&key).and_then(WeakShared::upgrade) {
+            return (execution, true);
+        }
+
+        in_flight.retain(|_, execution| execution.upgrade().is_some());
+        let execution = self.clone().execute(key.clone(), request, next).boxed().shared();
+        if let Some(weak) = execution.downgrade() {
+            in_flight.insert(key, weak);
+        }
+        (execution, false)
+    }
+
+    async fn execute(
+        self: Arc<Self>,
+        key: CoalesceKey,
+        request: Request,
+        next: Next,
+    ) -> Result<Arc<BufferedResponse>, String> {
+        let response = next.run(request).await;
+        let buffered = BufferedResponse::read(response, self.max_body_bytes).await;
+        self.in_flight.lock().unwrap().remove(&key);
+        buffered.map(Arc::new)
+    }
+}
+
+/// Middleware that coalesces GETs to the routes in [`RequestCoalescer`]
+pub async fn coalesce_middleware(
+    State(coalescer): State<Arc<RequestCoalescer>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str())
+        .filter(|route| request.method() == Method::GET && coalescer.routes.contains(*route))
+        .map(str::to_string);
+    let Some(route) = route else {
+        return next.run(request).await;
+    };
+
+    let key = CoalesceKey::new(&request, &coalescer.vary_headers);
+    let (execution, joined) = coalescer.join_or_start(key, request, next);
+    if joined {
+        if let Some(metrics) = &coalescer.metrics {
+            metrics.record_coalesced_request(&route);
+        }
+    }
+
+    match execution.await {
+        Ok(response) => response.to_response(),
+        Err(e) => AppError::Internal(e).into_response(),
+    }
+}
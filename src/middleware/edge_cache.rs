@@ -0,0 +1,200 @@
+use crate::auth::authenticator::API_KEY_HEADER;
+use crate::config::{EdgeCacheConfig, MetricsConfig};
+use crate::metrics::RouteGroups;
+use anyhow::Context;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const SURROGATE_CONTROL: HeaderName = HeaderName::from_static("surrogate-control");
+
+/// Request headers that carry credentials; a request with any of them gets
+/// a private response. The mTLS subject header is added with
+/// [`EdgeCache::with_credential_header`].
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "cookie", API_KEY_HEADER];
+
+#[derive(Debug, Clone)]
+struct Policy {
+    cache_control: Option<HeaderValue>,
+    surrogate_control: Option<HeaderValue>,
+}
+
+/// Sets `Cache-Control` / `Surrogate-Control` per route group so a CDN can
+/// cache public endpoints but never a response meant for one caller
+pub struct EdgeCache {
+    groups: RouteGroups,
+    policies: HashMap<String, Policy>,
+    default_cache_control: HeaderValue,
+    authenticated_cache_control: HeaderValue,
+    credential_headers: Vec<HeaderName>,
+}
+
+impl EdgeCache {
+    /// Policies are keyed by the group names in `[metrics.route_groups]`;
+    /// every configured value must be a valid header value
+    pub fn new(config: &EdgeCacheConfig, metrics: &MetricsConfig) -> anyhow::Result<Self> {
+        let header = |value: &str, what: &str| {
+            HeaderValue::from_str(value).with_context(|| format!("Invalid {}: '{}'", what, value))
+        };
+
+        let policies = config
+            .route_groups
+            .iter()
+            .map(|(group, policy)| {
+                let policy = Policy {
+                    cache_control: policy
+                        .cache_control
+                        .as_deref()
+                        .map(|value| header(value, "cache_control"))
+                        .transpose()?,
+                    surrogate_control: policy
+                        .surrogate_control
+                        .as_deref()
+                        .map(|value| header(value, "surrogate_control"))
+                        .transpose()?,
+                };
+                Ok((group.clone(), policy))
+            })
+            .collect::<anyhow::Result<_>>()
+            .context("Invalid edge cache route group policy")?;
+
+        Ok(Self {
+            groups: RouteGroups::from_config(&metrics.route_groups),
+            policies,
+            default_cache_control: header(&config.default_cache_control, "default_cache_control")?,
+            authenticated_cache_control: header(
+                &config.authenticated_cache_control,
+                "authenticated_cache_control",
+            )?,
+            credential_headers: CREDENTIAL_HEADERS
+                .iter()
+                .map(|name| HeaderName::from_static(name))
+                .collect(),
+        })
+    }
+
+    /// Build from config, or `None` when disabled
+    pub fn from_config(config: &EdgeCacheConfig, metrics: &MetricsConfig) -> anyhow::Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::new(config, metrics)?))
+    }
+
+    /// Also treat requests carrying `name` as authenticated, e.g. a header
+    /// an authenticator reads credentials from
+    pub fn with_credential_header(mut self, name: &str) -> anyhow::Result<Self> {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| anyhow::anyhow!("Invalid header name '{}': {}", name, e))?;
+        if !self.credential_headers.contains(&name) {
+            self.credential_headers.push(name);
+        }
+        Ok(self)
+    }
+
+    /// The credential headers `headers` carries; empty for an anonymous request
+    pub fn credentials(&self, headers: &HeaderMap) -> Vec<HeaderName> {
+        self.credential_headers
+            .iter()
+            .filter(|name| headers.contains_key(*name))
+            .cloned()
+            .collect()
+    }
+
+    /// Set the caching headers for a request that carried `credentials`
+    /// (see [`Self::credentials`])
+    pub fn apply_headers(
+        &self,
+        method: &Method,
+        route: Option<&str>,
+        credentials: &[HeaderName],
+        response: &mut Response,
+    ) {
+        let cacheable = (method == Method::GET || method == Method::HEAD)
+            && response.status().is_success();
+        let headers = response.headers_mut();
+
+        if !credentials.is_empty() {
+            // A surrogate header set upstream would let the CDN cache this anyway
+            headers.remove(SURROGATE_CONTROL);
+            for name in credentials {
+                if !vary_includes(headers, name.as_str()) {
+                    if let Ok(value) = HeaderValue::from_str(&vary_field(name)) {
+                        headers.append(header::VARY, value);
+                    }
+                }
+            }
+            if !headers.contains_key(header::CACHE_CONTROL) {
+                headers.insert(header::CACHE_CONTROL, self.authenticated_cache_control.clone());
+            }
+            return;
+        }
+
+        let policy = route
+            .filter(|_| cacheable)
+            .and_then(|route| self.policies.get(self.groups.resolve(route)));
+        if let Some(surrogate_control) = policy.and_then(|p| p.surrogate_control.as_ref()) {
+            if !headers.contains_key(SURROGATE_CONTROL) {
+                headers.insert(SURROGATE_CONTROL, surrogate_control.clone());
+            }
+        }
+        if !headers.contains_key(header::CACHE_CONTROL) {
+            let cache_control = policy
+                .and_then(|p| p.cache_control.as_ref())
+                .unwrap_or(&self.default_cache_control);
+            headers.insert(header::CACHE_CONTROL, cache_control.clone());
+        }
+    }
+}
+
+/// `x-api-key` as `X-Api-Key`; header names are case-insensitive, this is
+/// only how `Vary` conventionally spells them
+fn vary_field(name: &HeaderName) -> String {
+    name.as_str()
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn vary_includes(headers: &HeaderMap, name: &str) -> bool {
+    headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|field| {
+            let field = field.trim();
+            field == "*" || field.eq_ignore_ascii_case(name)
+        })
+}
+
+/// Middleware that adds caching headers according to [`EdgeCache`]
+pub async fn edge_cache_middleware(
+    State(edge_cache): State<Arc<EdgeCache>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string());
+    let credentials = edge_cache.credentials(request.headers());
+
+    let mut response = next.run(request).await;
+    edge_cache.apply_headers(&method, route.as_deref(), &credentials, &mut response);
+    response
+}
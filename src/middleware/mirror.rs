@@ -0,0 +1,173 @@
+use crate::auth::authenticator::API_KEY_HEADER;
+use crate::client::HttpClient;
+use crate::config::MirrorConfig;
+use crate::errors::AppError;
+use crate::metrics::MetricsRegistry;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Headers that must not be forwarded to the shadow environment: hop-by-hop
+/// headers, and credentials, which the shadow has no business seeing
+const SKIPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "upgrade",
+    "te",
+    "trailer",
+    "proxy-authorization",
+    "authorization",
+    "cookie",
+    API_KEY_HEADER,
+];
+
+/// Routes whose bodies carry passwords, tokens or reset codes; never mirrored
+const SKIPPED_PATH_PREFIXES: &[&str] = &["/api/v1/auth/"];
+
+/// Mirrors a sample of incoming requests to a shadow environment
+pub struct TrafficMirror {
+    client: HttpClient,
+    sample_percent: f64,
+    max_body_bytes: usize,
+    in_flight: Arc<Semaphore>,
+    skipped_headers: Vec<HeaderName>,
+}
+
+impl TrafficMirror {
+    pub fn new(config: &MirrorConfig, metrics: &MetricsRegistry) -> anyhow::Result<Self> {
+        let client = HttpClient::builder()
+            .dependency("mirror")
+            .metrics(metrics)
+            .base_url(config.target_url.clone())
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+
+        Ok(Self {
+            client,
+            sample_percent: config.sample_percent.clamp(0.0, 100.0),
+            max_body_bytes: config.max_body_bytes,
+            in_flight: Arc::new(Semaphore::new(config.max_in_flight.max(1))),
+            skipped_headers: SKIPPED_HEADERS
+                .iter()
+                .map(|name| HeaderName::from_static(name))
+                .collect(),
+        })
+    }
+
+    /// Build the mirror from config, or `None` when mirroring is disabled
+    pub fn from_config(
+        config: &MirrorConfig,
+        metrics: &MetricsRegistry,
+    ) -> anyhow::Result<Option<Self>> {
+        if !config.enabled || config.target_url.is_empty() {
+            return Ok(None);
+        }
+
+        tracing::info!(
+            target_url = %config.target_url,
+            sample_percent = config.sample_percent,
+            "Traffic mirroring enabled"
+        );
+
+        Ok(Some(Self::new(config, metrics)?))
+    }
+
+    /// Also keep `name` from the shadow, e.g. a header an authenticator
+    /// reads credentials from
+    pub fn with_skipped_header(mut self, name: &str) -> anyhow::Result<Self> {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| anyhow::anyhow!("Invalid header name '{}': {}", name, e))?;
+        if !self.skipped_headers.contains(&name) {
+            self.skipped_headers.push(name);
+        }
+        Ok(self)
+    }
+
+    fn should_sample(&self, path: &str) -> bool {
+        self.sample_percent > 0.0
+            && !SKIPPED_PATH_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+            && rand::random::<f64>() * 100.0 < self.sample_percent
+    }
+
+    /// Whether the body can be buffered without exceeding the cap
+    fn body_within_cap(&self, method: &Method, headers: &HeaderMap) -> bool {
+        match headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok())
+        {
+            Some(length) => length <= self.max_body_bytes,
+            // Without a length only body-less methods are safe to buffer
+            None => matches!(*method, Method::GET | Method::HEAD | Method::DELETE | Method::OPTIONS),
+        }
+    }
+
+    fn forward(&self, method: Method, path_and_query: String, headers: HeaderMap, body: Vec<u8>) {
+        let Ok(permit) = self.in_flight.clone().try_acquire_owned() else {
+            tracing::debug!("Traffic mirror saturated, dropping mirrored request");
+            return;
+        };
+
+        let mut forwarded = reqwest::header::HeaderMap::new();
+        for (name, value) in headers.iter() {
+            if !self.skipped_headers.contains(name) {
+                forwarded.append(name.clone(), value.clone());
+            }
+        }
+        forwarded.insert(
+            "x-mirrored-from",
+            reqwest::header::HeaderValue::from_static("reprime-backend"),
+        );
+
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            // Responses from the shadow environment are intentionally ignored
+            if let Err(e) = client.send_raw(method, &path_and_query, forwarded, body).await {
+                tracing::debug!(error = %e, path = %path_and_query, "Mirrored request failed");
+            }
+        });
+    }
+}
+
+/// Middleware that asynchronously mirrors sampled requests to the shadow environment
+pub async fn traffic_mirror_middleware(
+    State(mirror): State<Arc<TrafficMirror>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !mirror.should_sample(request.uri().path())
+        || !mirror.body_within_cap(request.method(), request.headers())
+    {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, mirror.max_body_bytes).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to buffer request body");
+            return AppError::BadRequest("Invalid request body".to_string()).into_response();
+        }
+    };
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str().to_string())
+        .unwrap_or_else(|| parts.uri.path().to_string());
+
+    mirror.forward(parts.method.clone(), path_and_query, parts.headers.clone(), bytes.to_vec());
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
@@ -1,9 +1,31 @@
+pub mod api_usage;
+pub mod bandwidth;
+pub mod body_digest;
+pub mod client_analytics;
+pub mod coalesce;
 pub mod cors;
+pub mod edge_cache;
 pub mod logging;
+pub mod mirror;
 pub mod prometheus;
+pub mod request_context;
+pub mod request_cost;
+pub mod response_format;
+pub mod service_notice;
 pub mod timeout;
 
+pub use api_usage::{api_usage_middleware, ApiUsage};
+pub use bandwidth::{BandwidthLayer, BandwidthThrottle};
+pub use body_digest::verify_body_digest;
+pub use client_analytics::{client_analytics_middleware, ClientAnalytics};
+pub use coalesce::{coalesce_middleware, RequestCoalescer};
 pub use cors::cors_layer;
+pub use edge_cache::{edge_cache_middleware, EdgeCache};
 pub use logging::logging_layer;
+pub use mirror::{traffic_mirror_middleware, TrafficMirror};
 pub use prometheus::prometheus_middleware;
+pub use request_context::request_context_middleware;
+pub use request_cost::request_cost_middleware;
+pub use response_format::response_format_middleware;
+pub use service_notice::service_notice_middleware;
 pub use timeout::timeout_layer;
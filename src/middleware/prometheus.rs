@@ -1,6 +1,7 @@
+use crate::auth::models::AuthContext;
 use crate::metrics::AppMetrics;
 use axum::{
-    extract::{Request, State},
+    extract::{MatchedPath, Request, State},
     middleware::Next,
     response::Response,
 };
@@ -15,6 +16,11 @@ pub async fn prometheus_middleware(
     let start = Instant::now();
     let method = request.method().to_string();
     let path = request.uri().path().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
 
     // Increment in-flight requests
     metrics.http_requests_in_flight
@@ -34,6 +40,12 @@ pub async fn prometheus_middleware(
     let status_code = response.status().as_u16();
 
     metrics.record_http_request(&method, &path, status_code, duration);
+    metrics.record_route_group_request(&route, status_code);
+
+    // The auth middleware copies the caller onto the response
+    if let Some(auth_context) = response.extensions().get::<AuthContext>() {
+        metrics.record_tenant_request(auth_context.tenant(), &route, status_code);
+    }
 
     response
 }
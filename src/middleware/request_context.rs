@@ -0,0 +1,27 @@
+use crate::config::RequestContextConfig;
+use crate::request_context::{RequestContext, REQUEST_ID_HEADER};
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Middleware that builds each request's [`RequestContext`], makes it
+/// available to handlers and services, and returns the request id
+pub async fn request_context_middleware(
+    State(config): State<Arc<RequestContextConfig>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let context = RequestContext::from_headers(request.headers(), &config);
+    let request_id = HeaderValue::from_str(&context.request_id).ok();
+    request.extensions_mut().insert(context.clone());
+
+    let mut response = context.scope(next.run(request)).await;
+    if let Some(request_id) = request_id {
+        response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+    }
+    response
+}
@@ -0,0 +1,52 @@
+use crate::auth::models::AuthContext;
+use crate::metrics::AppMetrics;
+use crate::request_cost::{RequestCost, REQUEST_COST_MESSAGE};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+/// Middleware that tallies each request's backend calls, then logs them and
+/// records them per route template
+pub async fn request_cost_middleware(
+    State(metrics): State<AppMetrics>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string());
+
+    let (response, cost) = RequestCost::scope(next.run(request)).await;
+
+    // Unmatched paths would make the route label unbounded
+    let Some(route) = route else {
+        return response;
+    };
+
+    metrics.record_request_cost(&method, &route, &cost);
+    if let Some(auth_context) = response.extensions().get::<AuthContext>() {
+        metrics.record_tenant_cost(auth_context.tenant(), &cost);
+    }
+
+    if cost != Default::default() {
+        tracing::info!(
+            method = %method,
+            route = %route,
+            status = response.status().as_u16(),
+            db_queries = cost.db_queries,
+            db_time_ms = cost.db_time.as_secs_f64() * 1000.0,
+            openfga_calls = cost.openfga_calls,
+            cache_hits = cost.cache_hits,
+            cache_misses = cost.cache_misses,
+            http_calls = cost.http_calls,
+            "{}",
+            REQUEST_COST_MESSAGE
+        );
+    }
+
+    response
+}
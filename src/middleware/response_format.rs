@@ -0,0 +1,38 @@
+use crate::errors::AppError;
+use crate::models::format::{ResponseFormat, RESPONSE_FORMAT_HEADER};
+use axum::{
+    extract::Request,
+    http::{header::VARY, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// Middleware that applies the `X-Response-Format` header to the response
+///
+/// The parsed format is also left in the request extensions, so the auth
+/// middleware knows not to replace it with a personal access token's.
+pub async fn response_format_middleware(mut request: Request, next: Next) -> Result<Response, AppError> {
+    let format = match request.headers().get(RESPONSE_FORMAT_HEADER) {
+        Some(value) => {
+            let value = value.to_str().map_err(|_| {
+                AppError::BadRequest("Malformed X-Response-Format header".to_string())
+            })?;
+            Some(ResponseFormat::parse(value)?)
+        }
+        None => None,
+    };
+
+    let mut response = match format {
+        Some(format) => {
+            request.extensions_mut().insert(format);
+            format.scope(next.run(request)).await
+        }
+        None => next.run(request).await,
+    };
+
+    // Shared caches must not hand one format's body to a request for another
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static(RESPONSE_FORMAT_HEADER));
+    Ok(response)
+}
@@ -0,0 +1,24 @@
+use crate::services::banner::{BannerService, SERVICE_NOTICE_HEADER};
+use axum::{
+    extract::{Request, State},
+    http::HeaderName,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Middleware that attaches the current banner to every response as
+/// `X-Service-Notice: <severity>; <message>`
+pub async fn service_notice_middleware(
+    State(banner): State<Arc<BannerService>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    if let Some(notice) = banner.notice_header() {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(SERVICE_NOTICE_HEADER), notice);
+    }
+    response
+}
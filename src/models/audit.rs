@@ -0,0 +1,169 @@
+use crate::models::format::{self, ResponseFormat};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Actions written to the audit log
+pub mod audit_actions {
+    pub const LOGGED_OUT: &str = "auth.logged_out";
+    pub const PASSWORD_RESET: &str = "auth.password_reset";
+    pub const SESSION_REVOKED: &str = "auth.session_revoked";
+    pub const SESSIONS_REVOKED_ALL: &str = "auth.sessions_revoked_all";
+    pub const NEW_DEVICE_SIGN_IN: &str = "auth.new_device_sign_in";
+    pub const DEVICE_CONFIRMED: &str = "auth.device_confirmed";
+    pub const TENANT_SWITCHED: &str = "auth.tenant_switched";
+    pub const PASSKEY_REGISTERED: &str = "auth.passkey_registered";
+    pub const PASSKEY_DELETED: &str = "auth.passkey_deleted";
+    pub const PERSONAL_ACCESS_TOKEN_CREATED: &str = "auth.personal_access_token_created";
+    pub const PERSONAL_ACCESS_TOKEN_REVOKED: &str = "auth.personal_access_token_revoked";
+    pub const SERVICE_ACCOUNT_CREATED: &str = "auth.service_account_created";
+    pub const SERVICE_ACCOUNT_DISABLED: &str = "auth.service_account_disabled";
+    pub const AUTHORIZATION_MODEL_WRITTEN: &str = "auth.authorization_model_written";
+    pub const ROLE_ADDED: &str = "role.added";
+    pub const ROLE_REMOVED: &str = "role.removed";
+    pub const USER_CREATED: &str = "user.created";
+    pub const USER_UPDATED: &str = "user.updated";
+    pub const USER_DELETED: &str = "user.deleted";
+    pub const USER_AVATAR_UPDATED: &str = "user.avatar_updated";
+    pub const USER_SUSPENDED: &str = "user.suspended";
+    pub const USER_REACTIVATED: &str = "user.reactivated";
+    pub const ORGANIZATION_MEMBER_ADDED: &str = "organization.member_added";
+    pub const ORGANIZATION_MEMBER_UPDATED: &str = "organization.member_updated";
+    pub const ORGANIZATION_MEMBER_REMOVED: &str = "organization.member_removed";
+}
+
+/// Kinds of resource an audited action applies to
+pub mod audit_resources {
+    pub const USER: &str = "user";
+    pub const SESSION: &str = "session";
+    pub const PASSKEY: &str = "passkey";
+    pub const PERSONAL_ACCESS_TOKEN: &str = "personal_access_token";
+    pub const SERVICE_ACCOUNT: &str = "service_account";
+    pub const AUTHORIZATION_MODEL: &str = "authorization_model";
+    pub const ORGANIZATION: &str = "organization";
+}
+
+/// One audit log entry, recorded against the tenant it happened in
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub tenant_id: String,
+    pub actor_id: Option<Uuid>,
+    pub action: &'static str,
+    pub details: Value,
+    pub ip_address: Option<String>,
+    pub resource_type: Option<&'static str>,
+    pub resource_id: Option<String>,
+    /// The resource before the action; `None` when it was created
+    pub before: Option<Value>,
+    /// The resource after the action; `None` when it was deleted
+    pub after: Option<Value>,
+    pub trace_id: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(tenant_id: impl Into<String>, actor_id: Option<Uuid>, action: &'static str) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            actor_id,
+            action,
+            details: Value::Object(Default::default()),
+            ip_address: None,
+            resource_type: None,
+            resource_id: None,
+            before: None,
+            after: None,
+            trace_id: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    pub fn with_ip_address(mut self, ip_address: Option<String>) -> Self {
+        self.ip_address = ip_address;
+        self
+    }
+
+    pub fn with_resource(mut self, resource_type: &'static str, resource_id: impl ToString) -> Self {
+        self.resource_type = Some(resource_type);
+        self.resource_id = Some(resource_id.to_string());
+        self
+    }
+
+    /// Record the resource's state around the action; either side may be
+    /// absent, for creations and deletions. Stored in the default format,
+    /// whatever the caller asked its responses in
+    pub fn with_change<B: Serialize, A: Serialize>(mut self, before: Option<&B>, after: Option<&A>) -> Self {
+        self.before = before.and_then(snapshot);
+        self.after = after.and_then(snapshot);
+        self
+    }
+
+    pub fn with_trace_id(mut self, trace_id: Option<String>) -> Self {
+        self.trace_id = trace_id;
+        self
+    }
+}
+
+fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+    ResponseFormat::default().sync_scope(|| serde_json::to_value(value).ok())
+}
+
+/// A stored audit log entry
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    #[schema(example = "default")]
+    pub tenant_id: String,
+    /// Who acted; absent for unauthenticated actions such as a password reset
+    #[serde(with = "format::option_id")]
+    pub actor_id: Option<Uuid>,
+    #[schema(example = "user.updated")]
+    pub action: String,
+    #[schema(example = "user")]
+    pub resource_type: Option<String>,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub resource_id: Option<String>,
+    #[schema(value_type = Object)]
+    pub details: Value,
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+    #[schema(example = "203.0.113.7")]
+    pub ip_address: Option<String>,
+    #[schema(example = "4bf92f3577b34da6a3ce929d0e0e4736")]
+    pub trace_id: Option<String>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Narrows an audit log query; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+}
+
+/// Position after the last entry of a page, newest first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AuditCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// A page of audit log entries, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditPage {
+    pub events: Vec<AuditEntry>,
+    /// Pass as `cursor` for the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
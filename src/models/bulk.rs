@@ -0,0 +1,153 @@
+use crate::errors::{AppError, Result};
+use crate::models::{format, roles};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Most users one bulk request may name
+pub const MAX_BULK_USERS: usize = 500;
+
+/// What a bulk operation does when some users can't be processed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Apply to every user or to none; one failure fails the request
+    #[default]
+    AllOrNothing,
+    /// Apply to every user it can and report the rest
+    BestEffort,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BulkDeleteUsersRequest {
+    #[serde(with = "format::id_list")]
+    #[schema(value_type = Vec<String>)]
+    pub user_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub mode: BulkMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkRoleAction {
+    Add,
+    Remove,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BulkUserRolesRequest {
+    #[serde(with = "format::id_list")]
+    #[schema(value_type = Vec<String>)]
+    pub user_ids: Vec<Uuid>,
+    pub action: BulkRoleAction,
+    #[schema(example = json!(["moderator"]))]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub mode: BulkMode,
+}
+
+/// A user a bulk operation skipped, and why
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkFailure {
+    #[serde(with = "format::id")]
+    pub user_id: Uuid,
+    pub error: String,
+}
+
+/// Outcome of a bulk operation
+///
+/// In `all_or_nothing` mode `failed` stays empty unless a follow-up step
+/// failed after the change itself was committed, or, for deletions, a
+/// database shard failed to commit after another one had.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkResult {
+    pub mode: BulkMode,
+    #[serde(with = "format::id_list")]
+    #[schema(value_type = Vec<String>)]
+    pub succeeded: Vec<Uuid>,
+    pub failed: Vec<BulkFailure>,
+}
+
+impl BulkResult {
+    pub fn new(mode: BulkMode) -> Self {
+        Self {
+            mode,
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    /// Start a result for `mode` given the users that don't exist, which
+    /// fail the whole request in `all_or_nothing` mode
+    pub fn with_missing(mode: BulkMode, missing: &[Uuid]) -> Result<Self> {
+        let mut result = Self::new(mode);
+        if missing.is_empty() {
+            return Ok(result);
+        }
+        if mode == BulkMode::AllOrNothing {
+            return Err(AppError::NotFound(format!(
+                "Users not found: {}",
+                missing
+                    .iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        for user_id in missing {
+            result.fail(*user_id, "User not found");
+        }
+        Ok(result)
+    }
+
+    pub fn fail(&mut self, user_id: Uuid, error: impl ToString) {
+        self.failed.push(BulkFailure {
+            user_id,
+            error: error.to_string(),
+        });
+    }
+}
+
+impl BulkDeleteUsersRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_user_ids(&self.user_ids)
+    }
+}
+
+impl BulkUserRolesRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_user_ids(&self.user_ids)?;
+        if self.roles.is_empty() {
+            return Err(AppError::Validation("roles must not be empty".to_string()));
+        }
+        if let Some(unknown) = self.roles.iter().find(|role| !roles::ALL.contains(&role.as_str())) {
+            return Err(AppError::Validation(format!(
+                "Unknown role '{}'; expected one of {}",
+                unknown,
+                roles::ALL.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn validate_user_ids(user_ids: &[Uuid]) -> Result<()> {
+    if user_ids.is_empty() {
+        return Err(AppError::Validation("user_ids must not be empty".to_string()));
+    }
+    if user_ids.len() > MAX_BULK_USERS {
+        return Err(AppError::Validation(format!(
+            "At most {} users per request",
+            MAX_BULK_USERS
+        )));
+    }
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = user_ids.iter().find(|id| !seen.insert(**id)) {
+        return Err(AppError::Validation(format!(
+            "user_ids lists {} more than once",
+            duplicate
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Request payloads shared by the OpenAPI examples and the test suite.
+//!
+//! Every example shown in Swagger UI comes from here, and `tests/fixture_tests.rs`
+//! checks each one validates (or fails) the way its name says.
+
+use serde_json::{json, Value};
+
+pub const EMAIL: &str = "user@example.com";
+pub const USERNAME: &str = "johndoe";
+pub const PASSWORD: &str = "password123";
+
+pub const INVALID_EMAIL: &str = "not-an-email";
+pub const SHORT_USERNAME: &str = "jo";
+pub const SHORT_PASSWORD: &str = "secret";
+
+/// Shortest accepted username
+pub const MIN_USERNAME: &str = "joe";
+/// Shortest accepted password
+pub const MIN_PASSWORD: &str = "12345678";
+/// Non-ASCII usernames are accepted as-is
+pub const UNICODE_USERNAME: &str = "zoë_ångström";
+
+pub mod register {
+    use super::*;
+
+    pub fn valid() -> Value {
+        json!({ "email": EMAIL, "username": USERNAME, "password": PASSWORD })
+    }
+
+    pub fn min_length() -> Value {
+        json!({ "email": EMAIL, "username": MIN_USERNAME, "password": MIN_PASSWORD })
+    }
+
+    pub fn invalid_email() -> Value {
+        json!({ "email": INVALID_EMAIL, "username": USERNAME, "password": PASSWORD })
+    }
+
+    pub fn short_password() -> Value {
+        json!({ "email": EMAIL, "username": USERNAME, "password": SHORT_PASSWORD })
+    }
+}
+
+pub mod login {
+    use super::*;
+
+    pub fn valid() -> Value {
+        json!({ "email": EMAIL, "password": PASSWORD })
+    }
+}
+
+pub mod create_user {
+    use super::*;
+
+    pub fn valid() -> Value {
+        json!({ "email": EMAIL, "username": USERNAME })
+    }
+
+    pub fn unicode_username() -> Value {
+        json!({ "email": EMAIL, "username": UNICODE_USERNAME })
+    }
+
+    pub fn invalid_email() -> Value {
+        json!({ "email": INVALID_EMAIL, "username": USERNAME })
+    }
+
+    pub fn short_username() -> Value {
+        json!({ "email": EMAIL, "username": SHORT_USERNAME })
+    }
+}
+
+pub mod update_user {
+    use super::*;
+
+    pub fn valid() -> Value {
+        json!({ "email": "newemail@example.com", "username": "newusername" })
+    }
+
+    /// Omitted fields are left unchanged
+    pub fn username_only() -> Value {
+        json!({ "username": "newusername" })
+    }
+
+    pub fn empty_username() -> Value {
+        json!({ "username": "   " })
+    }
+}
@@ -0,0 +1,267 @@
+//! Response formats for timestamps and IDs.
+//!
+//! Responses use RFC 3339 timestamps and hyphenated UUIDs unless the caller
+//! asks for another [`ResponseFormat`], through the `X-Response-Format`
+//! header or its personal access token's setting. The format is held in a
+//! task-local for the duration of the request, and DTO fields opt in with
+//! the serde helpers here:
+//!
+//! ```ignore
+//! #[serde(with = "format::id")]
+//! pub id: Uuid,
+//! #[serde(with = "format::timestamp")]
+//! pub created_at: DateTime<Utc>,
+//! ```
+//!
+//! Deserializing accepts every format, so a value a client got back in one
+//! can be sent in a request body as is. Path parameters are UUIDs only.
+
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::future::Future;
+use uuid::Uuid;
+
+/// Request header selecting a [`ResponseFormat`], e.g. `epoch-millis,base62`
+pub const RESPONSE_FORMAT_HEADER: &str = "x-response-format";
+
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// Characters needed for any 128-bit value; shorter encodings are zero-padded
+const BASE62_LEN: usize = 22;
+
+tokio::task_local! {
+    static RESPONSE_FORMAT: ResponseFormat;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// `2025-01-01T12:00:00Z`
+    #[default]
+    Rfc3339,
+    /// Milliseconds since the Unix epoch, as a number
+    EpochMillis,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdFormat {
+    /// `550e8400-e29b-41d4-a716-446655440000`
+    #[default]
+    Uuid,
+    /// 22 characters of `[0-9A-Za-z]`
+    Base62,
+}
+
+/// How timestamps and IDs are written in a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResponseFormat {
+    pub timestamps: TimestampFormat,
+    pub ids: IdFormat,
+}
+
+impl ResponseFormat {
+    /// Parse a comma-separated list of `epoch-millis`, `rfc3339`, `base62`
+    /// and `uuid`; `standard` is the default format
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut format = Self::default();
+        for option in value.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            match option.to_ascii_lowercase().as_str() {
+                "standard" => {}
+                "rfc3339" => format.timestamps = TimestampFormat::Rfc3339,
+                "epoch-millis" => format.timestamps = TimestampFormat::EpochMillis,
+                "uuid" => format.ids = IdFormat::Uuid,
+                "base62" => format.ids = IdFormat::Base62,
+                other => {
+                    return Err(AppError::Validation(format!(
+                        "Unknown response format option '{}'; expected epoch-millis, rfc3339, base62, uuid or standard",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(format)
+    }
+
+    /// The format of the current request; the default outside one
+    pub fn current() -> Self {
+        RESPONSE_FORMAT.try_with(|format| *format).unwrap_or_default()
+    }
+
+    /// Run `future` with this as the current format
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        RESPONSE_FORMAT.scope(self, future).await
+    }
+
+    /// Run `f` with this as the current format
+    pub fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        RESPONSE_FORMAT.sync_scope(self, f)
+    }
+}
+
+impl fmt::Display for ResponseFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timestamps = match self.timestamps {
+            TimestampFormat::Rfc3339 => "rfc3339",
+            TimestampFormat::EpochMillis => "epoch-millis",
+        };
+        let ids = match self.ids {
+            IdFormat::Uuid => "uuid",
+            IdFormat::Base62 => "base62",
+        };
+        write!(f, "{},{}", timestamps, ids)
+    }
+}
+
+pub fn encode_base62(id: Uuid) -> String {
+    let mut value = id.as_u128();
+    let mut encoded = [b'0'; BASE62_LEN];
+    for slot in encoded.iter_mut().rev() {
+        *slot = BASE62_ALPHABET[(value % 62) as usize];
+        value /= 62;
+    }
+    String::from_utf8(encoded.to_vec()).expect("alphabet is ASCII")
+}
+
+pub fn decode_base62(value: &str) -> Option<Uuid> {
+    if value.is_empty() || value.len() > BASE62_LEN {
+        return None;
+    }
+    let mut decoded: u128 = 0;
+    for byte in value.bytes() {
+        let digit = BASE62_ALPHABET.iter().position(|&c| c == byte)? as u128;
+        decoded = decoded.checked_mul(62)?.checked_add(digit)?;
+    }
+    Some(Uuid::from_u128(decoded))
+}
+
+/// A UUID in either form
+pub fn parse_id(value: &str) -> Option<Uuid> {
+    if value.len() == BASE62_LEN {
+        decode_base62(value)
+    } else {
+        Uuid::parse_str(value).ok()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TimestampRepr {
+    Millis(i64),
+    Text(String),
+}
+
+fn timestamp_from_repr<E: de::Error>(repr: TimestampRepr) -> std::result::Result<DateTime<Utc>, E> {
+    match repr {
+        TimestampRepr::Millis(millis) => DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| E::custom(format!("timestamp {} is out of range", millis))),
+        TimestampRepr::Text(text) => DateTime::parse_from_rfc3339(&text)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .map_err(|e| E::custom(format!("invalid timestamp '{}': {}", text, e))),
+    }
+}
+
+fn id_from_text<E: de::Error>(text: &str) -> std::result::Result<Uuid, E> {
+    parse_id(text).ok_or_else(|| E::custom(format!("invalid ID '{}'", text)))
+}
+
+/// `DateTime<Utc>` in the current [`TimestampFormat`]
+pub mod timestamp {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match ResponseFormat::current().timestamps {
+            TimestampFormat::Rfc3339 => value.serialize(serializer),
+            TimestampFormat::EpochMillis => serializer.serialize_i64(value.timestamp_millis()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<DateTime<Utc>, D::Error> {
+        timestamp_from_repr(TimestampRepr::deserialize(deserializer)?)
+    }
+}
+
+/// `Option<DateTime<Utc>>` in the current [`TimestampFormat`]
+pub mod option_timestamp {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => timestamp::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<TimestampRepr>::deserialize(deserializer)?
+            .map(timestamp_from_repr)
+            .transpose()
+    }
+}
+
+/// `Uuid` in the current [`IdFormat`]
+pub mod id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Uuid, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match ResponseFormat::current().ids {
+            IdFormat::Uuid => value.serialize(serializer),
+            IdFormat::Base62 => serializer.serialize_str(&encode_base62(*value)),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Uuid, D::Error> {
+        id_from_text(&String::deserialize(deserializer)?)
+    }
+}
+
+/// `Option<Uuid>` in the current [`IdFormat`]
+pub mod option_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Option<Uuid>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => id::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Uuid>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| id_from_text(&text))
+            .transpose()
+    }
+}
+
+/// `Vec<Uuid>` in the current [`IdFormat`]
+pub mod id_list {
+    use super::*;
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S: Serializer>(values: &[Uuid], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        struct Id<'a>(&'a Uuid);
+
+        impl Serialize for Id<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                id::serialize(self.0, serializer)
+            }
+        }
+
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&Id(value))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Uuid>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|text| id_from_text(text))
+            .collect()
+    }
+}
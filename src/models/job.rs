@@ -0,0 +1,67 @@
+use crate::models::format;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Kinds of background job
+pub mod job_kinds {
+    /// Delete every OpenFGA tuple a deleted user is the subject of
+    pub const OPENFGA_TUPLE_CLEANUP: &str = "openfga.tuple_cleanup";
+}
+
+crate::pg_enum! {
+    /// Where a job is in its lifecycle, stored as the `job_status` enum
+    pub enum JobStatus: "job_status" {
+        Pending => "pending",
+        Running => "running",
+        Succeeded => "succeeded",
+        Failed => "failed",
+    }
+}
+
+/// A queued or finished background job
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Job {
+    #[serde(with = "format::id")]
+    pub id: Uuid,
+    #[schema(example = "openfga.tuple_cleanup")]
+    pub kind: String,
+    /// While pending or running, another job with the same kind and key
+    /// isn't enqueued
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub dedupe_key: String,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    pub status: JobStatus,
+    /// Reported by the job while it runs
+    #[schema(value_type = Object, example = json!({ "deleted": 42 }))]
+    pub progress: Value,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    /// Earliest time the job is picked up; pushed back between retries
+    #[serde(with = "format::timestamp")]
+    pub run_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub locked_at: Option<DateTime<Utc>>,
+    /// Worker holding the job while it runs
+    #[schema(example = "api-7f9c:3b1e0c52a9d84f6b")]
+    pub lease_owner: Option<String>,
+    /// When another worker may take the job over
+    #[serde(with = "format::option_timestamp")]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(with = "format::option_timestamp")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Payload of an [`job_kinds::OPENFGA_TUPLE_CLEANUP`] job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TupleCleanupPayload {
+    pub user_id: Uuid,
+}
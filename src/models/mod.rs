@@ -1,9 +1,35 @@
+use crate::errors::{AppError, Result};
+use crate::utils::{validate_range, Validate};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+pub mod audit;
+pub mod bulk;
+pub mod fixtures;
+pub mod format;
+pub mod job;
+pub mod organization;
+pub mod tenant;
+pub mod types;
+
+pub use audit::{
+    audit_actions, audit_resources, AuditCursor, AuditEntry, AuditEvent, AuditFilter, AuditPage,
+};
+pub use bulk::{
+    BulkDeleteUsersRequest, BulkFailure, BulkMode, BulkResult, BulkRoleAction, BulkUserRolesRequest,
+    MAX_BULK_USERS,
+};
+pub use job::{job_kinds, Job, JobStatus, TupleCleanupPayload};
+pub use organization::{
+    AddOrganizationMemberRequest, CreateOrganizationRequest, Organization, OrganizationMember,
+    OrganizationRole, UpdateOrganizationMemberRequest, UpdateOrganizationRequest,
+};
+pub use tenant::*;
+pub use types::{Currency, Decimal, Money};
+
 // Re-export auth models
 pub use crate::auth::models::*;
 
@@ -11,12 +37,55 @@ pub use crate::auth::models::*;
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    #[serde(with = "format::id")]
     pub id: Uuid,
     #[schema(example = "user@example.com")]
     pub email: String,
     #[schema(example = "johndoe")]
     pub username: String,
+    /// Object storage key of the avatar, see [`crate::services::storage`]
+    #[serde(default, skip_serializing)]
+    #[schema(ignore)]
+    pub avatar_key: Option<String>,
+    #[serde(default)]
+    pub status: UserStatus,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+crate::pg_enum! {
+    /// Where an account is in its lifecycle, stored as the `user_status` enum
+    ///
+    /// Only active users get past the auth middleware; the others are
+    /// answered with a 403 until reactivated.
+    #[derive(Default)]
+    pub enum UserStatus: "user_status" {
+        #[default]
+        Active => "active",
+        Suspended => "suspended",
+        Banned => "banned",
+    }
+}
+
+/// Denormalized user row used for admin listings
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UserSummary {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    #[serde(with = "format::id")]
+    pub user_id: Uuid,
+    #[schema(example = "user@example.com")]
+    pub email: String,
+    #[schema(example = "johndoe")]
+    pub username: String,
+    #[schema(example = json!(["user"]))]
+    pub roles: Vec<String>,
+    #[serde(with = "format::option_timestamp")]
+    pub last_login_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::timestamp")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "format::timestamp")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -36,24 +105,104 @@ pub struct UpdateUserRequest {
     pub username: Option<String>,
 }
 
+/// Suspend a user, or ban them
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SuspendUserRequest {
+    /// Mark the account banned rather than suspended; either way an admin
+    /// can reactivate it
+    #[serde(default)]
+    pub ban: bool,
+    /// Recorded in the audit log
+    #[schema(example = "Chargeback under review")]
+    pub reason: Option<String>,
+}
+
+impl CreateUserRequest {
+    /// Field checks; uniqueness is checked by the service against the database
+    pub fn validate(&self) -> Result<()> {
+        if self.email.trim().is_empty() {
+            return Err(AppError::Validation("Email is required".to_string()));
+        }
+
+        if self.username.trim().is_empty() {
+            return Err(AppError::Validation("Username is required".to_string()));
+        }
+
+        if !is_valid_email(&self.email) {
+            return Err(AppError::Validation("Invalid email format".to_string()));
+        }
+
+        if self.username.len() < 3 {
+            return Err(AppError::Validation(
+                "Username must be at least 3 characters long".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl UpdateUserRequest {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref email) = self.email {
+            if email.trim().is_empty() {
+                return Err(AppError::Validation("Email cannot be empty".to_string()));
+            }
+
+            if !is_valid_email(email) {
+                return Err(AppError::Validation("Invalid email format".to_string()));
+            }
+        }
+
+        if let Some(ref username) = self.username {
+            if username.trim().is_empty() {
+                return Err(AppError::Validation("Username cannot be empty".to_string()));
+            }
+
+            if username.len() < 3 {
+                return Err(AppError::Validation(
+                    "Username must be at least 3 characters long".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_valid_email(email: &str) -> bool {
+    // Simple email validation - in production, use a proper email validation library
+    email.contains('@') && email.contains('.') && email.len() > 5
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    #[serde(with = "format::id")]
     pub id: Uuid,
     #[schema(example = "user@example.com")]
     pub email: String,
     #[schema(example = "johndoe")]
     pub username: String,
+    /// Signed, expiring link to the avatar; absent when there is none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    pub status: UserStatus,
+    #[serde(with = "format::timestamp")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "format::timestamp")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// Without `avatar_url`; [`crate::services::UserService`] signs it
 impl From<User> for UserResponse {
     fn from(user: User) -> Self {
         Self {
             id: user.id,
             email: user.email,
             username: user.username,
+            avatar_url: None,
+            status: user.status,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -101,28 +250,193 @@ pub struct PaginatedResponse<T> {
     pub page: i64,
     pub per_page: i64,
     pub total_pages: i64,
+    /// Pass as `cursor` for the next page, on listings that support it;
+    /// absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
 pub struct PaginationParams {
     #[param(example = 1, minimum = 1)]
     pub page: Option<i64>,
     #[param(example = 20, minimum = 1, maximum = 100)]
     pub per_page: Option<i64>,
+    /// Column to sort by; which ones are allowed depends on the listing
+    #[param(example = "created_at")]
+    pub sort_by: Option<String>,
+    /// Sort direction, `desc` unless given
+    pub order: Option<SortOrder>,
+    /// `next_cursor` from the previous page, in place of `page`. Seeks
+    /// instead of skipping rows, so deep pages stay fast
+    pub cursor: Option<String>,
+}
+
+/// Position after the last user of a page, for keyset pagination on
+/// `(created_at, id)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UserCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+    /// Number of the page the cursor leads to
+    pub page: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Columns `GET /api/v1/users` can be sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Email,
+    Username,
+}
+
+impl UserSortField {
+    pub const ALL: &'static [UserSortField] = &[
+        UserSortField::CreatedAt,
+        UserSortField::UpdatedAt,
+        UserSortField::Email,
+        UserSortField::Username,
+    ];
+
+    /// Name in `sort_by`, which is also the column name
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::UpdatedAt => "updated_at",
+            UserSortField::Email => "email",
+            UserSortField::Username => "username",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|field| field.as_str() == value)
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "sort_by must be one of {}, got {}",
+                    Self::ALL
+                        .iter()
+                        .map(|field| field.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    value
+                ))
+            })
+    }
+
+    /// Order of two users by this field, ties broken by id
+    pub fn compare(self, a: &User, b: &User) -> std::cmp::Ordering {
+        let ordering = match self {
+            UserSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            UserSortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            UserSortField::Email => a.email.cmp(&b.email),
+            UserSortField::Username => a.username.cmp(&b.username),
+        };
+        ordering.then_with(|| a.id.cmp(&b.id))
+    }
 }
 
 impl PaginationParams {
+    pub const MAX_PER_PAGE: i64 = 100;
+
     pub fn page(&self) -> i64 {
         self.page.unwrap_or(1).max(1)
     }
 
     pub fn per_page(&self) -> i64 {
-        self.per_page.unwrap_or(20).clamp(1, 100)
+        self.per_page.unwrap_or(20).clamp(1, Self::MAX_PER_PAGE)
     }
 
     pub fn offset(&self) -> i64 {
         (self.page() - 1) * self.per_page()
     }
+
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or_default()
+    }
+
+    /// `sort_by` as a user column, `created_at` when unset
+    pub fn user_sort(&self) -> Result<UserSortField> {
+        self.sort_by
+            .as_deref()
+            .map(UserSortField::parse)
+            .unwrap_or(Ok(UserSortField::default()))
+    }
+
+    /// Check a user listing can continue from a cursor: keyset pagination
+    /// only follows `created_at`, and a page number would be ambiguous
+    pub fn validate_user_cursor(&self) -> Result<()> {
+        if self.cursor.is_none() {
+            return Ok(());
+        }
+        if self.page.is_some() {
+            return Err(AppError::Validation(
+                "page and cursor can't be combined".to_string(),
+            ));
+        }
+        if self.user_sort()? != UserSortField::CreatedAt {
+            return Err(AppError::Validation(
+                "cursor pagination only supports sort_by=created_at".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for PaginationParams {
+    fn validate(&self) -> Result<()> {
+        validate_range("page", self.page, 1, i64::MAX / Self::MAX_PER_PAGE)?;
+        validate_range("per_page", self.per_page, 1, Self::MAX_PER_PAGE)
+    }
+}
+
+/// Narrows a user listing; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    /// Substring of the email or username, case-insensitive
+    pub q: Option<String>,
+    /// Created at or after this instant
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created before this instant
+    pub created_before: Option<DateTime<Utc>>,
+    /// Holds at least one of these roles
+    pub roles: Vec<String>,
+    /// In any of these statuses
+    pub statuses: Vec<UserStatus>,
+}
+
+impl UserFilter {
+    /// `q` as an ILIKE pattern, with its own wildcards escaped
+    pub fn search_pattern(&self) -> Option<String> {
+        self.q.as_deref().map(|q| {
+            let escaped = q
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{}%", escaped)
+        })
+    }
 }
 
 #[derive(Debug, Serialize, ToSchema)]
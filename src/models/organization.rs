@@ -0,0 +1,129 @@
+use crate::auth::models::relations;
+use crate::errors::{AppError, Result};
+use crate::models::format;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Longest organization id; ids double as tenant ids
+const MAX_ID_LEN: usize = 64;
+
+crate::pg_enum! {
+    /// A member's role in an organization, stored as the `organization_role` enum
+    pub enum OrganizationRole: "organization_role" {
+        Member => "member",
+        Admin => "admin",
+    }
+}
+
+impl OrganizationRole {
+    /// OpenFGA relations on `organization:{id}` that make up the role;
+    /// admins are members too, so they can switch to the tenant
+    pub fn relations(self) -> &'static [&'static str] {
+        match self {
+            OrganizationRole::Member => &[relations::MEMBER],
+            OrganizationRole::Admin => &[relations::MEMBER, relations::ADMIN],
+        }
+    }
+}
+
+/// An organization; its id is the tenant id tokens carry
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Organization {
+    #[schema(example = "acme")]
+    pub id: String,
+    #[schema(example = "Acme Inc.")]
+    pub name: String,
+    #[serde(with = "format::option_id")]
+    pub created_by: Option<Uuid>,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's membership in an organization
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct OrganizationMember {
+    #[schema(example = "acme")]
+    pub organization_id: String,
+    #[serde(with = "format::id")]
+    pub user_id: Uuid,
+    pub role: OrganizationRole,
+    #[serde(with = "format::timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateOrganizationRequest {
+    /// Lowercase letters, digits and hyphens; can't be changed later
+    #[schema(example = "acme")]
+    pub id: String,
+    #[schema(example = "Acme Inc.")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateOrganizationRequest {
+    #[schema(example = "Acme Corporation")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddOrganizationMemberRequest {
+    #[serde(with = "format::id")]
+    pub user_id: Uuid,
+    /// Defaults to `member`
+    #[serde(default)]
+    pub role: Option<OrganizationRole>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateOrganizationMemberRequest {
+    pub role: OrganizationRole,
+}
+
+impl CreateOrganizationRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_organization_id(&self.id)?;
+        validate_organization_name(&self.name)
+    }
+}
+
+impl UpdateOrganizationRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_organization_name(&self.name)
+    }
+}
+
+/// Ids become OpenFGA object ids and tenant claims, so they are kept to a
+/// URL- and tuple-safe alphabet
+pub fn validate_organization_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !id.starts_with('-')
+        && !id.ends_with('-');
+
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Organization id must be 1-{} lowercase letters, digits or inner hyphens",
+            MAX_ID_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn validate_organization_name(name: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err(AppError::Validation(
+            "Organization name must be 1-255 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::errors::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Every account joins the `default` organization on registration; it is
+/// the tenant for tokens that don't carry a tenant claim
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Tenant-level overrides; unset sections fall back to the service config
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TenantSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding: Option<Branding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_policy: Option<PasswordPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_templates: Option<EmailTemplates>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Branding {
+    #[schema(example = "Acme Inc.")]
+    pub display_name: Option<String>,
+    #[schema(example = "https://cdn.example.com/acme.png")]
+    pub logo_url: Option<String>,
+    #[schema(example = "#1a73e8")]
+    pub primary_color: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PasswordPolicy {
+    /// Can only tighten the built-in minimum of 8
+    #[schema(example = 12, minimum = 8, maximum = 128)]
+    pub min_length: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SessionSettings {
+    /// Lifetime of new sessions (refresh token families)
+    #[schema(example = 7, minimum = 1, maximum = 365)]
+    pub refresh_token_days: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EmailTemplates {
+    pub password_reset: Option<EmailTemplate>,
+}
+
+/// Plain-text template; `{username}`, `{url}` and `{minutes}` are substituted
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EmailTemplate {
+    #[schema(example = "Reset your Acme password")]
+    pub subject: String,
+    #[schema(example = "Hi {username}, reset your password within {minutes} minutes: {url}")]
+    pub text: String,
+}
+
+impl EmailTemplate {
+    pub fn render(&self, username: &str, url: &str, minutes: u64) -> (String, String) {
+        let fill = |template: &str| {
+            template
+                .replace("{username}", username)
+                .replace("{url}", url)
+                .replace("{minutes}", &minutes.to_string())
+        };
+        (fill(&self.subject), fill(&self.text))
+    }
+}
+
+impl TenantSettings {
+    /// Assemble settings from stored `(section, value)` rows
+    pub fn from_entries(entries: Vec<(String, serde_json::Value)>) -> Result<Self> {
+        let document: serde_json::Map<String, serde_json::Value> = entries.into_iter().collect();
+        serde_json::from_value(serde_json::Value::Object(document))
+            .map_err(|e| AppError::Internal(format!("Stored tenant settings are invalid: {}", e)))
+    }
+
+    /// Present sections as `(section, value)` rows
+    pub fn to_entries(&self) -> Result<Vec<(String, serde_json::Value)>> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(document)) => Ok(document.into_iter().collect()),
+            Ok(_) => Err(AppError::Internal("Tenant settings must serialize to an object".to_string())),
+            Err(e) => Err(AppError::Internal(format!("Failed to serialize tenant settings: {}", e))),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(branding) = &self.branding {
+            if let Some(color) = &branding.primary_color {
+                let is_hex = color.len() == 7
+                    && color.starts_with('#')
+                    && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+                if !is_hex {
+                    return Err(AppError::Validation(
+                        "branding.primary_color must be a #rrggbb color".to_string(),
+                    ));
+                }
+            }
+            if let Some(logo_url) = &branding.logo_url {
+                if !logo_url.starts_with("https://") {
+                    return Err(AppError::Validation(
+                        "branding.logo_url must be an https URL".to_string(),
+                    ));
+                }
+            }
+        }
+
+        if let Some(policy) = &self.password_policy {
+            if !(8..=128).contains(&policy.min_length) {
+                return Err(AppError::Validation(
+                    "password_policy.min_length must be between 8 and 128".to_string(),
+                ));
+            }
+        }
+
+        if let Some(session) = &self.session {
+            if !(1..=365).contains(&session.refresh_token_days) {
+                return Err(AppError::Validation(
+                    "session.refresh_token_days must be between 1 and 365".to_string(),
+                ));
+            }
+        }
+
+        if let Some(template) = self
+            .email_templates
+            .as_ref()
+            .and_then(|templates| templates.password_reset.as_ref())
+        {
+            if template.subject.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "email_templates.password_reset.subject is required".to_string(),
+                ));
+            }
+            if !template.text.contains("{url}") {
+                return Err(AppError::Validation(
+                    "email_templates.password_reset.text must include {url}".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
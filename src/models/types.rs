@@ -0,0 +1,170 @@
+//! Shared column types: exact decimals, money and Postgres enums.
+//!
+//! Amounts are `NUMERIC` in the database and strings on the wire, so they
+//! never pass through a float. Enums map to `CREATE TYPE ... AS ENUM` types
+//! declared with [`pg_enum!`], which keeps the Rust variants, the serde
+//! representation and the database labels in one place.
+
+use crate::errors::{AppError, Result};
+use rust_decimal::RoundingStrategy;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+pub use rust_decimal::Decimal;
+
+/// Declare a Rust enum backed by a Postgres enum type
+///
+/// Each variant is given with its database label, which is also its JSON
+/// representation. The type gets `sqlx::Type`, serde, `ToSchema`, `Display`
+/// and `FromStr`, so rows decode with `query_as` / `FromRow` and path or
+/// query parameters parse with `.parse()`.
+///
+/// ```ignore
+/// pg_enum! {
+///     /// How often a plan is billed
+///     pub enum BillingInterval: "billing_interval" {
+///         Monthly => "monthly",
+///         Yearly => "yearly",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! pg_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident : $type_name:literal {
+            $($(#[$variant_meta:meta])* $variant:ident => $label:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, Hash,
+            ::serde::Serialize, ::serde::Deserialize, ::sqlx::Type, ::utoipa::ToSchema,
+        )]
+        #[sqlx(type_name = $type_name)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                #[serde(rename = $label)]
+                #[sqlx(rename = $label)]
+                $variant,
+            )+
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $label,)+
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::errors::AppError;
+
+            fn from_str(value: &str) -> ::std::result::Result<Self, Self::Err> {
+                match value {
+                    $($label => Ok($name::$variant),)+
+                    other => Err($crate::errors::AppError::Validation(format!(
+                        "Invalid {}: '{}'",
+                        $type_name,
+                        other
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+pg_enum! {
+    /// ISO 4217 currency, stored as the `currency_code` enum
+    pub enum Currency: "currency_code" {
+        Usd => "USD",
+        Eur => "EUR",
+        Gbp => "GBP",
+        Jpy => "JPY",
+        Vnd => "VND",
+    }
+}
+
+impl Currency {
+    /// Digits after the decimal point in the smallest unit
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Currency::Jpy | Currency::Vnd => 0,
+            _ => 2,
+        }
+    }
+}
+
+/// An exact amount in a currency
+///
+/// Maps to an `amount money_amount` / `currency currency_code` column pair;
+/// embed it in a row type with `#[sqlx(flatten)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Money {
+    #[schema(value_type = String, example = "19.99")]
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    /// Amount rounded half away from zero to the currency's minor units
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self {
+            amount: amount.round_dp_with_strategy(
+                currency.minor_units(),
+                RoundingStrategy::MidpointAwayFromZero,
+            ),
+            currency,
+        }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self {
+            amount: Decimal::ZERO,
+            currency,
+        }
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money> {
+        self.same_currency(&other)?;
+        self.amount
+            .checked_add(other.amount)
+            .map(|amount| Money::new(amount, self.currency))
+            .ok_or_else(|| AppError::Validation("Amount out of range".to_string()))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money> {
+        self.same_currency(&other)?;
+        self.amount
+            .checked_sub(other.amount)
+            .map(|amount| Money::new(amount, self.currency))
+            .ok_or_else(|| AppError::Validation("Amount out of range".to_string()))
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<()> {
+        if self.currency != other.currency {
+            return Err(AppError::Validation(format!(
+                "Currency mismatch: {} and {}",
+                self.currency, other.currency
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
@@ -0,0 +1,308 @@
+//! The OpenAPI document served at `/api-docs/openapi.json`.
+
+use crate::routes::{AuthPolicy, Route};
+use axum::http::Method;
+use utoipa::{
+    openapi::{
+        extensions::Extensions,
+        path::Operation,
+        security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+    },
+    Modify, OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::handlers::health::health_check,
+        crate::handlers::health::readiness_check,
+        crate::handlers::health::warmup,
+        crate::handlers::status::get_status,
+        crate::handlers::status::set_status_incident,
+        crate::handlers::status::clear_status_incident,
+        crate::handlers::banner::get_banner,
+        crate::handlers::banner::set_banner,
+        crate::handlers::banner::clear_banner,
+        crate::handlers::user::create_user,
+        crate::handlers::user::get_users,
+        crate::handlers::user::list_user_summaries,
+        crate::handlers::user::get_user,
+        crate::handlers::user::update_user,
+        crate::handlers::user::delete_user,
+        crate::handlers::user::upload_avatar,
+        crate::handlers::user::get_avatar_file,
+        crate::handlers::user::bulk_delete_users,
+        crate::handlers::user::bulk_update_user_roles,
+        crate::handlers::user::suspend_user,
+        crate::handlers::user::reactivate_user,
+        crate::auth::handlers::register,
+        crate::auth::handlers::login,
+        crate::auth::handlers::logout,
+        crate::auth::handlers::me,
+        crate::auth::handlers::delete_me,
+        crate::auth::handlers::refresh_token,
+        crate::auth::handlers::check_permission,
+        crate::auth::handlers::list_users,
+        crate::auth::handlers::list_objects,
+        crate::auth::handlers::jwks,
+        crate::auth::handlers::forgot_password,
+        crate::auth::handlers::reset_password,
+        crate::auth::handlers::confirm_device,
+        crate::auth::handlers::start_passkey_registration,
+        crate::auth::handlers::finish_passkey_registration,
+        crate::auth::handlers::start_passkey_login,
+        crate::auth::handlers::finish_passkey_login,
+        crate::auth::handlers::list_sessions,
+        crate::auth::handlers::revoke_session,
+        crate::auth::handlers::list_passkeys,
+        crate::auth::handlers::delete_passkey,
+        crate::auth::handlers::switch_tenant,
+        crate::auth::handlers::create_personal_access_token,
+        crate::auth::handlers::list_personal_access_tokens,
+        crate::auth::handlers::revoke_personal_access_token,
+        crate::auth::handlers::client_credentials_token,
+        crate::auth::handlers::introspect_token,
+        crate::auth::handlers::create_service_account,
+        crate::auth::handlers::list_service_accounts,
+        crate::auth::handlers::disable_service_account,
+        crate::auth::handlers::revoke_all_sessions,
+        crate::handlers::tenant::get_tenant_settings,
+        crate::handlers::tenant::update_tenant_settings,
+        crate::handlers::organization::create_organization,
+        crate::handlers::organization::list_organizations,
+        crate::handlers::organization::list_all_organizations,
+        crate::handlers::organization::get_organization,
+        crate::handlers::organization::update_organization,
+        crate::handlers::organization::delete_organization,
+        crate::handlers::organization::list_organization_members,
+        crate::handlers::organization::add_organization_member,
+        crate::handlers::organization::update_organization_member,
+        crate::handlers::organization::remove_organization_member,
+        crate::handlers::admin::get_log_level,
+        crate::handlers::admin::update_log_level,
+        crate::handlers::admin::get_api_usage,
+        crate::handlers::admin::get_client_analytics,
+        crate::handlers::admin::get_trace_bundle,
+        crate::handlers::admin::get_dependencies,
+        crate::auth::handlers::list_authorization_models,
+        crate::auth::handlers::get_authorization_model,
+        crate::auth::handlers::write_authorization_model,
+        crate::auth::handlers::list_relationships,
+        crate::auth::handlers::get_user_access,
+        crate::handlers::jobs::list_jobs,
+        crate::handlers::jobs::get_job,
+        crate::handlers::audit::list_audit_events,
+    ),
+    components(
+        schemas(
+            crate::models::User,
+            crate::models::UserResponse,
+            crate::models::UserSummary,
+            crate::models::CreateUserRequest,
+            crate::models::UpdateUserRequest,
+            crate::models::UserStatus,
+            crate::models::SuspendUserRequest,
+            crate::models::ApiResponse<crate::models::UserResponse>,
+            crate::models::PaginatedResponse<crate::models::UserResponse>,
+            crate::models::PaginatedResponse<crate::models::UserSummary>,
+            crate::models::PaginationParams,
+            crate::models::SortOrder,
+            crate::models::DeleteResponse,
+            crate::models::BulkMode,
+            crate::models::BulkRoleAction,
+            crate::models::BulkDeleteUsersRequest,
+            crate::models::BulkUserRolesRequest,
+            crate::models::BulkFailure,
+            crate::models::BulkResult,
+            crate::models::ApiResponse<crate::models::BulkResult>,
+            crate::handlers::user::AvatarUploadForm,
+            crate::handlers::HealthResponse,
+            crate::handlers::ReadinessResponse,
+            crate::services::warmup::WarmupReport,
+            crate::services::warmup::WarmupStep,
+            crate::services::health::DependencyHealth,
+            crate::services::status::PublicStatus,
+            crate::services::status::ServiceStatus,
+            crate::services::status::ComponentStatus,
+            crate::services::status::IncidentNote,
+            crate::services::status::SetIncidentRequest,
+            crate::models::ApiResponse<crate::services::status::IncidentNote>,
+            crate::services::banner::BannerSeverity,
+            crate::services::banner::ServiceBanner,
+            crate::services::banner::SetBannerRequest,
+            crate::services::banner::BannerResponse,
+            crate::models::ApiResponse<crate::services::banner::ServiceBanner>,
+            crate::auth::models::LoginRequest,
+            crate::auth::models::LoginResponse,
+            crate::auth::models::RefreshTokenRequest,
+            crate::auth::models::ForgotPasswordRequest,
+            crate::auth::models::ResetPasswordRequest,
+            crate::auth::models::ConfirmDeviceRequest,
+            crate::auth::models::DeleteAccountRequest,
+            crate::auth::webauthn::PasskeyRegistrationStart,
+            crate::auth::webauthn::PasskeyRegistrationFinish,
+            crate::auth::webauthn::PasskeyLoginStartRequest,
+            crate::auth::webauthn::PasskeyLoginStart,
+            crate::auth::webauthn::PasskeyLoginFinish,
+            crate::auth::models::SessionInfo,
+            crate::auth::webauthn::PasskeyInfo,
+            crate::auth::models::CurrentUser,
+            crate::auth::models::SwitchTenantRequest,
+            crate::auth::models::SwitchTenantResponse,
+            crate::auth::models::CreatePersonalAccessTokenRequest,
+            crate::auth::models::PersonalAccessTokenInfo,
+            crate::auth::models::CreatedPersonalAccessToken,
+            crate::auth::models::ClientCredentialsRequest,
+            crate::auth::models::ClientCredentialsResponse,
+            crate::auth::models::IntrospectionRequest,
+            crate::auth::models::IntrospectionResponse,
+            crate::auth::models::TokenSessionStatus,
+            crate::auth::models::Claims,
+            crate::auth::models::CreateServiceAccountRequest,
+            crate::auth::models::ServiceAccountInfo,
+            crate::auth::models::RevokeAllSessionsRequest,
+            crate::auth::models::RevokeAllSessionsResponse,
+            crate::auth::models::CreatedServiceAccount,
+            crate::auth::webauthn::CreationOptions,
+            crate::auth::webauthn::RequestOptions,
+            crate::auth::webauthn::RelyingParty,
+            crate::auth::webauthn::PublicKeyUser,
+            crate::auth::webauthn::PublicKeyCredentialParameters,
+            crate::auth::webauthn::CredentialDescriptor,
+            crate::models::TenantSettings,
+            crate::models::Branding,
+            crate::models::PasswordPolicy,
+            crate::models::SessionSettings,
+            crate::models::EmailTemplates,
+            crate::models::EmailTemplate,
+            crate::models::Organization,
+            crate::models::OrganizationMember,
+            crate::models::OrganizationRole,
+            crate::models::CreateOrganizationRequest,
+            crate::models::UpdateOrganizationRequest,
+            crate::models::AddOrganizationMemberRequest,
+            crate::models::UpdateOrganizationMemberRequest,
+            crate::models::PaginatedResponse<crate::models::Organization>,
+            crate::handlers::admin::LogSettings,
+            crate::handlers::admin::UpdateLogSettingsRequest,
+            crate::handlers::admin::DependenciesResponse,
+            crate::dependencies::DependencyReport,
+            crate::dependencies::LatencySummary,
+            crate::dependencies::ErrorSample,
+            crate::middleware::api_usage::ApiUsageEntry,
+            crate::middleware::client_analytics::ClientUsageEntry,
+            crate::live_tail::TraceBundle,
+            crate::live_tail::LogEvent,
+            crate::live_tail::SpanTiming,
+            crate::auth::openfga::AuthorizationModel,
+            crate::auth::openfga::AuthorizationModelPage,
+            crate::auth::openfga::WriteAuthorizationModelResponse,
+            crate::auth::openfga::RelationshipTuple,
+            crate::auth::openfga::TuplePage,
+            crate::auth::openfga::ObjectUsers,
+            crate::auth::openfga::ObjectPage,
+            crate::auth::models::UserAccessReport,
+            crate::auth::models::ObjectAccess,
+            crate::auth::models::RelationAccess,
+            crate::auth::models::AccessSource,
+            crate::models::Job,
+            crate::models::JobStatus,
+            crate::models::AuditEntry,
+            crate::models::AuditPage,
+            crate::config::StatementRecording,
+            crate::auth::models::RegisterRequest,
+            crate::auth::models::UserInfo,
+            crate::auth::models::PermissionCheck,
+            crate::models::ApiResponse<crate::auth::models::LoginResponse>,
+            crate::models::ApiResponse<crate::auth::models::UserInfo>,
+            crate::models::ApiResponse<bool>,
+        )
+    ),
+    tags(
+        (name = "health", description = "Health check endpoints"),
+        (name = "users", description = "User management endpoints"),
+        (name = "authentication", description = "Authentication and authorization endpoints"),
+        (name = "tenant", description = "Tenant branding and settings endpoints"),
+        (name = "organizations", description = "Organization and membership endpoints"),
+        (name = "admin", description = "Internal administration endpoints"),
+    ),
+    info(
+        title = "Reprime Backend API",
+        version = "0.1.0",
+        description = "A modern Rust backend API with OpenAPI documentation and ABAC authorization using OpenFGA",
+        contact(
+            name = "API Support",
+            email = "support@reprime.com"
+        )
+    ),
+    modifiers(&SecurityAddon, &RoutePolicies)
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            )
+        }
+    }
+}
+
+/// Sets each operation's security from the route registry, so the document
+/// can't claim a route is public when the router guards it (or vice versa),
+/// and records the role or scope it needs as `x-required-role` /
+/// `x-required-scope`
+struct RoutePolicies;
+
+impl Modify for RoutePolicies {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for route in Route::ALL.iter().filter(|route| route.documented()) {
+            let Some(operation) = operation_mut(openapi, route.path(), &route.method()) else {
+                continue;
+            };
+
+            let policy = route.policy();
+            operation.security = policy
+                .requires_authentication()
+                .then(|| vec![SecurityRequirement::new("bearer_auth", Vec::<String>::new())]);
+
+            let requirement = match policy {
+                AuthPolicy::Role(role) => Some(("x-required-role", role)),
+                AuthPolicy::Scope(scope) => Some(("x-required-scope", scope)),
+                AuthPolicy::Service => Some(("x-required-subject", "service")),
+                AuthPolicy::Public | AuthPolicy::Authenticated => None,
+            };
+            if let Some((key, value)) = requirement {
+                operation
+                    .extensions
+                    .get_or_insert_with(Extensions::default)
+                    .insert(key.to_string(), value.into());
+            }
+        }
+    }
+}
+
+fn operation_mut<'a>(
+    openapi: &'a mut utoipa::openapi::OpenApi,
+    path: &str,
+    method: &Method,
+) -> Option<&'a mut Operation> {
+    let item = openapi.paths.paths.get_mut(path)?;
+    match *method {
+        Method::GET => item.get.as_mut(),
+        Method::POST => item.post.as_mut(),
+        Method::PUT => item.put.as_mut(),
+        Method::DELETE => item.delete.as_mut(),
+        Method::PATCH => item.patch.as_mut(),
+        _ => None,
+    }
+}
@@ -0,0 +1,103 @@
+use crate::database::{InstrumentedDatabase, ShardRouter};
+use crate::errors::{AppError, Result};
+use crate::models::{AuditCursor, AuditEntry, AuditEvent, AuditFilter};
+use crate::utils::id;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Audit log rows, placed on the tenant's shard
+#[derive(Clone)]
+pub struct AuditRepository {
+    shards: Arc<ShardRouter>,
+}
+
+impl AuditRepository {
+    pub fn new(shards: Arc<ShardRouter>) -> Self {
+        Self { shards }
+    }
+
+    fn db(&self, tenant_id: &str) -> &Arc<InstrumentedDatabase> {
+        self.shards.for_tenant(tenant_id)
+    }
+
+    /// Append an entry to the audit log
+    pub async fn record(&self, event: &AuditEvent) -> Result<()> {
+        let query = r#"
+            INSERT INTO audit_log
+                (id, tenant_id, actor_id, action, details, ip_address,
+                 resource_type, resource_id, before, after, trace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#;
+
+        sqlx::query(query)
+            .bind(id::generate())
+            .bind(&event.tenant_id)
+            .bind(event.actor_id)
+            .bind(event.action)
+            .bind(&event.details)
+            .bind(&event.ip_address)
+            .bind(event.resource_type)
+            .bind(&event.resource_id)
+            .bind(&event.before)
+            .bind(&event.after)
+            .bind(&event.trace_id)
+            .execute(self.db(&event.tenant_id).pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// A tenant's entries matching `filter`, newest first, starting after
+    /// `after`
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        filter: &AuditFilter,
+        after: Option<AuditCursor>,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>> {
+        let query = r#"
+            SELECT id, tenant_id, actor_id, action, resource_type, resource_id,
+                   details, before, after, ip_address, trace_id, created_at
+            FROM audit_log
+            WHERE tenant_id = $1
+              AND ($2::uuid IS NULL OR actor_id = $2)
+              AND ($3::text IS NULL OR action = $3)
+              AND ($4::text IS NULL OR resource_type = $4)
+              AND ($5::text IS NULL OR resource_id = $5)
+              AND ($6::timestamptz IS NULL OR (created_at, id) < ($6, $7))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $8
+        "#;
+
+        sqlx::query_as::<_, AuditEntry>(query)
+            .bind(tenant_id)
+            .bind(filter.actor_id)
+            .bind(&filter.action)
+            .bind(&filter.resource_type)
+            .bind(&filter.resource_id)
+            .bind(after.map(|cursor| cursor.created_at))
+            .bind(after.map(|cursor| cursor.id))
+            .bind(limit)
+            .fetch_all(self.db(tenant_id).pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Delete entries older than `cutoff` on every shard, returning how many
+    /// were removed
+    pub async fn delete_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let mut deleted = 0;
+        for db in self.shards.all() {
+            let result = sqlx::query("DELETE FROM audit_log WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(db.pool())
+                .await
+                .map_err(AppError::Database)?;
+            deleted += result.rows_affected();
+        }
+
+        Ok(deleted)
+    }
+}
@@ -1,6 +1,12 @@
-use crate::auth::models::{UserCredentials, UserRole};
+use crate::auth::models::{
+    PersonalAccessToken, RefreshRotation, RefreshToken, ServiceAccount, SessionMetadata,
+    UserCredentials, UserRole, UserSession, UserTokenState,
+};
+use crate::auth::webauthn::{VerifiedRegistration, WebAuthnChallenge, WebAuthnCredential};
 use crate::database::InstrumentedDatabase;
-use crate::errors::{AppError, Result};
+use crate::errors::{unique_violation, AppError, ConflictError, Result};
+use crate::models::DEFAULT_TENANT;
+use crate::utils::id;
 use sqlx::Row;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -32,7 +38,13 @@ impl AuthRepository {
             .bind(&password_hash)
             .fetch_one(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(|e| match unique_violation(&e) {
+                Some(_) => AppError::Conflict(ConflictError {
+                    message: "User already has a password".to_string(),
+                    field: None,
+                }),
+                None => AppError::Database(e),
+            })?;
 
         Ok(UserCredentials {
             id: row.get("id"),
@@ -55,7 +67,7 @@ impl AuthRepository {
             .bind(user_id)
             .fetch_optional(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(row.map(|r| UserCredentials {
             id: r.get("id"),
@@ -79,7 +91,7 @@ impl AuthRepository {
             .bind(&password_hash)
             .execute(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound("User credentials not found".to_string()));
@@ -88,12 +100,13 @@ impl AuthRepository {
         Ok(())
     }
 
-    /// Add role to user
+    /// Add role to user; adding one the user already has (e.g. twice at
+    /// once) returns the existing row
     pub async fn add_role(&self, user_id: Uuid, role: String) -> Result<UserRole> {
         let query = r#"
             INSERT INTO user_roles (user_id, role)
             VALUES ($1, $2)
-            ON CONFLICT (user_id, role) DO NOTHING
+            ON CONFLICT (user_id, role) DO UPDATE SET role = EXCLUDED.role
             RETURNING id, user_id, role, created_at
         "#;
 
@@ -102,7 +115,7 @@ impl AuthRepository {
             .bind(&role)
             .fetch_one(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(UserRole {
             id: row.get("id"),
@@ -124,7 +137,7 @@ impl AuthRepository {
             .bind(&role)
             .execute(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound("User role not found".to_string()));
@@ -133,6 +146,42 @@ impl AuthRepository {
         Ok(())
     }
 
+    /// Add or remove `roles` for every user in `user_ids`, in one transaction
+    ///
+    /// Roles a user already has (or, when removing, doesn't have) are left
+    /// alone.
+    pub async fn update_roles_many(
+        &self,
+        user_ids: &[Uuid],
+        roles: &[String],
+        add: bool,
+    ) -> Result<()> {
+        let query = if add {
+            r#"
+                INSERT INTO user_roles (user_id, role)
+                SELECT user_id, role
+                FROM UNNEST($1::uuid[]) AS user_id CROSS JOIN UNNEST($2::text[]) AS role
+                ON CONFLICT (user_id, role) DO NOTHING
+            "#
+        } else {
+            r#"
+                DELETE FROM user_roles
+                WHERE user_id = ANY($1) AND role = ANY($2)
+            "#
+        };
+
+        let mut tx = self.db.pool().begin().await.map_err(AppError::Database)?;
+        sqlx::query(query)
+            .bind(user_ids)
+            .bind(roles)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
     /// Get user roles
     pub async fn get_user_roles(&self, user_id: Uuid) -> Result<Vec<String>> {
         let query = r#"
@@ -146,7 +195,7 @@ impl AuthRepository {
             .bind(user_id)
             .fetch_all(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(rows.into_iter().map(|row| row.get("role")).collect())
     }
@@ -165,69 +214,952 @@ impl AuthRepository {
             .bind(role)
             .fetch_one(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(exists)
     }
 
-    /// Store session token hash
+    /// Create a server-side session record
+    ///
+    /// A `remember_me` session starts out expiring at `expires_at` and is
+    /// extended on use up to `absolute_expires_at`; others end at
+    /// `expires_at`, which both columns then hold.
     pub async fn create_session(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
-        token_hash: String,
+        metadata: &SessionMetadata,
         expires_at: chrono::DateTime<chrono::Utc>,
-    ) -> Result<Uuid> {
+        remember_me: bool,
+        absolute_expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<UserSession> {
         let query = r#"
-            INSERT INTO user_sessions (user_id, token_hash, expires_at)
-            VALUES ($1, $2, $3)
-            RETURNING id
+            INSERT INTO user_sessions (id, user_id, device, ip_address, scope, expires_at, remember_me, absolute_expires_at,
+                                       device_fingerprint)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, user_id, device, ip_address, scope, expires_at, created_at, last_used_at, revoked_at,
+                   remember_me, absolute_expires_at, device_fingerprint, verified
         "#;
 
-        let session_id: Uuid = sqlx::query_scalar(query)
+        let session = sqlx::query_as::<_, UserSession>(query)
+            .bind(session_id)
             .bind(user_id)
-            .bind(&token_hash)
+            .bind(&metadata.device)
+            .bind(&metadata.ip_address)
+            .bind(&metadata.scope)
             .bind(expires_at)
+            .bind(remember_me)
+            .bind(absolute_expires_at)
+            .bind(metadata.device_fingerprint())
             .fetch_one(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
+
+        Ok(session)
+    }
+
+    /// Whether the user has sessions, and whether one of them came from
+    /// `device_fingerprint`; expired sessions count until they're cleaned up
+    pub async fn has_seen_device(&self, user_id: Uuid, device_fingerprint: &str) -> Result<(bool, bool)> {
+        let query = r#"
+            SELECT COUNT(*) > 0, COUNT(*) FILTER (WHERE device_fingerprint = $2) > 0
+            FROM user_sessions
+            WHERE user_id = $1
+        "#;
+
+        sqlx::query_as(query)
+            .bind(user_id)
+            .bind(device_fingerprint)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Leave a session unverified until the hashed token is presented
+    pub async fn set_session_unverified(&self, session_id: Uuid, token_hash: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE user_sessions SET verified = FALSE, verification_token_hash = $2 WHERE id = $1",
+        )
+        .bind(session_id)
+        .bind(token_hash)
+        .execute(self.db.pool())
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Verify the live session the hashed token was issued for, returning it
+    pub async fn verify_session(&self, token_hash: &str) -> Result<Option<UserSession>> {
+        let query = r#"
+            UPDATE user_sessions
+            SET verified = TRUE, verification_token_hash = NULL
+            WHERE verification_token_hash = $1
+            AND revoked_at IS NULL
+            AND expires_at > NOW()
+            RETURNING id, user_id, device, ip_address, scope, expires_at, created_at, last_used_at, revoked_at,
+                   remember_me, absolute_expires_at, device_fingerprint, verified
+        "#;
+
+        sqlx::query_as::<_, UserSession>(query)
+            .bind(token_hash)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
 
-        Ok(session_id)
+    /// Get a session by ID
+    pub async fn get_session(&self, session_id: Uuid) -> Result<Option<UserSession>> {
+        let query = r#"
+            SELECT id, user_id, device, ip_address, scope, expires_at, created_at, last_used_at, revoked_at,
+                   remember_me, absolute_expires_at, device_fingerprint, verified
+            FROM user_sessions
+            WHERE id = $1
+        "#;
+
+        let session = sqlx::query_as::<_, UserSession>(query)
+            .bind(session_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(session)
+    }
+
+    /// List active (unexpired, unrevoked) sessions for a user
+    pub async fn list_active_sessions(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+        let query = r#"
+            SELECT id, user_id, device, ip_address, scope, expires_at, created_at, last_used_at, revoked_at,
+                   remember_me, absolute_expires_at, device_fingerprint, verified
+            FROM user_sessions
+            WHERE user_id = $1
+            AND expires_at > NOW()
+            AND revoked_at IS NULL
+            ORDER BY created_at DESC
+        "#;
+
+        let sessions = sqlx::query_as::<_, UserSession>(query)
+            .bind(user_id)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(sessions)
     }
 
     /// Check if session is valid
-    pub async fn is_session_valid(&self, token_hash: &str) -> Result<bool> {
+    ///
+    /// A valid `remember_me` session counts as used: it now expires
+    /// `idle_timeout` from now, or at its absolute expiry if that's sooner.
+    pub async fn is_session_valid(&self, session_id: Uuid, idle_timeout: chrono::Duration) -> Result<bool> {
+        // The update doesn't affect what the SELECT sees, which is the
+        // session as it was before this use
         let query = r#"
+            WITH extended AS (
+                UPDATE user_sessions
+                SET expires_at = LEAST(NOW() + $2 * INTERVAL '1 second', absolute_expires_at),
+                    last_used_at = NOW()
+                WHERE id = $1
+                AND remember_me
+                AND expires_at > NOW()
+                AND revoked_at IS NULL
+                RETURNING id
+            )
             SELECT EXISTS(
                 SELECT 1 FROM user_sessions
-                WHERE token_hash = $1 
-                AND expires_at > NOW() 
+                WHERE id = $1
+                AND expires_at > NOW()
                 AND revoked_at IS NULL
             )
         "#;
 
         let is_valid: bool = sqlx::query_scalar(query)
-            .bind(token_hash)
+            .bind(session_id)
+            .bind(idle_timeout.num_seconds() as f64)
             .fetch_one(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(is_valid)
     }
 
     /// Revoke session
-    pub async fn revoke_session(&self, token_hash: &str) -> Result<()> {
+    pub async fn revoke_session(&self, session_id: Uuid) -> Result<()> {
+        let query = r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE id = $1 AND revoked_at IS NULL
+        "#;
+
+        sqlx::query(query)
+            .bind(session_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Revoke one of a user's sessions; false when it isn't theirs or is already revoked
+    pub async fn revoke_user_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+        let query = r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(session_id)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the tenant an active session acts in
+    pub async fn set_session_scope(&self, user_id: Uuid, session_id: Uuid, scope: &str) -> Result<bool> {
+        let query = r#"
+            UPDATE user_sessions
+            SET scope = $3
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(session_id)
+            .bind(user_id)
+            .bind(scope)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke all active sessions of a user
+    pub async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<u64> {
+        let query = r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Revoke every active session, or only those acting in `tenant` or
+    /// created before `created_before`
+    ///
+    /// Sessions that never switched tenant act in the default tenant.
+    pub async fn revoke_sessions(
+        &self,
+        tenant: Option<&str>,
+        created_before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<u64> {
         let query = r#"
             UPDATE user_sessions
             SET revoked_at = NOW()
+            WHERE revoked_at IS NULL
+            AND ($1::TEXT IS NULL OR COALESCE(scope, $3) = $1)
+            AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(tenant)
+            .bind(created_before)
+            .bind(DEFAULT_TENANT)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Version that access tokens must carry to be accepted
+    pub async fn token_version(&self) -> Result<i64> {
+        sqlx::query_scalar("SELECT version FROM token_version")
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Invalidate every access token issued so far, returning the new version
+    pub async fn bump_token_version(&self) -> Result<i64> {
+        let query = r#"
+            UPDATE token_version
+            SET version = version + 1, updated_at = NOW()
+            RETURNING version
+        "#;
+
+        sqlx::query_scalar(query)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// The version `user_id`'s access tokens must carry to be accepted, and
+    /// when the user's roles last changed
+    pub async fn user_token_state(&self, user_id: Uuid) -> Result<UserTokenState> {
+        let query = r#"
+            SELECT token_version, roles_updated_at
+            FROM user_token_versions
+            WHERE user_id = $1
+        "#;
+
+        let state = sqlx::query_as::<_, UserTokenState>(query)
+            .bind(user_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(state.unwrap_or_default())
+    }
+
+    /// Invalidate every access token issued to `user_id` so far, returning
+    /// the new state
+    pub async fn bump_user_token_version(&self, user_id: Uuid) -> Result<UserTokenState> {
+        let query = r#"
+            INSERT INTO user_token_versions (user_id, token_version)
+            VALUES ($1, 1)
+            ON CONFLICT (user_id) DO UPDATE
+            SET token_version = user_token_versions.token_version + 1, updated_at = NOW()
+            RETURNING token_version, roles_updated_at
+        "#;
+
+        sqlx::query_as::<_, UserTokenState>(query)
+            .bind(user_id)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Record that `user_id`'s roles changed, returning the new state
+    pub async fn touch_user_roles(&self, user_id: Uuid) -> Result<UserTokenState> {
+        let query = r#"
+            INSERT INTO user_token_versions (user_id, roles_updated_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (user_id) DO UPDATE
+            SET roles_updated_at = NOW(), updated_at = NOW()
+            RETURNING token_version, roles_updated_at
+        "#;
+
+        sqlx::query_as::<_, UserTokenState>(query)
+            .bind(user_id)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Store a refresh token for a session
+    pub async fn create_refresh_token(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<RefreshToken> {
+        let query = r#"
+            INSERT INTO refresh_tokens (id, session_id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, session_id, user_id, token_hash, expires_at, created_at, used_at
+        "#;
+
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(id::generate())
+            .bind(session_id)
+            .bind(user_id)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(token)
+    }
+
+    /// Exchange a refresh token for a new one in the same session.
+    ///
+    /// Tokens are single-use: presenting one that was already rotated means it
+    /// leaked, so the whole session (token family) is revoked. Rotating counts
+    /// as using the session, extending a `remember_me` one by `idle_timeout`.
+    pub async fn rotate_refresh_token(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        idle_timeout: chrono::Duration,
+    ) -> Result<RefreshRotation> {
+        let mut tx = self.db.pool().begin().await.map_err(AppError::Database)?;
+
+        let token = sqlx::query_as::<_, RefreshToken>(
+            r#"
+            SELECT id, session_id, user_id, token_hash, expires_at, created_at, used_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        let Some(token) = token else {
+            return Ok(RefreshRotation::Invalid);
+        };
+
+        if token.used_at.is_some() {
+            sqlx::query("UPDATE user_sessions SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+                .bind(token.session_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+
+            tx.commit().await.map_err(AppError::Database)?;
+
+            return Ok(RefreshRotation::Reused {
+                session_id: token.session_id,
+                user_id: token.user_id,
+            });
+        }
+
+        let session_valid: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM user_sessions
+                WHERE id = $1
+                AND expires_at > NOW()
+                AND revoked_at IS NULL
+            )
+            "#,
+        )
+        .bind(token.session_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        if !session_valid || token.expires_at <= chrono::Utc::now() {
+            return Ok(RefreshRotation::Invalid);
+        }
+
+        sqlx::query("UPDATE refresh_tokens SET used_at = NOW() WHERE id = $1")
+            .bind(token.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        // The replacement keeps the family's absolute expiry
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, session_id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(id::generate())
+        .bind(token.session_id)
+        .bind(token.user_id)
+        .bind(new_token_hash)
+        .bind(token.expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            UPDATE user_sessions
+            SET last_used_at = NOW(),
+                expires_at = CASE
+                    WHEN remember_me THEN LEAST(NOW() + $2 * INTERVAL '1 second', absolute_expires_at)
+                    ELSE expires_at
+                END
+            WHERE id = $1
+            "#,
+        )
+        .bind(token.session_id)
+        .bind(idle_timeout.num_seconds() as f64)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(RefreshRotation::Rotated {
+            session_id: token.session_id,
+            user_id: token.user_id,
+            expires_at: token.expires_at,
+        })
+    }
+
+    /// Store a password reset token, invalidating any outstanding ones for the user
+    pub async fn create_password_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        let mut tx = self.db.pool().begin().await.map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = NOW()
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(id::generate())
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Mark a password reset token as used and set the new password in one
+    /// transaction, returning the token's user if it was valid
+    ///
+    /// A failed password update leaves the token unused, so the user can
+    /// try again with the same link.
+    pub async fn reset_password_with_token(
+        &self,
+        token_hash: &str,
+        password_hash: String,
+    ) -> Result<Option<Uuid>> {
+        let mut tx = self.db.pool().begin().await.map_err(AppError::Database)?;
+
+        let user_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = NOW()
             WHERE token_hash = $1
+            AND used_at IS NULL
+            AND expires_at > NOW()
+            RETURNING user_id
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        let Some(user_id) = user_id else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE user_credentials
+            SET password_hash = $2, updated_at = NOW()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(&password_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("User credentials not found".to_string()));
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(Some(user_id))
+    }
+
+    /// Store a WebAuthn ceremony challenge
+    pub async fn create_webauthn_challenge(
+        &self,
+        user_id: Option<Uuid>,
+        challenge: &str,
+        ceremony: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Uuid> {
+        let query = r#"
+            INSERT INTO webauthn_challenges (id, user_id, challenge, ceremony, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+        "#;
+
+        let challenge_id: Uuid = sqlx::query_scalar(query)
+            .bind(id::generate())
+            .bind(user_id)
+            .bind(challenge)
+            .bind(ceremony)
+            .bind(expires_at)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(challenge_id)
+    }
+
+    /// Consume an unexpired challenge; each challenge can be used once
+    pub async fn take_webauthn_challenge(
+        &self,
+        id: Uuid,
+        ceremony: &str,
+    ) -> Result<Option<WebAuthnChallenge>> {
+        let query = r#"
+            DELETE FROM webauthn_challenges
+            WHERE id = $1 AND ceremony = $2 AND expires_at > NOW()
+            RETURNING id, user_id, challenge, ceremony, expires_at
+        "#;
+
+        let challenge = sqlx::query_as::<_, WebAuthnChallenge>(query)
+            .bind(id)
+            .bind(ceremony)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(challenge)
+    }
+
+    /// Store a verified passkey
+    pub async fn create_webauthn_credential(
+        &self,
+        user_id: Uuid,
+        registration: &VerifiedRegistration,
+        algorithm: i32,
+        name: Option<&str>,
+    ) -> Result<WebAuthnCredential> {
+        let query = r#"
+            INSERT INTO webauthn_credentials (id, user_id, credential_id, public_key, algorithm, sign_count, name)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, credential_id, public_key, algorithm, sign_count, name, created_at, last_used_at
+        "#;
+
+        let credential = sqlx::query_as::<_, WebAuthnCredential>(query)
+            .bind(id::generate())
+            .bind(user_id)
+            .bind(&registration.credential_id)
+            .bind(&registration.public_key)
+            .bind(algorithm)
+            .bind(registration.sign_count as i64)
+            .bind(name)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(credential)
+    }
+
+    /// List a user's passkeys
+    pub async fn list_webauthn_credentials(&self, user_id: Uuid) -> Result<Vec<WebAuthnCredential>> {
+        let query = r#"
+            SELECT id, user_id, credential_id, public_key, algorithm, sign_count, name, created_at, last_used_at
+            FROM webauthn_credentials
+            WHERE user_id = $1
+            ORDER BY created_at
+        "#;
+
+        let credentials = sqlx::query_as::<_, WebAuthnCredential>(query)
+            .bind(user_id)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(credentials)
+    }
+
+    /// Find a passkey by its authenticator-assigned credential ID
+    pub async fn find_webauthn_credential(&self, credential_id: &[u8]) -> Result<Option<WebAuthnCredential>> {
+        let query = r#"
+            SELECT id, user_id, credential_id, public_key, algorithm, sign_count, name, created_at, last_used_at
+            FROM webauthn_credentials
+            WHERE credential_id = $1
+        "#;
+
+        let credential = sqlx::query_as::<_, WebAuthnCredential>(query)
+            .bind(credential_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(credential)
+    }
+
+    /// Record a successful passkey login
+    pub async fn update_webauthn_sign_count(&self, id: Uuid, sign_count: u32) -> Result<()> {
+        let query = r#"
+            UPDATE webauthn_credentials
+            SET sign_count = $2, last_used_at = NOW()
+            WHERE id = $1
         "#;
 
         sqlx::query(query)
+            .bind(id)
+            .bind(sign_count as i64)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Delete one of a user's passkeys
+    pub async fn delete_webauthn_credential(&self, user_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webauthn_credentials WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store a personal access token by its hash
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_personal_access_token(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        roles: &[String],
+        scopes: Option<&[String]>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+        response_format: Option<&str>,
+    ) -> Result<PersonalAccessToken> {
+        let query = r#"
+            INSERT INTO personal_access_tokens (id, user_id, name, token_hash, token_prefix, roles, scopes, expires_at, response_format)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, user_id, name, token_prefix, roles, scopes, expires_at, created_at, last_used_at, last_used_ip, revoked_at, response_format
+        "#;
+
+        let token = sqlx::query_as::<_, PersonalAccessToken>(query)
+            .bind(id::generate())
+            .bind(user_id)
+            .bind(name)
+            .bind(token_hash)
+            .bind(token_prefix)
+            .bind(roles)
+            .bind(scopes)
+            .bind(expires_at)
+            .bind(response_format)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(token)
+    }
+
+    /// List a user's personal access tokens that haven't been revoked, newest first
+    pub async fn list_personal_access_tokens(&self, user_id: Uuid) -> Result<Vec<PersonalAccessToken>> {
+        let query = r#"
+            SELECT id, user_id, name, token_prefix, roles, scopes, expires_at, created_at, last_used_at, last_used_ip, revoked_at, response_format
+            FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+        "#;
+
+        let tokens = sqlx::query_as::<_, PersonalAccessToken>(query)
+            .bind(user_id)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(tokens)
+    }
+
+    /// Look up a usable personal access token and record the use
+    ///
+    /// Returns `None` for unknown, revoked and expired tokens.
+    pub async fn use_personal_access_token(
+        &self,
+        token_hash: &str,
+        ip_address: Option<&str>,
+    ) -> Result<Option<PersonalAccessToken>> {
+        let query = r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = NOW(), last_used_ip = COALESCE($2, last_used_ip)
+            WHERE token_hash = $1
+            AND revoked_at IS NULL
+            AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING id, user_id, name, token_prefix, roles, scopes, expires_at, created_at, last_used_at, last_used_ip, revoked_at, response_format
+        "#;
+
+        let token = sqlx::query_as::<_, PersonalAccessToken>(query)
             .bind(token_hash)
+            .bind(ip_address)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(token)
+    }
+
+    /// Revoke one of a user's personal access tokens
+    pub async fn revoke_personal_access_token(&self, user_id: Uuid, id: Uuid) -> Result<bool> {
+        let query = r#"
+            UPDATE personal_access_tokens
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .bind(user_id)
             .execute(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store a service account by its secret's hash
+    pub async fn create_service_account(
+        &self,
+        name: &str,
+        secret_hash: &str,
+        secret_prefix: &str,
+        roles: &[String],
+        created_by: Uuid,
+    ) -> Result<ServiceAccount> {
+        let query = r#"
+            INSERT INTO service_accounts (id, name, secret_hash, secret_prefix, roles, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, secret_prefix, roles, created_by, created_at, last_used_at, disabled_at
+        "#;
+
+        let account = sqlx::query_as::<_, ServiceAccount>(query)
+            .bind(id::generate())
+            .bind(name)
+            .bind(secret_hash)
+            .bind(secret_prefix)
+            .bind(roles)
+            .bind(created_by)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(account)
+    }
+
+    /// List service accounts, disabled ones included, newest first
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccount>> {
+        let query = r#"
+            SELECT id, name, secret_prefix, roles, created_by, created_at, last_used_at, disabled_at
+            FROM service_accounts
+            ORDER BY created_at DESC
+        "#;
+
+        let accounts = sqlx::query_as::<_, ServiceAccount>(query)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(accounts)
+    }
+
+    /// Look up an enabled service account by id and secret hash and record the use
+    ///
+    /// Returns `None` for unknown and disabled accounts and wrong secrets.
+    pub async fn use_service_account(&self, id: Uuid, secret_hash: &str) -> Result<Option<ServiceAccount>> {
+        let query = r#"
+            UPDATE service_accounts
+            SET last_used_at = NOW()
+            WHERE id = $1 AND secret_hash = $2 AND disabled_at IS NULL
+            RETURNING id, name, secret_prefix, roles, created_by, created_at, last_used_at, disabled_at
+        "#;
+
+        let account = sqlx::query_as::<_, ServiceAccount>(query)
+            .bind(id)
+            .bind(secret_hash)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(account)
+    }
+
+    /// Look up an enabled service account by id and record the use, for
+    /// callers identified by something other than the secret (a client
+    /// certificate)
+    pub async fn use_service_account_by_id(&self, id: Uuid) -> Result<Option<ServiceAccount>> {
+        let query = r#"
+            UPDATE service_accounts
+            SET last_used_at = NOW()
+            WHERE id = $1 AND disabled_at IS NULL
+            RETURNING id, name, secret_prefix, roles, created_by, created_at, last_used_at, disabled_at
+        "#;
+
+        let account = sqlx::query_as::<_, ServiceAccount>(query)
+            .bind(id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(account)
+    }
+
+    /// Disable a service account so it can't get new tokens
+    pub async fn disable_service_account(&self, id: Uuid) -> Result<bool> {
+        let query = r#"
+            UPDATE service_accounts
+            SET disabled_at = NOW()
+            WHERE id = $1 AND disabled_at IS NULL
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .execute(self.db.pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete everything the auth tables hold for a user, in one transaction
+    ///
+    /// Service accounts the user created are kept; `created_by` only records
+    /// who made them.
+    pub async fn delete_user_data(&self, user_id: Uuid) -> Result<()> {
+        let mut tx = self.db.pool().begin().await.map_err(AppError::Database)?;
+
+        // Refresh tokens reference their sessions, so they go first
+        for table in [
+            "refresh_tokens",
+            "user_sessions",
+            "password_reset_tokens",
+            "webauthn_challenges",
+            "webauthn_credentials",
+            "personal_access_tokens",
+            "user_roles",
+            "user_credentials",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_id = $1", table))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+        }
 
+        tx.commit().await.map_err(AppError::Database)?;
         Ok(())
     }
 
@@ -241,7 +1173,7 @@ impl AuthRepository {
         let result = sqlx::query(query)
             .execute(self.db.pool())
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(result.rows_affected())
     }
@@ -0,0 +1,201 @@
+use crate::database::{InstrumentedDatabase, ShardRouter};
+use crate::errors::{AppError, Result};
+use crate::models::{Job, JobStatus};
+use crate::utils::id;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Background job queue, kept on the primary database
+#[derive(Clone)]
+pub struct JobRepository {
+    shards: Arc<ShardRouter>,
+}
+
+impl JobRepository {
+    pub fn new(shards: Arc<ShardRouter>) -> Self {
+        Self { shards }
+    }
+
+    fn db(&self) -> &Arc<InstrumentedDatabase> {
+        self.shards.primary()
+    }
+
+    /// Queue a job; returns false if the same work is already pending or running
+    pub async fn enqueue(&self, kind: &str, dedupe_key: &str, payload: &Value) -> Result<bool> {
+        let query = r#"
+            INSERT INTO jobs (id, kind, dedupe_key, payload)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, dedupe_key) WHERE status IN ('pending', 'running') DO NOTHING
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id::generate())
+            .bind(kind)
+            .bind(dedupe_key)
+            .bind(payload)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Take the next due job of one of `kinds` and lease it to `owner`
+    ///
+    /// A running job whose lease ran out is assumed to belong to a worker
+    /// that died and is handed out again, unless it has already had
+    /// `max_attempts` attempts (see [`Self::fail_abandoned`]). Each claim
+    /// counts as an attempt.
+    pub async fn claim(
+        &self,
+        kinds: &[String],
+        owner: &str,
+        lease: Duration,
+        max_attempts: i32,
+    ) -> Result<Option<Job>> {
+        let query = r#"
+            UPDATE jobs
+            SET status = 'running', attempts = attempts + 1, locked_at = NOW(),
+                lease_owner = $2, lease_expires_at = NOW() + make_interval(secs => $3),
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE kind = ANY($1)
+                  AND ((status = 'pending' AND run_at <= NOW())
+                    OR (status = 'running' AND lease_expires_at <= NOW() AND attempts < $4))
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+        "#;
+
+        sqlx::query_as::<_, Job>(query)
+            .bind(kinds)
+            .bind(owner)
+            .bind(lease.as_secs_f64())
+            .bind(max_attempts)
+            .fetch_optional(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Mark running jobs of `kinds` whose lease ran out on their last attempt
+    /// as failed; returns the kind of each
+    pub async fn fail_abandoned(&self, kinds: &[String], max_attempts: i32) -> Result<Vec<String>> {
+        let query = r#"
+            UPDATE jobs
+            SET status = 'failed', last_error = 'Abandoned after the last attempt',
+                locked_at = NULL, lease_owner = NULL, lease_expires_at = NULL,
+                finished_at = NOW(), updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM jobs
+                WHERE kind = ANY($1)
+                  AND status = 'running' AND lease_expires_at <= NOW() AND attempts >= $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING kind
+        "#;
+
+        sqlx::query_scalar::<_, String>(query)
+            .bind(kinds)
+            .bind(max_attempts)
+            .fetch_all(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Record how far a running job has got
+    pub async fn update_progress(&self, id: Uuid, progress: &Value) -> Result<()> {
+        sqlx::query("UPDATE jobs SET progress = $2, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(progress)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Mark a job succeeded; returns false if `owner` no longer holds its lease
+    pub async fn complete(&self, id: Uuid, owner: &str) -> Result<bool> {
+        let query = r#"
+            UPDATE jobs
+            SET status = 'succeeded', last_error = NULL, locked_at = NULL,
+                lease_owner = NULL, lease_expires_at = NULL,
+                finished_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = 'running'
+              AND lease_owner = $2 AND lease_expires_at > NOW()
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .bind(owner)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a failed attempt: back to pending until `retry_at`, or failed
+    /// for good when there's no retry left. Returns false if `owner` no
+    /// longer holds the job's lease.
+    pub async fn fail(
+        &self,
+        id: Uuid,
+        owner: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE jobs
+            SET status = CASE WHEN $4::timestamptz IS NULL THEN 'failed'::job_status ELSE 'pending'::job_status END,
+                run_at = COALESCE($4, run_at),
+                finished_at = CASE WHEN $4::timestamptz IS NULL THEN NOW() END,
+                last_error = $3, locked_at = NULL, lease_owner = NULL, lease_expires_at = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'running'
+              AND lease_owner = $2 AND lease_expires_at > NOW()
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .bind(owner)
+            .bind(error)
+            .bind(retry_at)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find(&self, id: Uuid) -> Result<Option<Job>> {
+        sqlx::query_as::<_, Job>("SELECT * FROM jobs WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Newest jobs first, optionally only those with `status`
+    pub async fn list_recent(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>> {
+        let query = r#"
+            SELECT * FROM jobs
+            WHERE $1::job_status IS NULL OR status = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+        "#;
+
+        sqlx::query_as::<_, Job>(query)
+            .bind(status)
+            .bind(limit)
+            .fetch_all(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+}
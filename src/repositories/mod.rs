@@ -1,23 +1,52 @@
+pub mod audit;
 pub mod auth;
+pub mod job;
+pub mod organization;
+pub mod tenant;
 pub mod user;
 
-use crate::database::InstrumentedDatabase;
+use crate::database::{InstrumentedDatabase, ShardRouter};
 use std::sync::Arc;
 
+pub use audit::AuditRepository;
 pub use auth::AuthRepository;
-pub use user::UserRepository;
+pub use job::JobRepository;
+pub use organization::OrganizationRepository;
+pub use tenant::TenantSettingsRepository;
+pub use user::{DeletedUsers, UserRepository};
 
 #[derive(Clone)]
 pub struct Repositories {
     pub user: UserRepository,
     pub auth: AuthRepository,
+    pub tenant: TenantSettingsRepository,
+    pub audit: AuditRepository,
+    pub jobs: JobRepository,
+    pub organizations: OrganizationRepository,
 }
 
 impl Repositories {
     pub fn new(instrumented_db: Arc<InstrumentedDatabase>) -> Self {
+        Self::with_auth_database(instrumented_db.clone(), instrumented_db)
+    }
+
+    /// Build repositories where auth tables live in their own database or schema
+    pub fn with_auth_database(
+        instrumented_db: Arc<InstrumentedDatabase>,
+        auth_db: Arc<InstrumentedDatabase>,
+    ) -> Self {
+        Self::sharded(Arc::new(ShardRouter::single(instrumented_db)), auth_db)
+    }
+
+    /// Build repositories where user data is partitioned across shards
+    pub fn sharded(shards: Arc<ShardRouter>, auth_db: Arc<InstrumentedDatabase>) -> Self {
         Self {
-            user: UserRepository::new(instrumented_db.clone()),
-            auth: AuthRepository::new(instrumented_db),
+            user: UserRepository::with_shards(shards.clone()),
+            auth: AuthRepository::new(auth_db),
+            tenant: TenantSettingsRepository::new(shards.clone()),
+            audit: AuditRepository::new(shards.clone()),
+            jobs: JobRepository::new(shards.clone()),
+            organizations: OrganizationRepository::new(shards),
         }
     }
 }
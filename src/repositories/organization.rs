@@ -0,0 +1,188 @@
+use crate::database::{InstrumentedDatabase, ShardRouter};
+use crate::errors::{AppError, Result};
+use crate::models::{Organization, OrganizationMember, OrganizationRole, PaginationParams};
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Organizations and their members, kept on the primary database so they
+/// can be listed without fanning out
+#[derive(Clone)]
+pub struct OrganizationRepository {
+    shards: Arc<ShardRouter>,
+}
+
+impl OrganizationRepository {
+    pub fn new(shards: Arc<ShardRouter>) -> Self {
+        Self { shards }
+    }
+
+    fn db(&self) -> &Arc<InstrumentedDatabase> {
+        self.shards.primary()
+    }
+
+    /// Insert an organization; returns `None` if the id is taken
+    pub async fn create(&self, id: &str, name: &str, created_by: Uuid) -> Result<Option<Organization>> {
+        let query = r#"
+            INSERT INTO organizations (id, name, created_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING *
+        "#;
+
+        sqlx::query_as::<_, Organization>(query)
+            .bind(id)
+            .bind(name)
+            .bind(created_by)
+            .fetch_optional(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Organization>> {
+        sqlx::query_as::<_, Organization>("SELECT * FROM organizations WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Organizations `user_id` belongs to, by name
+    pub async fn find_for_user(&self, user_id: Uuid) -> Result<Vec<Organization>> {
+        let query = r#"
+            SELECT o.*
+            FROM organizations o
+            JOIN organization_members m ON m.organization_id = o.id
+            WHERE m.user_id = $1
+            ORDER BY o.name, o.id
+        "#;
+
+        sqlx::query_as::<_, Organization>(query)
+            .bind(user_id)
+            .fetch_all(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    pub async fn find_all(&self, pagination: &PaginationParams) -> Result<(Vec<Organization>, i64)> {
+        let organizations = sqlx::query_as::<_, Organization>(
+            "SELECT * FROM organizations ORDER BY name, id LIMIT $1 OFFSET $2",
+        )
+        .bind(pagination.per_page())
+        .bind(pagination.offset())
+        .fetch_all(self.db().pool())
+        .await
+        .map_err(AppError::Database)?;
+
+        let total: i64 = sqlx::query("SELECT COUNT(*) AS count FROM organizations")
+            .fetch_one(self.db().pool())
+            .await
+            .map_err(AppError::Database)?
+            .get("count");
+
+        Ok((organizations, total))
+    }
+
+    pub async fn update(&self, id: &str, name: &str) -> Result<Option<Organization>> {
+        sqlx::query_as::<_, Organization>("UPDATE organizations SET name = $2 WHERE id = $1 RETURNING *")
+            .bind(id)
+            .bind(name)
+            .fetch_optional(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Delete an organization and, by cascade, its memberships
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM organizations WHERE id = $1")
+            .bind(id)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_member(&self, organization_id: &str, user_id: Uuid) -> Result<Option<OrganizationMember>> {
+        sqlx::query_as::<_, OrganizationMember>(
+            "SELECT * FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .fetch_optional(self.db().pool())
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Members of an organization, admins first, then oldest first
+    pub async fn find_members(&self, organization_id: &str) -> Result<Vec<OrganizationMember>> {
+        let query = r#"
+            SELECT * FROM organization_members
+            WHERE organization_id = $1
+            ORDER BY role DESC, created_at, user_id
+        "#;
+
+        sqlx::query_as::<_, OrganizationMember>(query)
+            .bind(organization_id)
+            .fetch_all(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    pub async fn count_admins(&self, organization_id: &str) -> Result<i64> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS count FROM organization_members WHERE organization_id = $1 AND role = 'admin'",
+        )
+        .bind(organization_id)
+        .fetch_one(self.db().pool())
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.get("count"))
+    }
+
+    /// Add a member or change their role
+    pub async fn upsert_member(
+        &self,
+        organization_id: &str,
+        user_id: Uuid,
+        role: OrganizationRole,
+    ) -> Result<OrganizationMember> {
+        let query = r#"
+            INSERT INTO organization_members (organization_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (organization_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            RETURNING *
+        "#;
+
+        sqlx::query_as::<_, OrganizationMember>(query)
+            .bind(organization_id)
+            .bind(user_id)
+            .bind(role)
+            .fetch_one(self.db().pool())
+            .await
+            .map_err(AppError::Database)
+    }
+
+    pub async fn delete_member(&self, organization_id: &str, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM organization_members WHERE organization_id = $1 AND user_id = $2")
+            .bind(organization_id)
+            .bind(user_id)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Drop every membership of a deleted user
+    pub async fn delete_memberships(&self, user_id: Uuid) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM organization_members WHERE user_id = $1")
+            .bind(user_id)
+            .execute(self.db().pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+}
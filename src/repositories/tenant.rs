@@ -0,0 +1,71 @@
+use crate::database::{InstrumentedDatabase, ShardRouter};
+use crate::errors::{AppError, Result};
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Tenant settings rows, placed on the tenant's shard
+#[derive(Clone)]
+pub struct TenantSettingsRepository {
+    shards: Arc<ShardRouter>,
+}
+
+impl TenantSettingsRepository {
+    pub fn new(shards: Arc<ShardRouter>) -> Self {
+        Self { shards }
+    }
+
+    fn db(&self, tenant_id: &str) -> &Arc<InstrumentedDatabase> {
+        self.shards.for_tenant(tenant_id)
+    }
+
+    /// All stored sections for a tenant
+    pub async fn find_all(&self, tenant_id: &str) -> Result<Vec<(String, serde_json::Value)>> {
+        let rows = sqlx::query("SELECT key, value FROM tenant_settings WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_all(self.db(tenant_id).pool())
+            .await
+            .map_err(AppError::Database)?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.try_get("key")?, row.try_get("value")?)))
+            .collect::<std::result::Result<_, sqlx::Error>>()
+            .map_err(AppError::Database)
+    }
+
+    /// Insert or replace sections in one transaction
+    pub async fn upsert(
+        &self,
+        tenant_id: &str,
+        entries: &[(String, serde_json::Value)],
+        updated_by: Uuid,
+    ) -> Result<()> {
+        let mut tx = self
+            .db(tenant_id)
+            .pool()
+            .begin()
+            .await
+            .map_err(AppError::Database)?;
+
+        for (key, value) in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO tenant_settings (tenant_id, key, value, updated_by, updated_at)
+                VALUES ($1, $2, $3, $4, NOW())
+                ON CONFLICT (tenant_id, key)
+                DO UPDATE SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(key)
+            .bind(value)
+            .bind(updated_by)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+}
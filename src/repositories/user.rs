@@ -1,129 +1,310 @@
-use crate::errors::Result;
-use crate::models::{CreateUserRequest, PaginationParams, UpdateUserRequest, User};
-use crate::database::InstrumentedDatabase;
-use chrono::Utc;
-use sqlx::{PgPool, Row};
+use crate::errors::{unique_violation, AppError, Result};
+use crate::models::{
+    CreateUserRequest, PaginationParams, SortOrder, UpdateUserRequest, User, UserCursor,
+    UserFilter, UserSortField, UserStatus, UserSummary,
+};
+use crate::database::{InstrumentedDatabase, ShardKey, ShardRouter};
+use crate::utils::{id, CollectionVersion};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{Postgres, Row, Transaction};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// `WHERE` clause of filtered user listings, binding the [`UserFilter`] as
+/// `$1..$5`. `q` is served by the trigram indexes on email and username,
+/// roles by the GIN index on `user_summaries.roles` (roles are denormalized
+/// there because auth tables may live in another database).
+const USER_FILTER: &str = r#"
+    WHERE ($1::text IS NULL OR email ILIKE $1 OR username ILIKE $1)
+      AND ($2::timestamptz IS NULL OR created_at >= $2)
+      AND ($3::timestamptz IS NULL OR created_at < $3)
+      AND (cardinality($4::text[]) = 0 OR EXISTS (
+          SELECT 1 FROM user_summaries s WHERE s.user_id = users.id AND s.roles && $4
+      ))
+      AND (cardinality($5::user_status[]) = 0 OR status = ANY($5))
+"#;
+
+/// Columns selected into a [`User`]
+const USER_COLUMNS: &str = "id, email, username, avatar_key, status, created_at, updated_at";
+
+fn user_from_row(row: &PgRow) -> User {
+    User {
+        id: row.get("id"),
+        email: row.get("email"),
+        username: row.get("username"),
+        avatar_key: row.get("avatar_key"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// A taken email or username as a 409; the service checks for both first,
+/// but two registrations can pass that check together
+fn taken_or_database(err: sqlx::Error) -> AppError {
+    match unique_violation(&err) {
+        Some("users_email_key" | "user_handles_email_key") => AppError::email_taken(),
+        Some("users_username_key" | "user_handles_username_key") => AppError::username_taken(),
+        _ => AppError::Database(err),
+    }
+}
+
+/// Record the user's email and username in `user_handles`, whose unique
+/// constraints span every shard
+async fn claim_handles(tx: &mut Transaction<'_, Postgres>, user: &User) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_handles (user_id, email, username)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO UPDATE SET email = EXCLUDED.email, username = EXCLUDED.username
+        "#,
+    )
+    .bind(user.id)
+    .bind(&user.email)
+    .bind(&user.username)
+    .execute(&mut **tx)
+    .await
+    .map_err(taken_or_database)?;
+
+    Ok(())
+}
+
+/// Outcome of [`UserRepository::delete_many`]
+#[derive(Debug, Default)]
+pub struct DeletedUsers {
+    /// Users that existed and are gone
+    pub deleted: Vec<Uuid>,
+    /// Users that existed, but whose shard failed to commit or was rolled
+    /// back after another shard did
+    pub uncommitted: Vec<Uuid>,
+}
+
+/// User rows are partitioned by user id; lookups without an id fan out
 #[derive(Clone)]
 pub struct UserRepository {
-    pool: Arc<PgPool>,
-    instrumented_db: Option<Arc<InstrumentedDatabase>>,
+    shards: Arc<ShardRouter>,
 }
 
 impl UserRepository {
     pub fn new(instrumented_db: Arc<InstrumentedDatabase>) -> Self {
-        Self {
-            pool: Arc::new(instrumented_db.pool().clone()),
-            instrumented_db: Some(instrumented_db),
-        }
+        Self::with_shards(Arc::new(ShardRouter::single(instrumented_db)))
+    }
+
+    pub fn with_shards(shards: Arc<ShardRouter>) -> Self {
+        Self { shards }
     }
 
     pub async fn create(&self, request: CreateUserRequest) -> Result<User> {
-        let id = Uuid::new_v4();
+        let id = id::generate();
         let now = Utc::now();
 
-        let row = sqlx::query(
-            r#"
-            INSERT INTO users (id, email, username, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, email, username, created_at, updated_at
-            "#,
-        )
+        let shard = self.shards.for_user(id);
+        let mut tx = shard.pool().begin().await?;
+        let row = sqlx::query(&format!(
+            "INSERT INTO users (id, email, username, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+            USER_COLUMNS
+        ))
         .bind(id)
         .bind(&request.email)
         .bind(&request.username)
         .bind(now)
         .bind(now)
-        .fetch_one(&*self.pool)
-        .await?;
-
-        let user = User {
-            id: row.get("id"),
-            email: row.get("email"),
-            username: row.get("username"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        };
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(taken_or_database)?;
 
+        let user = user_from_row(&row);
+        self.commit_with_handles(shard, tx, &user).await?;
         Ok(user)
     }
 
+    /// Claim the user's handles on the primary and commit `tx`, the user's
+    /// write on `shard`
+    ///
+    /// A user on the primary is claimed in `tx` itself. Otherwise the claim
+    /// is held in its own transaction until `tx` has committed, so a racing
+    /// claim of the same handle waits and then fails; if that last commit
+    /// fails, the user row stays without a claim and is only protected by
+    /// its shard's constraints.
+    async fn commit_with_handles(
+        &self,
+        shard: &Arc<InstrumentedDatabase>,
+        mut tx: Transaction<'_, Postgres>,
+        user: &User,
+    ) -> Result<()> {
+        if Arc::ptr_eq(shard, self.shards.primary()) {
+            claim_handles(&mut tx, user).await?;
+            tx.commit().await?;
+            return Ok(());
+        }
+
+        let mut handles = self.shards.primary().pool().begin().await?;
+        claim_handles(&mut handles, user).await?;
+        tx.commit().await?;
+        handles.commit().await?;
+        Ok(())
+    }
+
+    /// Give up the handles of deleted users
+    async fn release_handles(&self, ids: &[Uuid]) -> Result<()> {
+        sqlx::query("DELETE FROM user_handles WHERE user_id = ANY($1)")
+            .bind(ids)
+            .execute(self.shards.primary().pool())
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
-        let row = sqlx::query("SELECT id, email, username, created_at, updated_at FROM users WHERE id = $1")
+        let row = sqlx::query(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
             .bind(id)
-            .fetch_optional(&*self.pool)
+            .fetch_optional(self.shards.for_user(id).pool())
             .await?;
 
-        let user = row.map(|r| User {
-            id: r.get("id"),
-            email: r.get("email"),
-            username: r.get("username"),
-            created_at: r.get("created_at"),
-            updated_at: r.get("updated_at"),
-        });
-
-        Ok(user)
+        Ok(row.as_ref().map(user_from_row))
     }
 
     pub async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
-        let row = sqlx::query("SELECT id, email, username, created_at, updated_at FROM users WHERE email = $1")
-            .bind(email)
-            .fetch_optional(&*self.pool)
-            .await?;
+        for shard in self.shards.all() {
+            let row = sqlx::query(&format!("SELECT {} FROM users WHERE email = $1", USER_COLUMNS))
+                .bind(email)
+                .fetch_optional(shard.pool())
+                .await?;
 
-        let user = row.map(|r| User {
-            id: r.get("id"),
-            email: r.get("email"),
-            username: r.get("username"),
-            created_at: r.get("created_at"),
-            updated_at: r.get("updated_at"),
-        });
+            if let Some(row) = row {
+                return Ok(Some(user_from_row(&row)));
+            }
+        }
 
-        Ok(user)
+        Ok(None)
     }
 
-    pub async fn find_all(&self, pagination: PaginationParams) -> Result<(Vec<User>, i64)> {
-        let offset = pagination.offset();
+    /// A page of users and the filtered total; with `after` the page starts
+    /// past that cursor (keyset on `created_at, id`) instead of at an offset
+    pub async fn find_all(
+        &self,
+        pagination: PaginationParams,
+        filter: &UserFilter,
+        after: Option<UserCursor>,
+    ) -> Result<(Vec<User>, i64)> {
+        let offset = if after.is_some() { 0 } else { pagination.offset() };
         let limit = pagination.per_page();
+        let sort = pagination.user_sort()?;
+        let order = pagination.order();
+        if after.is_some() && sort != UserSortField::CreatedAt {
+            return Err(AppError::Validation(
+                "cursor pagination only supports sort_by=created_at".to_string(),
+            ));
+        }
 
-        let rows = sqlx::query(
-            r#"
-            SELECT id, email, username, created_at, updated_at
-            FROM users
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&*self.pool)
-        .await?;
+        // With several shards each one returns its first offset + limit rows,
+        // which are merged before the page is cut
+        let (shard_limit, shard_offset) = if self.shards.is_sharded() {
+            (offset + limit, 0)
+        } else {
+            (limit, offset)
+        };
 
-        let users: Vec<User> = rows
-            .into_iter()
-            .map(|r| User {
-                id: r.get("id"),
-                email: r.get("email"),
-                username: r.get("username"),
-                created_at: r.get("created_at"),
-                updated_at: r.get("updated_at"),
-            })
-            .collect();
-
-        let total_row = sqlx::query("SELECT COUNT(*) as count FROM users")
-            .fetch_one(&*self.pool)
-            .await?;
-        let total: i64 = total_row.get("count");
+        let search_pattern = filter.search_pattern();
+        // Both come from whitelists, never from the request as is
+        let page_query = format!(
+            "SELECT {} FROM users {} \
+             AND ($8::timestamptz IS NULL OR (created_at, id) {} ($8, $9)) \
+             ORDER BY {} {}, id {} LIMIT $6 OFFSET $7",
+            USER_COLUMNS,
+            USER_FILTER,
+            match order {
+                SortOrder::Asc => ">",
+                SortOrder::Desc => "<",
+            },
+            sort.as_str(),
+            order.as_sql(),
+            order.as_sql()
+        );
+        let count_query = format!("SELECT COUNT(*) as count FROM users {}", USER_FILTER);
+
+        let mut users = Vec::new();
+        let mut total = 0;
+
+        for shard in self.shards.all() {
+            let rows = sqlx::query(&page_query)
+                .bind(&search_pattern)
+                .bind(filter.created_after)
+                .bind(filter.created_before)
+                .bind(&filter.roles)
+                .bind(&filter.statuses)
+                .bind(shard_limit)
+                .bind(shard_offset)
+                .bind(after.map(|cursor| cursor.created_at))
+                .bind(after.map(|cursor| cursor.id))
+                .fetch_all(shard.pool())
+                .await?;
+
+            users.extend(rows.iter().map(user_from_row));
+
+            let total_row = sqlx::query(&count_query)
+                .bind(&search_pattern)
+                .bind(filter.created_after)
+                .bind(filter.created_before)
+                .bind(&filter.roles)
+                .bind(&filter.statuses)
+                .fetch_one(shard.pool())
+                .await?;
+            total += total_row.get::<i64, _>("count");
+        }
+
+        if self.shards.is_sharded() {
+            users.sort_by(|a, b| match order {
+                SortOrder::Asc => sort.compare(a, b),
+                SortOrder::Desc => sort.compare(b, a),
+            });
+            users = users
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect();
+        }
 
         Ok((users, total))
     }
 
+    /// Latest `updated_at` and row count of `users`, for conditional GETs
+    pub async fn users_version(&self) -> Result<CollectionVersion> {
+        self.table_version("users").await
+    }
+
+    /// Latest `updated_at` and row count of `user_summaries`
+    pub async fn summaries_version(&self) -> Result<CollectionVersion> {
+        self.table_version("user_summaries").await
+    }
+
+    async fn table_version(&self, table: &'static str) -> Result<CollectionVersion> {
+        let query = format!(
+            "SELECT MAX(updated_at) AS last_modified, COUNT(*) AS count FROM {}",
+            table
+        );
+
+        let mut version = CollectionVersion::default();
+        for shard in self.shards.all() {
+            let row = sqlx::query(&query).fetch_one(shard.pool()).await?;
+            version = version.merge(CollectionVersion {
+                last_modified: row.get("last_modified"),
+                count: row.get("count"),
+            });
+        }
+
+        Ok(version)
+    }
+
     pub async fn update(&self, id: Uuid, request: UpdateUserRequest) -> Result<Option<User>> {
         let now = Utc::now();
+        let shard = self.shards.for_user(id);
+        let mut tx = shard.pool().begin().await?;
 
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
             UPDATE users
             SET
@@ -131,53 +312,267 @@ impl UserRepository {
                 username = COALESCE($3, username),
                 updated_at = $4
             WHERE id = $1
-            RETURNING id, email, username, created_at, updated_at
+            RETURNING {}
             "#,
-        )
+            USER_COLUMNS
+        ))
         .bind(id)
         .bind(&request.email)
         .bind(&request.username)
         .bind(now)
-        .fetch_optional(&*self.pool)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(taken_or_database)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let user = user_from_row(&row);
+        self.commit_with_handles(shard, tx, &user).await?;
+        Ok(Some(user))
+    }
+
+    /// Point the user at a new avatar object (or none); returns the updated
+    /// user and the key it replaced, so the old object can be deleted
+    pub async fn set_avatar_key(
+        &self,
+        id: Uuid,
+        avatar_key: Option<&str>,
+    ) -> Result<Option<(User, Option<String>)>> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE users
+            SET avatar_key = $2, updated_at = $3
+            FROM (SELECT avatar_key AS previous_avatar_key FROM users WHERE id = $1 FOR UPDATE) previous
+            WHERE id = $1
+            RETURNING {}, previous.previous_avatar_key
+            "#,
+            USER_COLUMNS
+        ))
+        .bind(id)
+        .bind(avatar_key)
+        .bind(Utc::now())
+        .fetch_optional(self.shards.for_user(id).pool())
         .await?;
 
-        let user = row.map(|r| User {
-            id: r.get("id"),
-            email: r.get("email"),
-            username: r.get("username"),
-            created_at: r.get("created_at"),
-            updated_at: r.get("updated_at"),
-        });
+        Ok(row.map(|row| (user_from_row(&row), row.get("previous_avatar_key"))))
+    }
 
-        Ok(user)
+    /// Move the user to `status`; returns the updated user and the status
+    /// it replaced
+    pub async fn set_status(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+    ) -> Result<Option<(User, UserStatus)>> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE users
+            SET status = $2, updated_at = $3
+            FROM (SELECT status AS previous_status FROM users WHERE id = $1 FOR UPDATE) previous
+            WHERE id = $1
+            RETURNING {}, previous.previous_status
+            "#,
+            USER_COLUMNS
+        ))
+        .bind(id)
+        .bind(status)
+        .bind(Utc::now())
+        .fetch_optional(self.shards.for_user(id).pool())
+        .await?;
+
+        Ok(row.map(|row| (user_from_row(&row), row.get("previous_status"))))
+    }
+
+    /// Just the user's status, for the per-request check; `None` for
+    /// unknown users
+    pub async fn find_status(&self, id: Uuid) -> Result<Option<UserStatus>> {
+        let status = sqlx::query_scalar("SELECT status FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.shards.for_user(id).pool())
+            .await?;
+
+        Ok(status)
     }
 
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let result = sqlx::query("DELETE FROM users WHERE id = $1")
             .bind(id)
-            .execute(&*self.pool)
+            .execute(self.shards.for_user(id).pool())
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            self.release_handles(&[id]).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Delete users in one transaction per shard
+    ///
+    /// With `all_or_nothing` nothing is committed unless every id was found.
+    /// There is no transaction spanning shards, though: they commit one after
+    /// another, and a commit failing on a later shard can't undo an earlier
+    /// one. The ids on a shard that didn't commit come back as
+    /// [`DeletedUsers::uncommitted`]; with `all_or_nothing` the shards after
+    /// it are rolled back too, and a failure before anything was committed
+    /// is returned as an error.
+    pub async fn delete_many(&self, ids: &[Uuid], all_or_nothing: bool) -> Result<DeletedUsers> {
+        let mut by_shard: BTreeMap<usize, Vec<Uuid>> = BTreeMap::new();
+        for id in ids {
+            by_shard
+                .entry(self.shards.shard_index(ShardKey::User(*id)))
+                .or_default()
+                .push(*id);
+        }
+
+        let mut transactions = Vec::with_capacity(by_shard.len());
+        let mut found = 0;
+        for (index, shard_ids) in by_shard {
+            let mut tx = self.shards.all()[index].pool().begin().await?;
+            let rows: Vec<Uuid> =
+                sqlx::query_scalar("DELETE FROM users WHERE id = ANY($1) RETURNING id")
+                    .bind(&shard_ids)
+                    .fetch_all(&mut *tx)
+                    .await?;
+            found += rows.len();
+            transactions.push((index, tx, rows));
+        }
+
+        // Dropping the transactions rolls them back
+        if all_or_nothing && found < ids.len() {
+            let deleted: Vec<Uuid> = transactions
+                .iter()
+                .flat_map(|(_, _, rows)| rows.iter().copied())
+                .collect();
+            let missing: Vec<String> = ids
+                .iter()
+                .filter(|id| !deleted.contains(id))
+                .map(Uuid::to_string)
+                .collect();
+            return Err(AppError::NotFound(format!(
+                "Users not found: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let mut result = DeletedUsers::default();
+        for (index, tx, rows) in transactions {
+            if all_or_nothing && !result.uncommitted.is_empty() {
+                result.uncommitted.extend(rows);
+                continue;
+            }
+            match tx.commit().await {
+                Ok(()) => result.deleted.extend(rows),
+                Err(e) if all_or_nothing && result.deleted.is_empty() => return Err(e.into()),
+                Err(e) => {
+                    tracing::error!(shard = index, error = %e, "Failed to commit user deletions");
+                    result.uncommitted.extend(rows);
+                }
+            }
+        }
+
+        self.release_handles(&result.deleted).await?;
+        Ok(result)
     }
 
     pub async fn exists_by_email(&self, email: &str) -> Result<bool> {
-        let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1) as exists")
-            .bind(email)
-            .fetch_one(&*self.pool)
-            .await?;
+        for shard in self.shards.all() {
+            let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1) as exists")
+                .bind(email)
+                .fetch_one(shard.pool())
+                .await?;
+
+            if row.get::<bool, _>("exists") {
+                return Ok(true);
+            }
+        }
 
-        let exists: bool = row.get("exists");
-        Ok(exists)
+        Ok(false)
     }
 
     pub async fn exists_by_username(&self, username: &str) -> Result<bool> {
-        let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1) as exists")
-            .bind(username)
-            .fetch_one(&*self.pool)
+        for shard in self.shards.all() {
+            let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1) as exists")
+                .bind(username)
+                .fetch_one(shard.pool())
+                .await?;
+
+            if row.get::<bool, _>("exists") {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Page through user summaries; one indexed query per shard
+    pub async fn find_summaries(&self, pagination: PaginationParams) -> Result<(Vec<UserSummary>, i64)> {
+        let offset = pagination.offset();
+        let limit = pagination.per_page();
+
+        let (shard_limit, shard_offset) = if self.shards.is_sharded() {
+            (offset + limit, 0)
+        } else {
+            (limit, offset)
+        };
+
+        let mut summaries = Vec::new();
+        let mut total = 0;
+
+        for shard in self.shards.all() {
+            let rows = sqlx::query_as::<_, UserSummary>(
+                r#"
+                SELECT user_id, email, username, roles, last_login_at, created_at, updated_at
+                FROM user_summaries
+                ORDER BY created_at DESC, user_id
+                LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(shard_limit)
+            .bind(shard_offset)
+            .fetch_all(shard.pool())
+            .await?;
+
+            summaries.extend(rows);
+
+            let total_row = sqlx::query("SELECT COUNT(*) as count FROM user_summaries")
+                .fetch_one(shard.pool())
+                .await?;
+            total += total_row.get::<i64, _>("count");
+        }
+
+        if self.shards.is_sharded() {
+            summaries.sort_by_key(|summary| std::cmp::Reverse(summary.created_at));
+            summaries = summaries
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect();
+        }
+
+        Ok((summaries, total))
+    }
+
+    /// Replace the denormalized roles of a user's summary
+    pub async fn update_summary_roles(&self, user_id: Uuid, roles: &[String]) -> Result<()> {
+        sqlx::query("UPDATE user_summaries SET roles = $2, updated_at = NOW() WHERE user_id = $1")
+            .bind(user_id)
+            .bind(roles)
+            .execute(self.shards.for_user(user_id).pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record a successful login in the user's summary
+    pub async fn record_login(&self, user_id: Uuid, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE user_summaries SET last_login_at = $2, updated_at = NOW() WHERE user_id = $1")
+            .bind(user_id)
+            .bind(at)
+            .execute(self.shards.for_user(user_id).pool())
             .await?;
 
-        let exists: bool = row.get("exists");
-        Ok(exists)
+        Ok(())
     }
 }
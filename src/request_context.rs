@@ -0,0 +1,217 @@
+//! Who is calling, and on what terms, for the request being handled.
+//!
+//! The request context middleware reads the caller's locale, time zone,
+//! address, user agent and request id once per request and fixes the
+//! deadline the request should be done by. Handlers take [`RequestContext`]
+//! as an extractor; services read [`RequestContext::current`] instead of
+//! having it threaded through every call.
+//!
+//! The scope is task-local, like [`crate::request_cost`], so background jobs
+//! and work moved to a `tokio::spawn`ed task have no context.
+
+use crate::config::RequestContextConfig;
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderMap};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Carries the request id in, and back out on the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// IANA name of the caller's time zone, e.g. `Europe/Paris`
+pub const TIMEZONE_HEADER: &str = "x-timezone";
+/// Milliseconds the caller is willing to wait, when less than the default
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout-ms";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+const MAX_USER_AGENT_LEN: usize = 255;
+
+tokio::task_local! {
+    static CURRENT: RequestContext;
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    /// From `X-Request-Id`, or generated when it's missing or malformed
+    pub request_id: String,
+    /// Best match for `Accept-Language` among the supported locales
+    pub locale: String,
+    /// From `X-Timezone`; the default time zone when missing or malformed
+    pub timezone: String,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// When the caller stops waiting for a response
+    pub deadline: Instant,
+}
+
+impl RequestContext {
+    pub fn from_headers(headers: &HeaderMap, config: &RequestContextConfig) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let request_id = header(REQUEST_ID_HEADER)
+            .map(str::trim)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id.bytes().all(|byte| byte.is_ascii_graphic())
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let timeout = header(REQUEST_TIMEOUT_HEADER)
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map_or(config.timeout_ms, |ms| ms.min(config.timeout_ms));
+
+        Self {
+            request_id,
+            locale: negotiate_locale(header(header::ACCEPT_LANGUAGE.as_str()), config),
+            timezone: header(TIMEZONE_HEADER)
+                .map(str::trim)
+                .filter(|timezone| is_timezone_name(timezone))
+                .unwrap_or(&config.default_timezone)
+                .to_string(),
+            client_ip: client_ip(headers),
+            user_agent: user_agent(headers),
+            deadline: Instant::now() + Duration::from_millis(timeout),
+        }
+    }
+
+    /// The context of the request being handled, if any
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Self::clone).ok()
+    }
+
+    /// Run `future` with this as the current context
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// Time left before the deadline
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+}
+
+/// `budget` from now, or the current request's deadline if that comes first
+pub fn deadline_within(budget: Duration) -> Instant {
+    let deadline = Instant::now() + budget;
+    CURRENT
+        .try_with(|context| deadline.min(context.deadline))
+        .unwrap_or(deadline)
+}
+
+/// Falls back to reading the headers when the middleware hasn't run
+impl<S: Send + Sync> FromRequestParts<S> for RequestContext {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(match parts.extensions.get::<RequestContext>() {
+            Some(context) => context.clone(),
+            None => Self::from_headers(&parts.headers, &RequestContextConfig::default()),
+        })
+    }
+}
+
+/// First address in `X-Forwarded-For`, else `X-Real-IP`
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .or_else(|| headers.get("x-real-ip").and_then(|h| h.to_str().ok()))
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+/// Address of the client behind `trusted_hops` proxies: the connecting peer
+/// when there are none, else the `trusted_hops`th `X-Forwarded-For` entry
+/// from the right, the one the outermost trusted proxy appended. Entries
+/// further left come from the client and aren't trusted.
+///
+/// Falls back to the peer when the header has fewer entries than that.
+pub fn forwarded_client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trusted_hops: usize,
+) -> Option<String> {
+    let peer = peer.map(|peer| peer.ip().to_string());
+    if trusted_hops == 0 {
+        return peer;
+    }
+
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    forwarded
+        .len()
+        .checked_sub(trusted_hops)
+        .and_then(|index| forwarded.get(index))
+        .map(|ip| ip.to_string())
+        .or(peer)
+}
+
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// The supported locale the caller prefers most, by `q` weight and then
+/// order; a tag matches a locale exactly or by its language alone, so `fr-CA`
+/// gets `fr` when that's all there is
+fn negotiate_locale(accept_language: Option<&str>, config: &RequestContextConfig) -> String {
+    let mut preferences: Vec<(&str, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && weight > 0.0).then_some((tag, weight))
+        })
+        .collect();
+    // Stable, so equal weights keep the caller's order
+    preferences.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let language = |tag: &str| tag.split('-').next().unwrap_or_default().to_ascii_lowercase();
+    preferences
+        .into_iter()
+        .find_map(|(tag, _)| {
+            config
+                .supported_locales
+                .iter()
+                .find(|locale| locale.eq_ignore_ascii_case(tag))
+                .or_else(|| {
+                    config
+                        .supported_locales
+                        .iter()
+                        .find(|locale| language(locale) == language(tag))
+                })
+        })
+        .unwrap_or(&config.default_locale)
+        .clone()
+}
+
+/// Shaped like an IANA name (`UTC`, `America/Argentina/Buenos_Aires`,
+/// `Etc/GMT+5`); whether the zone exists is up to whoever uses it
+fn is_timezone_name(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && value.split('/').all(|part| {
+            part.starts_with(|c: char| c.is_ascii_alphabetic())
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+        })
+}
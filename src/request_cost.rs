@@ -0,0 +1,102 @@
+//! Per-request cost accounting.
+//!
+//! The request cost middleware runs each request inside a [`RequestCost`]
+//! scope; database queries, OpenFGA calls, cache lookups and outbound HTTP
+//! calls made while handling it add to the tally through the `record_*`
+//! functions. Outside a scope (background jobs, startup) those are no-ops.
+//!
+//! The scope is task-local, so work moved to a `tokio::spawn`ed task isn't
+//! charged to the request that started it.
+
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Message of the summary the request cost middleware logs per request
+pub const REQUEST_COST_MESSAGE: &str = "Request cost";
+
+tokio::task_local! {
+    static CURRENT: Arc<RequestCost>;
+}
+
+/// Running tally for one request
+#[derive(Debug, Default)]
+pub struct RequestCost {
+    db_queries: AtomicU64,
+    db_time_micros: AtomicU64,
+    openfga_calls: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    http_calls: AtomicU64,
+}
+
+/// What a request cost once it has completed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RequestCostSnapshot {
+    pub db_queries: u64,
+    pub db_time: Duration,
+    pub openfga_calls: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub http_calls: u64,
+}
+
+impl RequestCost {
+    /// Run `future` with a fresh tally, returning its output and what it cost
+    pub async fn scope<F: Future>(future: F) -> (F::Output, RequestCostSnapshot) {
+        let cost = Arc::new(RequestCost::default());
+        let output = CURRENT.scope(cost.clone(), future).await;
+        (output, cost.snapshot())
+    }
+
+    pub fn snapshot(&self) -> RequestCostSnapshot {
+        RequestCostSnapshot {
+            db_queries: self.db_queries.load(Ordering::Relaxed),
+            db_time: Duration::from_micros(self.db_time_micros.load(Ordering::Relaxed)),
+            openfga_calls: self.openfga_calls.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            http_calls: self.http_calls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn with_current(f: impl FnOnce(&RequestCost)) {
+    let _ = CURRENT.try_with(|cost| f(cost));
+}
+
+/// A database statement completed (or was abandoned) after `elapsed`
+pub fn record_db_query(elapsed: Duration) {
+    with_current(|cost| {
+        cost.db_queries.fetch_add(1, Ordering::Relaxed);
+        cost.db_time_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    });
+}
+
+pub fn record_openfga_call() {
+    with_current(|cost| {
+        cost.openfga_calls.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+pub fn record_cache_hit() {
+    with_current(|cost| {
+        cost.cache_hits.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+pub fn record_cache_miss() {
+    with_current(|cost| {
+        cost.cache_misses.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+/// An outbound HTTP call other than OpenFGA (mailer, mirror, webhooks)
+pub fn record_http_call() {
+    with_current(|cost| {
+        cost.http_calls.fetch_add(1, Ordering::Relaxed);
+    });
+}
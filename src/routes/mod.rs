@@ -0,0 +1,261 @@
+pub mod registry;
+
+pub use registry::{AuthPolicy, Route};
+
+use crate::auth::{
+    handlers as auth_handlers,
+    middleware::AuthState,
+    rate_limit::login_rate_limit_middleware,
+};
+use crate::handlers::{
+    clear_banner, clear_status_incident, get_api_usage, get_banner, get_client_analytics, get_dependencies, get_job, get_log_level, get_status,
+    get_tenant_settings, get_trace_bundle, health_check, list_audit_events, list_jobs, live_tail, organization, readiness_check,
+    set_banner, set_status_incident, update_log_level, update_tenant_settings, user, warmup, Handlers,
+};
+use crate::middleware::verify_body_digest;
+use axum::{
+    extract::DefaultBodyLimit,
+    handler::Handler,
+    middleware,
+    routing::{on, MethodFilter},
+    Router,
+};
+
+/// `Router::route` keyed by a [`Route`], which supplies the path, the method
+/// and the auth middleware
+trait MountRoute<S> {
+    fn mount<H, T>(self, route: Route, handler: H, auth_state: &AuthState) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static;
+}
+
+impl<S> MountRoute<S> for Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn mount<H, T>(self, route: Route, handler: H, auth_state: &AuthState) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(route.method()).expect("route methods are standard");
+        self.route(route.path(), route.guard(on(filter, handler), auth_state))
+    }
+}
+
+pub fn create_routes(
+    handlers: Handlers,
+    auth_state: AuthState,
+) -> Router {
+    let auth = &auth_state;
+
+    // Readiness and warmup routes used by deploy tooling
+    let lifecycle_routes = Router::new()
+        .mount(Route::Ready, readiness_check, auth)
+        .mount(Route::Warmup, warmup, auth)
+        .with_state(handlers.warmup.clone());
+
+    // Public status summary, and the admin incident note it shows
+    let status_routes = Router::new()
+        .mount(Route::Status, get_status, auth)
+        .mount(Route::SetStatusIncident, set_status_incident, auth)
+        .mount(Route::ClearStatusIncident, clear_status_incident, auth)
+        .with_state(handlers.status.clone());
+
+    // Service banner for API consumers, set by admins
+    let banner_routes = Router::new()
+        .mount(Route::GetBanner, get_banner, auth)
+        .mount(Route::SetBanner, set_banner, auth)
+        .mount(Route::ClearBanner, clear_banner, auth)
+        .with_state(handlers.banner.clone());
+
+    // Public routes (no authentication required)
+    let public_routes = Router::new()
+        .mount(Route::Health, health_check, auth)
+        .mount(Route::RefreshToken, auth_handlers::refresh_token, auth)
+        .mount(Route::ForgotPassword, auth_handlers::forgot_password, auth)
+        .mount(Route::ResetPassword, auth_handlers::reset_password, auth)
+        .mount(Route::ConfirmDevice, auth_handlers::confirm_device, auth)
+        .mount(Route::StartPasskeyLogin, auth_handlers::start_passkey_login, auth)
+        .mount(Route::FinishPasskeyLogin, auth_handlers::finish_passkey_login, auth)
+        .with_state(handlers.auth.clone());
+
+    // Credential endpoints, throttled per client IP and email (or client id)
+    // when enabled
+    let mut credential_routes = Router::new()
+        .mount(Route::Register, auth_handlers::register, auth)
+        .mount(Route::Login, auth_handlers::login, auth)
+        .mount(Route::ClientCredentialsToken, auth_handlers::client_credentials_token, auth);
+    if let Some(limiter) = handlers.login_rate_limit.clone() {
+        credential_routes = credential_routes.layer(middleware::from_fn_with_state(
+            limiter,
+            login_rate_limit_middleware,
+        ));
+    }
+    let credential_routes = credential_routes.with_state(handlers.auth.clone());
+
+    // Token verification keys for other services
+    let jwks_routes = Router::new()
+        .mount(Route::Jwks, auth_handlers::jwks, auth)
+        .with_state(auth_state.jwt_service.clone());
+
+    // Token introspection, for services holding client credentials
+    let introspection_routes = Router::new()
+        .mount(Route::IntrospectToken, auth_handlers::introspect_token, auth)
+        .with_state(handlers.auth.clone());
+
+    // The caller's own account
+    let protected_auth_routes = Router::new()
+        .mount(Route::Me, auth_handlers::me, auth)
+        .mount(Route::DeleteMe, auth_handlers::delete_me, auth)
+        .mount(Route::Logout, auth_handlers::logout, auth)
+        .mount(Route::SwitchTenant, auth_handlers::switch_tenant, auth)
+        .mount(Route::CheckPermission, auth_handlers::check_permission, auth)
+        .mount(Route::ListRelationUsers, auth_handlers::list_users, auth)
+        .mount(Route::ListRelationObjects, auth_handlers::list_objects, auth)
+        .mount(Route::ListSessions, auth_handlers::list_sessions, auth)
+        .mount(Route::RevokeSession, auth_handlers::revoke_session, auth)
+        .mount(Route::StartPasskeyRegistration, auth_handlers::start_passkey_registration, auth)
+        .mount(Route::FinishPasskeyRegistration, auth_handlers::finish_passkey_registration, auth)
+        .mount(Route::ListPasskeys, auth_handlers::list_passkeys, auth)
+        .mount(Route::DeletePasskey, auth_handlers::delete_passkey, auth)
+        .mount(Route::CreatePersonalAccessToken, auth_handlers::create_personal_access_token, auth)
+        .mount(Route::ListPersonalAccessTokens, auth_handlers::list_personal_access_tokens, auth)
+        .mount(Route::RevokePersonalAccessToken, auth_handlers::revoke_personal_access_token, auth)
+        .with_state(handlers.auth.clone());
+
+    let avatar_body_limit = handlers.user.avatar_body_limit();
+    let user_routes = Router::new()
+        .mount(
+            Route::CreateUser,
+            user::create_user.layer(middleware::from_fn(verify_body_digest)),
+            auth,
+        )
+        .mount(Route::ListUsers, user::get_users, auth)
+        .mount(Route::GetUser, user::get_user, auth)
+        .mount(
+            Route::UpdateUser,
+            user::update_user.layer(middleware::from_fn(verify_body_digest)),
+            auth,
+        )
+        .mount(Route::DeleteUser, user::delete_user, auth)
+        .mount(
+            Route::UploadAvatar,
+            user::upload_avatar.layer(DefaultBodyLimit::max(avatar_body_limit)),
+            auth,
+        )
+        .mount(Route::GetAvatarFile, user::get_avatar_file, auth)
+        .mount(Route::ListUserSummaries, user::list_user_summaries, auth)
+        .mount(Route::BulkDeleteUsers, user::bulk_delete_users, auth)
+        .mount(Route::BulkUpdateUserRoles, user::bulk_update_user_roles, auth)
+        .mount(Route::SuspendUser, user::suspend_user, auth)
+        .mount(Route::ReactivateUser, user::reactivate_user, auth)
+        .with_state(handlers.user);
+
+    // OpenFGA model management, tuple inspection and service accounts
+    let admin_authorization_routes = Router::new()
+        .mount(Route::ListAuthorizationModels, auth_handlers::list_authorization_models, auth)
+        .mount(
+            Route::WriteAuthorizationModel,
+            auth_handlers::write_authorization_model.layer(middleware::from_fn(verify_body_digest)),
+            auth,
+        )
+        .mount(Route::GetAuthorizationModel, auth_handlers::get_authorization_model, auth)
+        .mount(Route::ListRelationships, auth_handlers::list_relationships, auth)
+        .mount(Route::GetUserAccess, auth_handlers::get_user_access, auth)
+        .mount(Route::CreateServiceAccount, auth_handlers::create_service_account, auth)
+        .mount(Route::ListServiceAccounts, auth_handlers::list_service_accounts, auth)
+        .mount(Route::DisableServiceAccount, auth_handlers::disable_service_account, auth)
+        .mount(Route::RevokeAllSessions, auth_handlers::revoke_all_sessions, auth)
+        .with_state(handlers.auth.clone());
+
+    let tenant_routes = Router::new()
+        .mount(Route::GetTenantSettings, get_tenant_settings, auth)
+        .mount(
+            Route::UpdateTenantSettings,
+            update_tenant_settings.layer(middleware::from_fn(verify_body_digest)),
+            auth,
+        )
+        .with_state(handlers.tenant);
+
+    let organization_routes = Router::new()
+        .mount(Route::CreateOrganization, organization::create_organization, auth)
+        .mount(Route::ListOrganizations, organization::list_organizations, auth)
+        .mount(Route::GetOrganization, organization::get_organization, auth)
+        .mount(Route::UpdateOrganization, organization::update_organization, auth)
+        .mount(Route::DeleteOrganization, organization::delete_organization, auth)
+        .mount(Route::ListOrganizationMembers, organization::list_organization_members, auth)
+        .mount(Route::AddOrganizationMember, organization::add_organization_member, auth)
+        .mount(Route::UpdateOrganizationMember, organization::update_organization_member, auth)
+        .mount(Route::RemoveOrganizationMember, organization::remove_organization_member, auth)
+        .mount(Route::ListAllOrganizations, organization::list_all_organizations, auth)
+        .with_state(handlers.organizations);
+
+    // Background job queue
+    let admin_job_routes = Router::new()
+        .mount(Route::ListJobs, list_jobs, auth)
+        .mount(Route::GetJob, get_job, auth)
+        .with_state(handlers.jobs);
+
+    // Audit log
+    let admin_audit_routes = Router::new()
+        .mount(Route::ListAuditEvents, list_audit_events, auth)
+        .with_state(handlers.audit);
+
+    // Runtime logging controls
+    let admin_logging_routes = Router::new()
+        .mount(Route::GetLogLevel, get_log_level, auth)
+        .mount(Route::UpdateLogLevel, update_log_level, auth);
+
+    // Downstream dependency stats
+    let admin_dependency_routes = Router::new()
+        .mount(Route::GetDependencies, get_dependencies, auth)
+        .with_state(handlers.dependencies);
+
+    // Admin live log/metric tail, only when enabled
+    let live_tail_routes = match handlers.live_tail {
+        Some(live_tail_handlers) => Router::new()
+            .mount(Route::LiveTail, live_tail, auth)
+            .mount(Route::GetTraceBundle, get_trace_bundle, auth)
+            .with_state(live_tail_handlers),
+        None => Router::new(),
+    };
+
+    // Admin API usage report, only when tracking is enabled
+    let api_usage_routes = match handlers.api_usage {
+        Some(api_usage) => Router::new()
+            .mount(Route::GetApiUsage, get_api_usage, auth)
+            .with_state(api_usage),
+        None => Router::new(),
+    };
+
+    // Admin client analytics report, only when tracking is enabled
+    let client_analytics_routes = match handlers.client_analytics {
+        Some(analytics) => Router::new()
+            .mount(Route::GetClientAnalytics, get_client_analytics, auth)
+            .with_state(analytics),
+        None => Router::new(),
+    };
+
+    // Combine routes
+    public_routes
+        .merge(credential_routes)
+        .merge(lifecycle_routes)
+        .merge(status_routes)
+        .merge(banner_routes)
+        .merge(jwks_routes)
+        .merge(introspection_routes)
+        .merge(protected_auth_routes)
+        .merge(user_routes)
+        .merge(admin_authorization_routes)
+        .merge(tenant_routes)
+        .merge(organization_routes)
+        .merge(admin_job_routes)
+        .merge(admin_audit_routes)
+        .merge(admin_logging_routes)
+        .merge(admin_dependency_routes)
+        .merge(live_tail_routes)
+        .merge(api_usage_routes)
+        .merge(client_analytics_routes)
+}
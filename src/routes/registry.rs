@@ -0,0 +1,196 @@
+//! Every route the API serves, with its method, path template and auth policy.
+//!
+//! [`create_routes`](super::create_routes) mounts handlers through
+//! [`Route::guard`], so the middleware protecting a route always matches the
+//! policy listed here; the OpenAPI document takes each operation's security
+//! from the same table, and the route tests walk [`Route::ALL`].
+
+use crate::auth::middleware::{
+    auth_middleware, require_role, require_scope, require_service_account, AuthState,
+};
+use crate::auth::models::{roles, scopes};
+use axum::{http::Method, middleware, routing::MethodRouter};
+
+/// Who may call a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthPolicy {
+    /// No credentials needed
+    Public,
+    /// Any signed-in caller; the handler may check more, e.g. OpenFGA relations
+    Authenticated,
+    /// Signed in with this role
+    Role(&'static str),
+    /// Signed in, with this scope if the token is scoped
+    Scope(&'static str),
+    /// Signed in as a service account
+    Service,
+}
+
+impl AuthPolicy {
+    pub fn requires_authentication(self) -> bool {
+        self != AuthPolicy::Public
+    }
+}
+
+macro_rules! route_table {
+    (
+        $(
+            $(#[$meta:meta])*
+            $variant:ident => $method:ident $path:literal, $policy:expr;
+        )+
+    ) => {
+        /// A route in the API, see the module docs
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Route {
+            $($(#[$meta])* $variant),+
+        }
+
+        impl Route {
+            pub const ALL: &'static [Route] = &[$(Route::$variant),+];
+
+            pub fn method(self) -> Method {
+                match self {
+                    $(Route::$variant => Method::$method,)+
+                }
+            }
+
+            /// Path template, in axum's `{param}` syntax
+            pub const fn path(self) -> &'static str {
+                match self {
+                    $(Route::$variant => $path,)+
+                }
+            }
+
+            pub const fn policy(self) -> AuthPolicy {
+                use AuthPolicy::*;
+                match self {
+                    $(Route::$variant => $policy,)+
+                }
+            }
+        }
+    };
+}
+
+route_table! {
+    // Health and deploy tooling
+    Health => GET "/health", Public;
+    Ready => GET "/ready", Public;
+    Status => GET "/status", Public;
+    GetBanner => GET "/api/v1/banner", Public;
+    Warmup => POST "/internal/warmup", Service;
+    Jwks => GET "/.well-known/jwks.json", Public;
+
+    // Credentials and recovery
+    Register => POST "/api/v1/auth/register", Public;
+    Login => POST "/api/v1/auth/login", Public;
+    RefreshToken => POST "/api/v1/auth/refresh", Public;
+    ClientCredentialsToken => POST "/api/v1/auth/token", Public;
+    IntrospectToken => POST "/api/v1/auth/introspect", Service;
+    ForgotPassword => POST "/api/v1/auth/forgot-password", Public;
+    ResetPassword => POST "/api/v1/auth/reset-password", Public;
+    ConfirmDevice => POST "/api/v1/auth/confirm-device", Public;
+    StartPasskeyLogin => POST "/api/v1/auth/webauthn/login/start", Public;
+    FinishPasskeyLogin => POST "/api/v1/auth/webauthn/login/finish", Public;
+
+    // The caller's own account
+    Me => GET "/api/v1/auth/me", Authenticated;
+    DeleteMe => DELETE "/api/v1/auth/me", Authenticated;
+    Logout => POST "/api/v1/auth/logout", Authenticated;
+    SwitchTenant => POST "/api/v1/auth/switch-tenant", Authenticated;
+    CheckPermission => POST "/api/v1/auth/check-permission", Authenticated;
+    ListRelationUsers => GET "/api/v1/auth/list-users", Authenticated;
+    ListRelationObjects => GET "/api/v1/auth/list-objects", Authenticated;
+    ListSessions => GET "/api/v1/auth/sessions", Authenticated;
+    RevokeSession => DELETE "/api/v1/auth/sessions/{id}", Authenticated;
+    StartPasskeyRegistration => POST "/api/v1/auth/webauthn/register/start", Authenticated;
+    FinishPasskeyRegistration => POST "/api/v1/auth/webauthn/register/finish", Authenticated;
+    ListPasskeys => GET "/api/v1/auth/webauthn/credentials", Authenticated;
+    DeletePasskey => DELETE "/api/v1/auth/webauthn/credentials/{id}", Authenticated;
+    CreatePersonalAccessToken => POST "/api/v1/auth/tokens", Authenticated;
+    ListPersonalAccessTokens => GET "/api/v1/auth/tokens", Authenticated;
+    RevokePersonalAccessToken => DELETE "/api/v1/auth/tokens/{id}", Authenticated;
+
+    // Users
+    CreateUser => POST "/api/v1/users", Scope(scopes::USERS_WRITE);
+    ListUsers => GET "/api/v1/users", Scope(scopes::USERS_READ);
+    GetUser => GET "/api/v1/users/{id}", Scope(scopes::USERS_READ);
+    UpdateUser => PUT "/api/v1/users/{id}", Scope(scopes::USERS_WRITE);
+    DeleteUser => DELETE "/api/v1/users/{id}", Scope(scopes::USERS_WRITE);
+    UploadAvatar => PUT "/api/v1/users/{id}/avatar", Scope(scopes::USERS_WRITE);
+    /// Local-disk avatars; the signed URL is the credential
+    GetAvatarFile => GET "/api/v1/avatars/{user_id}/{file}", Public;
+
+    // Tenant settings: readable by members, writable by admins
+    GetTenantSettings => GET "/api/v1/tenant/settings", Authenticated;
+    UpdateTenantSettings => PUT "/api/v1/tenant/settings", Role(roles::ADMIN);
+
+    // Organizations; the service checks the caller's relation on each one
+    CreateOrganization => POST "/api/v1/organizations", Authenticated;
+    ListOrganizations => GET "/api/v1/organizations", Authenticated;
+    GetOrganization => GET "/api/v1/organizations/{id}", Authenticated;
+    UpdateOrganization => PUT "/api/v1/organizations/{id}", Authenticated;
+    DeleteOrganization => DELETE "/api/v1/organizations/{id}", Authenticated;
+    ListOrganizationMembers => GET "/api/v1/organizations/{id}/members", Authenticated;
+    AddOrganizationMember => POST "/api/v1/organizations/{id}/members", Authenticated;
+    UpdateOrganizationMember => PUT "/api/v1/organizations/{id}/members/{user_id}", Authenticated;
+    RemoveOrganizationMember => DELETE "/api/v1/organizations/{id}/members/{user_id}", Authenticated;
+
+    // Administration
+    ListUserSummaries => GET "/api/v1/admin/users", Role(roles::ADMIN);
+    BulkDeleteUsers => POST "/api/v1/admin/users/bulk-delete", Role(roles::ADMIN);
+    BulkUpdateUserRoles => POST "/api/v1/admin/users/bulk-roles", Role(roles::ADMIN);
+    SuspendUser => POST "/api/v1/admin/users/{id}/suspend", Role(roles::ADMIN);
+    ReactivateUser => POST "/api/v1/admin/users/{id}/reactivate", Role(roles::ADMIN);
+    ListAuthorizationModels => GET "/api/v1/admin/authorization-models", Role(roles::ADMIN);
+    WriteAuthorizationModel => POST "/api/v1/admin/authorization-models", Role(roles::ADMIN);
+    GetAuthorizationModel => GET "/api/v1/admin/authorization-models/{id}", Role(roles::ADMIN);
+    ListRelationships => GET "/api/v1/admin/relationships", Role(roles::ADMIN);
+    GetUserAccess => GET "/api/v1/admin/users/{id}/access", Role(roles::ADMIN);
+    CreateServiceAccount => POST "/api/v1/admin/service-accounts", Role(roles::ADMIN);
+    ListServiceAccounts => GET "/api/v1/admin/service-accounts", Role(roles::ADMIN);
+    DisableServiceAccount => DELETE "/api/v1/admin/service-accounts/{id}", Role(roles::ADMIN);
+    RevokeAllSessions => POST "/api/v1/admin/sessions/revoke-all", Role(roles::ADMIN);
+    ListAllOrganizations => GET "/api/v1/admin/organizations", Role(roles::ADMIN);
+    ListJobs => GET "/api/v1/admin/jobs", Role(roles::ADMIN);
+    GetJob => GET "/api/v1/admin/jobs/{id}", Role(roles::ADMIN);
+    ListAuditEvents => GET "/api/v1/admin/audit-events", Role(roles::ADMIN);
+    GetLogLevel => GET "/internal/admin/log-level", Role(roles::ADMIN);
+    UpdateLogLevel => PUT "/internal/admin/log-level", Role(roles::ADMIN);
+    GetDependencies => GET "/api/v1/admin/dependencies", Role(roles::ADMIN);
+    SetStatusIncident => PUT "/api/v1/admin/status/incident", Role(roles::ADMIN);
+    ClearStatusIncident => DELETE "/api/v1/admin/status/incident", Role(roles::ADMIN);
+    SetBanner => PUT "/api/v1/admin/banner", Role(roles::ADMIN);
+    ClearBanner => DELETE "/api/v1/admin/banner", Role(roles::ADMIN);
+    /// Only mounted with `live_tail.enabled`
+    LiveTail => GET "/internal/admin/tail", Role(roles::ADMIN);
+    /// Only mounted with `live_tail.enabled`
+    GetTraceBundle => GET "/internal/admin/traces/{trace_id}", Role(roles::ADMIN);
+    /// Only mounted with `api_usage.enabled`
+    GetApiUsage => GET "/api/v1/admin/api-usage", Role(roles::ADMIN);
+    /// Only mounted with `client_analytics.enabled`
+    GetClientAnalytics => GET "/api/v1/admin/clients", Role(roles::ADMIN);
+}
+
+impl Route {
+    /// Whether the route has an operation in the OpenAPI document; the live
+    /// tail is a WebSocket upgrade, which OpenAPI can't describe
+    pub fn documented(self) -> bool {
+        self != Route::LiveTail
+    }
+
+    /// Wrap `handler` in the middleware enforcing this route's policy
+    pub fn guard<S>(self, handler: MethodRouter<S>, auth_state: &AuthState) -> MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let handler = match self.policy() {
+            AuthPolicy::Public => return handler,
+            AuthPolicy::Authenticated => handler,
+            AuthPolicy::Role(role) => handler.layer(middleware::from_fn(require_role(role))),
+            AuthPolicy::Scope(scope) => handler.layer(middleware::from_fn(require_scope(scope))),
+            AuthPolicy::Service => handler.layer(middleware::from_fn(require_service_account)),
+        };
+
+        handler.layer(middleware::from_fn_with_state(auth_state.clone(), auth_middleware))
+    }
+}
@@ -0,0 +1,104 @@
+use crate::config::AuditConfig;
+use crate::errors::Result;
+use crate::models::{AuditCursor, AuditEntry, AuditEvent, AuditFilter};
+use crate::repositories::AuditRepository;
+use crate::request_context::RequestContext;
+use chrono::{Duration as ChronoDuration, Utc};
+use std::time::Duration;
+
+/// Writes and queries the audit log
+///
+/// Events are stamped with the current request's trace id, and its client IP
+/// unless they carry one, so an entry can be followed back to the request
+/// that caused it.
+#[derive(Clone)]
+pub struct AuditService {
+    repository: AuditRepository,
+}
+
+impl AuditService {
+    pub fn new(repository: AuditRepository) -> Self {
+        Self { repository }
+    }
+
+    /// Record an event, failing if it can't be written
+    pub async fn record(&self, event: AuditEvent) -> Result<()> {
+        let event = match event.trace_id {
+            Some(_) => event,
+            None => event.with_trace_id(crate::telemetry::current_trace_id()),
+        };
+        let event = match (&event.ip_address, RequestContext::current()) {
+            (None, Some(context)) => event.with_ip_address(context.client_ip),
+            _ => event,
+        };
+        self.repository.record(&event).await
+    }
+
+    /// Record an event for a change that has already happened; a failure is
+    /// logged rather than undoing it
+    pub async fn record_best_effort(&self, event: AuditEvent) {
+        let action = event.action;
+        let tenant_id = event.tenant_id.clone();
+        if let Err(e) = self.record(event).await {
+            tracing::error!(action, tenant_id = %tenant_id, error = %e, "Failed to record audit event");
+        }
+    }
+
+    /// A tenant's entries, newest first
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        filter: &AuditFilter,
+        after: Option<AuditCursor>,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>> {
+        self.repository.list(tenant_id, filter, after, limit).await
+    }
+
+    /// Delete entries older than `retention`, returning how many were removed
+    pub async fn purge_older_than(&self, retention: ChronoDuration) -> Result<u64> {
+        self.repository.delete_before(Utc::now() - retention).await
+    }
+}
+
+/// Periodically deletes audit log entries past the retention period
+pub struct AuditRetention {
+    audit: AuditService,
+    retention: ChronoDuration,
+    interval: Duration,
+}
+
+impl AuditRetention {
+    /// `None` when entries are kept forever
+    pub fn from_config(config: &AuditConfig, audit: AuditService) -> Option<Self> {
+        (config.retention_days > 0).then(|| Self {
+            audit,
+            retention: ChronoDuration::days(i64::from(config.retention_days)),
+            interval: Duration::from_secs(config.purge_interval_seconds.max(1)),
+        })
+    }
+
+    pub fn retention(&self) -> ChronoDuration {
+        self.retention
+    }
+
+    /// Purge until the process exits; a failed purge is retried next interval
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match self.audit.purge_older_than(self.retention).await {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!(
+                    deleted,
+                    retention_days = self.retention.num_days(),
+                    "Purged expired audit log entries"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Failed to purge audit log"),
+            }
+        }
+    }
+}
@@ -1,47 +1,131 @@
 use crate::auth::jwt::JwtService;
 use crate::auth::models::{
-    AuthContext, LoginRequest, LoginResponse, RegisterRequest, UserInfo, roles,
+    AccessSource, AuthContext, ClientCredentialsRequest, ClientCredentialsResponse,
+    CreatePersonalAccessTokenRequest, CreateServiceAccountRequest, CreatedPersonalAccessToken,
+    CreatedServiceAccount, IntrospectionResponse, LoginRequest, LoginResponse, ObjectAccess,
+    PersonalAccessTokenInfo, TokenSessionStatus,
+    RefreshRotation, RegisterRequest, RelationAccess, RevokeAllSessionsRequest,
+    RevokeAllSessionsResponse, ServiceAccountInfo, SessionInfo,
+    SessionMetadata, SubjectType, SwitchTenantResponse, UserAccessReport, UserInfo, object_types, relations,
+    hash_password, validate_password, verify_password, CLIENT_CREDENTIALS_GRANT,
+    PERSONAL_ACCESS_TOKEN_PREFIX, SERVICE_ACCOUNT_SECRET_PREFIX,
 };
-use crate::auth::openfga::OpenFgaService;
+use crate::auth::openfga::{OpenFgaService, RelationshipTuple, TupleFilter};
+use crate::auth::registry::{self, ObjectType, Relation};
+use crate::auth::session::SessionValidator;
+use crate::auth::webauthn::{
+    PasskeyInfo, PasskeyLoginFinish, PasskeyLoginStart, PasskeyLoginStartRequest,
+    PasskeyRegistrationFinish, PasskeyRegistrationStart, WebAuthnVerifier,
+};
+use crate::config::{AuthConfig, NewDeviceConfig, PasswordResetConfig, RememberMeConfig};
 use crate::errors::{AppError, Result};
-use crate::models::CreateUserRequest;
+use crate::metrics::AppMetrics;
+use crate::models::format::ResponseFormat;
+use crate::models::{
+    audit_actions, audit_resources, AuditEvent, BulkResult, BulkRoleAction, BulkUserRolesRequest,
+    CreateUserRequest, UserResponse, UserStatus, DEFAULT_TENANT,
+};
 use crate::repositories::Repositories;
+use crate::services::audit::AuditService;
+use crate::services::fanout::FanOut;
+use crate::services::mailer::{EmailMessage, Mailer};
+use crate::services::onboarding::{self, ConfiguredOnboarding, OnboardingPolicy};
+use crate::services::organization::OrganizationService;
+use crate::services::tenant::TenantSettingsService;
 use crate::services::user::UserService;
-use bcrypt::{hash, verify, DEFAULT_COST};
+use crate::utils::id;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures::TryStreamExt;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use uuid::Uuid;
 
+const PASSKEY_REGISTRATION: &str = "registration";
+const PASSKEY_AUTHENTICATION: &str = "authentication";
+
 #[derive(Clone)]
 pub struct AuthService {
     repositories: Arc<Repositories>,
     user_service: Arc<UserService>,
     jwt_service: Arc<JwtService>,
     openfga_service: Arc<OpenFgaService>,
+    mailer: Arc<dyn Mailer>,
+    password_reset: PasswordResetConfig,
+    new_device: NewDeviceConfig,
+    webauthn: WebAuthnVerifier,
+    sessions: Arc<SessionValidator>,
+    tenant_settings: Arc<TenantSettingsService>,
+    organizations: Arc<OrganizationService>,
+    fanout: FanOut,
+    audit: AuditService,
+    remember_me: RememberMeConfig,
+    onboarding: Arc<dyn OnboardingPolicy>,
 }
 
 impl AuthService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         repositories: Arc<Repositories>,
         user_service: Arc<UserService>,
         jwt_service: Arc<JwtService>,
         openfga_service: Arc<OpenFgaService>,
+        mailer: Arc<dyn Mailer>,
+        sessions: Arc<SessionValidator>,
+        tenant_settings: Arc<TenantSettingsService>,
+        organizations: Arc<OrganizationService>,
+        fanout: FanOut,
+        config: &AuthConfig,
     ) -> Self {
         Self {
+            audit: AuditService::new(repositories.audit.clone()),
             repositories,
             user_service,
             jwt_service,
             openfga_service,
+            mailer,
+            password_reset: config.password_reset.clone(),
+            new_device: config.new_device.clone(),
+            webauthn: WebAuthnVerifier::new(&config.webauthn),
+            sessions,
+            tenant_settings,
+            organizations,
+            fanout,
+            remember_me: config.remember_me.clone(),
+            onboarding: Arc::new(ConfiguredOnboarding::new(&config.onboarding)),
         }
     }
 
+    /// Export fan-out metrics
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.fanout = self.fanout.with_metrics(metrics);
+        self
+    }
+
+    /// Onboard new users with `policy` instead of `auth.onboarding`
+    pub fn with_onboarding(mut self, policy: Arc<dyn OnboardingPolicy>) -> Self {
+        self.onboarding = policy;
+        self
+    }
+
+    /// Use `user_service` for the accounts this service manages, e.g. one
+    /// that also cleans up avatars
+    pub fn with_user_service(mut self, user_service: Arc<UserService>) -> Self {
+        self.user_service = user_service;
+        self
+    }
+
     /// Register a new user
-    pub async fn register(&self, request: RegisterRequest) -> Result<LoginResponse> {
-        // Validate password strength
-        self.validate_password(&request.password)?;
+    pub async fn register(
+        &self,
+        request: RegisterRequest,
+        metadata: SessionMetadata,
+    ) -> Result<LoginResponse> {
+        // Validate input before hashing
+        request.validate()?;
+        self.enforce_password_policy(&request.password).await?;
 
         // Hash the password
-        let password_hash = hash(&request.password, DEFAULT_COST)
-            .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?;
+        let password_hash = hash_password(&request.password)?;
 
         // Create user
         let create_user_request = CreateUserRequest {
@@ -49,7 +133,7 @@ impl AuthService {
             username: request.username.clone(),
         };
 
-        let user = self.user_service.create_user(create_user_request).await?;
+        let user = self.user_service.create_user(None, create_user_request).await?;
 
         // Store password hash
         self.repositories
@@ -57,54 +141,54 @@ impl AuthService {
             .create_credentials(user.id, password_hash)
             .await?;
 
-        // Assign default role
-        self.repositories
-            .auth
-            .add_role(user.id, roles::USER.to_string())
-            .await?;
-
-        // Create default relationships in OpenFGA
-        self.openfga_service
-            .write_relationship(user.id, "member", "organization", "default")
-            .await?;
+        self.onboard(&user).await?;
 
         // Get user roles
         let user_roles = self.repositories.auth.get_user_roles(user.id).await?;
+        self.sync_summary(user.id, Some(&user_roles), true).await;
 
-        // Generate JWT token
-        let token = self.jwt_service.generate_token(
-            user.id,
-            user.email.clone(),
-            user.username.clone(),
-            user_roles.clone(),
-        )?;
-
-        // Store session
-        let token_hash = self.hash_token(&token);
-        let expires_at = chrono::Utc::now() + chrono::Duration::hours(24);
-        self.repositories
-            .auth
-            .create_session(user.id, token_hash, expires_at)
+        // Create session and issue a token bound to it
+        let response = self
+            .issue_session(user.id, user.email, user.username, user_roles, &metadata, false)
             .await?;
 
-        let response = LoginResponse {
-            access_token: token,
-            token_type: "Bearer".to_string(),
-            expires_in: 24 * 3600, // 24 hours in seconds
-            user: UserInfo {
-                id: user.id,
-                email: user.email,
-                username: user.username,
-                roles: user_roles,
-            },
-        };
-
-        tracing::info!("User registered successfully: {}", user.id);
+        tracing::info!("User registered successfully: {}", response.user.id);
         Ok(response)
     }
 
+    /// Give a new user what the onboarding policy says they start with
+    async fn onboard(&self, user: &UserResponse) -> Result<()> {
+        for role in self.onboarding.roles(user) {
+            self.repositories.auth.add_role(user.id, role).await?;
+        }
+
+        // Joining writes the membership's OpenFGA tuples
+        for (organization, role) in self.onboarding.memberships(user) {
+            self.organizations.join(&organization, user.id, role, None).await?;
+        }
+
+        let relationships = self.onboarding.relationships(user);
+        let tuples = relationships
+            .iter()
+            .map(|relationship| {
+                let (relation, object_type, object_id) = onboarding::relationship_parts(relationship)?;
+                Ok((user.id, relation, object_type, object_id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.openfga_service.batch_write_relationships(tuples).await?;
+
+        if let Err(e) = self.onboarding.after_registration(user).await {
+            tracing::error!(user_id = %user.id, error = %e, "Post-registration hook failed");
+        }
+        Ok(())
+    }
+
     /// Authenticate user login
-    pub async fn login(&self, request: LoginRequest) -> Result<LoginResponse> {
+    pub async fn login(
+        &self,
+        request: LoginRequest,
+        metadata: SessionMetadata,
+    ) -> Result<LoginResponse> {
         // Get user by email
         let user = self.user_service.get_user_by_email(&request.email).await?;
 
@@ -117,93 +201,361 @@ impl AuthService {
             .ok_or_else(|| AppError::Authentication("Invalid credentials".to_string()))?;
 
         // Verify password
-        let is_valid = verify(&request.password, &credentials.password_hash)
-            .map_err(|e| AppError::Internal(format!("Failed to verify password: {}", e)))?;
+        let is_valid = verify_password(&request.password, &credentials.password_hash)?;
 
         if !is_valid {
             return Err(AppError::Authentication("Invalid credentials".to_string()));
         }
+        ensure_active(user.status)?;
 
         // Get user roles
         let user_roles = self.repositories.auth.get_user_roles(user.id).await?;
+        self.sync_summary(user.id, None, true).await;
 
-        // Generate JWT token
-        let token = self.jwt_service.generate_token(
-            user.id,
-            user.email.clone(),
-            user.username.clone(),
-            user_roles.clone(),
-        )?;
+        // Create session and issue a token bound to it
+        let response = self
+            .issue_session(
+                user.id,
+                user.email,
+                user.username,
+                user_roles,
+                &metadata,
+                request.remember_me,
+            )
+            .await?;
 
-        // Store session
-        let token_hash = self.hash_token(&token);
-        let expires_at = chrono::Utc::now() + chrono::Duration::hours(24);
-        self.repositories
+        tracing::info!("User logged in successfully: {}", response.user.id);
+        Ok(response)
+    }
+
+    /// Refresh token grant: rotate the refresh token and issue a new access
+    /// token for the same session
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<LoginResponse> {
+        let new_refresh_token = JwtService::generate_opaque_token();
+
+        let rotation = self
+            .repositories
             .auth
-            .create_session(user.id, token_hash, expires_at)
+            .rotate_refresh_token(
+                &JwtService::hash_opaque_token(refresh_token),
+                &JwtService::hash_opaque_token(&new_refresh_token),
+                self.remember_me.idle_timeout(),
+            )
             .await?;
 
-        let response = LoginResponse {
-            access_token: token,
+        let (session_id, user_id, expires_at) = match rotation {
+            RefreshRotation::Rotated {
+                session_id,
+                user_id,
+                expires_at,
+            } => (session_id, user_id, expires_at),
+            RefreshRotation::Reused { session_id, user_id } => {
+                self.sessions.invalidate(session_id).await;
+                tracing::warn!(
+                    session_id = %session_id,
+                    user_id = %user_id,
+                    "Refresh token reuse detected, session revoked"
+                );
+                return Err(AppError::Authentication("Invalid refresh token".to_string()));
+            }
+            RefreshRotation::Invalid => {
+                return Err(AppError::Authentication("Invalid refresh token".to_string()));
+            }
+        };
+
+        // Get fresh user details and roles from database
+        let user = self.user_service.get_user_by_id(user_id).await?;
+        ensure_active(user.status)?;
+        let user_roles = self.repositories.auth.get_user_roles(user_id).await?;
+
+        // Keep the tenant the session switched to, while still a member
+        let tenant = match self
+            .repositories
+            .auth
+            .get_session(session_id)
+            .await?
+            .and_then(|session| session.scope)
+        {
+            Some(tenant) if self.is_tenant_member(user_id, &tenant).await? => Some(tenant),
+            _ => None,
+        };
+
+        let user_state = self.repositories.auth.user_token_state(user_id).await?;
+        let access_token = match tenant {
+            Some(tenant) => self.jwt_service.generate_tenant_token(
+                user_id,
+                user.email.clone(),
+                user.username.clone(),
+                user_roles.clone(),
+                session_id,
+                tenant,
+                user_state,
+            )?,
+            None => self.jwt_service.generate_session_token(
+                user_id,
+                user.email.clone(),
+                user.username.clone(),
+                user_roles.clone(),
+                session_id,
+                user_state,
+            )?,
+        };
+
+        Ok(LoginResponse {
+            access_token,
             token_type: "Bearer".to_string(),
-            expires_in: 24 * 3600, // 24 hours in seconds
+            expires_in: self.jwt_service.expires_in(),
+            refresh_token: new_refresh_token,
+            refresh_expires_in: (expires_at - chrono::Utc::now()).num_seconds().max(0) as u64,
             user: UserInfo {
-                id: user.id,
+                id: user_id,
                 email: user.email,
                 username: user.username,
                 roles: user_roles,
             },
-        };
+        })
+    }
 
-        tracing::info!("User logged in successfully: {}", user.id);
-        Ok(response)
+    /// Logout user (revoke session)
+    pub async fn logout(&self, auth_context: &AuthContext) -> Result<()> {
+        match auth_context.session_id {
+            Some(session_id) => {
+                self.repositories.auth.revoke_session(session_id).await?;
+                self.sessions.invalidate(session_id).await;
+                self.audit
+                    .record_best_effort(
+                        actor_event(auth_context, audit_actions::LOGGED_OUT)
+                            .with_resource(audit_resources::SESSION, session_id),
+                    )
+                    .await;
+                tracing::debug!("Revoked session {} for user {}", session_id, auth_context.user_id);
+            }
+            None => {
+                tracing::debug!(
+                    "Token for user {} carries no session ID; nothing to revoke",
+                    auth_context.user_id
+                );
+            }
+        }
+        Ok(())
     }
 
-    /// Refresh JWT token
-    pub async fn refresh_token(&self, auth_context: &AuthContext) -> Result<LoginResponse> {
-        // Get fresh user roles from database
-        let user_roles = self
+    /// Issue a token for the same session that acts in another tenant
+    pub async fn switch_tenant(
+        &self,
+        auth_context: &AuthContext,
+        tenant: &str,
+        metadata: SessionMetadata,
+    ) -> Result<SwitchTenantResponse> {
+        let tenant = tenant.trim();
+        if tenant.is_empty() || tenant.len() > 255 || tenant.contains(':') {
+            return Err(AppError::Validation("Invalid tenant".to_string()));
+        }
+
+        // The session remembers the tenant so refreshed tokens keep it
+        let session_id = auth_context.session_id.ok_or_else(|| {
+            AppError::BadRequest("Switching tenants requires a session token".to_string())
+        })?;
+
+        if !self.is_tenant_member(auth_context.user_id, tenant).await? {
+            tracing::warn!(
+                "User {} tried to switch to tenant '{}' without membership",
+                auth_context.user_id,
+                tenant
+            );
+            return Err(AppError::Forbidden);
+        }
+
+        if !self
             .repositories
             .auth
-            .get_user_roles(auth_context.user_id)
-            .await?;
+            .set_session_scope(auth_context.user_id, session_id, tenant)
+            .await?
+        {
+            return Err(AppError::Authentication(
+                "Session has been revoked or has expired".to_string(),
+            ));
+        }
 
-        // Generate new JWT token
-        let token = self.jwt_service.generate_token(
+        let user_state = self.repositories.auth.user_token_state(auth_context.user_id).await?;
+        let access_token = self.jwt_service.generate_tenant_token(
             auth_context.user_id,
             auth_context.email.clone(),
             auth_context.username.clone(),
-            user_roles.clone(),
+            auth_context.roles.clone(),
+            session_id,
+            tenant.to_string(),
+            user_state,
         )?;
 
-        // Store new session
-        let token_hash = self.hash_token(&token);
-        let expires_at = chrono::Utc::now() + chrono::Duration::hours(24);
-        self.repositories
-            .auth
-            .create_session(auth_context.user_id, token_hash, expires_at)
+        self.audit
+            .record(
+                AuditEvent::new(tenant, Some(auth_context.user_id), audit_actions::TENANT_SWITCHED)
+                    .with_details(serde_json::json!({
+                        "from": auth_context.tenant(),
+                        "session_id": session_id,
+                    }))
+                    .with_ip_address(metadata.ip_address),
+            )
             .await?;
 
-        let response = LoginResponse {
-            access_token: token,
+        tracing::info!(
+            "User {} switched from tenant '{}' to '{}'",
+            auth_context.user_id,
+            auth_context.tenant(),
+            tenant
+        );
+        Ok(SwitchTenantResponse {
+            access_token,
             token_type: "Bearer".to_string(),
-            expires_in: 24 * 3600, // 24 hours in seconds
-            user: UserInfo {
-                id: auth_context.user_id,
-                email: auth_context.email.clone(),
-                username: auth_context.username.clone(),
-                roles: user_roles,
-            },
+            expires_in: self.jwt_service.expires_in(),
+            tenant: tenant.to_string(),
+        })
+    }
+
+    /// Organizations the user is a member of, i.e. the tenants they can switch to
+    pub async fn available_tenants(&self, user_id: Uuid) -> Result<Vec<String>> {
+        let prefix = format!("{}:", object_types::ORGANIZATION);
+        let mut organizations = self
+            .openfga_service
+            .list_objects_stream(user_id, relations::MEMBER, object_types::ORGANIZATION)
+            .await?;
+
+        let mut tenants = Vec::new();
+        while let Some(object) = organizations.try_next().await? {
+            if let Some(tenant) = object.strip_prefix(&prefix) {
+                tenants.push(tenant.to_string());
+            }
+        }
+        tenants.sort();
+        Ok(tenants)
+    }
+
+    /// Access report for a user: their roles and every object they hold a
+    /// relation on, optionally for one object type only
+    pub async fn access_report(
+        &self,
+        user_id: Uuid,
+        object_type: Option<&str>,
+    ) -> Result<UserAccessReport> {
+        let object_types = match object_type {
+            Some(object_type) => vec![registry::validate_object_type(object_type)?],
+            None => ObjectType::ALL.to_vec(),
         };
 
-        Ok(response)
+        self.user_service.get_user_by_id(user_id).await?;
+        let roles = self.repositories.auth.get_user_roles(user_id).await?;
+        let objects = self.object_access(user_id, &object_types).await?;
+
+        Ok(UserAccessReport {
+            user_id,
+            roles,
+            object_types: object_types.iter().map(|t| t.to_string()).collect(),
+            objects,
+        })
     }
 
-    /// Logout user (revoke session)
-    pub async fn logout(&self, token: &str) -> Result<()> {
-        let token_hash = self.hash_token(token);
-        self.repositories.auth.revoke_session(&token_hash).await?;
-        Ok(())
+    /// Objects of `object_types` the user holds a relation on, each relation
+    /// marked with where it comes from
+    ///
+    /// Asks OpenFGA for every relation the model defines, so relations the
+    /// model derives are included, then compares with the user's direct
+    /// tuples. A derived relation counts as implied when the user holds some
+    /// other relation on the object directly, and as inherited otherwise.
+    pub async fn object_access(
+        &self,
+        user_id: Uuid,
+        object_types: &[ObjectType],
+    ) -> Result<Vec<ObjectAccess>> {
+        let queries: Vec<(ObjectType, Relation)> = object_types
+            .iter()
+            .flat_map(|object_type| {
+                object_type
+                    .relations()
+                    .iter()
+                    .filter(|relation| !relation.is_parent_link())
+                    .map(move |relation| (*object_type, *relation))
+            })
+            .collect();
+
+        let listed = self
+            .fanout
+            .run("list_objects", queries.iter().copied(), |(object_type, relation)| {
+                self.openfga_service
+                    .list_objects(user_id, relation.as_str(), object_type.as_str())
+            })
+            .await
+            .into_values()?;
+
+        let mut direct: HashMap<String, Vec<String>> = HashMap::new();
+        for object_type in object_types {
+            for tuple in self.direct_tuples(user_id, *object_type).await? {
+                direct.entry(tuple.object).or_default().push(tuple.relation);
+            }
+        }
+        // Strongest first, so `via` names e.g. owner rather than viewer
+        for held in direct.values_mut() {
+            held.sort_by_key(|held| Relation::ALL.iter().position(|r| r.as_str() == held));
+        }
+
+        let mut objects: BTreeMap<String, Vec<RelationAccess>> = BTreeMap::new();
+        for ((_, relation), listed_objects) in queries.iter().zip(listed) {
+            for object in listed_objects {
+                let held = direct.get(&object).map(Vec::as_slice).unwrap_or_default();
+                let (source, via) = if held.iter().any(|r| r == relation.as_str()) {
+                    (AccessSource::Direct, None)
+                } else if let Some(via) = held.first() {
+                    (AccessSource::Implied, Some(via.clone()))
+                } else {
+                    (AccessSource::Inherited, None)
+                };
+                objects.entry(object).or_default().push(RelationAccess {
+                    relation: relation.to_string(),
+                    source,
+                    via,
+                });
+            }
+        }
+
+        Ok(objects
+            .into_iter()
+            .map(|(object, relations)| ObjectAccess { object, relations })
+            .collect())
+    }
+
+    /// Every tuple naming the user directly on objects of one type
+    async fn direct_tuples(
+        &self,
+        user_id: Uuid,
+        object_type: ObjectType,
+    ) -> Result<Vec<RelationshipTuple>> {
+        let filter = TupleFilter {
+            user: Some(format!("user:{}", user_id)),
+            relation: None,
+            object: Some(format!("{}:", object_type)),
+        };
+
+        let mut tuples = Vec::new();
+        let mut continuation_token = String::new();
+        loop {
+            let page = self
+                .openfga_service
+                .read_tuples(&filter, Some(100), Some(&continuation_token))
+                .await?;
+            tuples.extend(page.tuples);
+            if page.continuation_token.is_empty() {
+                return Ok(tuples);
+            }
+            continuation_token = page.continuation_token;
+        }
+    }
+
+    async fn is_tenant_member(&self, user_id: Uuid, tenant: &str) -> Result<bool> {
+        let result = self
+            .openfga_service
+            .check_permission(user_id, relations::MEMBER, object_types::ORGANIZATION, tenant)
+            .await?;
+        Ok(result.allowed)
     }
 
     /// Change user password
@@ -222,19 +574,17 @@ impl AuthService {
             .ok_or_else(|| AppError::NotFound("User credentials not found".to_string()))?;
 
         // Verify current password
-        let is_valid = verify(current_password, &credentials.password_hash)
-            .map_err(|e| AppError::Internal(format!("Failed to verify password: {}", e)))?;
+        let is_valid = verify_password(current_password, &credentials.password_hash)?;
 
         if !is_valid {
             return Err(AppError::Authentication("Invalid current password".to_string()));
         }
 
         // Validate new password
-        self.validate_password(new_password)?;
+        self.enforce_password_policy(new_password).await?;
 
         // Hash new password
-        let new_password_hash = hash(new_password, DEFAULT_COST)
-            .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?;
+        let new_password_hash = hash_password(new_password)?;
 
         // Update password
         self.repositories
@@ -242,32 +592,878 @@ impl AuthService {
             .update_password(user_id, new_password_hash)
             .await?;
 
+        // Access tokens issued before the change stop working; sessions can
+        // still refresh
+        self.sessions.bump_user_token_version(user_id).await?;
+
         tracing::info!("Password changed successfully for user: {}", user_id);
         Ok(())
     }
 
+    /// Delete the caller's account after checking their password again
+    ///
+    /// This is [`UserService::delete_user`] on themselves, which also
+    /// removes their credentials, sessions and tokens.
+    pub async fn delete_account(&self, auth_context: &AuthContext, password: &str) -> Result<()> {
+        if auth_context.subject_type != SubjectType::User {
+            return Err(AppError::Forbidden);
+        }
+        let user_id = auth_context.user_id;
+
+        let invalid = || AppError::Authentication("Invalid password".to_string());
+        let credentials = self
+            .repositories
+            .auth
+            .get_credentials_by_user_id(user_id)
+            .await?
+            .ok_or_else(invalid)?;
+        let is_valid = verify_password(password, &credentials.password_hash)?;
+        if !is_valid {
+            return Err(invalid());
+        }
+
+        self.user_service.delete_user(auth_context, user_id).await?;
+        self.openfga_service.invalidate_user_cache(user_id).await;
+
+        tracing::info!("User deleted their account: {}", user_id);
+        Ok(())
+    }
+
+    /// Start a password reset by emailing a one-time token.
+    ///
+    /// Succeeds whether or not the email is registered, so the endpoint can't
+    /// be used to enumerate accounts.
+    pub async fn forgot_password(&self, email: &str) -> Result<()> {
+        let Some(user) = self.repositories.user.find_by_email(email).await? else {
+            tracing::debug!("Password reset requested for unknown email");
+            return Ok(());
+        };
+
+        let token = JwtService::generate_opaque_token();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::minutes(self.password_reset.token_ttl_minutes as i64);
+
+        self.repositories
+            .auth
+            .create_password_reset_token(user.id, &JwtService::hash_opaque_token(&token), expires_at)
+            .await?;
+
+        let settings = self.tenant_settings.get(DEFAULT_TENANT).await?;
+        let url = format!("{}?token={}", self.password_reset.url, token);
+        let minutes = self.password_reset.token_ttl_minutes;
+
+        let (subject, text) = match settings
+            .email_templates
+            .as_ref()
+            .and_then(|templates| templates.password_reset.as_ref())
+        {
+            Some(template) => template.render(&user.username, &url, minutes),
+            None => (
+                "Reset your password".to_string(),
+                format!(
+                    "Hi {},\n\nUse the link below to reset your password. It expires in {} minutes.\n\n{}\n\nIf you didn't request this, you can ignore this email.",
+                    user.username, minutes, url
+                ),
+            ),
+        };
+
+        let message = EmailMessage {
+            to: user.email,
+            subject,
+            text,
+        };
+
+        // Delivered in the background so response timing doesn't reveal
+        // whether the account exists
+        let mailer = self.mailer.clone();
+        let user_id = user.id;
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(message).await {
+                tracing::error!(user_id = %user_id, error = %e, "Failed to send password reset email");
+            }
+        });
+
+        tracing::info!("Password reset requested for user: {}", user_id);
+        Ok(())
+    }
+
+    /// Complete a password reset; all existing sessions and access tokens
+    /// are revoked
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<()> {
+        self.enforce_password_policy(new_password).await?;
+
+        let new_password_hash = hash_password(new_password)?;
+
+        let user_id = self
+            .repositories
+            .auth
+            .reset_password_with_token(&JwtService::hash_opaque_token(token), new_password_hash)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired reset token".to_string()))?;
+
+        let revoked = self.repositories.auth.revoke_user_sessions(user_id).await?;
+        self.sessions.invalidate_user(user_id).await;
+        self.sessions.bump_user_token_version(user_id).await?;
+
+        // Nobody is signed in; the reset token stands in for the actor
+        self.audit
+            .record_best_effort(
+                AuditEvent::new(DEFAULT_TENANT, None, audit_actions::PASSWORD_RESET)
+                    .with_resource(audit_resources::USER, user_id)
+                    .with_details(serde_json::json!({ "sessions_revoked": revoked })),
+            )
+            .await;
+
+        tracing::info!(
+            "Password reset for user: {} ({} sessions revoked)",
+            user_id,
+            revoked
+        );
+        Ok(())
+    }
+
+    /// Confirm a sign-in from a new device with the emailed one-time token,
+    /// marking its session verified
+    pub async fn confirm_device(&self, token: &str) -> Result<()> {
+        let session = self
+            .repositories
+            .auth
+            .verify_session(&JwtService::hash_opaque_token(token))
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired confirmation token".to_string()))?;
+
+        self.audit
+            .record_best_effort(
+                AuditEvent::new(
+                    session.scope.as_deref().unwrap_or(DEFAULT_TENANT),
+                    Some(session.user_id),
+                    audit_actions::DEVICE_CONFIRMED,
+                )
+                .with_resource(audit_resources::SESSION, session.id),
+            )
+            .await;
+
+        tracing::info!("Device confirmed for session {} of user {}", session.id, session.user_id);
+        Ok(())
+    }
+
+    /// Begin registering a passkey for the signed-in user
+    pub async fn start_passkey_registration(
+        &self,
+        auth_context: &AuthContext,
+    ) -> Result<PasskeyRegistrationStart> {
+        let existing = self
+            .repositories
+            .auth
+            .list_webauthn_credentials(auth_context.user_id)
+            .await?;
+
+        let challenge = WebAuthnVerifier::new_challenge();
+        let challenge_id = self
+            .repositories
+            .auth
+            .create_webauthn_challenge(
+                Some(auth_context.user_id),
+                &challenge,
+                PASSKEY_REGISTRATION,
+                self.webauthn.challenge_expires_at(),
+            )
+            .await?;
+
+        Ok(PasskeyRegistrationStart {
+            challenge_id,
+            options: self.webauthn.creation_options(
+                challenge,
+                auth_context.user_id,
+                &auth_context.email,
+                &auth_context.username,
+                &existing,
+            ),
+        })
+    }
+
+    /// Verify the authenticator's response and store the new passkey
+    pub async fn finish_passkey_registration(
+        &self,
+        auth_context: &AuthContext,
+        request: PasskeyRegistrationFinish,
+    ) -> Result<PasskeyInfo> {
+        let challenge = self
+            .repositories
+            .auth
+            .take_webauthn_challenge(request.challenge_id, PASSKEY_REGISTRATION)
+            .await?
+            .filter(|challenge| challenge.user_id == Some(auth_context.user_id))
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired challenge".to_string()))?;
+
+        let registration = self.webauthn.verify_registration(&challenge.challenge, &request)?;
+
+        if self
+            .repositories
+            .auth
+            .find_webauthn_credential(&registration.credential_id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::BadRequest("Passkey is already registered".to_string()));
+        }
+
+        let credential = self
+            .repositories
+            .auth
+            .create_webauthn_credential(
+                auth_context.user_id,
+                &registration,
+                request.public_key_algorithm,
+                request.name.as_deref(),
+            )
+            .await?;
+
+        let passkey = PasskeyInfo::from(credential);
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::PASSKEY_REGISTERED)
+                    .with_resource(audit_resources::PASSKEY, passkey.id)
+                    .with_change(None::<&PasskeyInfo>, Some(&passkey)),
+            )
+            .await;
+
+        tracing::info!(user_id = %auth_context.user_id, "Passkey registered");
+        Ok(passkey)
+    }
+
+    /// Begin a passkey login, optionally scoped to one account
+    pub async fn start_passkey_login(
+        &self,
+        request: PasskeyLoginStartRequest,
+    ) -> Result<PasskeyLoginStart> {
+        // Unknown emails get an empty allow list rather than an error, so the
+        // endpoint doesn't reveal which accounts exist
+        let (user_id, allowed) = match request.email {
+            Some(email) => match self.user_service.get_user_by_email(&email).await {
+                Ok(user) => (
+                    Some(user.id),
+                    self.repositories.auth.list_webauthn_credentials(user.id).await?,
+                ),
+                Err(AppError::NotFound(_)) => (None, Vec::new()),
+                Err(e) => return Err(e),
+            },
+            None => (None, Vec::new()),
+        };
+
+        let challenge = WebAuthnVerifier::new_challenge();
+        let challenge_id = self
+            .repositories
+            .auth
+            .create_webauthn_challenge(
+                user_id,
+                &challenge,
+                PASSKEY_AUTHENTICATION,
+                self.webauthn.challenge_expires_at(),
+            )
+            .await?;
+
+        Ok(PasskeyLoginStart {
+            challenge_id,
+            options: self.webauthn.request_options(challenge, &allowed),
+        })
+    }
+
+    /// Verify a passkey assertion and start a session
+    pub async fn finish_passkey_login(
+        &self,
+        request: PasskeyLoginFinish,
+        metadata: SessionMetadata,
+    ) -> Result<LoginResponse> {
+        let invalid = || AppError::Authentication("Invalid passkey".to_string());
+
+        let challenge = self
+            .repositories
+            .auth
+            .take_webauthn_challenge(request.challenge_id, PASSKEY_AUTHENTICATION)
+            .await?
+            .ok_or_else(invalid)?;
+
+        let credential_id = URL_SAFE_NO_PAD
+            .decode(request.credential_id.trim_end_matches('='))
+            .map_err(|_| invalid())?;
+        let credential = self
+            .repositories
+            .auth
+            .find_webauthn_credential(&credential_id)
+            .await?
+            .ok_or_else(invalid)?;
+
+        if challenge.user_id.is_some_and(|user_id| user_id != credential.user_id) {
+            return Err(invalid());
+        }
+
+        let sign_count = self
+            .webauthn
+            .verify_assertion(&challenge.challenge, &credential, &request)?;
+        self.repositories
+            .auth
+            .update_webauthn_sign_count(credential.id, sign_count)
+            .await?;
+
+        let user = self.user_service.get_user_by_id(credential.user_id).await?;
+        ensure_active(user.status)?;
+        let user_roles = self.repositories.auth.get_user_roles(user.id).await?;
+        self.sync_summary(user.id, None, true).await;
+
+        let response = self
+            .issue_session(user.id, user.email, user.username, user_roles, &metadata, false)
+            .await?;
+
+        tracing::info!("User logged in with passkey: {}", response.user.id);
+        Ok(response)
+    }
+
+    /// List the signed-in user's passkeys
+    pub async fn list_passkeys(&self, user_id: Uuid) -> Result<Vec<PasskeyInfo>> {
+        let credentials = self.repositories.auth.list_webauthn_credentials(user_id).await?;
+        Ok(credentials.into_iter().map(PasskeyInfo::from).collect())
+    }
+
+    /// Remove one of the signed-in user's passkeys
+    pub async fn delete_passkey(&self, auth_context: &AuthContext, passkey_id: Uuid) -> Result<()> {
+        if !self
+            .repositories
+            .auth
+            .delete_webauthn_credential(auth_context.user_id, passkey_id)
+            .await?
+        {
+            return Err(AppError::NotFound("Passkey not found".to_string()));
+        }
+
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::PASSKEY_DELETED)
+                    .with_resource(audit_resources::PASSKEY, passkey_id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// List the signed-in user's active sessions, newest first
+    pub async fn list_sessions(&self, auth_context: &AuthContext) -> Result<Vec<SessionInfo>> {
+        let sessions = self
+            .repositories
+            .auth
+            .list_active_sessions(auth_context.user_id)
+            .await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionInfo::from_session(session, auth_context.session_id))
+            .collect())
+    }
+
+    /// Sign out one of the signed-in user's sessions
+    pub async fn revoke_session(&self, auth_context: &AuthContext, session_id: Uuid) -> Result<()> {
+        if !self
+            .repositories
+            .auth
+            .revoke_user_session(auth_context.user_id, session_id)
+            .await?
+        {
+            return Err(AppError::NotFound("Session not found".to_string()));
+        }
+        self.sessions.invalidate(session_id).await;
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::SESSION_REVOKED)
+                    .with_resource(audit_resources::SESSION, session_id),
+            )
+            .await;
+
+        tracing::info!("Revoked session {} for user {}", session_id, auth_context.user_id);
+        Ok(())
+    }
+
+    /// Revoke sessions in bulk, e.g. after a secret leaks (admin only)
+    ///
+    /// Without filters, every session is revoked and the token version is
+    /// bumped, which also invalidates access tokens that aren't bound to a
+    /// session: service account and legacy tokens. A filtered revocation
+    /// only affects the matching sessions and their tokens.
+    pub async fn revoke_all_sessions(
+        &self,
+        auth_context: &AuthContext,
+        request: RevokeAllSessionsRequest,
+    ) -> Result<RevokeAllSessionsResponse> {
+        let tenant = request.tenant.as_deref().map(str::trim);
+        if tenant.is_some_and(|tenant| tenant.is_empty() || tenant.len() > 255) {
+            return Err(AppError::Validation("Invalid tenant".to_string()));
+        }
+
+        let sessions_revoked = self
+            .repositories
+            .auth
+            .revoke_sessions(tenant, request.issued_before)
+            .await?;
+        self.sessions.invalidate_all().await;
+
+        let token_version = match (tenant, request.issued_before) {
+            (None, None) => Some(self.sessions.bump_token_version().await?),
+            _ => None,
+        };
+
+        self.audit
+            .record_best_effort(
+                AuditEvent::new(
+                    tenant.unwrap_or(auth_context.tenant()),
+                    Some(auth_context.user_id),
+                    audit_actions::SESSIONS_REVOKED_ALL,
+                )
+                .with_details(serde_json::json!({
+                    "tenant": tenant,
+                    "issued_before": request.issued_before,
+                    "sessions_revoked": sessions_revoked,
+                    "token_version": token_version,
+                })),
+            )
+            .await;
+
+        tracing::warn!(
+            admin = %auth_context.user_id,
+            tenant = ?tenant,
+            issued_before = ?request.issued_before,
+            sessions_revoked,
+            token_version = ?token_version,
+            "Revoked sessions in bulk"
+        );
+        Ok(RevokeAllSessionsResponse {
+            sessions_revoked,
+            token_version,
+        })
+    }
+
+    /// Create a personal access token scoped to some of the caller's roles
+    pub async fn create_personal_access_token(
+        &self,
+        auth_context: &AuthContext,
+        request: CreatePersonalAccessTokenRequest,
+    ) -> Result<CreatedPersonalAccessToken> {
+        request.validate(&auth_context.roles, auth_context.scopes.as_deref())?;
+
+        let token = format!(
+            "{}{}",
+            PERSONAL_ACCESS_TOKEN_PREFIX,
+            JwtService::generate_opaque_token()
+        );
+        let token_prefix: String = token.chars().take(PERSONAL_ACCESS_TOKEN_PREFIX.len() + 4).collect();
+        let expires_at = request
+            .expires_in_days
+            .map(|days| chrono::Utc::now() + chrono::Duration::days(days));
+
+        let mut roles = request.roles;
+        roles.sort();
+        roles.dedup();
+        let scopes = request.scopes.map(|mut scopes| {
+            scopes.sort();
+            scopes.dedup();
+            scopes
+        });
+        // Stored normalized, e.g. `BASE62` as `rfc3339,base62`
+        let response_format = request
+            .response_format
+            .as_deref()
+            .map(ResponseFormat::parse)
+            .transpose()?
+            .map(|format| format.to_string());
+
+        let stored = self
+            .repositories
+            .auth
+            .create_personal_access_token(
+                auth_context.user_id,
+                request.name.trim(),
+                &JwtService::hash_opaque_token(&token),
+                &token_prefix,
+                &roles,
+                scopes.as_deref(),
+                expires_at,
+                response_format.as_deref(),
+            )
+            .await?;
+
+        let info = PersonalAccessTokenInfo::from(stored);
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::PERSONAL_ACCESS_TOKEN_CREATED)
+                    .with_resource(audit_resources::PERSONAL_ACCESS_TOKEN, info.id)
+                    .with_change(None::<&PersonalAccessTokenInfo>, Some(&info)),
+            )
+            .await;
+
+        tracing::info!(
+            "Created personal access token {} for user {}",
+            info.id,
+            auth_context.user_id
+        );
+        Ok(CreatedPersonalAccessToken { token, info })
+    }
+
+    /// List the signed-in user's personal access tokens, newest first
+    pub async fn list_personal_access_tokens(&self, user_id: Uuid) -> Result<Vec<PersonalAccessTokenInfo>> {
+        let tokens = self
+            .repositories
+            .auth
+            .list_personal_access_tokens(user_id)
+            .await?;
+        Ok(tokens.into_iter().map(PersonalAccessTokenInfo::from).collect())
+    }
+
+    /// Revoke one of the signed-in user's personal access tokens
+    pub async fn revoke_personal_access_token(&self, auth_context: &AuthContext, token_id: Uuid) -> Result<()> {
+        if !self
+            .repositories
+            .auth
+            .revoke_personal_access_token(auth_context.user_id, token_id)
+            .await?
+        {
+            return Err(AppError::NotFound("Token not found".to_string()));
+        }
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::PERSONAL_ACCESS_TOKEN_REVOKED)
+                    .with_resource(audit_resources::PERSONAL_ACCESS_TOKEN, token_id),
+            )
+            .await;
+
+        tracing::info!(
+            "Revoked personal access token {} for user {}",
+            token_id,
+            auth_context.user_id
+        );
+        Ok(())
+    }
+
+    /// Whether `token` would be accepted right now, with its claims and
+    /// session status, for the service `auth_context` (RFC 7662 style)
+    ///
+    /// Applies the checks the auth middleware does. Checking a
+    /// `remember_me` session counts as using it. Personal access tokens are
+    /// reported inactive; they're only meant for this API.
+    pub async fn introspect(
+        &self,
+        auth_context: &AuthContext,
+        token: &str,
+    ) -> Result<IntrospectionResponse> {
+        let Ok(claims) = self.jwt_service.validate_token(token) else {
+            return Ok(IntrospectionResponse::inactive(None));
+        };
+        let Ok(context) = self.jwt_service.auth_context(claims.clone()) else {
+            return Ok(IntrospectionResponse::inactive(None));
+        };
+
+        let session_status = match context.session_id {
+            Some(session_id) if self.sessions.is_valid(session_id, context.user_id).await? => {
+                TokenSessionStatus::Active
+            }
+            Some(_) => TokenSessionStatus::Revoked,
+            None => TokenSessionStatus::None,
+        };
+        let user_state = self.sessions.user_token_state(context.user_id).await;
+        let active = session_status != TokenSessionStatus::Revoked
+            && self.sessions.is_token_version_current(claims.ver.unwrap_or(0)).await
+            && !user_state.is_some_and(|state| {
+                state.revokes(claims.uver.unwrap_or(0)) || state.requires_refresh(claims.roles_updated_at)
+            })
+            && (context.subject_type != SubjectType::User
+                || self.sessions.user_status(context.user_id).await == Some(UserStatus::Active));
+
+        tracing::debug!(
+            service = %auth_context.user_id,
+            subject = %context.user_id,
+            active,
+            "Introspected token"
+        );
+        Ok(IntrospectionResponse {
+            active,
+            session_status: Some(session_status),
+            claims: active.then_some(claims),
+        })
+    }
+
+    /// Create a service account; its client secret is only returned here
+    pub async fn create_service_account(
+        &self,
+        auth_context: &AuthContext,
+        request: CreateServiceAccountRequest,
+    ) -> Result<CreatedServiceAccount> {
+        request.validate()?;
+
+        let client_secret = format!(
+            "{}{}",
+            SERVICE_ACCOUNT_SECRET_PREFIX,
+            JwtService::generate_opaque_token()
+        );
+        let secret_prefix: String = client_secret
+            .chars()
+            .take(SERVICE_ACCOUNT_SECRET_PREFIX.len() + 4)
+            .collect();
+
+        let mut roles = request.roles;
+        roles.sort();
+        roles.dedup();
+
+        let stored = self
+            .repositories
+            .auth
+            .create_service_account(
+                request.name.trim(),
+                &JwtService::hash_opaque_token(&client_secret),
+                &secret_prefix,
+                &roles,
+                auth_context.user_id,
+            )
+            .await?;
+
+        let info = ServiceAccountInfo::from(stored);
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::SERVICE_ACCOUNT_CREATED)
+                    .with_resource(audit_resources::SERVICE_ACCOUNT, info.id)
+                    .with_change(None::<&ServiceAccountInfo>, Some(&info)),
+            )
+            .await;
+
+        tracing::info!(
+            "Created service account {} for user {}",
+            info.id,
+            auth_context.user_id
+        );
+        Ok(CreatedServiceAccount { client_secret, info })
+    }
+
+    /// List service accounts, newest first
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccountInfo>> {
+        let accounts = self.repositories.auth.list_service_accounts().await?;
+        Ok(accounts.into_iter().map(ServiceAccountInfo::from).collect())
+    }
+
+    /// Disable a service account, revoking the tokens it already has
+    pub async fn disable_service_account(&self, auth_context: &AuthContext, id: Uuid) -> Result<()> {
+        if !self.repositories.auth.disable_service_account(id).await? {
+            return Err(AppError::NotFound("Service account not found".to_string()));
+        }
+        // Its tokens have no session to revoke
+        self.sessions.bump_user_token_version(id).await?;
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::SERVICE_ACCOUNT_DISABLED)
+                    .with_resource(audit_resources::SERVICE_ACCOUNT, id),
+            )
+            .await;
+
+        tracing::info!("Disabled service account {}", id);
+        Ok(())
+    }
+
+    /// Client-credentials grant: a short-lived token for a service account
+    pub async fn client_credentials(
+        &self,
+        request: ClientCredentialsRequest,
+    ) -> Result<ClientCredentialsResponse> {
+        if request.grant_type != CLIENT_CREDENTIALS_GRANT {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported grant_type '{}'",
+                request.grant_type
+            )));
+        }
+
+        let invalid = || AppError::Authentication("Invalid client credentials".to_string());
+        let client_id = Uuid::parse_str(&request.client_id).map_err(|_| invalid())?;
+        let account = self
+            .repositories
+            .auth
+            .use_service_account(client_id, &JwtService::hash_opaque_token(&request.client_secret))
+            .await?
+            .ok_or_else(invalid)?;
+
+        let account_state = self.repositories.auth.user_token_state(account.id).await?;
+        let access_token = self.jwt_service.generate_service_token(
+            account.id,
+            account.name,
+            account.roles,
+            account_state.token_version,
+        )?;
+
+        tracing::info!("Issued client-credentials token for service account {}", account.id);
+        Ok(ClientCredentialsResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: self.jwt_service.service_expires_in(),
+        })
+    }
+
     /// Add role to user
-    pub async fn add_role(&self, user_id: Uuid, role: &str) -> Result<()> {
+    pub async fn add_role(&self, auth_context: &AuthContext, user_id: Uuid, role: &str) -> Result<()> {
+        let previous_roles = self.repositories.auth.get_user_roles(user_id).await?;
         self.repositories
             .auth
             .add_role(user_id, role.to_string())
             .await?;
+        self.sessions.roles_changed(user_id).await?;
+
+        let user_roles = self.repositories.auth.get_user_roles(user_id).await?;
+        self.sync_summary(user_id, Some(&user_roles), false).await;
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::ROLE_ADDED)
+                    .with_resource(audit_resources::USER, user_id)
+                    .with_details(serde_json::json!({ "role": role }))
+                    .with_change(Some(&previous_roles), Some(&user_roles)),
+            )
+            .await;
 
         tracing::info!("Role '{}' added to user: {}", role, user_id);
         Ok(())
     }
 
     /// Remove role from user
-    pub async fn remove_role(&self, user_id: Uuid, role: &str) -> Result<()> {
+    pub async fn remove_role(&self, auth_context: &AuthContext, user_id: Uuid, role: &str) -> Result<()> {
+        let previous_roles = self.repositories.auth.get_user_roles(user_id).await?;
         self.repositories
             .auth
             .remove_role(user_id, role.to_string())
             .await?;
+        // Tokens list the user's roles; those issued before have to be
+        // refreshed
+        self.sessions.roles_changed(user_id).await?;
+
+        let user_roles = self.repositories.auth.get_user_roles(user_id).await?;
+        self.sync_summary(user_id, Some(&user_roles), false).await;
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, audit_actions::ROLE_REMOVED)
+                    .with_resource(audit_resources::USER, user_id)
+                    .with_details(serde_json::json!({ "role": role }))
+                    .with_change(Some(&previous_roles), Some(&user_roles)),
+            )
+            .await;
 
         tracing::info!("Role '{}' removed from user: {}", role, user_id);
         Ok(())
     }
 
+    /// Suspend, ban or reactivate a user
+    ///
+    /// Takes effect on this instance right away and on the others within
+    /// `session_cache.ttl_seconds`; the user's sessions and tokens are kept,
+    /// so reactivating restores them. Admins can't change their own status.
+    pub async fn set_user_status(
+        &self,
+        auth_context: &AuthContext,
+        user_id: Uuid,
+        status: UserStatus,
+        reason: Option<String>,
+    ) -> Result<UserResponse> {
+        if auth_context.user_id == user_id {
+            return Err(AppError::Validation(
+                "You can't change the status of your own account".to_string(),
+            ));
+        }
+
+        let (user, previous) = self
+            .repositories
+            .user
+            .set_status(user_id, status)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        self.sessions.status_changed(user_id, status);
+
+        let action = match status {
+            UserStatus::Active => audit_actions::USER_REACTIVATED,
+            UserStatus::Suspended | UserStatus::Banned => audit_actions::USER_SUSPENDED,
+        };
+        self.audit
+            .record_best_effort(
+                actor_event(auth_context, action)
+                    .with_resource(audit_resources::USER, user_id)
+                    .with_details(serde_json::json!({ "reason": reason }))
+                    .with_change(Some(&previous), Some(&status)),
+            )
+            .await;
+
+        tracing::info!(user_id = %user_id, from = %previous, to = %status, "User status changed");
+        Ok(self.user_service.respond(user))
+    }
+
+    /// Add or remove roles for several users at once
+    ///
+    /// The roles of every existing user change in one transaction; users
+    /// that don't exist fail the request or, in `best_effort` mode, are
+    /// reported and skipped. Adding a role a user has, or removing one they
+    /// don't, is not an error.
+    pub async fn bulk_update_roles(
+        &self,
+        auth_context: &AuthContext,
+        request: BulkUserRolesRequest,
+    ) -> Result<BulkResult> {
+        request.validate()?;
+
+        let mut previous_roles = Vec::with_capacity(request.user_ids.len());
+        let mut missing = Vec::new();
+        for user_id in &request.user_ids {
+            match self.repositories.user.find_by_id(*user_id).await? {
+                Some(_) => previous_roles.push((
+                    *user_id,
+                    self.repositories.auth.get_user_roles(*user_id).await?,
+                )),
+                None => missing.push(*user_id),
+            }
+        }
+        let mut result = BulkResult::with_missing(request.mode, &missing)?;
+        if previous_roles.is_empty() {
+            return Ok(result);
+        }
+
+        let user_ids: Vec<Uuid> = previous_roles.iter().map(|(user_id, _)| *user_id).collect();
+        self.repositories
+            .auth
+            .update_roles_many(&user_ids, &request.roles, request.action == BulkRoleAction::Add)
+            .await?;
+
+        let action = match request.action {
+            BulkRoleAction::Add => audit_actions::ROLE_ADDED,
+            BulkRoleAction::Remove => audit_actions::ROLE_REMOVED,
+        };
+        for (user_id, previous) in previous_roles {
+            // The roles are changed already; a user whose tokens can't be
+            // flagged for refresh is reported rather than failing the rest
+            if let Err(e) = self.sessions.roles_changed(user_id).await {
+                tracing::error!(user_id = %user_id, error = %e, "Failed to flag tokens for refresh after bulk role change");
+                result.fail(user_id, e);
+                continue;
+            }
+
+            let user_roles = self.repositories.auth.get_user_roles(user_id).await?;
+            self.sync_summary(user_id, Some(&user_roles), false).await;
+            self.audit
+                .record_best_effort(
+                    actor_event(auth_context, action)
+                        .with_resource(audit_resources::USER, user_id)
+                        .with_details(serde_json::json!({ "roles": request.roles, "bulk": true }))
+                        .with_change(Some(&previous), Some(&user_roles)),
+                )
+                .await;
+            result.succeeded.push(user_id);
+        }
+
+        tracing::info!(
+            action = ?request.action,
+            roles = ?request.roles,
+            updated = result.succeeded.len(),
+            failed = result.failed.len(),
+            "Roles updated in bulk"
+        );
+        Ok(result)
+    }
+
     /// Check if user has permission
     pub async fn check_permission(
         &self,
@@ -284,30 +1480,202 @@ impl AuthService {
         Ok(result.allowed)
     }
 
-    /// Validate password strength
-    fn validate_password(&self, password: &str) -> Result<()> {
-        if password.len() < 8 {
-            return Err(AppError::Validation(
-                "Password must be at least 8 characters long".to_string(),
-            ));
+    /// Built-in password rules plus the tenant's stricter minimum, if any
+    async fn enforce_password_policy(&self, password: &str) -> Result<()> {
+        validate_password(password)?;
+
+        let settings = self.tenant_settings.get(DEFAULT_TENANT).await?;
+        if let Some(policy) = &settings.password_policy {
+            if password.len() < policy.min_length {
+                return Err(AppError::Validation(format!(
+                    "Password must be at least {} characters long",
+                    policy.min_length
+                )));
+            }
         }
 
-        // Add more password validation rules as needed
-        // - Must contain uppercase letter
-        // - Must contain lowercase letter
-        // - Must contain number
-        // - Must contain special character
+        Ok(())
+    }
+
+    /// Mirror auth-owned fields into the user summary used for listings.
+    /// Failures only leave the listing stale, so they're logged, not returned
+    async fn sync_summary(&self, user_id: Uuid, roles: Option<&[String]>, login: bool) {
+        if let Some(roles) = roles {
+            if let Err(e) = self.repositories.user.update_summary_roles(user_id, roles).await {
+                tracing::warn!(user_id = %user_id, error = %e, "Failed to sync user summary roles");
+            }
+        }
+
+        if login {
+            if let Err(e) = self.repositories.user.record_login(user_id, chrono::Utc::now()).await {
+                tracing::warn!(user_id = %user_id, error = %e, "Failed to record login in user summary");
+            }
+        }
+    }
+
+    /// Create a server-side session and issue a JWT carrying its ID, plus the
+    /// first refresh token of the session's family
+    ///
+    /// A `remember_me` session slides with use, see `auth.remember_me`; its
+    /// refresh tokens last until its absolute expiry.
+    async fn issue_session(
+        &self,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        user_roles: Vec<String>,
+        metadata: &SessionMetadata,
+        remember_me: bool,
+    ) -> Result<LoginResponse> {
+        let session_id = id::generate();
+        let expires_in = self.jwt_service.expires_in();
+        let now = chrono::Utc::now();
+        let (session_expires_at, expires_at) = if remember_me {
+            let absolute = now + self.remember_me.absolute_timeout();
+            ((now + self.remember_me.idle_timeout()).min(absolute), absolute)
+        } else {
+            let refresh_expires_in = match &self.tenant_settings.get(DEFAULT_TENANT).await?.session {
+                Some(session) => session.refresh_token_days * 86400,
+                None => self.jwt_service.refresh_expires_in(),
+            };
+            // The session lives as long as its refresh tokens
+            let expires_at = now + chrono::Duration::seconds(refresh_expires_in as i64);
+            (expires_at, expires_at)
+        };
+        let refresh_expires_in = (expires_at - now).num_seconds().max(0) as u64;
+
+        // Checked before the new session exists, since it would count as seen
+        let new_device = self.is_new_device(user_id, metadata).await?;
+        self.repositories
+            .auth
+            .create_session(
+                session_id,
+                user_id,
+                metadata,
+                session_expires_at,
+                remember_me,
+                expires_at,
+            )
+            .await?;
+        if new_device {
+            self.notify_new_device(session_id, user_id, &email, &username, metadata)
+                .await?;
+        }
+
+        let refresh_token = JwtService::generate_opaque_token();
+        self.repositories
+            .auth
+            .create_refresh_token(
+                session_id,
+                user_id,
+                &JwtService::hash_opaque_token(&refresh_token),
+                expires_at,
+            )
+            .await?;
 
+        let user_state = self.repositories.auth.user_token_state(user_id).await?;
+        let token = self.jwt_service.generate_session_token(
+            user_id,
+            email.clone(),
+            username.clone(),
+            user_roles.clone(),
+            session_id,
+            user_state,
+        )?;
+
+        Ok(LoginResponse {
+            access_token: token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token,
+            refresh_expires_in,
+            user: UserInfo {
+                id: user_id,
+                email,
+                username,
+                roles: user_roles,
+            },
+        })
+    }
+
+    /// Whether a sign-in comes from a device none of the user's sessions
+    /// came from; a first sign-in has nothing to compare with, so it's not
+    async fn is_new_device(&self, user_id: Uuid, metadata: &SessionMetadata) -> Result<bool> {
+        if !self.new_device.enabled {
+            return Ok(false);
+        }
+        let Some(fingerprint) = metadata.device_fingerprint() else {
+            return Ok(false);
+        };
+
+        let (has_sessions, seen) = self
+            .repositories
+            .auth
+            .has_seen_device(user_id, &fingerprint)
+            .await?;
+        Ok(has_sessions && !seen)
+    }
+
+    /// Leave a new device's session unverified, record the sign-in and email
+    /// the user a link to confirm it
+    async fn notify_new_device(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        email: &str,
+        username: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<()> {
+        let token = JwtService::generate_opaque_token();
+        self.repositories
+            .auth
+            .set_session_unverified(session_id, &JwtService::hash_opaque_token(&token))
+            .await?;
+
+        let device = metadata.device.as_deref().unwrap_or("an unknown device");
+        let ip_address = metadata.ip_address.as_deref().unwrap_or("an unknown address");
+        self.audit
+            .record_best_effort(
+                AuditEvent::new(DEFAULT_TENANT, Some(user_id), audit_actions::NEW_DEVICE_SIGN_IN)
+                    .with_resource(audit_resources::SESSION, session_id)
+                    .with_details(serde_json::json!({
+                        "device": metadata.device,
+                        "ip_address": metadata.ip_address,
+                    })),
+            )
+            .await;
+
+        let message = EmailMessage {
+            to: email.to_string(),
+            subject: "New sign-in to your account".to_string(),
+            text: format!(
+                "Hi {},\n\nYour account was just signed in to from {} at {}.\n\nIf this was you, confirm the device:\n\n{}?token={}\n\nIf it wasn't, reset your password right away.",
+                username, device, ip_address, self.new_device.url, token
+            ),
+        };
+
+        // The sign-in doesn't wait on, or fail with, the mail relay
+        let mailer = self.mailer.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(message).await {
+                tracing::error!(user_id = %user_id, error = %e, "Failed to send new device email");
+            }
+        });
+
+        tracing::info!("Sign-in from a new device for user {} (session {})", user_id, session_id);
         Ok(())
     }
+}
 
-    /// Hash token for session storage
-    fn hash_token(&self, token: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+/// An audit event for something the signed-in caller did in their tenant
+fn actor_event(auth_context: &AuthContext, action: &'static str) -> AuditEvent {
+    AuditEvent::new(auth_context.tenant(), Some(auth_context.user_id), action)
+}
 
-        let mut hasher = DefaultHasher::new();
-        token.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+/// Refuse to start or extend a session for a user who isn't active
+fn ensure_active(status: UserStatus) -> Result<()> {
+    match status {
+        UserStatus::Active => Ok(()),
+        status => Err(AppError::Authentication(format!("Account is {}", status))),
     }
 }
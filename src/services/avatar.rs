@@ -0,0 +1,128 @@
+use crate::config::{AvatarConfig, StorageConfig};
+use crate::errors::{AppError, BodyError, Result};
+use crate::services::storage::ObjectStorage;
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Image formats avatars may be in, recognised by their leading bytes
+const IMAGE_FORMATS: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/webp", "webp"),
+    ("image/gif", "gif"),
+];
+
+/// Content type of an image by its magic bytes; `None` for anything that
+/// isn't one of [`IMAGE_FORMATS`]
+pub fn sniff_image(body: &[u8]) -> Option<&'static str> {
+    if body.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if body.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if body.len() >= 12 && body.starts_with(b"RIFF") && &body[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if body.starts_with(b"GIF87a") || body.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else {
+        None
+    }
+}
+
+/// Avatar upload rules, and the storage avatars are kept in
+#[derive(Clone)]
+pub struct Avatars {
+    storage: Arc<dyn ObjectStorage>,
+    max_bytes: usize,
+    content_types: Vec<String>,
+    url_ttl: Duration,
+}
+
+/// An upload that passed [`Avatars::check`]
+#[derive(Debug)]
+pub struct AvatarUpload {
+    pub key: String,
+    pub content_type: &'static str,
+}
+
+impl Avatars {
+    pub fn new(storage: Arc<dyn ObjectStorage>, config: &AvatarConfig, storage_config: &StorageConfig) -> Self {
+        Self {
+            storage,
+            max_bytes: config.max_bytes,
+            content_types: config.content_types.clone(),
+            url_ttl: Duration::seconds(storage_config.signed_url_ttl_seconds as i64),
+        }
+    }
+
+    pub fn storage(&self) -> &Arc<dyn ObjectStorage> {
+        &self.storage
+    }
+
+    /// Largest upload accepted, in bytes
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Check an upload's size and type and pick the key it's stored under
+    ///
+    /// The type comes from the file's leading bytes; a declared
+    /// `Content-Type` that disagrees with them is rejected.
+    pub fn check(&self, user_id: Uuid, declared: Option<&str>, body: &[u8]) -> Result<AvatarUpload> {
+        if body.is_empty() {
+            return Err(AppError::Validation("avatar must not be empty".to_string()));
+        }
+        if body.len() > self.max_bytes {
+            return Err(invalid_upload(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("avatar must be at most {} bytes", self.max_bytes),
+            ));
+        }
+
+        let unsupported = || {
+            invalid_upload(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("avatar must be one of {}", self.content_types.join(", ")),
+            )
+        };
+        let content_type = sniff_image(body)
+            .filter(|sniffed| self.content_types.iter().any(|allowed| allowed == sniffed))
+            .ok_or_else(unsupported)?;
+        if let Some(declared) = declared {
+            let declared = declared.split(';').next().unwrap_or_default().trim();
+            if !declared.eq_ignore_ascii_case(content_type) {
+                return Err(unsupported());
+            }
+        }
+
+        let extension = IMAGE_FORMATS
+            .iter()
+            .find(|(format, _)| *format == content_type)
+            .map(|(_, extension)| *extension)
+            .unwrap_or("bin");
+        Ok(AvatarUpload {
+            // A new key per upload, so cached copies of the old one never
+            // show through
+            key: format!("avatars/{}/{}.{}", user_id, Uuid::now_v7(), extension),
+            content_type,
+        })
+    }
+
+    /// Signed URL for an avatar; `None` (and a warning) if it can't be signed
+    pub fn url(&self, key: &str) -> Option<String> {
+        self.storage
+            .signed_url(key, Utc::now() + self.url_ttl)
+            .inspect_err(|e| tracing::warn!(key, error = %e, "Failed to sign avatar URL"))
+            .ok()
+    }
+}
+
+fn invalid_upload(status: StatusCode, message: String) -> AppError {
+    AppError::InvalidBody(BodyError {
+        status,
+        message,
+        field: Some("avatar".to_string()),
+        expected: None,
+    })
+}
@@ -0,0 +1,163 @@
+use crate::errors::{AppError, Result};
+use crate::models::format;
+use axum::http::HeaderValue;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use utoipa::ToSchema;
+
+/// Response header carrying the current banner, when enabled
+pub const SERVICE_NOTICE_HEADER: &str = "x-service-notice";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BannerSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl BannerSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BannerSeverity::Info => "info",
+            BannerSeverity::Warning => "warning",
+            BannerSeverity::Critical => "critical",
+        }
+    }
+}
+
+/// Message broadcast to API consumers, e.g. an upcoming maintenance window
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ServiceBanner {
+    pub severity: BannerSeverity,
+    #[schema(example = "Scheduled maintenance Sunday 02:00-03:00 UTC")]
+    pub message: String,
+    /// No longer shown after this; shown until cleared when unset
+    #[serde(default, with = "format::option_timestamp")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ServiceBanner {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetBannerRequest {
+    pub severity: BannerSeverity,
+    #[schema(example = "Scheduled maintenance Sunday 02:00-03:00 UTC")]
+    pub message: String,
+    #[serde(default, with = "format::option_timestamp")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// What `GET /api/v1/banner` returns; `banner` is null when none is shown
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BannerResponse {
+    pub banner: Option<ServiceBanner>,
+}
+
+struct ActiveBanner {
+    banner: ServiceBanner,
+    header: HeaderValue,
+}
+
+/// The admin-set banner, held per instance like the status page's incident
+/// note
+///
+/// The `X-Service-Notice` value is built once when the banner is set, so
+/// attaching it to every response costs a clone.
+#[derive(Default)]
+pub struct BannerService {
+    active: RwLock<Option<ActiveBanner>>,
+}
+
+impl BannerService {
+    pub const MAX_MESSAGE_LENGTH: usize = 500;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The banner, unless none is set or it has expired
+    pub fn current(&self) -> Option<ServiceBanner> {
+        self.active
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|active| !active.banner.is_expired(Utc::now()))
+            .map(|active| active.banner.clone())
+    }
+
+    /// `X-Service-Notice` value for the current banner
+    pub fn notice_header(&self) -> Option<HeaderValue> {
+        self.active
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|active| !active.banner.is_expired(Utc::now()))
+            .map(|active| active.header.clone())
+    }
+
+    /// Show a banner, replacing any earlier one
+    pub fn set(&self, request: SetBannerRequest) -> Result<ServiceBanner> {
+        let message = request.message.trim();
+        if message.is_empty() {
+            return Err(AppError::Validation("message must not be blank".to_string()));
+        }
+        if message.chars().count() > Self::MAX_MESSAGE_LENGTH {
+            return Err(AppError::Validation(format!(
+                "message must be at most {} characters",
+                Self::MAX_MESSAGE_LENGTH
+            )));
+        }
+        if message.chars().any(char::is_control) {
+            return Err(AppError::Validation(
+                "message must not contain control characters".to_string(),
+            ));
+        }
+        let now = Utc::now();
+        if request.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(AppError::Validation(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+
+        let banner = ServiceBanner {
+            severity: request.severity,
+            message: message.to_string(),
+            expires_at: request.expires_at,
+            updated_at: now,
+        };
+        let header = notice_header_value(&banner);
+        *self.active.write().unwrap() = Some(ActiveBanner {
+            banner: banner.clone(),
+            header,
+        });
+        Ok(banner)
+    }
+
+    pub fn clear(&self) {
+        *self.active.write().unwrap() = None;
+    }
+}
+
+/// `<severity>; <message>`, or a pointer to `GET /api/v1/banner` when the
+/// message isn't representable as a header value (non-ASCII text)
+fn notice_header_value(banner: &ServiceBanner) -> HeaderValue {
+    // `HeaderValue` accepts obs-text bytes, which clients decode
+    // inconsistently, so anything beyond ASCII takes the pointer instead
+    let value = if banner.message.is_ascii() {
+        format!("{}; {}", banner.severity.as_str(), banner.message)
+    } else {
+        format!("{}; see /api/v1/banner", banner.severity.as_str())
+    };
+    HeaderValue::from_str(&value).unwrap_or_else(|_| {
+        HeaderValue::from_str(&format!("{}; see /api/v1/banner", banner.severity.as_str()))
+            .expect("severity is ASCII")
+    })
+}
@@ -0,0 +1,221 @@
+//! Bounded concurrent fan-out of service calls.
+//!
+//! Batch features call another service once per item (a permission check per
+//! listed object, a lookup per user). [`FanOut::run`] runs those calls with a
+//! concurrency cap and a per-item timeout, keeps going when some items fail,
+//! and reports what failed as one [`FanOutError`].
+
+use crate::config::FanOutConfig;
+use crate::errors::AppError;
+use crate::metrics::AppMetrics;
+use futures::stream::{self, StreamExt};
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Failed items listed in a [`FanOutError`] message; the rest are counted
+const MAX_REPORTED_FAILURES: usize = 5;
+
+/// Why one item of a fan-out has no result
+#[derive(Debug)]
+pub enum ItemError {
+    TimedOut(Duration),
+    Failed(AppError),
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemError::TimedOut(after) => write!(f, "timed out after {:?}", after),
+            ItemError::Failed(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ItemFailure<K> {
+    pub item: K,
+    pub error: ItemError,
+}
+
+/// Every item's outcome, in input order
+#[derive(Debug)]
+pub struct FanOutResults<K, T> {
+    pub operation: &'static str,
+    pub succeeded: Vec<(K, T)>,
+    pub failed: Vec<ItemFailure<K>>,
+}
+
+impl<K: fmt::Debug, T> FanOutResults<K, T> {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// All results, or a [`FanOutError`] naming the items that failed
+    pub fn into_values(self) -> Result<Vec<T>, FanOutError> {
+        if self.failed.is_empty() {
+            return Ok(self.succeeded.into_iter().map(|(_, value)| value).collect());
+        }
+
+        Err(FanOutError {
+            operation: self.operation,
+            total: self.succeeded.len() + self.failed.len(),
+            failures: self
+                .failed
+                .into_iter()
+                .map(|failure| (format!("{:?}", failure.item), failure.error))
+                .collect(),
+        })
+    }
+}
+
+/// Some items of a fan-out failed
+#[derive(Debug)]
+pub struct FanOutError {
+    pub operation: &'static str,
+    pub total: usize,
+    /// (item, error) for each failed item
+    pub failures: Vec<(String, ItemError)>,
+}
+
+impl fmt::Display for FanOutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed for {} of {} items: ",
+            self.operation,
+            self.failures.len(),
+            self.total
+        )?;
+        let listed: Vec<String> = self
+            .failures
+            .iter()
+            .take(MAX_REPORTED_FAILURES)
+            .map(|(item, error)| format!("{} ({})", item, error))
+            .collect();
+        write!(f, "{}", listed.join(", "))?;
+        if self.failures.len() > MAX_REPORTED_FAILURES {
+            write!(f, " and {} more", self.failures.len() - MAX_REPORTED_FAILURES)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<FanOutError> for AppError {
+    /// A single failed item keeps its own error, e.g. a validation error
+    /// stays a 400; several become one internal error listing them
+    fn from(mut error: FanOutError) -> Self {
+        if error.failures.len() == 1 {
+            if let Some((_, ItemError::Failed(_))) = error.failures.first() {
+                if let Some((_, ItemError::Failed(inner))) = error.failures.pop() {
+                    return inner;
+                }
+            }
+        }
+        AppError::Internal(error.to_string())
+    }
+}
+
+/// Runs one call per item, at most `concurrency` at a time
+#[derive(Clone)]
+pub struct FanOut {
+    concurrency: usize,
+    item_timeout: Duration,
+    metrics: Option<AppMetrics>,
+}
+
+impl FanOut {
+    pub fn new(config: &FanOutConfig) -> Self {
+        Self {
+            concurrency: config.concurrency.max(1),
+            item_timeout: Duration::from_millis(config.item_timeout_ms),
+            metrics: None,
+        }
+    }
+
+    /// Export item outcomes and durations
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Call `call` for every item and collect the outcomes in input order
+    ///
+    /// `operation` labels metrics and errors, e.g. `"list_objects"`.
+    pub async fn run<K, T, F, Fut>(
+        &self,
+        operation: &'static str,
+        items: impl IntoIterator<Item = K>,
+        call: F,
+    ) -> FanOutResults<K, T>
+    where
+        K: Clone,
+        F: Fn(K) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let started = Instant::now();
+        let item_timeout = self.item_timeout;
+
+        let mut outcomes: Vec<(usize, K, Result<T, ItemError>)> =
+            stream::iter(items.into_iter().enumerate())
+                .map(|(index, item)| {
+                    let call = call(item.clone());
+                    async move {
+                        let outcome = match tokio::time::timeout(item_timeout, call).await {
+                            Ok(Ok(value)) => Ok(value),
+                            Ok(Err(error)) => Err(ItemError::Failed(error)),
+                            Err(_) => Err(ItemError::TimedOut(item_timeout)),
+                        };
+                        (index, item, outcome)
+                    }
+                })
+                .buffer_unordered(self.concurrency)
+                .collect()
+                .await;
+        outcomes.sort_by_key(|(index, _, _)| *index);
+
+        let mut results = FanOutResults {
+            operation,
+            succeeded: Vec::with_capacity(outcomes.len()),
+            failed: Vec::new(),
+        };
+        for (_, item, outcome) in outcomes {
+            match outcome {
+                Ok(value) => results.succeeded.push((item, value)),
+                Err(error) => results.failed.push(ItemFailure { item, error }),
+            }
+        }
+
+        let timed_out = results
+            .failed
+            .iter()
+            .filter(|failure| matches!(failure.error, ItemError::TimedOut(_)))
+            .count();
+        if !results.failed.is_empty() {
+            tracing::warn!(
+                operation,
+                failed = results.failed.len(),
+                timed_out,
+                total = results.succeeded.len() + results.failed.len(),
+                "Fan-out finished with failed items"
+            );
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record_fanout(
+                operation,
+                results.succeeded.len(),
+                results.failed.len() - timed_out,
+                timed_out,
+                started.elapsed().as_secs_f64(),
+            );
+        }
+
+        results
+    }
+}
+
+impl Default for FanOut {
+    fn default() -> Self {
+        Self::new(&FanOutConfig::default())
+    }
+}
@@ -0,0 +1,297 @@
+use crate::auth::openfga::OpenFgaService;
+use crate::client::HttpClient;
+use crate::config::{Config, HealthConfig};
+use crate::database::InstrumentedDatabase;
+use crate::metrics::MetricsRegistry;
+use crate::{dependencies, telemetry};
+use crate::models::format;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use utoipa::ToSchema;
+
+/// Shortest pause between background refreshes, however the TTL is set
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One dependency check
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    async fn check(&self) -> anyhow::Result<()>;
+}
+
+/// `SELECT 1` on every pool
+pub struct DatabaseProbe {
+    databases: Vec<Arc<InstrumentedDatabase>>,
+}
+
+impl DatabaseProbe {
+    pub fn new(databases: Vec<Arc<InstrumentedDatabase>>) -> Self {
+        Self { databases }
+    }
+}
+
+#[async_trait]
+impl HealthProbe for DatabaseProbe {
+    async fn check(&self) -> anyhow::Result<()> {
+        let checks = self
+            .databases
+            .iter()
+            .map(|db| sqlx::query("SELECT 1").execute(db.pool()));
+        for result in futures::future::join_all(checks).await {
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// OpenFGA's `/healthz`, then every store's authorization model
+pub struct OpenFgaProbe {
+    openfga_service: Arc<OpenFgaService>,
+}
+
+impl OpenFgaProbe {
+    pub fn new(openfga_service: Arc<OpenFgaService>) -> Self {
+        Self { openfga_service }
+    }
+}
+
+#[async_trait]
+impl HealthProbe for OpenFgaProbe {
+    async fn check(&self) -> anyhow::Result<()> {
+        if !self.openfga_service.health_check().await? {
+            anyhow::bail!("OpenFGA reported unhealthy");
+        }
+
+        let failed: Vec<String> = self
+            .openfga_service
+            .store_health()
+            .await
+            .into_iter()
+            .filter_map(|(tenant, result)| {
+                let error = result.err()?;
+                Some(format!("{} ({})", tenant.as_deref().unwrap_or("shared store"), error))
+            })
+            .collect();
+        if !failed.is_empty() {
+            anyhow::bail!("OpenFGA stores unavailable: {}", failed.join(", "));
+        }
+        Ok(())
+    }
+}
+
+/// Loki's `/ready`, through a client recorded in the dependency stats; log
+/// pushes don't go through this service, so the probe is what shows Loki
+/// in the dependencies report
+pub struct LokiProbe {
+    client: HttpClient,
+}
+
+impl LokiProbe {
+    pub fn new(loki_url: &str, metrics: &MetricsRegistry) -> anyhow::Result<Self> {
+        let client = HttpClient::builder()
+            .dependency(dependencies::LOKI)
+            .metrics(metrics)
+            .base_url(loki_url)
+            .build()?;
+        Ok(Self { client })
+    }
+
+    /// A probe for the Loki logs are exported to, or `None` when they aren't
+    pub fn from_config(config: &Config, metrics: &MetricsRegistry) -> anyhow::Result<Option<Self>> {
+        if !telemetry::exports_to_loki(config) {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(&telemetry::loki_url(), metrics)?))
+    }
+}
+
+#[async_trait]
+impl HealthProbe for LokiProbe {
+    async fn check(&self) -> anyhow::Result<()> {
+        let response = self.client.get_response("/ready").await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Loki not ready: HTTP {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Latest result of one dependency check
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyHealth {
+    #[schema(example = "database")]
+    pub name: String,
+    pub healthy: bool,
+    /// Whether a failure makes the instance unready
+    pub critical: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    #[serde(with = "format::timestamp")]
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Every dependency, as of the last refresh
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthReport {
+    /// False if any critical dependency failed
+    pub healthy: bool,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+struct Dependency {
+    name: String,
+    probe: Arc<dyn HealthProbe>,
+    timeout: Duration,
+    critical: bool,
+}
+
+/// Cached dependency checks for the readiness probe
+///
+/// A report younger than the TTL is served as is, and a background task
+/// refreshes it a little before it expires, so kubelet probes rarely wait on
+/// a check and never multiply the load on dependencies. Concurrent callers
+/// that find the report stale share one refresh.
+pub struct HealthService {
+    dependencies: Vec<Dependency>,
+    ttl: Duration,
+    jitter: Duration,
+    report: RwLock<Option<(Instant, Arc<HealthReport>)>>,
+    refreshing: Mutex<()>,
+}
+
+impl HealthService {
+    /// A service with no dependencies yet; see [`Self::with_dependency`]
+    pub fn new(config: &HealthConfig) -> Self {
+        Self {
+            dependencies: Vec::new(),
+            ttl: Duration::from_millis(config.ttl_ms),
+            jitter: Duration::from_millis(config.refresh_jitter_ms),
+            report: RwLock::new(None),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Check the databases and OpenFGA, or `None` when checks are disabled
+    pub fn from_config(
+        config: &HealthConfig,
+        databases: Vec<Arc<InstrumentedDatabase>>,
+        openfga_service: Arc<OpenFgaService>,
+    ) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        Some(
+            Self::new(config)
+                .with_dependency(
+                    "database",
+                    Arc::new(DatabaseProbe::new(databases)),
+                    Duration::from_millis(config.database_timeout_ms),
+                    true,
+                )
+                .with_dependency(
+                    "openfga",
+                    Arc::new(OpenFgaProbe::new(openfga_service)),
+                    Duration::from_millis(config.openfga_timeout_ms),
+                    config.openfga_critical,
+                ),
+        )
+    }
+
+    pub fn with_dependency(
+        mut self,
+        name: &str,
+        probe: Arc<dyn HealthProbe>,
+        timeout: Duration,
+        critical: bool,
+    ) -> Self {
+        self.dependencies.push(Dependency {
+            name: name.to_string(),
+            probe,
+            timeout,
+            critical,
+        });
+        self
+    }
+
+    /// The cached report, refreshed first if it's older than the TTL
+    pub async fn report(&self) -> Arc<HealthReport> {
+        if let Some(report) = self.fresh_report().await {
+            return report;
+        }
+
+        let _guard = self.refreshing.lock().await;
+        // Another caller may have refreshed while this one waited
+        if let Some(report) = self.fresh_report().await {
+            return report;
+        }
+        self.refresh().await
+    }
+
+    /// Refresh the report ahead of its expiry, forever
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(self.next_refresh_in()).await;
+            let _guard = self.refreshing.lock().await;
+            let report = self.refresh().await;
+            if !report.healthy {
+                tracing::warn!(
+                    unhealthy = ?report
+                        .dependencies
+                        .iter()
+                        .filter(|dependency| !dependency.healthy)
+                        .map(|dependency| dependency.name.as_str())
+                        .collect::<Vec<_>>(),
+                    "Dependency health check failed"
+                );
+            }
+        }
+    }
+
+    async fn fresh_report(&self) -> Option<Arc<HealthReport>> {
+        match &*self.report.read().await {
+            Some((checked_at, report)) if checked_at.elapsed() < self.ttl => Some(report.clone()),
+            _ => None,
+        }
+    }
+
+    /// Run every check at once, each within its own timeout
+    async fn refresh(&self) -> Arc<HealthReport> {
+        let checks = self.dependencies.iter().map(|dependency| async move {
+            let started = Instant::now();
+            let error = match tokio::time::timeout(dependency.timeout, dependency.probe.check()).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some(format!("timed out after {}ms", dependency.timeout.as_millis())),
+            };
+
+            DependencyHealth {
+                name: dependency.name.clone(),
+                healthy: error.is_none(),
+                critical: dependency.critical,
+                latency_ms: started.elapsed().as_millis() as u64,
+                error,
+                checked_at: Utc::now(),
+            }
+        });
+        let dependencies = futures::future::join_all(checks).await;
+
+        let report = Arc::new(HealthReport {
+            healthy: dependencies
+                .iter()
+                .all(|dependency| dependency.healthy || !dependency.critical),
+            dependencies,
+        });
+        *self.report.write().await = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    /// The TTL less a random share of the jitter
+    fn next_refresh_in(&self) -> Duration {
+        let jitter = self.jitter.min(self.ttl).mul_f64(rand::random::<f64>());
+        (self.ttl - jitter).max(MIN_REFRESH_INTERVAL)
+    }
+}
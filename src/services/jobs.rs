@@ -0,0 +1,252 @@
+use crate::config::JobsConfig;
+use crate::errors::{AppError, Result};
+use crate::metrics::MetricsRegistry;
+use crate::models::{Job, JobStatus};
+use crate::repositories::JobRepository;
+use crate::utils::instance_id;
+use async_trait::async_trait;
+use chrono::Utc;
+use prometheus::{CounterVec, HistogramVec};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Read access to the job queue for the admin API
+#[derive(Clone)]
+pub struct JobService {
+    jobs: JobRepository,
+}
+
+impl JobService {
+    pub fn new(jobs: JobRepository) -> Self {
+        Self { jobs }
+    }
+
+    pub async fn list(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>> {
+        self.jobs.list_recent(status, limit.clamp(1, 100)).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Job> {
+        self.jobs
+            .find(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))
+    }
+}
+
+/// Runs one kind of background job
+///
+/// A job can be attempted more than once (after a failure, or when a worker
+/// dies mid-run), so `run` has to be safe to repeat.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    async fn run(&self, job: &Job, progress: &JobProgress) -> Result<()>;
+}
+
+/// Lets a running job record how far it has got
+pub struct JobProgress {
+    jobs: JobRepository,
+    job_id: Uuid,
+}
+
+impl JobProgress {
+    pub fn new(jobs: JobRepository, job_id: Uuid) -> Self {
+        Self { jobs, job_id }
+    }
+
+    /// Store `progress` on the job; failures are logged, not returned, since
+    /// they shouldn't fail the work itself
+    pub async fn report(&self, progress: Value) {
+        if let Err(e) = self.jobs.update_progress(self.job_id, &progress).await {
+            tracing::warn!(job_id = %self.job_id, error = %e, "Failed to record job progress");
+        }
+    }
+}
+
+/// Job run counters, registered under the `jobs` subsystem
+struct JobMetrics {
+    runs_total: CounterVec,
+    run_duration_seconds: HistogramVec,
+}
+
+impl JobMetrics {
+    fn register(registry: &MetricsRegistry) -> std::result::Result<Self, prometheus::Error> {
+        let registry = registry.subsystem("jobs");
+        Ok(Self {
+            runs_total: registry.counter_vec(
+                "runs_total",
+                "Background job attempts by kind and outcome (succeeded, failed, abandoned)",
+                &["kind", "outcome"],
+            )?,
+            run_duration_seconds: registry.histogram_vec(
+                "run_duration_seconds",
+                "Background job attempt duration in seconds, by kind",
+                vec![0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0],
+                &["kind"],
+            )?,
+        })
+    }
+
+    fn record(&self, kind: &str, outcome: &str, duration: Option<Duration>) {
+        self.runs_total.with_label_values(&[kind, outcome]).inc();
+        if let Some(duration) = duration {
+            self.run_duration_seconds
+                .with_label_values(&[kind])
+                .observe(duration.as_secs_f64());
+        }
+    }
+}
+
+/// Claims queued jobs and hands them to the registered handlers
+pub struct JobWorker {
+    jobs: JobRepository,
+    handlers: HashMap<&'static str, Arc<dyn JobHandler>>,
+    poll_interval: Duration,
+    max_attempts: i32,
+    retry_backoff: Duration,
+    lease: Duration,
+    /// Identifies this worker's leases
+    owner: String,
+    metrics: Option<JobMetrics>,
+}
+
+impl JobWorker {
+    pub fn new(jobs: JobRepository, config: &JobsConfig) -> Self {
+        Self {
+            jobs,
+            handlers: HashMap::new(),
+            poll_interval: Duration::from_millis(config.poll_interval_ms.max(1)),
+            max_attempts: config.max_attempts.max(1),
+            retry_backoff: Duration::from_secs(config.retry_backoff_secs),
+            lease: Duration::from_secs(config.lease_secs.max(1)),
+            owner: format!("{}:{}", instance_id(), Uuid::new_v4().simple()),
+            metrics: None,
+        }
+    }
+
+    /// Count job attempts and their duration in `registry`
+    pub fn with_metrics(
+        mut self,
+        registry: &MetricsRegistry,
+    ) -> std::result::Result<Self, prometheus::Error> {
+        self.metrics = Some(JobMetrics::register(registry)?);
+        Ok(self)
+    }
+
+    /// Handle jobs of `handler.kind()`; jobs of other kinds are left queued
+    pub fn register(mut self, handler: Arc<dyn JobHandler>) -> Self {
+        self.handlers.insert(handler.kind(), handler);
+        self
+    }
+
+    /// Delay before retrying a job that failed its `attempts`th attempt, or
+    /// `None` once it has used them all. Doubles with each attempt.
+    pub fn retry_delay(&self, attempts: i32) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+        Some(self.retry_backoff.saturating_mul(1 << exponent))
+    }
+
+    /// Work through the queue until the process exits
+    pub async fn run(self: Arc<Self>) {
+        tracing::info!(
+            kinds = ?self.kinds(),
+            "Background job worker started"
+        );
+        loop {
+            match self.run_next().await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => tracing::warn!(error = %e, "Failed to claim background job"),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Claim and run one job; returns false if none was due
+    pub async fn run_next(&self) -> Result<bool> {
+        let kinds = self.kinds();
+
+        // Jobs whose worker stopped during their last attempt
+        for kind in self.jobs.fail_abandoned(&kinds, self.max_attempts).await? {
+            self.record(&kind, "abandoned", None);
+        }
+
+        let Some(job) = self
+            .jobs
+            .claim(&kinds, &self.owner, self.lease, self.max_attempts)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let started = Instant::now();
+        let result = match self.handlers.get(job.kind.as_str()) {
+            Some(handler) => {
+                let progress = JobProgress::new(self.jobs.clone(), job.id);
+                handler.run(&job, &progress).await
+            }
+            None => Err(AppError::Internal(format!("No handler for job kind '{}'", job.kind))),
+        };
+        let elapsed = started.elapsed();
+
+        match result {
+            Ok(()) => {
+                self.record(&job.kind, "succeeded", Some(elapsed));
+                if !self.jobs.complete(job.id, &self.owner).await? {
+                    self.warn_lease_lost(&job);
+                    return Ok(true);
+                }
+                tracing::info!(job_id = %job.id, kind = %job.kind, attempts = job.attempts, "Background job succeeded");
+            }
+            Err(e) => {
+                self.record(&job.kind, "failed", Some(elapsed));
+                let retry_at = self
+                    .retry_delay(job.attempts)
+                    .and_then(|delay| chrono::Duration::from_std(delay).ok())
+                    .map(|delay| Utc::now() + delay);
+                if !self.jobs.fail(job.id, &self.owner, &e.to_string(), retry_at).await? {
+                    self.warn_lease_lost(&job);
+                    return Ok(true);
+                }
+                tracing::warn!(
+                    job_id = %job.id,
+                    kind = %job.kind,
+                    attempts = job.attempts,
+                    retry_at = ?retry_at,
+                    error = %e,
+                    "Background job failed"
+                );
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// The job outlived its lease and may be running elsewhere, so its
+    /// result is dropped rather than written over the new holder's
+    fn warn_lease_lost(&self, job: &Job) {
+        tracing::warn!(
+            job_id = %job.id,
+            kind = %job.kind,
+            attempts = job.attempts,
+            "Background job lease expired before it finished; result discarded"
+        );
+    }
+
+    fn record(&self, kind: &str, outcome: &str, duration: Option<Duration>) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(kind, outcome, duration);
+        }
+    }
+
+    fn kinds(&self) -> Vec<String> {
+        self.handlers.keys().map(|kind| kind.to_string()).collect()
+    }
+}
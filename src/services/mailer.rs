@@ -0,0 +1,125 @@
+use crate::client::HttpClient;
+use crate::config::MailerConfig;
+use crate::metrics::MetricsRegistry;
+use async_trait::async_trait;
+use reqwest::Method;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A plain-text email
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+}
+
+/// Outgoing email delivery; implementations are selected by `mailer.provider`
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: EmailMessage) -> anyhow::Result<()>;
+}
+
+/// Logs that an email would have been sent instead of delivering it
+/// (development)
+///
+/// Only the recipient and subject are logged: bodies carry password reset
+/// and device confirmation tokens, which must not end up in log storage.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> anyhow::Result<()> {
+        tracing::info!(
+            to = %message.to,
+            subject = %message.subject,
+            "Email not delivered (log mailer)"
+        );
+        Ok(())
+    }
+}
+
+/// Posts emails as JSON to an HTTP email relay / provider API
+pub struct HttpMailer {
+    client: HttpClient,
+    endpoint: String,
+    from: String,
+}
+
+#[derive(Serialize)]
+struct HttpMailPayload<'a> {
+    from: &'a str,
+    to: &'a str,
+    subject: &'a str,
+    text: &'a str,
+}
+
+impl HttpMailer {
+    pub fn new(config: &MailerConfig, metrics: &MetricsRegistry) -> anyhow::Result<Self> {
+        let mut builder = HttpClient::builder()
+            .dependency("mailer")
+            .metrics(metrics)
+            .timeout(Duration::from_secs(10));
+        if let Some(api_token) = &config.api_token {
+            builder = builder.default_header(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", api_token),
+            );
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            endpoint: config.endpoint.clone(),
+            from: config.from.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for HttpMailer {
+    async fn send(&self, message: EmailMessage) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(&HttpMailPayload {
+            from: &self.from,
+            to: &message.to,
+            subject: &message.subject,
+            text: &message.text,
+        })?;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            reqwest::header::HeaderValue::from_static("application/json"),
+        );
+
+        let response = self
+            .client
+            .send_raw(Method::POST, &self.endpoint, headers, body)
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Mail relay responded with status {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+/// Build the configured mailer; the `log` provider is refused with
+/// `RUN_MODE=production`, where it would silently drop every email
+pub fn mailer_from_config(
+    config: &MailerConfig,
+    metrics: &MetricsRegistry,
+) -> anyhow::Result<Arc<dyn Mailer>> {
+    match config.provider.as_str() {
+        "log" => {
+            let run_mode = std::env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string());
+            if run_mode == "production" {
+                anyhow::bail!("Refusing to use the log mailer with RUN_MODE=production");
+            }
+            Ok(Arc::new(LogMailer))
+        }
+        "http" => Ok(Arc::new(HttpMailer::new(config, metrics)?)),
+        other => anyhow::bail!("Unknown mailer provider: {}", other),
+    }
+}
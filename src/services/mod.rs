@@ -1,16 +1,55 @@
+pub mod audit;
 pub mod auth;
+pub mod avatar;
+pub mod banner;
+pub mod fanout;
+pub mod health;
+pub mod jobs;
+pub mod mailer;
+pub mod onboarding;
+pub mod organization;
+pub mod seed;
+pub mod status;
+pub mod storage;
+pub mod tenant;
+pub mod tuple_cleanup;
 pub mod user;
+pub mod warmup;
 
+use crate::auth::session::SessionValidator;
+use crate::config::Config;
+use crate::metrics::AppMetrics;
 use crate::repositories::Repositories;
 use std::sync::Arc;
 
+pub use audit::{AuditRetention, AuditService};
 pub use auth::AuthService;
+pub use avatar::Avatars;
+pub use banner::BannerService;
+pub use fanout::{FanOut, FanOutError, FanOutResults};
+pub use health::{HealthProbe, HealthReport, HealthService, LokiProbe};
+pub use jobs::{JobHandler, JobProgress, JobService, JobWorker};
+pub use mailer::{mailer_from_config, EmailMessage, Mailer};
+pub use onboarding::{ConfiguredOnboarding, OnboardingPolicy};
+pub use organization::OrganizationService;
+pub use seed::{Seed, Seeder};
+pub use status::StatusPage;
+pub use storage::{storage_from_config, ObjectStorage};
+pub use tenant::TenantSettingsService;
+pub use tuple_cleanup::TupleCleanupJob;
 pub use user::UserService;
+pub use warmup::WarmupService;
 
 #[derive(Clone)]
 pub struct Services {
     pub user: UserService,
     pub auth: AuthService,
+    /// Session revocation checks shared with the auth middleware
+    pub sessions: Arc<SessionValidator>,
+    pub tenant_settings: Arc<TenantSettingsService>,
+    pub organizations: Arc<OrganizationService>,
+    pub jobs: JobService,
+    pub audit: AuditService,
 }
 
 impl Services {
@@ -18,8 +57,31 @@ impl Services {
         repositories: Arc<Repositories>,
         jwt_service: Arc<crate::auth::jwt::JwtService>,
         openfga_service: Arc<crate::auth::openfga::OpenFgaService>,
+        mailer: Arc<dyn Mailer>,
+        config: &Config,
     ) -> Self {
-        let user_service = Arc::new(UserService::new(repositories.clone()));
+        let sessions = Arc::new(SessionValidator::new(
+            repositories.auth.clone(),
+            repositories.user.clone(),
+            &config.auth.session_cache,
+        )
+        .with_remember_me(&config.auth.remember_me)
+        .with_token_version(jwt_service.clone())
+        .with_user_status());
+        let user_service =
+            Arc::new(UserService::new(repositories.clone()).with_sessions(sessions.clone()));
+        let tenant_settings = Arc::new(TenantSettingsService::new(
+            repositories.clone(),
+            &config.tenant,
+        ));
+
+        let organizations = Arc::new(OrganizationService::new(
+            repositories.clone(),
+            openfga_service.clone(),
+        ));
+
+        let jobs = JobService::new(repositories.jobs.clone());
+        let audit = AuditService::new(repositories.audit.clone());
 
         Self {
             user: (*user_service).clone(),
@@ -28,7 +90,37 @@ impl Services {
                 user_service,
                 jwt_service,
                 openfga_service,
+                mailer,
+                sessions.clone(),
+                tenant_settings.clone(),
+                organizations.clone(),
+                FanOut::new(&config.fanout),
+                &config.auth,
             ),
+            sessions,
+            tenant_settings,
+            organizations,
+            jobs,
+            audit,
         }
     }
+
+    /// Export fan-out metrics from the services that batch calls
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.auth = self.auth.with_metrics(metrics);
+        self
+    }
+
+    /// Onboard new users with `policy` instead of `auth.onboarding`
+    pub fn with_onboarding(mut self, policy: Arc<dyn OnboardingPolicy>) -> Self {
+        self.auth = self.auth.with_onboarding(policy);
+        self
+    }
+
+    /// Accept avatar uploads, see [`UserService::with_avatars`]
+    pub fn with_avatars(mut self, avatars: Avatars) -> Self {
+        self.user = self.user.with_avatars(avatars);
+        self.auth = self.auth.with_user_service(Arc::new(self.user.clone()));
+        self
+    }
 }
@@ -0,0 +1,97 @@
+use crate::auth::models::roles;
+use crate::auth::registry;
+use crate::config::{OnboardingConfig, OnboardingRelationship};
+use crate::errors::{AppError, Result};
+use crate::models::{OrganizationRole, UserResponse};
+use async_trait::async_trait;
+
+/// What a newly registered user starts with, and what runs once they're set
+/// up; the default follows `auth.onboarding`
+///
+/// Registration assigns the roles, joins the organizations and writes the
+/// relationships before issuing the user's first session, and fails if any
+/// of that does.
+#[async_trait]
+pub trait OnboardingPolicy: Send + Sync {
+    /// Global roles, see [`roles`]
+    fn roles(&self, user: &UserResponse) -> Vec<String>;
+
+    /// Organizations to join, with the role in each
+    fn memberships(&self, user: &UserResponse) -> Vec<(String, OrganizationRole)>;
+
+    /// Other OpenFGA relationships the user gets
+    fn relationships(&self, user: &UserResponse) -> Vec<OnboardingRelationship>;
+
+    /// Runs once the user is set up, e.g. to provision resources elsewhere;
+    /// a failure is logged and doesn't fail registration
+    async fn after_registration(&self, _user: &UserResponse) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Onboarding as configured in `auth.onboarding`
+pub struct ConfiguredOnboarding {
+    config: OnboardingConfig,
+}
+
+impl ConfiguredOnboarding {
+    pub fn new(config: &OnboardingConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Check the configured roles and relationships exist, so a typo fails
+    /// startup rather than every registration
+    pub fn check(&self) -> anyhow::Result<()> {
+        if let Some(role) = self
+            .config
+            .default_roles
+            .iter()
+            .find(|role| !roles::ALL.contains(&role.as_str()))
+        {
+            anyhow::bail!("Unknown onboarding role '{}'", role);
+        }
+        for relationship in &self.config.relationships {
+            relationship_parts(relationship)
+                .map_err(|e| anyhow::anyhow!("Invalid onboarding relationship: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OnboardingPolicy for ConfiguredOnboarding {
+    fn roles(&self, _user: &UserResponse) -> Vec<String> {
+        self.config.default_roles.clone()
+    }
+
+    fn memberships(&self, _user: &UserResponse) -> Vec<(String, OrganizationRole)> {
+        self.config
+            .memberships
+            .iter()
+            .map(|membership| (membership.organization.clone(), membership.role))
+            .collect()
+    }
+
+    fn relationships(&self, _user: &UserResponse) -> Vec<OnboardingRelationship> {
+        self.config.relationships.clone()
+    }
+}
+
+/// `(relation, object_type, object_id)` of a relationship, checked against
+/// the registry
+pub fn relationship_parts(relationship: &OnboardingRelationship) -> Result<(&str, &str, &str)> {
+    let (object_type, object_id) = relationship
+        .object
+        .split_once(':')
+        .filter(|(_, object_id)| !object_id.is_empty())
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Object '{}' is not of the form type:id",
+                relationship.object
+            ))
+        })?;
+    registry::validate(object_type, &relationship.relation)?;
+    Ok((relationship.relation.as_str(), object_type, object_id))
+}
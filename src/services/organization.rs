@@ -0,0 +1,329 @@
+use crate::auth::jwt::JwtService;
+use crate::auth::models::{object_types, relations, roles, AuthContext};
+use crate::auth::openfga::{OpenFgaService, RelationshipTuple};
+use crate::errors::{AppError, Result};
+use crate::models::{
+    audit_actions, audit_resources, AddOrganizationMemberRequest, AuditEvent,
+    CreateOrganizationRequest, Organization, OrganizationMember, OrganizationRole,
+    PaginatedResponse, PaginationParams, UpdateOrganizationMemberRequest,
+    UpdateOrganizationRequest, DEFAULT_TENANT,
+};
+use crate::repositories::Repositories;
+use crate::services::audit::AuditService;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Organization CRUD and membership
+///
+/// Memberships are stored in the database and as `member`/`admin` tuples on
+/// `organization:{id}` in OpenFGA. The tuples are what grants access; the
+/// rows are there to list members without asking OpenFGA. Callers need the
+/// `admin` relation to manage an organization and `member` to read it;
+/// holders of the admin role can manage every organization.
+#[derive(Clone)]
+pub struct OrganizationService {
+    repositories: Arc<Repositories>,
+    openfga_service: Arc<OpenFgaService>,
+    audit: AuditService,
+}
+
+impl OrganizationService {
+    pub fn new(repositories: Arc<Repositories>, openfga_service: Arc<OpenFgaService>) -> Self {
+        Self {
+            audit: AuditService::new(repositories.audit.clone()),
+            repositories,
+            openfga_service,
+        }
+    }
+
+    /// Create an organization with the caller as its first admin
+    pub async fn create(&self, caller: &AuthContext, request: CreateOrganizationRequest) -> Result<Organization> {
+        request.validate()?;
+
+        let organization = self
+            .repositories
+            .organizations
+            .create(&request.id, request.name.trim(), caller.user_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Validation(format!("Organization '{}' already exists", request.id))
+            })?;
+
+        // Without the tuples nobody could manage it, so don't keep it
+        if let Err(e) = self
+            .join(&organization.id, caller.user_id, OrganizationRole::Admin, Some(caller.user_id))
+            .await
+        {
+            if let Err(cleanup) = self.repositories.organizations.delete(&organization.id).await {
+                tracing::error!(organization_id = %organization.id, error = %cleanup, "Failed to remove organization after membership error");
+            }
+            return Err(e);
+        }
+
+        tracing::info!("Organization '{}' created by {}", organization.id, caller.user_id);
+        Ok(organization)
+    }
+
+    pub async fn get(&self, caller: &AuthContext, id: &str) -> Result<Organization> {
+        self.authorize(caller, id, relations::MEMBER).await?;
+        self.find(id).await
+    }
+
+    /// Organizations the user is a member of
+    pub async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<Organization>> {
+        self.repositories.organizations.find_for_user(user_id).await
+    }
+
+    /// Every organization, for admins
+    pub async fn list(&self, pagination: PaginationParams) -> Result<PaginatedResponse<Organization>> {
+        let (organizations, total) = self.repositories.organizations.find_all(&pagination).await?;
+        let total_pages = (total as f64 / pagination.per_page() as f64).ceil() as i64;
+
+        Ok(PaginatedResponse {
+            data: organizations,
+            total,
+            page: pagination.page(),
+            per_page: pagination.per_page(),
+            total_pages,
+            next_cursor: None,
+        })
+    }
+
+    pub async fn update(
+        &self,
+        caller: &AuthContext,
+        id: &str,
+        request: UpdateOrganizationRequest,
+    ) -> Result<Organization> {
+        request.validate()?;
+        self.authorize(caller, id, relations::ADMIN).await?;
+
+        self.repositories
+            .organizations
+            .update(id, request.name.trim())
+            .await?
+            .ok_or_else(|| AppError::NotFound("Organization not found".to_string()))
+    }
+
+    /// Delete an organization and every membership tuple on it
+    pub async fn delete(&self, caller: &AuthContext, id: &str) -> Result<()> {
+        if id == DEFAULT_TENANT {
+            return Err(AppError::Validation(
+                "The default organization can't be deleted".to_string(),
+            ));
+        }
+        self.authorize(caller, id, relations::ADMIN).await?;
+        self.find(id).await?;
+
+        let tuples: Vec<RelationshipTuple> = self
+            .repositories
+            .organizations
+            .find_members(id)
+            .await?
+            .iter()
+            .flat_map(|member| membership_tuples(id, member.user_id, member.role))
+            .collect();
+        self.openfga_service.delete_tuples(&tuples).await?;
+
+        self.repositories.organizations.delete(id).await?;
+        tracing::info!("Organization '{}' deleted by {}", id, caller.user_id);
+        Ok(())
+    }
+
+    pub async fn list_members(&self, caller: &AuthContext, id: &str) -> Result<Vec<OrganizationMember>> {
+        self.authorize(caller, id, relations::MEMBER).await?;
+        self.find(id).await?;
+        self.repositories.organizations.find_members(id).await
+    }
+
+    pub async fn add_member(
+        &self,
+        caller: &AuthContext,
+        id: &str,
+        request: AddOrganizationMemberRequest,
+    ) -> Result<OrganizationMember> {
+        self.authorize(caller, id, relations::ADMIN).await?;
+        self.find(id).await?;
+
+        if self.repositories.user.find_by_id(request.user_id).await?.is_none() {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        if self
+            .repositories
+            .organizations
+            .find_member(id, request.user_id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Validation(
+                "User is already a member of this organization".to_string(),
+            ));
+        }
+
+        let role = request.role.unwrap_or(OrganizationRole::Member);
+        self.join(id, request.user_id, role, Some(caller.user_id)).await
+    }
+
+    /// Make an existing user a member; used by registration, where there is
+    /// no caller to authorize
+    pub async fn join(
+        &self,
+        id: &str,
+        user_id: Uuid,
+        role: OrganizationRole,
+        actor_id: Option<Uuid>,
+    ) -> Result<OrganizationMember> {
+        self.openfga_service
+            .batch_write_relationships(
+                role.relations()
+                    .iter()
+                    .map(|relation| (user_id, *relation, object_types::ORGANIZATION, id))
+                    .collect(),
+            )
+            .await?;
+
+        let member = self
+            .repositories
+            .organizations
+            .upsert_member(id, user_id, role)
+            .await?;
+
+        self.audit(id, actor_id, audit_actions::ORGANIZATION_MEMBER_ADDED, user_id, role)
+            .await;
+        Ok(member)
+    }
+
+    pub async fn update_member(
+        &self,
+        caller: &AuthContext,
+        id: &str,
+        user_id: Uuid,
+        request: UpdateOrganizationMemberRequest,
+    ) -> Result<OrganizationMember> {
+        self.authorize(caller, id, relations::ADMIN).await?;
+        let member = self.find_member(id, user_id).await?;
+        if member.role == request.role {
+            return Ok(member);
+        }
+
+        match request.role {
+            OrganizationRole::Admin => {
+                self.openfga_service
+                    .write_relationship(user_id, relations::ADMIN, object_types::ORGANIZATION, id)
+                    .await?
+            }
+            OrganizationRole::Member => {
+                self.ensure_other_admin(id, &member).await?;
+                self.openfga_service
+                    .delete_relationship(user_id, relations::ADMIN, object_types::ORGANIZATION, id)
+                    .await?
+            }
+        }
+
+        let member = self
+            .repositories
+            .organizations
+            .upsert_member(id, user_id, request.role)
+            .await?;
+
+        self.audit(id, Some(caller.user_id), audit_actions::ORGANIZATION_MEMBER_UPDATED, user_id, request.role)
+            .await;
+        Ok(member)
+    }
+
+    /// Remove a member; members can also remove themselves
+    pub async fn remove_member(&self, caller: &AuthContext, id: &str, user_id: Uuid) -> Result<()> {
+        if caller.user_id != user_id {
+            self.authorize(caller, id, relations::ADMIN).await?;
+        }
+        let member = self.find_member(id, user_id).await?;
+        self.ensure_other_admin(id, &member).await?;
+
+        self.openfga_service
+            .delete_tuples(&membership_tuples(id, user_id, member.role))
+            .await?;
+        self.repositories.organizations.delete_member(id, user_id).await?;
+
+        self.audit(id, Some(caller.user_id), audit_actions::ORGANIZATION_MEMBER_REMOVED, user_id, member.role)
+            .await;
+        Ok(())
+    }
+
+    /// Require `relation` on the organization, unless the caller is an admin
+    async fn authorize(&self, caller: &AuthContext, id: &str, relation: &str) -> Result<()> {
+        if JwtService::has_role(caller, roles::ADMIN) {
+            return Ok(());
+        }
+
+        let result = self
+            .openfga_service
+            .check_permission(caller.user_id, relation, object_types::ORGANIZATION, id)
+            .await?;
+        if !result.allowed {
+            tracing::warn!(
+                "User {} lacks '{}' on organization '{}'",
+                caller.user_id,
+                relation,
+                id
+            );
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> Result<Organization> {
+        self.repositories
+            .organizations
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Organization not found".to_string()))
+    }
+
+    async fn find_member(&self, id: &str, user_id: Uuid) -> Result<OrganizationMember> {
+        self.repositories
+            .organizations
+            .find_member(id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Member not found".to_string()))
+    }
+
+    /// An organization that has admins keeps at least one
+    async fn ensure_other_admin(&self, id: &str, member: &OrganizationMember) -> Result<()> {
+        if member.role == OrganizationRole::Admin
+            && self.repositories.organizations.count_admins(id).await? <= 1
+        {
+            return Err(AppError::Validation(
+                "An organization must keep at least one admin".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Membership changes are audited on a best-effort basis; the change
+    /// itself has already happened
+    async fn audit(
+        &self,
+        id: &str,
+        actor_id: Option<Uuid>,
+        action: &'static str,
+        user_id: Uuid,
+        role: OrganizationRole,
+    ) {
+        let event = AuditEvent::new(id, actor_id, action)
+            .with_resource(audit_resources::ORGANIZATION, id)
+            .with_details(serde_json::json!({ "user_id": user_id, "role": role }));
+        self.audit.record_best_effort(event).await;
+    }
+}
+
+fn membership_tuples(id: &str, user_id: Uuid, role: OrganizationRole) -> Vec<RelationshipTuple> {
+    role.relations()
+        .iter()
+        .map(|relation| RelationshipTuple {
+            user: format!("user:{}", user_id),
+            relation: relation.to_string(),
+            object: format!("{}:{}", object_types::ORGANIZATION, id),
+            timestamp: None,
+        })
+        .collect()
+}
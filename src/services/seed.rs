@@ -0,0 +1,331 @@
+//! Declarative seed data: users, organizations and OpenFGA tuples
+//!
+//! A seed file says what should exist, and [`Seeder::run`] creates whatever
+//! doesn't yet, so it is safe to apply on every startup. Nothing a seed
+//! created is ever removed or reset by a later run.
+
+use crate::auth::models::{hash_password, roles, validate_password};
+use crate::auth::openfga::{OpenFgaService, RelationshipTuple, TupleFilter};
+use crate::auth::registry;
+use crate::auth::session::SessionValidator;
+use crate::errors::{AppError, Result};
+use crate::models::{CreateUserRequest, OrganizationRole};
+use crate::repositories::Repositories;
+use crate::services::{OrganizationService, Services, UserService};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Contents of a seed file such as `config/seeds/demo.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Seed {
+    pub users: Vec<SeedUser>,
+    pub organizations: Vec<SeedOrganization>,
+    /// Tuples beyond the memberships `organizations` implies
+    pub tuples: Vec<SeedTuple>,
+}
+
+/// A user, looked up by email; an existing one keeps its password
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedUser {
+    pub email: String,
+    pub username: String,
+    pub password: String,
+    /// Global roles, added to any the user already has
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedOrganization {
+    pub id: String,
+    pub name: String,
+    /// Needs at least one admin, who is recorded as the creator
+    #[serde(default)]
+    pub members: Vec<SeedMember>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedMember {
+    /// Email of a user in the same seed
+    pub user: String,
+    pub role: OrganizationRole,
+}
+
+/// `user` has `relation` on `object`
+///
+/// `user` is `user:<email>` for a user in the same seed, or another object
+/// (`organization:acme`) or userset (`organization:acme#member`).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SeedTuple {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+}
+
+impl Seed {
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let seed = ::config::Config::builder()
+            .add_source(::config::File::from(path))
+            .build()
+            .and_then(|file| file.try_deserialize())
+            .map_err(|e| anyhow::anyhow!("Failed to read seed file {}: {}", path.display(), e))?;
+        Ok(seed)
+    }
+
+    /// Check the seed is consistent and fits the authorization model, so a
+    /// typo fails startup rather than leaving half a seed applied
+    pub fn check(&self) -> anyhow::Result<()> {
+        for user in &self.users {
+            CreateUserRequest {
+                email: user.email.clone(),
+                username: user.username.clone(),
+            }
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Invalid seed user {}: {}", user.email, e))?;
+            validate_password(&user.password)
+                .map_err(|e| anyhow::anyhow!("Invalid seed user {}: {}", user.email, e))?;
+            if let Some(role) = user.roles.iter().find(|role| !roles::ALL.contains(&role.as_str())) {
+                anyhow::bail!("Unknown role '{}' for seed user {}", role, user.email);
+            }
+        }
+
+        for organization in &self.organizations {
+            if !organization
+                .members
+                .iter()
+                .any(|member| member.role == OrganizationRole::Admin)
+            {
+                anyhow::bail!("Seed organization '{}' has no admin", organization.id);
+            }
+            for member in &organization.members {
+                if !self.has_user(&member.user) {
+                    anyhow::bail!(
+                        "Member {} of seed organization '{}' is not a seed user",
+                        member.user,
+                        organization.id
+                    );
+                }
+            }
+        }
+
+        for tuple in &self.tuples {
+            self.check_tuple(tuple)
+                .map_err(|e| anyhow::anyhow!("Invalid seed tuple {:?}: {}", tuple, e))?;
+        }
+        Ok(())
+    }
+
+    fn check_tuple(&self, tuple: &SeedTuple) -> Result<()> {
+        let (object_type, object_id) = split_object(&tuple.object)?;
+        if object_id.is_empty() {
+            return Err(AppError::Validation(format!("Object '{}' has no id", tuple.object)));
+        }
+        registry::validate(object_type, &tuple.relation)?;
+
+        match tuple.user.strip_prefix("user:") {
+            Some(email) if !self.has_user(email) => Err(AppError::Validation(format!(
+                "User '{}' is not a seed user",
+                email
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                let subject = tuple.user.split('#').next().unwrap_or_default();
+                let (subject_type, _) = split_object(subject)?;
+                registry::validate_object_type(subject_type).map(|_| ())
+            }
+        }
+    }
+
+    fn has_user(&self, email: &str) -> bool {
+        self.users.iter().any(|user| user.email == email)
+    }
+}
+
+fn split_object(object: &str) -> Result<(&str, &str)> {
+    object.split_once(':').ok_or_else(|| {
+        AppError::Validation(format!("Object '{}' is not of the form type:id", object))
+    })
+}
+
+/// What one [`Seeder::run`] created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    pub users: usize,
+    pub roles: usize,
+    pub organizations: usize,
+    pub members: usize,
+    pub tuples: usize,
+}
+
+/// Applies a [`Seed`] through the same services the API uses
+pub struct Seeder {
+    seed: Seed,
+    repositories: Arc<Repositories>,
+    users: UserService,
+    organizations: Arc<OrganizationService>,
+    sessions: Arc<SessionValidator>,
+    openfga_service: Arc<OpenFgaService>,
+}
+
+impl Seeder {
+    pub fn new(
+        seed: Seed,
+        repositories: Arc<Repositories>,
+        services: &Services,
+        openfga_service: Arc<OpenFgaService>,
+    ) -> Self {
+        Self {
+            seed,
+            repositories,
+            users: services.user.clone(),
+            organizations: services.organizations.clone(),
+            sessions: services.sessions.clone(),
+            openfga_service,
+        }
+    }
+
+    /// Create what the seed describes and doesn't exist yet
+    pub async fn run(&self) -> Result<SeedReport> {
+        let mut report = SeedReport::default();
+
+        let mut user_ids = HashMap::new();
+        for user in &self.seed.users {
+            let user_id = self.ensure_user(user, &mut report).await?;
+            user_ids.insert(user.email.as_str(), user_id);
+        }
+
+        for organization in &self.seed.organizations {
+            self.ensure_organization(organization, &user_ids, &mut report)
+                .await?;
+        }
+
+        let mut missing = Vec::new();
+        for tuple in &self.seed.tuples {
+            let tuple = RelationshipTuple {
+                user: match tuple.user.strip_prefix("user:") {
+                    Some(email) => format!("user:{}", seed_user_id(&user_ids, email)?),
+                    None => tuple.user.clone(),
+                },
+                relation: tuple.relation.clone(),
+                object: tuple.object.clone(),
+                timestamp: None,
+            };
+            let filter = TupleFilter {
+                user: Some(tuple.user.clone()),
+                relation: Some(tuple.relation.clone()),
+                object: Some(tuple.object.clone()),
+            };
+            let existing = self
+                .openfga_service
+                .read_tuples(&filter, Some(1), None)
+                .await?;
+            if existing.tuples.is_empty() {
+                missing.push(tuple);
+            }
+        }
+        self.openfga_service.write_tuples(&missing).await?;
+        report.tuples = missing.len();
+
+        tracing::info!(
+            users = report.users,
+            roles = report.roles,
+            organizations = report.organizations,
+            members = report.members,
+            tuples = report.tuples,
+            "Applied seed"
+        );
+        Ok(report)
+    }
+
+    async fn ensure_user(&self, seed_user: &SeedUser, report: &mut SeedReport) -> Result<Uuid> {
+        let user_id = match self.users.get_user_by_email(&seed_user.email).await {
+            Ok(user) => user.id,
+            Err(AppError::NotFound(_)) => {
+                let user = self
+                    .users
+                    .create_user(
+                        None,
+                        CreateUserRequest {
+                            email: seed_user.email.clone(),
+                            username: seed_user.username.clone(),
+                        },
+                    )
+                    .await?;
+                self.repositories
+                    .auth
+                    .create_credentials(user.id, hash_password(&seed_user.password)?)
+                    .await?;
+                report.users += 1;
+                user.id
+            }
+            Err(e) => return Err(e),
+        };
+
+        let current_roles = self.repositories.auth.get_user_roles(user_id).await?;
+        let mut added = false;
+        for role in &seed_user.roles {
+            if !current_roles.contains(role) {
+                self.repositories.auth.add_role(user_id, role.clone()).await?;
+                report.roles += 1;
+                added = true;
+            }
+        }
+        if added {
+            self.sessions.roles_changed(user_id).await?;
+        }
+        Ok(user_id)
+    }
+
+    async fn ensure_organization(
+        &self,
+        organization: &SeedOrganization,
+        user_ids: &HashMap<&str, Uuid>,
+        report: &mut SeedReport,
+    ) -> Result<()> {
+        let repository = &self.repositories.organizations;
+        if repository.find_by_id(&organization.id).await?.is_none() {
+            let creator = organization
+                .members
+                .iter()
+                .find(|member| member.role == OrganizationRole::Admin)
+                .ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Seed organization '{}' has no admin",
+                        organization.id
+                    ))
+                })?;
+            let creator = seed_user_id(user_ids, &creator.user)?;
+            // Another replica may have created it in the meantime
+            if repository
+                .create(&organization.id, &organization.name, creator)
+                .await?
+                .is_some()
+            {
+                report.organizations += 1;
+            }
+        }
+
+        for member in &organization.members {
+            let user_id = seed_user_id(user_ids, &member.user)?;
+            if repository.find_member(&organization.id, user_id).await?.is_none() {
+                self.organizations
+                    .join(&organization.id, user_id, member.role, None)
+                    .await?;
+                report.members += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn seed_user_id(user_ids: &HashMap<&str, Uuid>, email: &str) -> Result<Uuid> {
+    user_ids
+        .get(email)
+        .copied()
+        .ok_or_else(|| AppError::Validation(format!("User '{}' is not a seed user", email)))
+}
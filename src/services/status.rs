@@ -0,0 +1,145 @@
+use crate::errors::{AppError, Result};
+use crate::models::format;
+use crate::services::warmup::WarmupService;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use utoipa::ToSchema;
+
+/// Status of the service or one of its components, as a status page shows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    Operational,
+    /// Something non-critical is down; requests are still served
+    Degraded,
+    /// Not serving requests
+    MajorOutage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComponentStatus {
+    #[schema(example = "database")]
+    pub name: String,
+    pub status: ServiceStatus,
+}
+
+/// Operator note shown on the status page while an incident is ongoing
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IncidentNote {
+    #[schema(example = "Logins are slow; we're investigating.")]
+    pub message: String,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetIncidentRequest {
+    #[schema(example = "Logins are slow; we're investigating.")]
+    pub message: String,
+}
+
+/// What `GET /status` shows
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicStatus {
+    pub status: ServiceStatus,
+    pub components: Vec<ComponentStatus>,
+    pub incident: Option<IncidentNote>,
+    #[serde(with = "format::timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Public summary of the service's health for status pages
+///
+/// Built from the cached dependency checks behind `/ready`, so serving it
+/// never probes a dependency. Only names and statuses are shown: errors,
+/// latencies and anything else that might name internal hosts stay in
+/// `/api/v1/admin/dependencies`. The incident note is set by admins and held
+/// per instance.
+pub struct StatusPage {
+    warmup: Arc<WarmupService>,
+    incident: RwLock<Option<IncidentNote>>,
+}
+
+impl StatusPage {
+    pub const MAX_INCIDENT_LENGTH: usize = 500;
+
+    pub fn new(warmup: Arc<WarmupService>) -> Self {
+        Self {
+            warmup,
+            incident: RwLock::new(None),
+        }
+    }
+
+    pub async fn summary(&self) -> PublicStatus {
+        let (status, components) = match self.warmup.health() {
+            Some(health) => {
+                let report = health.report().await;
+                let components: Vec<ComponentStatus> = report
+                    .dependencies
+                    .iter()
+                    .map(|dependency| ComponentStatus {
+                        name: dependency.name.clone(),
+                        status: match (dependency.healthy, dependency.critical) {
+                            (true, _) => ServiceStatus::Operational,
+                            (false, true) => ServiceStatus::MajorOutage,
+                            (false, false) => ServiceStatus::Degraded,
+                        },
+                    })
+                    .collect();
+                let status = if !report.healthy {
+                    ServiceStatus::MajorOutage
+                } else if components
+                    .iter()
+                    .any(|component| component.status != ServiceStatus::Operational)
+                {
+                    ServiceStatus::Degraded
+                } else {
+                    ServiceStatus::Operational
+                };
+                (status, components)
+            }
+            None => (ServiceStatus::Operational, Vec::new()),
+        };
+
+        PublicStatus {
+            status: if self.warmup.is_ready() {
+                status
+            } else {
+                ServiceStatus::MajorOutage
+            },
+            components,
+            incident: self.incident(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn incident(&self) -> Option<IncidentNote> {
+        self.incident.read().unwrap().clone()
+    }
+
+    /// Show `message` on the status page, replacing any earlier note
+    pub fn set_incident(&self, message: &str) -> Result<IncidentNote> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err(AppError::Validation("message must not be blank".to_string()));
+        }
+        if message.chars().count() > Self::MAX_INCIDENT_LENGTH {
+            return Err(AppError::Validation(format!(
+                "message must be at most {} characters",
+                Self::MAX_INCIDENT_LENGTH
+            )));
+        }
+
+        let note = IncidentNote {
+            message: message.to_string(),
+            updated_at: Utc::now(),
+        };
+        *self.incident.write().unwrap() = Some(note.clone());
+        Ok(note)
+    }
+
+    pub fn clear_incident(&self) {
+        *self.incident.write().unwrap() = None;
+    }
+}
@@ -0,0 +1,380 @@
+use crate::client::HttpClient;
+use crate::config::{S3StorageConfig, StorageConfig};
+use crate::metrics::MetricsRegistry;
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// An object read back from storage
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+/// Where uploaded files live; implementations are selected by
+/// `storage.provider`
+///
+/// Keys are `/`-separated relative paths such as `avatars/<user>/<file>`.
+/// Clients never get keys, only [`ObjectStorage::signed_url`]s that expire.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> anyhow::Result<()>;
+
+    /// `None` when there is no such object
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>>;
+
+    /// Deleting a missing object is not an error
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// URL anyone holding it can fetch the object from until `expires_at`
+    fn signed_url(&self, key: &str, expires_at: DateTime<Utc>) -> anyhow::Result<String>;
+
+    /// Whether `signature` is this backend's signature for `key` until
+    /// `expires`; only backends the API serves files for sign their own
+    fn verify_signature(&self, _key: &str, _expires: i64, _signature: &str) -> bool {
+        false
+    }
+}
+
+/// Files under a directory on local disk, served back through
+/// `GET /api/v1/avatars/{user_id}/{file}` (development and single-node setups)
+///
+/// URLs carry `expires` and an HMAC `signature` over the key and expiry.
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+    key: Vec<u8>,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: &str, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            key: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// Derive the URL signing key from another secret so the raw secret
+    /// isn't reused
+    pub fn derived_from(root: impl Into<PathBuf>, base_url: &str, secret: impl AsRef<[u8]>) -> Self {
+        let mut mac = HmacSha256::new_from_slice(secret.as_ref())
+            .expect("HMAC accepts keys of any length");
+        mac.update(b"reprime-backend/storage-url");
+        Self::new(root, base_url, mac.finalize().into_bytes())
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            anyhow::bail!("Invalid storage key '{}'", key);
+        }
+        Ok(self.root.join(relative))
+    }
+
+    fn mac(&self, key: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}\n{}", key, expires).as_bytes());
+        mac
+    }
+}
+
+/// Content type kept next to each local object, as `<file>.content-type`
+fn content_type_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".content-type");
+    PathBuf::from(sidecar)
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        tokio::fs::write(content_type_path(&path), content_type).await?;
+        tokio::fs::write(&path, body)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>> {
+        let path = self.path(key)?;
+        let body = match tokio::fs::read(&path).await {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let content_type = tokio::fs::read_to_string(content_type_path(&path))
+            .await
+            .unwrap_or_else(|_| "application/octet-stream".to_string());
+        Ok(Some(StoredObject { content_type, body }))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        for path in [content_type_path(&path), path] {
+            match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn signed_url(&self, key: &str, expires_at: DateTime<Utc>) -> anyhow::Result<String> {
+        let expires = expires_at.timestamp();
+        Ok(format!(
+            "{}/api/v1/{}?expires={}&signature={}",
+            self.base_url,
+            key,
+            expires,
+            URL_SAFE_NO_PAD.encode(self.mac(key, expires).finalize().into_bytes())
+        ))
+    }
+
+    fn verify_signature(&self, key: &str, expires: i64, signature: &str) -> bool {
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+        self.mac(key, expires).verify_slice(&signature).is_ok()
+            && Utc::now().timestamp() <= expires
+    }
+}
+
+/// An S3 bucket, or any S3-compatible store (MinIO, R2) via `endpoint`
+///
+/// Every request, including the server's own uploads, uses a SigV4
+/// pre-signed URL, so no AWS SDK is needed.
+pub struct S3Storage {
+    client: HttpClient,
+    base_url: Url,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+/// Longest lifetime S3 accepts for a pre-signed URL
+const S3_MAX_EXPIRES_SECONDS: i64 = 7 * 24 * 3600;
+/// Lifetime of the URLs the server signs for its own requests
+const S3_REQUEST_EXPIRES_SECONDS: i64 = 60;
+
+impl S3Storage {
+    pub fn new(config: &S3StorageConfig, metrics: &MetricsRegistry) -> anyhow::Result<Self> {
+        if config.bucket.is_empty() {
+            anyhow::bail!("storage.s3.bucket is required");
+        }
+        if config.access_key_id.is_empty() || config.secret_access_key.is_empty() {
+            anyhow::bail!("storage.s3.access_key_id and secret_access_key are required");
+        }
+
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => Url::parse(endpoint).context("Invalid storage.s3.endpoint")?,
+            None => Url::parse(&format!("https://s3.{}.amazonaws.com", config.region))?,
+        };
+        let base_url = if config.path_style {
+            endpoint.join(&format!("{}/", config.bucket))?
+        } else {
+            let host = endpoint
+                .host_str()
+                .context("storage.s3.endpoint has no host")?;
+            let mut url = endpoint.clone();
+            url.set_host(Some(&format!("{}.{}", config.bucket, host)))?;
+            url
+        };
+
+        Ok(Self {
+            client: HttpClient::builder()
+                .dependency("storage")
+                .metrics(metrics)
+                .timeout(Duration::from_secs(30))
+                .build()?,
+            base_url,
+            region: config.region.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+        })
+    }
+
+    /// SigV4 pre-signed URL for `method` on `key`, valid for `expires_in`
+    /// seconds from `now` (`UNSIGNED-PAYLOAD`, only `host` signed)
+    pub fn presigned_url(
+        &self,
+        method: &Method,
+        key: &str,
+        now: DateTime<Utc>,
+        expires_in: i64,
+    ) -> anyhow::Result<String> {
+        let mut url = self.base_url.join(&encode_path(key))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let date = now.format("%Y%m%d").to_string();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        // Already in the sorted order SigV4 requires
+        let query = [
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_string()),
+            ("X-Amz-Credential", format!("{}/{}", self.access_key_id, scope)),
+            ("X-Amz-Date", timestamp.clone()),
+            ("X-Amz-Expires", expires_in.clamp(1, S3_MAX_EXPIRES_SECONDS).to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ]
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, encode_component(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method.as_str(),
+            url.path(),
+            query,
+            host
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+            timestamp,
+            scope,
+            Sha256::digest(canonical_request.as_bytes())
+        );
+
+        let signing_key = [date.as_str(), &self.region, "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.secret_access_key).into_bytes(), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let signature = hmac_sha256(&signing_key, string_to_sign.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+
+        url.set_query(Some(&format!("{}&X-Amz-Signature={}", query, signature)));
+        Ok(url.to_string())
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        headers: reqwest::header::HeaderMap,
+        body: Vec<u8>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let url = self.presigned_url(&method, key, Utc::now(), S3_REQUEST_EXPIRES_SECONDS)?;
+        self.client.send_raw(method, &url, headers, body).await
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_TYPE, content_type.parse()?);
+
+        let response = self.send(Method::PUT, key, headers, body).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("S3 PUT {} responded with status {}", key, response.status());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>> {
+        let response = self
+            .send(Method::GET, key, reqwest::header::HeaderMap::new(), Vec::new())
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let body = response.bytes().await?.to_vec();
+                Ok(Some(StoredObject { content_type, body }))
+            }
+            status => anyhow::bail!("S3 GET {} responded with status {}", key, status),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let response = self
+            .send(Method::DELETE, key, reqwest::header::HeaderMap::new(), Vec::new())
+            .await?;
+        // S3 answers 204 whether or not the object existed
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            anyhow::bail!("S3 DELETE {} responded with status {}", key, response.status());
+        }
+        Ok(())
+    }
+
+    fn signed_url(&self, key: &str, expires_at: DateTime<Utc>) -> anyhow::Result<String> {
+        let now = Utc::now();
+        self.presigned_url(&Method::GET, key, now, (expires_at - now).num_seconds())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// [`encode_component`] for each segment of a key, keeping the `/`s
+fn encode_path(key: &str) -> String {
+    key.split('/').map(encode_component).collect::<Vec<_>>().join("/")
+}
+
+/// Build the configured storage backend; `secret` signs local-disk URLs when
+/// `storage.signing_secret` is unset
+pub fn storage_from_config(
+    config: &StorageConfig,
+    secret: &str,
+    metrics: &MetricsRegistry,
+) -> anyhow::Result<Arc<dyn ObjectStorage>> {
+    match config.provider.as_str() {
+        "local" => Ok(Arc::new(match config.signing_secret.as_deref() {
+            Some(signing_secret) if !signing_secret.is_empty() => {
+                LocalStorage::new(&config.local_path, &config.public_base_url, signing_secret)
+            }
+            _ => LocalStorage::derived_from(&config.local_path, &config.public_base_url, secret),
+        })),
+        "s3" => Ok(Arc::new(S3Storage::new(&config.s3, metrics)?)),
+        other => anyhow::bail!("Unknown storage provider: {}", other),
+    }
+}
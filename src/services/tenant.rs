@@ -0,0 +1,66 @@
+use crate::config::TenantConfig;
+use crate::errors::Result;
+use crate::models::TenantSettings;
+use crate::repositories::Repositories;
+use crate::request_cost;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Reads and updates tenant settings, cached per tenant
+pub struct TenantSettingsService {
+    repositories: Arc<Repositories>,
+    cache: RwLock<HashMap<String, (Instant, Arc<TenantSettings>)>>,
+    ttl: Duration,
+}
+
+impl TenantSettingsService {
+    pub fn new(repositories: Arc<Repositories>, config: &TenantConfig) -> Self {
+        Self {
+            repositories,
+            cache: RwLock::new(HashMap::new()),
+            ttl: Duration::from_secs(config.settings_cache_ttl_seconds),
+        }
+    }
+
+    pub async fn get(&self, tenant_id: &str) -> Result<Arc<TenantSettings>> {
+        if let Some((cached_at, settings)) = self.cache.read().await.get(tenant_id) {
+            if cached_at.elapsed() < self.ttl {
+                request_cost::record_cache_hit();
+                return Ok(settings.clone());
+            }
+        }
+        request_cost::record_cache_miss();
+
+        let entries = self.repositories.tenant.find_all(tenant_id).await?;
+        let settings = Arc::new(TenantSettings::from_entries(entries)?);
+
+        self.cache
+            .write()
+            .await
+            .insert(tenant_id.to_string(), (Instant::now(), settings.clone()));
+
+        Ok(settings)
+    }
+
+    /// Replace the sections present in `update`, leaving the others as they are
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        update: TenantSettings,
+        updated_by: Uuid,
+    ) -> Result<Arc<TenantSettings>> {
+        update.validate()?;
+
+        self.repositories
+            .tenant
+            .upsert(tenant_id, &update.to_entries()?, updated_by)
+            .await?;
+        self.cache.write().await.remove(tenant_id);
+
+        tracing::info!(tenant_id = %tenant_id, updated_by = %updated_by, "Tenant settings updated");
+        self.get(tenant_id).await
+    }
+}
@@ -0,0 +1,72 @@
+use crate::auth::openfga::{OpenFgaService, TupleFilter};
+use crate::auth::registry::ObjectType;
+use crate::errors::{AppError, Result};
+use crate::models::{job_kinds, Job, TupleCleanupPayload};
+use crate::services::jobs::{JobHandler, JobProgress};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+const PAGE_SIZE: u32 = 100;
+
+/// Deletes every tuple a deleted user is the subject of
+///
+/// Reads from the start of each object type and deletes what it finds until
+/// nothing is left, so a retry after a partial run just carries on. The
+/// `deleted` count in the job's progress adds up across attempts.
+pub struct TupleCleanupJob {
+    openfga: Arc<OpenFgaService>,
+}
+
+impl TupleCleanupJob {
+    pub fn new(openfga: Arc<OpenFgaService>) -> Self {
+        Self { openfga }
+    }
+}
+
+#[async_trait]
+impl JobHandler for TupleCleanupJob {
+    fn kind(&self) -> &'static str {
+        job_kinds::OPENFGA_TUPLE_CLEANUP
+    }
+
+    async fn run(&self, job: &Job, progress: &JobProgress) -> Result<()> {
+        let payload: TupleCleanupPayload = serde_json::from_value(job.payload.clone())
+            .map_err(|e| AppError::Validation(format!("Invalid tuple cleanup payload: {}", e)))?;
+        let user = format!("user:{}", payload.user_id);
+        let mut deleted = job.progress["deleted"].as_u64().unwrap_or(0);
+
+        // OpenFGA only reads by user within one object type
+        for object_type in ObjectType::ALL.iter().filter(|t| !t.relations().is_empty()) {
+            let filter = TupleFilter {
+                user: Some(user.clone()),
+                relation: None,
+                object: Some(format!("{}:", object_type)),
+            };
+
+            loop {
+                // Always the first page: what was on it is gone now
+                let page = self.openfga.read_tuples(&filter, Some(PAGE_SIZE), None).await?;
+                if page.tuples.is_empty() {
+                    break;
+                }
+
+                self.openfga.delete_tuples(&page.tuples).await?;
+                deleted += page.tuples.len() as u64;
+                progress
+                    .report(json!({ "deleted": deleted, "object_type": object_type.as_str() }))
+                    .await;
+
+                if page.continuation_token.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        self.openfga.invalidate_user_cache(payload.user_id).await;
+        progress.report(json!({ "deleted": deleted })).await;
+
+        tracing::info!(user_id = %payload.user_id, deleted, "Deleted OpenFGA tuples of removed user");
+        Ok(())
+    }
+}
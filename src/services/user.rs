@@ -1,24 +1,85 @@
+use crate::auth::jwt::JwtService;
+use crate::auth::models::{roles, AuthContext};
+use crate::auth::session::SessionValidator;
 use crate::errors::{AppError, Result};
 use crate::models::{
-    CreateUserRequest, PaginatedResponse, PaginationParams, UpdateUserRequest, UserResponse,
+    audit_actions, audit_resources, job_kinds, AuditEvent, BulkDeleteUsersRequest, BulkMode,
+    BulkResult, CreateUserRequest, PaginatedResponse, PaginationParams, TupleCleanupPayload,
+    UpdateUserRequest, User, UserCursor, UserFilter, UserResponse, UserSummary, DEFAULT_TENANT,
 };
 use crate::repositories::Repositories;
+use crate::services::audit::AuditService;
+use crate::services::avatar::Avatars;
+use crate::services::storage::StoredObject;
+use crate::utils::CollectionVersion;
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct UserService {
     repositories: Arc<Repositories>,
+    audit: AuditService,
+    /// Avatar uploads are refused while unset
+    avatars: Option<Avatars>,
+    /// Deleted users' cached sessions and token versions; without it their
+    /// tokens are only refused once the session cache expires
+    sessions: Option<Arc<SessionValidator>>,
 }
 
 impl UserService {
     pub fn new(repositories: Arc<Repositories>) -> Self {
-        Self { repositories }
+        Self {
+            audit: AuditService::new(repositories.audit.clone()),
+            repositories,
+            avatars: None,
+            sessions: None,
+        }
+    }
+
+    /// Revoke deleted users' tokens through `sessions`
+    pub fn with_sessions(mut self, sessions: Arc<SessionValidator>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Accept avatar uploads and sign avatar URLs in responses
+    pub fn with_avatars(mut self, avatars: Avatars) -> Self {
+        self.avatars = Some(avatars);
+        self
+    }
+
+    pub fn avatars(&self) -> Option<&Avatars> {
+        self.avatars.as_ref()
+    }
+
+    /// The user as returned to clients, with a freshly signed `avatar_url`
+    pub(crate) fn respond(&self, user: User) -> UserResponse {
+        let avatar_url = match (&self.avatars, &user.avatar_key) {
+            (Some(avatars), Some(key)) => avatars.url(key),
+            _ => None,
+        };
+        UserResponse {
+            avatar_url,
+            ..UserResponse::from(user)
+        }
+    }
+
+    async fn find_user(&self, id: Uuid) -> Result<User> {
+        self.repositories
+            .user
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
     }
 
-    pub async fn create_user(&self, request: CreateUserRequest) -> Result<UserResponse> {
+    /// Create a user; `actor` is `None` when people register themselves
+    pub async fn create_user(
+        &self,
+        actor: Option<&AuthContext>,
+        request: CreateUserRequest,
+    ) -> Result<UserResponse> {
         // Validate input
-        self.validate_create_request(&request).await?;
+        request.validate()?;
 
         // Check if a user already exists
         if self
@@ -27,9 +88,7 @@ impl UserService {
             .exists_by_email(&request.email)
             .await?
         {
-            return Err(AppError::Validation(
-                "User with this email already exists".to_string(),
-            ));
+            return Err(AppError::email_taken());
         }
 
         if self
@@ -38,9 +97,7 @@ impl UserService {
             .exists_by_username(&request.username)
             .await?
         {
-            return Err(AppError::Validation(
-                "User with this username already exists".to_string(),
-            ));
+            return Err(AppError::username_taken());
         }
 
         // Create user
@@ -48,18 +105,14 @@ impl UserService {
 
         tracing::info!("User created successfully: {}", user.id);
 
-        Ok(UserResponse::from(user))
+        let user = UserResponse::from(user);
+        self.audit(actor, audit_actions::USER_CREATED, user.id, None, Some(&user))
+            .await;
+        Ok(user)
     }
 
     pub async fn get_user_by_id(&self, id: Uuid) -> Result<UserResponse> {
-        let user = self
-            .repositories
-            .user
-            .find_by_id(id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
-
-        Ok(UserResponse::from(user))
+        Ok(self.respond(self.find_user(id).await?))
     }
 
     pub async fn get_user_by_email(&self, email: &str) -> Result<UserResponse> {
@@ -70,36 +123,80 @@ impl UserService {
             .await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-        Ok(UserResponse::from(user))
+        Ok(self.respond(user))
+    }
+
+    /// Validator for the user list; read before the page so it's never newer
+    ///
+    /// A role filter reads `user_summaries`, where role changes land without
+    /// touching `users`, so the summaries' version is folded in for it.
+    pub async fn users_version(&self, filter: &UserFilter) -> Result<CollectionVersion> {
+        let version = self.repositories.user.users_version().await?;
+        if filter.roles.is_empty() {
+            return Ok(version);
+        }
+        Ok(version.merge(self.repositories.user.summaries_version().await?))
     }
 
+    pub async fn user_summaries_version(&self) -> Result<CollectionVersion> {
+        self.repositories.user.summaries_version().await
+    }
+
+    /// A page of users; `after` continues from a cursor instead of `page`
     pub async fn get_users(
         &self,
         pagination: PaginationParams,
+        filter: &UserFilter,
+        after: Option<UserCursor>,
     ) -> Result<PaginatedResponse<UserResponse>> {
-        let (users, total) = self.repositories.user.find_all(pagination.clone()).await?;
+        let (users, total) = self
+            .repositories
+            .user
+            .find_all(pagination.clone(), filter, after)
+            .await?;
 
         let user_responses: Vec<UserResponse> =
-            users.into_iter().map(UserResponse::from).collect();
+            users.into_iter().map(|user| self.respond(user)).collect();
 
         let total_pages = (total as f64 / pagination.per_page() as f64).ceil() as i64;
 
         Ok(PaginatedResponse {
             data: user_responses,
             total,
+            page: after.map_or(pagination.page(), |cursor| cursor.page),
+            per_page: pagination.per_page(),
+            total_pages,
+            next_cursor: None,
+        })
+    }
+
+    /// Admin listing backed by the denormalized user summaries
+    pub async fn get_user_summaries(
+        &self,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResponse<UserSummary>> {
+        let (summaries, total) = self.repositories.user.find_summaries(pagination.clone()).await?;
+
+        let total_pages = (total as f64 / pagination.per_page() as f64).ceil() as i64;
+
+        Ok(PaginatedResponse {
+            data: summaries,
+            total,
             page: pagination.page(),
             per_page: pagination.per_page(),
             total_pages,
+            next_cursor: None,
         })
     }
 
     pub async fn update_user(
         &self,
+        actor: &AuthContext,
         id: Uuid,
         request: UpdateUserRequest,
     ) -> Result<UserResponse> {
         // Validate input
-        self.validate_update_request(&request).await?;
+        request.validate()?;
 
         // Check if email is being updated and already exists
         if let Some(ref email) = request.email {
@@ -107,9 +204,7 @@ impl UserService {
                 // Check if it's not the same user
                 if let Ok(existing_user) = self.get_user_by_email(email).await {
                     if existing_user.id != id {
-                        return Err(AppError::Validation(
-                            "User with this email already exists".to_string(),
-                        ));
+                        return Err(AppError::email_taken());
                     }
                 }
             }
@@ -122,10 +217,18 @@ impl UserService {
                 let existing_users = self
                     .repositories
                     .user
-                    .find_all(PaginationParams {
-                        page: Some(1),
-                        per_page: Some(1000),
-                    })
+                    .find_all(
+                        PaginationParams {
+                            page: Some(1),
+                            per_page: Some(1000),
+                            ..PaginationParams::default()
+                        },
+                        &UserFilter {
+                            q: Some(username.clone()),
+                            ..UserFilter::default()
+                        },
+                        None,
+                    )
                     .await?
                     .0;
 
@@ -133,13 +236,13 @@ impl UserService {
                     .iter()
                     .find(|u| u.username == *username && u.id != id)
                 {
-                    return Err(AppError::Validation(
-                        "User with this username already exists".to_string(),
-                    ));
+                    return Err(AppError::username_taken());
                 }
             }
         }
 
+        // Audited without the signed avatar URL, which changes every time
+        let before = UserResponse::from(self.find_user(id).await?);
         let user = self
             .repositories
             .user
@@ -149,71 +252,208 @@ impl UserService {
 
         tracing::info!("User updated successfully: {}", user.id);
 
-        Ok(UserResponse::from(user))
+        let after = UserResponse::from(user.clone());
+        self.audit(Some(actor), audit_actions::USER_UPDATED, id, Some(&before), Some(&after))
+            .await;
+        Ok(self.respond(user))
     }
 
-    pub async fn delete_user(&self, id: Uuid) -> Result<()> {
-        let deleted = self.repositories.user.delete(id).await?;
-
-        if !deleted {
+    /// Replace a user's avatar; callers may only change their own unless
+    /// they're an admin
+    pub async fn set_avatar(
+        &self,
+        actor: &AuthContext,
+        id: Uuid,
+        content_type: Option<&str>,
+        body: Vec<u8>,
+    ) -> Result<UserResponse> {
+        if actor.user_id != id && !JwtService::has_role(actor, roles::ADMIN) {
+            return Err(AppError::Forbidden);
+        }
+        let avatars = self
+            .avatars
+            .as_ref()
+            .ok_or_else(|| AppError::Internal("Avatar storage is not configured".to_string()))?;
+
+        let upload = avatars.check(id, content_type, &body)?;
+        self.find_user(id).await?;
+        avatars
+            .storage()
+            .put(&upload.key, upload.content_type, body)
+            .await?;
+
+        let Some((user, previous)) = self
+            .repositories
+            .user
+            .set_avatar_key(id, Some(&upload.key))
+            .await?
+        else {
+            // Deleted while uploading
+            self.delete_avatar_object(&upload.key).await;
             return Err(AppError::NotFound("User not found".to_string()));
+        };
+        if let Some(previous) = &previous {
+            self.delete_avatar_object(previous).await;
         }
 
-        tracing::info!("User deleted successfully: {}", id);
-
-        Ok(())
+        tracing::info!(user_id = %id, key = %upload.key, "Avatar updated");
+        let event = AuditEvent::new(actor.tenant(), Some(actor.user_id), audit_actions::USER_AVATAR_UPDATED)
+            .with_resource(audit_resources::USER, id)
+            .with_details(serde_json::json!({
+                "avatar_key": upload.key,
+                "previous_avatar_key": previous,
+                "content_type": upload.content_type,
+            }));
+        self.audit.record_best_effort(event).await;
+
+        Ok(self.respond(user))
     }
 
-    async fn validate_create_request(&self, request: &CreateUserRequest) -> Result<()> {
-        if request.email.trim().is_empty() {
-            return Err(AppError::Validation("Email is required".to_string()));
+    /// An avatar served through the API (local-disk storage), if `signature`
+    /// is the storage backend's signature for it and hasn't expired
+    pub async fn avatar_file(
+        &self,
+        key: &str,
+        expires: i64,
+        signature: &str,
+    ) -> Result<StoredObject> {
+        let not_found = || AppError::NotFound("Avatar not found".to_string());
+        let avatars = self.avatars.as_ref().ok_or_else(not_found)?;
+        if !avatars.storage().verify_signature(key, expires, signature) {
+            return Err(not_found());
         }
+        avatars.storage().get(key).await?.ok_or_else(not_found)
+    }
 
-        if request.username.trim().is_empty() {
-            return Err(AppError::Validation("Username is required".to_string()));
+    /// Objects outlive a failed delete; the user no longer points at them
+    async fn delete_avatar_object(&self, key: &str) {
+        if let Some(avatars) = &self.avatars {
+            if let Err(e) = avatars.storage().delete(key).await {
+                tracing::warn!(key, error = %e, "Failed to delete avatar object");
+            }
         }
+    }
 
-        if !self.is_valid_email(&request.email) {
-            return Err(AppError::Validation("Invalid email format".to_string()));
-        }
+    pub async fn delete_user(&self, actor: &AuthContext, id: Uuid) -> Result<()> {
+        let user = self.find_user(id).await?;
+        let deleted = self.repositories.user.delete(id).await?;
 
-        if request.username.len() < 3 {
-            return Err(AppError::Validation(
-                "Username must be at least 3 characters long".to_string(),
-            ));
+        if !deleted {
+            return Err(AppError::NotFound("User not found".to_string()));
         }
 
-        Ok(())
+        tracing::info!("User deleted successfully: {}", id);
+        self.after_delete(actor, &user).await
     }
 
-    async fn validate_update_request(&self, request: &UpdateUserRequest) -> Result<()> {
-        if let Some(ref email) = request.email {
-            if email.trim().is_empty() {
-                return Err(AppError::Validation("Email cannot be empty".to_string()));
+    /// Delete several users at once; see [`BulkMode`] for what happens to
+    /// the rest when some can't be deleted
+    pub async fn bulk_delete_users(
+        &self,
+        actor: &AuthContext,
+        request: BulkDeleteUsersRequest,
+    ) -> Result<BulkResult> {
+        request.validate()?;
+
+        let mut existing = Vec::with_capacity(request.user_ids.len());
+        let mut missing = Vec::new();
+        for id in &request.user_ids {
+            match self.repositories.user.find_by_id(*id).await? {
+                Some(user) => existing.push(user),
+                None => missing.push(*id),
             }
+        }
+        let mut result = BulkResult::with_missing(request.mode, &missing)?;
+
+        let ids: Vec<Uuid> = existing.iter().map(|user| user.id).collect();
+        let deleted = self
+            .repositories
+            .user
+            .delete_many(&ids, request.mode == BulkMode::AllOrNothing)
+            .await?;
 
-            if !self.is_valid_email(email) {
-                return Err(AppError::Validation("Invalid email format".to_string()));
+        for user in existing {
+            if deleted.uncommitted.contains(&user.id) {
+                result.fail(user.id, "User could not be deleted, try again");
+                continue;
             }
+            // Deleted by someone else since it was read
+            if !deleted.deleted.contains(&user.id) {
+                result.fail(user.id, "User not found");
+                continue;
+            }
+            self.after_delete(actor, &user).await?;
+            result.succeeded.push(user.id);
         }
 
-        if let Some(ref username) = request.username {
-            if username.trim().is_empty() {
-                return Err(AppError::Validation("Username cannot be empty".to_string()));
-            }
+        tracing::info!(
+            deleted = result.succeeded.len(),
+            failed = result.failed.len(),
+            "Users deleted in bulk"
+        );
+        Ok(result)
+    }
 
-            if username.len() < 3 {
-                return Err(AppError::Validation(
-                    "Username must be at least 3 characters long".to_string(),
-                ));
+    /// Audit a deleted user and clean up what refers to them
+    ///
+    /// Their credentials, sessions, roles and tokens go in one transaction
+    /// and their token version is bumped, so tokens already issued are
+    /// refused. The auth tables may live in their own database, so this
+    /// can't share the user row's transaction; once the row is gone the
+    /// account can't sign in even if a step here fails.
+    async fn after_delete(&self, actor: &AuthContext, user: &User) -> Result<()> {
+        let id = user.id;
+        let before = UserResponse::from(user.clone());
+        self.audit(Some(actor), audit_actions::USER_DELETED, id, Some(&before), None)
+            .await;
+
+        if let Err(e) = self.repositories.auth.delete_user_data(id).await {
+            tracing::error!(user_id = %id, error = %e, "Failed to delete auth data of deleted user");
+        }
+        if let Some(sessions) = &self.sessions {
+            sessions.user_deleted(id);
+            sessions.invalidate_user(id).await;
+            if let Err(e) = sessions.bump_user_token_version(id).await {
+                tracing::error!(user_id = %id, error = %e, "Failed to revoke deleted user's tokens");
             }
         }
 
+        if let Some(key) = &user.avatar_key {
+            self.delete_avatar_object(key).await;
+        }
+
+        if let Err(e) = self.repositories.organizations.delete_memberships(id).await {
+            tracing::error!(user_id = %id, error = %e, "Failed to remove organization memberships");
+        }
+
+        // Their OpenFGA tuples are removed in the background; the user is
+        // already gone, so a failure here is only logged
+        let payload = serde_json::to_value(TupleCleanupPayload { user_id: id })
+            .map_err(|e| AppError::Internal(format!("Failed to encode job payload: {}", e)))?;
+        if let Err(e) = self
+            .repositories
+            .jobs
+            .enqueue(job_kinds::OPENFGA_TUPLE_CLEANUP, &id.to_string(), &payload)
+            .await
+        {
+            tracing::error!(user_id = %id, error = %e, "Failed to queue OpenFGA tuple cleanup");
+        }
+
         Ok(())
     }
 
-    fn is_valid_email(&self, email: &str) -> bool {
-        // Simple email validation - in production, use a proper email validation library
-        email.contains('@') && email.contains('.') && email.len() > 5
+    async fn audit(
+        &self,
+        actor: Option<&AuthContext>,
+        action: &'static str,
+        id: Uuid,
+        before: Option<&UserResponse>,
+        after: Option<&UserResponse>,
+    ) {
+        let tenant_id = actor.map_or(DEFAULT_TENANT, |actor| actor.tenant());
+        let event = AuditEvent::new(tenant_id, actor.map(|actor| actor.user_id), action)
+            .with_resource(audit_resources::USER, id)
+            .with_change(before, after);
+        self.audit.record_best_effort(event).await;
     }
 }
@@ -0,0 +1,198 @@
+use crate::auth::jwt::JwtService;
+use crate::auth::openfga::OpenFgaService;
+use crate::database::InstrumentedDatabase;
+use crate::services::health::HealthService;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Result of a single warmup step
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WarmupStep {
+    #[schema(example = "database")]
+    pub name: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Generic failure message; the underlying error is only logged
+    pub error: Option<String>,
+}
+
+/// Outcome of a warmup run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WarmupReport {
+    pub ready: bool,
+    pub duration_ms: u64,
+    pub steps: Vec<WarmupStep>,
+}
+
+/// Pre-establishes connections and exercises hot paths before the instance
+/// reports ready, so traffic switched over after a deploy doesn't pay
+/// cold-start costs
+pub struct WarmupService {
+    databases: Vec<Arc<InstrumentedDatabase>>,
+    min_connections: u32,
+    jwt_service: Arc<JwtService>,
+    openfga_service: Arc<OpenFgaService>,
+    health: Option<Arc<HealthService>>,
+    ready: AtomicBool,
+    migrations_pending: AtomicBool,
+    // Serializes concurrent warmup requests
+    running: Mutex<()>,
+}
+
+impl WarmupService {
+    pub fn new(
+        databases: Vec<Arc<InstrumentedDatabase>>,
+        min_connections: u32,
+        jwt_service: Arc<JwtService>,
+        openfga_service: Arc<OpenFgaService>,
+    ) -> Self {
+        Self {
+            databases,
+            min_connections,
+            jwt_service,
+            openfga_service,
+            health: None,
+            ready: AtomicBool::new(false),
+            migrations_pending: AtomicBool::new(false),
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Also check dependencies on `/ready` once warmed up
+    pub fn with_health(mut self, health: Arc<HealthService>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn health(&self) -> Option<&Arc<HealthService>> {
+        self.health.as_ref()
+    }
+
+    /// Whether a warmup has completed successfully and the schema is current
+    pub fn is_ready(&self) -> bool {
+        !self.migrations_pending() && self.ready.load(Ordering::Acquire)
+    }
+
+    /// Set while startup migrations run or wait for another instance's
+    pub fn set_migrations_pending(&self, pending: bool) {
+        self.migrations_pending.store(pending, Ordering::Release);
+    }
+
+    pub fn migrations_pending(&self) -> bool {
+        self.migrations_pending.load(Ordering::Acquire)
+    }
+
+    /// Readiness as reported by `/ready`
+    pub fn status(&self) -> &'static str {
+        if self.migrations_pending() {
+            "migrations_pending"
+        } else if self.is_ready() {
+            "ready"
+        } else {
+            "warming_up"
+        }
+    }
+
+    /// Run all warmup steps; the instance becomes ready once the database is warm
+    pub async fn warmup(&self) -> WarmupReport {
+        let _guard = self.running.lock().await;
+        let start = Instant::now();
+
+        let database = self.run_step("database", self.warm_databases()).await;
+        let openfga = self.run_step("openfga", self.warm_openfga()).await;
+        let hot_paths = self.run_step("hot_paths", self.warm_hot_paths()).await;
+
+        // OpenFGA failures are reported but don't block readiness; permission
+        // checks surface their own errors. Warm pools don't help while the
+        // schema is still being migrated.
+        let ready = database.success && !self.migrations_pending();
+        if ready {
+            self.ready.store(true, Ordering::Release);
+        }
+
+        let report = WarmupReport {
+            ready,
+            duration_ms: start.elapsed().as_millis() as u64,
+            steps: vec![database, openfga, hot_paths],
+        };
+
+        tracing::info!(
+            ready = report.ready,
+            duration_ms = report.duration_ms,
+            "Warmup completed"
+        );
+
+        report
+    }
+
+    async fn run_step<F>(&self, name: &str, step: F) -> WarmupStep
+    where
+        F: std::future::Future<Output = anyhow::Result<()>>,
+    {
+        let start = Instant::now();
+        let result = step.await;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        if let Err(e) = &result {
+            tracing::warn!(step = name, error = %e, "Warmup step failed");
+        }
+
+        WarmupStep {
+            name: name.to_string(),
+            success: result.is_ok(),
+            duration_ms,
+            error: result.err().map(|_| format!("{} warmup failed", name)),
+        }
+    }
+
+    /// Open `min_connections` connections on every pool by holding them simultaneously
+    async fn warm_databases(&self) -> anyhow::Result<()> {
+        for db in &self.databases {
+            let target = self.min_connections.max(1);
+            let mut connections = Vec::with_capacity(target as usize);
+
+            for _ in 0..target {
+                connections.push(db.pool().acquire().await?);
+            }
+
+            for connection in connections.iter_mut() {
+                sqlx::query("SELECT 1").execute(&mut **connection).await?;
+            }
+
+            let (active, idle, size) = db.get_pool_metrics();
+            tracing::debug!(active, idle, size, "Database pool warmed");
+        }
+
+        Ok(())
+    }
+
+    async fn warm_openfga(&self) -> anyhow::Result<()> {
+        self.openfga_service.warm_up().await?;
+        Ok(())
+    }
+
+    /// Exercise token signing/validation and response serialization
+    async fn warm_hot_paths(&self) -> anyhow::Result<()> {
+        let token = self.jwt_service.generate_token(
+            Uuid::nil(),
+            "warmup@localhost".to_string(),
+            "warmup".to_string(),
+            vec![],
+        )?;
+        self.jwt_service.validate_token(&token)?;
+
+        serde_json::to_vec(&crate::models::ApiResponse::success(WarmupStep {
+            name: "hot_paths".to_string(),
+            success: true,
+            duration_ms: 0,
+            error: None,
+        }))?;
+
+        Ok(())
+    }
+}
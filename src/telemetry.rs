@@ -1,91 +1,372 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use opentelemetry::KeyValue;
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_otlp::{LogExporter, WithExportConfig};
+use opentelemetry_sdk::logs::{BatchConfigBuilder, BatchLogProcessor, SdkLoggerProvider};
+use opentelemetry_sdk::Resource;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
 use tracing_subscriber::{
+    filter::filter_fn,
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
+    reload,
     util::SubscriberInitExt,
-    EnvFilter, Registry,
+    EnvFilter, Layer, Registry,
 };
 use uuid::Uuid;
-use crate::config::Config;
+use crate::config::{Config, LokiLabelConfig};
+use crate::live_tail::LiveTail;
+use std::sync::Arc;
+
+/// Resource attributes shared by every telemetry signal (Loki labels, OTLP
+/// resource), so logs, traces and metrics line up across backends
+#[derive(Debug, Clone)]
+pub struct ResourceAttributes {
+    pub service: String,
+    pub version: String,
+    pub environment: String,
+    pub region: String,
+    pub instance: String,
+}
+
+impl ResourceAttributes {
+    pub fn from_env(config: &Config) -> Self {
+        Self {
+            service: config.telemetry.service_name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            environment: std::env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string()),
+            region: std::env::var("REGION").unwrap_or_else(|_| "local".to_string()),
+            instance: std::env::var("INSTANCE_ID").unwrap_or_else(|_| {
+                format!("reprime-{}", &uuid::Uuid::new_v4().to_string()[..8])
+            }),
+        }
+    }
+
+    /// Attributes by the names Loki labels refer to them by
+    pub fn named(&self) -> [(&'static str, &str); 5] {
+        [
+            ("service", &self.service),
+            ("version", &self.version),
+            ("environment", &self.environment),
+            ("region", &self.region),
+            ("instance", &self.instance),
+        ]
+    }
+
+    /// OpenTelemetry resource using semantic convention keys
+    pub fn otel_resource(&self) -> Resource {
+        Resource::builder()
+            .with_service_name(self.service.clone())
+            .with_attributes([
+                KeyValue::new("service.version", self.version.clone()),
+                KeyValue::new("deployment.environment", self.environment.clone()),
+                KeyValue::new("cloud.region", self.region.clone()),
+                KeyValue::new("service.instance.id", self.instance.clone()),
+            ])
+            .build()
+    }
+}
+
+/// Label names that usually take a value per instance or per request; each
+/// value is a new Loki stream
+pub const HIGH_CARDINALITY_LABELS: &[&str] = &[
+    "instance",
+    "pod",
+    "pod_name",
+    "host",
+    "hostname",
+    "container_id",
+    "ip",
+    "client_ip",
+    "trace_id",
+    "span_id",
+    "request_id",
+    "user_id",
+    "session_id",
+];
+
+/// Loki's default `max_label_names_per_series`
+pub const MAX_LOKI_LABELS: usize = 15;
+
+/// Stream labels and per-line fields for Loki pushes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LokiLabels {
+    pub labels: BTreeMap<String, String>,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl LokiLabels {
+    /// Resolve `telemetry.loki` against the resource attributes and the
+    /// extra labels read from the environment (`name=value,...`)
+    pub fn from_config(
+        config: &LokiLabelConfig,
+        resource: &ResourceAttributes,
+        env_labels: Option<&str>,
+    ) -> Result<Self> {
+        let mut labels = BTreeMap::new();
+        let mut fields = BTreeMap::new();
+
+        for name in &config.resource_labels {
+            if !resource.named().iter().any(|(attribute, _)| attribute == name) {
+                anyhow::bail!(
+                    "telemetry.loki.resource_labels: unknown resource attribute '{}'",
+                    name
+                );
+            }
+        }
+        for (name, value) in resource.named() {
+            if config.resource_labels.iter().any(|label| label == name) {
+                labels.insert(name.to_string(), value.to_string());
+            } else {
+                fields.insert(name.to_string(), value.to_string());
+            }
+        }
+
+        labels.extend(
+            config
+                .extra_labels
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+        if let Some(env_labels) = env_labels {
+            labels.extend(parse_label_map(env_labels)?);
+        }
+
+        for (name, value) in &labels {
+            validate_label(name, value, config)?;
+            // Demoted resource attributes are replaced, not duplicated
+            fields.remove(name);
+        }
+        if labels.len() > MAX_LOKI_LABELS {
+            anyhow::bail!(
+                "{} Loki labels configured; at most {} are allowed",
+                labels.len(),
+                MAX_LOKI_LABELS
+            );
+        }
+
+        Ok(Self { labels, fields })
+    }
+}
+
+/// Parse `name=value,name=value`; blank entries are skipped
+pub fn parse_label_map(value: &str) -> Result<BTreeMap<String, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, value) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Loki label '{}' is not name=value", entry))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn validate_label(name: &str, value: &str, config: &LokiLabelConfig) -> Result<()> {
+    let mut chars = name.chars();
+    let valid_name = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("__");
+    if !valid_name {
+        anyhow::bail!("'{}' is not a valid Loki label name", name);
+    }
+    if value.is_empty() {
+        anyhow::bail!("Loki label '{}' has an empty value", name);
+    }
+    if HIGH_CARDINALITY_LABELS.contains(&name)
+        && !config.allow_high_cardinality.iter().any(|allowed| allowed == name)
+    {
+        anyhow::bail!(
+            "Loki label '{}' creates a stream per value; send it as a field, or list it in \
+             telemetry.loki.allow_high_cardinality",
+            name
+        );
+    }
+    Ok(())
+}
+
+static LOGGER_PROVIDER: OnceLock<SdkLoggerProvider> = OnceLock::new();
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Replace the active log filter (`EnvFilter` directives, e.g. `info,sqlx=debug`)
+pub fn set_log_filter(directives: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(directives)?;
+    let handle = LOG_FILTER
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Telemetry has not been initialized"))?;
+    handle.reload(filter)?;
 
-/// Initialize comprehensive telemetry with Loki and structured logging
-pub async fn init_telemetry_with_loki(config: &Config) -> Result<()> {
+    tracing::info!(filter = %directives, "Log filter changed");
+    Ok(())
+}
+
+/// The active log filter directives
+pub fn current_log_filter() -> Option<String> {
+    LOG_FILTER.get()?.with_current(|filter| filter.to_string()).ok()
+}
+
+/// Initialize comprehensive telemetry with Loki and structured logging.
+///
+/// Logs are exported to Loki, OTLP, or both depending on
+/// `telemetry.log_exporter`; an exporter that can't be set up is skipped with
+/// a warning. When a `LiveTail` is given, events are also recorded into its
+/// buffer.
+pub async fn init_telemetry_with_loki(config: &Config, live_tail: Option<Arc<LiveTail>>) -> Result<()> {
     // Create environment filter
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(&config.logging.level));
 
-    // Try to create Loki layer
-    let loki_url = std::env::var("LOKI_URL").unwrap_or_else(|_| "http://localhost:3100".to_string());
+    // Reloadable so the level can be changed at runtime by admins
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+    let _ = LOG_FILTER.set(filter_handle);
 
-    let environment = std::env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string());
-    let region = std::env::var("REGION").unwrap_or_else(|_| "local".to_string());
-    let instance_id = std::env::var("INSTANCE_ID").unwrap_or_else(|_| {
-        format!("reprime-{}", uuid::Uuid::new_v4().to_string()[..8].to_string())
-    });
+    let resource = ResourceAttributes::from_env(config);
+    let exporter = config.telemetry.log_exporter.as_str();
+    let mut setup_errors = Vec::new();
 
-    match tracing_loki::builder()
-        .label("service", "reprime-backend")?
-        .label("version", env!("CARGO_PKG_VERSION"))?
-        .label("environment", &environment)?
-        .label("region", &region)?
-        .label("instance", &instance_id)?
-        .build_url(loki_url.parse()?)
-    {
-        Ok((loki_layer, task)) => {
-            // Spawn the background task for Loki
-            tokio::spawn(task);
-
-            // Create structured JSON formatter with trace correlation
-            let fmt_layer = fmt::layer()
-                .json()
-                .flatten_event(true)
-                .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-                .with_current_span(true)
-                .with_span_list(true);
-
-            // Initialize subscriber with all layers
-            Registry::default()
-                .with(env_filter)
-                .with(fmt_layer)
-                .with(loki_layer)
-                .init();
-
-            tracing::info!(
-                loki_url = %loki_url,
-                service = "reprime-backend",
-                version = env!("CARGO_PKG_VERSION"),
-                "Telemetry initialized with Loki and structured logging"
-            );
+    // Try to create Loki layer
+    let loki_url = loki_url();
+    let loki_layer = if exports_to_loki(config) {
+        // Misconfigured labels fail startup rather than silently dropping logs
+        let loki_labels = LokiLabels::from_config(
+            &config.telemetry.loki,
+            &resource,
+            std::env::var(&config.telemetry.loki.extra_labels_env).ok().as_deref(),
+        )?;
+        match loki_layer(&loki_labels, &loki_url) {
+            Ok(layer) => Some(layer),
+            Err(e) => {
+                setup_errors.push(format!("Failed to initialize Loki layer: {}", e));
+                None
+            }
         }
-        Err(e) => {
-            // Fall back to console only
-            tracing::warn!("Failed to initialize Loki layer: {}. Using console logging only.", e);
-
-            let fmt_layer = fmt::layer()
-                .json()
-                .flatten_event(true)
-                .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-                .with_current_span(true)
-                .with_span_list(true);
-
-            Registry::default()
-                .with(env_filter)
-                .with(fmt_layer)
-                .init();
-
-            tracing::info!("Telemetry initialized with console logging");
+    } else {
+        None
+    };
+
+    let otlp_layer = if matches!(exporter, "otlp" | "both") {
+        match otlp_log_provider(config, &resource) {
+            Ok(provider) => {
+                let layer = OpenTelemetryTracingBridge::new(&provider)
+                    .with_filter(filter_fn(|metadata| !is_exporter_internal(metadata.target())));
+                let _ = LOGGER_PROVIDER.set(provider);
+                Some(layer)
+            }
+            Err(e) => {
+                setup_errors.push(format!("Failed to initialize OTLP log exporter: {}", e));
+                None
+            }
         }
+    } else {
+        None
+    };
+
+    // Create structured JSON formatter with trace correlation
+    let fmt_layer = fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+        .with_current_span(true)
+        .with_span_list(true);
+
+    let live_tail_layer = live_tail.as_ref().map(|tail| tail.layer());
+    let loki_enabled = loki_layer.is_some();
+    let otlp_enabled = otlp_layer.is_some();
+
+    // Initialize subscriber with all layers
+    Registry::default()
+        .with(env_filter)
+        .with(fmt_layer)
+        .with(loki_layer)
+        .with(otlp_layer)
+        .with(live_tail_layer)
+        .init();
+
+    // Fall back to console only for exporters that failed
+    for error in setup_errors {
+        tracing::warn!("{}. Continuing without it.", error);
     }
 
+    tracing::info!(
+        loki = loki_enabled,
+        otlp = otlp_enabled,
+        service = %resource.service,
+        version = %resource.version,
+        instance = %resource.instance,
+        "Telemetry initialized with structured logging"
+    );
+
     Ok(())
 }
 
+/// Where logs are pushed to when exporting to Loki
+pub fn loki_url() -> String {
+    std::env::var("LOKI_URL").unwrap_or_else(|_| "http://localhost:3100".to_string())
+}
+
+pub fn exports_to_loki(config: &Config) -> bool {
+    matches!(config.telemetry.log_exporter.as_str(), "loki" | "both")
+}
+
+fn loki_layer(loki_labels: &LokiLabels, loki_url: &str) -> Result<tracing_loki::Layer> {
+    let mut builder = tracing_loki::builder();
+    for (name, value) in &loki_labels.labels {
+        builder = builder.label(name, value)?;
+    }
+    for (name, value) in &loki_labels.fields {
+        builder = builder.extra_field(name, value)?;
+    }
+    let (layer, task) = builder.build_url(loki_url.parse()?)?;
 
+    // Spawn the background task for Loki
+    tokio::spawn(task);
+
+    Ok(layer)
+}
+
+/// OTLP log pipeline; records are buffered and exported in batches, and
+/// dropped (not blocking the app) once the queue is full
+fn otlp_log_provider(config: &Config, resource: &ResourceAttributes) -> Result<SdkLoggerProvider> {
+    let exporter = LogExporter::builder()
+        .with_tonic()
+        .with_endpoint(&config.telemetry.otlp_endpoint)
+        .build()?;
+
+    let processor = BatchLogProcessor::builder(exporter)
+        .with_batch_config(
+            BatchConfigBuilder::default()
+                .with_max_queue_size(config.telemetry.otlp_log_queue_size)
+                .build(),
+        )
+        .build();
+
+    Ok(SdkLoggerProvider::builder()
+        .with_resource(resource.otel_resource())
+        .with_log_processor(processor)
+        .build())
+}
+
+/// Events emitted while exporting must not be exported again
+fn is_exporter_internal(target: &str) -> bool {
+    ["opentelemetry", "tonic", "h2", "hyper", "tower"]
+        .iter()
+        .any(|prefix| target.starts_with(prefix))
+}
 
 /// Shutdown telemetry gracefully
 pub fn shutdown_telemetry() {
     tracing::info!("Shutting down telemetry...");
+    if let Some(provider) = LOGGER_PROVIDER.get() {
+        // Flushes buffered log records
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to shut down OTLP log exporter: {}", e);
+        }
+    }
     // TODO: Add OpenTelemetry shutdown when implemented
     tracing::info!("Telemetry shutdown complete");
 }
@@ -206,7 +487,7 @@ pub fn extract_or_generate_trace_id(headers: &axum::http::HeaderMap) -> (String,
         .get("x-trace-id")
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string())
-        .unwrap_or_else(|| generate_trace_id());
+        .unwrap_or_else(generate_trace_id);
 
     let span_id = generate_span_id();
     set_trace_context(trace_id.clone(), span_id.clone());
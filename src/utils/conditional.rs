@@ -0,0 +1,73 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use chrono::{DateTime, Utc};
+
+/// Validator for a whole collection, used for conditional GETs
+///
+/// The row count is part of the ETag because deleting a row doesn't move
+/// `max(updated_at)`. The validator covers every page, so a change anywhere
+/// in the collection invalidates all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionVersion {
+    pub last_modified: Option<DateTime<Utc>>,
+    pub count: i64,
+}
+
+impl CollectionVersion {
+    /// Combine per-shard versions
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            last_modified: self.last_modified.max(other.last_modified),
+            count: self.count + other.count,
+        }
+    }
+
+    pub fn etag(&self) -> String {
+        let millis = self.last_modified.map_or(0, |at| at.timestamp_millis());
+        format!("W/\"{}-{}\"", self.count, millis)
+    }
+
+    /// Whether the request's validators still match (RFC 9110 §13.2.2)
+    pub fn is_not_modified(&self, headers: &HeaderMap) -> bool {
+        // If-None-Match takes precedence over If-Modified-Since
+        if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+            let Ok(value) = if_none_match.to_str() else {
+                return false;
+            };
+            let etag = self.etag();
+            let opaque = etag.trim_start_matches("W/");
+            return value.split(',').map(str::trim).any(|candidate| {
+                candidate == "*" || candidate.trim_start_matches("W/") == opaque
+            });
+        }
+
+        let (Some(last_modified), Some(since)) = (
+            self.last_modified,
+            headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|h| h.to_str().ok())
+                .and_then(|v| DateTime::parse_from_rfc2822(v).ok()),
+        ) else {
+            return false;
+        };
+
+        // HTTP dates have second precision
+        last_modified.timestamp() <= since.timestamp()
+    }
+
+    /// Set `Last-Modified` and `ETag` on a response
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        if let Some(last_modified) = self.last_modified {
+            if let Ok(value) = HeaderValue::from_str(&http_date(last_modified)) {
+                headers.insert(header::LAST_MODIFIED, value);
+            }
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.etag()) {
+            headers.insert(header::ETAG, value);
+        }
+    }
+}
+
+/// Format a timestamp as an IMF-fixdate, e.g. `Tue, 01 Jul 2025 00:00:00 GMT`
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
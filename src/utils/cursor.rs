@@ -0,0 +1,158 @@
+use crate::auth::models::AuthContext;
+use crate::errors::AppError;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const CURSOR_VERSION: u8 = 1;
+
+/// Signed envelope around cursor data
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorPayload<T> {
+    v: u8,
+    /// Subject the cursor was issued to; other callers can't replay it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sub: Option<String>,
+    /// Expiry as a unix timestamp, for long-lived continuation tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exp: Option<i64>,
+    data: T,
+}
+
+/// Signs and verifies opaque pagination/continuation cursors (HMAC-SHA256)
+///
+/// Cursors look like `<base64url payload>.<base64url signature>`, so clients
+/// can pass them back but cannot forge offsets or keys into other data.
+#[derive(Clone)]
+pub struct CursorSigner {
+    key: Vec<u8>,
+}
+
+impl CursorSigner {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// Derive a cursor key from another secret so the raw secret isn't reused
+    pub fn derived_from(secret: impl AsRef<[u8]>) -> Self {
+        let mut mac = HmacSha256::new_from_slice(secret.as_ref())
+            .expect("HMAC accepts keys of any length");
+        mac.update(b"reprime-backend/pagination-cursor");
+        Self::new(mac.finalize().into_bytes())
+    }
+
+    /// Encode cursor data, optionally bound to a subject and expiry
+    pub fn encode<T: Serialize>(
+        &self,
+        data: &T,
+        subject: Option<&str>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<String, AppError> {
+        let payload = CursorPayload {
+            v: CURSOR_VERSION,
+            sub: subject.map(str::to_string),
+            exp: expires_at.map(|at| at.timestamp()),
+            data,
+        };
+
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&payload)?);
+        let signature = URL_SAFE_NO_PAD.encode(self.sign(payload.as_bytes()));
+
+        Ok(format!("{}.{}", payload, signature))
+    }
+
+    /// Verify the signature, expiry and subject binding, then decode the data
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        cursor: &str,
+        subject: Option<&str>,
+    ) -> Result<T, AppError> {
+        let invalid = || AppError::BadRequest("Invalid pagination cursor".to_string());
+
+        let (payload, signature) = cursor.split_once('.').ok_or_else(invalid)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).map_err(|_| invalid())?;
+
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let payload: CursorPayload<T> = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+
+        if payload.v != CURSOR_VERSION {
+            return Err(invalid());
+        }
+
+        if let Some(exp) = payload.exp {
+            if chrono::Utc::now().timestamp() > exp {
+                return Err(AppError::BadRequest("Pagination cursor has expired".to_string()));
+            }
+        }
+
+        if payload.sub.is_some() && payload.sub.as_deref() != subject {
+            return Err(invalid());
+        }
+
+        Ok(payload.data)
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length")
+    }
+
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = self.mac();
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+/// Extractor for a signed `cursor` query parameter
+///
+/// Resolves to `Cursor(None)` when no cursor is given (first page). Requires
+/// a `CursorSigner` in request extensions; cursors bound to a subject are
+/// checked against the authenticated user.
+#[derive(Debug, Clone)]
+pub struct Cursor<T>(pub Option<T>);
+
+impl<T, S> FromRequestParts<S> for Cursor<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(raw) = cursor_param(parts.uri.query()) else {
+            return Ok(Cursor(None));
+        };
+
+        let signer = parts
+            .extensions
+            .get::<Arc<CursorSigner>>()
+            .ok_or_else(|| AppError::Internal("Cursor signer not configured".to_string()))?;
+
+        let subject = parts
+            .extensions
+            .get::<AuthContext>()
+            .map(|auth_context| auth_context.user_id.to_string());
+
+        signer.decode(&raw, subject.as_deref()).map(|data| Cursor(Some(data)))
+    }
+}
+
+fn cursor_param(query: Option<&str>) -> Option<String> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "cursor")
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty())
+}
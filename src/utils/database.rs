@@ -1,7 +1,17 @@
 use crate::config::Config;
 use anyhow::Result;
-use sqlx::{postgres::PgPoolOptions, PgPool};
-use std::{sync::Arc, time::Duration};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, Executor, PgPool,
+};
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+/// Connection options that also have sqlx log statements over the slow
+/// query threshold, covering queries that bypass `InstrumentedDatabase`
+fn connect_options(url: &str, config: &Config) -> Result<PgConnectOptions> {
+    let threshold = Duration::from_millis(config.database.slow_query.threshold_ms);
+    Ok(PgConnectOptions::from_str(url)?.log_slow_statements(log::LevelFilter::Warn, threshold))
+}
 
 pub async fn create_database_pool(config: &Config) -> Result<Arc<PgPool>> {
     let pool = PgPoolOptions::new()
@@ -10,13 +20,98 @@ pub async fn create_database_pool(config: &Config) -> Result<Arc<PgPool>> {
         .acquire_timeout(Duration::from_secs(config.database.acquire_timeout))
         .idle_timeout(Duration::from_secs(config.database.idle_timeout))
         .max_lifetime(Duration::from_secs(config.database.max_lifetime))
-        .connect(&config.database.url)
+        .connect_with(connect_options(&config.database.url, config)?)
         .await?;
 
-    // Run migrations if available
-    sqlx::migrate!("./migrations").run(&pool).await?;
-
     tracing::info!("Database connection pool created successfully");
 
     Ok(Arc::new(pool))
 }
+
+/// Create pools for the additional shards in `database.shards`.
+///
+/// Each shard carries the full application schema, so `run_migrations`
+/// applies the main migrations to every one of them.
+pub async fn create_shard_pools(config: &Config) -> Result<Vec<Arc<PgPool>>> {
+    // Auth tables in the primary database reference `users` by foreign key,
+    // which breaks once users live on other shards
+    if !config.database.shards.is_empty() && config.database.auth.is_none() {
+        anyhow::bail!("database.shards requires auth tables in a dedicated store (database.auth)");
+    }
+
+    let mut pools = Vec::with_capacity(config.database.shards.len());
+
+    for (index, shard) in config.database.shards.iter().enumerate() {
+        let pool = PgPoolOptions::new()
+            .max_connections(shard.max_connections.unwrap_or(config.database.max_connections))
+            .min_connections(shard.min_connections.unwrap_or(config.database.min_connections))
+            .acquire_timeout(Duration::from_secs(config.database.acquire_timeout))
+            .idle_timeout(Duration::from_secs(config.database.idle_timeout))
+            .max_lifetime(Duration::from_secs(config.database.max_lifetime))
+            .connect_with(connect_options(&shard.url, config)?)
+            .await?;
+
+        // Shard 0 is the primary database
+        tracing::info!(shard = index + 1, "Shard connection pool created successfully");
+
+        pools.push(Arc::new(pool));
+    }
+
+    Ok(pools)
+}
+
+/// Create a dedicated pool for the auth tables when `database.auth` is configured.
+///
+/// Returns `None` when auth data shares the application database. Connections
+/// get their `search_path` pinned to the configured schema, so the auth
+/// migrations (and their `_sqlx_migrations` bookkeeping) land there too.
+pub async fn create_auth_database_pool(config: &Config) -> Result<Option<Arc<PgPool>>> {
+    let Some(auth_config) = &config.database.auth else {
+        return Ok(None);
+    };
+
+    let url = auth_config.url.as_deref().unwrap_or(&config.database.url);
+    let schema = auth_config.schema.clone();
+
+    if let Some(schema) = &schema {
+        if !is_valid_schema_name(schema) {
+            anyhow::bail!("Invalid auth database schema name: {}", schema);
+        }
+    }
+
+    let mut options = PgPoolOptions::new()
+        .max_connections(auth_config.max_connections.unwrap_or(config.database.max_connections))
+        .min_connections(auth_config.min_connections.unwrap_or(config.database.min_connections))
+        .acquire_timeout(Duration::from_secs(config.database.acquire_timeout))
+        .idle_timeout(Duration::from_secs(config.database.idle_timeout))
+        .max_lifetime(Duration::from_secs(config.database.max_lifetime));
+
+    if let Some(schema) = schema.clone() {
+        options = options.after_connect(move |conn, _meta| {
+            let schema = schema.clone();
+            Box::pin(async move {
+                conn.execute(format!("CREATE SCHEMA IF NOT EXISTS \"{}\"", schema).as_str())
+                    .await?;
+                conn.execute(format!("SET search_path TO \"{}\", public", schema).as_str())
+                    .await?;
+                Ok(())
+            })
+        });
+    }
+
+    let pool = options.connect_with(connect_options(url, config)?).await?;
+
+    tracing::info!(
+        separate_database = auth_config.url.is_some(),
+        schema = ?auth_config.schema,
+        "Auth database connection pool created successfully"
+    );
+
+    Ok(Some(Arc::new(pool)))
+}
+
+fn is_valid_schema_name(schema: &str) -> bool {
+    !schema.is_empty()
+        && schema.len() <= 63
+        && schema.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,89 @@
+//! Primary keys for new rows
+//!
+//! Random UUIDv4 keys land all over a B-tree index, so every insert touches a
+//! cold page. Time-ordered keys (UUIDv7, ULID) append to the right edge
+//! instead. The strategy is process-wide and set from
+//! `database.id_strategy` on startup; repositories call [`generate`] rather
+//! than relying on the column defaults.
+//!
+//! Switching strategy needs no schema migration: every strategy produces a
+//! 128-bit value stored in the existing `UUID` columns, and rows created
+//! before the switch keep their ids. Two things to keep in mind:
+//!
+//! - Column defaults (`uuid_generate_v4()`, `gen_random_uuid()`) still apply
+//!   to rows inserted without an id, e.g. by hand or by other services.
+//! - A table only sorts by id once all of its rows were created with a
+//!   time-ordered strategy. Until then, keyset pagination has to order by
+//!   `(created_at, id)`; afterwards `ORDER BY id` alone is enough, and
+//!   [`timestamp`] recovers the creation time from a UUIDv7 cursor.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU8, Ordering};
+use uuid::Uuid;
+
+/// How new primary keys are generated
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IdStrategy {
+    /// Random UUIDv4
+    UuidV4,
+    /// RFC 9562 UUIDv7: 48-bit millisecond timestamp, version and variant
+    /// bits, then random bits; monotonic within this process
+    #[default]
+    UuidV7,
+    /// ULID layout: 48-bit millisecond timestamp, then 80 random bits.
+    /// Stored and rendered as a UUID; it carries no version bits, so it
+    /// can't be told apart from other UUIDs afterwards
+    Ulid,
+}
+
+impl IdStrategy {
+    /// Whether ids sort by creation time (to the millisecond)
+    pub fn is_time_ordered(self) -> bool {
+        !matches!(self, IdStrategy::UuidV4)
+    }
+
+    pub fn generate(self) -> Uuid {
+        match self {
+            IdStrategy::UuidV4 => Uuid::new_v4(),
+            IdStrategy::UuidV7 => Uuid::now_v7(),
+            IdStrategy::Ulid => ulid(Utc::now()),
+        }
+    }
+}
+
+static ID_STRATEGY: AtomicU8 = AtomicU8::new(IdStrategy::UuidV7 as u8);
+
+/// Change how new ids are generated, effective immediately
+pub fn set_strategy(strategy: IdStrategy) {
+    ID_STRATEGY.store(strategy as u8, Ordering::Relaxed);
+}
+
+pub fn strategy() -> IdStrategy {
+    match ID_STRATEGY.load(Ordering::Relaxed) {
+        x if x == IdStrategy::UuidV4 as u8 => IdStrategy::UuidV4,
+        x if x == IdStrategy::Ulid as u8 => IdStrategy::Ulid,
+        _ => IdStrategy::UuidV7,
+    }
+}
+
+/// A new primary key from the current strategy
+pub fn generate() -> Uuid {
+    strategy().generate()
+}
+
+/// When a UUIDv7 was generated; `None` for other versions, including ULIDs
+pub fn timestamp(id: Uuid) -> Option<DateTime<Utc>> {
+    if id.get_version_num() != 7 {
+        return None;
+    }
+    let millis = (id.as_u128() >> 80) as i64;
+    DateTime::from_timestamp_millis(millis)
+}
+
+fn ulid(now: DateTime<Utc>) -> Uuid {
+    let millis = now.timestamp_millis().max(0) as u128 & ((1 << 48) - 1);
+    let random = rand::random::<u128>() & ((1 << 80) - 1);
+    Uuid::from_u128((millis << 80) | random)
+}
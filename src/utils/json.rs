@@ -0,0 +1,130 @@
+use crate::errors::{AppError, BodyError};
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// JSON request body extractor and response, in place of `axum::Json`
+///
+/// A body that doesn't deserialize is rejected with the crate's JSON error
+/// body instead of axum's plain text, naming the offending field and what
+/// was expected there:
+///
+/// ```json
+/// { "error": "invalid type: integer `1`, expected a string", "field": "email", "expected": "a string" }
+/// ```
+///
+/// Malformed JSON is a 400, well-formed JSON of the wrong shape a 422 and a
+/// missing `Content-Type: application/json` a 415, as with `axum::Json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        if !is_json_content_type(req.headers()) {
+            return Err(AppError::InvalidBody(BodyError {
+                status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                message: "Expected request with `Content-Type: application/json`".to_string(),
+                field: None,
+                expected: None,
+            }));
+        }
+
+        let bytes = Bytes::from_request(req, state).await.map_err(|rejection| {
+            AppError::InvalidBody(BodyError {
+                status: rejection.status(),
+                message: rejection.body_text(),
+                field: None,
+                expected: None,
+            })
+        })?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl<T: DeserializeOwned> Json<T> {
+    /// Deserialize a body, reporting failures as [`AppError::InvalidBody`]
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, AppError> {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let value = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| body_error(Some(e.path().to_string()), e.into_inner()))?;
+        // Trailing characters after the value
+        deserializer.end().map_err(|e| body_error(None, e))?;
+        Ok(Json(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+fn body_error(path: Option<String>, inner: serde_json::Error) -> AppError {
+    let status = match inner.classify() {
+        serde_json::error::Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::BAD_REQUEST,
+    };
+
+    // serde_json appends the position; the field path says more
+    let message = inner.to_string();
+    let position = format!(" at line {} column {}", inner.line(), inner.column());
+    let message = message
+        .strip_suffix(&position)
+        .unwrap_or(&message)
+        .to_string();
+
+    let mut field = path.filter(|path| path != ".");
+    if let Some(missing) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        field = Some(match field {
+            Some(parent) => format!("{}.{}", parent, missing),
+            None => missing.to_string(),
+        });
+    }
+
+    let expected = if status == StatusCode::UNPROCESSABLE_ENTITY {
+        message
+            .split_once(", expected ")
+            .map(|(_, expected)| expected.to_string())
+    } else {
+        None
+    };
+
+    AppError::InvalidBody(BodyError {
+        status,
+        message,
+        field,
+        expected,
+    })
+}
@@ -0,0 +1,133 @@
+//! Startup migrations, serialized across replicas.
+//!
+//! Every replica runs the migrations on boot. They take a Postgres advisory
+//! lock on the primary database first, so when several start at once one of
+//! them migrates and the rest wait (reporting `migrations_pending` on
+//! `/ready`) and then find nothing left to apply.
+
+use crate::config::{Config, MigrationConfig};
+use anyhow::Result;
+use sqlx::{migrate::Migrator, PgPool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+// Auth tables have no foreign keys into application data, since they may
+// live in a different database
+static AUTH_MIGRATOR: Migrator = sqlx::migrate!("./migrations/auth");
+
+/// Name this process logs as when it migrates: the pod/host name, else the PID
+pub fn instance_id() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("pid-{}", std::process::id()))
+}
+
+/// Run the application migrations on the primary and every shard, and the
+/// auth migrations on the auth store, holding the migration lock throughout
+pub async fn run_migrations(
+    config: &Config,
+    primary: &PgPool,
+    shards: &[Arc<PgPool>],
+    auth: Option<&PgPool>,
+) -> Result<()> {
+    let settings = &config.database.migrations;
+    let instance = instance_id();
+
+    // Session-level lock, so it lives on one connection kept for the whole run;
+    // closing that connection releases the lock even if unlocking fails
+    let mut lock_conn = primary.acquire().await?;
+    lock_conn.close_on_drop();
+    wait_for_lock(&mut lock_conn, settings, &instance).await?;
+
+    let start = Instant::now();
+    let result = async {
+        let mut applied = 0;
+        for pool in std::iter::once(primary).chain(shards.iter().map(|pool| pool.as_ref())) {
+            applied += apply(&MIGRATOR, pool).await?;
+        }
+        if let Some(auth) = auth {
+            applied += apply(&AUTH_MIGRATOR, auth).await?;
+        }
+        Ok::<_, anyhow::Error>(applied)
+    }
+    .await;
+
+    if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(settings.lock_key)
+        .execute(&mut *lock_conn)
+        .await
+    {
+        tracing::warn!(error = %e, "Failed to release migration lock; closing its connection");
+    }
+
+    let applied = result?;
+    if applied > 0 {
+        tracing::info!(
+            instance = %instance,
+            applied,
+            duration_ms = start.elapsed().as_millis() as u64,
+            "Applied database migrations"
+        );
+    } else {
+        tracing::info!(instance = %instance, "Database schema already up to date");
+    }
+
+    Ok(())
+}
+
+/// Poll `pg_try_advisory_lock` until it succeeds or `lock_timeout_secs` passes
+async fn wait_for_lock(
+    conn: &mut sqlx::PgConnection,
+    settings: &MigrationConfig,
+    instance: &str,
+) -> Result<()> {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(settings.lock_timeout_secs);
+    let poll_interval = Duration::from_millis(settings.lock_poll_interval_ms.max(10));
+    let mut logged = false;
+
+    loop {
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(settings.lock_key)
+            .fetch_one(&mut *conn)
+            .await?;
+        if locked {
+            tracing::debug!(instance = %instance, "Acquired migration lock");
+            return Ok(());
+        }
+
+        if !logged {
+            tracing::info!(
+                instance = %instance,
+                lock_key = settings.lock_key,
+                "Another instance is running migrations, waiting"
+            );
+            logged = true;
+        }
+
+        if start.elapsed() >= timeout {
+            anyhow::bail!(
+                "Timed out after {}s waiting for the migration lock",
+                settings.lock_timeout_secs
+            );
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Run one migrator against a pool, returning how many migrations it applied
+async fn apply(migrator: &Migrator, pool: &PgPool) -> Result<i64> {
+    let before = applied_count(pool).await;
+    migrator.run(pool).await?;
+    Ok(applied_count(pool).await - before)
+}
+
+async fn applied_count(pool: &PgPool) -> i64 {
+    // The bookkeeping table doesn't exist before the first run
+    sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
+        .unwrap_or(0)
+}
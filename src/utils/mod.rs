@@ -1,5 +1,17 @@
+pub mod conditional;
+pub mod cursor;
 pub mod database;
+pub mod id;
+pub mod json;
 pub mod logging;
+pub mod migrations;
+pub mod retry;
+pub mod validation;
 
-pub use database::create_database_pool;
+pub use conditional::{http_date, CollectionVersion};
+pub use cursor::{Cursor, CursorSigner};
+pub use database::{create_auth_database_pool, create_database_pool, create_shard_pools};
+pub use json::Json;
 pub use logging::{init_tracing, init_tracing_with_loki};
+pub use migrations::{instance_id, run_migrations};
+pub use validation::{validate_range, Validate, ValidatedQuery};
@@ -0,0 +1,249 @@
+//! Retrying fallible calls with exponential backoff
+//!
+//! A [`RetryPolicy`] is built once per subsystem (from a [`RetryConfig`] or
+//! with its builder methods) and shared; [`RetryPolicy::run`] calls an
+//! operation until it succeeds, fails with an error the caller doesn't want
+//! retried, or the policy gives up. Only retry operations that are safe to
+//! repeat.
+//!
+//! Policies that share a [`RetryBudget`] stop retrying once retries make up
+//! more than the budget's share of calls, so an outage doesn't multiply the
+//! traffic sent to a struggling dependency. Retries are counted in
+//! `retries_total` by policy name and outcome.
+
+use crate::config::RetryConfig;
+use crate::metrics::AppMetrics;
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How the delay before a retry is randomized
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Jitter {
+    /// Exactly the backoff delay
+    None,
+    /// Anywhere between zero and the backoff delay; spreads out callers that
+    /// failed together the most
+    #[default]
+    Full,
+    /// Half the backoff delay, plus up to another half
+    Equal,
+}
+
+/// Caps retries to a share of calls across every policy holding it
+///
+/// Each call deposits `ratio` tokens and each retry withdraws one. Tokens are
+/// capped at `burst`, which is also what the budget starts with.
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    burst: f64,
+    tokens: Mutex<f64>,
+}
+
+impl RetryBudget {
+    pub fn new(ratio: f64, burst: u32) -> Self {
+        Self {
+            ratio: ratio.max(0.0),
+            burst: f64::from(burst),
+            tokens: Mutex::new(f64::from(burst)),
+        }
+    }
+
+    /// Tokens left; a retry needs one
+    pub fn available(&self) -> f64 {
+        *self.tokens.lock().unwrap()
+    }
+
+    fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap();
+        *tokens = (*tokens + self.ratio).min(self.burst);
+    }
+
+    fn withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap();
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// When and how often a failed call is tried again
+#[derive(Clone)]
+pub struct RetryPolicy {
+    name: &'static str,
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: Jitter,
+    budget: Option<Arc<RetryBudget>>,
+    metrics: Option<AppMetrics>,
+}
+
+impl RetryPolicy {
+    /// Three attempts, 50ms then 100ms apart with full jitter, no budget.
+    /// `name` labels the policy's metrics and logs.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: Jitter::Full,
+            budget: None,
+            metrics: None,
+        }
+    }
+
+    /// A policy with its own budget
+    pub fn from_config(name: &'static str, config: &RetryConfig) -> Self {
+        Self::new(name)
+            .max_attempts(config.max_attempts)
+            .initial_delay(Duration::from_millis(config.initial_delay_ms))
+            .max_delay(Duration::from_millis(config.max_delay_ms))
+            .multiplier(config.multiplier)
+            .jitter(config.jitter)
+            .budget(Arc::new(RetryBudget::new(config.budget_ratio, config.budget_burst)))
+    }
+
+    /// Attempts in total, the first included; 1 disables retries
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Backoff before the first retry
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Ceiling for the backoff, before jitter
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Growth of the backoff from one retry to the next
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Share `budget` with other policies; replaces any budget already set
+    pub fn budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: AppMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Backoff before retry number `retry` (1 for the second attempt),
+    /// jitter applied
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let backoff = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+        let backoff = Duration::from_secs_f64(backoff);
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => backoff.mul_f64(rand::random::<f64>()),
+            Jitter::Equal => backoff / 2 + (backoff / 2).mul_f64(rand::random::<f64>()),
+        }
+    }
+
+    /// Call `operation` with the attempt number (starting at 1) until it
+    /// succeeds or returns an error `retryable` rejects; once the policy
+    /// gives up, the last error is returned
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        operation: F,
+        retryable: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.run_until(std::future::pending(), operation, retryable).await
+    }
+
+    /// [`Self::run`], giving up with the last error if `cancelled` completes
+    /// while waiting to retry; an attempt already under way isn't interrupted
+    pub async fn run_until<T, E, F, Fut>(
+        &self,
+        cancelled: impl Future<Output = ()>,
+        mut operation: F,
+        retryable: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        tokio::pin!(cancelled);
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+
+        let mut attempt = 1;
+        loop {
+            let error = match operation(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if !retryable(&error) {
+                return Err(error);
+            }
+            if attempt >= self.max_attempts {
+                self.record("exhausted");
+                return Err(error);
+            }
+            if !self.budget.as_ref().is_none_or(|budget| budget.withdraw()) {
+                tracing::debug!(policy = self.name, attempt, "Retry budget exhausted, giving up");
+                self.record("budget_exhausted");
+                return Err(error);
+            }
+
+            let delay = self.delay(attempt);
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = &mut cancelled => {
+                    self.record("cancelled");
+                    return Err(error);
+                }
+            }
+            tracing::debug!(
+                policy = self.name,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "Retrying"
+            );
+            self.record("retried");
+            attempt += 1;
+        }
+    }
+
+    fn record(&self, outcome: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_retry(self.name, outcome);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::errors::{AppError, Result};
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+
+/// Checks a value beyond what deserializing it guarantees
+pub trait Validate {
+    fn validate(&self) -> Result<()>;
+}
+
+/// `Option` fields left unset pass; set ones must lie in `min..=max`
+pub fn validate_range<T>(field: &str, value: Option<T>, min: T, max: T) -> Result<()>
+where
+    T: PartialOrd + Display + Copy,
+{
+    match value {
+        Some(value) if value < min || value > max => Err(AppError::Validation(format!(
+            "{} must be between {} and {}, got {}",
+            field, min, max, value
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Extractor for query parameters that are deserialized, then validated
+///
+/// Unlike `Query<T>`, both a query string that doesn't deserialize (e.g.
+/// `page=abc`) and one that fails [`Validate`] (e.g. `page=-1`) are
+/// rejected with the crate's JSON 400 body.
+#[derive(Debug, Clone)]
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| AppError::Validation(rejection.body_text()))?;
+
+        value.validate()?;
+        Ok(ValidatedQuery(value))
+    }
+}
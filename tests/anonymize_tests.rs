@@ -0,0 +1,109 @@
+use reprime_backend::{
+    anonymize::{self, fake_email, fake_username, FAKE_EMAIL_DOMAIN},
+    config::Config,
+    database::InstrumentedDatabase,
+    metrics::MetricsRegistry,
+    models::{audit_actions, audit_resources, AuditEvent, AuditFilter, CreateUserRequest},
+    repositories::Repositories,
+    services::storage_from_config,
+    utils::run_migrations,
+};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[test]
+fn test_fake_values_are_deterministic_per_user() {
+    let id = Uuid::new_v4();
+    assert_eq!(fake_email(id), fake_email(id));
+    assert_eq!(fake_username(id), fake_username(id));
+
+    let other = Uuid::new_v4();
+    assert_ne!(fake_email(id), fake_email(other));
+    assert_ne!(fake_username(id), fake_username(other));
+}
+
+#[test]
+fn test_fake_values_pass_user_validation() {
+    let id = Uuid::new_v4();
+    let request = CreateUserRequest {
+        email: fake_email(id),
+        username: fake_username(id),
+    };
+
+    assert!(request.validate().is_ok());
+    assert!(request.email.ends_with(FAKE_EMAIL_DOMAIN));
+    assert!(request.username.len() <= 100);
+}
+
+#[tokio::test]
+async fn test_avatars_and_audit_details_are_scrubbed() {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return;
+    };
+    let mut config = Config::default();
+    config.database.url = url.clone();
+    config.storage.local_path = std::env::temp_dir()
+        .join(format!("anonymize-{}", Uuid::new_v4()))
+        .to_string_lossy()
+        .into_owned();
+
+    let pool = PgPoolOptions::new().connect(&url).await.expect("test database");
+    run_migrations(&config, &pool, &[], None).await.expect("migrations");
+    let repositories = Repositories::new(Arc::new(InstrumentedDatabase::new(pool, None)));
+    let run = Uuid::new_v4().simple().to_string();
+    let user = repositories
+        .user
+        .create(CreateUserRequest {
+            email: format!("avatar-{}@example.com", run),
+            username: format!("avatar-{}", &run[..12]),
+        })
+        .await
+        .unwrap();
+
+    let storage = storage_from_config(&config.storage, &config.auth.jwt_secret, &MetricsRegistry::new()).unwrap();
+    let key = format!("avatars/{}/{}.png", user.id, run);
+    storage.put(&key, "image/png", vec![0x89, b'P', b'N', b'G']).await.unwrap();
+    repositories
+        .user
+        .set_avatar_key(user.id, Some(&key))
+        .await
+        .unwrap();
+
+    let tenant = format!("anonymize-{}", run);
+    repositories
+        .audit
+        .record(
+            &AuditEvent::new(tenant.clone(), Some(user.id), audit_actions::USER_UPDATED)
+                .with_resource(audit_resources::USER, user.id)
+                .with_details(serde_json::json!({ "email": user.email }))
+                .with_change(Some(&user), Some(&user))
+                .with_ip_address(Some("203.0.113.7".to_string())),
+        )
+        .await
+        .unwrap();
+
+    let report = anonymize::run(&config).await.unwrap();
+    assert!(report.avatars >= 1);
+    assert!(report.audit_events >= 1);
+
+    let entries = repositories
+        .audit
+        .list(&tenant, &AuditFilter::default(), None, 10)
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.actor_id, Some(user.id));
+    assert_eq!(entry.resource_id, Some(user.id.to_string()));
+    assert_eq!(entry.details, serde_json::json!({}));
+    assert!(entry.before.is_none() && entry.after.is_none());
+    assert!(entry.ip_address.is_none());
+
+    let user = repositories.user.find_by_id(user.id).await.unwrap().unwrap();
+    assert_eq!(user.avatar_key, None);
+    assert_eq!(user.email, fake_email(user.id));
+    assert!(storage.get(&key).await.unwrap().is_none());
+    let _ = std::fs::remove_dir_all(&config.storage.local_path);
+}
@@ -0,0 +1,107 @@
+use axum::{body::Body, http::Request, middleware, routing::get, Router};
+use reprime_backend::config::{ApiUsageConfig, DeprecatedRouteConfig};
+use reprime_backend::middleware::api_usage::{api_version, ANONYMOUS_CLIENT, OVERFLOW_CLIENT};
+use reprime_backend::middleware::{api_usage_middleware, ApiUsage};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn usage(max_entries: usize, deprecations: Vec<DeprecatedRouteConfig>) -> Arc<ApiUsage> {
+    Arc::new(
+        ApiUsage::new(&ApiUsageConfig {
+            enabled: true,
+            max_entries,
+            deprecations,
+        })
+        .expect("valid config"),
+    )
+}
+
+fn deprecated(route: &str, method: Option<&str>) -> DeprecatedRouteConfig {
+    DeprecatedRouteConfig {
+        route: route.to_string(),
+        method: method.map(str::to_string),
+        deprecated_on: "2025-01-01".to_string(),
+        sunset_on: Some("2025-07-01".to_string()),
+        link: Some("https://docs.example.com/migrate".to_string()),
+    }
+}
+
+#[test]
+fn test_counts_per_route_and_client() {
+    let usage = usage(100, Vec::new());
+    usage.record("GET", "/api/v1/users/{id}", "user:a");
+    usage.record("GET", "/api/v1/users/{id}", "user:a");
+    usage.record("GET", "/api/v1/users/{id}", "user:b");
+
+    let entries = usage.snapshot();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].client, "user:a");
+    assert_eq!(entries[0].calls, 2);
+    assert_eq!(entries[0].version, "v1");
+    assert!(entries[0].deprecated_on.is_none());
+}
+
+#[test]
+fn test_new_clients_fold_into_other_when_full() {
+    let usage = usage(1, Vec::new());
+    usage.record("GET", "/health", "user:a");
+    usage.record("GET", "/health", "user:b");
+    usage.record("GET", "/health", "user:c");
+    usage.record("GET", "/health", "user:a");
+
+    let entries = usage.snapshot();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().any(|e| e.client == OVERFLOW_CLIENT && e.calls == 2));
+    assert!(entries.iter().any(|e| e.client == "user:a" && e.calls == 2));
+}
+
+#[test]
+fn test_api_version_from_route() {
+    assert_eq!(api_version("/api/v1/users"), "v1");
+    assert_eq!(api_version("/api/v2/admin/api-usage"), "v2");
+    assert_eq!(api_version("/health"), "unversioned");
+}
+
+#[test]
+fn test_invalid_deprecation_date_is_rejected() {
+    let mut route = deprecated("/api/v1/users", None);
+    route.deprecated_on = "soon".to_string();
+
+    assert!(ApiUsage::new(&ApiUsageConfig {
+        enabled: true,
+        max_entries: 10,
+        deprecations: vec![route],
+    })
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_deprecated_route_gets_headers() {
+    let usage = usage(100, vec![deprecated("/api/v1/users", Some("get"))]);
+    let app = Router::new()
+        .route("/api/v1/users", get(|| async { "ok" }).post(|| async { "ok" }))
+        .layer(middleware::from_fn_with_state(usage.clone(), api_usage_middleware));
+
+    let response = app
+        .clone()
+        .oneshot(Request::get("/api/v1/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let headers = response.headers();
+    assert_eq!(headers["deprecation"], "@1735689600");
+    assert_eq!(headers["sunset"], "Tue, 01 Jul 2025 00:00:00 GMT");
+    assert_eq!(headers["link"], "<https://docs.example.com/migrate>; rel=\"deprecation\"");
+
+    let response = app
+        .oneshot(Request::post("/api/v1/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(response.headers().get("deprecation").is_none());
+
+    let entries = usage.snapshot();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.client == ANONYMOUS_CLIENT));
+    assert!(entries
+        .iter()
+        .any(|e| e.method == "GET" && e.sunset_on.is_some()));
+}
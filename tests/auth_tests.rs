@@ -1,6 +1,8 @@
 use reprime_backend::auth::{
     jwt::JwtService,
-    models::{AuthContext, Claims},
+    models::{
+        AuthContext, SessionInfo, SessionMetadata, SubjectType, UserSession, UserTokenState,
+    },
 };
 use reprime_backend::config::Config;
 use uuid::Uuid;
@@ -8,7 +10,7 @@ use uuid::Uuid;
 #[tokio::test]
 async fn test_jwt_token_generation_and_validation() {
     let config = Config::default();
-    let jwt_service = JwtService::new(&config);
+    let jwt_service = JwtService::new(&config).expect("jwt service");
 
     let user_id = Uuid::new_v4();
     let email = "test@example.com".to_string();
@@ -43,6 +45,45 @@ async fn test_jwt_token_generation_and_validation() {
     assert_eq!(auth_context.roles, roles);
 }
 
+#[tokio::test]
+async fn test_session_token_carries_session_id() {
+    let config = Config::default();
+    let jwt_service = JwtService::new(&config).expect("jwt service");
+
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let token = jwt_service
+        .generate_session_token(
+            user_id,
+            "test@example.com".to_string(),
+            "testuser".to_string(),
+            vec!["user".to_string()],
+            session_id,
+            UserTokenState::default(),
+        )
+        .expect("Failed to generate token");
+
+    let claims = jwt_service
+        .validate_token(&token)
+        .expect("Failed to validate token");
+    assert_eq!(claims.sid, Some(session_id.to_string()));
+
+    let auth_context = jwt_service
+        .extract_auth_context(&token)
+        .expect("Failed to extract auth context");
+    assert_eq!(auth_context.session_id, Some(session_id));
+
+    // Tokens issued without a session have no `sid` claim
+    let plain_token = jwt_service
+        .generate_token(user_id, "test@example.com".to_string(), "testuser".to_string(), vec![])
+        .expect("Failed to generate token");
+    let auth_context = jwt_service
+        .extract_auth_context(&plain_token)
+        .expect("Failed to extract auth context");
+    assert_eq!(auth_context.session_id, None);
+}
+
 #[tokio::test]
 async fn test_jwt_token_expiration() {
     // Create a token that expires in the past
@@ -50,7 +91,7 @@ async fn test_jwt_token_expiration() {
     use jsonwebtoken::{encode, Header};
 
     let config = Config::default();
-    let jwt_service = JwtService::new(&config);
+    let jwt_service = JwtService::new(&config).expect("jwt service");
 
     // Create an expired token manually
     let now = Utc::now();
@@ -63,6 +104,15 @@ async fn test_jwt_token_expiration() {
         roles: vec!["user".to_string()],
         exp: expired_time.timestamp() as usize,
         iat: expired_time.timestamp() as usize,
+        sid: None,
+        tenant: None,
+        scope: None,
+        sub_type: None,
+        iss: Some(config.auth.jwt_issuer.clone()),
+        aud: Some(config.auth.jwt_audience.clone()),
+        ver: None,
+        uver: None,
+        roles_updated_at: None,
     };
 
     let secret = config.auth.jwt_secret.as_bytes();
@@ -82,6 +132,10 @@ fn test_role_checking() {
         email: "test@example.com".to_string(),
         username: "testuser".to_string(),
         roles: vec!["user".to_string(), "admin".to_string()],
+        session_id: None,
+        tenant_id: None,
+        scopes: None,
+        subject_type: SubjectType::User,
     };
 
     // Test has_role
@@ -115,6 +169,58 @@ fn test_token_header_extraction() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_refresh_tokens_are_opaque_and_hashed() {
+    let config = Config::default();
+    let jwt_service = JwtService::new(&config).expect("jwt service");
+
+    let first = JwtService::generate_opaque_token();
+    let second = JwtService::generate_opaque_token();
+    assert_ne!(first, second);
+    assert!(jwt_service.validate_token(&first).is_err());
+
+    // Lookups hash the presented token the same way it was stored
+    let hash = JwtService::hash_opaque_token(&first);
+    assert_eq!(hash, JwtService::hash_opaque_token(&first));
+    assert_ne!(hash, first);
+    assert_ne!(hash, JwtService::hash_opaque_token(&second));
+
+    assert_eq!(jwt_service.refresh_expires_in(), 30 * 86400);
+}
+
+#[test]
+fn test_session_info_marks_current_session() {
+    let mut headers = axum::http::HeaderMap::new();
+    headers.insert("user-agent", "curl/8.5.0".parse().unwrap());
+    headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+    let metadata = SessionMetadata::from_headers(&headers);
+
+    let now = chrono::Utc::now();
+    let session = UserSession {
+        id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        device: metadata.device,
+        ip_address: metadata.ip_address,
+        scope: None,
+        expires_at: now + chrono::Duration::days(30),
+        created_at: now,
+        last_used_at: None,
+        revoked_at: None,
+        remember_me: false,
+        absolute_expires_at: None,
+        device_fingerprint: None,
+        verified: true,
+    };
+    let session_id = session.id;
+
+    let info = SessionInfo::from_session(session.clone(), Some(session_id));
+    assert!(info.current);
+    assert_eq!(info.device.as_deref(), Some("curl/8.5.0"));
+    assert_eq!(info.ip_address.as_deref(), Some("203.0.113.7"));
+
+    assert!(!SessionInfo::from_session(session, Some(Uuid::new_v4())).current);
+}
+
 #[tokio::test]
 async fn test_openfga_service_creation() {
     let config = Config::default();
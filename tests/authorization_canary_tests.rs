@@ -0,0 +1,106 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    middleware::{self, Next},
+    routing::get,
+    Extension, Router,
+};
+use reprime_backend::{
+    auth::{
+        authorizer::StaticAuthorizer,
+        canary::AuthorizationCanary,
+        middleware::require_role,
+        models::{roles, AuthContext, SubjectType},
+    },
+    config::{AuthorizationCanaryConfig, CanaryMode, MetricsConfig},
+    metrics::AppMetrics,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// `/api/v1/admin/*` runs in `mode`; the caller holds `admin` on the default
+/// tenant in OpenFGA, but not the admin role
+async fn call(mode: CanaryMode, metrics: &AppMetrics) -> StatusCode {
+    let user_id = Uuid::new_v4();
+    let authorizer = Arc::new(StaticAuthorizer::new().with_tuple(
+        user_id,
+        "admin",
+        "organization",
+        "default",
+    ));
+    let config = AuthorizationCanaryConfig {
+        enabled: true,
+        route_groups: HashMap::from([("admin".to_string(), mode)]),
+        ..Default::default()
+    };
+    let canary = AuthorizationCanary::new(&config, &MetricsConfig::default(), authorizer)
+        .unwrap()
+        .with_metrics(metrics.clone());
+
+    let context = AuthContext {
+        user_id,
+        email: "member@example.com".to_string(),
+        username: "member".to_string(),
+        roles: vec![roles::USER.to_string()],
+        session_id: None,
+        tenant_id: None,
+        scopes: None,
+        subject_type: SubjectType::User,
+    };
+    let app = Router::new()
+        .route("/api/v1/admin/jobs", get(|| async { "ok" }))
+        .layer(middleware::from_fn(require_role(roles::ADMIN)))
+        .layer(middleware::from_fn(move |mut request: Request, next: Next| {
+            request.extensions_mut().insert(context.clone());
+            next.run(request)
+        }))
+        .layer(Extension(Arc::new(canary)));
+
+    app.oneshot(Request::get("/api/v1/admin/jobs").body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_shadow_mode_serves_the_role_decision_and_counts_disagreements() {
+    let metrics = AppMetrics::new().unwrap();
+
+    assert_eq!(call(CanaryMode::Legacy, &metrics).await, StatusCode::FORBIDDEN);
+    let disagreements = metrics
+        .authz_canary_evaluations_total
+        .with_label_values(&["admin", "disagree"]);
+    assert_eq!(disagreements.get(), 0.0);
+
+    assert_eq!(call(CanaryMode::Shadow, &metrics).await, StatusCode::FORBIDDEN);
+    assert_eq!(disagreements.get(), 1.0);
+}
+
+#[tokio::test]
+async fn test_relations_mode_serves_the_relation_decision() {
+    let metrics = AppMetrics::new().unwrap();
+
+    assert_eq!(call(CanaryMode::Relations, &metrics).await, StatusCode::OK);
+    let disagreements = metrics
+        .authz_canary_evaluations_total
+        .with_label_values(&["admin", "disagree"]);
+    assert_eq!(disagreements.get(), 1.0);
+}
+
+#[test]
+fn test_unknown_relation_is_rejected_at_startup() {
+    let mut config = AuthorizationCanaryConfig::default();
+    config
+        .role_relations
+        .insert("admin".to_string(), "superuser".to_string());
+
+    let result = AuthorizationCanary::new(
+        &config,
+        &MetricsConfig::default(),
+        Arc::new(StaticAuthorizer::new()),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,117 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    http::Request,
+    routing::{get, post},
+    Router,
+};
+use reprime_backend::{
+    config::{BandwidthConfig, BandwidthLimit, MetricsConfig},
+    middleware::{BandwidthLayer, BandwidthThrottle},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+const PAYLOAD_BYTES: usize = 20 * 1024;
+
+/// `/exports` limited to 100 KiB/s with 5 KiB bursts, so 20 KiB takes 150ms
+fn config() -> (BandwidthConfig, MetricsConfig) {
+    let limit = BandwidthLimit {
+        upload_bytes_per_sec: Some(100 * 1024),
+        download_bytes_per_sec: Some(100 * 1024),
+        burst_bytes: Some(5 * 1024),
+    };
+    let bandwidth = BandwidthConfig {
+        enabled: true,
+        route_groups: HashMap::from([("exports".to_string(), limit)]),
+        ..BandwidthConfig::default()
+    };
+    let metrics = MetricsConfig {
+        route_groups: HashMap::from([("exports".to_string(), vec!["/exports".to_string()])]),
+        ..MetricsConfig::default()
+    };
+    (bandwidth, metrics)
+}
+
+fn app() -> Router {
+    let (bandwidth, metrics) = config();
+    let throttle = BandwidthThrottle::from_config(&bandwidth, &metrics).expect("enabled");
+
+    Router::new()
+        .route("/exports/report", get(|| async { "x".repeat(PAYLOAD_BYTES) }))
+        .route("/exports/import", post(|body: String| async move { body.len().to_string() }))
+        .route("/other", get(|| async { "x".repeat(PAYLOAD_BYTES) }))
+        .layer(BandwidthLayer::new(throttle))
+}
+
+/// A GET as served to `peer`
+fn get_from(uri: &str, peer: &str) -> Request<Body> {
+    let mut request = Request::get(uri).body(Body::empty()).unwrap();
+    let peer: SocketAddr = peer.parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(peer));
+    request
+}
+
+async fn timed(app: Router, request: Request<Body>) -> (String, Duration) {
+    let started = Instant::now();
+    let response = app.oneshot(request).await.unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (String::from_utf8(body.to_vec()).unwrap(), started.elapsed())
+}
+
+#[tokio::test]
+async fn test_download_is_paced_to_the_group_rate() {
+    let (body, elapsed) = timed(app(), get_from("/exports/report", "10.0.0.1:4000")).await;
+    assert_eq!(body.len(), PAYLOAD_BYTES);
+    assert!(elapsed >= Duration::from_millis(140), "took {:?}", elapsed);
+
+    let (body, elapsed) = timed(app(), get_from("/other", "10.0.0.1:4000")).await;
+    assert_eq!(body.len(), PAYLOAD_BYTES);
+    assert!(elapsed < Duration::from_millis(100), "took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_upload_is_paced_to_the_group_rate() {
+    let request = Request::post("/exports/import")
+        .body(Body::from("x".repeat(PAYLOAD_BYTES)))
+        .unwrap();
+    let (body, elapsed) = timed(app(), request).await;
+
+    assert_eq!(body, PAYLOAD_BYTES.to_string());
+    assert!(elapsed >= Duration::from_millis(140), "took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_concurrent_downloads_from_one_client_share_its_rate() {
+    let app = app();
+
+    // 40 KiB between them, less the 5 KiB burst, takes about 350ms
+    let (first, second, other) = tokio::join!(
+        timed(app.clone(), get_from("/exports/report", "10.0.0.1:4000")),
+        timed(app.clone(), get_from("/exports/report", "10.0.0.1:4001")),
+        timed(app.clone(), get_from("/exports/report", "10.0.0.2:4000")),
+    );
+    assert_eq!(first.0.len(), PAYLOAD_BYTES);
+    assert_eq!(second.0.len(), PAYLOAD_BYTES);
+    let slowest = first.1.max(second.1);
+    assert!(slowest >= Duration::from_millis(300), "took {:?}", slowest);
+
+    // Another client has its own bucket
+    assert_eq!(other.0.len(), PAYLOAD_BYTES);
+    assert!(other.1 < Duration::from_millis(300), "took {:?}", other.1);
+}
+
+#[test]
+fn test_disabled_or_empty_config_adds_no_layer() {
+    let (mut bandwidth, metrics) = config();
+    bandwidth.enabled = false;
+    assert!(BandwidthThrottle::from_config(&bandwidth, &metrics).is_none());
+
+    let no_groups = BandwidthConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    assert!(BandwidthThrottle::from_config(&no_groups, &metrics).is_none());
+}
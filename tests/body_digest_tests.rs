@@ -0,0 +1,79 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    middleware,
+    routing::post,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reprime_backend::middleware::{body_digest::ExpectedDigest, verify_body_digest};
+use sha2::{Digest, Sha256};
+use tower::ServiceExt;
+
+const BODY: &str = r#"{"email":"new@example.com"}"#;
+
+fn app() -> Router {
+    Router::new()
+        .route("/echo", post(|body: String| async move { body }))
+        .layer(middleware::from_fn(verify_body_digest))
+}
+
+async fn send(header: Option<(&str, String)>, body: &str) -> (StatusCode, String) {
+    let mut request = Request::builder().method("POST").uri("/echo");
+    if let Some((name, value)) = header {
+        request = request.header(name, value);
+    }
+    let response = app()
+        .oneshot(request.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_matching_digests_pass_the_body_through() {
+    let digest = Sha256::digest(BODY.as_bytes());
+
+    let content_digest = format!("sha-256=:{}:", STANDARD.encode(digest));
+    assert_eq!(send(Some(("content-digest", content_digest)), BODY).await, (StatusCode::OK, BODY.to_string()));
+
+    let hex = format!("{:x}", digest);
+    assert_eq!(send(Some(("x-content-sha256", hex)), BODY).await, (StatusCode::OK, BODY.to_string()));
+
+    assert_eq!(send(None, BODY).await, (StatusCode::OK, BODY.to_string()));
+}
+
+#[tokio::test]
+async fn test_truncated_body_is_rejected() {
+    let digest = Sha256::digest(BODY.as_bytes());
+    let truncated = &BODY[..BODY.len() - 2];
+
+    let (status, _) = send(Some(("x-content-sha256", format!("{:x}", digest))), truncated).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let content_digest = format!("sha-256=:{}:", STANDARD.encode(digest));
+    let (status, _) = send(Some(("content-digest", content_digest)), truncated).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_digest_headers_are_parsed_strictly() {
+    let parse = |name: &str, value: &str| {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        ExpectedDigest::from_headers(&headers)
+    };
+
+    assert!(parse("x-content-sha256", "abc").is_err());
+    assert!(parse("content-digest", "sha-256=not-base64").is_err());
+    assert!(parse("content-digest", "md5=:AAAA:").is_err());
+
+    // Unknown algorithms are skipped when a supported one is present
+    let digest = parse("content-digest", "unixsum=:AAAA:, sha-256=:AAAA:").unwrap();
+    assert_eq!(digest, Some(ExpectedDigest::Sha256(vec![0, 0, 0])));
+}
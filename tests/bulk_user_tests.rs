@@ -0,0 +1,180 @@
+use reprime_backend::config::Config;
+use reprime_backend::database::{InstrumentedDatabase, ShardKey, ShardRouter};
+use reprime_backend::models::{
+    BulkDeleteUsersRequest, BulkMode, BulkResult, BulkUserRolesRequest, CreateUserRequest, User,
+    MAX_BULK_USERS,
+};
+use reprime_backend::repositories::Repositories;
+use reprime_backend::utils::{run_migrations, Json};
+use serde_json::json;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn roles_request(body: serde_json::Value) -> BulkUserRolesRequest {
+    Json::<BulkUserRolesRequest>::from_bytes(body.to_string().as_bytes())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_mode_defaults_to_all_or_nothing() {
+    let request: BulkDeleteUsersRequest = Json::from_bytes(
+        json!({ "user_ids": [Uuid::from_u128(1).to_string()] }).to_string().as_bytes(),
+    )
+    .unwrap()
+    .0;
+
+    assert_eq!(request.mode, BulkMode::AllOrNothing);
+    assert!(request.validate().is_ok());
+}
+
+#[test]
+fn test_user_ids_are_validated() {
+    let id = Uuid::from_u128(1);
+    let request = |user_ids: Vec<Uuid>| BulkDeleteUsersRequest {
+        user_ids,
+        mode: BulkMode::BestEffort,
+    };
+
+    assert!(request(vec![]).validate().is_err());
+    assert!(request(vec![id, id]).validate().is_err());
+    assert!(request((0..=MAX_BULK_USERS as u128).map(Uuid::from_u128).collect())
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_roles_must_be_known() {
+    let user_ids = [Uuid::from_u128(1).to_string()];
+    let valid = roles_request(json!({ "user_ids": user_ids, "action": "add", "roles": ["moderator"] }));
+    assert!(valid.validate().is_ok());
+
+    let unknown = roles_request(json!({ "user_ids": user_ids, "action": "remove", "roles": ["root"] }));
+    assert!(unknown.validate().is_err());
+
+    let empty = roles_request(json!({ "user_ids": user_ids, "action": "add", "roles": [] }));
+    assert!(empty.validate().is_err());
+}
+
+#[test]
+fn test_missing_users_depend_on_mode() {
+    let missing = [Uuid::from_u128(7)];
+
+    assert!(BulkResult::with_missing(BulkMode::AllOrNothing, &missing).is_err());
+    assert!(BulkResult::with_missing(BulkMode::AllOrNothing, &[]).is_ok());
+
+    let result = BulkResult::with_missing(BulkMode::BestEffort, &missing).unwrap();
+    assert_eq!(result.failed.len(), 1);
+    assert_eq!(result.failed[0].user_id, missing[0]);
+    assert!(result.succeeded.is_empty());
+}
+
+/// The test database and a second one next to it as two shards, with a
+/// trigger on the second that fails the commit of any transaction deleting
+/// an `undeletable-` user
+async fn sharded_repositories() -> Option<(Arc<ShardRouter>, Repositories)> {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return None;
+    };
+    let primary = PgPoolOptions::new().connect(&url).await.expect("test database");
+
+    let (server, name) = url.rsplit_once('/').expect("database url with a name");
+    let shard_name = format!("{}_shard1", name);
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pg_database WHERE datname = $1)")
+        .bind(&shard_name)
+        .fetch_one(&primary)
+        .await
+        .unwrap();
+    if !exists {
+        // Another test binary may be creating it too
+        let _ = sqlx::query(&format!("CREATE DATABASE \"{}\"", shard_name))
+            .execute(&primary)
+            .await;
+    }
+    let shard = Arc::new(
+        PgPoolOptions::new()
+            .connect(&format!("{}/{}", server, shard_name))
+            .await
+            .expect("shard database"),
+    );
+    run_migrations(&Config::default(), &primary, std::slice::from_ref(&shard), None)
+        .await
+        .expect("migrations");
+
+    sqlx::raw_sql(
+        r#"
+        CREATE OR REPLACE FUNCTION refuse_undeletable() RETURNS trigger AS $$
+        BEGIN
+            RAISE EXCEPTION 'user % is undeletable', OLD.id;
+        END;
+        $$ LANGUAGE plpgsql;
+        DROP TRIGGER IF EXISTS refuse_undeletable ON users;
+        CREATE CONSTRAINT TRIGGER refuse_undeletable AFTER DELETE ON users
+            DEFERRABLE INITIALLY DEFERRED FOR EACH ROW
+            WHEN (OLD.username LIKE 'undeletable-%')
+            EXECUTE FUNCTION refuse_undeletable();
+        "#,
+    )
+    .execute(shard.as_ref())
+    .await
+    .unwrap();
+
+    let primary = Arc::new(InstrumentedDatabase::new(primary, None));
+    let shards = Arc::new(ShardRouter::new(
+        primary.clone(),
+        vec![Arc::new(InstrumentedDatabase::new((*shard).clone(), None))],
+    ));
+    Some((shards.clone(), Repositories::sharded(shards, primary)))
+}
+
+/// A new user on shard `index`; ids are random, so create until one lands there
+async fn user_on_shard(shards: &ShardRouter, repositories: &Repositories, index: usize, prefix: &str) -> User {
+    loop {
+        let run = Uuid::new_v4().simple().to_string();
+        let user = repositories
+            .user
+            .create(CreateUserRequest {
+                email: format!("{}-{}@example.com", prefix, run),
+                username: format!("{}-{}", prefix, &run[..12]),
+            })
+            .await
+            .unwrap();
+        if shards.shard_index(ShardKey::User(user.id)) == index {
+            return user;
+        }
+        repositories.user.delete(user.id).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_deletions_committed_before_a_failing_shard_are_reported() {
+    let Some((shards, repositories)) = sharded_repositories().await else {
+        return;
+    };
+    let kept = user_on_shard(&shards, &repositories, 1, "undeletable").await;
+    let gone = user_on_shard(&shards, &repositories, 0, "deletable").await;
+
+    // The primary commits first; the second shard's commit then fails
+    let result = repositories
+        .user
+        .delete_many(&[gone.id, kept.id], true)
+        .await
+        .unwrap();
+    assert_eq!(result.deleted, vec![gone.id]);
+    assert_eq!(result.uncommitted, vec![kept.id]);
+    assert!(repositories.user.find_by_id(gone.id).await.unwrap().is_none());
+    assert!(repositories.user.find_by_id(kept.id).await.unwrap().is_some());
+
+    // Nothing committed yet, so it's an error
+    assert!(repositories.user.delete_many(&[kept.id], true).await.is_err());
+
+    sqlx::query("UPDATE users SET username = $2 WHERE id = $1")
+        .bind(kept.id)
+        .bind(format!("kept-{}", kept.id.simple()))
+        .execute(shards.for_user(kept.id).pool())
+        .await
+        .unwrap();
+    repositories.user.delete(kept.id).await.unwrap();
+}
@@ -0,0 +1,96 @@
+use reprime_backend::{
+    auth::{
+        breaker::{BreakerState, CircuitBreaker},
+        openfga::OpenFgaService,
+    },
+    config::{Config, FallbackMode},
+    metrics::AppMetrics,
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// OpenFGA that refuses connections, with the breaker tripping on the first failure
+fn unreachable_config() -> Config {
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = "http://127.0.0.1:1".to_string();
+    config.auth.openfga.cache_enabled = false;
+    config.auth.openfga.circuit_breaker.failure_threshold = 1;
+    config
+        .auth
+        .openfga
+        .circuit_breaker
+        .relation_fallback
+        .insert("viewer".to_string(), FallbackMode::FailOpen);
+    config
+}
+
+#[tokio::test]
+async fn test_breaker_opens_probes_and_closes() {
+    let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+
+    breaker.record_failure();
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    breaker.record_failure();
+    assert_eq!(breaker.state(), BreakerState::Open);
+    assert!(!breaker.try_acquire());
+
+    // One probe after the open period; a failed probe opens it again
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(breaker.try_acquire());
+    assert!(!breaker.try_acquire());
+    breaker.record_failure();
+    assert_eq!(breaker.state(), BreakerState::Open);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(breaker.try_acquire());
+    breaker.record_success();
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    assert!(breaker.try_acquire());
+}
+
+#[tokio::test]
+async fn test_outage_is_answered_by_the_fallback_policy() {
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&unreachable_config())
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+    let user_id = Uuid::new_v4();
+
+    let result = service
+        .check_permission(user_id, "editor", "document", "roadmap")
+        .await
+        .unwrap();
+    assert!(!result.allowed);
+    assert_eq!(service.breaker_state(), Some(BreakerState::Open));
+
+    // Open now: answered without calling OpenFGA, per relation
+    let result = service
+        .check_permission(user_id, "viewer", "document", "roadmap")
+        .await
+        .unwrap();
+    assert!(result.allowed);
+
+    assert!(service.list_objects(user_id, "viewer", "document").await.is_err());
+    assert_eq!(metrics.openfga_circuit_breaker_state.get(), 2.0);
+    assert_eq!(
+        metrics
+            .openfga_fallback_decisions_total
+            .with_label_values(&["viewer", "allow"])
+            .get(),
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn test_without_a_breaker_failures_are_errors() {
+    let mut config = unreachable_config();
+    config.auth.openfga.circuit_breaker.enabled = false;
+    let service = OpenFgaService::new(&config).await.unwrap();
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "roadmap")
+        .await;
+    assert!(result.is_err());
+    assert_eq!(service.breaker_state(), None);
+}
@@ -0,0 +1,106 @@
+use axum::{body::Body, http::Request, middleware, routing::get, Router};
+use reprime_backend::config::{ClientAnalyticsConfig, MetricsConfig};
+use reprime_backend::metrics::{AppMetrics, RouteGroups};
+use reprime_backend::middleware::client_analytics::{
+    ClientInfo, BROWSER_CLIENT, OTHER_CLIENT, UNKNOWN_CLIENT,
+};
+use reprime_backend::middleware::{client_analytics_middleware, ClientAnalytics};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn families() -> Vec<String> {
+    ClientAnalyticsConfig::default().families
+}
+
+#[test]
+fn test_user_agent_parsing() {
+    let parse = |user_agent| ClientInfo::parse(user_agent, &families());
+
+    let sdk = parse(Some("acme-app/3.0 (Linux; x64) Reprime-JS/2.1.0-beta.1"));
+    assert_eq!(sdk.family, "reprime-js");
+    assert_eq!(sdk.version.as_deref(), Some("2.1.0-beta.1"));
+
+    let browser = parse(Some("Mozilla/5.0 (X11; Linux x86_64) curl/8.0 Chrome/120.0"));
+    assert_eq!(browser.family, "curl", "a configured family wins over the browser token");
+    let browser = parse(Some("Mozilla/5.0 (Macintosh; Intel Mac OS X) Safari/605.1.15"));
+    assert_eq!((browser.family.as_str(), browser.version), (BROWSER_CLIENT, None));
+
+    assert_eq!(parse(Some("scraper/1.0")).family, OTHER_CLIENT);
+    assert_eq!(parse(Some("   ")).family, UNKNOWN_CLIENT);
+    assert_eq!(parse(None).family, UNKNOWN_CLIENT);
+
+    let long = parse(Some(&format!("curl/{}<script>", "9".repeat(64))));
+    assert_eq!(long.version.unwrap().len(), 32);
+}
+
+#[test]
+fn test_snapshot_groups_versions_and_bounds_the_store() {
+    let analytics = ClientAnalytics::new(&ClientAnalyticsConfig {
+        max_entries: 2,
+        ..ClientAnalyticsConfig::default()
+    });
+    for user_agent in ["reprime-js/2.9.0", "reprime-js/2.10.0", "reprime-js/2.10.0", "curl/8.4.0"] {
+        let client = analytics.identify(Some(user_agent));
+        analytics.record(&client, "GET", "/api/v1/users");
+    }
+
+    let entries = analytics.snapshot();
+    let summary: Vec<(&str, Option<&str>, u64)> = entries
+        .iter()
+        .map(|e| (e.client_family.as_str(), e.client_version.as_deref(), e.calls))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("other", None, 1),
+            ("reprime-js", Some("2.10.0"), 2),
+            ("reprime-js", Some("2.9.0"), 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_counts_matched_routes_by_family() {
+    let metrics = AppMetrics::new()
+        .unwrap()
+        .with_route_groups(RouteGroups::from_config(&MetricsConfig::default().route_groups));
+    let analytics = Arc::new(
+        ClientAnalytics::new(&ClientAnalyticsConfig::default()).with_metrics(metrics.clone()),
+    );
+    let app = Router::new()
+        .route(
+            "/api/v1/users/{id}",
+            get(|client: axum::Extension<ClientInfo>| async move { client.0.family }),
+        )
+        .layer(middleware::from_fn_with_state(analytics.clone(), client_analytics_middleware));
+
+    for uri in ["/api/v1/users/1", "/api/v1/users/2", "/nowhere"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header("user-agent", "reprime-python/1.4.2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        if uri != "/nowhere" {
+            let body = axum::body::to_bytes(response.into_body(), 64).await.unwrap();
+            assert_eq!(&body[..], b"reprime-python");
+        }
+    }
+
+    let entries = analytics.snapshot();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].route, "/api/v1/users/{id}");
+    assert_eq!(entries[0].calls, 2);
+    assert_eq!(
+        metrics
+            .http_client_requests_total
+            .with_label_values(&["reprime-python", "users"])
+            .get(),
+        2.0
+    );
+}
@@ -0,0 +1,154 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use reprime_backend::{
+    config::CoalesceConfig,
+    metrics::AppMetrics,
+    middleware::{coalesce_middleware, RequestCoalescer},
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+/// `/stats` (coalesced) and `/other` (not), both slow and counting calls
+fn app(coalescer: Arc<RequestCoalescer>, calls: Arc<AtomicUsize>) -> Router {
+    let handler = move || {
+        let calls = calls.clone();
+        async move {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            format!("call {}", call)
+        }
+    };
+
+    Router::new()
+        .route("/stats", get(handler.clone()))
+        .route("/other", get(handler))
+        .layer(middleware::from_fn_with_state(coalescer, coalesce_middleware))
+}
+
+fn coalescer() -> RequestCoalescer {
+    RequestCoalescer::new(&CoalesceConfig {
+        enabled: true,
+        routes: vec!["/stats".to_string()],
+        ..CoalesceConfig::default()
+    })
+}
+
+async fn get_all(app: &Router, requests: Vec<(&str, &str)>) -> Vec<String> {
+    let handles: Vec<_> = requests
+        .into_iter()
+        .map(|(uri, token)| {
+            let request = Request::builder()
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            tokio::spawn(app.clone().oneshot(request))
+        })
+        .collect();
+
+    let mut bodies = Vec::new();
+    for handle in handles {
+        let response = handle.await.unwrap().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        bodies.push(String::from_utf8(body.to_vec()).unwrap());
+    }
+    bodies
+}
+
+#[tokio::test]
+async fn test_identical_concurrent_gets_share_one_execution() {
+    let metrics = AppMetrics::new().unwrap();
+    let coalescer = Arc::new(coalescer().with_metrics(metrics.clone()));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(coalescer.clone(), calls.clone());
+
+    let bodies = get_all(&app, vec![("/stats?days=7", "alice"); 10]).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(bodies.iter().all(|body| body == "call 1"), "{:?}", bodies);
+    assert_eq!(
+        metrics
+            .http_coalesced_requests_total
+            .with_label_values(&["/stats"])
+            .get(),
+        9.0
+    );
+    assert_eq!(coalescer.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_callers_queries_and_unlisted_routes_are_kept_apart() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(Arc::new(coalescer()), calls.clone());
+
+    get_all(
+        &app,
+        vec![
+            ("/stats?days=7", "alice"),
+            ("/stats?days=7", "bob"),
+            ("/stats?days=30", "alice"),
+            ("/other", "alice"),
+            ("/other", "alice"),
+        ],
+    )
+    .await;
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn test_responses_are_not_cached_once_complete() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(Arc::new(coalescer()), calls.clone());
+
+    assert_eq!(get_all(&app, vec![("/stats", "alice")]).await, vec!["call 1"]);
+    assert_eq!(get_all(&app, vec![("/stats", "alice")]).await, vec!["call 2"]);
+
+    // Disabled, or with nothing to coalesce, there's no layer at all
+    assert!(RequestCoalescer::from_config(&CoalesceConfig::default()).is_none());
+    assert!(RequestCoalescer::from_config(&CoalesceConfig {
+        enabled: true,
+        routes: vec![],
+        ..CoalesceConfig::default()
+    })
+    .is_none());
+}
+
+#[tokio::test]
+async fn test_requests_with_other_credentials_or_preconditions_are_kept_apart() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mtls = coalescer().with_vary_header("X-Client-Cert-Subject").unwrap();
+    let app = app(Arc::new(mtls), calls.clone());
+
+    let responses = [
+        ("x-api-key", "rpat_alice"),
+        ("x-api-key", "rpat_bob"),
+        ("x-client-cert-subject", "CN=alice"),
+        ("x-client-cert-subject", "CN=bob"),
+        ("if-none-match", "\"v1\""),
+        ("if-modified-since", "Wed, 21 Oct 2026 07:28:00 GMT"),
+    ]
+    .into_iter()
+    .map(|(name, value)| {
+        let request = Request::builder()
+            .uri("/stats")
+            .header("authorization", "Bearer shared")
+            .header(name, value)
+            .body(Body::empty())
+            .unwrap();
+        tokio::spawn(app.clone().oneshot(request))
+    })
+    .collect::<Vec<_>>();
+    for response in responses {
+        assert_eq!(response.await.unwrap().unwrap().status(), StatusCode::OK);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+    assert!(coalescer().with_vary_header("not a header").is_err());
+}
@@ -0,0 +1,168 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, Request, StatusCode},
+    Extension,
+};
+use chrono::{TimeZone, Utc};
+use reprime_backend::{
+    auth::{
+        jwt::JwtService,
+        middleware::AuthState,
+        models::{roles, AuthContext, SubjectType},
+        openfga::OpenFgaService,
+    },
+    config::Config,
+    database::InstrumentedDatabase,
+    handlers::Handlers,
+    models::CreateUserRequest,
+    repositories::Repositories,
+    routes::create_routes,
+    services::{mailer::LogMailer, Services, WarmupService},
+    utils::{http_date, run_migrations, CollectionVersion, CursorSigner},
+};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn version(count: i64) -> CollectionVersion {
+    CollectionVersion {
+        last_modified: Some(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap()),
+        count,
+    }
+}
+
+fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(name, value.parse().unwrap());
+    headers
+}
+
+#[test]
+fn test_if_modified_since() {
+    let version = version(3);
+
+    assert!(version.is_not_modified(&headers(header::IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 12:00:00 GMT")));
+    assert!(!version.is_not_modified(&headers(header::IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 11:59:59 GMT")));
+    assert!(!version.is_not_modified(&headers(header::IF_MODIFIED_SINCE, "yesterday")));
+    assert!(!version.is_not_modified(&HeaderMap::new()));
+
+    // Nothing to compare against in an empty collection
+    assert!(!CollectionVersion::default()
+        .is_not_modified(&headers(header::IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 12:00:00 GMT")));
+}
+
+#[test]
+fn test_deletes_change_the_etag() {
+    let before = version(3);
+    let after = version(2);
+    assert_ne!(before.etag(), after.etag());
+
+    let mut request = headers(header::IF_NONE_MATCH, &before.etag());
+    assert!(before.is_not_modified(&request));
+    assert!(!after.is_not_modified(&request));
+
+    // If-None-Match wins over an otherwise matching If-Modified-Since
+    request.insert(header::IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 12:00:00 GMT".parse().unwrap());
+    assert!(!after.is_not_modified(&request));
+}
+
+#[test]
+fn test_shard_versions_merge_and_render() {
+    let merged = version(3).merge(CollectionVersion {
+        last_modified: Some(Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap()),
+        count: 2,
+    });
+    assert_eq!(merged.count, 5);
+
+    let mut response = HeaderMap::new();
+    merged.apply(&mut response);
+    assert_eq!(response[header::LAST_MODIFIED], "Tue, 01 Apr 2025 00:00:00 GMT");
+    assert_eq!(response[header::ETAG], merged.etag());
+    assert_eq!(http_date(merged.last_modified.unwrap()), "Tue, 01 Apr 2025 00:00:00 GMT");
+}
+
+#[tokio::test]
+async fn test_role_filtered_listing_changes_with_roles() {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return;
+    };
+    let mut config = Config::default();
+    config.auth.openfga.cache_enabled = false;
+    let pool = PgPoolOptions::new().connect(&url).await.expect("test database");
+    run_migrations(&config, &pool, &[], None).await.expect("migrations");
+    let db = Arc::new(InstrumentedDatabase::new(pool, None));
+    let repositories = Arc::new(Repositories::new(db.clone()));
+    let jwt_service = Arc::new(JwtService::new(&config).expect("jwt service"));
+    let openfga = Arc::new(OpenFgaService::new(&config).await.expect("openfga service"));
+    let services = Arc::new(Services::new(
+        repositories.clone(),
+        jwt_service.clone(),
+        openfga.clone(),
+        Arc::new(LogMailer),
+        &config,
+    ));
+    let auth_state = AuthState::new(jwt_service.clone(), services.sessions.clone());
+    let warmup = Arc::new(WarmupService::new(vec![db], 0, jwt_service.clone(), openfga.clone()));
+    let app = create_routes(Handlers::new(services.clone(), openfga, warmup), auth_state)
+        .layer(Extension(Arc::new(CursorSigner::new("etag-cursor-secret"))));
+
+    let run = Uuid::new_v4().simple().to_string();
+    let user = repositories
+        .user
+        .create(CreateUserRequest {
+            email: format!("etag-{}@example.com", run),
+            username: format!("etag-{}", &run[..12]),
+        })
+        .await
+        .unwrap();
+    // Listed by someone else, whose token the role change doesn't touch
+    let lister = repositories
+        .user
+        .create(CreateUserRequest {
+            email: format!("etag-lister-{}@example.com", run),
+            username: format!("etag-l-{}", &run[..12]),
+        })
+        .await
+        .unwrap();
+    let token = jwt_service
+        .generate_token(lister.id, lister.email, lister.username, vec![roles::USER.to_string()])
+        .unwrap();
+    let list = |validator: Option<(header::HeaderName, String)>| {
+        let mut request = Request::builder()
+            .uri(format!("/api/v1/users?role={}", roles::MODERATOR))
+            .header("authorization", format!("Bearer {}", token));
+        if let Some((name, value)) = validator {
+            request = request.header(name, value);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    let listed = list(None).await.unwrap();
+    assert_eq!(listed.status(), StatusCode::OK);
+    let etag = listed.headers()[header::ETAG].to_str().unwrap().to_string();
+    let last_modified = listed.headers()[header::LAST_MODIFIED].to_str().unwrap().to_string();
+    let unchanged = list(Some((header::IF_NONE_MATCH, etag.clone()))).await.unwrap();
+    assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+
+    // Only the user's summary changes, in a later second than Last-Modified
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let admin = AuthContext {
+        user_id: user.id,
+        email: user.email.clone(),
+        username: user.username.clone(),
+        roles: vec![roles::ADMIN.to_string()],
+        session_id: None,
+        tenant_id: None,
+        scopes: None,
+        subject_type: SubjectType::User,
+    };
+    services.auth.add_role(&admin, user.id, roles::MODERATOR).await.unwrap();
+
+    let by_etag = list(Some((header::IF_NONE_MATCH, etag))).await.unwrap();
+    assert_eq!(by_etag.status(), StatusCode::OK);
+    let by_date = list(Some((header::IF_MODIFIED_SINCE, last_modified))).await.unwrap();
+    assert_eq!(by_date.status(), StatusCode::OK);
+}
@@ -0,0 +1,52 @@
+use reprime_backend::utils::CursorSigner;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Position {
+    offset: i64,
+}
+
+#[test]
+fn test_cursor_roundtrip() {
+    let signer = CursorSigner::new("test-secret");
+    let cursor = signer.encode(&Position { offset: 40 }, None, None).unwrap();
+
+    let decoded: Position = signer.decode(&cursor, None).unwrap();
+    assert_eq!(decoded, Position { offset: 40 });
+}
+
+#[test]
+fn test_tampered_cursor_is_rejected() {
+    let signer = CursorSigner::new("test-secret");
+    let cursor = signer.encode(&Position { offset: 40 }, None, None).unwrap();
+
+    // Re-sign a different payload with another key
+    let forged = CursorSigner::new("other-secret")
+        .encode(&Position { offset: 1_000_000 }, None, None)
+        .unwrap();
+    let (forged_payload, _) = forged.split_once('.').unwrap();
+    let (_, signature) = cursor.split_once('.').unwrap();
+
+    let result = signer.decode::<Position>(&format!("{}.{}", forged_payload, signature), None);
+    assert!(result.is_err());
+    assert!(signer.decode::<Position>("not-a-cursor", None).is_err());
+}
+
+#[test]
+fn test_cursor_bound_to_subject() {
+    let signer = CursorSigner::derived_from("jwt-secret");
+    let cursor = signer.encode(&Position { offset: 20 }, Some("user-a"), None).unwrap();
+
+    assert!(signer.decode::<Position>(&cursor, Some("user-a")).is_ok());
+    assert!(signer.decode::<Position>(&cursor, Some("user-b")).is_err());
+    assert!(signer.decode::<Position>(&cursor, None).is_err());
+}
+
+#[test]
+fn test_expired_cursor_is_rejected() {
+    let signer = CursorSigner::new("test-secret");
+    let expired = chrono::Utc::now() - chrono::Duration::minutes(1);
+    let cursor = signer.encode(&Position { offset: 20 }, None, Some(expired)).unwrap();
+
+    assert!(signer.decode::<Position>(&cursor, None).is_err());
+}
@@ -0,0 +1,74 @@
+use axum::{http::StatusCode, routing::get, Router};
+use reprime_backend::{client::HttpClient, dependencies::DependencyStats};
+use std::time::Duration;
+
+#[test]
+fn test_stats_summarize_latency_availability_and_errors() {
+    let stats = DependencyStats::new();
+    stats.register("postgres");
+    let idle = stats.get("postgres").unwrap();
+    assert_eq!(idle.calls, 0);
+    assert_eq!(idle.availability, 1.0);
+    assert!(idle.last_call_at.is_none());
+
+    for millis in 1..=100 {
+        stats.record("postgres", Duration::from_millis(millis), None);
+    }
+    for attempt in 1..=7 {
+        stats.record(
+            "postgres",
+            Duration::from_millis(500),
+            Some(format!("pool timed out ({})", attempt)),
+        );
+    }
+
+    let report = stats.get("postgres").unwrap();
+    assert_eq!((report.calls, report.errors, report.window_calls), (107, 7, 107));
+    assert!((report.availability - 100.0 / 107.0).abs() < 1e-9);
+    assert_eq!(report.latency.p50_ms, 54.0);
+    assert_eq!(report.latency.max_ms, 500.0);
+
+    // Only the latest samples are kept, newest first
+    let messages: Vec<&str> = report.recent_errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[0], "pool timed out (7)");
+    assert_eq!(report.last_error_at, Some(report.recent_errors[0].at));
+
+    let names: Vec<String> = stats.snapshot().into_iter().map(|r| r.name).collect();
+    assert_eq!(names, vec!["postgres".to_string()]);
+}
+
+#[tokio::test]
+async fn test_named_http_client_records_server_errors() {
+    let app = Router::new()
+        .route("/ok", get(|| async { "ok" }))
+        .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+        .route("/broken", get(|| async { StatusCode::BAD_GATEWAY }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let client = HttpClient::builder()
+        .dependency("billing-api")
+        .base_url(format!("http://{}", addr))
+        .build()
+        .unwrap();
+    for path in ["/ok", "/missing", "/broken"] {
+        client.get_response(path).await.unwrap();
+    }
+
+    // A 4xx is an answer; only the 502 counts against availability
+    let report = DependencyStats::global().get("billing-api").unwrap();
+    assert_eq!((report.calls, report.errors), (3, 1));
+    assert_eq!(report.recent_errors[0].message, "HTTP 502 Bad Gateway");
+
+    // Unnamed clients aren't recorded
+    HttpClient::with_base_url(format!("http://{}", addr))
+        .unwrap()
+        .get_response("/ok")
+        .await
+        .unwrap();
+    assert_eq!(DependencyStats::global().get("billing-api").unwrap().calls, 3);
+}
@@ -0,0 +1,123 @@
+use axum::{
+    body::Body,
+    http::{header, Method, Request},
+    middleware,
+    response::Response,
+    routing::get,
+    Router,
+};
+use reprime_backend::config::{EdgeCacheConfig, MetricsConfig};
+use reprime_backend::middleware::{edge_cache_middleware, EdgeCache};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const SUBJECT_HEADER: &str = "x-client-cert-subject";
+
+fn app() -> Router {
+    let edge_cache = EdgeCache::from_config(&EdgeCacheConfig::default(), &MetricsConfig::default())
+        .expect("valid config")
+        .expect("enabled by default")
+        .with_credential_header(SUBJECT_HEADER)
+        .expect("valid header name");
+    let edge_cache = Arc::new(edge_cache);
+
+    Router::new()
+        .route("/health", get(|| async { "ok" }).post(|| async { "ok" }))
+        .route("/api/v1/users", get(|| async { "[]" }))
+        .route(
+            "/.well-known/jwks.json",
+            get(|| async { ([(header::CACHE_CONTROL, "public, max-age=300")], "{}") }),
+        )
+        .layer(middleware::from_fn_with_state(edge_cache, edge_cache_middleware))
+}
+
+async fn call(method: Method, uri: &str, token: Option<&str>) -> Response {
+    let credentials = token.map(|token| (header::AUTHORIZATION.as_str(), format!("Bearer {}", token)));
+    call_with(method, uri, credentials.as_slice()).await
+}
+
+async fn call_with(method: Method, uri: &str, headers: &[(&str, String)]) -> Response {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+fn header_value<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+#[tokio::test]
+async fn test_public_groups_are_cacheable_by_the_edge() {
+    let response = call(Method::GET, "/health", None).await;
+    assert_eq!(header_value(&response, "cache-control"), Some("public, max-age=5"));
+    assert_eq!(header_value(&response, "surrogate-control"), Some("max-age=5"));
+    assert_eq!(header_value(&response, "vary"), None);
+
+    // Only reads are cached, and only for groups with a policy
+    let response = call(Method::POST, "/health", None).await;
+    assert_eq!(header_value(&response, "cache-control"), Some("no-store"));
+    let response = call(Method::GET, "/api/v1/users", None).await;
+    assert_eq!(header_value(&response, "cache-control"), Some("no-store"));
+    assert_eq!(header_value(&response, "surrogate-control"), None);
+}
+
+#[tokio::test]
+async fn test_credentials_make_responses_private() {
+    let response = call(Method::GET, "/health", Some("token")).await;
+    assert_eq!(header_value(&response, "cache-control"), Some("private, no-store"));
+    assert_eq!(header_value(&response, "vary"), Some("Authorization"));
+    assert_eq!(header_value(&response, "surrogate-control"), None);
+
+    // A handler's own policy is kept, but still varies by caller
+    let response = call(Method::GET, "/.well-known/jwks.json", Some("token")).await;
+    assert_eq!(header_value(&response, "cache-control"), Some("public, max-age=300"));
+    assert_eq!(header_value(&response, "vary"), Some("Authorization"));
+}
+
+#[tokio::test]
+async fn test_every_kind_of_credential_makes_responses_private() {
+    for (name, value, vary) in [
+        ("cookie", "session=abc", "Cookie"),
+        ("x-api-key", "rpat_abc", "X-Api-Key"),
+        (SUBJECT_HEADER, "CN=billing", "X-Client-Cert-Subject"),
+    ] {
+        let response = call_with(Method::GET, "/health", &[(name, value.to_string())]).await;
+        assert_eq!(header_value(&response, "cache-control"), Some("private, no-store"), "{}", name);
+        assert_eq!(header_value(&response, "surrogate-control"), None, "{}", name);
+        assert_eq!(header_value(&response, "vary"), Some(vary), "{}", name);
+    }
+
+    // Each credential sent is varied on
+    let response = call_with(
+        Method::GET,
+        "/health",
+        &[
+            ("authorization", "Bearer token".to_string()),
+            ("cookie", "session=abc".to_string()),
+        ],
+    )
+    .await;
+    let vary: Vec<_> = response
+        .headers()
+        .get_all("vary")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect();
+    assert_eq!(vary, vec!["Authorization", "Cookie"]);
+}
+
+#[test]
+fn test_invalid_header_values_are_rejected() {
+    let mut config = EdgeCacheConfig {
+        default_cache_control: "no-store\n".to_string(),
+        ..EdgeCacheConfig::default()
+    };
+    assert!(EdgeCache::new(&config, &MetricsConfig::default()).is_err());
+
+    config.enabled = false;
+    assert!(EdgeCache::from_config(&config, &MetricsConfig::default())
+        .unwrap()
+        .is_none());
+}
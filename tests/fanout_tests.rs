@@ -0,0 +1,93 @@
+use reprime_backend::{
+    config::FanOutConfig,
+    errors::AppError,
+    metrics::AppMetrics,
+    services::fanout::{FanOut, ItemError},
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn fanout(concurrency: usize, item_timeout_ms: u64) -> FanOut {
+    FanOut::new(&FanOutConfig {
+        concurrency,
+        item_timeout_ms,
+    })
+}
+
+#[tokio::test]
+async fn test_results_keep_input_order_within_the_concurrency_cap() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    let results = fanout(4, 1000)
+        .run("square", 0..20u64, |n| {
+            let (in_flight, peak) = (in_flight.clone(), peak.clone());
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Later items finish first
+                tokio::time::sleep(Duration::from_millis(20 - n)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, AppError>(n * n)
+            }
+        })
+        .await;
+
+    assert!(peak.load(Ordering::SeqCst) <= 4);
+    let values = results.into_values().unwrap();
+    assert_eq!(values, (0..20u64).map(|n| n * n).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_failures_and_timeouts_are_aggregated() {
+    let metrics = AppMetrics::new().unwrap();
+    let results = fanout(8, 50)
+        .with_metrics(metrics.clone())
+        .run("lookup", 0..6u32, |n| async move {
+            match n {
+                2 => Err(AppError::NotFound(format!("item {}", n))),
+                4 => {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(n)
+                }
+                _ => Ok(n),
+            }
+        })
+        .await;
+
+    assert_eq!(results.succeeded.len(), 4);
+    assert_eq!(results.failed[0].item, 2);
+    assert!(matches!(results.failed[1].error, ItemError::TimedOut(_)));
+
+    let error = results.into_values().unwrap_err();
+    assert_eq!((error.total, error.failures.len()), (6, 2));
+    assert!(matches!(
+        AppError::from(error),
+        AppError::Internal(message) if message.contains("2 of 6")
+    ));
+
+    let outcome = |outcome: &str| {
+        metrics
+            .fanout_items_total
+            .with_label_values(&["lookup", outcome])
+            .get()
+    };
+    assert_eq!((outcome("ok"), outcome("error"), outcome("timeout")), (4.0, 1.0, 1.0));
+}
+
+#[tokio::test]
+async fn test_a_single_failure_keeps_its_error() {
+    let results = fanout(2, 1000)
+        .run("validate", ["ok", "bad"], |value| async move {
+            match value {
+                "bad" => Err(AppError::Validation("bad value".to_string())),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+    assert!(!results.is_complete());
+    let error = AppError::from(results.into_values().unwrap_err());
+    assert!(matches!(error, AppError::Validation(_)));
+}
@@ -0,0 +1,39 @@
+use reprime_backend::models::{
+    fixtures, CreateUserRequest, LoginRequest, RegisterRequest, UpdateUserRequest,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+fn parse<T: DeserializeOwned>(value: Value) -> T {
+    serde_json::from_value(value).expect("fixture matches the request schema")
+}
+
+#[test]
+fn test_register_examples() {
+    assert!(parse::<RegisterRequest>(fixtures::register::valid()).validate().is_ok());
+    assert!(parse::<RegisterRequest>(fixtures::register::min_length()).validate().is_ok());
+    assert!(parse::<RegisterRequest>(fixtures::register::invalid_email()).validate().is_err());
+    assert!(parse::<RegisterRequest>(fixtures::register::short_password()).validate().is_err());
+
+    let login: LoginRequest = parse(fixtures::login::valid());
+    assert_eq!(login.email, fixtures::EMAIL);
+}
+
+#[test]
+fn test_create_user_examples() {
+    assert!(parse::<CreateUserRequest>(fixtures::create_user::valid()).validate().is_ok());
+    assert!(parse::<CreateUserRequest>(fixtures::create_user::unicode_username()).validate().is_ok());
+    assert!(parse::<CreateUserRequest>(fixtures::create_user::invalid_email()).validate().is_err());
+    assert!(parse::<CreateUserRequest>(fixtures::create_user::short_username()).validate().is_err());
+}
+
+#[test]
+fn test_update_user_examples() {
+    assert!(parse::<UpdateUserRequest>(fixtures::update_user::valid()).validate().is_ok());
+
+    let partial: UpdateUserRequest = parse(fixtures::update_user::username_only());
+    assert!(partial.email.is_none());
+    assert!(partial.validate().is_ok());
+
+    assert!(parse::<UpdateUserRequest>(fixtures::update_user::empty_username()).validate().is_err());
+}
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use reprime_backend::{
+    config::HealthConfig,
+    services::{HealthProbe, HealthService},
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Counts checks; each takes `delay` and then fails if `fail` is set
+struct FakeProbe {
+    calls: AtomicUsize,
+    delay: Duration,
+    fail: bool,
+}
+
+impl FakeProbe {
+    fn new(delay: Duration, fail: bool) -> Arc<Self> {
+        Arc::new(Self {
+            calls: AtomicUsize::new(0),
+            delay,
+            fail,
+        })
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl HealthProbe for FakeProbe {
+    async fn check(&self) -> anyhow::Result<()> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        if self.fail {
+            anyhow::bail!("connection refused");
+        }
+        Ok(())
+    }
+}
+
+fn config(ttl_ms: u64) -> HealthConfig {
+    HealthConfig {
+        ttl_ms,
+        ..HealthConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_reports_are_cached_for_the_ttl() {
+    let probe = FakeProbe::new(Duration::ZERO, false);
+    let health = HealthService::new(&config(200)).with_dependency(
+        "database",
+        probe.clone(),
+        Duration::from_secs(1),
+        true,
+    );
+
+    assert!(health.report().await.healthy);
+    assert!(health.report().await.healthy);
+    assert_eq!(probe.calls(), 1);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    health.report().await;
+    assert_eq!(probe.calls(), 2);
+}
+
+#[tokio::test]
+async fn test_concurrent_probes_share_one_refresh() {
+    let probe = FakeProbe::new(Duration::from_millis(50), false);
+    let health = Arc::new(HealthService::new(&config(5000)).with_dependency(
+        "database",
+        probe.clone(),
+        Duration::from_secs(1),
+        true,
+    ));
+
+    let handles: Vec<_> = (0..20)
+        .map(|_| {
+            let health = health.clone();
+            tokio::spawn(async move { health.report().await.healthy })
+        })
+        .collect();
+    for handle in handles {
+        assert!(handle.await.unwrap());
+    }
+    assert_eq!(probe.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_slow_and_failing_dependencies_are_bounded_by_their_budget() {
+    let slow = FakeProbe::new(Duration::from_secs(10), false);
+    let failing = FakeProbe::new(Duration::ZERO, true);
+    let health = HealthService::new(&config(5000))
+        .with_dependency("openfga", slow, Duration::from_millis(50), false)
+        .with_dependency("database", failing.clone(), Duration::from_secs(1), true);
+
+    let started = Instant::now();
+    let report = health.report().await;
+    assert!(started.elapsed() < Duration::from_secs(1), "took {:?}", started.elapsed());
+
+    let openfga = &report.dependencies[0];
+    assert!(!openfga.healthy);
+    assert_eq!(openfga.error.as_deref(), Some("timed out after 50ms"));
+
+    let database = &report.dependencies[1];
+    assert!(!database.healthy);
+    assert_eq!(database.error.as_deref(), Some("connection refused"));
+    assert!(!report.healthy, "the database is critical");
+
+    // Only non-critical failures: still healthy
+    let health = HealthService::new(&config(5000)).with_dependency(
+        "openfga",
+        failing,
+        Duration::from_secs(1),
+        false,
+    );
+    assert!(health.report().await.healthy);
+}
@@ -0,0 +1,49 @@
+use chrono::Utc;
+use reprime_backend::utils::id::{self, IdStrategy};
+use std::time::Duration;
+
+#[test]
+fn test_uuid_v7_ids_sort_by_creation_time() {
+    let before = Utc::now().timestamp_millis();
+    let ids: Vec<_> = (0..100).map(|_| IdStrategy::UuidV7.generate()).collect();
+    let after = Utc::now().timestamp_millis();
+
+    assert!(ids.iter().all(|id| id.get_version_num() == 7));
+    // Monotonic within the process, even inside one millisecond
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let created = id::timestamp(ids[0]).unwrap().timestamp_millis();
+    assert!((before..=after).contains(&created));
+    assert!(id::timestamp(IdStrategy::UuidV4.generate()).is_none());
+    assert!(IdStrategy::UuidV7.is_time_ordered());
+    assert!(!IdStrategy::UuidV4.is_time_ordered());
+}
+
+#[test]
+fn test_ulids_lead_with_the_millisecond_timestamp() {
+    let before = Utc::now().timestamp_millis() as u128;
+    let first = IdStrategy::Ulid.generate();
+    std::thread::sleep(Duration::from_millis(2));
+    let second = IdStrategy::Ulid.generate();
+
+    let millis = first.as_u128() >> 80;
+    assert!(millis >= before && millis <= Utc::now().timestamp_millis() as u128);
+    // Ordered across milliseconds
+    assert!(first < second);
+    assert!(IdStrategy::Ulid.is_time_ordered());
+}
+
+#[test]
+fn test_strategy_is_configurable() {
+    let strategy: IdStrategy = serde_json::from_str("\"uuid_v4\"").unwrap();
+    assert_eq!(strategy, IdStrategy::UuidV4);
+    assert_eq!(IdStrategy::default(), IdStrategy::UuidV7);
+    assert!(serde_json::from_str::<IdStrategy>("\"uuid_v1\"").is_err());
+
+    id::set_strategy(IdStrategy::UuidV4);
+    assert_eq!(id::strategy(), IdStrategy::UuidV4);
+    assert_eq!(id::generate().get_version_num(), 4);
+
+    id::set_strategy(IdStrategy::UuidV7);
+    assert_eq!(id::generate().get_version_num(), 7);
+}
@@ -0,0 +1,99 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    routing::post,
+    Router,
+};
+use reprime_backend::utils::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Member {
+    email: String,
+    role: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Invite {
+    name: String,
+    members: Vec<Member>,
+}
+
+fn app() -> Router {
+    Router::new().route(
+        "/invites",
+        post(|Json(invite): Json<Invite>| async move {
+            Json(json!({ "name": invite.name, "members": invite.members.len() }))
+        }),
+    )
+}
+
+async fn post_invite(content_type: Option<&str>, body: &str) -> (StatusCode, Value) {
+    let mut request = Request::builder().method("POST").uri("/invites");
+    if let Some(content_type) = content_type {
+        request = request.header(header::CONTENT_TYPE, content_type);
+    }
+    let response = app()
+        .oneshot(request.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_valid_body_is_extracted() {
+    let (status, body) = post_invite(
+        Some("application/json; charset=utf-8"),
+        r#"{"name":"ops","members":[{"email":"a@example.com","role":"admin"}]}"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "name": "ops", "members": 1 }));
+}
+
+#[tokio::test]
+async fn test_wrong_type_names_field_and_expected_type() {
+    let (status, body) = post_invite(
+        Some("application/json"),
+        r#"{"name":"ops","members":[{"email":"a@example.com","role":7}]}"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "members[0].role");
+    assert_eq!(body["expected"], "a string");
+    assert!(body["error"].as_str().unwrap().starts_with("invalid type"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_missing_field_is_named() {
+    let (status, body) =
+        post_invite(Some("application/json"), r#"{"name":"ops","members":[{"role":"admin"}]}"#)
+            .await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "members[0].email");
+    assert_eq!(body["error"], "missing field `email`");
+}
+
+#[tokio::test]
+async fn test_malformed_json_and_content_type_are_json_errors() {
+    let (status, body) = post_invite(Some("application/json"), r#"{"name":"ops","#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].is_string());
+    assert!(body.get("expected").is_none());
+
+    let (status, _) = post_invite(Some("application/json"), r#"{"name":"ops","members":[]} x"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = post_invite(None, r#"{"name":"ops","members":[]}"#).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert!(body["error"].is_string());
+}
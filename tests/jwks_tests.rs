@@ -0,0 +1,124 @@
+use p256::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use reprime_backend::auth::jwt::JwtService;
+use reprime_backend::config::{Config, JwtKeyConfig};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+fn es256_private_pem(seed: u8) -> String {
+    let key = p256::SecretKey::from_bytes(&[seed; 32].into()).expect("valid scalar");
+    key.to_pkcs8_pem(LineEnding::LF).unwrap().to_string()
+}
+
+fn es256_public_pem(seed: u8) -> String {
+    let key = p256::SecretKey::from_bytes(&[seed; 32].into()).expect("valid scalar");
+    key.public_key().to_public_key_pem(LineEnding::LF).unwrap()
+}
+
+fn key(kid: &str, pem: String) -> JwtKeyConfig {
+    JwtKeyConfig {
+        kid: kid.to_string(),
+        algorithm: "ES256".to_string(),
+        pem: Some(pem),
+        pem_file: None,
+    }
+}
+
+fn service(keys: Vec<JwtKeyConfig>, active_kid: Option<&str>) -> JwtService {
+    let mut config = Config::default();
+    config.auth.jwt_keys = keys;
+    config.auth.jwt_active_kid = active_kid.map(str::to_string);
+    JwtService::new(&config).expect("jwt service")
+}
+
+fn token(service: &JwtService) -> String {
+    service
+        .generate_token(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "testuser".to_string(),
+            vec!["user".to_string()],
+        )
+        .expect("token")
+}
+
+#[test]
+fn test_es256_tokens_carry_kid_and_are_published() {
+    let jwt_service = service(vec![key("2025-01", es256_private_pem(1))], None);
+
+    let token = token(&jwt_service);
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::ES256);
+    assert_eq!(header.kid.as_deref(), Some("2025-01"));
+    assert!(jwt_service.validate_token(&token).is_ok());
+
+    let jwks = serde_json::to_value(jwt_service.jwks()).unwrap();
+    assert_eq!(jwks["keys"][0]["kid"], "2025-01");
+    assert_eq!(jwks["keys"][0]["kty"], "EC");
+    assert!(jwks["keys"][0].get("d").is_none());
+}
+
+#[test]
+fn test_shared_secret_is_used_without_keys() {
+    let jwt_service = service(Vec::new(), None);
+
+    let token = token(&jwt_service);
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::HS256);
+    assert!(header.kid.is_none());
+    assert!(jwt_service.jwks().keys.is_empty());
+}
+
+#[test]
+fn test_rotated_out_key_still_validates() {
+    let old = service(vec![key("old", es256_private_pem(1))], None);
+    let old_token = token(&old);
+
+    let rotated = service(
+        vec![
+            key("old", es256_public_pem(1)),
+            key("new", es256_private_pem(2)),
+        ],
+        Some("new"),
+    );
+
+    assert!(rotated.validate_token(&old_token).is_ok());
+    assert_eq!(rotated.jwks().keys.len(), 2);
+
+    let new_token = token(&rotated);
+    assert_eq!(
+        jsonwebtoken::decode_header(&new_token).unwrap().kid.as_deref(),
+        Some("new")
+    );
+    // The old deployment doesn't know the new key
+    assert!(old.validate_token(&new_token).is_err());
+}
+
+#[test]
+fn test_active_key_must_be_able_to_sign() {
+    let mut config = Config::default();
+    config.auth.jwt_keys = vec![key("old", es256_public_pem(1))];
+    config.auth.jwt_active_kid = Some("old".to_string());
+
+    assert!(JwtService::new(&config).is_err());
+}
+
+#[test]
+fn test_shared_secret_tokens_are_rejected_once_a_key_signs() {
+    let legacy = token(&service(Vec::new(), None));
+
+    let switched = service(vec![key("2025-01", es256_private_pem(1))], None);
+    assert!(switched.validate_token(&legacy).is_err());
+
+    // Accepted during the grace period, and not after it
+    let mut config = Config::default();
+    config.auth.jwt_keys = vec![key("2025-01", es256_private_pem(1))];
+    config.auth.jwt_accept_shared_secret_until = Some(Utc::now() + Duration::hours(1));
+    assert!(JwtService::new(&config).unwrap().validate_token(&legacy).is_ok());
+
+    config.auth.jwt_accept_shared_secret_until = Some(Utc::now() - Duration::hours(1));
+    assert!(JwtService::new(&config).unwrap().validate_token(&legacy).is_err());
+
+    // Public keys alone don't sign, so HS256 stays in use
+    let verifying = service(vec![key("2025-01", es256_public_pem(1))], None);
+    assert!(verifying.validate_token(&legacy).is_ok());
+}
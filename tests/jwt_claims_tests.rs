@@ -0,0 +1,86 @@
+use jsonwebtoken::{encode, EncodingKey, Header};
+use reprime_backend::{
+    auth::{jwt::JwtService, models::Claims},
+    config::Config,
+    errors::AppError,
+};
+use uuid::Uuid;
+
+fn config(issuer: &str, audience: &str) -> Config {
+    let mut config = Config::default();
+    config.auth.jwt_issuer = issuer.to_string();
+    config.auth.jwt_audience = audience.to_string();
+    config
+}
+
+fn token(jwt_service: &JwtService) -> String {
+    jwt_service
+        .generate_token(Uuid::new_v4(), "a@example.com".to_string(), "a".to_string(), vec![])
+        .unwrap()
+}
+
+#[test]
+fn test_tokens_from_another_issuer_or_audience_are_rejected() {
+    let staging = JwtService::new(&config("reprime-staging", "reprime-api")).unwrap();
+    let claims = staging.validate_token(&token(&staging)).unwrap();
+    assert_eq!(claims.iss.as_deref(), Some("reprime-staging"));
+    assert_eq!(claims.aud.as_deref(), Some("reprime-api"));
+
+    // Same secret, different environment
+    let production = JwtService::new(&config("reprime-production", "reprime-api")).unwrap();
+    assert!(matches!(
+        production.validate_token(&token(&staging)),
+        Err(AppError::Authentication(_))
+    ));
+
+    // Same environment, minted for another service
+    let billing = JwtService::new(&config("reprime-staging", "billing-api")).unwrap();
+    assert!(matches!(
+        staging.validate_token(&token(&billing)),
+        Err(AppError::Authentication(_))
+    ));
+}
+
+#[test]
+fn test_tokens_without_the_claims_are_only_accepted_when_allowed() {
+    let mut config = Config::default();
+    let legacy = Claims {
+        sub: Uuid::new_v4().to_string(),
+        email: "a@example.com".to_string(),
+        username: "a".to_string(),
+        roles: vec![],
+        exp: (chrono::Utc::now().timestamp() + 600) as usize,
+        iat: chrono::Utc::now().timestamp() as usize,
+        sid: None,
+        tenant: None,
+        scope: None,
+        sub_type: None,
+        iss: None,
+        aud: None,
+        ver: None,
+        uver: None,
+        roles_updated_at: None,
+    };
+    let legacy = encode(
+        &Header::default(),
+        &legacy,
+        &EncodingKey::from_secret(config.auth.jwt_secret.as_bytes()),
+    )
+    .unwrap();
+
+    let strict = JwtService::new(&config).unwrap();
+    assert!(strict.validate_token(&legacy).is_err());
+
+    config.auth.jwt_require_issuer_audience = false;
+    let lenient = JwtService::new(&config).unwrap();
+    assert!(lenient.validate_token(&legacy).is_ok());
+
+    // Claims that are present still have to match
+    let other = JwtService::new(&{
+        let mut other = config.clone();
+        other.auth.jwt_issuer = "someone-else".to_string();
+        other
+    })
+    .unwrap();
+    assert!(lenient.validate_token(&token(&other)).is_err());
+}
@@ -0,0 +1,96 @@
+use axum::{body::Body, routing::post, Json, Router};
+use futures::{stream, StreamExt, TryStreamExt};
+use reprime_backend::{auth::openfga::OpenFgaService, config::Config, errors::AppError};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn service(app: Router, max_results: usize) -> OpenFgaService {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    config.auth.openfga.auth_model_id = None;
+    config.auth.openfga.list_objects.max_results = max_results;
+    config.auth.openfga.list_objects.default_page_size = 2;
+    OpenFgaService::new(&config).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_streamed_objects_are_capped_and_paged() {
+    // Lines split across chunks, out of order, the last without a newline
+    let app = Router::new().route(
+        "/stores/{store}/streamed-list-objects",
+        post(|| async {
+            let chunks = [
+                "{\"result\":{\"object\":\"document:d\"}}\n{\"res",
+                "ult\":{\"object\":\"document:b\"}}\n\n",
+                "{\"result\":{\"object\":\"document:a\"}}\n",
+                "{\"result\":{\"object\":\"document:c\"}}",
+            ];
+            Body::from_stream(stream::iter(chunks.map(Ok::<_, std::io::Error>)))
+        }),
+    );
+    let openfga = service(app, 3).await;
+    let user = Uuid::new_v4();
+
+    let streamed: Vec<String> = openfga
+        .list_objects_stream(user, "viewer", "document")
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(streamed, vec!["document:d", "document:b", "document:a"]);
+    assert_eq!(openfga.list_objects(user, "viewer", "document").await.unwrap().len(), 3);
+
+    let first = openfga.list_objects_page(user, "viewer", "document", None, None).await.unwrap();
+    assert_eq!(first.objects, vec!["document:a", "document:b"]);
+    assert!(first.truncated);
+    let second = openfga
+        .list_objects_page(user, "viewer", "document", None, first.continuation_token.as_deref())
+        .await
+        .unwrap();
+    assert_eq!(second.objects, vec!["document:d"]);
+    assert!(second.continuation_token.is_none());
+
+    assert!(matches!(
+        openfga.list_objects_page(user, "viewer", "document", None, Some("%%%")).await,
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_stream_errors_surface_and_servers_without_streaming_fall_back() {
+    let app = Router::new().route(
+        "/stores/{store}/streamed-list-objects",
+        post(|| async {
+            "{\"result\":{\"object\":\"document:a\"}}\n{\"error\":{\"code\":4000,\"message\":\"deadline exceeded\"}}\n"
+        }),
+    );
+    let openfga = service(app, 1000).await;
+    let mut objects =
+        openfga.list_objects_stream(Uuid::new_v4(), "viewer", "document").await.unwrap();
+    assert_eq!(objects.next().await.unwrap().unwrap(), "document:a");
+    assert!(matches!(objects.next().await, Some(Err(AppError::Internal(_)))));
+    assert!(objects.next().await.is_none());
+
+    // Only the buffered endpoint: the streamed one is a 404
+    let app = Router::new().route(
+        "/stores/{store}/list-objects",
+        post(|| async { Json(json!({ "objects": ["document:b", "document:a"] })) }),
+    );
+    let openfga = service(app, 1000).await;
+    for _ in 0..2 {
+        let page = openfga
+            .list_objects_page(Uuid::new_v4(), "viewer", "document", Some(10), None)
+            .await
+            .unwrap();
+        assert_eq!(page.objects, vec!["document:a", "document:b"]);
+        assert!(!page.truncated && page.continuation_token.is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use reprime_backend::live_tail::LiveTail;
+use std::sync::Arc;
+use tracing::Level;
+use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+#[test]
+fn test_layer_records_structured_events() {
+    let tail = Arc::new(LiveTail::new(10));
+    let subscriber = Registry::default().with(tail.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::warn!(user_id = "abc", attempts = 3, "Login failed");
+    });
+
+    let events = tail.recent();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].message, "Login failed");
+    assert_eq!(events[0].level, "WARN");
+    assert_eq!(events[0].fields["user_id"], "abc");
+    assert_eq!(events[0].fields["attempts"], 3);
+    assert!(events[0].is_at_least(Level::WARN));
+    assert!(!events[0].is_at_least(Level::ERROR));
+}
+
+#[test]
+fn test_buffer_keeps_most_recent_events() {
+    let tail = Arc::new(LiveTail::new(3));
+    let subscriber = Registry::default().with(tail.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        for i in 0..5 {
+            tracing::info!("event {}", i);
+        }
+    });
+
+    let messages: Vec<String> = tail.recent().into_iter().map(|e| e.message).collect();
+    assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+}
+
+#[tokio::test]
+async fn test_subscribers_receive_new_events() {
+    let tail = Arc::new(LiveTail::new(10));
+    let mut receiver = tail.subscribe();
+    let subscriber = Registry::default().with(tail.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::error!("Database unavailable");
+    });
+
+    let event = receiver.recv().await.unwrap();
+    assert_eq!(event.message, "Database unavailable");
+}
+
+#[test]
+fn test_trace_bundle_collects_one_request() {
+    let tail = Arc::new(LiveTail::new(50));
+    let subscriber = Registry::default().with(tail.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        for trace_id in ["trace-a", "trace-b"] {
+            let request = tracing::info_span!("http_request", trace_id = tracing::field::Empty);
+            request.record("trace_id", trace_id);
+            let _request = request.enter();
+
+            tracing::info_span!("database_query").in_scope(|| {
+                tracing::info!("Database query completed successfully");
+            });
+            tracing::info!(db_queries = 1u64, "Request cost");
+        }
+        tracing::info!("Outside any request");
+    });
+
+    let bundle = tail.trace_bundle("trace-a").expect("bundle");
+    let messages: Vec<&str> = bundle.logs.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["Database query completed successfully", "Request cost"]);
+    let spans: Vec<&str> = bundle.spans.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(spans, vec!["database_query", "http_request"]);
+    assert_eq!(bundle.cost.unwrap()["db_queries"], 1);
+
+    assert!(tail.trace_bundle("trace-c").is_none());
+}
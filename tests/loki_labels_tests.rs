@@ -0,0 +1,78 @@
+use reprime_backend::config::LokiLabelConfig;
+use reprime_backend::telemetry::{parse_label_map, LokiLabels, ResourceAttributes};
+use std::collections::HashMap;
+
+fn resource() -> ResourceAttributes {
+    ResourceAttributes {
+        service: "reprime-backend".to_string(),
+        version: "1.2.3".to_string(),
+        environment: "production".to_string(),
+        region: "eu-west-1".to_string(),
+        instance: "reprime-4f2a9c1e".to_string(),
+    }
+}
+
+#[test]
+fn test_default_labels_leave_instance_as_a_field() {
+    let loki = LokiLabels::from_config(&LokiLabelConfig::default(), &resource(), None).unwrap();
+
+    assert_eq!(
+        loki.labels.keys().collect::<Vec<_>>(),
+        vec!["environment", "region", "service", "version"]
+    );
+    assert_eq!(loki.fields.get("instance").map(String::as_str), Some("reprime-4f2a9c1e"));
+}
+
+#[test]
+fn test_extra_labels_from_config_and_env() {
+    let config = LokiLabelConfig {
+        extra_labels: HashMap::from([
+            ("cluster".to_string(), "prod-eu-1".to_string()),
+            ("team".to_string(), "platform".to_string()),
+        ]),
+        ..LokiLabelConfig::default()
+    };
+    let loki =
+        LokiLabels::from_config(&config, &resource(), Some("team=identity, namespace=auth ,")).unwrap();
+
+    assert_eq!(loki.labels["cluster"], "prod-eu-1");
+    assert_eq!(loki.labels["team"], "identity");
+    assert_eq!(loki.labels["namespace"], "auth");
+}
+
+#[test]
+fn test_high_cardinality_labels_need_opting_in() {
+    let config = LokiLabelConfig {
+        resource_labels: vec!["service".to_string(), "instance".to_string()],
+        ..LokiLabelConfig::default()
+    };
+    assert!(LokiLabels::from_config(&config, &resource(), None).is_err());
+    assert!(LokiLabels::from_config(&LokiLabelConfig::default(), &resource(), Some("pod=api-7d9f")).is_err());
+
+    let allowed = LokiLabelConfig {
+        allow_high_cardinality: vec!["instance".to_string()],
+        ..config
+    };
+    let loki = LokiLabels::from_config(&allowed, &resource(), None).unwrap();
+    assert!(loki.labels.contains_key("instance"));
+    assert!(!loki.fields.contains_key("instance"));
+}
+
+#[test]
+fn test_invalid_labels_are_rejected() {
+    let default = LokiLabelConfig::default();
+    assert!(LokiLabels::from_config(&default, &resource(), Some("cluster")).is_err());
+    assert!(LokiLabels::from_config(&default, &resource(), Some("1cluster=a")).is_err());
+    assert!(LokiLabels::from_config(&default, &resource(), Some("__name__=a")).is_err());
+    assert!(LokiLabels::from_config(&default, &resource(), Some("cluster=")).is_err());
+
+    let unknown = LokiLabelConfig {
+        resource_labels: vec!["datacenter".to_string()],
+        ..LokiLabelConfig::default()
+    };
+    assert!(LokiLabels::from_config(&unknown, &resource(), None).is_err());
+
+    let too_many: String = (0..20).map(|i| format!("label{}=v,", i)).collect();
+    assert!(LokiLabels::from_config(&default, &resource(), Some(&too_many)).is_err());
+    assert_eq!(parse_label_map(&too_many).unwrap().len(), 20);
+}
@@ -0,0 +1,40 @@
+use reprime_backend::config::MailerConfig;
+use reprime_backend::metrics::MetricsRegistry;
+use reprime_backend::services::{mailer_from_config, EmailMessage};
+
+#[tokio::test]
+async fn test_log_mailer_is_default() {
+    let mailer = mailer_from_config(&MailerConfig::default(), &MetricsRegistry::new()).expect("log mailer");
+
+    let result = mailer
+        .send(EmailMessage {
+            to: "user@example.com".to_string(),
+            subject: "Reset your password".to_string(),
+            text: "token".to_string(),
+        })
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_http_mailer_from_config() {
+    let config = MailerConfig {
+        provider: "http".to_string(),
+        endpoint: "http://localhost:8025/send".to_string(),
+        api_token: Some("secret".to_string()),
+        ..MailerConfig::default()
+    };
+
+    assert!(mailer_from_config(&config, &MetricsRegistry::new()).is_ok());
+}
+
+#[test]
+fn test_unknown_mailer_provider_is_rejected() {
+    let config = MailerConfig {
+        provider: "carrier-pigeon".to_string(),
+        ..MailerConfig::default()
+    };
+
+    assert!(mailer_from_config(&config, &MetricsRegistry::new()).is_err());
+}
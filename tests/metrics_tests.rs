@@ -0,0 +1,155 @@
+use reprime_backend::config::MetricsConfig;
+use reprime_backend::metrics::{
+    error_class, AppMetrics, MetricsRegistry, RouteGroups, TenantLabels, DEFAULT_ROUTE_GROUP,
+    OTHER_TENANT,
+};
+use std::collections::HashMap;
+
+#[test]
+fn test_route_group_resolution() {
+    let route_groups = RouteGroups::from_config(&MetricsConfig::default().route_groups);
+
+    assert_eq!(route_groups.resolve("/api/v1/auth/login"), "auth");
+    assert_eq!(route_groups.resolve("/api/v1/users"), "users");
+    assert_eq!(route_groups.resolve("/api/v1/users/{id}"), "users");
+    assert_eq!(route_groups.resolve("/health"), "system");
+
+    // Prefixes only match on path segment boundaries
+    assert_eq!(route_groups.resolve("/api/v1/usersettings"), DEFAULT_ROUTE_GROUP);
+    assert_eq!(route_groups.resolve("/unknown"), DEFAULT_ROUTE_GROUP);
+}
+
+#[test]
+fn test_route_group_longest_prefix_wins() {
+    let config = HashMap::from([
+        ("users".to_string(), vec!["/api/v1/users".to_string()]),
+        ("admin".to_string(), vec!["/api/v1/users/admin/".to_string()]),
+    ]);
+    let route_groups = RouteGroups::from_config(&config);
+
+    assert_eq!(route_groups.resolve("/api/v1/users/admin/stats"), "admin");
+    assert_eq!(route_groups.resolve("/api/v1/users/{id}"), "users");
+}
+
+#[test]
+fn test_error_classes() {
+    assert_eq!(error_class(200), None);
+    assert_eq!(error_class(304), None);
+    assert_eq!(error_class(401), Some("unauthorized"));
+    assert_eq!(error_class(403), Some("unauthorized"));
+    assert_eq!(error_class(404), Some("client_error"));
+    assert_eq!(error_class(429), Some("rate_limited"));
+    assert_eq!(error_class(500), Some("server_error"));
+    assert_eq!(error_class(504), Some("timeout"));
+}
+
+#[test]
+fn test_route_group_counters() {
+    let metrics = AppMetrics::new()
+        .unwrap()
+        .with_route_groups(RouteGroups::from_config(&MetricsConfig::default().route_groups));
+
+    metrics.record_route_group_request("/api/v1/users/{id}", 200);
+    metrics.record_route_group_request("/api/v1/users/{id}", 500);
+
+    let requests = metrics
+        .http_route_group_requests_total
+        .with_label_values(&["users"])
+        .get();
+    let errors = metrics
+        .http_route_group_errors_total
+        .with_label_values(&["users", "server_error"])
+        .get();
+
+    assert_eq!(requests, 2.0);
+    assert_eq!(errors, 1.0);
+}
+
+#[test]
+fn test_registry_returns_existing_metric() {
+    let registry = MetricsRegistry::new();
+    let jobs = registry.subsystem("jobs");
+
+    let first = jobs.counter_vec("runs_total", "Job runs", &["kind"]).unwrap();
+    let second = jobs.counter_vec("runs_total", "Job runs", &["kind"]).unwrap();
+    first.with_label_values(&["cleanup"]).inc();
+    second.with_label_values(&["cleanup"]).inc();
+
+    assert_eq!(first.with_label_values(&["cleanup"]).get(), 2.0);
+    assert_eq!(jobs.full_name("runs_total"), "jobs_runs_total");
+    assert!(registry
+        .gather()
+        .iter()
+        .any(|family| family.name() == "jobs_runs_total"));
+}
+
+#[test]
+fn test_registry_rejects_conflicting_metric() {
+    let registry = MetricsRegistry::new();
+    registry.counter_vec("calls_total", "Calls", &["dependency"]).unwrap();
+
+    assert!(registry.counter_vec("calls_total", "Calls", &["outcome"]).is_err());
+    assert!(registry.gauge("calls_total", "Calls").is_err());
+}
+
+#[test]
+fn test_app_metrics_share_one_registry() {
+    let registry = MetricsRegistry::new();
+    let first = AppMetrics::with_registry(&registry).unwrap();
+    let second = AppMetrics::with_registry(&registry).unwrap();
+
+    first.record_user_created();
+    second.record_user_created();
+
+    assert_eq!(first.users_created_total.get(), 2.0);
+}
+
+#[test]
+fn test_tenant_labels_fold_past_the_limit() {
+    let labels = TenantLabels::new(["acme".to_string()], 2);
+
+    assert_eq!(labels.resolve("tenant-a"), "tenant-a");
+    assert_eq!(labels.resolve("tenant-b"), "tenant-b");
+    assert_eq!(labels.resolve("tenant-c"), OTHER_TENANT);
+    // Admitted and pinned tenants keep their label
+    assert_eq!(labels.resolve("tenant-a"), "tenant-a");
+    assert_eq!(labels.resolve("acme"), "acme");
+}
+
+#[test]
+fn test_tenant_counters_only_with_labels() {
+    let route_groups = RouteGroups::from_config(&MetricsConfig::default().route_groups);
+    let unlabeled = AppMetrics::with_registry(&MetricsRegistry::new())
+        .unwrap()
+        .with_route_groups(route_groups.clone());
+    unlabeled.record_tenant_request("tenant-a", "/api/v1/users", 200);
+    assert_eq!(
+        unlabeled
+            .tenant_http_requests_total
+            .with_label_values(&["tenant-a", "users"])
+            .get(),
+        0.0
+    );
+
+    let metrics = AppMetrics::with_registry(&MetricsRegistry::new())
+        .unwrap()
+        .with_route_groups(route_groups)
+        .with_tenant_labels(TenantLabels::new([], 1));
+    metrics.record_tenant_request("tenant-a", "/api/v1/users", 200);
+    metrics.record_tenant_request("tenant-b", "/api/v1/users/{id}", 429);
+
+    assert_eq!(
+        metrics
+            .tenant_http_requests_total
+            .with_label_values(&["tenant-a", "users"])
+            .get(),
+        1.0
+    );
+    assert_eq!(
+        metrics
+            .tenant_http_errors_total
+            .with_label_values(&[OTHER_TENANT, "users", "rate_limited"])
+            .get(),
+        1.0
+    );
+}
@@ -0,0 +1,48 @@
+use reprime_backend::{
+    auth::{jwt::JwtService, openfga::OpenFgaService},
+    config::{Config, MigrationConfig},
+    services::WarmupService,
+    utils::instance_id,
+};
+use std::sync::Arc;
+
+async fn warmup_service() -> WarmupService {
+    let config = Config::default();
+    let jwt_service = Arc::new(JwtService::new(&config).expect("jwt service"));
+    let openfga = Arc::new(OpenFgaService::new(&config).await.expect("openfga service"));
+    // No databases, so the database step trivially succeeds
+    WarmupService::new(Vec::new(), 0, jwt_service, openfga)
+}
+
+#[tokio::test]
+async fn test_readiness_waits_for_migrations() {
+    let warmup = warmup_service().await;
+    assert_eq!(warmup.status(), "warming_up");
+
+    warmup.set_migrations_pending(true);
+    assert_eq!(warmup.status(), "migrations_pending");
+
+    // Warming up doesn't make the instance ready while the schema is migrating
+    let report = warmup.warmup().await;
+    assert!(!report.ready);
+    assert!(!warmup.is_ready());
+
+    warmup.set_migrations_pending(false);
+    assert!(warmup.warmup().await.ready);
+    assert_eq!(warmup.status(), "ready");
+}
+
+#[test]
+fn test_migration_lock_defaults() {
+    let config = Config::default();
+    let defaults = MigrationConfig::default();
+
+    assert_eq!(config.database.migrations.lock_key, defaults.lock_key);
+    assert!(defaults.lock_timeout_secs > 0);
+    assert!(defaults.lock_poll_interval_ms > 0);
+}
+
+#[test]
+fn test_instance_id_is_never_empty() {
+    assert!(!instance_id().is_empty());
+}
@@ -0,0 +1,117 @@
+//! What the traffic mirror sends to the shadow environment.
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+    middleware,
+    routing::{any, post},
+    Router,
+};
+use reprime_backend::{
+    config::MirrorConfig,
+    metrics::MetricsRegistry,
+    middleware::{traffic_mirror_middleware, TrafficMirror},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+/// A shadow environment reporting every request it gets, and an app
+/// mirroring all of its traffic there
+async fn mirrored_app() -> (Router, mpsc::UnboundedReceiver<(String, HeaderMap, String)>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let shadow = Router::new().fallback(any(
+        move |request: Request<Body>| {
+            let sender = sender.clone();
+            async move {
+                let path = request.uri().to_string();
+                let headers = request.headers().clone();
+                let body = axum::body::to_bytes(request.into_body(), 1024).await.unwrap();
+                sender.send((path, headers, String::from_utf8_lossy(&body).into_owned())).unwrap();
+                StatusCode::OK
+            }
+        },
+    ));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, shadow).await.unwrap();
+    });
+
+    let config = MirrorConfig {
+        enabled: true,
+        target_url: format!("http://{}", addr),
+        sample_percent: 100.0,
+        ..MirrorConfig::default()
+    };
+    let mirror = TrafficMirror::from_config(&config, &MetricsRegistry::new())
+        .unwrap()
+        .expect("enabled")
+        .with_skipped_header("x-client-cert-subject")
+        .unwrap();
+    let app = Router::new()
+        .route("/api/v1/users", post(|body: String| async move { body }))
+        .route("/api/v1/auth/login", post(|| async { "token" }))
+        .layer(middleware::from_fn_with_state(Arc::new(mirror), traffic_mirror_middleware));
+
+    (app, receiver)
+}
+
+fn request(path: &str, body: &'static str) -> Request<Body> {
+    Request::post(path)
+        .header("authorization", "Bearer secret-token")
+        .header("cookie", "session=secret")
+        .header("x-api-key", "secret-key")
+        .header("x-client-cert-subject", "CN=billing")
+        .header("x-request-id", "mirrored")
+        // Bodies without a length aren't mirrored
+        .header("content-length", body.len())
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_mirrored_requests_carry_no_credentials() {
+    let (app, mut shadow) = mirrored_app().await;
+
+    let response = app.oneshot(request("/api/v1/users?page=2", "{\"name\":\"a\"}")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // The app still sees the body it was sent
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(&body[..], b"{\"name\":\"a\"}");
+
+    let (path, headers, body) = tokio::time::timeout(Duration::from_secs(5), shadow.recv())
+        .await
+        .expect("mirrored request")
+        .unwrap();
+    assert_eq!(path, "/api/v1/users?page=2");
+    assert_eq!(body, "{\"name\":\"a\"}");
+    assert_eq!(headers["x-request-id"], "mirrored");
+    assert_eq!(headers["x-mirrored-from"], "reprime-backend");
+    for name in ["authorization", "cookie", "x-api-key", "x-client-cert-subject"] {
+        assert!(!headers.contains_key(name), "{} was mirrored", name);
+    }
+}
+
+#[tokio::test]
+async fn test_auth_routes_are_not_mirrored() {
+    let (app, mut shadow) = mirrored_app().await;
+
+    let login = request("/api/v1/auth/login", "{\"email\":\"a@example.com\",\"password\":\"hunter2\"}");
+    let response = app.clone().oneshot(login).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Once a later request has made it to the shadow, the login would have
+    // too if it had been mirrored
+    app.oneshot(request("/api/v1/users", "after")).await.unwrap();
+    let (path, _, body) = tokio::time::timeout(Duration::from_secs(5), shadow.recv())
+        .await
+        .expect("mirrored request")
+        .unwrap();
+    assert_eq!(path, "/api/v1/users");
+    assert_eq!(body, "after");
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(shadow.try_recv().is_err());
+}
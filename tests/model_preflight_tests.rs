@@ -0,0 +1,67 @@
+use axum::{routing::get, Json, Router};
+use reprime_backend::{
+    auth::{
+        openfga::{AuthorizationModel, OpenFgaService},
+        registry::{self, ObjectType, Permission, Relation},
+    },
+    config::Config,
+};
+use serde_json::json;
+
+/// The model file with `project` and `document#viewer` removed
+fn incomplete_model() -> AuthorizationModel {
+    let mut model = AuthorizationModel::from_file("config/openfga-model.json").unwrap();
+    model.type_definitions.retain(|definition| definition["type"] != "project");
+    let document = model
+        .type_definitions
+        .iter_mut()
+        .find(|definition| definition["type"] == "document")
+        .unwrap();
+    document["relations"].as_object_mut().unwrap().remove("viewer");
+    model
+}
+
+#[test]
+fn test_diff_lists_exactly_what_is_missing() {
+    let model = AuthorizationModel::from_file("config/openfga-model.json").unwrap();
+    assert!(registry::diff_model(&model).is_empty());
+
+    let diff = registry::diff_model(&incomplete_model());
+    assert_eq!(diff.missing_types, vec![ObjectType::Project]);
+    assert_eq!(
+        diff.missing_relations,
+        vec![Permission::new(ObjectType::Document, Relation::Viewer)]
+    );
+    assert_eq!(diff.to_string(), "project, document#viewer");
+    assert!(registry::check_model(&incomplete_model())
+        .unwrap_err()
+        .to_string()
+        .contains("project, document#viewer"));
+}
+
+#[tokio::test]
+async fn test_preflight_reads_the_latest_deployed_model() {
+    let model = serde_json::to_value(AuthorizationModel {
+        id: Some("01HMODEL".to_string()),
+        ..incomplete_model()
+    })
+    .unwrap();
+    let app = Router::new().route(
+        "/stores/{store}/authorization-models",
+        get(move || async move {
+            Json(json!({ "authorization_models": [model], "continuation_token": "" }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    let service = OpenFgaService::new(&config).await.unwrap();
+
+    let diff = service.preflight_model().await.unwrap();
+    assert_eq!(diff.to_string(), "project, document#viewer");
+}
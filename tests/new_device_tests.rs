@@ -0,0 +1,43 @@
+use reprime_backend::auth::models::SessionMetadata;
+
+fn metadata(device: Option<&str>, ip_address: Option<&str>) -> SessionMetadata {
+    SessionMetadata {
+        device: device.map(str::to_string),
+        ip_address: ip_address.map(str::to_string),
+        scope: None,
+    }
+}
+
+#[test]
+fn test_device_fingerprint_covers_user_agent_and_subnet() {
+    let safari = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) Safari/605.1.15";
+    let fingerprint = metadata(Some(safari), Some("203.0.113.7")).device_fingerprint().unwrap();
+    assert_eq!(fingerprint.len(), 64);
+
+    // Another address on the same /24 is the same device
+    assert_eq!(
+        metadata(Some(safari), Some("203.0.113.200")).device_fingerprint().unwrap(),
+        fingerprint
+    );
+    assert_ne!(
+        metadata(Some(safari), Some("203.0.114.7")).device_fingerprint().unwrap(),
+        fingerprint
+    );
+    assert_ne!(
+        metadata(Some("curl/8.5.0"), Some("203.0.113.7")).device_fingerprint().unwrap(),
+        fingerprint
+    );
+
+    // IPv6 addresses are grouped by /48
+    assert_eq!(
+        metadata(Some(safari), Some("2001:db8:1:2::1")).device_fingerprint(),
+        metadata(Some(safari), Some("2001:db8:1:ffff::9")).device_fingerprint()
+    );
+    assert_ne!(
+        metadata(Some(safari), Some("2001:db8:1::1")).device_fingerprint(),
+        metadata(Some(safari), Some("2001:db8:2::1")).device_fingerprint()
+    );
+
+    // Nothing to tell devices apart by
+    assert!(metadata(None, None).device_fingerprint().is_none());
+}
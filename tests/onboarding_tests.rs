@@ -0,0 +1,75 @@
+use chrono::Utc;
+use reprime_backend::{
+    config::{Config, OnboardingConfig, OnboardingRelationship},
+    models::{OrganizationRole, UserResponse, UserStatus},
+    services::{
+        onboarding::relationship_parts, ConfiguredOnboarding, OnboardingPolicy,
+    },
+};
+use uuid::Uuid;
+
+fn user() -> UserResponse {
+    UserResponse {
+        id: Uuid::new_v4(),
+        email: "new@example.com".to_string(),
+        username: "new".to_string(),
+        avatar_url: None,
+        status: UserStatus::Active,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_default_onboarding_matches_the_built_in_one() {
+    let onboarding = ConfiguredOnboarding::new(&Config::default().auth.onboarding);
+    assert!(onboarding.check().is_ok());
+
+    let user = user();
+    assert_eq!(onboarding.roles(&user), vec!["user".to_string()]);
+    assert_eq!(
+        onboarding.memberships(&user),
+        vec![("default".to_string(), OrganizationRole::Member)]
+    );
+    assert!(onboarding.relationships(&user).is_empty());
+}
+
+#[test]
+fn test_configured_onboarding_is_checked() {
+    let config: OnboardingConfig = serde_json::from_str(
+        r#"{
+            "default_roles": ["user", "moderator"],
+            "memberships": [{"organization": "acme", "role": "admin"}],
+            "relationships": [{"relation": "member", "object": "organization:partners"}]
+        }"#,
+    )
+    .unwrap();
+    let onboarding = ConfiguredOnboarding::new(&config);
+    assert!(onboarding.check().is_ok());
+    assert_eq!(
+        onboarding.memberships(&user()),
+        vec![("acme".to_string(), OrganizationRole::Admin)]
+    );
+    assert_eq!(
+        relationship_parts(&config.relationships[0]).unwrap(),
+        ("member", "organization", "partners")
+    );
+
+    let unknown_role = OnboardingConfig {
+        default_roles: vec!["superuser".to_string()],
+        ..config.clone()
+    };
+    assert!(ConfiguredOnboarding::new(&unknown_role).check().is_err());
+
+    for (relation, object) in [("member", "organization"), ("member", "organization:"), ("member", "widget:acme")] {
+        let relationship = OnboardingRelationship {
+            relation: relation.to_string(),
+            object: object.to_string(),
+        };
+        let config = OnboardingConfig {
+            relationships: vec![relationship],
+            ..config.clone()
+        };
+        assert!(ConfiguredOnboarding::new(&config).check().is_err(), "{} {}", relation, object);
+    }
+}
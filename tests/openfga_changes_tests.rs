@@ -0,0 +1,143 @@
+use axum::{
+    extract::{Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use reprime_backend::{
+    auth::{changes::ChangeWatcher, openfga::OpenFgaService},
+    config::{Config, OpenFgaChangesConfig},
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct FakeStore {
+    allowed: Arc<AtomicBool>,
+    /// Change log entries not yet read
+    pending: Arc<Mutex<Vec<Value>>>,
+    /// Query string of every changes request
+    requests: Arc<Mutex<Vec<HashMap<String, String>>>>,
+}
+
+impl FakeStore {
+    fn push_change(&self, user: &str, relation: &str, object: &str) {
+        self.pending.lock().unwrap().push(json!({
+            "tuple_key": { "user": user, "relation": relation, "object": object },
+            "operation": "TUPLE_OPERATION_WRITE",
+            "timestamp": "2026-10-16T12:00:00Z",
+        }));
+    }
+}
+
+async fn check(State(store): State<FakeStore>) -> Json<Value> {
+    Json(json!({ "allowed": store.allowed.load(Ordering::SeqCst) }))
+}
+
+async fn changes(
+    State(store): State<FakeStore>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let page_size = query["page_size"].parse::<usize>().unwrap();
+    store.requests.lock().unwrap().push(query);
+
+    let mut pending = store.pending.lock().unwrap();
+    let end = page_size.min(pending.len());
+    let page: Vec<Value> = pending.drain(..end).collect();
+    let token = format!("token-{}", store.requests.lock().unwrap().len());
+    Json(json!({ "changes": page, "continuation_token": token }))
+}
+
+async fn service(store: &FakeStore) -> Arc<OpenFgaService> {
+    let app = Router::new()
+        .route("/stores/{store}/check", post(check))
+        .route("/stores/{store}/changes", get(changes))
+        .with_state(store.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    Arc::new(OpenFgaService::new(&config).await.unwrap())
+}
+
+fn watcher(openfga: Arc<OpenFgaService>, page_size: u32) -> ChangeWatcher {
+    let config = OpenFgaChangesConfig {
+        enabled: true,
+        page_size,
+        ..OpenFgaChangesConfig::default()
+    };
+    ChangeWatcher::from_config(&config, openfga).unwrap()
+}
+
+async fn allowed(openfga: &OpenFgaService, user_id: Uuid) -> bool {
+    openfga
+        .check_permission(user_id, "viewer", "document", "1")
+        .await
+        .unwrap()
+        .allowed
+}
+
+#[tokio::test]
+async fn test_direct_tuple_change_invalidates_that_user() {
+    let store = FakeStore::default();
+    let openfga = service(&store).await;
+    let mut watcher = watcher(openfga.clone(), 100);
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+
+    assert!(!allowed(&openfga, alice).await);
+    assert!(!allowed(&openfga, bob).await);
+
+    // Another service grants alice access
+    store.allowed.store(true, Ordering::SeqCst);
+    store.push_change(&format!("user:{}", alice), "viewer", "document:1");
+    assert_eq!(watcher.poll().await.unwrap(), 1);
+
+    assert!(allowed(&openfga, alice).await);
+    assert!(!allowed(&openfga, bob).await, "bob's entry is untouched");
+}
+
+#[tokio::test]
+async fn test_userset_and_parent_changes_clear_the_cache() {
+    let store = FakeStore::default();
+    let openfga = service(&store).await;
+    let mut watcher = watcher(openfga.clone(), 100);
+    let user_id = Uuid::new_v4();
+
+    assert!(!allowed(&openfga, user_id).await);
+    store.allowed.store(true, Ordering::SeqCst);
+    store.push_change("organization:acme#member", "viewer", "project:apollo");
+    watcher.poll().await.unwrap();
+    assert!(allowed(&openfga, user_id).await);
+
+    store.allowed.store(false, Ordering::SeqCst);
+    store.push_change("project:apollo", "project", "document:1");
+    watcher.poll().await.unwrap();
+    assert!(!allowed(&openfga, user_id).await);
+}
+
+#[tokio::test]
+async fn test_poll_pages_through_and_resumes_from_the_token() {
+    let store = FakeStore::default();
+    let openfga = service(&store).await;
+    let mut watcher = watcher(openfga, 2);
+
+    for i in 0..3 {
+        store.push_change(&format!("user:{}", Uuid::new_v4()), "viewer", &format!("document:{}", i));
+    }
+    assert_eq!(watcher.poll().await.unwrap(), 3);
+    assert_eq!(watcher.poll().await.unwrap(), 0);
+
+    let requests = store.requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[0].contains_key("start_time"));
+    assert!(!requests[0].contains_key("continuation_token"));
+    assert_eq!(requests[1]["continuation_token"], "token-1");
+    assert_eq!(requests[2]["continuation_token"], "token-2");
+}
@@ -0,0 +1,236 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use reprime_backend::{
+    auth::openfga::{AuthorizationModel, OpenFgaService, TupleFilter},
+    config::Config,
+    errors::AppError,
+};
+use serde_json::{json, Value};
+
+const MODEL_ID: &str = "01HVMMBCMGZNT3SED4Z17ECXCA";
+const WRITTEN_MODEL_ID: &str = "01HVMMBCMGZNT3SED4Z17ECXCB";
+
+/// Two pages of tuples; a user filter without an object type is rejected
+async fn read_tuples(Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let key = &body["tuple_key"];
+    if key.get("user").is_some() && key.get("object").is_none() {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "code": "type_required" })));
+    }
+
+    let (object, next) = match body["continuation_token"].as_str() {
+        None => ("document:roadmap", "page-2"),
+        Some(_) => ("document:budget", ""),
+    };
+    (
+        StatusCode::OK,
+        Json(json!({
+            "tuples": [{
+                "key": { "user": key["user"], "relation": "viewer", "object": object },
+                "timestamp": "2026-01-01T00:00:00Z"
+            }],
+            "continuation_token": next
+        })),
+    )
+}
+
+/// Editors of `document:roadmap`; other relations aren't in the model
+async fn list_users(Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    if body["relation"] != "editor" {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "code": "relation_not_found" })));
+    }
+    (
+        StatusCode::OK,
+        Json(json!({
+            "users": [
+                { "object": { "type": "user", "id": "anne" } },
+                { "userset": { "type": "group", "id": "eng", "relation": "member" } },
+                { "wildcard": { "type": "user" } }
+            ]
+        })),
+    )
+}
+
+/// Just enough of OpenFGA's model and read endpoints to exercise the client
+async fn fake_openfga() -> OpenFgaService {
+    let app = Router::new()
+        .route("/stores/{store}/read", axum::routing::post(read_tuples))
+        .route("/stores/{store}/list-users", axum::routing::post(list_users))
+        .route(
+            "/stores/{store}/authorization-models",
+            get(|| async {
+                Json(json!({
+                    "authorization_models": [{
+                        "id": MODEL_ID,
+                        "schema_version": "1.1",
+                        "type_definitions": [{ "type": "user", "relations": {}, "metadata": null }]
+                    }],
+                    "continuation_token": ""
+                }))
+            })
+            .post(|Json(body): Json<Value>| async move {
+                if body.get("id").is_some() {
+                    return (StatusCode::BAD_REQUEST, Json(json!({ "message": "unexpected id" })));
+                }
+                (
+                    StatusCode::CREATED,
+                    Json(json!({ "authorization_model_id": WRITTEN_MODEL_ID })),
+                )
+            }),
+        )
+        .route(
+            "/stores/{store}/authorization-models/{id}",
+            get(|Path((_, id)): Path<(String, String)>| async move {
+                if id != MODEL_ID {
+                    return (StatusCode::NOT_FOUND, Json(json!({ "code": "authorization_model_not_found" })));
+                }
+                (
+                    StatusCode::OK,
+                    Json(json!({
+                        "authorization_model": {
+                            "id": MODEL_ID,
+                            "schema_version": "1.1",
+                            "type_definitions": [{ "type": "user" }]
+                        }
+                    })),
+                )
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    OpenFgaService::new(&config).await.expect("openfga service")
+}
+
+fn model(type_definitions: Vec<Value>) -> AuthorizationModel {
+    AuthorizationModel {
+        id: Some("ignored".to_string()),
+        schema_version: "1.1".to_string(),
+        type_definitions,
+        conditions: None,
+    }
+}
+
+#[tokio::test]
+async fn test_write_authorization_model_returns_id() {
+    let openfga = fake_openfga().await;
+
+    let id = openfga
+        .write_authorization_model(&model(vec![json!({ "type": "user" })]))
+        .await
+        .unwrap();
+    assert_eq!(id, WRITTEN_MODEL_ID);
+}
+
+#[tokio::test]
+async fn test_write_authorization_model_requires_type_definitions() {
+    let openfga = fake_openfga().await;
+
+    let result = openfga.write_authorization_model(&model(Vec::new())).await;
+    assert!(matches!(result, Err(AppError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_read_and_list_authorization_models() {
+    let openfga = fake_openfga().await;
+
+    let read = openfga.read_authorization_model(MODEL_ID).await.unwrap();
+    assert_eq!(read.id.as_deref(), Some(MODEL_ID));
+
+    let missing = openfga.read_authorization_model("missing").await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+    let page = openfga.list_authorization_models(Some(10), None).await.unwrap();
+    assert_eq!(page.authorization_models.len(), 1);
+    assert!(page.continuation_token.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_keeps_unchanged_model() {
+    let mut openfga = fake_openfga().await;
+
+    // The stored copy carries empty defaults the file doesn't
+    let id = openfga
+        .sync_authorization_model(&model(vec![json!({ "type": "user" })]))
+        .await
+        .unwrap();
+    assert_eq!(id, MODEL_ID);
+    assert_eq!(openfga.auth_model_id(), Some(MODEL_ID));
+}
+
+#[tokio::test]
+async fn test_sync_writes_changed_model_from_file() {
+    let mut openfga = fake_openfga().await;
+
+    let path = std::env::temp_dir().join(format!("openfga-model-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        json!({
+            "schema_version": "1.1",
+            "type_definitions": [{ "type": "user" }, { "type": "document" }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let from_file = AuthorizationModel::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let id = openfga.sync_authorization_model(&from_file).await.unwrap();
+    assert_eq!(id, WRITTEN_MODEL_ID);
+    assert_eq!(openfga.auth_model_id(), Some(WRITTEN_MODEL_ID));
+}
+
+#[tokio::test]
+async fn test_read_tuples_pages_through_results() {
+    let openfga = fake_openfga().await;
+    let filter = TupleFilter {
+        user: Some("user:alice".to_string()),
+        relation: None,
+        object: Some("document:".to_string()),
+    };
+
+    let first = openfga.read_tuples(&filter, Some(1), None).await.unwrap();
+    assert_eq!(first.tuples[0].object, "document:roadmap");
+    assert_eq!(first.tuples[0].user, "user:alice");
+    assert_eq!(first.continuation_token, "page-2");
+
+    let second = openfga
+        .read_tuples(&filter, Some(1), Some(&first.continuation_token))
+        .await
+        .unwrap();
+    assert_eq!(second.tuples[0].object, "document:budget");
+    assert!(second.continuation_token.is_empty());
+
+    let without_type = TupleFilter {
+        object: None,
+        ..filter
+    };
+    let result = openfga.read_tuples(&without_type, None, None).await;
+    assert!(matches!(result, Err(AppError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_list_users_in_tuple_notation() {
+    let openfga = fake_openfga().await;
+
+    let users = openfga
+        .list_users("document", "roadmap", "editor", "user", None)
+        .await
+        .unwrap();
+    assert_eq!(users, vec!["user:anne", "group:eng#member", "user:*"]);
+
+    let result = openfga
+        .list_users("document", "roadmap", "approver", "user", None)
+        .await;
+    assert!(matches!(result, Err(AppError::Validation(_))));
+}
@@ -0,0 +1,113 @@
+use axum::{
+    http::StatusCode,
+    response::IntoResponse,
+    routing::post,
+    Json, Router,
+};
+use reprime_backend::{
+    auth::openfga::OpenFgaService,
+    config::Config,
+    errors::AppError,
+    metrics::AppMetrics,
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// OpenFGA that answers checks after `check_delay` and writes after
+/// `write_delay`
+async fn slow_openfga(check_delay: Duration, write_delay: Duration) -> Config {
+    let app = Router::new()
+        .route(
+            "/stores/{store}/check",
+            post(move || async move {
+                tokio::time::sleep(check_delay).await;
+                Json(json!({ "allowed": true }))
+            }),
+        )
+        .route(
+            "/stores/{store}/write",
+            post(move |Json(_): Json<Value>| async move {
+                tokio::time::sleep(write_delay).await;
+                Json(json!({}))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    config.auth.openfga.cache_enabled = false;
+    config.auth.openfga.timeouts.check_ms = 100;
+    config.auth.openfga.timeouts.write_ms = 1000;
+    config
+}
+
+#[tokio::test]
+async fn test_slow_check_times_out_on_its_own_budget() {
+    let mut config = slow_openfga(Duration::from_millis(400), Duration::ZERO).await;
+    config.auth.openfga.circuit_breaker.enabled = false;
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&config).await.unwrap().with_metrics(metrics.clone());
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await;
+    let Err(error @ AppError::Timeout(_)) = result else {
+        panic!("expected a timeout, got {:?}", result);
+    };
+    assert_eq!(error.into_response().status(), StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(metrics.openfga_timeouts_total.with_label_values(&["check"]).get(), 1.0);
+
+    // The same latency fits the write budget
+    service
+        .write_relationship(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_timed_out_check_is_answered_by_the_fallback() {
+    let config = slow_openfga(Duration::from_millis(400), Duration::ZERO).await;
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&config).await.unwrap().with_metrics(metrics.clone());
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+    assert!(!result.allowed);
+    assert_eq!(
+        metrics
+            .openfga_fallback_decisions_total
+            .with_label_values(&["viewer", "deny"])
+            .get(),
+        1.0
+    );
+    assert_eq!(metrics.openfga_timeouts_total.with_label_values(&["check"]).get(), 1.0);
+}
+
+#[tokio::test]
+async fn test_slow_write_times_out_on_the_write_budget() {
+    let mut config = slow_openfga(Duration::ZERO, Duration::from_secs(3)).await;
+    config.auth.openfga.timeouts.write_ms = 200;
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&config).await.unwrap().with_metrics(metrics.clone());
+
+    let result = service
+        .write_relationship(Uuid::new_v4(), "viewer", "document", "1")
+        .await;
+    assert!(matches!(result, Err(AppError::Timeout(_))), "{:?}", result);
+    assert_eq!(metrics.openfga_timeouts_total.with_label_values(&["write"]).get(), 1.0);
+
+    // Checks are unaffected by the write budget
+    let checked = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+    assert!(checked.allowed);
+}
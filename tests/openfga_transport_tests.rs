@@ -0,0 +1,74 @@
+use axum::{routing::post, Json, Router};
+use reprime_backend::{
+    auth::openfga::OpenFgaService,
+    config::{Config, OpenFgaTransportKind},
+    errors::AppError,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// Permission checks over gRPC to a port nothing listens on
+fn grpc_config() -> Config {
+    let mut config = Config::default();
+    config.auth.openfga.transport = OpenFgaTransportKind::Grpc;
+    config.auth.openfga.grpc_endpoint = "http://127.0.0.1:1".to_string();
+    config.auth.openfga.request_timeout_seconds = 2;
+    config.auth.openfga.cache_enabled = false;
+    config
+}
+
+#[tokio::test]
+async fn test_http_is_the_default_transport() {
+    let app = Router::new().route(
+        "/stores/{store}/check",
+        post(|Json(body): Json<Value>| async move {
+            Json(json!({ "allowed": body["tuple_key"]["relation"] == "viewer" }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    config.auth.openfga.cache_enabled = false;
+    let service = OpenFgaService::new(&config).await.unwrap();
+    assert_eq!(service.transport_name(), "http");
+
+    let user_id = Uuid::new_v4();
+    let viewer = service.check_permission(user_id, "viewer", "document", "1").await.unwrap();
+    assert!(viewer.allowed);
+    let editor = service.check_permission(user_id, "editor", "document", "1").await.unwrap();
+    assert!(!editor.allowed);
+}
+
+#[tokio::test]
+async fn test_unreachable_grpc_endpoint_uses_the_fallback() {
+    let service = OpenFgaService::new(&grpc_config()).await.unwrap();
+    assert_eq!(service.transport_name(), "grpc");
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+    assert!(!result.allowed);
+
+    let mut config = grpc_config();
+    config.auth.openfga.circuit_breaker.enabled = false;
+    let service = OpenFgaService::new(&config).await.unwrap();
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await;
+    assert!(matches!(result, Err(AppError::Internal(_))));
+}
+
+#[tokio::test]
+async fn test_invalid_grpc_endpoint_is_rejected_at_startup() {
+    let mut config = grpc_config();
+    config.auth.openfga.grpc_endpoint = "not a uri".to_string();
+
+    assert!(OpenFgaService::new(&config).await.is_err());
+}
@@ -0,0 +1,141 @@
+use axum::{routing::post, Json, Router};
+use reprime_backend::{
+    auth::{
+        cache::{CacheTtl, PermissionCache, PermissionCacheBackend, RedisPermissionCache},
+        openfga::OpenFgaService,
+    },
+    config::{Config, PermissionCacheBackendKind, RedisConfig},
+    metrics::AppMetrics,
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Redis on a port nothing listens on
+fn unreachable_redis() -> RedisConfig {
+    RedisConfig {
+        url: "redis://127.0.0.1:1".to_string(),
+        ..RedisConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_memory_backend_invalidates_by_user_and_object() {
+    let cache: Box<dyn PermissionCacheBackend> =
+        Box::new(PermissionCache::new(Duration::from_secs(60), 100));
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+
+    cache.set(alice, "viewer", "document", "1", true).await;
+    cache.set(alice, "viewer", "document", "2", false).await;
+    cache.set(bob, "viewer", "document", "1", true).await;
+    assert_eq!(cache.get(alice, "viewer", "document", "2").await, Some(false));
+
+    cache.invalidate_object("document", "1").await;
+    assert_eq!(cache.get(alice, "viewer", "document", "1").await, None);
+    assert_eq!(cache.get(bob, "viewer", "document", "1").await, None);
+    assert_eq!(cache.get(alice, "viewer", "document", "2").await, Some(false));
+
+    cache.invalidate_user(alice).await;
+    assert_eq!(cache.stats().await.total_entries, 0);
+}
+
+#[tokio::test]
+async fn test_full_shard_evicts_the_least_recently_used_entry() {
+    // 16 shards of two entries; one user's entries share a shard
+    let cache = PermissionCache::new(Duration::from_secs(60), 32);
+    let user_id = Uuid::new_v4();
+
+    cache.set(user_id, "viewer", "document", "a", true).await;
+    cache.set(user_id, "viewer", "document", "b", true).await;
+    assert_eq!(cache.get(user_id, "viewer", "document", "a").await, Some(true));
+    cache.set(user_id, "viewer", "document", "c", true).await;
+
+    assert_eq!(cache.get(user_id, "viewer", "document", "b").await, None);
+    assert_eq!(cache.get(user_id, "viewer", "document", "a").await, Some(true));
+    assert_eq!(cache.get(user_id, "viewer", "document", "c").await, Some(true));
+
+    let stats = cache.stats().await;
+    assert_eq!((stats.hits, stats.misses, stats.evictions), (3, 1, 1));
+}
+
+#[tokio::test]
+async fn test_denied_and_per_relation_ttls() {
+    let mut config = Config::default();
+    config.auth.openfga.cache_ttl_seconds = 300;
+    config.auth.openfga.cache_denied_ttl_seconds = Some(0);
+    config.auth.openfga.cache_relation_ttl_seconds.insert("owner".to_string(), 60);
+    let ttl = CacheTtl::from_config(&config.auth.openfga).unwrap();
+
+    assert_eq!(ttl.for_result("viewer", true), Duration::from_secs(300));
+    assert_eq!(ttl.for_result("owner", true), Duration::from_secs(60));
+    assert_eq!(ttl.for_result("owner", false), Duration::ZERO);
+
+    // A zero denied TTL leaves denials uncached, so a new grant applies at once
+    let cache = PermissionCache::new(Duration::from_secs(300), 100).with_ttl(ttl);
+    let user_id = Uuid::new_v4();
+    cache.set(user_id, "viewer", "document", "1", false).await;
+    cache.set(user_id, "viewer", "document", "2", true).await;
+    assert_eq!(cache.get(user_id, "viewer", "document", "1").await, None);
+    assert_eq!(cache.get(user_id, "viewer", "document", "2").await, Some(true));
+
+    config.auth.openfga.cache_relation_ttl_seconds.insert("reader".to_string(), 60);
+    assert!(CacheTtl::from_config(&config.auth.openfga).is_err());
+}
+
+#[tokio::test]
+async fn test_unreachable_redis_behaves_as_a_miss() {
+    let cache = RedisPermissionCache::new(&unreachable_redis(), Duration::from_secs(60)).unwrap();
+    let user_id = Uuid::new_v4();
+
+    cache.set(user_id, "viewer", "document", "1", true).await;
+    assert_eq!(cache.get(user_id, "viewer", "document", "1").await, None);
+
+    // Reconnects are backed off, so an outage doesn't slow every lookup
+    let started = Instant::now();
+    assert_eq!(cache.get(user_id, "viewer", "document", "1").await, None);
+    assert!(started.elapsed() < Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_checks_record_cache_metrics_and_survive_without_redis() {
+    let app = Router::new().route(
+        "/stores/{store}/check",
+        post(|| async { Json(json!({ "allowed": true })) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&config)
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+
+    let user_id = Uuid::new_v4();
+    for _ in 0..2 {
+        let result = service.check_permission(user_id, "viewer", "document", "1").await.unwrap();
+        assert!(result.allowed);
+    }
+    let labels = ["openfga_permission", "check"];
+    assert_eq!(metrics.cache_hits_total.with_label_values(&labels).get(), 1.0);
+    assert_eq!(metrics.cache_misses_total.with_label_values(&labels).get(), 1.0);
+
+    config.auth.openfga.cache_backend = PermissionCacheBackendKind::Redis;
+    config.redis = unreachable_redis();
+    let service = OpenFgaService::new(&config).await.unwrap();
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+    assert!(result.allowed);
+
+    config.redis.url = "not a url".to_string();
+    assert!(OpenFgaService::new(&config).await.is_err());
+}
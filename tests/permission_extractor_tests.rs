@@ -0,0 +1,103 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::StatusCode,
+    middleware::{self, Next},
+    routing::get,
+    Router,
+};
+use reprime_backend::{
+    auth::{
+        authorizer::{Authorizer, StaticAuthorizer},
+        models::{AuthContext, SubjectType},
+        permission::{RequirePermission, RequiredPermission},
+        registry::{ObjectType, Relation},
+    },
+    required_permission,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+required_permission!(ViewDocument = Document.Viewer on "document_id");
+required_permission!(AdministerOrganization = Organization.Admin on "id");
+
+fn app(authorizer: StaticAuthorizer, caller: Option<Uuid>) -> Router {
+    Router::new()
+        .route(
+            "/documents/{document_id}",
+            get(|document: RequirePermission<ViewDocument>| async move {
+                format!("{} for {}", document.object_id, document.auth_context.username)
+            }),
+        )
+        .route(
+            "/organizations/{id}",
+            get(|RequirePermission { object_id, .. }: RequirePermission<AdministerOrganization>| async move {
+                object_id
+            }),
+        )
+        .with_state(Arc::new(authorizer) as Arc<dyn Authorizer>)
+        .layer(middleware::from_fn(move |mut request: Request, next: Next| async move {
+            if let Some(user_id) = caller {
+                request.extensions_mut().insert(AuthContext {
+                    user_id,
+                    email: "caller@example.com".to_string(),
+                    username: "caller".to_string(),
+                    roles: vec![],
+                    session_id: None,
+                    tenant_id: None,
+                    scopes: None,
+                    subject_type: SubjectType::User,
+                });
+            }
+            next.run(request).await
+        }))
+}
+
+async fn get_body(app: Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn test_declared_permissions_come_from_the_registry() {
+    assert_eq!(ViewDocument::PERMISSION.object_type, ObjectType::Document);
+    assert_eq!(ViewDocument::PERMISSION.relation, Relation::Viewer);
+    assert_eq!(ViewDocument::PARAM, "document_id");
+    assert_eq!(AdministerOrganization::PARAM, "id");
+}
+
+#[tokio::test]
+async fn test_handler_runs_only_when_the_check_allows() {
+    let user_id = Uuid::new_v4();
+    let authorizer = || {
+        StaticAuthorizer::new()
+            .with_tuple(user_id, "viewer", "document", "q3-report")
+            .with_tuple(user_id, "admin", "organization", "acme")
+    };
+
+    assert_eq!(
+        get_body(app(authorizer(), Some(user_id)), "/documents/q3-report").await,
+        (StatusCode::OK, "q3-report for caller".to_string())
+    );
+    assert_eq!(
+        get_body(app(authorizer(), Some(user_id)), "/organizations/acme").await,
+        (StatusCode::OK, "acme".to_string())
+    );
+
+    let (status, body) = get_body(app(authorizer(), Some(user_id)), "/documents/q4-report").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["error"], "Forbidden");
+}
+
+#[tokio::test]
+async fn test_unauthenticated_callers_are_rejected_before_the_check() {
+    let (status, _) = get_body(app(StaticAuthorizer::new(), None), "/organizations/acme").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,101 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::StatusCode,
+    middleware::{self, Next},
+    routing::get,
+    Router,
+};
+use reprime_backend::auth::{
+    authorizer::{Authorizer, StaticAuthorizer},
+    middleware::require_permission,
+    models::{object_types, relations, AuthContext, SubjectType},
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Routes guarded by `require_permission`, called as `user_id`
+fn app(user_id: Uuid, authorizer: StaticAuthorizer) -> Router {
+    let authorizer = Arc::new(authorizer) as Arc<dyn Authorizer>;
+
+    Router::new()
+        .route(
+            "/organizations/{org_id}/documents/{document_id}",
+            get(|| async { "document" }),
+        )
+        .layer(middleware::from_fn_with_state(
+            authorizer.clone(),
+            require_permission(relations::VIEWER, object_types::DOCUMENT, "document_id"),
+        ))
+        .merge(
+            Router::new()
+                .route("/organizations/{org_id}", get(|| async { "organization" }))
+                .route("/settings", get(|| async { "settings" }))
+                .layer(middleware::from_fn_with_state(
+                    authorizer,
+                    require_permission(relations::MEMBER, object_types::ORGANIZATION, "org_id"),
+                )),
+        )
+        .layer(middleware::from_fn(move |mut request: Request, next: Next| async move {
+            request.extensions_mut().insert(AuthContext {
+                user_id,
+                email: "member@example.com".to_string(),
+                username: "member".to_string(),
+                roles: vec![],
+                session_id: None,
+                tenant_id: None,
+                scopes: None,
+                subject_type: SubjectType::User,
+            });
+            next.run(request).await
+        }))
+}
+
+async fn get_status(app: &Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_object_id_comes_from_the_named_parameter() {
+    let user_id = Uuid::new_v4();
+    let app = app(
+        user_id,
+        StaticAuthorizer::new().with_tuple(user_id, relations::VIEWER, object_types::DOCUMENT, "q3-report"),
+    );
+
+    // Not the organization id, and no UUID guessing
+    assert_eq!(get_status(&app, "/organizations/acme/documents/q3-report").await.0, StatusCode::OK);
+    assert_eq!(
+        get_status(&app, "/organizations/q3-report/documents/acme").await.0,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn test_non_uuid_ids_are_percent_decoded() {
+    let user_id = Uuid::new_v4();
+    let app = app(
+        user_id,
+        StaticAuthorizer::new().with_tuple(user_id, relations::MEMBER, object_types::ORGANIZATION, "acme corp"),
+    );
+
+    assert_eq!(get_status(&app, "/organizations/acme%20corp").await.0, StatusCode::OK);
+    assert_eq!(get_status(&app, "/organizations/acme").await.0, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_route_without_the_parameter_is_a_server_error() {
+    let app = app(Uuid::new_v4(), StaticAuthorizer::new());
+
+    let (status, body) = get_status(&app, "/settings").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body, "Route has no 'org_id' path parameter");
+}
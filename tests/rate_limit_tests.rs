@@ -0,0 +1,138 @@
+use axum::{body::Body, extract::ConnectInfo, http::Request, middleware, routing::post, Router};
+use reprime_backend::auth::rate_limit::{login_rate_limit_middleware, LoginRateLimiter, TokenBuckets};
+use reprime_backend::config::LoginRateLimitConfig;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+#[test]
+fn test_bucket_refills_over_time() {
+    let buckets = TokenBuckets::new(2, 60, 100);
+    let start = Instant::now();
+
+    assert!(buckets.try_acquire_at("ip", start).is_ok());
+    assert!(buckets.try_acquire_at("ip", start).is_ok());
+    let wait = buckets.try_acquire_at("ip", start).unwrap_err();
+    assert!(wait <= Duration::from_secs(1));
+
+    // One token per second
+    assert!(buckets.try_acquire_at("ip", start + Duration::from_secs(1)).is_ok());
+    assert!(buckets.try_acquire_at("other", start).is_ok());
+}
+
+#[test]
+fn test_full_table_keeps_limiting() {
+    let buckets = TokenBuckets::new(1, 1, 2);
+    let start = Instant::now();
+
+    assert!(buckets.try_acquire_at("a", start).is_ok());
+    assert!(buckets.try_acquire_at("b", start + Duration::from_secs(1)).is_ok());
+
+    // The table is full of depleted buckets; a new key still gets a bucket
+    // of its own and is limited once it runs out
+    let later = start + Duration::from_secs(2);
+    assert!(buckets.try_acquire_at("c", later).is_ok());
+    assert!(buckets.try_acquire_at("c", later).is_err());
+
+    // "a" was idle the longest and made room; "b" is still limited
+    assert!(buckets.try_acquire_at("b", later).is_err());
+}
+
+#[test]
+fn test_account_limit_ignores_email_case() {
+    let limiter = LoginRateLimiter::new(&LoginRateLimitConfig {
+        account_burst: 1,
+        ..LoginRateLimitConfig::default()
+    });
+
+    assert!(limiter.check(Some("203.0.113.1"), Some("User@Example.com")).is_ok());
+    assert!(limiter.check(Some("203.0.113.2"), Some(" user@example.com")).is_err());
+    assert!(limiter.check(Some("203.0.113.2"), Some("other@example.com")).is_ok());
+}
+
+#[tokio::test]
+async fn test_middleware_returns_429_with_retry_after() {
+    let limiter = LoginRateLimiter::from_config(&LoginRateLimitConfig {
+        ip_burst: 1,
+        ..LoginRateLimitConfig::default()
+    })
+    .expect("enabled by default");
+    let app = Router::new()
+        .route("/api/v1/auth/login", post(|body: String| async move { body }))
+        .layer(middleware::from_fn_with_state(limiter, login_rate_limit_middleware));
+
+    let login = || {
+        Request::post("/api/v1/auth/login")
+            .extension(ConnectInfo(SocketAddr::from(([203, 0, 113, 9], 40000))))
+            .body(Body::from(r#"{"email":"user@example.com","password":"x"}"#))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(login()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    // The handler still sees the body
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert!(body.starts_with(br#"{"email""#));
+
+    let response = app.oneshot(login()).await.unwrap();
+    assert_eq!(response.status(), 429);
+    let retry_after: u64 = response.headers()["retry-after"].to_str().unwrap().parse().unwrap();
+    assert!(retry_after >= 1);
+}
+
+/// App allowing each client IP one login, with `trusted_proxy_hops` proxies
+fn limited_app(trusted_proxy_hops: usize) -> Router {
+    let limiter = LoginRateLimiter::from_config(&LoginRateLimitConfig {
+        ip_burst: 1,
+        account_burst: 100,
+        trusted_proxy_hops,
+        ..LoginRateLimitConfig::default()
+    })
+    .expect("enabled by default");
+    Router::new()
+        .route("/api/v1/auth/register", post(|| async { "ok" }))
+        .layer(middleware::from_fn_with_state(limiter, login_rate_limit_middleware))
+}
+
+fn register(peer: [u8; 4], forwarded_for: Option<&str>) -> Request<Body> {
+    let mut request = Request::post("/api/v1/auth/register")
+        .extension(ConnectInfo(SocketAddr::from((peer, 40000))));
+    if let Some(forwarded_for) = forwarded_for {
+        request = request.header("x-forwarded-for", forwarded_for);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_ip_limit_keys_on_the_peer_without_trusted_proxies() {
+    let app = limited_app(0);
+
+    // Without any headers the peer is still limited
+    let first = app.clone().oneshot(register([198, 51, 100, 1], None)).await.unwrap();
+    assert_eq!(first.status(), 200);
+    let second = app.clone().oneshot(register([198, 51, 100, 1], None)).await.unwrap();
+    assert_eq!(second.status(), 429);
+
+    // ... and a made-up X-Forwarded-For doesn't get it a fresh bucket
+    let spoofed = register([198, 51, 100, 1], Some("192.0.2.77"));
+    assert_eq!(app.clone().oneshot(spoofed).await.unwrap().status(), 429);
+
+    let other = app.oneshot(register([198, 51, 100, 2], None)).await.unwrap();
+    assert_eq!(other.status(), 200);
+}
+
+#[tokio::test]
+async fn test_ip_limit_takes_the_entry_the_trusted_proxy_appended() {
+    let app = limited_app(1);
+    let proxy = [10, 0, 0, 1];
+
+    let first = register(proxy, Some("192.0.2.1, 203.0.113.5"));
+    assert_eq!(app.clone().oneshot(first).await.unwrap().status(), 200);
+
+    // A different client-supplied prefix is the same client
+    let spoofed = register(proxy, Some("192.0.2.2, 203.0.113.5"));
+    assert_eq!(app.clone().oneshot(spoofed).await.unwrap().status(), 429);
+
+    let other = register(proxy, Some("192.0.2.1, 203.0.113.6"));
+    assert_eq!(app.oneshot(other).await.unwrap().status(), 200);
+}
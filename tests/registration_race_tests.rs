@@ -0,0 +1,204 @@
+use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+use futures::future::join_all;
+use reprime_backend::{
+    config::Config,
+    database::{InstrumentedDatabase, ShardRouter},
+    errors::AppError,
+    models::{CreateUserRequest, UpdateUserRequest},
+    repositories::Repositories,
+    services::UserService,
+    utils::run_migrations,
+};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const RACERS: usize = 8;
+
+/// Repositories on the test database from `APP_DATABASE_URL`, migrated;
+/// `None` (and the test skipped) when it isn't set
+async fn repositories() -> Option<Arc<Repositories>> {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return None;
+    };
+    let pool = PgPoolOptions::new()
+        .max_connections(RACERS as u32 * 2)
+        .connect(&url)
+        .await
+        .expect("test database");
+    run_migrations(&Config::default(), &pool, &[], None)
+        .await
+        .expect("migrations");
+    Some(Arc::new(Repositories::new(Arc::new(
+        InstrumentedDatabase::new(pool, None),
+    ))))
+}
+
+/// Repositories sharding users across the test database and a second one
+/// next to it, created on first use
+async fn sharded_repositories() -> Option<Arc<Repositories>> {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return None;
+    };
+    let primary = PgPoolOptions::new()
+        .max_connections(RACERS as u32 * 2)
+        .connect(&url)
+        .await
+        .expect("test database");
+
+    let (server, name) = url.rsplit_once('/').expect("database url with a name");
+    let shard_name = format!("{}_shard1", name);
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pg_database WHERE datname = $1)")
+        .bind(&shard_name)
+        .fetch_one(&primary)
+        .await
+        .unwrap();
+    if !exists {
+        // Another test binary may be creating it too
+        let _ = sqlx::query(&format!("CREATE DATABASE \"{}\"", shard_name))
+            .execute(&primary)
+            .await;
+    }
+    let shard = Arc::new(
+        PgPoolOptions::new()
+            .max_connections(RACERS as u32 * 2)
+            .connect(&format!("{}/{}", server, shard_name))
+            .await
+            .expect("shard database"),
+    );
+
+    run_migrations(&Config::default(), &primary, std::slice::from_ref(&shard), None)
+        .await
+        .expect("migrations");
+    let primary = Arc::new(InstrumentedDatabase::new(primary, None));
+    let shards = ShardRouter::new(
+        primary.clone(),
+        vec![Arc::new(InstrumentedDatabase::new((*shard).clone(), None))],
+    );
+    Some(Arc::new(Repositories::sharded(Arc::new(shards), primary)))
+}
+
+fn request(email: &str, username: &str) -> CreateUserRequest {
+    CreateUserRequest {
+        email: email.to_string(),
+        username: username.to_string(),
+    }
+}
+
+fn conflict_field(error: &AppError) -> Option<&str> {
+    match error {
+        AppError::Conflict(conflict) => conflict.field.as_deref(),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn test_taken_email_is_a_structured_conflict() {
+    let response = AppError::email_taken().into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, json!({ "error": "Email already registered", "field": "email" }));
+
+    let response = AppError::username_taken().into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_concurrent_inserts_with_one_email_conflict() {
+    let Some(repositories) = repositories().await else {
+        return;
+    };
+    let run = Uuid::new_v4().simple().to_string();
+    let email = format!("race-{}@example.com", run);
+
+    // Straight to the insert, so every racer gets past any existence check
+    let results = join_all((0..RACERS).map(|i| {
+        let repositories = repositories.clone();
+        let request = request(&email, &format!("race-{}-{}", &run[..8], i));
+        async move { repositories.user.create(request).await }
+    }))
+    .await;
+
+    let created: Vec<_> = results.iter().filter_map(|result| result.as_ref().ok()).collect();
+    assert_eq!(created.len(), 1, "{:?}", results);
+    for error in results.iter().filter_map(|result| result.as_ref().err()) {
+        assert_eq!(conflict_field(error), Some("email"), "{:?}", error);
+    }
+
+    repositories.user.delete(created[0].id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_registrations_with_one_username_conflict() {
+    let Some(repositories) = repositories().await else {
+        return;
+    };
+    let users = UserService::new(repositories.clone());
+    let username = format!("race-{}", &Uuid::new_v4().simple().to_string()[..12]);
+
+    let results = join_all((0..RACERS).map(|i| {
+        let users = users.clone();
+        let request = request(&format!("{}-{}@example.com", username, i), &username);
+        async move { users.create_user(None, request).await }
+    }))
+    .await;
+
+    // Whether caught by the service's check or by the constraint, the loser
+    // sees the same 409
+    let created: Vec<_> = results.iter().filter_map(|result| result.as_ref().ok()).collect();
+    assert_eq!(created.len(), 1, "{:?}", results);
+    for error in results.iter().filter_map(|result| result.as_ref().err()) {
+        assert_eq!(conflict_field(error), Some("username"), "{:?}", error);
+    }
+
+    repositories.user.delete(created[0].id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_inserts_across_shards_with_one_email_conflict() {
+    let Some(repositories) = sharded_repositories().await else {
+        return;
+    };
+    let run = Uuid::new_v4().simple().to_string();
+    let email = format!("shard-race-{}@example.com", run);
+
+    // Racers land on both shards, whose own constraints can't see each other
+    let results = join_all((0..RACERS).map(|i| {
+        let repositories = repositories.clone();
+        let request = request(&email, &format!("shard-race-{}-{}", &run[..8], i));
+        async move { repositories.user.create(request).await }
+    }))
+    .await;
+
+    let created: Vec<_> = results.iter().filter_map(|result| result.as_ref().ok()).collect();
+    assert_eq!(created.len(), 1, "{:?}", results);
+    for error in results.iter().filter_map(|result| result.as_ref().err()) {
+        assert_eq!(conflict_field(error), Some("email"), "{:?}", error);
+    }
+
+    // Renaming another user to the taken username is refused too, and
+    // deleting the first user frees its handles
+    let username = created[0].username.clone();
+    let other = repositories
+        .user
+        .create(request(&format!("shard-other-{}@example.com", run), &format!("shard-other-{}", &run[..8])))
+        .await
+        .unwrap();
+    let rename = || UpdateUserRequest {
+        email: None,
+        username: Some(username.clone()),
+    };
+    let error = repositories.user.update(other.id, rename()).await.unwrap_err();
+    assert_eq!(conflict_field(&error), Some("username"), "{:?}", error);
+
+    repositories.user.delete(created[0].id).await.unwrap();
+    let renamed = repositories.user.update(other.id, rename()).await.unwrap().unwrap();
+    assert_eq!(renamed.username, username);
+
+    repositories.user.delete(other.id).await.unwrap();
+}
@@ -0,0 +1,40 @@
+use reprime_backend::{
+    auth::{
+        middleware::require_permission,
+        openfga::AuthorizationModel,
+        registry::{self, ObjectType, Permission, Relation},
+    },
+    errors::AppError,
+};
+
+// Fails to compile if the model stops defining `viewer` on documents
+const VIEW_DOCUMENT: Permission = Permission::new(ObjectType::Document, Relation::Viewer);
+
+#[test]
+fn test_registry_matches_model_file() {
+    let model = AuthorizationModel::from_file("config/openfga-model.json").unwrap();
+    registry::check_model(&model).unwrap();
+
+    // ... and nothing in the model is missing from the registry
+    for definition in &model.type_definitions {
+        let object_type = ObjectType::parse(definition["type"].as_str().unwrap())
+            .unwrap_or_else(|| panic!("type {} is not registered", definition["type"]));
+        let relations = definition["relations"].as_object().unwrap();
+        assert_eq!(relations.len(), object_type.relations().len(), "{}", object_type);
+    }
+}
+
+#[test]
+fn test_validate_rejects_undefined_pairs() {
+    assert_eq!(registry::validate("document", "viewer").unwrap(), VIEW_DOCUMENT);
+
+    assert!(matches!(registry::validate("document", "member"), Err(AppError::Validation(_))));
+    assert!(matches!(registry::validate("folder", "viewer"), Err(AppError::Validation(_))));
+    assert!(matches!(registry::validate("organization", "viewr"), Err(AppError::Validation(_))));
+}
+
+#[test]
+#[should_panic(expected = "is not defined on 'organization'")]
+fn test_require_permission_rejects_undefined_pairs_when_built() {
+    let _ = require_permission("viewer", "organization", "id");
+}
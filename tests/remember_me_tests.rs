@@ -0,0 +1,50 @@
+use reprime_backend::{
+    auth::models::{LoginRequest, SessionInfo, UserSession},
+    config::{Config, RememberMeConfig},
+};
+use uuid::Uuid;
+
+#[test]
+fn test_remember_me_is_opt_in_on_login() {
+    let request: LoginRequest =
+        serde_json::from_str(r#"{"email":"a@example.com","password":"password123"}"#).unwrap();
+    assert!(!request.remember_me);
+
+    let request: LoginRequest = serde_json::from_str(
+        r#"{"email":"a@example.com","password":"password123","remember_me":true}"#,
+    )
+    .unwrap();
+    assert!(request.remember_me);
+}
+
+#[test]
+fn test_idle_timeout_is_shorter_than_the_absolute_one() {
+    let config = Config::default().auth.remember_me;
+    assert_eq!(config.idle_timeout(), chrono::Duration::days(14));
+    assert_eq!(config.absolute_timeout(), chrono::Duration::days(90));
+
+    let config: RememberMeConfig =
+        serde_json::from_str(r#"{"idle_timeout_hours":12,"absolute_timeout_days":7}"#).unwrap();
+    assert_eq!(config.idle_timeout(), chrono::Duration::hours(12));
+    assert_eq!(config.absolute_timeout(), chrono::Duration::days(7));
+
+    // Sessions show whether they slide
+    let now = chrono::Utc::now();
+    let session = UserSession {
+        id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        device: None,
+        ip_address: None,
+        scope: None,
+        expires_at: now + config.idle_timeout(),
+        created_at: now,
+        last_used_at: Some(now),
+        revoked_at: None,
+        remember_me: true,
+        absolute_expires_at: Some(now + config.absolute_timeout()),
+        device_fingerprint: None,
+        verified: true,
+    };
+    let info = serde_json::to_value(SessionInfo::from_session(session, None)).unwrap();
+    assert_eq!(info["remember_me"], true);
+}
@@ -0,0 +1,103 @@
+use axum::{body::Body, extract::Request, http::HeaderMap, middleware, routing::get, Router};
+use reprime_backend::{
+    config::RequestContextConfig,
+    middleware::request_context_middleware,
+    request_context::{self, RequestContext},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, value.parse().unwrap());
+    }
+    headers
+}
+
+#[test]
+fn test_context_is_read_from_headers_with_fallbacks() {
+    let config = RequestContextConfig {
+        supported_locales: vec!["en".to_string(), "fr".to_string(), "pt-BR".to_string()],
+        ..RequestContextConfig::default()
+    };
+
+    let context = RequestContext::from_headers(
+        &headers(&[
+            ("accept-language", "de;q=0.9, fr-CA;q=0.8, en;q=0.5"),
+            ("x-timezone", "America/Argentina/Buenos_Aires"),
+            ("x-request-id", "req-123"),
+            ("x-forwarded-for", "203.0.113.7, 10.0.0.1"),
+            ("user-agent", "curl/8.5.0"),
+            ("x-request-timeout-ms", "2000"),
+        ]),
+        &config,
+    );
+    // German isn't supported; Canadian French gets French
+    assert_eq!(context.locale, "fr");
+    assert_eq!(context.timezone, "America/Argentina/Buenos_Aires");
+    assert_eq!(context.request_id, "req-123");
+    assert_eq!(context.client_ip.as_deref(), Some("203.0.113.7"));
+    assert_eq!(context.user_agent.as_deref(), Some("curl/8.5.0"));
+    assert!(context.remaining() <= Duration::from_millis(2000));
+
+    let context = RequestContext::from_headers(
+        &headers(&[
+            ("accept-language", "pt-br, en;q=0"),
+            ("x-timezone", "../etc/passwd"),
+            ("x-request-id", "has spaces"),
+            ("x-request-timeout-ms", "999999999"),
+        ]),
+        &config,
+    );
+    assert_eq!(context.locale, "pt-BR");
+    assert_eq!(context.timezone, "UTC");
+    assert_eq!(context.request_id.len(), 36);
+    assert!(context.client_ip.is_none());
+    // Callers can't extend the deadline
+    assert!(context.remaining() <= Duration::from_millis(30000));
+
+    let context = RequestContext::from_headers(&headers(&[("accept-language", "ja")]), &config);
+    assert_eq!(context.locale, "en");
+}
+
+#[tokio::test]
+async fn test_middleware_shares_one_context_with_handlers_and_services() {
+    let handler = |context: RequestContext| async move {
+        // What a service deeper down sees
+        let current = RequestContext::current().unwrap();
+        assert_eq!(current.request_id, context.request_id);
+        assert!(request_context::deadline_within(Duration::from_secs(60)) <= context.deadline);
+        format!("{} {}", context.locale, context.timezone)
+    };
+    let app = Router::new().route("/", get(handler)).layer(middleware::from_fn_with_state(
+        Arc::new(RequestContextConfig::default()),
+        request_context_middleware,
+    ));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header("x-request-id", "abc-1")
+                .header("x-timezone", "Asia/Tokyo")
+                .header("x-request-timeout-ms", "500")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-request-id"], "abc-1");
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(&body[..], b"en Asia/Tokyo");
+
+    // A request id is made up when the caller sends none
+    let response =
+        app.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()).await.unwrap();
+    assert!(!response.headers()["x-request-id"].is_empty());
+
+    // No request, no context
+    assert!(RequestContext::current().is_none());
+}
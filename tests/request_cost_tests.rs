@@ -0,0 +1,89 @@
+use axum::{body::Body, http::Request, middleware, routing::get, Router};
+use reprime_backend::{
+    client::HttpClient,
+    metrics::AppMetrics,
+    middleware::request_cost_middleware,
+    request_cost::{self, RequestCost},
+};
+use std::time::Duration;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_scope_tallies_recorded_calls() {
+    let ((), cost) = RequestCost::scope(async {
+        request_cost::record_db_query(Duration::from_millis(3));
+        request_cost::record_db_query(Duration::from_millis(2));
+        request_cost::record_openfga_call();
+        request_cost::record_cache_hit();
+        request_cost::record_cache_miss();
+    })
+    .await;
+
+    assert_eq!(cost.db_queries, 2);
+    assert_eq!(cost.db_time, Duration::from_millis(5));
+    assert_eq!(cost.openfga_calls, 1);
+    assert_eq!(cost.cache_hits, 1);
+    assert_eq!(cost.cache_misses, 1);
+    assert_eq!(cost.http_calls, 0);
+}
+
+#[tokio::test]
+async fn test_work_outside_the_scope_is_not_charged() {
+    // No scope at all: recording is a no-op
+    request_cost::record_db_query(Duration::from_millis(1));
+
+    let ((), cost) = RequestCost::scope(async {
+        tokio::spawn(async { request_cost::record_openfga_call() })
+            .await
+            .unwrap();
+    })
+    .await;
+
+    assert_eq!(cost, Default::default());
+}
+
+#[tokio::test]
+async fn test_outbound_http_calls_are_counted() {
+    let upstream = Router::new().route("/ping", get(|| async { "{}" }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, upstream).await.unwrap();
+    });
+
+    let client = HttpClient::with_base_url(format!("http://{}", addr)).unwrap();
+    let (response, cost) = RequestCost::scope(client.get_response("/ping")).await;
+
+    assert!(response.unwrap().status().is_success());
+    assert_eq!(cost.http_calls, 1);
+}
+
+#[tokio::test]
+async fn test_middleware_records_cost_per_route() {
+    let metrics = AppMetrics::new().unwrap();
+    let app = Router::new()
+        .route(
+            "/things/{id}",
+            get(|| async {
+                request_cost::record_db_query(Duration::from_millis(4));
+                request_cost::record_db_query(Duration::from_millis(4));
+                "thing"
+            }),
+        )
+        .layer(middleware::from_fn_with_state(metrics.clone(), request_cost_middleware));
+
+    for id in 1..=2 {
+        let request = Request::builder()
+            .uri(format!("/things/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap();
+    }
+
+    // Both requests land on the route template, not the concrete path
+    let queries = metrics
+        .request_db_queries
+        .with_label_values(&["GET", "/things/{id}"]);
+    assert_eq!(queries.get_sample_count(), 2);
+    assert_eq!(queries.get_sample_sum(), 4.0);
+}
@@ -0,0 +1,103 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use chrono::{TimeZone, Utc};
+use reprime_backend::middleware::response_format_middleware;
+use reprime_backend::models::format::{
+    decode_base62, encode_base62, parse_id, IdFormat, ResponseFormat, TimestampFormat,
+};
+use reprime_backend::models::{User, UserResponse, UserStatus};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn user() -> UserResponse {
+    let at = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    UserResponse {
+        id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
+        email: "user@example.com".to_string(),
+        username: "johndoe".to_string(),
+        avatar_url: None,
+        status: UserStatus::Active,
+        created_at: at,
+        updated_at: at,
+    }
+}
+
+#[test]
+fn test_format_options_and_base62_ids() {
+    let format = ResponseFormat::parse("Epoch-Millis, base62").unwrap();
+    assert_eq!(format.timestamps, TimestampFormat::EpochMillis);
+    assert_eq!(format.ids, IdFormat::Base62);
+    assert_eq!(format.to_string(), "epoch-millis,base62");
+    assert_eq!(ResponseFormat::parse("standard").unwrap(), ResponseFormat::default());
+    assert!(ResponseFormat::parse("unix").is_err());
+
+    for id in [Uuid::nil(), Uuid::max(), Uuid::new_v4()] {
+        let encoded = encode_base62(id);
+        assert_eq!(encoded.len(), 22);
+        assert_eq!(decode_base62(&encoded), Some(id));
+        assert_eq!(parse_id(&encoded), Some(id));
+        assert_eq!(parse_id(&id.to_string()), Some(id));
+    }
+    assert_eq!(decode_base62("not-base62"), None);
+}
+
+#[tokio::test]
+async fn test_fields_follow_the_current_format_and_read_back_in_any() {
+    assert_eq!(
+        serde_json::to_value(user()).unwrap()["created_at"],
+        "2025-01-01T12:00:00Z"
+    );
+
+    let format = ResponseFormat::parse("epoch-millis,base62").unwrap();
+    let value = format.scope(async { serde_json::to_value(user()).unwrap() }).await;
+    assert_eq!(value["created_at"], 1735732800000i64);
+    assert_eq!(value["id"], encode_base62(user().id));
+
+    // Either form deserializes, so values can be sent back as received
+    let parsed: User = serde_json::from_value(json!({
+        "id": value["id"],
+        "email": "user@example.com",
+        "username": "johndoe",
+        "created_at": 1735732800000i64,
+        "updated_at": "2025-01-01T12:00:00Z",
+    }))
+    .unwrap();
+    assert_eq!(parsed.id, user().id);
+    assert_eq!(parsed.created_at, parsed.updated_at);
+}
+
+#[tokio::test]
+async fn test_header_selects_the_format() {
+    let app = Router::new()
+        .route("/user", get(|| async { Json(user()) }))
+        .layer(middleware::from_fn(response_format_middleware));
+    let call = |format: Option<&'static str>| {
+        let mut request = Request::builder().uri("/user");
+        if let Some(format) = format {
+            request = request.header("x-response-format", format);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    let response = call(Some("epoch-millis")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["vary"], "x-response-format");
+    let body = axum::body::to_bytes(response.into_body(), 4096).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["updated_at"], 1735732800000i64);
+    assert_eq!(body["id"], "550e8400-e29b-41d4-a716-446655440000");
+
+    let response = call(None).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), 4096).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["updated_at"], "2025-01-01T12:00:00Z");
+
+    let response = call(Some("yaml")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,148 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use reprime_backend::{
+    auth::openfga::OpenFgaService,
+    config::Config,
+    metrics::AppMetrics,
+    utils::retry::{Jitter, RetryBudget, RetryPolicy},
+};
+use serde_json::json;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[test]
+fn test_backoff_grows_to_the_ceiling_within_its_jitter() {
+    let policy = RetryPolicy::new("test")
+        .initial_delay(Duration::from_millis(100))
+        .max_delay(Duration::from_millis(300))
+        .multiplier(2.0);
+
+    let exact = policy.clone().jitter(Jitter::None);
+    assert_eq!(exact.delay(1), Duration::from_millis(100));
+    assert_eq!(exact.delay(2), Duration::from_millis(200));
+    assert_eq!(exact.delay(3), Duration::from_millis(300));
+    assert_eq!(exact.delay(u32::MAX), Duration::from_millis(300));
+
+    for _ in 0..100 {
+        assert!(policy.clone().jitter(Jitter::Full).delay(2) <= Duration::from_millis(200));
+        let equal = policy.clone().jitter(Jitter::Equal).delay(2);
+        assert!(equal >= Duration::from_millis(100) && equal <= Duration::from_millis(200));
+    }
+}
+
+#[tokio::test]
+async fn test_retries_stop_at_the_budget_and_on_cancellation() {
+    let metrics = AppMetrics::new().unwrap();
+    let budget = Arc::new(RetryBudget::new(0.0, 1));
+    let policy = RetryPolicy::new("test")
+        .max_attempts(3)
+        .initial_delay(Duration::from_millis(1))
+        .budget(budget.clone())
+        .with_metrics(metrics.clone());
+    let retries = |outcome: &str| metrics.retries_total.with_label_values(&["test", outcome]).get();
+
+    // The budget's single token buys one retry, then the call gives up
+    let calls = AtomicU32::new(0);
+    let result: Result<(), u32> = policy
+        .run(
+            |attempt| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move { Err(attempt) }
+            },
+            |_| true,
+        )
+        .await;
+    assert_eq!(result, Err(2));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(retries("retried"), 1.0);
+    assert_eq!(retries("budget_exhausted"), 1.0);
+    assert!(budget.available() < 1.0);
+
+    // Errors the caller doesn't retry are returned straight away
+    let result: Result<(), &str> = RetryPolicy::new("test")
+        .run(|_| async { Err("rejected") }, |_| false)
+        .await;
+    assert_eq!(result, Err("rejected"));
+
+    // Cancelling while waiting to retry returns the last error
+    let policy = RetryPolicy::new("test")
+        .initial_delay(Duration::from_secs(60))
+        .jitter(Jitter::None)
+        .with_metrics(metrics.clone());
+    let result: Result<(), u32> = tokio::time::timeout(
+        Duration::from_secs(5),
+        policy.run_until(
+            tokio::time::sleep(Duration::from_millis(20)),
+            |attempt| async move { Err(attempt) },
+            |_| true,
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, Err(1));
+    assert_eq!(retries("cancelled"), 1.0);
+}
+
+#[tokio::test]
+async fn test_openfga_retries_failed_checks_but_not_writes() {
+    let checks = Arc::new(AtomicU32::new(0));
+    let writes = Arc::new(AtomicU32::new(0));
+    let app = Router::new()
+        .route(
+            "/stores/{store}/check",
+            post({
+                let checks = checks.clone();
+                move || async move {
+                    // The first check fails on OpenFGA's side
+                    if checks.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(Json(json!({ "allowed": true })))
+                }
+            }),
+        )
+        .route(
+            "/stores/{store}/write",
+            post({
+                let writes = writes.clone();
+                move || async move {
+                    writes.fetch_add(1, Ordering::SeqCst);
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "store".to_string();
+    config.auth.openfga.cache_enabled = false;
+    config.auth.openfga.retry.initial_delay_ms = 1;
+    let metrics = AppMetrics::new().unwrap();
+    let service = OpenFgaService::new(&config).await.unwrap().with_metrics(metrics.clone());
+
+    let result = service
+        .check_permission(Uuid::new_v4(), "viewer", "document", "1")
+        .await
+        .unwrap();
+    assert!(result.allowed);
+    assert_eq!(checks.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        metrics.retries_total.with_label_values(&["openfga", "retried"]).get(),
+        1.0
+    );
+
+    // A write that reached OpenFGA may have been applied, so it isn't resent
+    let result = service
+        .write_relationship(Uuid::new_v4(), "viewer", "document", "1")
+        .await;
+    assert!(result.is_err());
+    assert_eq!(writes.load(Ordering::SeqCst), 1);
+}
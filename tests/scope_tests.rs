@@ -0,0 +1,112 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use reprime_backend::{
+    auth::{
+        jwt::JwtService,
+        middleware::require_scope,
+        models::{scopes, AuthContext, SubjectType},
+    },
+    config::Config,
+    errors::AppError,
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn jwt_service() -> JwtService {
+    JwtService::new(&Config::default()).expect("jwt service")
+}
+
+fn scoped_token(jwt_service: &JwtService, granted: &[&str]) -> String {
+    jwt_service
+        .generate_scoped_token(
+            Uuid::new_v4(),
+            "integration@example.com".to_string(),
+            "integration".to_string(),
+            vec!["user".to_string()],
+            granted,
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_scope_claim_round_trips_into_the_auth_context() {
+    let jwt_service = jwt_service();
+
+    let token = scoped_token(&jwt_service, &[scopes::USERS_READ, "reports:read"]);
+    assert_eq!(
+        jwt_service.validate_token(&token).unwrap().scope.as_deref(),
+        Some("users:read reports:read")
+    );
+    let context = jwt_service.extract_auth_context(&token).unwrap();
+    assert!(context.has_scope(scopes::USERS_READ));
+    assert!(!context.has_scope(scopes::USERS_WRITE));
+
+    // No claim at all: unrestricted
+    let token = jwt_service
+        .generate_token(Uuid::new_v4(), "a@example.com".to_string(), "a".to_string(), vec![])
+        .unwrap();
+    assert!(jwt_service.validate_token(&token).unwrap().scope.is_none());
+    assert!(jwt_service.extract_auth_context(&token).unwrap().has_scope(scopes::USERS_WRITE));
+}
+
+#[test]
+fn test_scopes_with_whitespace_are_rejected() {
+    let jwt_service = jwt_service();
+    for bad in [&["users:read users:write"][..], &[""]] {
+        let result = jwt_service.generate_scoped_token(
+            Uuid::new_v4(),
+            "a@example.com".to_string(),
+            "a".to_string(),
+            vec![],
+            bad,
+        );
+        assert!(matches!(result, Err(AppError::Validation(_))), "{:?}", bad);
+    }
+}
+
+#[tokio::test]
+async fn test_require_scope_only_restricts_scoped_tokens() {
+    let jwt_service = jwt_service();
+    let app = Router::new()
+        .route(
+            "/users",
+            get(|| async { "users" }).layer(middleware::from_fn(require_scope(scopes::USERS_READ))),
+        )
+        .route(
+            "/users/{id}",
+            get(|| async { "user" })
+                .delete(|| async { "deleted" })
+                .layer(middleware::from_fn(require_scope(scopes::USERS_WRITE))),
+        );
+    let call = |method: &'static str, uri: &'static str, context: Option<AuthContext>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            if let Some(context) = context {
+                request.extensions_mut().insert(context);
+            }
+            app.oneshot(request).await.unwrap().status()
+        }
+    };
+
+    let read_only = jwt_service
+        .extract_auth_context(&scoped_token(&jwt_service, &[scopes::USERS_READ]))
+        .unwrap();
+    assert_eq!(call("GET", "/users", Some(read_only.clone())).await, StatusCode::OK);
+    assert_eq!(call("DELETE", "/users/1", Some(read_only)).await, StatusCode::FORBIDDEN);
+
+    let unscoped = AuthContext {
+        scopes: None,
+        subject_type: SubjectType::User,
+        ..jwt_service
+            .extract_auth_context(&scoped_token(&jwt_service, &[]))
+            .unwrap()
+    };
+    assert_eq!(call("DELETE", "/users/1", Some(unscoped)).await, StatusCode::OK);
+    assert_eq!(call("GET", "/users", None).await, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,102 @@
+use reprime_backend::{
+    config::Config,
+    models::OrganizationRole,
+    services::seed::{Seed, SeedTuple},
+};
+
+fn seed(toml: &str) -> Seed {
+    let path = std::env::temp_dir().join(format!("seed-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(&path, toml).unwrap();
+    let seed = Seed::from_file(&path);
+    let _ = std::fs::remove_file(path);
+    seed.unwrap()
+}
+
+const MINIMAL: &str = r#"
+[[users]]
+email = "owner@example.com"
+username = "owner"
+password = "owner-password"
+roles = ["user", "admin"]
+
+[[organizations]]
+id = "acme"
+name = "Acme"
+members = [{ user = "owner@example.com", role = "admin" }]
+"#;
+
+#[test]
+fn test_demo_seed_is_valid() {
+    let config = Config::default();
+    assert!(!config.seed.enabled);
+
+    let demo = Seed::from_file(&config.seed.file).unwrap();
+    demo.check().unwrap();
+    assert!(demo
+        .users
+        .iter()
+        .any(|user| user.roles.contains(&"admin".to_string())));
+    // The documents the OpenAPI examples use
+    for document in ["document:roadmap", "document:spec"] {
+        assert!(demo.tuples.iter().any(|tuple| tuple.object == document), "{}", document);
+    }
+}
+
+#[test]
+fn test_seed_file_is_parsed() {
+    let seed = seed(MINIMAL);
+    assert!(seed.check().is_ok());
+    assert_eq!(seed.users[0].roles, vec!["user", "admin"]);
+    assert_eq!(seed.organizations[0].members[0].role, OrganizationRole::Admin);
+    assert!(seed.tuples.is_empty());
+
+    assert!(Seed::from_file("config/seeds/missing.toml").is_err());
+}
+
+#[test]
+fn test_invalid_seeds_are_rejected() {
+    let base = seed(MINIMAL);
+
+    let mut unknown_role = base.clone();
+    unknown_role.users[0].roles.push("superuser".to_string());
+    assert!(unknown_role.check().is_err());
+
+    let mut short_password = base.clone();
+    short_password.users[0].password = "secret".to_string();
+    assert!(short_password.check().is_err());
+
+    let mut no_admin = base.clone();
+    no_admin.organizations[0].members[0].role = OrganizationRole::Member;
+    assert!(no_admin.check().is_err());
+
+    let mut unknown_member = base.clone();
+    unknown_member.organizations[0].members[0].user = "nobody@example.com".to_string();
+    assert!(unknown_member.check().is_err());
+}
+
+#[test]
+fn test_seed_tuples_are_checked() {
+    let base = seed(MINIMAL);
+    let with_tuple = |user: &str, relation: &str, object: &str| {
+        let mut seed = base.clone();
+        seed.tuples.push(SeedTuple {
+            user: user.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+        });
+        seed.check()
+    };
+
+    assert!(with_tuple("user:owner@example.com", "owner", "document:roadmap").is_ok());
+    assert!(with_tuple("organization:acme", "organization", "project:website").is_ok());
+    assert!(with_tuple("organization:acme#member", "viewer", "project:website").is_ok());
+
+    // Only users from the seed can be named by email
+    assert!(with_tuple("user:nobody@example.com", "owner", "document:roadmap").is_err());
+    // Relations and types must exist in the model
+    assert!(with_tuple("user:owner@example.com", "reader", "document:roadmap").is_err());
+    assert!(with_tuple("user:owner@example.com", "owner", "widget:roadmap").is_err());
+    assert!(with_tuple("user:owner@example.com", "owner", "document:").is_err());
+    assert!(with_tuple("acme", "organization", "project:website").is_err());
+    assert!(with_tuple("team:acme", "organization", "project:website").is_err());
+}
@@ -0,0 +1,119 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    middleware::{self, Next},
+    routing::get,
+    Router,
+};
+use reprime_backend::{
+    auth::{
+        authorizer::{Authorizer, StaticAuthorizer},
+        jwt::JwtService,
+        middleware::require_permission,
+        models::{object_types, relations, roles, CreateServiceAccountRequest, Subject, SubjectType},
+    },
+    config::Config,
+    errors::AppError,
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[test]
+fn test_service_token_carries_the_service_subject() {
+    let mut config = Config::default();
+    config.auth.service_accounts.token_ttl_seconds = 300;
+    let jwt_service = JwtService::new(&config).unwrap();
+    let account_id = Uuid::new_v4();
+
+    let token = jwt_service
+        .generate_service_token(
+            account_id,
+            "billing-sync".to_string(),
+            vec![roles::USER.to_string()],
+            0,
+        )
+        .unwrap();
+    let claims = jwt_service.validate_token(&token).unwrap();
+    assert_eq!(claims.sub_type.as_deref(), Some("service"));
+    assert_eq!(claims.exp - claims.iat, 300);
+    assert_eq!(jwt_service.service_expires_in(), 300);
+
+    let context = jwt_service.extract_auth_context(&token).unwrap();
+    assert_eq!(context.subject_type, SubjectType::Service);
+    assert_eq!(context.subject().to_string(), format!("service:{}", account_id));
+    assert_eq!(context.roles, vec![roles::USER.to_string()]);
+
+    // User tokens don't carry the claim
+    let token = jwt_service
+        .generate_token(Uuid::new_v4(), "a@example.com".to_string(), "a".to_string(), vec![])
+        .unwrap();
+    assert!(jwt_service.validate_token(&token).unwrap().sub_type.is_none());
+    assert_eq!(
+        jwt_service.extract_auth_context(&token).unwrap().subject_type,
+        SubjectType::User
+    );
+}
+
+#[test]
+fn test_service_account_requests_are_validated() {
+    let request = |name: &str, roles: &[&str]| CreateServiceAccountRequest {
+        name: name.to_string(),
+        roles: roles.iter().map(|role| role.to_string()).collect(),
+    };
+
+    assert!(request("billing-sync", &[roles::USER, roles::MODERATOR]).validate().is_ok());
+    assert!(request("billing-sync", &[]).validate().is_ok());
+    assert!(matches!(
+        request("  ", &[roles::USER]).validate(),
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        request("billing-sync", &["superuser"]).validate(),
+        Err(AppError::Validation(message)) if message.contains("superuser")
+    ));
+}
+
+#[tokio::test]
+async fn test_permission_checks_use_the_service_subject() {
+    let jwt_service = JwtService::new(&Config::default()).unwrap();
+    let account_id = Uuid::new_v4();
+    let token = jwt_service
+        .generate_service_token(account_id, "billing-sync".to_string(), vec![], 0)
+        .unwrap();
+    let context = jwt_service.extract_auth_context(&token).unwrap();
+
+    let app = |authorizer: StaticAuthorizer| {
+        let context = context.clone();
+        Router::new()
+            .route("/documents/{id}", get(|| async { "document" }))
+            .layer(middleware::from_fn_with_state(
+                Arc::new(authorizer) as Arc<dyn Authorizer>,
+                require_permission(relations::VIEWER, object_types::DOCUMENT, "id"),
+            ))
+            .layer(middleware::from_fn(move |mut request: Request, next: Next| {
+                request.extensions_mut().insert(context.clone());
+                next.run(request)
+            }))
+    };
+    let status = |app: Router| async move {
+        app.oneshot(Request::builder().uri("/documents/1").body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    };
+
+    let granted = StaticAuthorizer::new().with_tuple(
+        Subject::service(account_id),
+        relations::VIEWER,
+        object_types::DOCUMENT,
+        "1",
+    );
+    assert_eq!(status(app(granted)).await, StatusCode::OK);
+
+    // A user with the same id is a different subject
+    let user_only =
+        StaticAuthorizer::new().with_tuple(account_id, relations::VIEWER, object_types::DOCUMENT, "1");
+    assert_eq!(status(app(user_only)).await, StatusCode::FORBIDDEN);
+}
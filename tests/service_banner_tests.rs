@@ -0,0 +1,94 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use chrono::{Duration, Utc};
+use reprime_backend::{
+    middleware::service_notice_middleware,
+    services::banner::{BannerSeverity, BannerService, SetBannerRequest, SERVICE_NOTICE_HEADER},
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn request(message: &str) -> SetBannerRequest {
+    SetBannerRequest {
+        severity: BannerSeverity::Warning,
+        message: message.to_string(),
+        expires_at: None,
+    }
+}
+
+async fn notice(banner: Arc<BannerService>) -> Option<String> {
+    let app = Router::new()
+        .route("/ping", get(|| async { "pong" }))
+        .layer(middleware::from_fn_with_state(banner, service_notice_middleware));
+    let response = app
+        .oneshot(Request::builder().uri("/ping").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .headers()
+        .get(SERVICE_NOTICE_HEADER)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_banner_is_attached_as_notice_header() {
+    let banner = Arc::new(BannerService::new());
+    assert_eq!(notice(banner.clone()).await, None);
+
+    let set = banner.set(request("  Maintenance at 02:00 UTC ")).unwrap();
+    assert_eq!(set.message, "Maintenance at 02:00 UTC");
+    assert_eq!(
+        notice(banner.clone()).await.as_deref(),
+        Some("warning; Maintenance at 02:00 UTC")
+    );
+
+    banner.set(request("Wartung um 02:00 — bitte speichern")).unwrap();
+    assert_eq!(
+        notice(banner.clone()).await.as_deref(),
+        Some("warning; see /api/v1/banner")
+    );
+
+    banner.clear();
+    assert!(banner.current().is_none());
+    assert_eq!(notice(banner).await, None);
+}
+
+#[test]
+fn test_invalid_banners_are_rejected() {
+    let banner = BannerService::new();
+
+    assert!(banner.set(request(" ")).is_err());
+    assert!(banner.set(request("line one\nline two")).is_err());
+    assert!(banner
+        .set(request(&"x".repeat(BannerService::MAX_MESSAGE_LENGTH + 1)))
+        .is_err());
+    assert!(banner
+        .set(SetBannerRequest {
+            expires_at: Some(Utc::now() - Duration::minutes(1)),
+            ..request("Too late")
+        })
+        .is_err());
+    assert!(banner.current().is_none());
+}
+
+#[test]
+fn test_banner_expires() {
+    let banner = BannerService::new();
+    banner
+        .set(SetBannerRequest {
+            expires_at: Some(Utc::now() + Duration::milliseconds(20)),
+            ..request("Brief notice")
+        })
+        .unwrap();
+    assert!(banner.current().is_some());
+
+    std::thread::sleep(std::time::Duration::from_millis(40));
+    assert!(banner.current().is_none());
+    assert!(banner.notice_header().is_none());
+}
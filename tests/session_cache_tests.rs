@@ -0,0 +1,57 @@
+use reprime_backend::auth::cache::SessionCache;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_session_cache_hits_and_expiry() {
+    let cache = SessionCache::new(Duration::from_millis(50), 10);
+    let (session_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+    assert_eq!(cache.get(session_id).await, None);
+
+    cache.set(session_id, user_id, true).await;
+    assert_eq!(cache.get(session_id).await, Some(true));
+
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert_eq!(cache.get(session_id).await, None);
+}
+
+#[tokio::test]
+async fn test_session_cache_invalidation() {
+    let cache = SessionCache::new(Duration::from_secs(60), 10);
+    let user_id = Uuid::new_v4();
+    let (first, second, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+    cache.set(first, user_id, true).await;
+    cache.set(second, user_id, true).await;
+    cache.set(other, Uuid::new_v4(), true).await;
+
+    cache.invalidate(first).await;
+    assert_eq!(cache.get(first).await, None);
+    assert_eq!(cache.get(second).await, Some(true));
+
+    cache.invalidate_user(user_id).await;
+    assert_eq!(cache.get(second).await, None);
+    assert_eq!(cache.get(other).await, Some(true));
+}
+
+#[tokio::test]
+async fn test_session_cache_respects_capacity_and_zero_ttl() {
+    let cache = SessionCache::new(Duration::from_secs(60), 2);
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        cache.set(*id, Uuid::new_v4(), true).await;
+    }
+    let mut cached = 0;
+    for id in &ids {
+        if cache.get(*id).await.is_some() {
+            cached += 1;
+        }
+    }
+    assert_eq!(cached, 2);
+
+    let disabled = SessionCache::new(Duration::ZERO, 10);
+    let session_id = Uuid::new_v4();
+    disabled.set(session_id, Uuid::new_v4(), true).await;
+    assert_eq!(disabled.get(session_id).await, None);
+}
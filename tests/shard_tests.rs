@@ -0,0 +1,62 @@
+use reprime_backend::database::{InstrumentedDatabase, ShardKey, ShardRouter};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn lazy_database(name: &str) -> Arc<InstrumentedDatabase> {
+    let pool = PgPoolOptions::new()
+        .connect_lazy(&format!("postgresql://localhost/{}", name))
+        .expect("valid database url");
+    Arc::new(InstrumentedDatabase::new(pool, None))
+}
+
+fn router(shards: usize) -> ShardRouter {
+    let additional = (1..shards).map(|i| lazy_database(&format!("shard{}", i))).collect();
+    ShardRouter::new(lazy_database("primary"), additional)
+}
+
+#[tokio::test]
+async fn test_single_shard_resolves_to_primary() {
+    let router = router(1);
+
+    assert!(!router.is_sharded());
+    for _ in 0..100 {
+        assert_eq!(router.shard_index(ShardKey::User(Uuid::new_v4())), 0);
+    }
+    assert_eq!(router.shard_index(ShardKey::Tenant("acme")), 0);
+}
+
+#[tokio::test]
+async fn test_shard_placement_is_stable_and_spread() {
+    let router = router(4);
+    let mut counts = [0usize; 4];
+
+    for _ in 0..4000 {
+        let user_id = Uuid::new_v4();
+        let index = router.shard_index(ShardKey::User(user_id));
+        assert_eq!(index, router.shard_index(ShardKey::User(user_id)));
+        counts[index] += 1;
+    }
+
+    // Every shard should receive a reasonable share of the keys
+    assert!(counts.iter().all(|&count| count > 700), "{:?}", counts);
+}
+
+#[tokio::test]
+async fn test_adding_shard_moves_few_keys() {
+    let before = router(3);
+    let after = router(4);
+
+    let keys: Vec<Uuid> = (0..3000).map(|_| Uuid::new_v4()).collect();
+    let moved = keys
+        .iter()
+        .filter(|id| before.shard_index(ShardKey::User(**id)) != after.shard_index(ShardKey::User(**id)))
+        .count();
+
+    // Roughly 1/4 of the keys should move to the new shard, and only to it
+    assert!(moved < 1000, "moved {}", moved);
+    assert!(keys
+        .iter()
+        .filter(|id| before.shard_index(ShardKey::User(**id)) != after.shard_index(ShardKey::User(**id)))
+        .all(|id| after.shard_index(ShardKey::User(*id)) == 3));
+}
@@ -0,0 +1,78 @@
+use reprime_backend::config::SlowQueryConfig;
+use reprime_backend::database::{InstrumentedDatabase, SlowQueryLog};
+use reprime_backend::live_tail::LiveTail;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+#[test]
+fn test_slow_query_threshold() {
+    let log = SlowQueryLog::new(&SlowQueryConfig {
+        threshold_ms: 200,
+        explain: false,
+        explain_per_minute: 10,
+    });
+
+    assert!(!log.is_slow(Duration::from_millis(199)));
+    assert!(log.is_slow(Duration::from_millis(200)));
+}
+
+#[test]
+fn test_explain_disabled_by_default() {
+    let log = SlowQueryLog::new(&SlowQueryConfig::default());
+    assert!(!log.try_acquire_explain());
+}
+
+#[test]
+fn test_explain_is_rate_limited() {
+    let log = SlowQueryLog::new(&SlowQueryConfig {
+        threshold_ms: 200,
+        explain: true,
+        explain_per_minute: 3,
+    });
+
+    let captured = (0..10).filter(|_| log.try_acquire_explain()).count();
+    assert_eq!(captured, 3);
+}
+
+#[tokio::test]
+async fn test_slow_parameterized_statements_have_their_plan_captured() {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return;
+    };
+    // A current-thread runtime, so the background EXPLAIN logs here too
+    let tail = Arc::new(LiveTail::new(50));
+    let _guard = tracing::subscriber::set_default(Registry::default().with(tail.layer()));
+
+    let pool = PgPoolOptions::new().connect(&url).await.expect("test database");
+    let log = Arc::new(SlowQueryLog::new(&SlowQueryConfig {
+        threshold_ms: 0,
+        explain: true,
+        explain_per_minute: 10,
+    }));
+    let db = InstrumentedDatabase::new(pool, None).with_slow_query_log(log);
+
+    // Through the pool every repository uses, with a bound parameter
+    let count: i64 = sqlx::query_scalar("SELECT count(*) FROM pg_class WHERE relname = $1")
+        .bind("pg_class")
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let mut plan = None;
+    for _ in 0..50 {
+        plan = tail.recent().into_iter().find_map(|event| {
+            (event.message == "Slow database query").then(|| event.fields["plan"].clone())
+        });
+        if plan.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let plan = plan.expect("slow query logged").as_str().unwrap().to_string();
+    assert!(plan.contains("\"Plan\""), "{}", plan);
+    assert!(plan.contains("pg_class"), "{}", plan);
+}
@@ -0,0 +1,61 @@
+use reprime_backend::models::{EmailTemplate, TenantSettings};
+use serde_json::json;
+
+fn settings(value: serde_json::Value) -> TenantSettings {
+    serde_json::from_value(value).expect("valid settings document")
+}
+
+#[test]
+fn test_tenant_settings_validation() {
+    let valid = settings(json!({
+        "branding": { "display_name": "Acme", "primary_color": "#1a73e8" },
+        "password_policy": { "min_length": 12 },
+        "session": { "refresh_token_days": 7 },
+        "email_templates": {
+            "password_reset": { "subject": "Reset", "text": "Go to {url}" }
+        }
+    }));
+    assert!(valid.validate().is_ok());
+
+    assert!(settings(json!({ "branding": { "primary_color": "blue" } })).validate().is_err());
+    assert!(settings(json!({ "password_policy": { "min_length": 6 } })).validate().is_err());
+    assert!(settings(json!({ "session": { "refresh_token_days": 0 } })).validate().is_err());
+    assert!(settings(json!({
+        "email_templates": { "password_reset": { "subject": "Reset", "text": "no link" } }
+    }))
+    .validate()
+    .is_err());
+
+    // Unknown sections and fields are rejected outright
+    assert!(serde_json::from_value::<TenantSettings>(json!({ "theme": {} })).is_err());
+    assert!(
+        serde_json::from_value::<TenantSettings>(json!({ "session": { "days": 7 } })).is_err()
+    );
+}
+
+#[test]
+fn test_tenant_settings_entries_roundtrip() {
+    let current = settings(json!({
+        "password_policy": { "min_length": 10 },
+        "session": { "refresh_token_days": 30 }
+    }));
+
+    let entries = current.to_entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    let restored = TenantSettings::from_entries(entries).unwrap();
+    assert_eq!(restored.password_policy.unwrap().min_length, 10);
+    assert_eq!(restored.session.unwrap().refresh_token_days, 30);
+    assert!(restored.branding.is_none());
+}
+
+#[test]
+fn test_email_template_render() {
+    let template = EmailTemplate {
+        subject: "Reset your password, {username}".to_string(),
+        text: "Valid for {minutes} minutes: {url}".to_string(),
+    };
+
+    let (subject, text) = template.render("johndoe", "https://app/reset?token=abc", 30);
+    assert_eq!(subject, "Reset your password, johndoe");
+    assert_eq!(text, "Valid for 30 minutes: https://app/reset?token=abc");
+}
@@ -0,0 +1,105 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use reprime_backend::{
+    auth::{openfga::OpenFgaService, stores},
+    config::{Config, OpenFgaStoreConfig},
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// OpenFGA with stores `shared` and `acme-store`, recording which store
+/// each check went to
+async fn openfga(checked: Arc<Mutex<Vec<String>>>) -> Config {
+    let known = |store: &str| store == "shared" || store == "acme-store";
+    let app = Router::new()
+        .route(
+            "/stores/{store}/check",
+            post(move |Path(store): Path<String>| async move {
+                checked.lock().unwrap().push(store.clone());
+                Json(json!({ "allowed": store == "acme-store" }))
+            }),
+        )
+        .route(
+            "/stores/{store}/authorization-models",
+            get(move |Path(store): Path<String>| async move {
+                if !known(&store) {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(json!({ "authorization_models": [] })))
+            }),
+        )
+        .route("/healthz", get(|| async { Json(json!({ "status": "SERVING" })) }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = Config::default();
+    config.auth.openfga.endpoint = format!("http://{}", addr);
+    config.auth.openfga.store_id = "shared".to_string();
+    config.auth.openfga.tenant_stores.insert(
+        "acme".to_string(),
+        OpenFgaStoreConfig {
+            store_id: "acme-store".to_string(),
+            auth_model_id: None,
+        },
+    );
+    config
+}
+
+#[tokio::test]
+async fn test_checks_go_to_the_tenants_store_with_its_own_cache() {
+    let checked = Arc::new(Mutex::new(Vec::new()));
+    let service = OpenFgaService::new(&openfga(checked.clone()).await).await.unwrap();
+    let user_id = Uuid::new_v4();
+    let check = || service.check_permission(user_id, "viewer", "document", "1");
+
+    assert!(stores::scope("acme", check()).await.unwrap().allowed);
+    // Tenants without a dedicated store, and calls outside a request, use the
+    // shared store; acme's cached answer doesn't leak into it
+    assert!(!stores::scope("globex", check()).await.unwrap().allowed);
+    assert!(!check().await.unwrap().allowed);
+    // Cached per store from here on
+    assert!(stores::scope("acme", check()).await.unwrap().allowed);
+
+    assert_eq!(
+        *checked.lock().unwrap(),
+        vec!["acme-store".to_string(), "shared".to_string()]
+    );
+    assert_eq!(service.dedicated_tenants(), vec!["acme".to_string()]);
+}
+
+#[tokio::test]
+async fn test_store_health_reports_each_store() {
+    let mut config = openfga(Arc::new(Mutex::new(Vec::new()))).await;
+    let service = OpenFgaService::new(&config).await.unwrap();
+    service.warm_up().await.unwrap();
+    let health = service.store_health().await;
+    assert_eq!(health.len(), 2);
+    assert!(health.iter().all(|(_, result)| result.is_ok()));
+
+    // A tenant store that doesn't exist fails warmup and its health check
+    config.auth.openfga.tenant_stores.insert(
+        "initech".to_string(),
+        OpenFgaStoreConfig {
+            store_id: "missing".to_string(),
+            auth_model_id: None,
+        },
+    );
+    let service = OpenFgaService::new(&config).await.unwrap();
+    assert!(service.warm_up().await.is_err());
+    let unhealthy: Vec<_> = service
+        .store_health()
+        .await
+        .into_iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(tenant, _)| tenant)
+        .collect();
+    assert_eq!(unhealthy, vec![Some("initech".to_string())]);
+}
@@ -0,0 +1,48 @@
+use reprime_backend::models::{Currency, Decimal, Money};
+use std::str::FromStr;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[test]
+fn test_money_serializes_amount_as_string() {
+    let price = Money::new(dec("19.99"), Currency::Usd);
+
+    let json = serde_json::to_value(price).unwrap();
+    assert_eq!(json, serde_json::json!({ "amount": "19.99", "currency": "USD" }));
+
+    let parsed: Money = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, price);
+}
+
+#[test]
+fn test_money_rounds_to_minor_units() {
+    assert_eq!(Money::new(dec("0.125"), Currency::Eur).amount, dec("0.13"));
+    assert_eq!(Money::new(dec("-0.125"), Currency::Eur).amount, dec("-0.13"));
+    assert_eq!(Money::new(dec("1500.5"), Currency::Jpy).amount, dec("1501"));
+
+    // 0.1 + 0.2 is exact
+    let sum = Money::new(dec("0.1"), Currency::Usd)
+        .checked_add(Money::new(dec("0.2"), Currency::Usd))
+        .unwrap();
+    assert_eq!(sum.amount, dec("0.3"));
+}
+
+#[test]
+fn test_mixed_currencies_are_rejected() {
+    let usd = Money::zero(Currency::Usd);
+    let eur = Money::zero(Currency::Eur);
+    assert!(usd.checked_add(eur).is_err());
+    assert!(usd.checked_sub(eur).is_err());
+}
+
+#[test]
+fn test_enum_labels_round_trip() {
+    for currency in Currency::ALL {
+        assert_eq!(currency.as_str().parse::<Currency>().unwrap(), *currency);
+        assert_eq!(currency.to_string(), currency.as_str());
+    }
+    assert!("usd".parse::<Currency>().is_err());
+    assert!(serde_json::from_str::<Currency>("\"XXX\"").is_err());
+}
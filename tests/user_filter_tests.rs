@@ -0,0 +1,133 @@
+use axum::{extract::Query, http::Uri};
+use reprime_backend::handlers::user::UserListParams;
+use reprime_backend::models::{
+    PaginationParams, SortOrder, User, UserCursor, UserFilter, UserSortField, UserStatus,
+};
+use reprime_backend::utils::CursorSigner;
+use std::cmp::Ordering;
+use reprime_backend::utils::Validate;
+
+fn params(query: &str) -> UserListParams {
+    let uri: Uri = format!("/api/v1/users?{}", query).parse().unwrap();
+    Query::try_from_uri(&uri).unwrap().0
+}
+
+#[test]
+fn test_filter_from_params() {
+    let filter = params("q=%20Jane%20&role=admin,,%20moderator&created_after=2024-01-01T00:00:00Z")
+        .filter();
+
+    assert_eq!(filter.q.as_deref(), Some("Jane"));
+    assert_eq!(filter.roles, vec!["admin", "moderator"]);
+    assert!(filter.created_after.is_some());
+    assert!(filter.created_before.is_none());
+
+    let empty = params("q=%20%20&role=&status=").filter();
+    assert!(empty.q.is_none());
+    assert!(empty.roles.is_empty());
+    assert!(empty.statuses.is_empty());
+
+    let statuses = params("status=suspended,%20banned").filter().statuses;
+    assert_eq!(statuses, vec![UserStatus::Suspended, UserStatus::Banned]);
+}
+
+#[test]
+fn test_search_pattern_escapes_wildcards() {
+    let filter = UserFilter {
+        q: Some(r"50%_off\".to_string()),
+        ..UserFilter::default()
+    };
+
+    assert_eq!(filter.search_pattern().as_deref(), Some(r"%50\%\_off\\%"));
+    assert!(UserFilter::default().search_pattern().is_none());
+}
+
+#[test]
+fn test_invalid_filters_are_rejected() {
+    assert!(params("q=jane&role=admin").validate().is_ok());
+    assert!(params(&format!("q={}", "a".repeat(101))).validate().is_err());
+    assert!(params("role=a,b,c,d,e,f,g,h,i,j,k").validate().is_err());
+    assert!(params("status=active,suspended").validate().is_ok());
+    assert!(params("status=active,frozen").validate().is_err());
+    assert!(params("created_after=2024-02-01T00:00:00Z&created_before=2024-01-01T00:00:00Z")
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_sort_params_are_whitelisted() {
+    let uri: Uri = "/api/v1/users?sort_by=email&order=asc".parse().unwrap();
+    let pagination: PaginationParams = Query::try_from_uri(&uri).unwrap().0;
+    assert_eq!(pagination.user_sort().unwrap(), UserSortField::Email);
+    assert_eq!(pagination.order(), SortOrder::Asc);
+
+    let defaults = PaginationParams::default();
+    assert_eq!(defaults.user_sort().unwrap(), UserSortField::CreatedAt);
+    assert_eq!(defaults.order(), SortOrder::Desc);
+
+    let injected = PaginationParams {
+        sort_by: Some("email; DROP TABLE users".to_string()),
+        ..PaginationParams::default()
+    };
+    assert!(injected.user_sort().is_err());
+
+    let uri: Uri = "/api/v1/users?order=sideways".parse().unwrap();
+    assert!(Query::<PaginationParams>::try_from_uri(&uri).is_err());
+}
+
+#[test]
+fn test_sort_compare_breaks_ties_by_id() {
+    let now = chrono::Utc::now();
+    let user = |id: u128, email: &str| User {
+        id: uuid::Uuid::from_u128(id),
+        email: email.to_string(),
+        username: email.to_string(),
+        avatar_key: None,
+        status: UserStatus::Active,
+        created_at: now,
+        updated_at: now,
+    };
+    let (a, b) = (user(1, "b@example.com"), user(2, "a@example.com"));
+
+    assert_eq!(UserSortField::Email.compare(&a, &b), Ordering::Greater);
+    assert_eq!(UserSortField::CreatedAt.compare(&a, &b), Ordering::Less);
+}
+
+#[test]
+fn test_cursor_pagination_requires_created_at_order() {
+    let cursor = Some("opaque".to_string());
+    let with_cursor = PaginationParams {
+        cursor: cursor.clone(),
+        ..PaginationParams::default()
+    };
+    assert!(with_cursor.validate_user_cursor().is_ok());
+    assert!(PaginationParams::default().validate_user_cursor().is_ok());
+
+    let with_page = PaginationParams {
+        page: Some(3),
+        cursor: cursor.clone(),
+        ..PaginationParams::default()
+    };
+    assert!(with_page.validate_user_cursor().is_err());
+
+    let by_email = PaginationParams {
+        sort_by: Some("email".to_string()),
+        cursor,
+        ..PaginationParams::default()
+    };
+    assert!(by_email.validate_user_cursor().is_err());
+}
+
+#[test]
+fn test_user_cursor_roundtrip() {
+    let signer = CursorSigner::new("user-cursor-secret");
+    let position = UserCursor {
+        created_at: chrono::Utc::now(),
+        id: uuid::Uuid::from_u128(7),
+        page: 4,
+    };
+    let cursor = signer.encode(&position, Some("caller"), None).unwrap();
+
+    assert_eq!(signer.decode::<UserCursor>(&cursor, Some("caller")).unwrap(), position);
+    assert!(signer.decode::<UserCursor>(&cursor, Some("someone-else")).is_err());
+}
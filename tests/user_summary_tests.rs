@@ -0,0 +1,98 @@
+//! The denormalized `user_summaries` rows behind the admin user listing.
+
+use chrono::{Duration, Utc};
+use reprime_backend::{
+    config::Config,
+    database::InstrumentedDatabase,
+    models::{CreateUserRequest, PaginationParams, UpdateUserRequest, UserSummary},
+    repositories::Repositories,
+    utils::run_migrations,
+};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn repositories() -> Option<Repositories> {
+    let Ok(url) = std::env::var("APP_DATABASE_URL") else {
+        eprintln!("APP_DATABASE_URL not set, skipping");
+        return None;
+    };
+    let pool = PgPoolOptions::new().connect(&url).await.expect("test database");
+    run_migrations(&Config::default(), &pool, &[], None)
+        .await
+        .expect("migrations");
+    Some(Repositories::new(Arc::new(InstrumentedDatabase::new(pool, None))))
+}
+
+/// The user's summary from the first page of the listing, newest first
+async fn summary(repositories: &Repositories, user_id: Uuid) -> Option<UserSummary> {
+    let (summaries, total) = repositories
+        .user
+        .find_summaries(PaginationParams {
+            page: Some(1),
+            per_page: Some(100),
+            ..PaginationParams::default()
+        })
+        .await
+        .unwrap();
+    assert!(total >= summaries.len() as i64);
+    assert!(summaries.windows(2).all(|pair| pair[0].created_at >= pair[1].created_at));
+    summaries.into_iter().find(|summary| summary.user_id == user_id)
+}
+
+#[tokio::test]
+async fn test_summaries_follow_users_roles_and_logins() {
+    let Some(repositories) = repositories().await else {
+        return;
+    };
+    let run = Uuid::new_v4().simple().to_string();
+    let user = repositories
+        .user
+        .create(CreateUserRequest {
+            email: format!("summary-{}@example.com", run),
+            username: format!("summary-{}", &run[..12]),
+        })
+        .await
+        .unwrap();
+
+    // Written by the trigger on insert
+    let created = summary(&repositories, user.id).await.expect("summary of a new user");
+    assert_eq!(created.email, user.email);
+    assert_eq!(created.username, user.username);
+    assert!(created.roles.is_empty());
+    assert!(created.last_login_at.is_none());
+
+    // ... and on update
+    let email = format!("summary-renamed-{}@example.com", run);
+    repositories
+        .user
+        .update(
+            user.id,
+            UpdateUserRequest {
+                email: Some(email.clone()),
+                username: None,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Roles and logins live with the auth tables, so the application
+    // writes them
+    let roles = vec!["admin".to_string(), "user".to_string()];
+    repositories.user.update_summary_roles(user.id, &roles).await.unwrap();
+    let login = Utc::now() - Duration::minutes(5);
+    repositories.user.record_login(user.id, login).await.unwrap();
+
+    let updated = summary(&repositories, user.id).await.unwrap();
+    assert_eq!(updated.email, email);
+    assert_eq!(updated.roles, roles);
+    assert_eq!(
+        updated.last_login_at.map(|at| at.timestamp_millis()),
+        Some(login.timestamp_millis())
+    );
+
+    // And goes with the user
+    repositories.user.delete(user.id).await.unwrap();
+    assert!(summary(&repositories, user.id).await.is_none());
+}
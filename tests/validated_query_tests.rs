@@ -0,0 +1,68 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use reprime_backend::{
+    models::PaginationParams,
+    utils::{validate_range, ValidatedQuery},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+fn app() -> Router {
+    Router::new().route(
+        "/items",
+        get(|ValidatedQuery(pagination): ValidatedQuery<PaginationParams>| async move {
+            Json(json!({ "page": pagination.page(), "per_page": pagination.per_page() }))
+        }),
+    )
+}
+
+async fn get_items(query: &str) -> (StatusCode, Value) {
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/items{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_valid_and_missing_parameters_pass() {
+    assert_eq!(get_items("").await, (StatusCode::OK, json!({ "page": 1, "per_page": 20 })));
+    assert_eq!(
+        get_items("?page=3&per_page=100").await,
+        (StatusCode::OK, json!({ "page": 3, "per_page": 100 }))
+    );
+}
+
+#[tokio::test]
+async fn test_junk_and_out_of_range_values_are_json_400s() {
+    for query in ["?page=abc", "?per_page=1.5", "?page=0", "?page=-1", "?per_page=0", "?per_page=500"] {
+        let (status, body) = get_items(query).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert!(body["error"].is_string(), "{}: {}", query, body);
+    }
+
+    let (_, body) = get_items("?per_page=500").await;
+    assert_eq!(body["error"], "per_page must be between 1 and 100, got 500");
+    let (_, body) = get_items("?page=abc").await;
+    assert!(body["error"].as_str().unwrap().contains("page"), "{}", body);
+}
+
+#[test]
+fn test_validate_range_bounds_are_inclusive() {
+    assert!(validate_range("limit", None::<i64>, 1, 100).is_ok());
+    assert!(validate_range("limit", Some(1), 1, 100).is_ok());
+    assert!(validate_range("limit", Some(100), 1, 100).is_ok());
+    assert!(validate_range("limit", Some(101), 1, 100).is_err());
+    assert!(validate_range("page_size", Some(0u32), 1, 100).is_err());
+}
@@ -0,0 +1,123 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use p256::pkcs8::EncodePublicKey;
+use reprime_backend::auth::webauthn::{
+    PasskeyLoginFinish, PasskeyRegistrationFinish, WebAuthnCredential, WebAuthnVerifier,
+    COSE_ALG_ES256,
+};
+use reprime_backend::config::WebAuthnConfig;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const ORIGIN: &str = "http://localhost:3000";
+const CHALLENGE: &str = "test-challenge";
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32].into()).expect("valid scalar")
+}
+
+fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+    serde_json::json!({ "type": kind, "challenge": challenge, "origin": origin })
+        .to_string()
+        .into_bytes()
+}
+
+fn authenticator_data(sign_count: u32, credential_id: Option<&[u8]>) -> Vec<u8> {
+    let mut data = Sha256::digest(b"localhost").to_vec();
+    let mut flags = 0x01;
+    if credential_id.is_some() {
+        flags |= 0x40;
+    }
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    if let Some(id) = credential_id {
+        data.extend_from_slice(&[0u8; 16]);
+        data.extend_from_slice(&(id.len() as u16).to_be_bytes());
+        data.extend_from_slice(id);
+    }
+    data
+}
+
+fn stored_credential(sign_count: i64) -> WebAuthnCredential {
+    WebAuthnCredential {
+        id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        credential_id: b"cred-1".to_vec(),
+        public_key: signing_key()
+            .verifying_key()
+            .to_public_key_der()
+            .unwrap()
+            .as_bytes()
+            .to_vec(),
+        algorithm: COSE_ALG_ES256,
+        sign_count,
+        name: None,
+        created_at: chrono::Utc::now(),
+        last_used_at: None,
+    }
+}
+
+fn assertion(challenge: &str, origin: &str, sign_count: u32) -> PasskeyLoginFinish {
+    let client_data_json = client_data("webauthn.get", challenge, origin);
+    let auth_data = authenticator_data(sign_count, None);
+
+    let mut signed = auth_data.clone();
+    signed.extend_from_slice(&Sha256::digest(&client_data_json));
+    let signature: Signature = signing_key().sign(&signed);
+
+    PasskeyLoginFinish {
+        challenge_id: Uuid::new_v4(),
+        credential_id: URL_SAFE_NO_PAD.encode(b"cred-1"),
+        client_data_json: URL_SAFE_NO_PAD.encode(&client_data_json),
+        authenticator_data: URL_SAFE_NO_PAD.encode(&auth_data),
+        signature: URL_SAFE_NO_PAD.encode(signature.to_der().as_bytes()),
+    }
+}
+
+#[test]
+fn test_registration_accepts_matching_response() {
+    let verifier = WebAuthnVerifier::new(&WebAuthnConfig::default());
+    let public_key = signing_key().verifying_key().to_public_key_der().unwrap();
+
+    let response = PasskeyRegistrationFinish {
+        challenge_id: Uuid::new_v4(),
+        credential_id: URL_SAFE_NO_PAD.encode(b"cred-1"),
+        client_data_json: URL_SAFE_NO_PAD.encode(client_data("webauthn.create", CHALLENGE, ORIGIN)),
+        authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data(0, Some(b"cred-1"))),
+        public_key: URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+        public_key_algorithm: COSE_ALG_ES256,
+        name: None,
+    };
+
+    let registration = verifier.verify_registration(CHALLENGE, &response).unwrap();
+    assert_eq!(registration.credential_id, b"cred-1");
+    assert!(verifier.verify_registration("other-challenge", &response).is_err());
+}
+
+#[test]
+fn test_assertion_verifies_signature_and_counter() {
+    let verifier = WebAuthnVerifier::new(&WebAuthnConfig::default());
+
+    let sign_count = verifier
+        .verify_assertion(CHALLENGE, &stored_credential(4), &assertion(CHALLENGE, ORIGIN, 5))
+        .unwrap();
+    assert_eq!(sign_count, 5);
+
+    // Counter replay
+    assert!(verifier
+        .verify_assertion(CHALLENGE, &stored_credential(5), &assertion(CHALLENGE, ORIGIN, 5))
+        .is_err());
+}
+
+#[test]
+fn test_assertion_rejects_wrong_origin_or_challenge() {
+    let verifier = WebAuthnVerifier::new(&WebAuthnConfig::default());
+    let credential = stored_credential(0);
+
+    assert!(verifier
+        .verify_assertion(CHALLENGE, &credential, &assertion(CHALLENGE, "https://evil.example", 1))
+        .is_err());
+    assert!(verifier
+        .verify_assertion(CHALLENGE, &credential, &assertion("stale", ORIGIN, 1))
+        .is_err());
+}